@@ -0,0 +1,58 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Criterion benchmarks of the parser and the output pipeline. `compass bench`
+//! runs the same workloads without Criterion, for quick checks and CI.
+
+use compass_cli::core::infrastructure::bench::{
+    SAMPLE_SECRET, append_chunks, sample_document, sample_output,
+};
+use compass_cli::core::parser::parse_readme;
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use std::hint::black_box;
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for steps in [100, 1_000, 5_000] {
+        let document = sample_document(steps);
+        group.throughput(Throughput::Bytes(document.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(steps),
+            &document,
+            |b, document| {
+                b.iter(|| parse_readme(black_box(document)));
+            },
+        );
+    }
+    group.finish();
+}
+
+fn output_append(c: &mut Criterion) {
+    let chunks = sample_output(20_000);
+    let secrets = [SAMPLE_SECRET.to_string()];
+    let mut group = c.benchmark_group("output_append");
+    group.throughput(Throughput::Bytes(
+        chunks.iter().map(String::len).sum::<usize>() as u64,
+    ));
+    group.bench_function("plain", |b| {
+        b.iter(|| append_chunks(black_box(&chunks), &[]))
+    });
+    group.bench_function("redacted", |b| {
+        b.iter(|| append_chunks(black_box(&chunks), &secrets));
+    });
+    group.finish();
+}
+
+criterion_group!(benches, parse, output_append);
+criterion_main!(benches);
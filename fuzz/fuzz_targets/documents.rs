@@ -0,0 +1,38 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Converts arbitrary AsciiDoc, reStructuredText, MDX and notebooks to
+//! Markdown, then parses the result. Run with
+//! `cargo +nightly fuzz run documents` from the repository root.
+
+#![no_main]
+
+use compass_cli::core::documents::to_markdown;
+use compass_cli::core::parser::parse_readme;
+use libfuzzer_sys::fuzz_target;
+
+const LOCATIONS: [&str; 4] = [
+    "runbook.adoc",
+    "runbook.rst",
+    "runbook.mdx",
+    "runbook.ipynb",
+];
+
+fuzz_target!(|input: (u8, &str)| {
+    let (format, source) = input;
+    let location = LOCATIONS[usize::from(format) % LOCATIONS.len()];
+    if let Ok(markdown) = to_markdown(location, source.to_string()) {
+        parse_readme(&markdown);
+    }
+});
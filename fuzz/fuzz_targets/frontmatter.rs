@@ -0,0 +1,37 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Feeds arbitrary YAML to the frontmatter of a runbook, followed by a step
+//! using placeholders so their specs (patterns, choices) are exercised. Run
+//! with `cargo +nightly fuzz run frontmatter` from the repository root.
+
+#![no_main]
+
+use compass_cli::core::parser::parse_readme;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|yaml: &str| {
+    let content = format!(
+        "---\n{yaml}\n---\n# Deploy\n```bash\ndeploy <ENV:dev|prod> {{{{PORT}}}} <SECRET:TOKEN>\n```\n"
+    );
+    let (steps, manifest) = parse_readme(&content);
+    if let Some(manifest) = manifest {
+        for spec in manifest.placeholders.values() {
+            let _ = spec.validate("8080");
+        }
+    }
+    for step in &steps {
+        let _ = step.check_choice("ENV", "dev");
+    }
+});
@@ -0,0 +1,27 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Feeds arbitrary text to the Markdown parser, which reads runbooks fetched
+//! from any URL. Run with `cargo +nightly fuzz run parse_readme` from the
+//! repository root.
+
+#![no_main]
+
+use compass_cli::core::parser::parse_readme_at;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (Option<u8>, &str)| {
+    let (step_level, content) = input;
+    parse_readme_at(content, step_level);
+});
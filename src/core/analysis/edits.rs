@@ -0,0 +1,307 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # File Edits
+//!
+//! Detects steps that write files, either through a heredoc (`cat > file <<EOF`,
+//! `tee -a file <<EOF`) or a `compass:edits` annotation, so the change can be
+//! previewed as a diff before it is applied and rolled back afterwards.
+
+use anyhow::{Context, Result, bail};
+use directories::BaseDirs;
+use regex::Regex;
+use similar::{ChangeTag, TextDiff};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::LazyLock;
+
+/// Lines of unchanged content shown around each change.
+const DIFF_CONTEXT: usize = 3;
+
+/// `cat`/`tee` commands fed by a heredoc: (arguments before, delimiter, arguments after).
+static HEREDOC: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*(?:sudo\s+)?(cat|tee)\b([^<]*)<<-?\s*(['"]?)(\w+)['"]?(.*)$"#).unwrap()
+});
+
+/// An output redirection (`> file`, `>> file`).
+static REDIRECT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(>>?)\s*("[^"]+"|'[^']+'|[^\s;&|]+)"#).unwrap());
+
+/// A file written by a heredoc.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileEdit {
+    /// The path as written in the script.
+    pub path: String,
+    /// Whether the content is appended instead of replacing the file.
+    pub append: bool,
+    /// The content written.
+    pub content: String,
+}
+
+/// A line of a diff preview.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine {
+    /// The file the following lines apply to.
+    File(String),
+    /// The start of a group of changes.
+    Hunk,
+    Added(String),
+    Removed(String),
+    Context(String),
+    /// A message in place of the diff.
+    Note(String),
+}
+
+/// The content of a file before a step edited it.
+#[derive(Debug, Clone)]
+pub struct FileBackup {
+    pub path: PathBuf,
+    /// `None` when the file did not exist.
+    pub original: Option<Vec<u8>>,
+}
+
+/// Finds the files written by heredocs in a shell script.
+///
+/// Targets containing variables are ignored, since their path is only known
+/// when the script runs.
+pub fn detect(script: &str) -> Vec<FileEdit> {
+    let mut edits = Vec::new();
+    let mut lines = script.lines();
+    while let Some(line) = lines.next() {
+        let Some(caps) = HEREDOC.captures(line) else {
+            continue;
+        };
+        let delimiter = &caps[4];
+        let body: Vec<&str> = lines
+            .by_ref()
+            .take_while(|l| l.trim() != delimiter)
+            .collect();
+        let arguments = format!("{} {}", &caps[2], &caps[5]);
+
+        let target = if &caps[1] == "tee" {
+            let append = arguments.split_whitespace().any(|a| a == "-a");
+            arguments
+                .split_whitespace()
+                .take_while(|a| !a.starts_with(['|', '>', ';', '&']))
+                .find(|a| !a.starts_with('-'))
+                .map(|path| (path.to_string(), append))
+        } else {
+            REDIRECT
+                .captures(&arguments)
+                .map(|r| (r[2].to_string(), &r[1] == ">>"))
+        };
+        let Some((path, append)) = target else {
+            continue;
+        };
+        let path = path.trim_matches(|c| c == '"' || c == '\'').to_string();
+        if path.contains('$') || path == "/dev/null" {
+            continue;
+        }
+
+        let mut content = body.join("\n");
+        content.push('\n');
+        edits.push(FileEdit {
+            path,
+            append,
+            content,
+        });
+    }
+    edits
+}
+
+/// Resolves a path written in a step against the working directory.
+pub fn resolve(path: &str, base_dir: &Path) -> PathBuf {
+    if let Some(rest) = path.strip_prefix("~/")
+        && let Some(dirs) = BaseDirs::new()
+    {
+        return dirs.home_dir().join(rest);
+    }
+    base_dir.join(path)
+}
+
+/// Resolves the path of a `compass:write` block against the working
+/// directory it must stay in: absolute paths, `~` and `..` leaving it are
+/// refused.
+pub fn confine(path: &str, base_dir: &Path) -> Result<PathBuf> {
+    if path.starts_with('~') {
+        bail!("the path must be relative to the working directory");
+    }
+    let mut depth = 0usize;
+    for component in Path::new(path).components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir if depth > 0 => depth -= 1,
+            Component::ParentDir => bail!("the path leaves the working directory"),
+            Component::RootDir | Component::Prefix(_) => {
+                bail!("the path must be relative to the working directory")
+            }
+        }
+    }
+    Ok(base_dir.join(path))
+}
+
+/// Builds the diff of the files before and after the edits, followed by the
+/// `annotated` files whose changes cannot be known in advance.
+pub fn preview(edits: &[FileEdit], annotated: &[String], base_dir: &Path) -> Vec<DiffLine> {
+    let mut lines = Vec::new();
+    // Content of each file after the previous edits of the step
+    let mut files: HashMap<PathBuf, String> = HashMap::new();
+
+    for edit in edits {
+        let path = resolve(&edit.path, base_dir);
+        let old = files
+            .get(&path)
+            .cloned()
+            .unwrap_or_else(|| fs::read_to_string(&path).unwrap_or_default());
+        let new = if edit.append {
+            format!("{old}{}", edit.content)
+        } else {
+            edit.content.clone()
+        };
+
+        let label = if path.exists() || files.contains_key(&path) {
+            edit.path.clone()
+        } else {
+            format!("{} (new file)", edit.path)
+        };
+        lines.push(DiffLine::File(label));
+        let changes = diff(&old, &new);
+        if changes.is_empty() {
+            lines.push(DiffLine::Note("No changes.".to_string()));
+        }
+        lines.extend(changes);
+        files.insert(path, new);
+    }
+
+    for path in annotated {
+        if edits.iter().any(|e| &e.path == path) {
+            continue;
+        }
+        lines.push(DiffLine::File(path.clone()));
+        lines.push(DiffLine::Note(
+            "Modified by the step (no preview available).".to_string(),
+        ));
+    }
+    lines
+}
+
+/// The groups of changed lines between two texts, with some context; empty
+/// when they are the same.
+pub fn diff(old: &str, new: &str) -> Vec<DiffLine> {
+    let mut lines = Vec::new();
+    let diff = TextDiff::from_lines(old, new);
+    for group in diff.grouped_ops(DIFF_CONTEXT) {
+        lines.push(DiffLine::Hunk);
+        for op in &group {
+            for change in diff.iter_changes(op) {
+                let text = change.value().trim_end_matches(['\n', '\r']).to_string();
+                lines.push(match change.tag() {
+                    ChangeTag::Insert => DiffLine::Added(text),
+                    ChangeTag::Delete => DiffLine::Removed(text),
+                    ChangeTag::Equal => DiffLine::Context(text),
+                });
+            }
+        }
+    }
+    lines
+}
+
+/// Saves the current content of the files, so they can be restored later.
+pub fn backup<'a>(paths: impl IntoIterator<Item = &'a str>, base_dir: &Path) -> Vec<FileBackup> {
+    let mut backups: Vec<FileBackup> = Vec::new();
+    for path in paths {
+        let path = resolve(path, base_dir);
+        if backups.iter().any(|b| b.path == path) {
+            continue;
+        }
+        let original = fs::read(&path).ok();
+        backups.push(FileBackup { path, original });
+    }
+    backups
+}
+
+/// Restores the files to their backed up content, deleting the files that did
+/// not exist. Returns the number of files restored.
+///
+/// # Errors
+///
+/// Returns an error if a file cannot be written or removed.
+pub fn restore(backups: &[FileBackup]) -> Result<usize> {
+    for backup in backups {
+        match &backup.original {
+            Some(content) => fs::write(&backup.path, content)
+                .with_context(|| format!("Failed to restore {}", backup.path.display()))?,
+            None if backup.path.exists() => fs::remove_file(&backup.path)
+                .with_context(|| format!("Failed to remove {}", backup.path.display()))?,
+            None => {}
+        }
+    }
+    Ok(backups.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_heredocs() {
+        let script = "cat > .env <<'EOF'\nPORT=80\nEOF\nsudo tee -a /etc/hosts <<EOF >/dev/null\n127.0.0.1 app\nEOF\ncat <<EOF >> \"$HOME/x\"\nskipped\nEOF\necho done";
+        let edits = detect(script);
+
+        assert_eq!(edits.len(), 2);
+        assert_eq!(edits[0].path, ".env");
+        assert!(!edits[0].append);
+        assert_eq!(edits[0].content, "PORT=80\n");
+        assert_eq!(edits[1].path, "/etc/hosts");
+        assert!(edits[1].append);
+    }
+
+    #[test]
+    fn test_confine_to_working_directory() {
+        let base = Path::new("/work");
+        assert_eq!(
+            confine("conf/app.env", base).unwrap(),
+            base.join("conf/app.env")
+        );
+        assert!(confine("conf/../app.env", base).is_ok());
+        assert!(confine("../app.env", base).is_err());
+        assert!(confine("conf/../../app.env", base).is_err());
+        assert!(confine("/etc/hosts", base).is_err());
+        assert!(confine("~/.bashrc", base).is_err());
+    }
+
+    #[test]
+    fn test_preview_and_restore() {
+        let dir = std::env::temp_dir().join(format!("compass_edits_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("app.conf"), "a\nb\n").unwrap();
+
+        let edits = detect("cat > app.conf <<EOF\na\nc\nEOF\ncat > new.txt <<EOF\nx\nEOF");
+        let lines = preview(&edits, &[], &dir);
+        assert!(lines.contains(&DiffLine::Removed("b".to_string())));
+        assert!(lines.contains(&DiffLine::Added("c".to_string())));
+        assert!(lines.contains(&DiffLine::File("new.txt (new file)".to_string())));
+
+        let backups = backup(edits.iter().map(|e| e.path.as_str()), &dir);
+        fs::write(dir.join("app.conf"), "changed").unwrap();
+        fs::write(dir.join("new.txt"), "x").unwrap();
+        assert_eq!(restore(&backups).unwrap(), 2);
+        assert_eq!(fs::read_to_string(dir.join("app.conf")).unwrap(), "a\nb\n");
+        assert!(!dir.join("new.txt").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,180 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::core::executor::languages::get_language_handler;
+use crate::core::executor::prerequisites;
+use crate::core::executor::security::safety::SafetyShield;
+use crate::core::models::Step;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// Languages used for data or sample output rather than commands.
+pub const NON_EXECUTABLE: &[&str] = &[
+    "json",
+    "yaml",
+    "yml",
+    "toml",
+    "xml",
+    "ini",
+    "csv",
+    "text",
+    "txt",
+    "plaintext",
+    "output",
+    "log",
+    "diff",
+    "markdown",
+    "md",
+];
+
+/// How serious a lint issue is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// A problem found in a runbook.
+#[derive(Debug, Clone, Serialize)]
+pub struct LintIssue {
+    pub severity: Severity,
+    /// Index of the step the issue belongs to, if any.
+    pub step: Option<usize>,
+    pub message: String,
+}
+
+impl LintIssue {
+    fn warning(step: usize, message: String) -> Self {
+        Self {
+            severity: Severity::Warning,
+            step: Some(step),
+            message,
+        }
+    }
+}
+
+/// Checks a parsed runbook for common authoring mistakes.
+pub fn lint(steps: &[Step]) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+    if steps.is_empty() {
+        issues.push(LintIssue {
+            severity: Severity::Error,
+            step: None,
+            message: "No sections (headers) found.".to_string(),
+        });
+    }
+
+    let mut titles = HashSet::new();
+    for (i, step) in steps.iter().enumerate() {
+        let title = step.title.trim();
+        if title.is_empty() {
+            issues.push(LintIssue::warning(i, "Heading has no text.".to_string()));
+        } else if !titles.insert(title) {
+            issues.push(LintIssue::warning(
+                i,
+                format!("Duplicate step title '{title}'."),
+            ));
+        }
+
+        for required in &step.requires {
+            if prerequisites::find(steps, required).is_none() {
+                issues.push(LintIssue::warning(
+                    i,
+                    format!("Requires unknown step '{required}'."),
+                ));
+            }
+        }
+
+        for capture in &step.captures {
+            if let Err(e) = Regex::new(&capture.pattern) {
+                // Syntax errors span several lines, the last one says what is wrong
+                let e = e.to_string();
+                let reason = e.lines().last().unwrap_or_default();
+                issues.push(LintIssue::warning(
+                    i,
+                    format!(
+                        "Invalid capture pattern '{}': {}.",
+                        capture.pattern,
+                        reason.trim_start_matches("error: ")
+                    ),
+                ));
+            }
+        }
+
+        for block in &step.code_blocks {
+            if block.content.trim().is_empty() {
+                issues.push(LintIssue::warning(i, "Empty code block.".to_string()));
+                continue;
+            }
+            let Some(language) = block.language.as_deref() else {
+                issues.push(LintIssue::warning(
+                    i,
+                    "Code block has no language and will run with the default shell.".to_string(),
+                ));
+                continue;
+            };
+            if NON_EXECUTABLE.contains(&language) {
+                issues.push(LintIssue::warning(
+                    i,
+                    format!("'{language}' block will be executed as a shell command."),
+                ));
+                continue;
+            }
+            let handler = get_language_handler(Some(language));
+            if let Some(pattern) =
+                SafetyShield::check(&block.content, handler.get_dangerous_patterns())
+            {
+                issues.push(LintIssue::warning(
+                    i,
+                    format!("Dangerous pattern '{pattern}'."),
+                ));
+            }
+        }
+    }
+
+    let all: Vec<usize> = (0..steps.len()).collect();
+    if let Err(e) = prerequisites::order(steps, &all) {
+        issues.push(LintIssue {
+            severity: Severity::Error,
+            step: None,
+            message: e.to_string(),
+        });
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_readme;
+
+    #[test]
+    fn test_lint_issues() {
+        let content = "# A\n```\nls\n```\n# A\n<!-- compass:requires \"B\" -->\n```json\n{}\n```\n";
+        let (steps, _) = parse_readme(content);
+        let messages: Vec<String> = lint(&steps).into_iter().map(|i| i.message).collect();
+
+        assert_eq!(
+            messages,
+            vec![
+                "Code block has no language and will run with the default shell.",
+                "Duplicate step title 'A'.",
+                "Requires unknown step 'B'.",
+                "'json' block will be executed as a shell command.",
+            ]
+        );
+    }
+}
@@ -1 +1,5 @@
+pub mod edits;
+pub mod lint;
 pub mod recovery;
+pub mod validate;
+pub mod verify;
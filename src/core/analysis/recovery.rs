@@ -12,7 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::core::executor::engine::session::STDERR_TAG;
+use crate::core::plugins::Registry;
 use regex::Regex;
+use std::sync::{Arc, LazyLock};
 
 #[derive(Debug, Clone)]
 pub struct RecoveryRecommendation {
@@ -20,58 +23,135 @@ pub struct RecoveryRecommendation {
     pub fix_command: Option<String>,
 }
 
+/// Recognizes a failure from its stderr and suggests how to recover.
+pub trait RecoveryRule: Send + Sync {
+    fn analyze(&self, stderr: &str) -> Option<RecoveryRecommendation>;
+}
+
+static RULES: LazyLock<Registry<dyn RecoveryRule>> = LazyLock::new(|| {
+    let builtins: Vec<Arc<dyn RecoveryRule>> = vec![
+        Arc::new(PortInUse),
+        Arc::new(PermissionDenied),
+        Arc::new(PythonModuleMissing),
+        Arc::new(CommandNotFound),
+        Arc::new(AptLocked),
+    ];
+    Registry::new(builtins)
+});
+
+/// Adds a rule, tried before the built-in ones.
+pub fn register_recovery_rule(rule: Arc<dyn RecoveryRule>) {
+    RULES.register(rule);
+}
+
 /// Analyzes stderr output to suggest recovery actions.
-pub fn analyze_error(stderr: &str) -> Option<RecoveryRecommendation> {
-    // 1. Port already in use
-    // Matches: "Address already in use", "EADDRINUSE", "bind: address already in use"
-    let re_port =
-        Regex::new(r"(?i)(address already in use|EADDRINUSE|bind: address already in use)")
-            .unwrap();
-    if re_port.is_match(stderr) {
-        return Some(RecoveryRecommendation {
+///
+/// When the output tells stderr lines apart (runs without a PTY), only those
+/// are analyzed.
+pub fn analyze_error(output: &str) -> Option<RecoveryRecommendation> {
+    let tagged: Vec<&str> = output
+        .lines()
+        .filter_map(|line| line.strip_prefix(STDERR_TAG))
+        .collect();
+    let tagged = tagged.join("\n");
+    let stderr = if tagged.is_empty() {
+        output
+    } else {
+        tagged.as_str()
+    };
+
+    RULES.entries().iter().find_map(|rule| rule.analyze(stderr))
+}
+
+/// Port already in use.
+struct PortInUse;
+
+impl RecoveryRule for PortInUse {
+    fn analyze(&self, stderr: &str) -> Option<RecoveryRecommendation> {
+        // Matches: "Address already in use", "EADDRINUSE", "bind: address already in use"
+        static RE: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(r"(?i)(address already in use|EADDRINUSE|bind: address already in use)")
+                .unwrap()
+        });
+        RE.is_match(stderr).then(|| RecoveryRecommendation {
             message: "Port seems to be occupied. You might want to kill the process utilizing it."
                 .to_string(),
             fix_command: None, // Too risky to auto-kill without knowing the port accurately
-        });
+        })
     }
+}
 
-    // 2. Permission denied
-    if stderr.contains("Permission denied") || stderr.contains("EACCES") {
-        return Some(RecoveryRecommendation {
-            message: "Permission denied. You might need 'sudo' or check file permissions."
-                .to_string(),
-            fix_command: None,
-        });
+struct PermissionDenied;
+
+impl RecoveryRule for PermissionDenied {
+    fn analyze(&self, stderr: &str) -> Option<RecoveryRecommendation> {
+        (stderr.contains("Permission denied") || stderr.contains("EACCES")).then(|| {
+            RecoveryRecommendation {
+                message: "Permission denied. You might need 'sudo' or check file permissions."
+                    .to_string(),
+                fix_command: None,
+            }
+        })
     }
+}
+
+/// Module not found (Python).
+struct PythonModuleMissing;
 
-    // 3. Module not found (Python)
-    // Matches: "ModuleNotFoundError: No module named 'xyz'"
-    let re_py_mod = Regex::new(r"ModuleNotFoundError: No module named '([^']+)'").unwrap();
-    if let Some(caps) = re_py_mod.captures(stderr) {
+impl RecoveryRule for PythonModuleMissing {
+    fn analyze(&self, stderr: &str) -> Option<RecoveryRecommendation> {
+        // Matches: "ModuleNotFoundError: No module named 'xyz'"
+        static RE: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(r"ModuleNotFoundError: No module named '([^']+)'").unwrap()
+        });
+        let caps = RE.captures(stderr)?;
         let module = caps.get(1).map_or("", |m| m.as_str());
-        return Some(RecoveryRecommendation {
+        Some(RecoveryRecommendation {
             message: format!("Python module '{}' is missing.", module),
             fix_command: Some(format!("pip install {}", module)),
-        });
+        })
     }
+}
 
-    // 4. Command not found
-    // Matches: "command not found", "is not recognized as an internal or external command"
-    if stderr.contains("command not found") || stderr.contains("not recognized as an internal") {
-        return Some(RecoveryRecommendation {
-            message: "Command not found. Ensure it is installed and in your PATH.".to_string(),
-            fix_command: None,
-        });
+struct CommandNotFound;
+
+impl RecoveryRule for CommandNotFound {
+    fn analyze(&self, stderr: &str) -> Option<RecoveryRecommendation> {
+        // Matches: "command not found", "is not recognized as an internal or external command"
+        (stderr.contains("command not found") || stderr.contains("not recognized as an internal"))
+            .then(|| RecoveryRecommendation {
+                message: "Command not found. Ensure it is installed and in your PATH.".to_string(),
+                fix_command: None,
+            })
     }
+}
 
-    // 5. Apt lock (Linux)
-    if stderr.contains("Could not get lock /var/lib/dpkg/lock") {
-        return Some(RecoveryRecommendation {
-            message: "APT database is locked. Another process might be installing software."
-                .to_string(),
-            fix_command: Some("sudo fuser -v /var/lib/dpkg/lock".to_string()),
-        });
+/// Apt lock (Linux).
+struct AptLocked;
+
+impl RecoveryRule for AptLocked {
+    fn analyze(&self, stderr: &str) -> Option<RecoveryRecommendation> {
+        stderr
+            .contains("Could not get lock /var/lib/dpkg/lock")
+            .then(|| RecoveryRecommendation {
+                message: "APT database is locked. Another process might be installing software."
+                    .to_string(),
+                fix_command: Some("sudo fuser -v /var/lib/dpkg/lock".to_string()),
+            })
     }
+}
 
-    None
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_tagged_stderr_only() {
+        // "Permission denied" on stdout is not an error when stderr is tagged
+        let output = "Permission denied is a fine phrase\n[stderr] bash: foo: command not found\n";
+        let rec = analyze_error(output).unwrap();
+        assert!(rec.message.starts_with("Command not found"));
+
+        assert!(analyze_error("Permission denied").is_some());
+    }
 }
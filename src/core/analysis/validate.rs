@@ -0,0 +1,467 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Static checks of a runbook for `compass validate`: the lint of
+//! `compass verify`, plus the frontmatter, the `compass:` annotations, the
+//! declared placeholders and the hooks. Nothing is executed.
+
+use crate::core::analysis::lint::{LintIssue, NON_EXECUTABLE, Severity, lint};
+use crate::core::executor::check_dependencies;
+use crate::core::executor::conditions::expression;
+use crate::core::executor::engine::CommandBuilder;
+use crate::core::executor::languages::is_supported;
+use crate::core::manifest::RunbookManifest;
+use crate::core::models::{CodeBlock, Condition, Step};
+use crate::core::parser::parse_duration;
+use crate::core::secrets::SecretSource;
+use pulldown_cmark::{Event, Parser};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+/// Keys of the frontmatter (see [`RunbookManifest`]).
+const MANIFEST_KEYS: &[&str] = &[
+    "title",
+    "description",
+    "author",
+    "requires",
+    "min_version",
+    "image",
+    "pre_run",
+    "post_run",
+    "on_failure",
+    "on_success",
+    "context",
+    "step_level",
+    "env",
+    "secrets",
+    "placeholders",
+    "matrix",
+];
+
+/// Keys of a placeholder declaration (see `PlaceholderSpec`).
+const PLACEHOLDER_KEYS: &[&str] = &["default", "description", "pattern", "choices", "source"];
+
+/// Names of the `compass:` annotations the parser understands.
+const ANNOTATIONS: &[&str] = &[
+    "if",
+    "elif",
+    "else",
+    "endif",
+    "context",
+    "tag",
+    "tags",
+    "eta",
+    "timeout",
+    "edits",
+    "requires_env",
+    "env",
+    "requires",
+    "write",
+    "expect",
+    "capture",
+    "group",
+    "endgroup",
+    "include",
+    "origin",
+    "endorigin",
+];
+
+/// Values of `std::env::consts::OS` an `os` condition may test.
+const OPERATING_SYSTEMS: &[&str] = &[
+    "linux",
+    "macos",
+    "windows",
+    "freebsd",
+    "openbsd",
+    "netbsd",
+    "dragonfly",
+    "solaris",
+    "illumos",
+    "android",
+    "ios",
+];
+
+/// Architectures an `arch` condition may test: the values of
+/// `std::env::consts::ARCH` and their usual aliases.
+const ARCHITECTURES: &[&str] = &[
+    "x86_64",
+    "amd64",
+    "x64",
+    "x86",
+    "i386",
+    "i686",
+    "aarch64",
+    "arm64",
+    "arm",
+    "riscv64",
+    "powerpc64",
+    "s390x",
+    "loongarch64",
+];
+
+/// Languages run by the default shell without being unknown.
+const SHELL_ALIASES: &[&str] = &["shell", "console", "terminal"];
+
+static ANNOTATION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<!--\s*compass:([\w-]*)\s*(.*?)\s*-->").unwrap());
+
+/// The outcome of `compass validate`.
+#[derive(Debug, Serialize)]
+pub struct ValidationReport {
+    pub issues: Vec<LintIssue>,
+    pub passed: bool,
+}
+
+/// An issue of the whole runbook rather than of a step.
+fn issue(severity: Severity, message: String) -> LintIssue {
+    LintIssue {
+        severity,
+        step: None,
+        message,
+    }
+}
+
+/// Checks the runbook `content`, parsed into `steps`. With `strict`, warnings
+/// fail the validation too.
+pub fn validate(content: &str, steps: &[Step], strict: bool) -> ValidationReport {
+    let mut issues = lint(steps);
+    let manifest = check_frontmatter(content, &mut issues);
+    check_annotations(content, &mut issues);
+    check_languages(steps, &mut issues);
+    if let Some(manifest) = &manifest {
+        check_placeholders(steps, manifest, &mut issues);
+        check_hooks(manifest, &mut issues);
+    }
+
+    let passed = !issues
+        .iter()
+        .any(|i| i.severity == Severity::Error || strict);
+    ValidationReport { issues, passed }
+}
+
+/// Checks the frontmatter against the schema of [`RunbookManifest`] and
+/// returns it if it is valid.
+fn check_frontmatter(content: &str, issues: &mut Vec<LintIssue>) -> Option<RunbookManifest> {
+    let rest = content.strip_prefix("---")?;
+    let Some(end) = rest.find("\n---") else {
+        issues.push(issue(
+            Severity::Error,
+            "Frontmatter is not closed: add a `---` line after it.".to_string(),
+        ));
+        return None;
+    };
+    let yaml: serde_yaml::Value = match serde_yaml::from_str(&rest[..end]) {
+        Ok(yaml) => yaml,
+        Err(e) => {
+            issues.push(issue(
+                Severity::Error,
+                format!("Frontmatter is not valid YAML: {e}"),
+            ));
+            return None;
+        }
+    };
+    if yaml.is_null() {
+        return None;
+    }
+
+    if let Some(map) = yaml.as_mapping() {
+        for key in map.keys() {
+            let key = key.as_str().unwrap_or_default();
+            if !MANIFEST_KEYS.contains(&key) {
+                issues.push(issue(
+                    Severity::Warning,
+                    format!("Unknown frontmatter key '{key}'."),
+                ));
+            }
+        }
+        let specs = map.get("placeholders").and_then(|p| p.as_mapping());
+        for (name, spec) in specs.into_iter().flatten() {
+            for key in spec.as_mapping().into_iter().flat_map(|s| s.keys()) {
+                let key = key.as_str().unwrap_or_default();
+                if !PLACEHOLDER_KEYS.contains(&key) {
+                    issues.push(issue(
+                        Severity::Warning,
+                        format!(
+                            "Unknown key '{key}' in placeholder '{}'.",
+                            name.as_str().unwrap_or_default()
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    match serde_yaml::from_value(yaml) {
+        Ok(manifest) => Some(manifest),
+        Err(e) => {
+            issues.push(issue(Severity::Error, format!("Invalid frontmatter: {e}")));
+            None
+        }
+    }
+}
+
+/// Checks the `compass:` annotations of the runbook, outside code blocks.
+fn check_annotations(content: &str, issues: &mut Vec<LintIssue>) {
+    for (event, range) in Parser::new(content).into_offset_iter() {
+        let (Event::Html(html) | Event::InlineHtml(html)) = event else {
+            continue;
+        };
+        for caps in ANNOTATION.captures_iter(&html) {
+            let offset = range.start + caps.get(0).map_or(0, |m| m.start());
+            let line = content[..offset.min(content.len())].matches('\n').count() + 1;
+            if let Some(problem) = check_annotation(&caps[1], &caps[2]) {
+                issues.push(issue(problem.0, format!("Line {line}: {}", problem.1)));
+            }
+        }
+    }
+}
+
+/// Describes what is wrong with the annotation `compass:<name> <args>`.
+fn check_annotation(name: &str, args: &str) -> Option<(Severity, String)> {
+    match name {
+        "if" | "elif" => {
+            let condition = match expression::parse(args) {
+                Ok(condition) => condition,
+                Err(e) => {
+                    return Some((Severity::Error, format!("{e}; the step would always run.")));
+                }
+            };
+            condition
+                .predicates()
+                .into_iter()
+                .find_map(|predicate| match predicate {
+                    Condition::Os(os)
+                        if !OPERATING_SYSTEMS.contains(&os.to_lowercase().as_str()) =>
+                    {
+                        Some(format!("Unknown operating system '{os}'; {predicate}"))
+                    }
+                    Condition::Arch(arch)
+                        if !ARCHITECTURES.contains(&arch.to_lowercase().as_str()) =>
+                    {
+                        Some(format!("Unknown architecture '{arch}'; {predicate}"))
+                    }
+                    _ => None,
+                })
+                .map(|unknown| (Severity::Warning, format!("{unknown} is never true.")))
+        }
+        "context" if !matches!(args, "shared" | "isolated") => Some((
+            Severity::Warning,
+            format!("Unknown context mode '{args}' (expected shared or isolated)."),
+        )),
+        "group" if args != "parallel" => Some((
+            Severity::Warning,
+            format!("Unknown group kind '{args}' (expected parallel); the steps run in order."),
+        )),
+        "eta" | "timeout" if parse_duration(args).is_none() => Some((
+            Severity::Warning,
+            format!("Invalid duration '{args}' for compass:{name} (e.g. 90, 45s, 5m, 1h30m)."),
+        )),
+        _ if !ANNOTATIONS.contains(&name) => Some((
+            Severity::Warning,
+            format!("Unknown annotation 'compass:{name}'; it is ignored."),
+        )),
+        _ => None,
+    }
+}
+
+/// Flags code blocks tagged with a language Compass does not know, which
+/// run with the default shell instead.
+fn check_languages(steps: &[Step], issues: &mut Vec<LintIssue>) {
+    for (i, step) in steps.iter().enumerate() {
+        let blocks = step.code_blocks.iter().filter(|b| b.write_to.is_none());
+        for language in blocks.filter_map(|b| b.language.as_deref()) {
+            if !is_supported(language)
+                && !SHELL_ALIASES.contains(&language)
+                && !NON_EXECUTABLE.contains(&language)
+            {
+                issues.push(LintIssue {
+                    severity: Severity::Warning,
+                    step: Some(i),
+                    message: format!(
+                        "Unknown language '{language}'; the block will run with the default shell."
+                    ),
+                });
+            }
+        }
+    }
+}
+
+/// Checks the placeholders declared in the frontmatter: each one should be
+/// used by a step, and its default should be a value it accepts.
+fn check_placeholders(steps: &[Step], manifest: &RunbookManifest, issues: &mut Vec<LintIssue>) {
+    let used: HashSet<String> = steps
+        .iter()
+        .flat_map(CommandBuilder::get_required_placeholders)
+        .collect();
+    let declared = manifest.placeholders.keys().chain(&manifest.secrets);
+    let mut reported = HashSet::new();
+    for name in declared {
+        if !used.contains(name) && reported.insert(name) {
+            issues.push(issue(
+                Severity::Warning,
+                format!("Placeholder '{name}' is declared in the frontmatter but no step uses it."),
+            ));
+        }
+    }
+
+    for (name, spec) in &manifest.placeholders {
+        if let Some(Err(e)) = spec.source.as_deref().map(SecretSource::parse) {
+            issues.push(issue(
+                Severity::Error,
+                format!("Placeholder '{name}' has an invalid source: {e}."),
+            ));
+        }
+        if let Some(pattern) = &spec.pattern
+            && let Err(e) = Regex::new(pattern)
+        {
+            issues.push(issue(
+                Severity::Error,
+                format!("Placeholder '{name}' has an invalid pattern: {e}"),
+            ));
+            continue;
+        }
+        let Some(default) = &spec.default else {
+            continue;
+        };
+        if let Err(e) = spec.validate(default) {
+            issues.push(issue(
+                Severity::Error,
+                format!("Default of placeholder '{name}' is rejected: {e}."),
+            ));
+        }
+        if !spec.choices.is_empty() && !spec.choices.contains(default) {
+            issues.push(issue(
+                Severity::Error,
+                format!("Default of placeholder '{name}' is not one of its choices."),
+            ));
+        }
+    }
+}
+
+/// Checks that the commands called by the hooks are installed.
+fn check_hooks(manifest: &RunbookManifest, issues: &mut Vec<LintIssue>) {
+    let hooks = [
+        ("pre_run", &manifest.pre_run),
+        ("post_run", &manifest.post_run),
+        ("on_failure", &manifest.on_failure),
+        ("on_success", &manifest.on_success),
+    ];
+    for (name, command) in hooks {
+        let Some(command) = command else {
+            continue;
+        };
+        // Checked like the code of a step
+        let step = Step {
+            code_blocks: vec![CodeBlock {
+                language: Some("bash".to_string()),
+                content: command.clone(),
+                ..CodeBlock::default()
+            }],
+            ..Step::default()
+        };
+        for missing in check_dependencies(&[step]).missing {
+            issues.push(issue(
+                Severity::Error,
+                format!("The {name} hook calls '{missing}', which is not installed."),
+            ));
+        }
+    }
+}
+
+impl ValidationReport {
+    /// Prints the report, with workflow annotations when running in GitHub Actions.
+    pub fn print(&self, file: &str) {
+        let annotate = std::env::var("GITHUB_ACTIONS").is_ok_and(|v| v == "true");
+
+        println!("\n🔎 Validation of {file}");
+        if self.issues.is_empty() {
+            println!("   ✅ No issues");
+        }
+        for issue in &self.issues {
+            let (icon, level) = match issue.severity {
+                Severity::Warning => ("⚠️ ", "warning"),
+                Severity::Error => ("❌", "error"),
+            };
+            let location = issue
+                .step
+                .map(|s| format!("step {}: ", s + 1))
+                .unwrap_or_default();
+            println!("   {icon} {location}{}", issue.message);
+            if annotate {
+                println!("::{level} file={file}::{location}{}", issue.message);
+            }
+        }
+
+        if self.passed {
+            println!("\n✅ PASS");
+        } else {
+            println!("\n❌ FAIL");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_readme;
+
+    fn messages(content: &str) -> Vec<String> {
+        let (steps, _) = parse_readme(content);
+        validate(content, &steps, false)
+            .issues
+            .into_iter()
+            .map(|i| i.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_frontmatter() {
+        let content = "---\nprerun: ls\nplaceholders:\n  PORT:\n    default: \"80a\"\n    pattern: \"[0-9]+\"\n    help: Port\n  UNUSED:\n    choices: [a, b]\n    default: c\n---\n# Run\n```bash\nserve <PORT>\n```\n";
+        assert_eq!(
+            messages(content),
+            vec![
+                "Unknown frontmatter key 'prerun'.",
+                "Unknown key 'help' in placeholder 'PORT'.",
+                "Placeholder 'UNUSED' is declared in the frontmatter but no step uses it.",
+                "Default of placeholder 'PORT' is rejected: The value must match [0-9]+.",
+                "Default of placeholder 'UNUSED' is not one of its choices.",
+            ]
+        );
+        assert_eq!(
+            messages("---\ncontext: sometimes\n---\n# Run\n```bash\nls\n```\n").len(),
+            1
+        );
+        assert_eq!(
+            messages("---\ncontext: shared\n# Run\n"),
+            vec!["Frontmatter is not closed: add a `---` line after it."]
+        );
+    }
+
+    #[test]
+    fn test_validate_annotations() {
+        let content = "# Run\n<!-- compass:if shell=\"zsh\" -->\n```bash\nls\n```\n<!-- compass:elif arch=\"arm64\" || arch=\"sparc\" -->\n<!-- compass:timout 5m -->\n```pyhton\nprint(1)\n```\n\n```markdown\n<!-- compass:nothing -->\n```\n";
+        assert_eq!(
+            messages(content),
+            vec![
+                "'markdown' block will be executed as a shell command.",
+                "Line 2: Unknown condition key 'shell' (expected os, arch, env_var_exists, file_exists, command_exists); the step would always run.",
+                "Line 6: Unknown architecture 'sparc'; arch=sparc is never true.",
+                "Line 7: Unknown annotation 'compass:timout'; it is ignored.",
+                "Unknown language 'pyhton'; the block will run with the default shell.",
+            ]
+        );
+    }
+}
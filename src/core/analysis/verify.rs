@@ -0,0 +1,241 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::core::analysis::lint::{LintIssue, Severity, lint};
+use crate::core::executor::checker::{CheckResult, check_dependencies_in_image};
+use crate::core::executor::conditions::evaluator::{ConditionEvaluator, StandardEvaluator};
+use crate::core::executor::engine::CommandBuilder;
+use crate::core::executor::{Executor, check_dependencies};
+use crate::core::models::{Step, StepStatus};
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::mpsc;
+
+/// Tag marking the steps `compass verify --run` is allowed to execute.
+pub const CI_SAFE_TAG: &str = "ci-safe";
+
+/// Settings of a verification run.
+pub struct VerifyOptions {
+    /// Execute the steps tagged `ci-safe`.
+    pub run: bool,
+    /// Check dependencies and run steps inside a Docker image.
+    pub sandbox: bool,
+    pub image: String,
+    /// Treat lint warnings as failures.
+    pub strict: bool,
+}
+
+/// The result of executing a `ci-safe` step.
+#[derive(Debug, Serialize)]
+pub struct StepVerification {
+    pub step: usize,
+    pub title: String,
+    pub status: StepStatus,
+    pub output: String,
+}
+
+/// The outcome of `compass verify`.
+#[derive(Debug, Serialize)]
+pub struct VerifyReport {
+    pub issues: Vec<LintIssue>,
+    pub dependencies: CheckResult,
+    pub executions: Vec<StepVerification>,
+    pub passed: bool,
+}
+
+/// Lints the runbook, checks its dependencies and optionally runs its
+/// `ci-safe` steps.
+///
+/// Placeholders of executed steps are read from environment variables of the
+/// same name.
+///
+/// # Errors
+///
+/// Returns an error if the dependency check cannot run in the sandbox image.
+pub fn verify(steps: &[Step], base_dir: &Path, options: &VerifyOptions) -> Result<VerifyReport> {
+    let issues = lint(steps);
+    let dependencies = if options.sandbox {
+        check_dependencies_in_image(steps, &options.image)?
+    } else {
+        check_dependencies(steps)
+    };
+
+    let mut executions = Vec::new();
+    if options.run {
+        let mut executor = Executor::new();
+        executor.context.current_dir = base_dir.to_path_buf();
+        executor.context.sandbox_enabled = options.sandbox;
+        executor.context.docker_image = options.image.clone();
+
+        for (i, step) in steps.iter().enumerate() {
+            if step.has_tag(CI_SAFE_TAG) && step.is_executable() {
+                executions.push(run_step(&mut executor, i, step));
+            }
+        }
+    }
+
+    let passed = !issues
+        .iter()
+        .any(|i| i.severity == Severity::Error || options.strict)
+        && dependencies.missing.is_empty()
+        && executions
+            .iter()
+            .all(|e| matches!(e.status, StepStatus::Success | StepStatus::Skipped));
+
+    Ok(VerifyReport {
+        issues,
+        dependencies,
+        executions,
+        passed,
+    })
+}
+
+/// Runs a single step unattended.
+fn run_step(executor: &mut Executor, index: usize, step: &Step) -> StepVerification {
+    let mut result = StepVerification {
+        step: index,
+        title: step.title.clone(),
+        status: StepStatus::Skipped,
+        output: String::new(),
+    };
+
+    if let Some(condition) = &step.condition
+        && !StandardEvaluator::new().evaluate(condition)
+    {
+        result.output = format!("Condition not met: {condition}");
+        return result;
+    }
+
+    let mut variables = HashMap::new();
+    for name in CommandBuilder::get_required_placeholders(step) {
+        let Ok(value) = std::env::var(&name) else {
+            result.status = StepStatus::Failed;
+            result.output = format!(
+                "Missing value for placeholder '{name}' (set it as an environment variable)."
+            );
+            return result;
+        };
+        variables.insert(name, value);
+    }
+
+    let content = CommandBuilder::build_command(step, &variables);
+    let language = step
+        .code_blocks
+        .iter()
+        .find(|b| b.write_to.is_none())
+        .and_then(|b| b.language.as_deref());
+    let (tx, rx) = mpsc::channel();
+    executor.context.timeout = step.timeout();
+    executor.scope = step.scope.clone();
+    result.status = StepStatus::Success;
+    for edit in CommandBuilder::build_writes(step, &variables) {
+        result.status = executor.write_file(&edit, &tx);
+        if result.status != StepStatus::Success {
+            break;
+        }
+    }
+    if result.status == StepStatus::Success && !content.trim().is_empty() {
+        result.status = executor.execute_streamed(&content, language, true, &tx);
+    }
+    drop(tx);
+    result.output = rx.into_iter().collect();
+    if let Some(expect) = &step.expect {
+        let failures = expect.check(
+            result.status,
+            executor.context.last_exit_code,
+            &result.output,
+        );
+        result.status = if failures.is_empty() {
+            StepStatus::Success
+        } else {
+            StepStatus::Failed
+        };
+        for failure in failures {
+            result.output.push_str(&format!("\n✗ {failure}"));
+        }
+    }
+    result
+}
+
+impl VerifyReport {
+    /// Prints the report, with workflow annotations when running in GitHub Actions.
+    pub fn print(&self, file: &str) {
+        let annotate = std::env::var("GITHUB_ACTIONS").is_ok_and(|v| v == "true");
+
+        println!("\n🔎 Lint");
+        if self.issues.is_empty() {
+            println!("   ✅ No issues");
+        }
+        for issue in &self.issues {
+            let (icon, level) = match issue.severity {
+                Severity::Warning => ("⚠️ ", "warning"),
+                Severity::Error => ("❌", "error"),
+            };
+            let location = issue
+                .step
+                .map(|s| format!("step {}: ", s + 1))
+                .unwrap_or_default();
+            println!("   {icon} {location}{}", issue.message);
+            if annotate {
+                println!("::{level} file={file}::{location}{}", issue.message);
+            }
+        }
+
+        println!("\n📦 Dependencies");
+        println!(
+            "   {} present, {} missing",
+            self.dependencies.present.len(),
+            self.dependencies.missing.len()
+        );
+        for cmd in &self.dependencies.missing {
+            println!("   ❌ {cmd}");
+            if annotate {
+                println!("::error file={file}::Missing dependency '{cmd}'");
+            }
+        }
+
+        if !self.executions.is_empty() {
+            println!("\n▶️  Executed steps");
+        }
+        for run in &self.executions {
+            let icon = match run.status {
+                StepStatus::Success => "✅",
+                StepStatus::Skipped => "⏭️ ",
+                StepStatus::TimedOut => "⏱️ ",
+                _ => "❌",
+            };
+            println!("   {icon} {}. {}", run.step + 1, run.title);
+            if run.status.is_failure() {
+                for line in run.output.trim_end().lines() {
+                    println!("      {line}");
+                }
+                if annotate {
+                    println!(
+                        "::error file={file}::Step {} '{}' failed",
+                        run.step + 1,
+                        run.title
+                    );
+                }
+            }
+        }
+
+        if self.passed {
+            println!("\n✅ PASS");
+        } else {
+            println!("\n❌ FAIL");
+        }
+    }
+}
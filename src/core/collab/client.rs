@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use super::events::CompassEvent;
+use super::protocol::Handshake;
 use futures_util::StreamExt;
 use std::sync::Arc;
 use tokio_tungstenite::tungstenite::client::IntoClientRequest;
@@ -54,9 +55,10 @@ pub async fn start_guest_client(
     // 2. Prepare Request with Auth Header
     let mut request = url.into_client_request()?;
     request.headers_mut().insert("x-compass-pin", pin.parse()?);
+    Handshake::local().write_headers(request.headers_mut());
 
     // 3. Connect
-    let (ws_stream, _) = match tokio_tungstenite::connect_async_tls_with_config(
+    let (ws_stream, response) = match tokio_tungstenite::connect_async_tls_with_config(
         request,
         None,
         false,
@@ -65,6 +67,19 @@ pub async fn start_guest_client(
     .await
     {
         Ok(v) => v,
+        Err(tokio_tungstenite::tungstenite::Error::Http(response)) => {
+            // The host answered but refused the handshake (PIN or protocol)
+            let reason = response
+                .body()
+                .as_deref()
+                .map(String::from_utf8_lossy)
+                .unwrap_or_default();
+            anyhow::bail!(
+                "Host rejected the connection ({}): {}",
+                response.status(),
+                reason
+            );
+        }
         Err(e) => {
             eprintln!(
                 "🔥 Security Alert: Connection rejected. The server's certificate did NOT match the pinned fingerprint."
@@ -74,7 +89,18 @@ pub async fn start_guest_client(
         }
     };
 
+    let host = Handshake::from_headers(response.headers());
+    if let Err(message) = host.check_compatible("host") {
+        anyhow::bail!(message);
+    }
+
     println!("✅ Securely connected to Host.");
+    tracing::info!(
+        host = ?parsed_url.host_str(),
+        protocol = host.version,
+        capabilities = ?host.capabilities,
+        "Connected to host"
+    );
 
     let (_, mut read) = ws_stream.split();
 
@@ -92,6 +118,7 @@ pub async fn start_guest_client(
                 break;
             }
             Err(e) => {
+                tracing::warn!("Connection to host lost: {e}");
                 let _ = app_tx.send(CompassEvent::ConnectionLost(format!(
                     "Connection error: {}",
                     e
@@ -14,6 +14,7 @@
 
 use crate::core::models::Step;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum CompassEvent {
@@ -28,6 +29,26 @@ pub enum CompassEvent {
         steps: Vec<Step>,
         current_step: usize,
     },
+    /// Placeholder values filled by the host. Secret values are withheld
+    /// (`None`) and never leave the host.
+    Placeholders {
+        values: HashMap<String, Option<String>>,
+    },
     /// Connection to host was lost/closed (Local event).
     ConnectionLost(String),
 }
+
+impl CompassEvent {
+    /// The capability a guest must advertise to receive this event, or `None`
+    /// for events of the base protocol.
+    pub const fn capability(&self) -> Option<&'static str> {
+        match self {
+            Self::StepChanged(_)
+            | Self::StatusChanged { .. }
+            | Self::OutputReceived { .. }
+            | Self::Snapshot { .. }
+            | Self::ConnectionLost(_) => None,
+            Self::Placeholders { .. } => Some("placeholders"),
+        }
+    }
+}
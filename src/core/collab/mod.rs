@@ -14,6 +14,7 @@
 
 pub mod client;
 pub mod events;
+pub mod protocol;
 pub mod security;
 pub mod server;
 pub mod session;
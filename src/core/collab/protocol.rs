@@ -0,0 +1,138 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Collaboration protocol versioning and capability negotiation.
+//!
+//! Both sides send their protocol version and the optional capabilities they
+//! understand in the WebSocket handshake headers. A peer whose version is too
+//! old is rejected with a clear message, and the host only forwards events
+//! whose capability the guest advertised.
+
+use tokio_tungstenite::tungstenite::http::{HeaderMap, HeaderValue};
+
+/// Version of the event protocol. Bump it on any incompatible change to
+/// `CompassEvent` (renamed or removed variants or fields).
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Oldest peer version this build can still talk to.
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// Optional event kinds this build understands, on top of the base protocol.
+pub const CAPABILITIES: &[&str] = &["placeholders"];
+
+/// Header carrying the protocol version.
+pub const VERSION_HEADER: &str = "x-compass-protocol";
+
+/// Header carrying the comma-separated capabilities.
+pub const CAPABILITIES_HEADER: &str = "x-compass-capabilities";
+
+/// What a peer announced in its handshake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Handshake {
+    pub version: u32,
+    pub capabilities: Vec<String>,
+}
+
+impl Handshake {
+    /// The handshake of this build.
+    pub fn local() -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            capabilities: CAPABILITIES.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    /// Reads a peer's handshake from its headers.
+    ///
+    /// Peers built before versioning send no header; they speak version 1
+    /// without capabilities.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let header = |name| headers.get(name).and_then(|v| v.to_str().ok());
+        Self {
+            version: header(VERSION_HEADER)
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(1),
+            capabilities: header(CAPABILITIES_HEADER)
+                .map(|v| {
+                    v.split(',')
+                        .map(str::trim)
+                        .filter(|c| !c.is_empty())
+                        .map(ToString::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Writes this handshake into outgoing headers.
+    pub fn write_headers(&self, headers: &mut HeaderMap) {
+        headers.insert(VERSION_HEADER, HeaderValue::from(self.version));
+        if let Ok(value) = HeaderValue::from_str(&self.capabilities.join(",")) {
+            headers.insert(CAPABILITIES_HEADER, value);
+        }
+    }
+
+    /// Checks that a peer can be talked to, explaining who has to upgrade
+    /// otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns a user-facing message when the peer's version is too old.
+    pub fn check_compatible(&self, peer: &str) -> Result<(), String> {
+        if self.version < MIN_PROTOCOL_VERSION {
+            return Err(format!(
+                "The {peer} speaks collaboration protocol v{}, but this Compass needs v{MIN_PROTOCOL_VERSION} or newer. Please upgrade Compass on the {peer}.",
+                self.version
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether the peer advertised the given capability.
+    pub fn supports(&self, capability: &str) -> bool {
+        self.capabilities.iter().any(|c| c == capability)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handshake_roundtrip_and_legacy_peer() {
+        let local = Handshake {
+            version: 3,
+            capabilities: vec!["a".to_string(), "b".to_string()],
+        };
+        let mut headers = HeaderMap::new();
+        local.write_headers(&mut headers);
+        assert_eq!(Handshake::from_headers(&headers), local);
+        assert!(local.supports("b") && !local.supports("c"));
+
+        let legacy = Handshake::from_headers(&HeaderMap::new());
+        assert_eq!(legacy.version, 1);
+        assert!(legacy.capabilities.is_empty());
+        assert!(legacy.check_compatible("guest").is_ok());
+
+        let old = Handshake {
+            version: 0,
+            capabilities: Vec::new(),
+        };
+        assert!(
+            old.check_compatible("guest")
+                .unwrap_err()
+                .contains("upgrade")
+        );
+    }
+}
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use super::events::CompassEvent;
+use super::protocol::Handshake;
 use futures_util::{SinkExt, StreamExt};
 use std::net::SocketAddr;
 use std::sync::Arc;
@@ -40,11 +41,11 @@ pub async fn start_host_server(
 
     let port = 3030;
 
-    // 3. Setup Broadcast Channel
-    let (broadcast_tx, _) = broadcast::channel::<String>(100);
+    // 3. Setup Broadcast Channel (events are tagged with their capability)
+    let (broadcast_tx, _) = broadcast::channel::<(Option<&'static str>, String)>(100);
 
-    // State Cache for new joiners
-    let last_snapshot = Arc::new(std::sync::RwLock::new(None::<String>));
+    // State Cache for new joiners (last snapshot and placeholder values)
+    let last_snapshot = Arc::new(std::sync::RwLock::new(StateCache::default()));
 
     // 4. Spawn Event Broadcaster
     let b_tx = broadcast_tx.clone();
@@ -52,22 +53,26 @@ pub async fn start_host_server(
 
     tokio::spawn(async move {
         while let Some(event) = app_rx.recv().await {
-            // Cache snapshot if valid
-            if let CompassEvent::Snapshot { .. } = &event
-                && let Ok(json) = serde_json::to_string(&event)
+            // Cache snapshot and placeholders if valid
+            if let Ok(json) = serde_json::to_string(&event)
                 && let Ok(mut writer) = cache_writer.write()
             {
-                *writer = Some(json.clone());
+                match &event {
+                    CompassEvent::Snapshot { .. } => writer.snapshot = Some(json),
+                    CompassEvent::Placeholders { .. } => writer.placeholders = Some(json),
+                    _ => {}
+                }
             }
 
             if let Ok(json) = serde_json::to_string(&event) {
-                let _ = b_tx.send(json);
+                let _ = b_tx.send((event.capability(), json));
             }
         }
     });
 
     // 5. Listen for Connections
     let listener = TcpListener::bind(format!("0.0.0.0:{}", port)).await?;
+    tracing::info!(port, "Host server listening");
 
     while let Ok((stream, addr)) = listener.accept().await {
         let b_rx = broadcast_tx.subscribe();
@@ -79,14 +84,15 @@ pub async fn start_host_server(
             match acceptor_clone.accept(stream).await {
                 Ok(tls_stream) => {
                     // Upgrade to WebSocket over TLS
-                    if let Err(_e) =
+                    if let Err(e) =
                         handle_connection(tls_stream, addr, b_rx, pin_clone, cache_reader).await
                     {
                         // Connection failed, usually client disconnect or handshake error
+                        tracing::debug!(%addr, "Guest connection ended: {e}");
                     }
                 }
                 Err(e) => {
-                    eprintln!("TLS Handshake failed from {}: {}", addr, e);
+                    tracing::warn!("TLS Handshake failed from {}: {}", addr, e);
                 }
             }
         });
@@ -95,47 +101,83 @@ pub async fn start_host_server(
     Ok(())
 }
 
+/// The latest state sent to new guests on join.
+#[derive(Default)]
+struct StateCache {
+    snapshot: Option<String>,
+    placeholders: Option<String>,
+}
+
 /// Handles a single guest connection (already wrapped in TLS).
 /// Note: We strictly use the "websocket" stream which abstracts over TlsStream.
 async fn handle_connection(
     stream: tokio_rustls::server::TlsStream<TcpStream>,
     addr: SocketAddr,
-    mut b_rx: broadcast::Receiver<String>,
+    mut b_rx: broadcast::Receiver<(Option<&'static str>, String)>,
     expected_pin: String,
-    initial_state_cache: Arc<std::sync::RwLock<Option<String>>>,
+    initial_state_cache: Arc<std::sync::RwLock<StateCache>>,
 ) -> anyhow::Result<()> {
+    use tokio_tungstenite::tungstenite::handshake::server::ErrorResponse;
+    use tokio_tungstenite::tungstenite::http::StatusCode;
+
+    let reject = |status: StatusCode, message: String| {
+        let mut response = ErrorResponse::new(Some(message));
+        *response.status_mut() = status;
+        response
+    };
+
     // Explicitly verify the client knows the PIN.
     // This prevents unauthorized connections from just ignoring cert errors.
+    // Then check the guest speaks a compatible protocol version.
+    // The error type is dictated by tungstenite's handshake callback signature.
+    let mut guest = Handshake::local();
+    #[allow(clippy::result_large_err)]
     let callback =
         |req: &tokio_tungstenite::tungstenite::handshake::server::Request,
-         response: tokio_tungstenite::tungstenite::handshake::server::Response| {
-            if let Some(header_value) = req.headers().get("x-compass-pin")
-                && let Ok(val) = header_value.to_str()
-                && val == expected_pin
-            {
-                return Ok(response);
-            }
-            Err(
-                tokio_tungstenite::tungstenite::handshake::server::ErrorResponse::new(Some(
+         mut response: tokio_tungstenite::tungstenite::handshake::server::Response| {
+            let pin_ok = req
+                .headers()
+                .get("x-compass-pin")
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|val| val == expected_pin);
+            if !pin_ok {
+                return Err(reject(
+                    StatusCode::UNAUTHORIZED,
                     "Unauthorized: Invalid or Missing PIN".to_string(),
-                )),
-            )
+                ));
+            }
+
+            guest = Handshake::from_headers(req.headers());
+            if let Err(message) = guest.check_compatible("guest") {
+                return Err(reject(StatusCode::UPGRADE_REQUIRED, message));
+            }
+            Handshake::local().write_headers(response.headers_mut());
+            Ok(response)
         };
 
     let ws_stream = tokio_tungstenite::accept_hdr_async(stream, callback).await?;
-    // println!("✨ Guest connected (Secure + Authenticated): {}", addr); // Disabled to prevent TUI pollution
+    tracing::info!(
+        %addr,
+        protocol = guest.version,
+        capabilities = ?guest.capabilities,
+        "Guest connected (Secure + Authenticated)"
+    );
 
     let (mut write, mut read) = ws_stream.split();
 
-    // Send immediate snapshot if available
+    // Send immediate snapshot (and placeholders, if understood) if available
     {
-        let snapshot_opt = if let Ok(reader) = initial_state_cache.read() {
-            reader.clone()
+        let initial = if let Ok(reader) = initial_state_cache.read() {
+            let placeholders = reader
+                .placeholders
+                .clone()
+                .filter(|_| guest.supports("placeholders"));
+            [reader.snapshot.clone(), placeholders]
         } else {
-            None
+            [None, None]
         };
 
-        if let Some(json) = snapshot_opt {
+        for json in initial.into_iter().flatten() {
             write
                 .send(tokio_tungstenite::tungstenite::Message::Text(json.into()))
                 .await?;
@@ -146,8 +188,11 @@ async fn handle_connection(
         tokio::select! {
             msg = b_rx.recv() => {
                 match msg {
-                    Ok(json) => {
-                        write.send(tokio_tungstenite::tungstenite::Message::Text(json.into())).await?;
+                    Ok((capability, json)) => {
+                        // Skip events the guest would not understand
+                        if capability.is_none_or(|c| guest.supports(c)) {
+                            write.send(tokio_tungstenite::tungstenite::Message::Text(json.into())).await?;
+                        }
                     }
                     Err(broadcast::error::RecvError::Lagged(_)) => {}
                     Err(broadcast::error::RecvError::Closed) => break,
@@ -167,6 +212,6 @@ async fn handle_connection(
         }
     }
 
-    println!("👋 Guest disconnected: {}", addr);
+    tracing::info!(%addr, "Guest disconnected");
     Ok(())
 }
@@ -26,6 +26,8 @@ pub struct CollabSession {
     pub tx: Option<UnboundedSender<CompassEvent>>,
     /// Channel to receive events from the network layer (Guest only)
     pub rx: Option<Receiver<CompassEvent>>,
+    /// Send non-secret placeholder values to guests (Host only)
+    pub share_placeholders: bool,
 }
 
 impl CollabSession {
@@ -40,6 +42,13 @@ impl CollabSession {
             id,
             tx,
             rx,
+            share_placeholders: false,
         }
     }
+
+    /// Enables sending the host's placeholder values to guests.
+    pub const fn with_placeholder_sync(mut self, enabled: bool) -> Self {
+        self.share_placeholders = enabled;
+        self
+    }
 }
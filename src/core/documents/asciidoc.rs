@@ -0,0 +1,155 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{DocumentFormat, annotation};
+
+/// AsciiDoc: `=` section titles, `[source,lang]` listing blocks and `//` comments.
+pub struct AsciiDoc;
+
+impl DocumentFormat for AsciiDoc {
+    fn name(&self) -> &'static str {
+        "AsciiDoc"
+    }
+
+    fn to_markdown(&self, source: &str) -> anyhow::Result<String> {
+        let mut output = Vec::new();
+        let mut language: Option<String> = None;
+        // Delimiter closing the current listing or comment block
+        let mut open_block: Option<&str> = None;
+        let mut in_comment = false;
+
+        for line in source.lines() {
+            let trimmed = line.trim_end();
+            if let Some(delimiter) = open_block {
+                if trimmed == delimiter {
+                    if !in_comment {
+                        output.push("```".to_string());
+                    }
+                    open_block = None;
+                    in_comment = false;
+                } else if !in_comment {
+                    output.push(line.to_string());
+                }
+                continue;
+            }
+
+            if trimmed.starts_with("////") && trimmed.chars().all(|c| c == '/') {
+                open_block = Some(trimmed);
+                in_comment = true;
+            } else if is_delimiter(trimmed, '-') || is_delimiter(trimmed, '.') {
+                output.push(format!("```{}", language.take().unwrap_or_default()));
+                open_block = Some(trimmed);
+            } else if let Some(attributes) = trimmed
+                .strip_prefix('[')
+                .and_then(|rest| rest.strip_suffix(']'))
+                && let Some(rest) = attributes.strip_prefix("source")
+            {
+                // `[source,bash]` or `[source, bash, linenums]`
+                language = rest
+                    .split(',')
+                    .map(str::trim)
+                    .find(|part| !part.is_empty())
+                    .map(ToString::to_string);
+            } else if let Some(comment) = trimmed.strip_prefix("//") {
+                output.extend(annotation(comment));
+            } else if let Some((level, title)) = section_title(trimmed) {
+                output.push(format!("{} {title}", "#".repeat(level)));
+            } else if is_attribute_entry(trimmed)
+                || ['=', '*', '_'].iter().any(|&c| is_delimiter(trimmed, c))
+            {
+                // Document attributes (`:toc:`) and example, sidebar or quote
+                // delimiters have no Markdown equivalent
+            } else {
+                // Block titles (`.Install`) may sit between `[source]` and the block
+                if !trimmed.starts_with('.') {
+                    language = None;
+                }
+                output.push(line.to_string());
+            }
+        }
+        if open_block.is_some() && !in_comment {
+            output.push("```".to_string());
+        }
+        Ok(output.join("\n") + "\n")
+    }
+}
+
+/// Whether a document without a telling extension is AsciiDoc: it starts with a
+/// `= Title` document header, or has a `[source]` block, which Markdown never
+/// writes that way.
+pub fn looks_like_asciidoc(source: &str) -> bool {
+    let header = source
+        .lines()
+        .map(str::trim_end)
+        .find(|line| !line.is_empty() && !line.starts_with("//"));
+    if header.is_some_and(|line| {
+        line.strip_prefix("= ")
+            .is_some_and(|t| !t.trim().is_empty())
+    }) {
+        return true;
+    }
+    let lines: Vec<_> = source.lines().map(str::trim_end).collect();
+    lines.windows(2).any(|pair| {
+        pair[0].starts_with("[source") && pair[0].ends_with(']') && is_delimiter(pair[1], '-')
+    })
+}
+
+/// Whether a line is a block delimiter: four or more `c`.
+fn is_delimiter(line: &str, c: char) -> bool {
+    line.len() >= 4 && line.chars().all(|ch| ch == c)
+}
+
+/// `== Title` -> (2, "Title").
+fn section_title(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '=').count();
+    let title = line[level..].strip_prefix(' ')?.trim();
+    (1..=6)
+        .contains(&level)
+        .then_some((level, title.trim_end_matches([' ', '='])))
+}
+
+/// `:name: value` lines defining document attributes.
+fn is_attribute_entry(line: &str) -> bool {
+    line.strip_prefix(':')
+        .and_then(|rest| rest.split_once(':'))
+        .is_some_and(|(name, _)| {
+            !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '!')
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_readme;
+
+    #[test]
+    fn test_asciidoc_to_steps() {
+        let source = "= Deploy Guide\n:toc:\n\nIntro text.\n\n== Build\n// compass:tag ci-safe\n[source,bash]\n----\nmake build\n----\n\n////\nnot shown\n////\n\n=== Check\n....\nmake test\n....\n";
+        let markdown = AsciiDoc.to_markdown(source).unwrap();
+        assert!(!markdown.contains(":toc:"));
+        assert!(!markdown.contains("not shown"));
+
+        let (steps, _) = parse_readme(&markdown);
+        let titles: Vec<_> = steps.iter().map(|s| s.title.as_str()).collect();
+        assert_eq!(titles, vec!["Deploy Guide", "Build", "Check"]);
+        assert_eq!(steps[1].level, 2);
+        assert_eq!(steps[1].tags, vec!["ci-safe"]);
+        assert_eq!(steps[1].code_blocks[0].language.as_deref(), Some("bash"));
+        assert_eq!(steps[1].code_blocks[0].content, "make build\n");
+        assert_eq!(steps[2].code_blocks[0].language, None);
+    }
+}
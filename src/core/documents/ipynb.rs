@@ -0,0 +1,226 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+
+use super::DocumentFormat;
+
+/// Jupyter notebooks: markdown cells become the prose, code cells become code
+/// blocks in the language of the kernel. Outputs are ignored.
+pub struct Notebook;
+
+#[derive(Deserialize)]
+struct NotebookFile {
+    #[serde(default)]
+    cells: Option<Vec<Cell>>,
+    #[serde(default)]
+    metadata: NotebookMetadata,
+    #[serde(default)]
+    nbformat: u32,
+}
+
+#[derive(Deserialize, Default)]
+struct NotebookMetadata {
+    kernelspec: Option<KernelSpec>,
+    language_info: Option<LanguageInfo>,
+}
+
+#[derive(Deserialize)]
+struct KernelSpec {
+    language: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LanguageInfo {
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Cell {
+    cell_type: String,
+    #[serde(default)]
+    source: Source,
+}
+
+/// Cell sources are either a string or a list of lines.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Source {
+    Text(String),
+    Lines(Vec<String>),
+}
+
+impl Default for Source {
+    fn default() -> Self {
+        Source::Text(String::new())
+    }
+}
+
+impl Source {
+    fn text(&self) -> String {
+        match self {
+            Source::Text(text) => text.clone(),
+            Source::Lines(lines) => lines.concat(),
+        }
+    }
+}
+
+impl DocumentFormat for Notebook {
+    fn name(&self) -> &'static str {
+        "Jupyter notebook"
+    }
+
+    fn to_markdown(&self, source: &str) -> Result<String> {
+        let notebook: NotebookFile =
+            serde_json::from_str(source).context("Invalid Jupyter notebook")?;
+        let Some(cells) = notebook.cells else {
+            bail!(
+                "Unsupported notebook format version {} (expected 4)",
+                notebook.nbformat
+            );
+        };
+        let metadata = notebook.metadata;
+        let language = metadata
+            .kernelspec
+            .and_then(|k| k.language)
+            .or_else(|| metadata.language_info.and_then(|l| l.name))
+            .unwrap_or_else(|| "python".to_string())
+            .to_lowercase();
+
+        let mut output = Vec::new();
+        for cell in cells {
+            let text = cell.source.text();
+            if text.trim().is_empty() {
+                continue;
+            }
+            match cell.cell_type.as_str() {
+                "markdown" => output.push(text.trim_end().to_string()),
+                "code" => {
+                    let (language, code) = shell_cell(&text)
+                        .map(|code| ("bash", code))
+                        .unwrap_or((language.as_str(), text));
+                    output.push(fenced(language, code.trim_end()));
+                }
+                // Raw cells are passed through unrendered by Jupyter
+                _ => {}
+            }
+        }
+        Ok(output.join("\n\n") + "\n")
+    }
+}
+
+/// Shell code of a `%%bash` / `%%sh` cell, or of a cell made only of `!`
+/// commands, which the kernel would run in a shell.
+fn shell_cell(text: &str) -> Option<String> {
+    let (first, rest) = text.split_once('\n').unwrap_or((text, ""));
+    if matches!(first.trim(), "%%bash" | "%%sh") {
+        return Some(rest.to_string());
+    }
+    let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+    lines
+        .iter()
+        .all(|line| line.trim_start().starts_with('!'))
+        .then(|| {
+            lines
+                .iter()
+                .map(|line| &line.trim_start()[1..])
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+}
+
+/// Wraps code in a fence longer than any backtick run it contains.
+fn fenced(language: &str, code: &str) -> String {
+    let longest = code
+        .lines()
+        .map(|line| line.trim_start().chars().take_while(|&c| c == '`').count())
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{fence}{language}\n{code}\n{fence}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_readme;
+
+    #[test]
+    fn test_notebook_to_steps() {
+        let source = r###"{
+            "cells": [
+                {"cell_type": "markdown", "source": ["# Setup\n", "\n", "Install the dependencies."]},
+                {"cell_type": "code", "source": "!pip install numpy\n!pip install pandas", "outputs": []},
+                {"cell_type": "markdown", "source": "## Load\n<!-- compass:tag ci-safe -->"},
+                {"cell_type": "code", "source": ["import numpy\n", "print(numpy.ones(2))"], "outputs": [{"text": "ignored"}]},
+                {"cell_type": "code", "source": "", "outputs": []},
+                {"cell_type": "raw", "source": "not shown"}
+            ],
+            "metadata": {"kernelspec": {"language": "python", "name": "python3"}},
+            "nbformat": 4,
+            "nbformat_minor": 5
+        }"###;
+        let markdown = Notebook.to_markdown(source).unwrap();
+        assert!(!markdown.contains("not shown"));
+        assert!(!markdown.contains("ignored"));
+
+        let (steps, _) = parse_readme(&markdown);
+        let titles: Vec<_> = steps.iter().map(|s| s.title.as_str()).collect();
+        assert_eq!(titles, vec!["Setup", "Load"]);
+        assert_eq!(steps[0].code_blocks[0].language.as_deref(), Some("bash"));
+        assert_eq!(
+            steps[0].code_blocks[0].content,
+            "pip install numpy\npip install pandas\n"
+        );
+        assert_eq!(steps[1].tags, vec!["ci-safe"]);
+        assert_eq!(steps[1].code_blocks[0].language.as_deref(), Some("python"));
+        assert_eq!(steps[1].code_blocks.len(), 1);
+
+        assert!(Notebook.to_markdown("{\"nbformat\": 3}").is_err());
+        assert!(Notebook.to_markdown("not json").is_err());
+    }
+
+    #[test]
+    fn test_notebook_kernel_language() {
+        let notebook = |metadata: &str| {
+            format!(
+                r##"{{"cells": [
+                    {{"cell_type": "markdown", "source": "# Plot\nDraw the results."}},
+                    {{"cell_type": "code", "source": "plot(x)"}}
+                ], "metadata": {metadata}, "nbformat": 4}}"##
+            )
+        };
+        let steps = |metadata: &str| {
+            let markdown = super::super::to_markdown("setup.ipynb", notebook(metadata)).unwrap();
+            parse_readme(&markdown).0
+        };
+
+        let steps_r = steps(r#"{"language_info": {"name": "R"}}"#);
+        assert_eq!(steps_r[0].description.trim(), "Draw the results.");
+        assert_eq!(steps_r[0].code_blocks[0].language.as_deref(), Some("r"));
+        assert_eq!(steps_r[0].code_blocks[0].content, "plot(x)\n");
+
+        // The kernel wins over the language info, Python is the default
+        let julia = r#"{"kernelspec": {"language": "julia"}, "language_info": {"name": "R"}}"#;
+        assert_eq!(
+            steps(julia)[0].code_blocks[0].language.as_deref(),
+            Some("julia")
+        );
+        assert_eq!(
+            steps("{}")[0].code_blocks[0].language.as_deref(),
+            Some("python")
+        );
+    }
+}
@@ -0,0 +1,111 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::Result;
+use regex::Regex;
+use std::sync::LazyLock;
+
+use super::{DocumentFormat, annotation};
+
+/// MDX: Markdown with `import`/`export` statements, JSX components and
+/// `{/* */}` comments. Components are left to the parser, which flattens them
+/// like HTML.
+pub struct Mdx;
+
+impl DocumentFormat for Mdx {
+    fn name(&self) -> &'static str {
+        "MDX"
+    }
+
+    fn to_markdown(&self, source: &str) -> Result<String> {
+        let mut output = Vec::new();
+        // Prose lines since the last code block, where comments are replaced
+        let mut prose: Vec<&str> = Vec::new();
+        let mut fence: Option<&str> = None;
+        let mut in_statement = false;
+        let mut previous_blank = true;
+
+        for line in source.lines() {
+            let trimmed = line.trim_start();
+            if let Some(marker) = fence {
+                if trimmed.starts_with(marker) {
+                    fence = None;
+                }
+                output.push(line.to_string());
+                continue;
+            }
+
+            // `import`/`export` statements run until the next blank line
+            if previous_blank && (trimmed.starts_with("import ") || trimmed.starts_with("export "))
+            {
+                in_statement = true;
+            }
+            previous_blank = trimmed.is_empty();
+            if in_statement {
+                in_statement = !previous_blank;
+                continue;
+            }
+
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                fence = Some(&trimmed[..3]);
+                if !prose.is_empty() {
+                    output.push(replace_comments(&prose.join("\n")));
+                    prose.clear();
+                }
+                output.push(line.to_string());
+            } else {
+                prose.push(line);
+            }
+        }
+        if !prose.is_empty() {
+            output.push(replace_comments(&prose.join("\n")));
+        }
+        Ok(output.join("\n") + "\n")
+    }
+}
+
+/// Turns `{/* compass:... */}` comments into annotations and drops the others.
+fn replace_comments(prose: &str) -> String {
+    static COMMENT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)\{/\*(.*?)\*/\}").unwrap());
+    COMMENT
+        .replace_all(prose, |caps: &regex::Captures| {
+            annotation(&caps[1]).unwrap_or_default()
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_readme;
+
+    #[test]
+    fn test_mdx_to_steps() {
+        let source = "import Tabs from '@theme/Tabs';\nimport {\n  TabItem,\n} from '@theme/TabItem';\n\nexport const meta = {title: 'Setup'};\n\n# Setup\n{/* compass:tag ci-safe */}\n{/* Reviewed by docs team */}\n\n<Tabs>\n<TabItem value=\"npm\">\n\n```bash\nimport_data.sh\nnpm install\n```\n\n</TabItem>\n</Tabs>\n\n## Embed\n```jsx\n<App>{/* keep */}</App>\n```\n";
+        let markdown = Mdx.to_markdown(source).unwrap();
+        assert!(!markdown.contains("@theme"));
+        assert!(!markdown.contains("Reviewed"));
+
+        let (steps, _) = parse_readme(&markdown);
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].title, "Setup");
+        assert_eq!(steps[0].tags, vec!["ci-safe"]);
+        assert_eq!(steps[0].description, "");
+        assert_eq!(
+            steps[0].code_blocks[0].content,
+            "import_data.sh\nnpm install\n"
+        );
+        assert!(steps[1].code_blocks[0].content.contains("{/* keep */}"));
+    }
+}
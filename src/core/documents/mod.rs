@@ -0,0 +1,207 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Document Formats
+//!
+//! Converts runbooks written in other markup languages to the Markdown
+//! understood by the parser, so they become the same `Step` model.
+//!
+//! - `asciidoc` - AsciiDoc (`.adoc`, `.asciidoc`, `.asc`, or detected from the content)
+//! - `rst` - reStructuredText (`.rst`)
+//! - `ipynb` - Jupyter notebooks (`.ipynb`)
+//! - `mdx` - MDX (`.mdx`)
+
+use anyhow::Result;
+
+pub mod asciidoc;
+pub mod ipynb;
+pub mod mdx;
+pub mod rst;
+
+/// A markup language that can be converted to Markdown.
+pub trait DocumentFormat {
+    /// The name of the format, for messages.
+    fn name(&self) -> &'static str;
+
+    /// Converts a document to Markdown. Compass annotations written as comments
+    /// of the format (e.g. `// compass:tag ci-safe`) become HTML comments.
+    fn to_markdown(&self, source: &str) -> Result<String>;
+}
+
+/// Picks the format of a file or URL from its extension (`None` for Markdown).
+pub fn get_document_format(location: &str) -> Option<Box<dyn DocumentFormat>> {
+    let path = location.split(['?', '#']).next().unwrap_or(location);
+    let extension = path.rsplit_once('.')?.1.to_ascii_lowercase();
+    match extension.as_str() {
+        "adoc" | "asciidoc" | "asc" => Some(Box::new(asciidoc::AsciiDoc)),
+        "rst" | "rest" => Some(Box::new(rst::RestructuredText)),
+        "ipynb" => Some(Box::new(ipynb::Notebook)),
+        "mdx" => Some(Box::new(mdx::Mdx)),
+        _ => None,
+    }
+}
+
+/// Picks the format of a document from its extension, or from its content when
+/// the location has none that Compass knows (`README`, a `.txt` file, a raw URL).
+/// `None` for Markdown.
+pub fn detect_format(location: &str, content: &str) -> Option<Box<dyn DocumentFormat>> {
+    if let Some(format) = get_document_format(location) {
+        return Some(format);
+    }
+    let path = location.split(['?', '#']).next().unwrap_or(location);
+    let extension = path
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_ascii_lowercase());
+    if matches!(extension.as_deref(), Some("md" | "markdown")) {
+        return None;
+    }
+    asciidoc::looks_like_asciidoc(content).then(|| Box::new(asciidoc::AsciiDoc) as _)
+}
+
+/// Converts the content of `location` to Markdown when it is written in
+/// another format.
+pub fn to_markdown(location: &str, content: String) -> Result<String> {
+    match detect_format(location, &content) {
+        Some(format) => {
+            tracing::info!(
+                format = format.name(),
+                location,
+                "Converting document to Markdown"
+            );
+            format.to_markdown(&content)
+        }
+        None => Ok(content),
+    }
+}
+
+/// Wraps a Compass annotation in an HTML comment, the form the parser reads.
+fn annotation(text: &str) -> Option<String> {
+    let text = text.trim();
+    text.starts_with("compass:")
+        .then(|| format!("<!-- {text} -->"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_document_format() {
+        assert_eq!(
+            get_document_format("docs/setup.adoc").unwrap().name(),
+            "AsciiDoc"
+        );
+        assert_eq!(
+            get_document_format("https://example.com/INSTALL.rst?plain=1")
+                .unwrap()
+                .name(),
+            "reStructuredText"
+        );
+        assert_eq!(
+            get_document_format("setup.ipynb").unwrap().name(),
+            "Jupyter notebook"
+        );
+        assert!(get_document_format("README.md").is_none());
+        assert!(get_document_format("-").is_none());
+    }
+
+    #[test]
+    fn test_detect_format() {
+        let asciidoc = "= Setup\n:toc:\n\n== Install\n[source,bash]\n----\nmake\n----\n";
+        assert_eq!(
+            detect_format("INSTALL", asciidoc).unwrap().name(),
+            "AsciiDoc"
+        );
+        assert_eq!(
+            detect_format("https://example.com/raw/setup.txt", asciidoc)
+                .unwrap()
+                .name(),
+            "AsciiDoc"
+        );
+        // An explicit Markdown extension wins over the content
+        assert!(detect_format("README.md", asciidoc).is_none());
+        assert!(detect_format("-", "# Setup\n\n```bash\nmake\n```\n").is_none());
+    }
+}
+
+/// Property tests converting malformed documents: like Markdown runbooks,
+/// they can come from any URL.
+#[cfg(test)]
+mod properties {
+    use super::*;
+    use proptest::prelude::*;
+    use proptest::sample::select;
+
+    /// Markup the converters handle specially, to be shuffled into broken
+    /// documents.
+    const FRAGMENTS: &[&str] = &[
+        "= Title",
+        "== Step",
+        "[source,bash]",
+        "----",
+        "....",
+        "// compass:tag x",
+        "Title",
+        "=====",
+        "-----",
+        ".. code-block:: bash",
+        ".. code-block::",
+        "::",
+        "   indented",
+        ".. compass:if os=\"linux\"",
+        "import X from 'y'",
+        "export const a = 1",
+        "<Tabs>",
+        "{/* compass:tag x */}",
+        "{",
+        "}",
+        "é",
+    ];
+
+    fn document() -> impl Strategy<Value = String> {
+        let piece = prop_oneof![
+            3 => select(FRAGMENTS).prop_map(str::to_string),
+            1 => any::<String>(),
+        ];
+        proptest::collection::vec(piece, 0..40).prop_map(|pieces| pieces.join("\n"))
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn convert_broken_documents(content in document()) {
+            for location in ["a.adoc", "a.rst", "a.mdx", "a.ipynb"] {
+                if let Ok(markdown) = to_markdown(location, content.clone()) {
+                    crate::core::parser::parse_readme(&markdown);
+                }
+            }
+        }
+
+        #[test]
+        fn convert_broken_notebooks(cells in proptest::collection::vec(any::<(bool, String)>(), 0..8)) {
+            let cells: Vec<_> = cells
+                .into_iter()
+                .map(|(code, source)| serde_json::json!({
+                    "cell_type": if code { "code" } else { "markdown" },
+                    "source": source,
+                }))
+                .collect();
+            let notebook = serde_json::json!({ "cells": cells }).to_string();
+            if let Ok(markdown) = to_markdown("a.ipynb", notebook) {
+                crate::core::parser::parse_readme(&markdown);
+            }
+        }
+    }
+}
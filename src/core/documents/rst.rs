@@ -0,0 +1,218 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{DocumentFormat, annotation};
+
+/// Characters reStructuredText accepts for section underlines.
+const ADORNMENTS: &str = "=-~^\"'`#*+:._";
+
+/// Directives whose content is prose.
+const ADMONITIONS: &[&str] = &[
+    "attention",
+    "caution",
+    "danger",
+    "error",
+    "hint",
+    "important",
+    "note",
+    "tip",
+    "warning",
+];
+
+/// reStructuredText: underlined section titles, `code-block` directives,
+/// `::` literal blocks and `..` comments.
+pub struct RestructuredText;
+
+impl DocumentFormat for RestructuredText {
+    fn name(&self) -> &'static str {
+        "reStructuredText"
+    }
+
+    fn to_markdown(&self, source: &str) -> anyhow::Result<String> {
+        let lines: Vec<&str> = source.lines().collect();
+        let mut output = Vec::new();
+        // Adornment styles in order of appearance: the first one is level 1
+        let mut styles: Vec<(char, bool)> = Vec::new();
+        let mut i = 0;
+
+        while i < lines.len() {
+            let line = lines[i].trim_end();
+
+            // Section title, optionally overlined
+            if let Some((style, title, consumed)) = section_title(&lines[i..]) {
+                let level = match styles.iter().position(|s| *s == style) {
+                    Some(index) => index + 1,
+                    None => {
+                        styles.push(style);
+                        styles.len()
+                    }
+                };
+                output.push(format!("{} {title}", "#".repeat(level.min(6))));
+                i += consumed;
+                continue;
+            }
+
+            if let Some(directive) = line.strip_prefix(".. ") {
+                let language = ["code-block::", "code::", "sourcecode::"]
+                    .iter()
+                    .find_map(|name| directive.strip_prefix(name))
+                    .map(str::trim);
+                if let Some(language) = language {
+                    let (code, next) = indented_block(&lines, i + 1, true);
+                    output.push(format!("```{language}"));
+                    output.extend(code);
+                    output.push("```".to_string());
+                    i = next;
+                    continue;
+                }
+                let Some((name, argument)) = directive.split_once("::") else {
+                    // Comments and link targets: only keep Compass annotations
+                    output.extend(annotation(directive));
+                    i += 1;
+                    continue;
+                };
+                // Admonitions keep their text, other directives (images,
+                // tables of contents, ...) are dropped
+                let (body, next) = indented_block(&lines, i + 1, true);
+                if ADMONITIONS.contains(&name.trim()) {
+                    let mut label = name.trim().to_string();
+                    label[..1].make_ascii_uppercase();
+                    output.push(format!("**{label}:** {}", inline_literals(argument.trim())));
+                    output.extend(body.iter().map(|l| inline_literals(l)));
+                }
+                i = next;
+                continue;
+            }
+
+            // A paragraph ending with `::` introduces a literal block
+            if line.ends_with("::") {
+                let text = line.trim_start().trim_end_matches(':');
+                if !text.is_empty() {
+                    let separator = if text.ends_with(' ') { "" } else { ":" };
+                    output.push(format!("{}{separator}", text.trim_end()));
+                }
+                let (code, next) = indented_block(&lines, i + 1, false);
+                if !code.is_empty() {
+                    output.push(String::new());
+                    output.push("```".to_string());
+                    output.extend(code);
+                    output.push("```".to_string());
+                }
+                i = next;
+                continue;
+            }
+
+            // Indented text would be an (executable) code block in Markdown
+            output.push(inline_literals(line.trim_start()));
+            i += 1;
+        }
+        Ok(output.join("\n") + "\n")
+    }
+}
+
+/// Recognizes a section title at the start of `lines`: returns its style
+/// (adornment character, overlined), its text and the number of lines used.
+fn section_title<'a>(lines: &[&'a str]) -> Option<((char, bool), &'a str, usize)> {
+    let adornment = |line: &str| {
+        let line = line.trim_end();
+        let c = line.chars().next()?;
+        (line.len() >= 2 && ADORNMENTS.contains(c) && line.chars().all(|ch| ch == c))
+            .then_some((c, line.len()))
+    };
+    let is_title = |text: &str| !text.trim().is_empty() && !text.starts_with(char::is_whitespace);
+
+    // Overline, title, underline
+    if let [over, title, under, ..] = lines
+        && let Some((c, len)) = adornment(over)
+        && adornment(under) == Some((c, len))
+        && is_title(title.trim_start())
+    {
+        return Some(((c, true), title.trim(), 3));
+    }
+    // Title, underline at least as long
+    if let [title, under, ..] = lines
+        && is_title(title)
+        && let Some((c, len)) = adornment(under)
+        && len >= title.trim_end().chars().count()
+    {
+        return Some(((c, false), title.trim(), 2));
+    }
+    None
+}
+
+/// Collects the indented lines starting at `start` (after blank lines and,
+/// for directives, `:option:` lines), dedented. Returns them and the index of
+/// the first line after the block.
+fn indented_block(lines: &[&str], start: usize, directive: bool) -> (Vec<String>, usize) {
+    let mut i = start;
+    while i < lines.len()
+        && (lines[i].trim().is_empty() || (directive && lines[i].trim_start().starts_with(':')))
+    {
+        i += 1;
+    }
+    let end = (i..lines.len())
+        .find(|&j| !lines[j].trim().is_empty() && !lines[j].starts_with(char::is_whitespace))
+        .unwrap_or(lines.len());
+    let mut block: Vec<&str> = lines[i..end].to_vec();
+    while block.last().is_some_and(|l| l.trim().is_empty()) {
+        block.pop();
+    }
+    let indent = block
+        .iter()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.len() - l.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let code = block
+        .iter()
+        .map(|l| l.get(indent..).unwrap_or("").trim_end().to_string())
+        .collect();
+    (code, end)
+}
+
+/// ``code`` -> `code`.
+fn inline_literals(line: &str) -> String {
+    line.replace("``", "`")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_readme;
+
+    #[test]
+    fn test_rst_to_steps() {
+        let source = "=============\nInstall Guide\n=============\n\nRead ``INSTALL`` first.\n\nBuild\n=====\n\n.. compass:tag ci-safe\n.. code-block:: bash\n   :linenos:\n\n   make build\n   make check\n\nTest\n----\n\nRun the suite::\n\n    make test\n\n.. warning::\n   Slow on CI.\n\n.. image:: diagram.png\n   :alt: Diagram\n";
+        let markdown = RestructuredText.to_markdown(source).unwrap();
+        let (steps, _) = parse_readme(&markdown);
+
+        let titles: Vec<_> = steps.iter().map(|s| s.title.as_str()).collect();
+        assert_eq!(titles, vec!["Install Guide", "Build", "Test"]);
+        assert_eq!(
+            steps.iter().map(|s| s.level).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert!(steps[0].description.contains("Read `INSTALL` first."));
+        assert_eq!(steps[1].tags, vec!["ci-safe"]);
+        assert_eq!(steps[1].code_blocks[0].language.as_deref(), Some("bash"));
+        assert_eq!(steps[1].code_blocks[0].content, "make build\nmake check\n");
+        assert!(steps[2].description.contains("Run the suite:"));
+        assert_eq!(steps[2].code_blocks[0].content, "make test\n");
+        assert!(!steps[2].code_blocks[0].indented);
+        assert_eq!(steps[2].code_blocks.len(), 1);
+        assert!(steps[2].description.contains("Warning:"));
+        assert!(steps[2].description.contains("Slow on CI."));
+        assert!(!markdown.contains("diagram.png"));
+    }
+}
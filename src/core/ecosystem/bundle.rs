@@ -0,0 +1,589 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Package Bundles
+//!
+//! Turns the dependencies of a runbook into a single package-manager
+//! transaction: a Brewfile, an `apt-get install` line or a winget import file,
+//! or into a Nix development shell (`flake.nix` or `shell.nix`).
+
+use crate::core::executor::checker::{CheckResult, check_dependencies};
+use crate::core::models::Step;
+use regex::Regex;
+use serde_json::json;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::LazyLock;
+
+/// The package managers a bundle can be generated for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageManager {
+    Brew,
+    Apt,
+    Winget,
+    /// A `flake.nix` exposing a development shell.
+    NixFlake,
+    /// A `shell.nix` for `nix-shell`.
+    NixShell,
+}
+
+impl PackageManager {
+    const fn is_nix(self) -> bool {
+        matches!(self, Self::NixFlake | Self::NixShell)
+    }
+}
+
+/// A package known to provide some commands.
+struct KnownPackage {
+    commands: &'static [&'static str],
+    /// Homebrew formula, or cask when prefixed with `cask:`.
+    brew: Option<&'static str>,
+    apt: Option<&'static str>,
+    /// winget package identifier.
+    winget: Option<&'static str>,
+    /// nixpkgs attribute.
+    nix: Option<&'static str>,
+}
+
+const fn known(
+    commands: &'static [&'static str],
+    brew: Option<&'static str>,
+    apt: Option<&'static str>,
+    winget: Option<&'static str>,
+    nix: Option<&'static str>,
+) -> KnownPackage {
+    KnownPackage {
+        commands,
+        brew,
+        apt,
+        winget,
+        nix,
+    }
+}
+
+const KNOWN_PACKAGES: &[KnownPackage] = &[
+    known(
+        &["git"],
+        Some("git"),
+        Some("git"),
+        Some("Git.Git"),
+        Some("git"),
+    ),
+    known(
+        &["curl"],
+        Some("curl"),
+        Some("curl"),
+        Some("cURL.cURL"),
+        Some("curl"),
+    ),
+    known(
+        &["wget"],
+        Some("wget"),
+        Some("wget"),
+        Some("JernejSimoncic.Wget"),
+        Some("wget"),
+    ),
+    known(
+        &["jq"],
+        Some("jq"),
+        Some("jq"),
+        Some("jqlang.jq"),
+        Some("jq"),
+    ),
+    known(
+        &["rg"],
+        Some("ripgrep"),
+        Some("ripgrep"),
+        Some("BurntSushi.ripgrep.MSVC"),
+        Some("ripgrep"),
+    ),
+    known(
+        &["make"],
+        Some("make"),
+        Some("make"),
+        Some("GnuWin32.Make"),
+        Some("gnumake"),
+    ),
+    known(
+        &["cmake"],
+        Some("cmake"),
+        Some("cmake"),
+        Some("Kitware.CMake"),
+        Some("cmake"),
+    ),
+    known(
+        &["gcc", "g++"],
+        Some("gcc"),
+        Some("build-essential"),
+        None,
+        Some("gcc"),
+    ),
+    known(
+        &["unzip"],
+        Some("unzip"),
+        Some("unzip"),
+        None,
+        Some("unzip"),
+    ),
+    known(
+        &["node", "npx"],
+        Some("node"),
+        Some("nodejs"),
+        Some("OpenJS.NodeJS.LTS"),
+        Some("nodejs"),
+    ),
+    known(
+        &["npm"],
+        Some("node"),
+        Some("npm"),
+        Some("OpenJS.NodeJS.LTS"),
+        Some("nodejs"),
+    ),
+    known(
+        &["nvm"],
+        Some("nvm"),
+        None,
+        Some("CoreyButler.NVMforWindows"),
+        Some("nodejs"),
+    ),
+    known(
+        &["yarn"],
+        Some("yarn"),
+        Some("yarnpkg"),
+        Some("Yarn.Yarn"),
+        Some("yarn"),
+    ),
+    known(
+        &["pnpm"],
+        Some("pnpm"),
+        None,
+        Some("pnpm.pnpm"),
+        Some("pnpm"),
+    ),
+    known(
+        &["deno"],
+        Some("deno"),
+        None,
+        Some("DenoLand.Deno"),
+        Some("deno"),
+    ),
+    known(
+        &["python", "python3"],
+        Some("python"),
+        Some("python3"),
+        Some("Python.Python.3.12"),
+        Some("python3"),
+    ),
+    known(
+        &["pip", "pip3"],
+        Some("python"),
+        Some("python3-pip"),
+        Some("Python.Python.3.12"),
+        Some("python3Packages.pip"),
+    ),
+    known(&["pyenv"], Some("pyenv"), None, None, Some("python3")),
+    known(
+        &["go"],
+        Some("go"),
+        Some("golang"),
+        Some("GoLang.Go"),
+        Some("go"),
+    ),
+    known(
+        &["cargo"],
+        Some("rustup"),
+        Some("rustup"),
+        Some("Rustlang.Rustup"),
+        Some("cargo"),
+    ),
+    known(
+        &["rustc"],
+        Some("rustup"),
+        Some("rustup"),
+        Some("Rustlang.Rustup"),
+        Some("rustc"),
+    ),
+    known(
+        &["rustup"],
+        Some("rustup"),
+        Some("rustup"),
+        Some("Rustlang.Rustup"),
+        Some("rustup"),
+    ),
+    known(
+        &["ruby", "gem"],
+        Some("ruby"),
+        Some("ruby"),
+        Some("RubyInstallerTeam.Ruby.3.2"),
+        Some("ruby"),
+    ),
+    known(&["php"], Some("php"), Some("php-cli"), None, Some("php")),
+    known(
+        &["java", "javac"],
+        Some("openjdk"),
+        Some("default-jdk"),
+        Some("Microsoft.OpenJDK.21"),
+        Some("jdk"),
+    ),
+    known(
+        &["dotnet"],
+        Some("cask:dotnet-sdk"),
+        Some("dotnet-sdk-8.0"),
+        Some("Microsoft.DotNet.SDK.8"),
+        Some("dotnet-sdk"),
+    ),
+    known(
+        &["docker"],
+        Some("cask:docker"),
+        Some("docker.io"),
+        Some("Docker.DockerDesktop"),
+        Some("docker"),
+    ),
+    known(
+        &["kubectl"],
+        Some("kubectl"),
+        None,
+        Some("Kubernetes.kubectl"),
+        Some("kubectl"),
+    ),
+    known(
+        &["helm"],
+        Some("helm"),
+        None,
+        Some("Helm.Helm"),
+        Some("kubernetes-helm"),
+    ),
+    known(
+        &["terraform"],
+        Some("hashicorp/tap/terraform"),
+        None,
+        Some("Hashicorp.Terraform"),
+        Some("terraform"),
+    ),
+    known(
+        &["gh"],
+        Some("gh"),
+        Some("gh"),
+        Some("GitHub.cli"),
+        Some("gh"),
+    ),
+];
+
+/// A generated bundle.
+#[derive(Debug)]
+pub struct Bundle {
+    /// The file content (or command line, for apt).
+    pub content: String,
+    /// Missing commands no package could be found for.
+    pub skipped: Vec<String>,
+}
+
+/// Finds the known package providing `command`, ignoring a version suffix
+/// (`python3.12`, `go1.22`).
+fn known_package(command: &str) -> Option<&'static KnownPackage> {
+    let find = |name: &str| KNOWN_PACKAGES.iter().find(|p| p.commands.contains(&name));
+    find(command)
+        .or_else(|| find(command.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.')))
+}
+
+/// Finds the package providing `command` with `manager`.
+///
+/// Unknown commands are assumed to be packaged under their own name, except for
+/// winget and Nix where a wrong guess would make the whole bundle fail.
+fn package_for(command: &str, manager: PackageManager) -> Option<String> {
+    match known_package(command) {
+        Some(package) => match manager {
+            PackageManager::Brew => package.brew,
+            PackageManager::Apt => package.apt,
+            PackageManager::Winget => package.winget,
+            PackageManager::NixFlake | PackageManager::NixShell => package.nix,
+        }
+        .map(str::to_string),
+        None if manager == PackageManager::Winget || manager.is_nix() => None,
+        None => Some(command.to_string()),
+    }
+}
+
+/// Resolves the packages to install, sorted and deduplicated.
+///
+/// Only the missing commands are covered unless `all` is set, in which case
+/// present commands with a known package are added too. Nix shells always
+/// cover both, since they replace what is installed on the host.
+fn resolve(check: &CheckResult, manager: PackageManager, all: bool) -> (Vec<String>, Vec<String>) {
+    let mut packages = Vec::new();
+    let mut skipped = Vec::new();
+    for command in &check.missing {
+        match package_for(command, manager) {
+            Some(package) => packages.push(package),
+            None => skipped.push(command.clone()),
+        }
+    }
+    if all || manager.is_nix() {
+        for command in &check.present {
+            if known_package(command).is_some()
+                && let Some(package) = package_for(command, manager)
+            {
+                packages.push(package);
+            }
+        }
+    }
+    packages.sort();
+    packages.dedup();
+    (packages, skipped)
+}
+
+/// Generates the bundle installing the dependencies of a runbook.
+///
+/// `source` names the runbook in the generated header.
+pub fn render(steps: &[Step], manager: PackageManager, all: bool, source: &str) -> Bundle {
+    let check = check_dependencies(steps);
+    let versions = if manager.is_nix() {
+        detect_versions(steps)
+    } else {
+        HashMap::new()
+    };
+    render_check(&check, &versions, manager, all, source)
+}
+
+fn render_check(
+    check: &CheckResult,
+    versions: &HashMap<&'static str, String>,
+    manager: PackageManager,
+    all: bool,
+    source: &str,
+) -> Bundle {
+    let (packages, skipped) = resolve(check, manager, all);
+    let content = match manager {
+        PackageManager::Brew => brewfile(&packages, source),
+        PackageManager::Apt => apt_line(&packages, source),
+        PackageManager::Winget => winget_import(&packages),
+        PackageManager::NixFlake | PackageManager::NixShell => {
+            let mut attributes: Vec<String> = packages
+                .iter()
+                .map(|attribute| versioned_attribute(attribute, versions))
+                .collect();
+            attributes.sort();
+            attributes.dedup();
+            if manager == PackageManager::NixFlake {
+                nix_flake(&attributes, source)
+            } else {
+                nix_shell(&attributes, source)
+            }
+        }
+    };
+    Bundle { content, skipped }
+}
+
+/// Patterns revealing the language versions a runbook expects, by nixpkgs attribute.
+static VERSION_PATTERNS: LazyLock<Vec<(&'static str, Regex)>> = LazyLock::new(|| {
+    [
+        ("nodejs", r"\bnvm\s+(?:install|use)\s+v?(\d+)"),
+        ("python3", r"\bpython(3\.\d+)\b"),
+        (
+            "python3",
+            r"\bpyenv\s+(?:install|local|global|shell)\s+(3\.\d+)",
+        ),
+        ("go", r"\bgo(1\.\d+)\b"),
+    ]
+    .into_iter()
+    .filter_map(|(attribute, pattern)| Some((attribute, Regex::new(pattern).ok()?)))
+    .collect()
+});
+
+/// Finds the language versions used by the code blocks (e.g. `nvm use 18`).
+///
+/// The first version found for a language wins.
+fn detect_versions(steps: &[Step]) -> HashMap<&'static str, String> {
+    let mut versions = HashMap::new();
+    for block in steps.iter().flat_map(|s| &s.code_blocks) {
+        for (attribute, pattern) in VERSION_PATTERNS.iter() {
+            if let Some(caps) = pattern.captures(&block.content) {
+                versions
+                    .entry(*attribute)
+                    .or_insert_with(|| caps[1].to_string());
+            }
+        }
+    }
+    versions
+}
+
+/// Pins a nixpkgs attribute to the detected version (`nodejs` + 18 -> `nodejs_18`).
+fn versioned_attribute(attribute: &str, versions: &HashMap<&'static str, String>) -> String {
+    let base = attribute.split('.').next().unwrap_or(attribute);
+    let base = base.strip_suffix("Packages").unwrap_or(base);
+    let Some(version) = versions.get(base) else {
+        return attribute.to_string();
+    };
+    let pinned = match base {
+        "nodejs" => format!("nodejs_{version}"),
+        "python3" => format!("python{}", version.replace('.', "")),
+        "go" => format!("go_{}", version.replace('.', "_")),
+        _ => return attribute.to_string(),
+    };
+    format!("{pinned}{}", &attribute[base.len()..])
+}
+
+fn brewfile(packages: &[String], source: &str) -> String {
+    let mut out = format!("# Dependencies of {source}, generated by Compass\n");
+    // Formulae first, then casks
+    let (casks, formulae): (Vec<_>, Vec<_>) = packages.iter().partition(|p| p.starts_with("cask:"));
+    for formula in formulae {
+        let _ = writeln!(out, "brew \"{formula}\"");
+    }
+    for cask in casks {
+        let _ = writeln!(out, "cask \"{}\"", cask.trim_start_matches("cask:"));
+    }
+    out
+}
+
+fn apt_line(packages: &[String], source: &str) -> String {
+    let mut out = format!("# Dependencies of {source}, generated by Compass\n");
+    if packages.is_empty() {
+        out.push_str("# Nothing to install\n");
+    } else {
+        let _ = writeln!(
+            out,
+            "sudo apt-get update && sudo apt-get install -y {}",
+            packages.join(" ")
+        );
+    }
+    out
+}
+
+fn winget_import(packages: &[String]) -> String {
+    let packages: Vec<_> = packages
+        .iter()
+        .map(|id| json!({ "PackageIdentifier": id }))
+        .collect();
+    let import = json!({
+        "$schema": "https://aka.ms/winget-packages.schema.2.0.json",
+        "CreationDate": chrono::Local::now().to_rfc3339(),
+        "Sources": [{
+            "Packages": packages,
+            "SourceDetails": {
+                "Argument": "https://cdn.winget.microsoft.com/cache",
+                "Identifier": "Microsoft.Winget.Source_8wekyb3d8bbwe",
+                "Name": "winget",
+                "Type": "Microsoft.PreIndexed.Package"
+            }
+        }],
+        "WinGetVersion": "1.6.0"
+    });
+    let mut out = serde_json::to_string_pretty(&import).unwrap_or_default();
+    out.push('\n');
+    out
+}
+
+/// Lists the attributes one per line, indented by `indent` spaces.
+fn nix_packages(attributes: &[String], indent: usize) -> String {
+    let mut out = String::new();
+    for attribute in attributes {
+        let _ = writeln!(out, "{:indent$}{attribute}", "");
+    }
+    out
+}
+
+fn nix_flake(attributes: &[String], source: &str) -> String {
+    let packages = nix_packages(attributes, 12);
+    format!(
+        r#"# Development shell for {source}, generated by Compass
+{{
+  description = "Development shell for {source}";
+
+  inputs = {{
+    nixpkgs.url = "github:NixOS/nixpkgs/nixos-unstable";
+    flake-utils.url = "github:numtide/flake-utils";
+  }};
+
+  outputs = {{ nixpkgs, flake-utils, ... }}:
+    flake-utils.lib.eachDefaultSystem (system:
+      let
+        pkgs = nixpkgs.legacyPackages.${{system}};
+      in
+      {{
+        devShells.default = pkgs.mkShell {{
+          packages = with pkgs; [
+{packages}          ];
+        }};
+      }});
+}}
+"#
+    )
+}
+
+fn nix_shell(attributes: &[String], source: &str) -> String {
+    format!(
+        "# Development shell for {source}, generated by Compass\n\
+         {{ pkgs ? import <nixpkgs> {{ }} }}:\n\
+         \n\
+         pkgs.mkShell {{\n  packages = with pkgs; [\n{}  ];\n}}\n",
+        nix_packages(attributes, 4)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(present: &[&str], missing: &[&str]) -> CheckResult {
+        CheckResult {
+            present: present.iter().map(ToString::to_string).collect(),
+            missing: missing.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    #[test]
+    fn test_bundle_packages() {
+        let result = check(&["git", "ls"], &["npm", "node", "docker", "mytool"]);
+
+        let none = HashMap::new();
+        let brew = render_check(&result, &none, PackageManager::Brew, false, "README.md");
+        assert!(brew.content.contains("brew \"node\"\ncask \"docker\""));
+        assert!(brew.content.contains("brew \"mytool\""));
+        assert!(!brew.content.contains("git"));
+
+        let apt = render_check(&result, &none, PackageManager::Apt, true, "README.md");
+        assert!(
+            apt.content
+                .contains("install -y docker.io git mytool nodejs npm\n")
+        );
+
+        let winget = render_check(&result, &none, PackageManager::Winget, false, "README.md");
+        assert_eq!(winget.skipped, vec!["mytool"]);
+        assert_eq!(winget.content.matches("OpenJS.NodeJS.LTS").count(), 1);
+    }
+
+    #[test]
+    fn test_nix_versions() {
+        let result = check(&["git", "ls"], &["nvm", "pip", "python3"]);
+        let versions = HashMap::from([
+            ("nodejs", "18".to_string()),
+            ("python3", "3.11".to_string()),
+        ]);
+
+        let shell = render_check(
+            &result,
+            &versions,
+            PackageManager::NixShell,
+            false,
+            "README.md",
+        );
+        assert!(
+            shell
+                .content
+                .contains("    git\n    nodejs_18\n    python311\n    python311Packages.pip\n  ];")
+        );
+        assert!(shell.skipped.is_empty());
+    }
+}
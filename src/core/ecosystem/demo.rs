@@ -0,0 +1,111 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Example runbooks embedded in the binary. `compass demo` opens them, so new
+//! users can try the TUI without looking for a suitable README first.
+
+use anyhow::{Context, Result, bail};
+use std::fs;
+use std::path::PathBuf;
+
+/// A runbook shipped with Compass.
+pub struct Demo {
+    /// Name given to `compass demo`.
+    pub name: &'static str,
+    /// What the demo shows, for `compass demo --list`.
+    pub summary: &'static str,
+    /// The Markdown of the runbook.
+    pub content: &'static str,
+}
+
+/// The demos, the one opened by default first.
+pub const DEMOS: &[Demo] = &[
+    Demo {
+        name: "tour",
+        summary: "Navigation, placeholders, choice lists, secrets and state between steps",
+        content: include_str!("../../../demos/tour.md"),
+    },
+    Demo {
+        name: "safety",
+        summary: "Dangerous command alerts, missing tools and fix suggestions",
+        content: include_str!("../../../demos/safety.md"),
+    },
+    Demo {
+        name: "sandbox",
+        summary: "Running steps in a Docker container (needs Docker)",
+        content: include_str!("../../../demos/sandbox.md"),
+    },
+];
+
+/// The runbook of `compass tutorial`, whose lessons expect its steps in this order.
+pub const TUTORIAL: Demo = Demo {
+    name: "tutorial",
+    summary: "Guided tour of the keys: navigation, execution, placeholders and export",
+    content: include_str!("../../../demos/tutorial.md"),
+};
+
+/// Finds a demo by name, ignoring case.
+///
+/// # Errors
+///
+/// Returns an error listing the demos if none has this name.
+pub fn find(name: &str) -> Result<&'static Demo> {
+    match DEMOS.iter().find(|d| d.name.eq_ignore_ascii_case(name)) {
+        Some(demo) => Ok(demo),
+        None => {
+            let names: Vec<_> = DEMOS.iter().map(|d| d.name).collect();
+            bail!("Unknown demo '{name}'. Available: {}.", names.join(", "))
+        }
+    }
+}
+
+/// Writes a demo to a scratch directory of its own, where its steps run, and
+/// returns the path of the runbook.
+///
+/// # Errors
+///
+/// Returns an error if the directory or the file cannot be written.
+pub fn install(demo: &Demo) -> Result<PathBuf> {
+    let dir = std::env::temp_dir().join("compass-demo").join(demo.name);
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create the demo directory {}", dir.display()))?;
+    let path = dir.join(format!("{}.md", demo.name));
+    fs::write(&path, demo.content)
+        .with_context(|| format!("Failed to write the demo runbook {}", path.display()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_readme;
+
+    #[test]
+    fn test_demos_parse() {
+        for demo in DEMOS {
+            let (steps, _) = parse_readme(demo.content);
+            assert!(
+                steps.iter().filter(|s| s.is_executable()).count() >= 3,
+                "{} has too few steps",
+                demo.name
+            );
+        }
+        assert_eq!(find("Safety").unwrap().name, "safety");
+
+        let (steps, _) = parse_readme(TUTORIAL.content);
+        assert!(steps[1].is_executable() && steps[2].is_executable());
+        assert!(steps[2].code_blocks[0].content.contains("<NAME>"));
+        assert!(find("missing").is_err());
+    }
+}
@@ -18,7 +18,8 @@ use std::path::{Path, PathBuf};
 
 const MAX_DEPTH: usize = 5;
 
-/// Scans the directory for Compass runbooks (README.md or *.runbook.md).
+/// their MDX, AsciiDoc, reStructuredText and notebook equivalents).
+/// their AsciiDoc and reStructuredText equivalents).
 /// Uses an iterative approach to prevent stack overflow and respects max depth.
 pub fn scan_directory(root: &Path) -> Result<Vec<PathBuf>> {
     let mut results = Vec::new();
@@ -60,5 +61,8 @@ pub fn scan_directory(root: &Path) -> Result<Vec<PathBuf>> {
 fn is_runbook(path: &Path) -> bool {
     let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
     // Check for README.md (case-insensitive) or .runbook.md extension
-    name.eq_ignore_ascii_case("README.md") || name.ends_with(".runbook.md")
+    ["md", "mdx", "adoc", "rst", "ipynb"].iter().any(|ext| {
+        name.eq_ignore_ascii_case(&format!("README.{ext}"))
+            || name.ends_with(&format!(".runbook.{ext}"))
+    })
 }
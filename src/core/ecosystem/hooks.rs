@@ -12,29 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::process::Command;
 use std::thread;
 
-/// Configuration for event hooks extracted from frontmatter.
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
-pub struct HookConfig {
-    pub pre_run: Option<String>,
-    pub post_run: Option<String>,
-    pub on_failure: Option<String>,
-    pub on_success: Option<String>,
-}
-
-impl HookConfig {
-    pub fn has_any(&self) -> bool {
-        self.pre_run.is_some()
-            || self.post_run.is_some()
-            || self.on_failure.is_some()
-            || self.on_success.is_some()
-    }
-}
-
 /// Triggers a hook command in a background thread.
 ///
 /// # Arguments
@@ -48,6 +29,8 @@ pub fn trigger_hook(hook_cmd: &Option<String>, context_env: &HashMap<String, Str
             .map(|(k, v)| (k.clone(), v.clone()))
             .collect();
 
+        tracing::info!(command = %cmd_string, "Triggering hook");
+
         // Spawn a thread to avoid blocking the main UI loop
         thread::spawn(move || {
             #[cfg(target_os = "windows")]
@@ -69,11 +52,11 @@ pub fn trigger_hook(hook_cmd: &Option<String>, context_env: &HashMap<String, Str
                 Ok(output) => {
                     if !output.status.success() {
                         let stderr = String::from_utf8_lossy(&output.stderr);
-                        eprintln!("[Hook Error] Command '{}' failed: {}", cmd_string, stderr);
+                        tracing::error!("Hook command '{}' failed: {}", cmd_string, stderr);
                     }
                 }
                 Err(e) => {
-                    eprintln!("[Hook Failed] Could not execute '{}': {}", cmd_string, e);
+                    tracing::error!("Could not execute hook '{}': {}", cmd_string, e);
                 }
             }
         });
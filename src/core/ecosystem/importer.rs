@@ -0,0 +1,288 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::{Context, Result};
+use directories::BaseDirs;
+use regex::Regex;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Maximum length of a step title derived from a command.
+const MAX_TITLE_LEN: usize = 60;
+
+/// A group of related commands that becomes one runbook step.
+#[derive(Debug, Default)]
+struct CommandGroup {
+    /// Comment lines found right above the commands.
+    comments: Vec<String>,
+    commands: Vec<String>,
+}
+
+/// Converts a shell script into a runbook skeleton.
+///
+/// Blank lines delimit the steps, and the comments right above a group of
+/// commands become its title and description.
+pub fn from_script(content: &str, source: &Path) -> String {
+    let language = match source.extension().and_then(|e| e.to_str()) {
+        Some("ps1") => "powershell",
+        _ => "bash",
+    };
+    let heredoc_re = Regex::new(r#"<<-?\s*['"]?(\w+)['"]?"#).unwrap();
+
+    let mut groups = Vec::new();
+    let mut current = CommandGroup::default();
+    let mut heredoc: Option<String> = None;
+    let mut depth = 0usize;
+    let mut continued = false;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+
+        if let Some(end) = &heredoc {
+            if trimmed == end {
+                heredoc = None;
+            }
+            current.commands.push(line.to_string());
+            continue;
+        }
+        if depth > 0 || continued {
+            current.commands.push(line.to_string());
+        } else if trimmed.is_empty() {
+            if !current.commands.is_empty() {
+                groups.push(std::mem::take(&mut current));
+            }
+            continue;
+        } else if trimmed.starts_with('#') {
+            // Shebangs and comments between commands of a group stay in the code
+            if line.starts_with("#!") {
+                continue;
+            }
+            if current.commands.is_empty() {
+                current
+                    .comments
+                    .push(trimmed.trim_start_matches('#').trim().to_string());
+            } else {
+                current.commands.push(line.to_string());
+            }
+            continue;
+        } else if is_shell_option(trimmed) {
+            // Each step runs on its own, so script-wide options do not carry over
+            continue;
+        } else {
+            current.commands.push(line.to_string());
+        }
+
+        if let Some(caps) = heredoc_re.captures(trimmed) {
+            heredoc = Some(caps[1].to_string());
+        }
+        continued = trimmed.ends_with('\\');
+        depth = block_depth(trimmed, depth);
+    }
+    if !current.commands.is_empty() {
+        groups.push(current);
+    }
+
+    let name = source
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("script");
+    render_runbook(
+        name,
+        &format!("Imported from `{}`.", source.display()),
+        language,
+        &groups,
+    )
+}
+
+/// Converts the last `count` commands of the user's shell history into a
+/// runbook skeleton, grouping consecutive commands that use the same program.
+pub fn from_history(count: usize) -> Result<String> {
+    let path = history_file().context("No shell history file found")?;
+    let raw =
+        fs::read(&path).with_context(|| format!("Failed to read history: {}", path.display()))?;
+    let mut commands = parse_history(&String::from_utf8_lossy(&raw));
+    commands.dedup();
+    let commands = commands.split_off(commands.len().saturating_sub(count));
+
+    let mut groups: Vec<CommandGroup> = Vec::new();
+    for command in commands {
+        // `cd` opens a new group that includes the commands run there
+        let same_program = groups
+            .last()
+            .and_then(|g| g.commands.last())
+            .is_some_and(|last| program(last) == "cd" || program(last) == program(&command));
+        match groups.last_mut() {
+            Some(group) if same_program => group.commands.push(command),
+            _ => groups.push(CommandGroup {
+                comments: Vec::new(),
+                commands: vec![command],
+            }),
+        }
+    }
+
+    Ok(render_runbook(
+        "Shell History",
+        &format!("Imported from `{}`.", path.display()),
+        "bash",
+        &groups,
+    ))
+}
+
+/// Renders command groups as a Markdown runbook.
+fn render_runbook(name: &str, intro: &str, language: &str, groups: &[CommandGroup]) -> String {
+    let mut out = format!("# {name}\n\n{intro}\n");
+    for (i, group) in groups.iter().enumerate() {
+        let (title, description) = match group.comments.split_first() {
+            Some((title, rest)) if !title.is_empty() => (title.clone(), rest.join("\n")),
+            _ => (command_title(&group.commands[0]), String::new()),
+        };
+        let _ = write!(out, "\n## {}. {title}\n\n", i + 1);
+        if !description.trim().is_empty() {
+            let _ = write!(out, "{}\n\n", description.trim());
+        }
+        let _ = write!(out, "```{language}\n{}\n```\n", group.commands.join("\n"));
+    }
+    out
+}
+
+/// Builds a step title from the first line of a command.
+fn command_title(command: &str) -> String {
+    let line = command.lines().next().unwrap_or_default().trim();
+    if line.chars().count() > MAX_TITLE_LEN {
+        let cut: String = line.chars().take(MAX_TITLE_LEN).collect();
+        format!("{}…", cut.trim_end())
+    } else {
+        line.to_string()
+    }
+}
+
+/// Returns the program a command line runs.
+fn program(command: &str) -> &str {
+    command
+        .split_whitespace()
+        .find(|w| *w != "sudo" && !w.contains('='))
+        .unwrap_or_default()
+}
+
+/// Whether the line only sets shell options (e.g. `set -euo pipefail`).
+fn is_shell_option(line: &str) -> bool {
+    line.strip_prefix("set ")
+        .is_some_and(|rest| rest.trim_start().starts_with(['-', '+']))
+}
+
+/// Updates the nesting depth of compound commands (`if`, loops, functions)
+/// so blank lines inside them do not split the step.
+fn block_depth(line: &str, mut depth: usize) -> usize {
+    let code = line.split(" #").next().unwrap_or(line);
+    let mut command_position = true;
+    for word in code.split_whitespace() {
+        let keyword = word.trim_end_matches(';');
+        match keyword {
+            "{" => depth += 1,
+            "}" => depth = depth.saturating_sub(1),
+            "if" | "for" | "while" | "until" | "case" if command_position => depth += 1,
+            "fi" | "done" | "esac" if command_position => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        command_position = word.ends_with(';')
+            || matches!(keyword, "&&" | "||" | "|" | "then" | "do" | "else" | "{");
+    }
+    depth
+}
+
+/// Locates the history file of the user's shell.
+fn history_file() -> Option<PathBuf> {
+    if let Ok(file) = std::env::var("HISTFILE") {
+        let path = PathBuf::from(file);
+        if path.is_file() {
+            return Some(path);
+        }
+    }
+    let home = BaseDirs::new()?.home_dir().to_path_buf();
+    let shell = std::env::var("SHELL").unwrap_or_default();
+    let mut candidates = vec![
+        home.join(".bash_history"),
+        home.join(".zsh_history"),
+        home.join(".local/share/fish/fish_history"),
+    ];
+    if shell.ends_with("zsh") {
+        candidates.swap(0, 1);
+    } else if shell.ends_with("fish") {
+        candidates.swap(0, 2);
+    }
+    candidates.into_iter().find(|p| p.is_file())
+}
+
+/// Extracts commands from bash, zsh (extended format) or fish history.
+fn parse_history(content: &str) -> Vec<String> {
+    let mut commands: Vec<String> = Vec::new();
+    let mut continued = false;
+    for line in content.lines() {
+        if continued && let Some(last) = commands.last_mut() {
+            last.push('\n');
+            last.push_str(line);
+            continued = line.ends_with('\\');
+            continue;
+        }
+        // Bash timestamps and fish metadata lines
+        if line.starts_with('#') || line.starts_with("  when:") || line.starts_with("  paths:") {
+            continue;
+        }
+        let command = if let Some(rest) = line.strip_prefix("- cmd: ") {
+            rest
+        } else if line.starts_with(": ")
+            && let Some((_, rest)) = line.split_once(';')
+        {
+            rest
+        } else {
+            line
+        };
+        let command = command.trim();
+        if command.is_empty() || command.starts_with("compass ") {
+            continue;
+        }
+        continued = command.ends_with('\\');
+        commands.push(command.to_string());
+    }
+    commands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_script_groups_by_blank_lines() {
+        let script = "#!/bin/bash\nset -euo pipefail\n\n# Install deps\n# Needs network\napt-get update\napt-get install -y curl\n\nif true; then\n  echo a\n\n  echo b\nfi\n";
+        let runbook = from_script(script, Path::new("setup.sh"));
+        let (steps, _) = crate::core::parser::parse_readme(&runbook);
+
+        assert_eq!(steps.len(), 3);
+        assert_eq!(steps[1].title, "1. Install deps");
+        assert_eq!(steps[1].description.trim(), "Needs network");
+        assert!(steps[1].code_blocks[0].content.contains("apt-get install"));
+        assert!(steps[2].code_blocks[0].content.contains("echo b\nfi"));
+        assert!(!runbook.contains("pipefail"));
+    }
+
+    #[test]
+    fn test_parse_history_formats() {
+        let history = ": 1700000000:0;git status\n#1700000001\nls -la\n- cmd: cargo build\n  when: 1700000002\n";
+        assert_eq!(
+            parse_history(history),
+            vec!["git status", "ls -la", "cargo build"]
+        );
+    }
+}
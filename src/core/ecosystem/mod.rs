@@ -12,7 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod bundle;
+pub mod demo;
 pub mod discovery;
 pub mod hooks;
 pub mod hub;
+pub mod importer;
 pub mod rpc;
@@ -12,12 +12,19 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::core::analysis::edits::FileEdit;
 use crate::core::executor::Executor;
-use crate::core::models::{Step, StepStatus};
+use crate::core::executor::engine::builder::CommandBuilder;
+use crate::core::executor::engine::context::ContextSnapshot;
+use crate::core::infrastructure::config::GlobalConfig;
+use crate::core::manifest::{PlaceholderSpec, RunbookManifest};
+use crate::core::models::{ContextMode, Step, StepStatus};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::sync::Mutex;
 
@@ -46,15 +53,281 @@ struct RpcError {
 struct HeadlessState {
     steps: Vec<Step>,
     executor: Executor,
+    /// The initial directory and environment, used by isolated steps.
+    baseline: ContextSnapshot,
+    /// Largest runbook `load_runbook` downloads, in bytes.
+    max_download_bytes: u64,
+    /// Header depth of the steps (`--step-level`), for loaded runbooks too.
+    step_level: Option<u8>,
+    /// Default value and format of placeholders, from the frontmatter.
+    specs: BTreeMap<String, PlaceholderSpec>,
+}
+
+/// Settings of the headless server, besides the runbook.
+pub struct HeadlessOptions {
+    /// Run steps in a Docker sandbox.
+    pub sandbox: bool,
+    /// Docker image of the sandbox.
+    pub image: String,
+    /// Frontmatter of the runbook.
+    pub manifest: Option<RunbookManifest>,
+    /// Header depth of the steps, overriding the frontmatter.
+    pub step_level: Option<u8>,
+    /// Whether the runbook was fetched from a URL.
+    pub remote: bool,
+}
+
+/// Maximum size of the output returned in an `execute_step` response.
+/// The full output is still streamed through `log` notifications.
+const MAX_RESULT_OUTPUT: usize = 1024 * 1024;
+
+/// Parameters of `execute_step`. Everything but `index` overrides the
+/// global CLI settings for this call only.
+#[derive(Deserialize, Debug)]
+struct ExecuteParams {
+    index: usize,
+    sandbox: Option<bool>,
+    image: Option<String>,
+    cwd: Option<PathBuf>,
+    #[serde(default)]
+    env: HashMap<String, String>,
+    /// Values of the step placeholders.
+    #[serde(default)]
+    placeholders: HashMap<String, String>,
+    /// Timeout in seconds (default: the step's `compass:timeout`).
+    timeout: Option<u64>,
+    /// Confirms the step for the organization policies asking for a
+    /// confirmation (it fails otherwise).
+    #[serde(default)]
+    confirm_policy: bool,
+}
+
+/// Parameters of `load_runbook`: exactly one of `content` or `url`.
+#[derive(Deserialize, Debug)]
+struct LoadParams {
+    /// Raw Markdown, e.g. an unsaved editor buffer.
+    content: Option<String>,
+    /// A remote runbook to download instead.
+    url: Option<String>,
+    /// Directory the steps run from (default: the current one).
+    cwd: Option<PathBuf>,
+}
+
+/// Result of `execute_step`.
+#[derive(Serialize, Debug)]
+struct ExecuteResult {
+    status: StepStatus,
+    output: String,
+    duration_ms: u128,
+    exit_code: Option<u32>,
+    /// Whether `output` was cut to `MAX_RESULT_OUTPUT` bytes.
+    truncated: bool,
+}
+
+/// Runs all code blocks of a step with the per-call overrides applied.
+///
+/// Context changes made by the step (`cd`, `export`) are kept for later calls,
+/// except for the values that were overridden.
+fn execute_step(state: &mut HeadlessState, params: &ExecuteParams) -> ExecuteResult {
+    let isolated = state.steps[params.index].context_mode == ContextMode::Isolated;
+    let mut context = state.executor.context.clone();
+    if isolated {
+        context.restore(state.baseline.clone());
+    }
+    if let Some(sandbox) = params.sandbox {
+        context.sandbox_enabled = sandbox;
+    }
+    if let Some(image) = &params.image {
+        context.docker_image = image.clone();
+    }
+    if let Some(cwd) = &params.cwd {
+        context.current_dir = cwd.clone();
+    }
+    context.env_vars.extend(params.env.clone());
+    context.timeout = params
+        .timeout
+        .map(Duration::from_secs)
+        .or_else(|| state.steps[params.index].timeout());
+
+    let mut executor = Executor {
+        context,
+        input: None,
+        cancel: None,
+        scope: state.steps[params.index].scope.clone(),
+        policy_confirmed: false,
+    };
+    let mut final_status = StepStatus::Success;
+    let (tx, rx) = std::sync::mpsc::channel::<String>();
+
+    // Spawn a thread to stream logs as JSON-RPC notifications
+    let logger_handle = std::thread::spawn(move || {
+        let mut collected = String::new();
+        while let Ok(msg) = rx.recv() {
+            collected.push_str(&msg);
+            // Send "log" notification
+            let note = RpcRequest {
+                jsonrpc: "2.0".to_string(),
+                method: "log".to_string(),
+                params: Some(serde_json::json!({ "output": msg })),
+                id: None,
+            };
+            if let Ok(json) = serde_json::to_string(&note) {
+                println!("{}", json);
+            }
+        }
+        collected
+    });
+
+    let started = Instant::now();
+    let missing_env = executor
+        .context
+        .missing_env(&state.steps[params.index].required_env);
+    if !missing_env.is_empty() {
+        let _ = tx.send(format!(
+            "Missing environment variables: {} (pass them in `env`).\n",
+            missing_env.join(", ")
+        ));
+        final_status = StepStatus::Failed;
+    }
+    // Placeholders not given take the default of the frontmatter
+    let mut placeholders = params.placeholders.clone();
+    for name in CommandBuilder::get_required_placeholders(&state.steps[params.index]) {
+        if let Some(default) = state.specs.get(&name).and_then(|s| s.default.clone()) {
+            placeholders.entry(name).or_insert(default);
+        }
+    }
+    for (name, value) in &placeholders {
+        let format = state.specs.get(name).map(|s| s.validate(value));
+        if let Err(problem) = state.steps[params.index]
+            .check_choice(name, value)
+            .and(format.unwrap_or(Ok(())))
+        {
+            let _ = tx.send(format!("Invalid value for {name}: {problem}.\n"));
+            final_status = StepStatus::Failed;
+        }
+    }
+    for block in &state.steps[params.index].code_blocks {
+        if final_status != StepStatus::Success {
+            break;
+        }
+        let status = if let Some(path) = &block.write_to {
+            let edit = FileEdit {
+                path: path.clone(),
+                append: false,
+                content: CommandBuilder::fill(block, &placeholders),
+            };
+            executor.write_file(&edit, &tx)
+        } else {
+            executor.policy_confirmed = params.confirm_policy;
+            executor.execute_streamed(
+                &CommandBuilder::fill(block, &placeholders),
+                block.language.as_deref(),
+                true, // Headless assumes intention to run
+                &tx,
+            )
+        };
+        if status != StepStatus::Success {
+            final_status = status;
+            break;
+        }
+    }
+    let duration_ms = started.elapsed().as_millis();
+
+    // Close channel to stop logger, then wait for the full output
+    drop(tx);
+    let full_output = logger_handle.join().unwrap_or_default();
+
+    // Persist context mutations of shared steps, restoring the overridden values
+    let shared = &mut state.executor.context;
+    if !isolated {
+        if params.cwd.is_none() {
+            shared.current_dir = executor.context.current_dir;
+        }
+        for (key, value) in executor.context.env_vars {
+            if !params.env.contains_key(&key) {
+                shared.env_vars.insert(key, value);
+            }
+        }
+    }
+
+    let step = &mut state.steps[params.index];
+    step.status = final_status;
+    if !full_output.is_empty() {
+        step.output = full_output;
+    }
+    step.record_run(
+        u64::try_from(duration_ms).ok(),
+        executor.context.last_exit_code,
+    );
+
+    let truncated = step.output.len() > MAX_RESULT_OUTPUT;
+    let mut output = step.output.clone();
+    if truncated {
+        let mut cut = MAX_RESULT_OUTPUT;
+        while !output.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        output.truncate(cut);
+    }
+
+    ExecuteResult {
+        status: final_status,
+        output,
+        duration_ms,
+        exit_code: executor.context.last_exit_code,
+        truncated,
+    }
+}
+
+/// Replaces the session steps with a runbook pushed by the client, and starts
+/// over from the initial context (or `cwd`). Hooks of the new runbook are not run.
+async fn load_runbook(state: &mut HeadlessState, params: LoadParams) -> Result<(), String> {
+    let (content, remote) = match (params.content, params.url) {
+        (Some(content), None) => (content, false),
+        (None, Some(url)) => {
+            let max_bytes = state.max_download_bytes;
+            let (content, _) = tokio::task::spawn_blocking(move || {
+                let content = crate::core::fetcher::fetch_remote_content(&url, max_bytes)?;
+                let content = crate::core::documents::to_markdown(&url, content)?;
+                crate::core::fetcher::includes::expand_includes(&content, &url, max_bytes)
+            })
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| format!("{e:#}"))?;
+            (content, true)
+        }
+        _ => return Err("Invalid params: expected exactly one of content or url".to_string()),
+    };
+
+    let (steps, manifest) = crate::core::parser::parse_readme_at(&content, state.step_level);
+    state.specs = manifest.map(|m| m.placeholders).unwrap_or_default();
+    state.executor.context.restore(state.baseline.clone());
+    state.executor.context.remote = remote;
+    if let Some(cwd) = params.cwd {
+        state.executor.context.current_dir = cwd;
+        state.baseline = state.executor.context.snapshot();
+    }
+    crate::core::executor::checker::warm_cache(&steps);
+    state.steps = steps;
+    tracing::info!(steps = state.steps.len(), "Runbook loaded over RPC");
+    Ok(())
 }
 
 pub async fn start_headless_server(
     steps: Vec<Step>,
     path: PathBuf,
-    sandbox: bool,
-    image: String,
+    options: HeadlessOptions,
+    settings: &GlobalConfig,
 ) -> anyhow::Result<()> {
+    let HeadlessOptions {
+        sandbox,
+        image,
+        manifest,
+        step_level,
+        remote,
+    } = options;
     let mut executor = Executor::new();
+    settings.apply_shell(&mut executor.context);
     // Default CWD to the parent of the README file
     executor.context.current_dir = if path.is_file() {
         path.parent().unwrap_or(&path).to_path_buf()
@@ -63,8 +336,18 @@ pub async fn start_headless_server(
     };
     executor.context.sandbox_enabled = sandbox;
     executor.context.docker_image = image;
+    executor.context.remote = remote;
 
-    let state = Arc::new(Mutex::new(HeadlessState { steps, executor }));
+    crate::core::executor::checker::warm_cache(&steps);
+    let baseline = executor.context.snapshot();
+    let state = Arc::new(Mutex::new(HeadlessState {
+        steps,
+        executor,
+        baseline,
+        max_download_bytes: settings.max_download_bytes(),
+        step_level,
+        specs: manifest.map(|m| m.placeholders).unwrap_or_default(),
+    }));
 
     let stdin = tokio::io::stdin();
     let mut reader = BufReader::new(stdin);
@@ -85,12 +368,14 @@ pub async fn start_headless_server(
         let req: RpcRequest = match serde_json::from_str(req_str) {
             Ok(r) => r,
             Err(e) => {
+                tracing::warn!("Invalid RPC request: {e}");
                 send_error(None, -32700, &format!("Parse error: {}", e)).await;
                 continue;
             }
         };
 
         let state_clone = state.clone();
+        tracing::debug!(method = %req.method, id = ?req.id, "RPC request");
 
         // Process request
         match req.method.as_str() {
@@ -99,83 +384,49 @@ pub async fn start_headless_server(
                 send_response(req.id, serde_json::to_value(&state.steps)?).await;
             }
             "execute_step" => {
-                if let Some(params) = req.params {
-                    let index = params
-                        .get("index")
-                        .and_then(|v| v.as_u64())
-                        .map(|v| v as usize);
-                    if let Some(idx) = index {
-                        let mut state = state_clone.lock().await; // Lock for duration of execution
-                        if idx < state.steps.len() {
-                            let mut final_status = StepStatus::Success;
-                            let mut full_output = String::new();
-                            let (tx, rx): (
-                                std::sync::mpsc::Sender<String>,
-                                std::sync::mpsc::Receiver<String>,
-                            ) = std::sync::mpsc::channel();
-
-                            // Spawn a thread to stream logs as JSON-RPC notifications
-                            let logger_handle = std::thread::spawn(move || {
-                                let mut collected = String::new();
-                                while let Ok(msg) = rx.recv() {
-                                    collected.push_str(&msg);
-                                    // Send "log" notification
-                                    let note = RpcRequest {
-                                        jsonrpc: "2.0".to_string(),
-                                        method: "log".to_string(),
-                                        params: Some(serde_json::json!({ "output": msg })),
-                                        id: None,
-                                    };
-                                    if let Ok(json) = serde_json::to_string(&note) {
-                                        println!("{}", json);
-                                    }
-                                }
-                                collected
-                            });
-
-                            // Clone needed blocks to avoid borrowing conflict with state
-                            let code_blocks = state.steps[idx].code_blocks.clone();
-
-                            for block in code_blocks {
-                                let status = state.executor.execute_streamed(
-                                    &block.content,
-                                    block.language.as_deref(),
-                                    true, // Headless assumes intention to run
-                                    &tx,
-                                );
-                                if status != StepStatus::Success {
-                                    final_status = status;
-                                    break;
-                                }
-                            }
-
-                            // Close channel to stop logger
-                            drop(tx);
-
-                            // Wait for logger and get valid full output
-                            if let Ok(collected_output) = logger_handle.join() {
-                                full_output = collected_output;
-                            }
-
-                            state.steps[idx].status = final_status;
-                            if !full_output.is_empty() {
-                                state.steps[idx].output = full_output.clone();
-                            }
-
-                            send_response(
-                                req.id,
-                                serde_json::json!({
-                                   "status": final_status,
-                                   "output": state.steps[idx].output
-                                }),
-                            )
-                            .await;
-                        } else {
-                            send_error(req.id, -32602, "Invalid params: index out of bounds").await;
-                        }
-                    } else {
+                let params: ExecuteParams = match req.params.map(serde_json::from_value).transpose()
+                {
+                    Ok(Some(p)) => p,
+                    Ok(None) => {
                         send_error(req.id, -32602, "Invalid params: missing index").await;
+                        continue;
+                    }
+                    Err(e) => {
+                        send_error(req.id, -32602, &format!("Invalid params: {}", e)).await;
+                        continue;
+                    }
+                };
+
+                let mut state = state_clone.lock().await; // Lock for duration of execution
+                if params.index >= state.steps.len() {
+                    send_error(req.id, -32602, "Invalid params: index out of bounds").await;
+                    continue;
+                }
+
+                let result = execute_step(&mut state, &params);
+                crate::core::executor::binaries::forget_missing();
+                send_response(req.id, serde_json::to_value(result)?).await;
+            }
+            "load_runbook" => {
+                let params: LoadParams = match req.params.map(serde_json::from_value).transpose() {
+                    Ok(Some(p)) => p,
+                    Ok(None) => {
+                        send_error(req.id, -32602, "Invalid params: missing content or url").await;
+                        continue;
+                    }
+                    Err(e) => {
+                        send_error(req.id, -32602, &format!("Invalid params: {}", e)).await;
+                        continue;
+                    }
+                };
+
+                let mut state = state_clone.lock().await;
+                match load_runbook(&mut state, params).await {
+                    Ok(()) => send_response(req.id, serde_json::to_value(&state.steps)?).await,
+                    Err(e) if e.starts_with("Invalid params") => {
+                        send_error(req.id, -32602, &e).await;
                     }
+                    Err(e) => send_error(req.id, -32603, &e).await,
                 }
             }
             _ => {
@@ -219,3 +470,131 @@ async fn send_error(id: Option<u64>, code: i32, message: &str) {
         let _ = stdout.flush().await;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_load_runbook() {
+        let executor = Executor::new();
+        let mut state = HeadlessState {
+            steps: Vec::new(),
+            baseline: executor.context.snapshot(),
+            executor,
+            max_download_bytes: 0,
+            step_level: None,
+            specs: BTreeMap::new(),
+        };
+        let params = |value: Value| serde_json::from_value::<LoadParams>(value).unwrap();
+        state
+            .executor
+            .context
+            .env_vars
+            .insert("LEFTOVER".to_string(), "1".to_string());
+
+        let loaded = load_runbook(
+            &mut state,
+            params(serde_json::json!({ "content": "# One\n```bash\na\n```\n# Two\n```bash\nb\n```\n", "cwd": "/srv/app" })),
+        )
+        .await;
+        assert_eq!(loaded, Ok(()));
+        assert_eq!(state.steps.len(), 2);
+        assert_eq!(
+            state.executor.context.current_dir,
+            PathBuf::from("/srv/app")
+        );
+        assert!(!state.executor.context.env_vars.contains_key("LEFTOVER"));
+
+        assert!(!state.executor.context.remote);
+
+        // Parsed like the CLI does: frontmatter and `--step-level`
+        state.step_level = Some(2);
+        let loaded = load_runbook(
+            &mut state,
+            params(serde_json::json!({ "content": "---\nplaceholders:\n  ENV:\n    default: dev\n---\n# Deploy\n## Build\n### Details\n```bash\nmake <ENV>\n```\n" })),
+        )
+        .await;
+        assert_eq!(loaded, Ok(()));
+        let titles: Vec<&str> = state.steps.iter().map(|s| s.title.trim()).collect();
+        assert_eq!(titles, vec!["Build"]);
+        assert_eq!(
+            state.specs.get("ENV").and_then(|s| s.default.as_deref()),
+            Some("dev")
+        );
+
+        let error = load_runbook(&mut state, params(serde_json::json!({}))).await;
+        assert!(error.unwrap_err().contains("exactly one of content or url"));
+        assert_eq!(state.steps.len(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_step_exit_code() {
+        let (steps, _) = crate::core::parser::parse_readme("# Fail\n```bash\nexit 3\n```\n");
+        let dir = std::env::temp_dir().join("compass_rpc_exit_code");
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut executor = Executor::new();
+        executor.context.current_dir = dir.clone();
+        let mut state = HeadlessState {
+            steps,
+            baseline: executor.context.snapshot(),
+            executor,
+            max_download_bytes: 0,
+            step_level: None,
+            specs: BTreeMap::new(),
+        };
+
+        let result = execute_step(
+            &mut state,
+            &serde_json::from_value(serde_json::json!({ "index": 0 })).unwrap(),
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(result.status, StepStatus::Failed);
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["exit_code"], 3);
+        assert_eq!(
+            state.steps[0].history.last().and_then(|run| run.exit_code),
+            Some(3)
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_step_overrides() {
+        let (steps, _) = crate::core::parser::parse_readme(
+            "# Greet\n```bash\necho \"$GREETING\"\n```\n# Wait\n```bash\nsleep 5\n```\n",
+        );
+        let dir = std::env::temp_dir().join("compass_rpc_execute");
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut executor = Executor::new();
+        executor.context.current_dir = dir.clone();
+        let mut state = HeadlessState {
+            steps,
+            baseline: executor.context.snapshot(),
+            executor,
+            max_download_bytes: 0,
+            step_level: None,
+            specs: BTreeMap::new(),
+        };
+        let params = |value: Value| serde_json::from_value::<ExecuteParams>(value).unwrap();
+
+        let result = execute_step(
+            &mut state,
+            &params(serde_json::json!({ "index": 0, "env": { "GREETING": "hello" } })),
+        );
+        assert_eq!(result.status, StepStatus::Success);
+        assert_eq!(result.exit_code, Some(0));
+        assert!(result.output.contains("hello"), "{}", result.output);
+        // The overrides only apply to this call
+        assert!(!state.executor.context.env_vars.contains_key("GREETING"));
+
+        let result = execute_step(
+            &mut state,
+            &params(serde_json::json!({ "index": 1, "timeout": 1 })),
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(result.status, StepStatus::TimedOut);
+        assert!(result.duration_ms < 5000);
+    }
+}
@@ -0,0 +1,102 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Cached lookups of binaries in `PATH`.
+//!
+//! `which` walks every `PATH` entry (and every `PATHEXT` extension on
+//! Windows), and the execution path checks the same binaries before each run.
+//! Results are kept for `TTL` and dropped as soon as `PATH` changes. Misses are
+//! also forgotten after a step runs, since the step may have installed them.
+
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// How long a lookup result stays valid.
+const TTL: Duration = Duration::from_secs(60);
+
+#[derive(Default)]
+struct Cache {
+    /// The `PATH` the entries were looked up with.
+    path: Option<OsString>,
+    /// Lookup results and when they were made.
+    entries: HashMap<String, (Option<PathBuf>, Instant)>,
+}
+
+fn cache() -> &'static Mutex<Cache> {
+    static CACHE: OnceLock<Mutex<Cache>> = OnceLock::new();
+    CACHE.get_or_init(Mutex::default)
+}
+
+/// Finds a binary in `PATH`, like `which`, reusing recent results.
+pub fn find(name: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH");
+    if let Ok(mut cache) = cache().lock() {
+        if cache.path != path {
+            cache.entries.clear();
+            cache.path = path;
+        }
+        if let Some((found, at)) = cache.entries.get(name)
+            && at.elapsed() < TTL
+        {
+            return found.clone();
+        }
+    }
+
+    let found = which::which(name).ok();
+    if let Ok(mut cache) = cache().lock() {
+        cache
+            .entries
+            .insert(name.to_string(), (found.clone(), Instant::now()));
+    }
+    found
+}
+
+/// Checks if a binary is in `PATH`.
+pub fn exists(name: &str) -> bool {
+    find(name).is_some()
+}
+
+/// Forgets the binaries that were not found, e.g. after a step that may have
+/// installed some of them.
+pub fn forget_missing() {
+    if let Ok(mut cache) = cache().lock() {
+        cache.entries.retain(|_, (found, _)| found.is_some());
+    }
+}
+
+/// Looks the binaries up in the background so the first run does not wait.
+pub fn warm(names: Vec<String>) {
+    std::thread::spawn(move || {
+        for name in names {
+            find(&name);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_caches_misses_until_forgotten() {
+        let name = "compass-test-binary-that-does-not-exist";
+        assert!(!exists(name));
+        assert!(cache().lock().unwrap().entries.contains_key(name));
+        forget_missing();
+        assert!(!cache().lock().unwrap().entries.contains_key(name));
+    }
+}
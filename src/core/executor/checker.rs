@@ -12,13 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::core::executor::binaries;
 use crate::core::executor::languages::get_language_handler;
+use crate::core::executor::tokenizer::extract_commands;
 use crate::core::models::Step;
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
 use std::collections::HashSet;
-use which::which;
+use std::process::Command;
 
 /// Result of the dependency check.
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct CheckResult {
     /// List of commands found in the system.
     pub present: Vec<String>,
@@ -26,16 +30,99 @@ pub struct CheckResult {
     pub missing: Vec<String>,
 }
 
+impl CheckResult {
+    /// Checks more commands, such as the binaries a runbook declares in its
+    /// frontmatter.
+    pub fn add(&mut self, commands: &[String]) {
+        for cmd in commands {
+            if self.present.contains(cmd) || self.missing.contains(cmd) {
+                continue;
+            }
+            if binaries::exists(cmd) {
+                self.present.push(cmd.clone());
+            } else {
+                self.missing.push(cmd.clone());
+            }
+        }
+        self.present.sort();
+        self.missing.sort();
+    }
+}
+
 /// Scans the provided steps for potential external dependencies (commands)
 /// and verifies if they exist on the host system.
 ///
-/// This uses a heuristic approach to identify commands in shell code blocks.
+/// Shell code blocks are tokenized to find the words in command position,
+/// including those inside command substitutions, loops and aliases.
 pub fn check_dependencies(steps: &[Step]) -> CheckResult {
+    let (present, missing) = collect_commands(steps)
+        .into_iter()
+        .partition(|cmd| binaries::exists(cmd));
+    CheckResult { present, missing }
+}
+
+/// Checks the dependencies of the steps inside a Docker image instead of the
+/// host system.
+///
+/// # Errors
+///
+/// Returns an error if the container cannot be started.
+pub fn check_dependencies_in_image(steps: &[Step], image: &str) -> Result<CheckResult> {
+    let commands = collect_commands(steps);
+    if commands.is_empty() {
+        return Ok(CheckResult {
+            present: Vec::new(),
+            missing: Vec::new(),
+        });
+    }
+
+    // Print the commands found in the image, one per line
+    let script = r#"for c in "$@"; do command -v "$c" >/dev/null 2>&1 && echo "$c"; done; true"#;
+    let output = Command::new("docker")
+        .args([
+            "run",
+            "--rm",
+            "--entrypoint",
+            "sh",
+            image,
+            "-c",
+            script,
+            "sh",
+        ])
+        .args(&commands)
+        .output()
+        .context("Failed to run docker")?;
+    if !output.status.success() {
+        bail!(
+            "Failed to check dependencies in '{image}': {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    let found: HashSet<&str> = std::str::from_utf8(&output.stdout)
+        .unwrap_or_default()
+        .lines()
+        .collect();
+    let (present, missing) = commands
+        .into_iter()
+        .partition(|cmd| found.contains(cmd.as_str()));
+    Ok(CheckResult { present, missing })
+}
+
+/// Looks up the commands used by the steps in the background, so that
+/// checks before each run hit the cache.
+pub fn warm_cache(steps: &[Step]) {
+    binaries::warm(collect_commands(steps));
+}
+
+/// Collects the external commands used by the steps, sorted and deduplicated.
+fn collect_commands(steps: &[Step]) -> Vec<String> {
     let mut candidates = HashSet::new();
     let builtins = get_builtins();
 
     for step in steps {
-        for block in &step.code_blocks {
+        // Blocks written to a file (`compass:write`) are not run
+        for block in step.code_blocks.iter().filter(|b| b.write_to.is_none()) {
             // Only check shell-like blocks or blocks with no language specified
             let is_shell = block.language.as_ref().is_none_or(|lang| {
                 ["bash", "sh", "shell", "zsh", "fish", "cmd", "powershell"].contains(&lang.as_str())
@@ -55,71 +142,55 @@ pub fn check_dependencies(steps: &[Step]) -> CheckResult {
                 continue;
             }
 
-            for line in block.content.lines() {
-                let line = line.trim();
-
-                // Skip comments and common non-command lines
-                if line.is_empty() || line.starts_with('#') {
-                    continue;
-                }
-
-                // Handle simple command chains (&&, ||, ;, |)
-                // This is a naive split to catch more commands
-                let parts: Vec<&str> = line.split(['&', '|', ';']).collect();
-
-                for part in parts {
-                    let part = part.trim();
-                    if part.is_empty() {
-                        continue;
-                    }
-
-                    // Extract first word
-                    if let Some(cmd) = part.split_whitespace().next() {
-                        // Skip variable assignments (VAR=val)
-                        if cmd.contains('=') {
-                            continue;
-                        }
-
-                        // Skip sudo (check the next word)
-                        let target_cmd = if cmd == "sudo" {
-                            part.split_whitespace().nth(1).unwrap_or("")
-                        } else {
-                            cmd
-                        };
-
-                        if target_cmd.is_empty() {
-                            continue;
-                        }
-
-                        // Filter out paths (./script), builtins, and variables ($VAR)
-                        if !target_cmd.contains('/')
-                            && !target_cmd.contains('\\')
-                            && !target_cmd.starts_with('$')
-                            && !builtins.contains(target_cmd)
-                        {
-                            candidates.insert(target_cmd.to_string());
-                        }
-                    }
+            for cmd in extract_commands(&block.content) {
+                // Filter out paths (./script), flags, builtins, and variables ($VAR)
+                if !cmd.contains('/')
+                    && !cmd.contains('\\')
+                    && !cmd.starts_with('$')
+                    && !cmd.starts_with('-')
+                    && !builtins.contains(cmd.as_str())
+                {
+                    candidates.insert(cmd);
                 }
             }
         }
     }
 
-    let mut missing = Vec::new();
-    let mut present = Vec::new();
+    let mut commands: Vec<String> = candidates.into_iter().collect();
+    commands.sort();
+    commands
+}
 
-    for cmd in candidates {
-        if which(&cmd).is_ok() {
-            present.push(cmd);
-        } else {
-            missing.push(cmd);
-        }
+/// Suggests how to install a missing command with the system package manager.
+pub fn install_hint(cmd: &str) -> String {
+    if matches!(cmd, "cargo" | "rustc" | "rustup") {
+        return "curl --proto '=https' -sSf https://sh.rustup.rs | sh".to_string();
     }
 
-    present.sort();
-    missing.sort();
-
-    CheckResult { present, missing }
+    let manager = if cfg!(target_os = "macos") {
+        "brew"
+    } else if cfg!(target_os = "windows") {
+        "winget"
+    } else {
+        ["apt-get", "dnf", "pacman", "apk", "zypper"]
+            .into_iter()
+            .find(|m| binaries::exists(m))
+            .unwrap_or("apt-get")
+    };
+    let package = match (cmd, manager) {
+        ("node" | "npm" | "npx", "brew" | "winget") => "node",
+        ("node" | "npm" | "npx", _) => "nodejs",
+        ("python" | "pip" | "pip3", _) => "python3",
+        ("go", "apt-get") => "golang",
+        ("rg", _) => "ripgrep",
+        _ => cmd,
+    };
+    match manager {
+        "pacman" => format!("sudo pacman -S {package}"),
+        "apk" => format!("sudo apk add {package}"),
+        "brew" | "winget" => format!("{manager} install {package}"),
+        _ => format!("sudo {manager} install {package}"),
+    }
 }
 
 fn get_builtins() -> HashSet<&'static str> {
@@ -129,11 +200,47 @@ fn get_builtins() -> HashSet<&'static str> {
         "exec", "exit", "return", "true", "false", "test", "[", "[[", "read", "wait", "bg", "fg",
         "jobs", "kill", "history", "pwd", "pushd", "popd", "dirs", "shift", "umask", "if", "then",
         "else", "elif", "fi", "for", "while", "until", "do", "done", "case", "esac", "function",
-        "select", "break",
-        "continue",
+        "select", "break", "continue", "command", "builtin", "local", "declare", "typeset",
+        "readonly", "trap", "shopt", "let", "type", "hash", "ulimit",
+        ":",
         // Common standard utilities (often expected to assume present, but debatable)
         // Leaving these out of builtins means we WILL check for them,
         // which is good: a fresh container might miss 'curl' or 'git'.
         // So we strictly keep to shell syntax keywords.
     ])
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_readme;
+
+    #[test]
+    fn test_check_result_json() {
+        let (steps, _) = parse_readme("# Deploy\n```bash\ncompass-missing-tool --version\n```\n");
+        let mut result = check_dependencies(&steps);
+        result.add(&["compass-missing-cli".to_string()]);
+
+        assert_eq!(
+            result.missing,
+            ["compass-missing-cli", "compass-missing-tool"]
+        );
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(
+            json["missing"],
+            serde_json::json!(["compass-missing-cli", "compass-missing-tool"])
+        );
+        assert!(json["present"].is_array());
+    }
+
+    #[test]
+    fn test_install_hint() {
+        assert_eq!(
+            install_hint("cargo"),
+            "curl --proto '=https' -sSf https://sh.rustup.rs | sh"
+        );
+        let hint = install_hint("rg");
+        assert!(hint.ends_with(" ripgrep"), "{hint}");
+        assert!(install_hint("jq").ends_with(" jq"));
+    }
+}
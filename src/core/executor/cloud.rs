@@ -0,0 +1,252 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Cloud CLIs (`aws`, `gcloud`, `az`): which account a step would act as,
+//! probed before it runs so that it does not run against the wrong one.
+
+use super::tokenizer::simple_commands;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// How long a probe may take. `aws sts get-caller-identity` goes over the
+/// network.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A cloud provider CLI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloudCli {
+    Aws,
+    Gcloud,
+    Azure,
+}
+
+impl CloudCli {
+    /// Name of the provider, for messages.
+    #[must_use]
+    pub fn provider(self) -> &'static str {
+        match self {
+            Self::Aws => "AWS",
+            Self::Gcloud => "Google Cloud",
+            Self::Azure => "Azure",
+        }
+    }
+}
+
+/// The first cloud CLI command of a snippet, with the account options it
+/// passes (`--profile`, `--project`, `--subscription`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CloudCall {
+    pub cli: CloudCli,
+    /// Options choosing the account, passed on to the probe.
+    pub options: Vec<String>,
+}
+
+/// Finds the first command of a snippet that runs a cloud CLI.
+#[must_use]
+pub fn find(script: &str) -> Option<CloudCall> {
+    simple_commands(script).into_iter().find_map(|words| {
+        let (cli, account_flags): (CloudCli, &[&str]) = match words[0].as_str() {
+            "aws" => (CloudCli::Aws, &["--profile"]),
+            "gcloud" => (CloudCli::Gcloud, &["--project", "--account"]),
+            "az" => (CloudCli::Azure, &["--subscription"]),
+            _ => return None,
+        };
+        let mut options = Vec::new();
+        let mut args = words[1..].iter();
+        while let Some(arg) = args.next() {
+            if let Some((name, _)) = arg.split_once('=')
+                && account_flags.contains(&name)
+            {
+                options.push(arg.clone());
+            } else if account_flags.contains(&arg.as_str())
+                && let Some(value) = args.next()
+            {
+                options.extend([arg.clone(), value.clone()]);
+            }
+        }
+        Some(CloudCall { cli, options })
+    })
+}
+
+impl CloudCall {
+    /// Asks the CLI which account and project it would use, in the
+    /// environment and directory the step runs with. Returns a description
+    /// of the account, or why none is usable.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message if the CLI is not installed, has no credentials or
+    /// does not answer within `PROBE_TIMEOUT`.
+    pub fn probe(&self, env: &HashMap<String, String>, dir: &Path) -> Result<String, String> {
+        let (program, args): (&str, &[&str]) = match self.cli {
+            CloudCli::Aws => ("aws", &["sts", "get-caller-identity", "--output", "json"]),
+            CloudCli::Gcloud => ("gcloud", &["config", "list", "--format=json"]),
+            CloudCli::Azure => ("az", &["account", "show", "--output", "json"]),
+        };
+        let mut command = Command::new(program);
+        command
+            .args(args)
+            .args(&self.options)
+            .envs(env)
+            .current_dir(dir);
+        let json = run(command, program)?;
+        let value: serde_json::Value = serde_json::from_str(&json)
+            .map_err(|e| format!("{program} gave an unexpected answer: {e}"))?;
+        self.describe(&value, env)
+    }
+
+    /// Describes the account in the answer of the probe.
+    fn describe(
+        &self,
+        value: &serde_json::Value,
+        env: &HashMap<String, String>,
+    ) -> Result<String, String> {
+        let field = |path: &[&str]| {
+            path.iter()
+                .try_fold(value, |value, key| value.get(key))
+                .and_then(serde_json::Value::as_str)
+                .filter(|s| !s.is_empty())
+                .map(ToString::to_string)
+        };
+        let option = |name: &str| {
+            let prefix = format!("{name}=");
+            self.options.iter().enumerate().find_map(|(i, o)| {
+                o.strip_prefix(&prefix)
+                    .map(ToString::to_string)
+                    .or_else(|| {
+                        (o == name)
+                            .then(|| self.options.get(i + 1).cloned())
+                            .flatten()
+                    })
+            })
+        };
+        match self.cli {
+            CloudCli::Aws => {
+                let account = field(&["Account"]).ok_or("no AWS account in the caller identity")?;
+                let profile = option("--profile")
+                    .or_else(|| env.get("AWS_PROFILE").cloned())
+                    .or_else(|| std::env::var("AWS_PROFILE").ok())
+                    .unwrap_or_else(|| "default".to_string());
+                let arn = field(&["Arn"]).unwrap_or_default();
+                Ok(format!(
+                    "AWS account {account} as {arn} (profile '{profile}')"
+                ))
+            }
+            CloudCli::Gcloud => {
+                let account = option("--account")
+                    .or_else(|| field(&["core", "account"]))
+                    .ok_or("no active gcloud account: run `gcloud auth login`")?;
+                let project = option("--project")
+                    .or_else(|| field(&["core", "project"]))
+                    .unwrap_or_else(|| "none".to_string());
+                Ok(format!("Google Cloud project '{project}' as {account}"))
+            }
+            CloudCli::Azure => {
+                let name = field(&["name"]).ok_or("no Azure subscription")?;
+                let id = field(&["id"]).unwrap_or_default();
+                let user = field(&["user", "name"]).unwrap_or_default();
+                Ok(format!("Azure subscription '{name}' ({id}) as {user}"))
+            }
+        }
+    }
+}
+
+/// Runs a probe, killing it after `PROBE_TIMEOUT`. Returns its standard
+/// output, or the first line of its errors if it failed. Also used to read
+/// secrets from the CLIs of the secret managers.
+pub fn run(mut command: Command, program: &str) -> Result<String, String> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("{program} could not be started: {e}"))?;
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if started.elapsed() < PROBE_TIMEOUT => {
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!(
+                    "{program} did not answer within {}s",
+                    PROBE_TIMEOUT.as_secs()
+                ));
+            }
+        }
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("{program} failed: {e}"))?;
+    if output.status.success() {
+        return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let reason = stderr
+        .lines()
+        .map(|line| line.trim().trim_end_matches('.'))
+        .find(|line| !line.is_empty())
+        .unwrap_or("no usable credentials");
+    Err(format!("{program} failed: {reason}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_find_cloud_call() {
+        let call = find("export X=1\naws --profile prod s3 ls\ngcloud projects list").unwrap();
+        assert_eq!(call.cli, CloudCli::Aws);
+        assert_eq!(call.options, ["--profile", "prod"]);
+        assert_eq!(
+            find("gcloud run deploy api --project=staging")
+                .unwrap()
+                .options,
+            ["--project=staging"]
+        );
+        assert!(find("echo aws").is_none());
+    }
+
+    #[test]
+    fn test_describe_accounts() {
+        let env = HashMap::new();
+        let aws = find("aws --profile prod s3 ls").unwrap();
+        assert_eq!(
+            aws.describe(
+                &json!({ "Account": "123456789012", "Arn": "arn:aws:iam::123456789012:user/ci" }),
+                &env
+            ),
+            Ok(
+                "AWS account 123456789012 as arn:aws:iam::123456789012:user/ci (profile 'prod')"
+                    .to_string()
+            )
+        );
+        let gcloud = find("gcloud run deploy --project=staging").unwrap();
+        assert_eq!(
+            gcloud.describe(
+                &json!({ "core": { "account": "me@example.com", "project": "dev" } }),
+                &env
+            ),
+            Ok("Google Cloud project 'staging' as me@example.com".to_string())
+        );
+        assert!(gcloud.describe(&json!({ "core": {} }), &env).is_err());
+    }
+}
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::core::executor::binaries;
 use crate::core::models::Condition;
 use std::env;
 use std::path::Path;
@@ -41,12 +42,28 @@ impl ConditionEvaluator for StandardEvaluator {
                 // Loose matching: "windows" == "windows", "macos" == "macos"
                 current_os.eq_ignore_ascii_case(os_name)
             }
+            Condition::Arch(arch) => arch_names(arch).contains(&std::env::consts::ARCH),
             Condition::EnvVarExists(var_name) => env::var(var_name).is_ok(),
             Condition::FileExists(path_str) => Path::new(path_str).exists(),
+            Condition::CommandExists(command) => binaries::exists(command),
+            Condition::Not(inner) => !self.evaluate(inner),
+            Condition::All(conditions) => conditions.iter().all(|c| self.evaluate(c)),
+            Condition::Any(conditions) => conditions.iter().any(|c| self.evaluate(c)),
         }
     }
 }
 
+/// Names an architecture goes by: the one of `std::env::consts::ARCH` first
+/// (`aarch64`), then those of `uname -m` and other tools (`arm64`).
+pub fn arch_names(arch: &str) -> Vec<&str> {
+    match arch.to_ascii_lowercase().as_str() {
+        "aarch64" | "arm64" => vec!["aarch64", "arm64"],
+        "x86_64" | "amd64" | "x64" => vec!["x86_64", "amd64"],
+        "x86" | "i386" | "i686" => vec!["x86", "i386", "i686"],
+        _ => vec![arch],
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -58,5 +75,18 @@ mod tests {
 
         assert!(evaluator.evaluate(&Condition::Os(current.to_string())));
         assert!(!evaluator.evaluate(&Condition::Os("non_existent_os".to_string())));
+        assert!(evaluator.evaluate(&Condition::Not(Box::new(Condition::Os(
+            "non_existent_os".to_string()
+        )))));
+        assert!(evaluator.evaluate(&Condition::Arch(std::env::consts::ARCH.to_string())));
+        assert_eq!(arch_names("ARM64"), vec!["aarch64", "arm64"]);
+        assert!(evaluator.evaluate(&Condition::Any(vec![
+            Condition::Os("non_existent_os".to_string()),
+            Condition::CommandExists("sh".to_string()),
+        ])));
+        assert!(!evaluator.evaluate(&Condition::All(vec![
+            Condition::Os(current.to_string()),
+            Condition::Os("non_existent_os".to_string()),
+        ])));
     }
 }
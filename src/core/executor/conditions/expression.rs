@@ -0,0 +1,205 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Condition expressions of `compass:if` and `compass:elif`: predicates
+//! (`os="linux"`) combined with `!`, `&&`, `||` and parentheses. `!` binds
+//! tighter than `&&`, which binds tighter than `||`.
+
+use crate::core::models::Condition;
+use anyhow::{Result, bail};
+
+/// Keys of the predicates.
+pub const KEYS: &[&str] = &[
+    "os",
+    "arch",
+    "env_var_exists",
+    "file_exists",
+    "command_exists",
+];
+
+/// Parses a condition expression, such as
+/// `os="linux" && !env_var_exists="CI"`.
+///
+/// # Errors
+///
+/// Returns an error if the expression is malformed or uses an unknown key.
+pub fn parse(text: &str) -> Result<Condition> {
+    let mut parser = ExpressionParser {
+        text,
+        rest: text.trim_start(),
+    };
+    let condition = parser.any()?;
+    if !parser.rest.is_empty() {
+        return parser.malformed();
+    }
+    Ok(condition)
+}
+
+struct ExpressionParser<'a> {
+    text: &'a str,
+    /// What is left to parse, without leading spaces.
+    rest: &'a str,
+}
+
+impl ExpressionParser<'_> {
+    /// Consumes `token` if the rest starts with it.
+    fn eat(&mut self, token: &str) -> bool {
+        match self.rest.strip_prefix(token) {
+            Some(rest) => {
+                self.rest = rest.trim_start();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// `a || b || ...`
+    fn any(&mut self) -> Result<Condition> {
+        let mut conditions = vec![self.all()?];
+        while self.eat("||") {
+            conditions.push(self.all()?);
+        }
+        Ok(combine(conditions, Condition::Any))
+    }
+
+    /// `a && b && ...`
+    fn all(&mut self) -> Result<Condition> {
+        let mut conditions = vec![self.unary()?];
+        while self.eat("&&") {
+            conditions.push(self.unary()?);
+        }
+        Ok(combine(conditions, Condition::All))
+    }
+
+    /// `!a`, `(a)` or a predicate.
+    fn unary(&mut self) -> Result<Condition> {
+        if self.eat("!") {
+            return Ok(Condition::Not(Box::new(self.unary()?)));
+        }
+        if self.eat("(") {
+            let condition = self.any()?;
+            if !self.eat(")") {
+                return self.malformed();
+            }
+            return Ok(condition);
+        }
+        self.predicate()
+    }
+
+    /// `key="value"`
+    fn predicate(&mut self) -> Result<Condition> {
+        let end = self
+            .rest
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(self.rest.len());
+        let key = &self.rest[..end];
+        let Some(quoted) = self.rest[end..].trim_start().strip_prefix('=') else {
+            return self.malformed();
+        };
+        let Some((value, rest)) = quoted
+            .trim_start()
+            .strip_prefix('"')
+            .and_then(|v| v.split_once('"'))
+            .filter(|(value, _)| !value.is_empty())
+        else {
+            return self.malformed();
+        };
+        let value = value.to_string();
+        let condition = match key {
+            "os" => Condition::Os(value),
+            "arch" => Condition::Arch(value),
+            "env_var_exists" => Condition::EnvVarExists(value),
+            "file_exists" => Condition::FileExists(value),
+            "command_exists" => Condition::CommandExists(value),
+            _ => bail!(
+                "Unknown condition key '{key}' (expected {})",
+                KEYS.join(", ")
+            ),
+        };
+        self.rest = rest.trim_start();
+        Ok(condition)
+    }
+
+    fn malformed<T>(&self) -> Result<T> {
+        bail!(
+            "Malformed condition '{}': expected key=\"value\", combined with !, && and ||",
+            self.text
+        )
+    }
+}
+
+/// A single condition, or `combinator` of several.
+fn combine(
+    mut conditions: Vec<Condition>,
+    combinator: fn(Vec<Condition>) -> Condition,
+) -> Condition {
+    if conditions.len() == 1 {
+        conditions.remove(0)
+    } else {
+        combinator(conditions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_expressions() {
+        let linux = || Condition::Os("linux".to_string());
+        let ci = || Condition::EnvVarExists("CI".to_string());
+        assert_eq!(parse(r#"os="linux""#).unwrap(), linux());
+        assert_eq!(
+            parse(r#"os="linux" && env_var_exists="CI""#).unwrap(),
+            Condition::All(vec![linux(), ci()])
+        );
+        // `&&` binds tighter than `||`
+        let expression = parse(r#"!command_exists="docker" || arch="aarch64" && (os = "linux" || env_var_exists="CI")"#).unwrap();
+        assert_eq!(
+            expression,
+            Condition::Any(vec![
+                Condition::Not(Box::new(Condition::CommandExists("docker".to_string()))),
+                Condition::All(vec![
+                    Condition::Arch("aarch64".to_string()),
+                    Condition::Any(vec![linux(), ci()]),
+                ]),
+            ])
+        );
+        assert_eq!(
+            expression.to_string(),
+            "!command_exists=docker || arch=aarch64 && (os=linux || env_var_exists=CI)"
+        );
+
+        for malformed in [
+            r#"os="linux" &&"#,
+            r#"(os="linux""#,
+            "os=linux",
+            r#"os="""#,
+            "",
+        ] {
+            assert!(
+                parse(malformed)
+                    .unwrap_err()
+                    .to_string()
+                    .starts_with("Malformed")
+            );
+        }
+        assert!(
+            parse(r#"shell="zsh""#)
+                .unwrap_err()
+                .to_string()
+                .starts_with("Unknown condition key")
+        );
+    }
+}
@@ -12,11 +12,28 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::core::models::Step;
+use super::builtin::BuiltinHandler;
+use super::context::ExecutionContext;
+use crate::core::analysis::edits::FileEdit;
+use crate::core::models::{CodeBlock, Step};
+use crate::core::parser::{PLACEHOLDERS, placeholder_name};
 use std::collections::HashMap;
 
 pub struct CommandBuilder;
 
+/// A piece of a code line, as shown in a command preview.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Segment<'a> {
+    /// Text copied as is from the code block.
+    Text(&'a str),
+    /// A placeholder of the block, with its value once filled.
+    Placeholder {
+        name: &'a str,
+        token: &'a str,
+        value: Option<&'a str>,
+    },
+}
+
 impl CommandBuilder {
     /// Extracts unique keys of all placeholders required by the step.
     pub fn get_required_placeholders(step: &Step) -> Vec<String> {
@@ -32,19 +49,144 @@ impl CommandBuilder {
     }
 
     /// Builds the final command string by substituting variables.
+    ///
+    /// Blocks written to a file (`compass:write`) are left out, see [`Self::build_writes`].
     pub fn build_command(step: &Step, variables: &HashMap<String, String>) -> String {
         let mut content = String::new();
-        for block in &step.code_blocks {
-            let mut block_content = block.content.clone();
-            for (key, val) in variables {
-                let target_angle = format!("<{key}>");
-                let target_brace = format!("{{{{{key}}}}}");
-                block_content = block_content.replace(&target_angle, val);
-                block_content = block_content.replace(&target_brace, val);
-            }
-            content.push_str(&block_content);
+        for block in step.code_blocks.iter().filter(|b| b.write_to.is_none()) {
+            content.push_str(&Self::fill(block, variables));
             content.push('\n');
         }
         content
     }
+
+    /// Builds the files written by the `compass:write` blocks of the step,
+    /// with the variables substituted.
+    pub fn build_writes(step: &Step, variables: &HashMap<String, String>) -> Vec<FileEdit> {
+        step.code_blocks
+            .iter()
+            .filter_map(|block| {
+                Some(FileEdit {
+                    path: block.write_to.clone()?,
+                    append: false,
+                    content: Self::fill(block, variables),
+                })
+            })
+            .collect()
+    }
+
+    /// Builds what running the step would do, for a dry run: the files its
+    /// `compass:write` blocks write, then the command with the lines Compass
+    /// handles itself (`cd`, `export`) annotated. `context` is updated by
+    /// those lines, as a real run would.
+    pub fn dry_run(
+        step: &Step,
+        variables: &HashMap<String, String>,
+        context: &mut ExecutionContext,
+    ) -> String {
+        let mut preview = String::new();
+        for edit in Self::build_writes(step, variables) {
+            preview.push_str(&format!("# writes {}:\n", edit.path));
+            preview.push_str(&edit.content);
+            if !edit.content.ends_with('\n') {
+                preview.push('\n');
+            }
+            preview.push_str(&format!("# end of {}\n", edit.path));
+        }
+        let command = Self::build_command(step, variables);
+        preview.push_str(&BuiltinHandler::annotate(command.trim_end(), context));
+        preview
+    }
+
+    /// Replaces the `<KEY>` and `{{KEY}}` placeholders of a block (in any of
+    /// their forms, like `<SECRET:KEY>`) that have a value.
+    pub fn fill(block: &CodeBlock, variables: &HashMap<String, String>) -> String {
+        let re = &*PLACEHOLDERS;
+        re.replace_all(&block.content, |caps: &regex::Captures| {
+            placeholder_name(caps)
+                .and_then(|name| variables.get(name.as_str()))
+                .map_or_else(|| caps[0].to_string(), Clone::clone)
+        })
+        .into_owned()
+    }
+
+    /// Splits a line into literal text and the placeholders of `placeholders`,
+    /// so a preview can show the values that will be substituted.
+    pub fn split_placeholders<'a>(
+        line: &'a str,
+        placeholders: &[String],
+        variables: &'a HashMap<String, String>,
+    ) -> Vec<Segment<'a>> {
+        let re = &*PLACEHOLDERS;
+        let mut segments = Vec::new();
+        let mut last = 0;
+        for caps in re.captures_iter(line) {
+            let (Some(token), Some(name)) = (caps.get(0), placeholder_name(&caps)) else {
+                continue;
+            };
+            if !placeholders.iter().any(|p| p == name.as_str()) {
+                continue;
+            }
+            if token.start() > last {
+                segments.push(Segment::Text(&line[last..token.start()]));
+            }
+            segments.push(Segment::Placeholder {
+                name: name.as_str(),
+                token: token.as_str(),
+                value: variables.get(name.as_str()).map(String::as_str),
+            });
+            last = token.end();
+        }
+        if last < line.len() {
+            segments.push(Segment::Text(&line[last..]));
+        }
+        segments
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_choice_placeholders() {
+        let mut variables = HashMap::new();
+        variables.insert("ENV".to_string(), "prod".to_string());
+        let block = CodeBlock {
+            content: "deploy <ENV:dev|prod> && echo {{ENV}} <PORT>".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            CommandBuilder::fill(&block, &variables),
+            "deploy prod && echo prod <PORT>"
+        );
+    }
+
+    #[test]
+    fn test_split_placeholders() {
+        let mut variables = HashMap::new();
+        variables.insert("HOST".to_string(), "example.com".to_string());
+        let placeholders = vec!["HOST".to_string(), "PORT".to_string()];
+
+        let segments =
+            CommandBuilder::split_placeholders("curl <HOST>:{{PORT}}/", &placeholders, &variables);
+        assert_eq!(
+            segments,
+            vec![
+                Segment::Text("curl "),
+                Segment::Placeholder {
+                    name: "HOST",
+                    token: "<HOST>",
+                    value: Some("example.com")
+                },
+                Segment::Text(":"),
+                Segment::Placeholder {
+                    name: "PORT",
+                    token: "{{PORT}}",
+                    value: None
+                },
+                Segment::Text("/"),
+            ]
+        );
+    }
 }
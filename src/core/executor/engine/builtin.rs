@@ -13,9 +13,14 @@
 // limitations under the License.
 
 use super::context::ExecutionContext;
+use crate::core::executor::languages::strategies::shell::Shell;
 use std::fmt::Write;
 
 /// Handles "built-in" commands that affect the `ExecutionContext` directly.
+///
+/// Understands POSIX shells (`cd`, `export`), PowerShell (`Set-Location`,
+/// `$env:FOO = "bar"`) and cmd (`cd /d`, `set FOO=bar`). When fish is the
+/// preferred shell, `set -gx FOO bar` is understood too.
 pub struct BuiltinHandler;
 
 impl BuiltinHandler {
@@ -25,52 +30,26 @@ impl BuiltinHandler {
     pub fn process(cmd_content: &str, context: &mut ExecutionContext) -> (String, String) {
         let mut remaining_lines = Vec::new();
         let mut simulated_output = String::new();
+        let fish = context.shell == Shell::Fish;
 
         for line in cmd_content.lines() {
             let trimmed = line.trim();
 
-            // Detect 'cd'
-            if let Some(rest) = trimmed.strip_prefix("cd ") {
-                let path_str = rest.trim().trim_matches(|c| c == '\"' || c == '\'');
-                let new_path = context.current_dir.join(path_str);
-                if new_path.exists() && new_path.is_dir() {
-                    let mut final_path = new_path.canonicalize().unwrap_or(new_path);
-
-                    // On Windows, canonicalize() adds the \\?\ prefix.
-                    // This can break some tools, so we strip it.
-                    if cfg!(target_os = "windows") {
-                        let path_str = final_path.to_string_lossy();
-                        if let Some(stripped) = path_str.strip_prefix(r"\\?\") {
-                            final_path = std::path::PathBuf::from(stripped);
-                        }
-                    }
-
-                    context.current_dir = final_path;
-                    let _ = writeln!(
-                        simulated_output,
-                        "cd: {} (Handled by Compass)",
-                        context.current_dir.display()
-                    );
-                }
+            // Detect directory changes ('cd', 'Set-Location', ...)
+            if let Some(rest) = Self::strip_cd(trimmed) {
+                Self::change_dir(rest, context, &mut simulated_output);
                 continue;
             }
 
-            // Detect 'export'
-            if let Some(rest) = trimmed.strip_prefix("export ") {
-                let assignment = rest.trim();
-                if let Some((key, val)) = assignment.split_once('=') {
-                    let val = val.trim_matches(|c| c == '\"' || c == '\'');
-                    context
-                        .env_vars
-                        .insert(key.trim().to_string(), val.to_string());
-
-                    let _ = writeln!(
-                        simulated_output,
-                        "export: {}={} (Handled by Compass)",
-                        key.trim(),
-                        val
-                    );
-                }
+            // Detect variable assignments ('export', '$env:', 'set')
+            let assignment = if fish {
+                Self::parse_fish_assignment(trimmed)
+            } else {
+                None
+            };
+            if let Some((key, val)) = assignment.or_else(|| Self::parse_assignment(trimmed)) {
+                let _ = writeln!(simulated_output, "export: {key}={val} (Handled by Compass)");
+                context.env_vars.insert(key, val);
                 continue;
             }
 
@@ -79,4 +58,184 @@ impl BuiltinHandler {
 
         (remaining_lines.join("\n"), simulated_output)
     }
+
+    /// Returns the content with each line Compass handles itself followed by
+    /// a comment saying what it does, without running anything. The context
+    /// is updated as [`Self::process`] would, so later lines resolve against it.
+    pub fn annotate(cmd_content: &str, context: &mut ExecutionContext) -> String {
+        let mut annotated = String::new();
+        for line in cmd_content.lines() {
+            let (remaining, output) = Self::process(line, context);
+            annotated.push_str(line);
+            if remaining.trim().is_empty() && !line.trim().is_empty() {
+                match output.trim() {
+                    "" => {
+                        let target = Self::strip_cd(line.trim()).unwrap_or_default().trim();
+                        let _ = write!(
+                            annotated,
+                            "  # cd: {target} not found, ignored (Handled by Compass)"
+                        );
+                    }
+                    note => {
+                        let _ = write!(annotated, "  # {note}");
+                    }
+                }
+            }
+            annotated.push('\n');
+        }
+        annotated
+    }
+
+    /// Returns the target of a directory change command, if `line` is one.
+    fn strip_cd(line: &str) -> Option<&str> {
+        let rest = ["cd ", "chdir ", "set-location ", "sl "]
+            .iter()
+            .find_map(|prefix| strip_prefix_ignore_case(line, prefix))?;
+
+        // cmd: 'cd /d D:\path', PowerShell: 'Set-Location -Path C:\path'
+        let rest = rest.trim();
+        let rest = strip_prefix_ignore_case(rest, "/d ")
+            .or_else(|| strip_prefix_ignore_case(rest, "-path "))
+            .or_else(|| strip_prefix_ignore_case(rest, "-literalpath "))
+            .unwrap_or(rest);
+        Some(rest)
+    }
+
+    /// Resolves `target` against the current directory and switches to it if it exists.
+    fn change_dir(target: &str, context: &mut ExecutionContext, output: &mut String) {
+        let path_str = target.trim().trim_matches(|c| c == '\"' || c == '\'');
+        let new_path = context.current_dir.join(path_str);
+        if new_path.exists() && new_path.is_dir() {
+            let mut final_path = new_path.canonicalize().unwrap_or(new_path);
+
+            // On Windows, canonicalize() adds the \\?\ prefix.
+            // This can break some tools, so we strip it.
+            if cfg!(target_os = "windows") {
+                let path_str = final_path.to_string_lossy();
+                if let Some(stripped) = path_str.strip_prefix(r"\\?\") {
+                    final_path = std::path::PathBuf::from(stripped);
+                }
+            }
+
+            context.current_dir = final_path;
+            let _ = writeln!(
+                output,
+                "cd: {} (Handled by Compass)",
+                context.current_dir.display()
+            );
+        }
+    }
+
+    /// Parses an environment variable assignment into a `(key, value)` pair.
+    fn parse_assignment(line: &str) -> Option<(String, String)> {
+        let assignment = if let Some(rest) = line.strip_prefix("export ") {
+            rest
+        } else if let Some(rest) = strip_prefix_ignore_case(line, "$env:") {
+            // PowerShell: $env:FOO = "bar"
+            rest
+        } else if let Some(rest) = strip_prefix_ignore_case(line, "set ") {
+            // cmd: set FOO=bar / set "FOO=bar" (but not 'set -e' in POSIX shells)
+            let rest = rest.trim();
+            if rest.starts_with('-') || rest.starts_with('/') {
+                return None;
+            }
+            rest.strip_prefix('"')
+                .and_then(|r| r.strip_suffix('"'))
+                .unwrap_or(rest)
+        } else {
+            return None;
+        };
+
+        let (key, val) = assignment.trim().split_once('=')?;
+        let key = key.trim();
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            return None;
+        }
+        let val = val.trim().trim_matches(|c| c == '\"' || c == '\'');
+        Some((key.to_string(), val.to_string()))
+    }
+
+    /// Parses a fish `set -x`/`set -gx`/`set --export` into a `(key, value)` pair.
+    fn parse_fish_assignment(line: &str) -> Option<(String, String)> {
+        let mut words = line.strip_prefix("set ")?.split_whitespace().peekable();
+        let mut exported = false;
+        while let Some(flag) = words.next_if(|w| w.starts_with('-')) {
+            exported |= flag == "--export" || (!flag.starts_with("--") && flag.contains('x'));
+        }
+        if !exported {
+            return None;
+        }
+        let key = words.next()?;
+        let val = words
+            .map(|w| w.trim_matches(|c| c == '\"' || c == '\''))
+            .collect::<Vec<_>>()
+            .join(" ");
+        Some((key.to_string(), val))
+    }
+}
+
+/// Like `str::strip_prefix`, but ASCII case-insensitive.
+fn strip_prefix_ignore_case<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    s.get(..prefix.len())
+        .filter(|head| head.eq_ignore_ascii_case(prefix))
+        .map(|_| &s[prefix.len()..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_assignments() {
+        let mut context = ExecutionContext::new();
+        let script = "export A=1\n$env:B = \"two\"\nset C=3\nSET \"D=4\"\nset -e\necho done";
+        let (remaining, _) = BuiltinHandler::process(script, &mut context);
+
+        assert_eq!(context.env_vars.get("A").map(String::as_str), Some("1"));
+        assert_eq!(context.env_vars.get("B").map(String::as_str), Some("two"));
+        assert_eq!(context.env_vars.get("C").map(String::as_str), Some("3"));
+        assert_eq!(context.env_vars.get("D").map(String::as_str), Some("4"));
+        assert_eq!(remaining, "set -e\necho done");
+    }
+
+    #[test]
+    fn test_fish_assignments() {
+        let mut context = ExecutionContext::new();
+        context.shell = Shell::Fish;
+        let script = "set -gx A 1\nset --export B \"two\"\nset -l C 3";
+        let (remaining, _) = BuiltinHandler::process(script, &mut context);
+
+        assert_eq!(context.env_vars.get("A").map(String::as_str), Some("1"));
+        assert_eq!(context.env_vars.get("B").map(String::as_str), Some("two"));
+        assert_eq!(remaining, "set -l C 3");
+    }
+
+    #[test]
+    fn test_annotate() {
+        let mut context = ExecutionContext::new();
+        context.current_dir = std::env::temp_dir();
+        let annotated =
+            BuiltinHandler::annotate("export A=1\ncd missing-dir\necho $A", &mut context);
+
+        let lines: Vec<&str> = annotated.lines().collect();
+        assert_eq!(lines[0], "export A=1  # export: A=1 (Handled by Compass)");
+        assert!(lines[1].starts_with("cd missing-dir  # cd: missing-dir not found"));
+        assert_eq!(lines[2], "echo $A");
+        assert_eq!(context.env_vars.get("A").map(String::as_str), Some("1"));
+    }
+
+    #[test]
+    fn test_directory_changes() {
+        let base = std::env::temp_dir();
+        for cmd in ["cd", "Set-Location -Path", "cd /d", "sl"] {
+            let mut context = ExecutionContext::new();
+            context.current_dir = base.clone();
+            let target = base.join(".");
+            let script = format!("{cmd} \"{}\"", target.display());
+            let (remaining, output) = BuiltinHandler::process(&script, &mut context);
+
+            assert!(remaining.is_empty(), "{cmd} was not intercepted");
+            assert!(output.starts_with("cd: "), "{cmd} did not change directory");
+        }
+    }
 }
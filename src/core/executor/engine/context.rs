@@ -12,8 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashMap;
+use crate::core::executor::languages::strategies::shell::Shell;
+use crate::core::executor::security::redact::{MASK, Redactor};
+use crate::core::models::{ContextDiff, VarChange, is_secret_placeholder};
+use std::collections::{BTreeSet, HashMap};
 use std::path::PathBuf;
+use std::time::Duration;
 
 /// Holds the mutable state of the execution environment.
 #[derive(Debug, Clone, Default)]
@@ -22,6 +26,21 @@ pub struct ExecutionContext {
     pub env_vars: HashMap<String, String>,
     pub sandbox_enabled: bool,
     pub docker_image: String,
+    /// Shell running untagged code blocks on the host.
+    pub shell: Shell,
+    /// Load the user's shell profile before running shell blocks.
+    pub source_profile: bool,
+    /// Run commands with pipes instead of a PTY, so stdout and stderr are
+    /// told apart (commands then get no input).
+    pub separate_streams: bool,
+    /// Maximum run time of a single command before it is killed.
+    pub timeout: Option<Duration>,
+    /// Exit code of the last process run in this context (like `$?`).
+    pub last_exit_code: Option<u32>,
+    /// Values of secret placeholders, masked in the output of commands.
+    pub secrets: Vec<String>,
+    /// Whether the runbook was fetched from a URL (told to the policies).
+    pub remote: bool,
 }
 
 impl ExecutionContext {
@@ -34,6 +53,149 @@ impl ExecutionContext {
             env_vars: HashMap::new(),
             sandbox_enabled: false,
             docker_image: "ubuntu:latest".to_string(),
+            shell: Shell::System,
+            source_profile: false,
+            separate_streams: false,
+            timeout: None,
+            last_exit_code: None,
+            secrets: Vec::new(),
+            remote: false,
         }
     }
+
+    /// Masks `value` in the output of the next commands.
+    pub fn add_secret(&mut self, value: &str) {
+        if !value.is_empty() && !self.secrets.iter().any(|s| s == value) {
+            self.secrets.push(value.to_string());
+        }
+    }
+
+    /// Exports variables read from an env file, masking the values of
+    /// secret-looking names (`API_TOKEN`, ...) in the output.
+    pub fn import_env(&mut self, vars: &[(String, String)]) {
+        for (name, value) in vars {
+            if is_secret_placeholder(name) {
+                self.add_secret(value);
+            }
+            self.env_vars.insert(name.clone(), value.clone());
+        }
+    }
+}
+
+/// The parts of an [`ExecutionContext`] that steps mutate through `cd`/`export`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextSnapshot {
+    pub current_dir: PathBuf,
+    pub env_vars: HashMap<String, String>,
+}
+
+impl ContextSnapshot {
+    /// What changed between this snapshot and `after`, variables sorted by
+    /// name. Values of secret-looking variables are masked, and so are the
+    /// values of `secrets` within the others.
+    #[must_use]
+    pub fn diff(&self, after: &Self, secrets: &[String]) -> ContextDiff {
+        let redactor = Redactor::new(secrets.iter().cloned());
+        let names: BTreeSet<&String> = self.env_vars.keys().chain(after.env_vars.keys()).collect();
+        let variables = names
+            .into_iter()
+            .filter(|name| self.env_vars.get(*name) != after.env_vars.get(*name))
+            .map(|name| {
+                let shown = |value: Option<&String>| {
+                    value.map(|v| {
+                        if is_secret_placeholder(name) {
+                            MASK.to_string()
+                        } else {
+                            redactor.redact(v)
+                        }
+                    })
+                };
+                VarChange {
+                    name: name.clone(),
+                    before: shown(self.env_vars.get(name)),
+                    after: shown(after.env_vars.get(name)),
+                }
+            })
+            .collect();
+        let moved = self.current_dir != after.current_dir;
+        ContextDiff {
+            previous_dir: moved.then(|| self.current_dir.display().to_string()),
+            current_dir: moved.then(|| after.current_dir.display().to_string()),
+            variables,
+        }
+    }
+}
+
+impl ExecutionContext {
+    /// Captures the working directory and environment.
+    #[must_use]
+    pub fn snapshot(&self) -> ContextSnapshot {
+        ContextSnapshot {
+            current_dir: self.current_dir.clone(),
+            env_vars: self.env_vars.clone(),
+        }
+    }
+
+    /// Returns the variables of `names` set neither in this context nor, outside
+    /// the sandbox (which does not inherit it), in the host environment.
+    #[must_use]
+    pub fn missing_env(&self, names: &[String]) -> Vec<String> {
+        names
+            .iter()
+            .filter(|name| {
+                !self.env_vars.contains_key(*name)
+                    && (self.sandbox_enabled || std::env::var_os(name).is_none())
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Restores the working directory and environment from a snapshot.
+    pub fn restore(&mut self, snapshot: ContextSnapshot) {
+        self.current_dir = snapshot.current_dir;
+        self.env_vars = snapshot.env_vars;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_diff() {
+        let before = ContextSnapshot {
+            current_dir: PathBuf::from("/srv"),
+            env_vars: HashMap::from([
+                ("KEPT".to_string(), "1".to_string()),
+                ("CHANGED".to_string(), "old".to_string()),
+                ("REMOVED".to_string(), "x".to_string()),
+            ]),
+        };
+        let mut after = before.clone();
+        after.current_dir = PathBuf::from("/srv/app");
+        after
+            .env_vars
+            .insert("CHANGED".to_string(), "new".to_string());
+        after.env_vars.remove("REMOVED");
+        after
+            .env_vars
+            .insert("API_TOKEN".to_string(), "hunter2".to_string());
+        after
+            .env_vars
+            .insert("DB_URL".to_string(), "postgres://app:pw42@db".to_string());
+
+        let diff = before.diff(&after, &["pw42".to_string()]);
+        assert_eq!(diff.current_dir.as_deref(), Some("/srv/app"));
+        let lines: Vec<String> = diff.variables.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            lines,
+            [
+                "+ API_TOKEN=••••••",
+                "~ CHANGED=new (was old)",
+                "+ DB_URL=postgres://app:••••••@db",
+                "- REMOVED"
+            ]
+        );
+        assert!(before.diff(&before, &[]).is_empty());
+    }
 }
@@ -12,18 +12,39 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::core::analysis::edits::{self, FileEdit};
 use crate::core::executor::engine::builtin::BuiltinHandler;
 use crate::core::executor::engine::context::ExecutionContext;
 use crate::core::executor::engine::session::ShellSession;
-use crate::core::executor::languages::get_language_handler;
+use crate::core::executor::languages::{get_language_handler, get_shell_aware_handler};
+use crate::core::executor::security::policy::{self, Decision, PolicyInput};
 use crate::core::executor::security::safety::SafetyShield;
 use crate::core::executor::security::validator::DependencyValidator;
-use crate::core::models::StepStatus;
-use std::sync::mpsc::Sender;
+use crate::core::executor::transform;
+use crate::core::models::{StepScope, StepStatus};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{Receiver, Sender};
 
 /// The main entry point for the execution engine.
 pub struct Executor {
     pub context: ExecutionContext,
+    /// Keystrokes forwarded to the next command run in a PTY.
+    pub input: Option<Receiver<Vec<u8>>>,
+    /// Kills the next command run when a message is received.
+    pub cancel: Option<Receiver<()>>,
+    /// Working directory, environment and verification of the next command
+    /// run, from a `compass` block.
+    pub scope: StepScope,
+    /// Whether the user confirmed the next command run, and its verification,
+    /// for the organization policies asking for a confirmation.
+    pub policy_confirmed: bool,
+}
+
+impl Default for Executor {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Executor {
@@ -32,6 +53,72 @@ impl Executor {
     pub fn new() -> Self {
         Self {
             context: ExecutionContext::new(),
+            input: None,
+            cancel: None,
+            scope: StepScope::default(),
+            policy_confirmed: false,
+        }
+    }
+
+    /// Writes the file of a `compass:write` block, relative to the working
+    /// directory it cannot leave, creating its parent directories. In the
+    /// sandbox, the container writes it.
+    pub fn write_file(&mut self, edit: &FileEdit, tx: &Sender<String>) -> StepStatus {
+        let path = match edits::confine(&edit.path, &self.context.current_dir) {
+            Ok(path) => path,
+            Err(e) => {
+                tracing::warn!(path = edit.path, "Refused to write file: {e}");
+                let _ = tx.send(format!("🛑 Refused to write {}: {e}.\n", edit.path));
+                return StepStatus::Failed;
+            }
+        };
+        let result = if self.context.sandbox_enabled {
+            self.write_in_sandbox(edit)
+        } else {
+            path.parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|()| std::fs::write(&path, &edit.content))
+        };
+        match result {
+            Ok(()) => {
+                let _ = tx.send(format!(
+                    "write: {} ({} lines, Handled by Compass)\n",
+                    path.display(),
+                    edit.content.lines().count()
+                ));
+                StepStatus::Success
+            }
+            Err(e) => {
+                tracing::warn!(path = %path.display(), "Failed to write file: {e}");
+                let _ = tx.send(format!("Failed to write {}: {e}\n", path.display()));
+                StepStatus::Failed
+            }
+        }
+    }
+
+    /// Writes the file of a `compass:write` block from the sandbox container,
+    /// which mounts the working directory like for the commands it runs.
+    fn write_in_sandbox(&self, edit: &FileEdit) -> std::io::Result<()> {
+        let mut child = Command::new("docker")
+            .args(["run", "--rm", "-i", "-v"])
+            .arg(format!("{}:/workspace", self.context.current_dir.display()))
+            .args(["-w", "/workspace", &self.context.docker_image])
+            .args(["sh", "-c", r#"mkdir -p "$(dirname "$1")" && cat > "$1""#])
+            .args(["sh", &edit.path])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(edit.content.as_bytes())?;
+        }
+        let output = child.wait_with_output()?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(std::io::Error::other(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ))
         }
     }
 
@@ -43,7 +130,20 @@ impl Executor {
         bypass_safety: bool,
         tx: &Sender<String>,
     ) -> StepStatus {
-        // 1. Dependency Validation
+        tracing::debug!(?language, bypass_safety, "Executing code block");
+        self.context.last_exit_code = None;
+        let policy_confirmed = std::mem::take(&mut self.policy_confirmed);
+
+        // 0. Plugins rewriting the command (checked like the original below)
+        let cmd_content = &transform::transform(cmd_content, language);
+
+        // 1. Organization policies. Neither a denial nor a confirmation is
+        // lifted by bypass_safety: only the user confirms, in the UI.
+        if !policy_allows(cmd_content, language, &self.context, policy_confirmed, tx) {
+            return StepStatus::Failed;
+        }
+
+        // 2. Dependency Validation
         // This acts as a final enforcement. The UI should have already prompted the user,
         // so if we are here with bypass_safety=false and it fails, it means we are in headless mode
         // or something went wrong. We return Failed.
@@ -56,7 +156,17 @@ impl Executor {
                 );
 
             if is_shell {
-                if let Err(e) = DependencyValidator::validate(cmd_content) {
+                // The preferred shell itself must be installed to run untagged blocks
+                let shell_check = if language.is_none() && !self.context.sandbox_enabled {
+                    let handler = get_shell_aware_handler(None, self.context.shell, false);
+                    DependencyValidator::validate_binary(handler.get_required_command())
+                } else {
+                    Ok(())
+                };
+                if let Err(e) =
+                    shell_check.and_then(|()| DependencyValidator::validate(cmd_content))
+                {
+                    tracing::warn!("Dependency validation failed: {e}");
                     let _ = tx.send(format!("{e}\n"));
                     return StepStatus::Failed;
                 }
@@ -64,18 +174,20 @@ impl Executor {
                 let handler = get_language_handler(language);
                 let required_cmd = handler.get_required_command();
                 if let Err(e) = DependencyValidator::validate_binary(required_cmd) {
+                    tracing::warn!("Dependency validation failed: {e}");
                     let _ = tx.send(format!("{e}\n"));
                     return StepStatus::Failed;
                 }
             }
         }
 
-        // 2. Safety Shield
+        // 3. Safety Shield
         if !bypass_safety {
             let handler = get_language_handler(language);
             let patterns = handler.get_dangerous_patterns();
 
             if let Some(pattern) = SafetyShield::check(cmd_content, patterns) {
+                tracing::warn!(pattern, "Execution blocked by safety shield");
                 let _ = tx.send(format!(
                     "Safety alert: Dangerous pattern detected ('{pattern}'). Execution blocked.\n"
                 ));
@@ -85,7 +197,7 @@ impl Executor {
             }
         }
 
-        // 3. Handle side-effects (builtins)
+        // 4. Handle side-effects (builtins)
         let (cleaned_content, simulated_output) =
             BuiltinHandler::process(cmd_content, &mut self.context);
 
@@ -94,11 +206,78 @@ impl Executor {
         }
 
         if cleaned_content.trim().is_empty() {
+            self.context.last_exit_code = Some(0);
             return StepStatus::Success;
         }
 
-        // 4. Run via ShellSession
-        let session = ShellSession::new(self.context.clone());
-        session.run(&cleaned_content, language, tx)
+        // 5. Run via ShellSession, in the scope of the step
+        let scope = std::mem::take(&mut self.scope);
+        let mut context = self.context.clone();
+        if let Some(cwd) = &scope.cwd {
+            context.current_dir = edits::resolve(cwd, &context.current_dir);
+            if !context.current_dir.is_dir() {
+                let _ = tx.send(format!(
+                    "Directory not found: {}\n",
+                    context.current_dir.display()
+                ));
+                return StepStatus::Failed;
+            }
+        }
+        context.env_vars.extend(scope.env);
+        let session = ShellSession::new(context.clone())
+            .with_input(self.input.take())
+            .with_cancel(self.cancel.take());
+        let mut outcome = session.run(&cleaned_content, language, tx);
+
+        if outcome.status == StepStatus::Success
+            && let Some(verify) = &scope.verify
+        {
+            let _ = tx.send(format!("verify: {verify}\n"));
+            if !policy_allows(verify, None, &context, policy_confirmed, tx) {
+                self.context.last_exit_code = outcome.exit_code;
+                return StepStatus::Failed;
+            }
+            outcome = ShellSession::new(context).run(verify, None, tx);
+            if outcome.status != StepStatus::Success {
+                let _ = tx.send("Verification failed.\n".to_string());
+            }
+        }
+        self.context.last_exit_code = outcome.exit_code;
+        outcome.status
+    }
+}
+
+/// Whether the organization policies let `command` run in `context`,
+/// `confirmed` when the user gave the confirmation they may ask for. Tells why
+/// not on `tx`.
+fn policy_allows(
+    command: &str,
+    language: Option<&str>,
+    context: &ExecutionContext,
+    confirmed: bool,
+    tx: &Sender<String>,
+) -> bool {
+    let decision = policy::evaluate(&PolicyInput {
+        command,
+        language,
+        cwd: &context.current_dir,
+        remote: context.remote,
+        sandbox: context.sandbox_enabled,
+    });
+    if decision.permits(confirmed) {
+        return true;
+    }
+    match decision {
+        Decision::Confirm(reason) => {
+            let _ = tx.send(format!(
+                "Policy requires a confirmation that was not given: {reason}.\n"
+            ));
+        }
+        Decision::Deny(reason) => {
+            tracing::warn!(reason, "Execution denied by policy");
+            let _ = tx.send(format!("🛑 Denied by policy: {reason}.\n"));
+        }
+        Decision::Allow => {}
     }
+    false
 }
@@ -12,10 +12,16 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::core::analysis::edits::FileEdit;
 use crate::core::executor::Executor;
+use crate::core::executor::engine::context::ContextSnapshot;
+use crate::core::executor::security::redact::Redactor;
+use crate::core::models::{ContextMode, ExecutionRecord, StepStatus};
 use crate::ui::state::ExecutionMessage;
+use std::collections::HashMap;
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::thread;
+use std::time::{Duration, Instant};
 
 /// Manages background execution of commands.
 pub struct ExecutionManager {
@@ -26,67 +32,206 @@ pub struct ExecutionManager {
     rx: Receiver<ExecutionMessage>,
     /// Sender to be cloned for background threads.
     tx: Sender<ExecutionMessage>,
+    /// The initial directory and environment, used by isolated steps.
+    baseline: ContextSnapshot,
+    /// Channels reaching the running steps, by step index.
+    running: HashMap<usize, RunningStep>,
+}
+
+/// What a background run executes, and how.
+#[derive(Debug, Default)]
+pub struct BackgroundRun {
+    /// Code run once the files are written.
+    pub content: String,
+    /// Files written first, from `compass:write` blocks.
+    pub writes: Vec<FileEdit>,
+    pub language: Option<String>,
+    /// Whether the user confirmed the safety and dependency alerts.
+    pub bypass_safety: bool,
+    pub mode: ContextMode,
+    /// Kills the command once elapsed.
+    pub timeout: Option<Duration>,
+    /// Whether the content runs in Docker, for this run only.
+    pub sandbox: bool,
+}
+
+/// Channels to a step running in the background.
+struct RunningStep {
+    /// Keystrokes for the PTY.
+    input: Sender<Vec<u8>>,
+    /// Kills the command.
+    cancel: Sender<()>,
+    /// When the run started.
+    started: Instant,
+}
+
+impl Default for ExecutionManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ExecutionManager {
     pub fn new() -> Self {
         let (tx, rx) = mpsc::channel();
+        let executor = Executor::new();
+        let baseline = executor.context.snapshot();
         Self {
-            executor: Executor::new(),
+            executor,
             rx,
             tx,
+            baseline,
+            running: HashMap::new(),
         }
     }
 
-    /// Spawns a background thread to execute the given content.
-    pub fn execute_background(
-        &self,
-        index: usize,
-        content: String,
-        language: Option<String>,
-        bypass_safety: bool,
-    ) {
+    /// Makes the current directory and environment the starting point of
+    /// isolated steps (e.g. after loading a saved context).
+    pub fn reset_baseline(&mut self) {
+        self.baseline = self.executor.context.snapshot();
+    }
+
+    /// Spawns a background thread to write the files of `run`, then execute
+    /// its content, killing it after its timeout. The scope set on `executor`
+    /// applies to this run only.
+    pub fn execute_background(&mut self, index: usize, run: BackgroundRun) {
+        let BackgroundRun {
+            content,
+            writes,
+            language,
+            bypass_safety,
+            mode,
+            timeout,
+            sandbox,
+        } = run;
         let tx = self.tx.clone();
-        let context = self.executor.context.clone();
+        let (input_tx, input_rx) = mpsc::channel();
+        let (cancel_tx, cancel_rx) = mpsc::channel();
+        self.running.insert(
+            index,
+            RunningStep {
+                input: input_tx,
+                cancel: cancel_tx,
+                started: Instant::now(),
+            },
+        );
+        let mut context = self.executor.context.clone();
+        context.timeout = timeout;
+        context.sandbox_enabled = sandbox;
+        let scope = std::mem::take(&mut self.executor.scope);
+        let policy_confirmed = std::mem::take(&mut self.executor.policy_confirmed);
+        // Isolated runs start fresh and report the shared context back unchanged
+        let shared = (mode == ContextMode::Isolated).then(|| {
+            let shared = context.snapshot();
+            context.restore(self.baseline.clone());
+            shared
+        });
 
         thread::spawn(move || {
-            let mut local_executor = Executor { context };
+            let started = chrono::Local::now();
+            let mut local_executor = Executor {
+                context,
+                input: Some(input_rx),
+                cancel: Some(cancel_rx),
+                scope,
+                policy_confirmed,
+            };
             let (stream_tx, stream_rx) = mpsc::channel::<String>();
 
             let tx_for_streaming = tx.clone();
+            let mut redactor = Redactor::new(local_executor.context.secrets.clone());
 
-            // Spawn a sub-thread to forward streaming output
-            thread::spawn(move || {
+            // Spawn a sub-thread to forward streaming output, secrets masked.
+            // Sends fail once the TUI has quit: stop forwarding then.
+            let forwarder = thread::spawn(move || {
+                let send = |partial: String| {
+                    partial.is_empty()
+                        || tx_for_streaming
+                            .send(ExecutionMessage::OutputPartial(index, partial))
+                            .is_ok()
+                };
                 while let Ok(partial) = stream_rx.recv() {
-                    tx_for_streaming
-                        .send(ExecutionMessage::OutputPartial(index, partial))
-                        .unwrap();
+                    if !send(redactor.push(&partial)) {
+                        return;
+                    }
                 }
+                send(redactor.finish());
             });
 
-            // Execute the command
-            let status = local_executor.execute_streamed(
-                &content,
-                language.as_deref(),
-                bypass_safety,
-                &stream_tx,
-            );
+            // Write the files, then execute the command
+            let mut status = StepStatus::Success;
+            for edit in &writes {
+                status = local_executor.write_file(edit, &stream_tx);
+                if status != StepStatus::Success {
+                    break;
+                }
+            }
+            if status == StepStatus::Success && !content.trim().is_empty() {
+                status = local_executor.execute_streamed(
+                    &content,
+                    language.as_deref(),
+                    bypass_safety,
+                    &stream_tx,
+                );
+            }
+
+            tracing::info!(step = index, ?status, "Step execution finished");
+            // The output held back by the redactor comes before the end
+            drop(stream_tx);
+            let _ = forwarder.join();
 
-            // Send finish event
-            tx.send(ExecutionMessage::Finished(
+            // Send finish event, unless the TUI has quit
+            let final_context = shared.unwrap_or_else(|| local_executor.context.snapshot());
+            let _ = tx.send(ExecutionMessage::Finished(
                 index,
                 status,
-                local_executor.context.current_dir,
-                local_executor.context.env_vars,
-            ))
-            .unwrap();
+                final_context.current_dir,
+                final_context.env_vars,
+                ExecutionRecord::new(
+                    started,
+                    chrono::Local::now(),
+                    local_executor.context.last_exit_code,
+                ),
+            ));
         });
     }
 
+    /// Forwards keystrokes to a running step. Returns `false` once the step
+    /// has finished.
+    pub fn send_input(&mut self, index: usize, bytes: Vec<u8>) -> bool {
+        let sent = self
+            .running
+            .get(&index)
+            .is_some_and(|step| step.input.send(bytes).is_ok());
+        if !sent {
+            self.running.remove(&index);
+        }
+        sent
+    }
+
+    /// Kills the command of a running step, which then finishes as
+    /// `StepStatus::Cancelled`. Returns `false` if the step was not running.
+    pub fn cancel(&mut self, index: usize) -> bool {
+        self.running
+            .get(&index)
+            .is_some_and(|step| step.cancel.send(()).is_ok())
+    }
+
+    /// How long each running step has been running, by step index.
+    pub fn elapsed(&self) -> HashMap<usize, Duration> {
+        self.running
+            .iter()
+            .map(|(&index, step)| (index, step.started.elapsed()))
+            .collect()
+    }
+
     /// Polls for any new execution messages.
-    pub fn poll_messages(&self) -> Vec<ExecutionMessage> {
+    pub fn poll_messages(&mut self) -> Vec<ExecutionMessage> {
         let mut messages = Vec::new();
         while let Ok(msg) = self.rx.try_recv() {
+            if let ExecutionMessage::Finished(index, ..) = &msg {
+                self.running.remove(index);
+            }
             messages.push(msg);
         }
         messages
@@ -21,4 +21,4 @@ pub mod session;
 
 pub use builder::CommandBuilder;
 pub use core::Executor;
-pub use manager::ExecutionManager;
+pub use manager::{BackgroundRun, ExecutionManager};
@@ -13,22 +13,233 @@
 // limitations under the License.
 
 use super::context::ExecutionContext;
-use crate::core::executor::languages::get_language_handler;
+use crate::core::executor::languages::get_shell_aware_handler;
+use crate::core::executor::languages::strategies::shell::Shell;
 use crate::core::models::StepStatus;
 use portable_pty::{CommandBuilder, PtySize, native_pty_system};
-use std::io::Read;
-use std::sync::mpsc::Sender;
+use std::io::Write;
+use std::io::{BufRead, BufReader, Read};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{Receiver, Sender};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Prefix of the output lines read from stderr when running without a PTY.
+///
+/// A PTY merges both streams, so only runs with pipes can tell them apart.
+pub const STDERR_TAG: &str = "[stderr] ";
+
+/// The result of running a command in a `ShellSession`.
+#[derive(Debug, Clone, Copy)]
+pub struct SessionOutcome {
+    pub status: StepStatus,
+    /// The process exit code, if the process was spawned and exited.
+    pub exit_code: Option<u32>,
+}
+
+impl SessionOutcome {
+    const fn failed() -> Self {
+        Self {
+            status: StepStatus::Failed,
+            exit_code: None,
+        }
+    }
+}
+
+/// Decodes a byte stream as UTF-8, carrying a multibyte character split across
+/// two reads over to the next one. Invalid bytes become U+FFFD.
+#[derive(Default)]
+struct Utf8Decoder {
+    /// The start of an incomplete character from the previous read.
+    pending: Vec<u8>,
+}
+
+impl Utf8Decoder {
+    /// Decodes a chunk, keeping a trailing incomplete character for later.
+    fn push(&mut self, bytes: &[u8]) -> String {
+        self.pending.extend_from_slice(bytes);
+        let mut text = String::new();
+        let mut rest = self.pending.as_slice();
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(valid) => {
+                    text.push_str(valid);
+                    rest = &[];
+                    break;
+                }
+                Err(e) => {
+                    let (valid, after) = rest.split_at(e.valid_up_to());
+                    text.push_str(std::str::from_utf8(valid).unwrap_or_default());
+                    match e.error_len() {
+                        Some(len) => {
+                            text.push(char::REPLACEMENT_CHARACTER);
+                            rest = &after[len..];
+                        }
+                        // Incomplete character at the end: wait for the next read
+                        None => {
+                            rest = after;
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        self.pending = rest.to_vec();
+        text
+    }
+
+    /// Returns what is left once the stream ended.
+    fn finish(self) -> String {
+        String::from_utf8_lossy(&self.pending).into_owned()
+    }
+}
+
+/// A command to spawn, either in a PTY or with pipes.
+struct CommandSpec {
+    program: String,
+    args: Vec<String>,
+    cwd: Option<PathBuf>,
+    env: Vec<(String, String)>,
+}
+
+impl CommandSpec {
+    fn new(program: impl AsRef<str>) -> Self {
+        Self {
+            program: program.as_ref().to_string(),
+            args: Vec::new(),
+            cwd: None,
+            env: Vec::new(),
+        }
+    }
+
+    fn arg(&mut self, arg: impl AsRef<str>) {
+        self.args.push(arg.as_ref().to_string());
+    }
+
+    fn args(&mut self, args: impl IntoIterator<Item = impl AsRef<str>>) {
+        for arg in args {
+            self.arg(arg);
+        }
+    }
+
+    fn env(&mut self, key: impl AsRef<str>, val: impl AsRef<str>) {
+        self.env
+            .push((key.as_ref().to_string(), val.as_ref().to_string()));
+    }
+
+    fn to_pty(&self) -> CommandBuilder {
+        let mut cmd = CommandBuilder::new(&self.program);
+        cmd.args(&self.args);
+        if let Some(cwd) = &self.cwd {
+            cmd.cwd(cwd);
+        }
+        for (key, val) in &self.env {
+            cmd.env(key, val);
+        }
+        cmd
+    }
+
+    fn to_piped(&self) -> Command {
+        let mut cmd = Command::new(&self.program);
+        // Without a PTY, `docker run -t` would fail: keep stdin only
+        cmd.args(
+            self.args
+                .iter()
+                .map(|arg| if arg == "-it" { "-i" } else { arg.as_str() }),
+        );
+        if let Some(cwd) = &self.cwd {
+            cmd.current_dir(cwd);
+        }
+        cmd.envs(self.env.iter().map(|(k, v)| (k, v)));
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        // In its own process group, killed with the processes it starts
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
+        cmd
+    }
+}
+
+/// Kills a command run with pipes along with the processes it started, which
+/// would otherwise keep running and hold its output open.
+fn kill_group(child: &mut Child) {
+    #[cfg(unix)]
+    {
+        let group = format!("-{}", child.id());
+        let _ = Command::new("kill").args(["-KILL", "--", &group]).status();
+    }
+    let _ = child.kill();
+}
+
+/// Kills a sandbox container. Killing the `docker run` client leaves the
+/// container running, so it is stopped by name.
+fn kill_container(name: &str) {
+    let _ = Command::new("docker")
+        .args(["kill", name])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
 
 /// Manages a PTY session for executing a shell command.
 pub struct ShellSession {
     context: ExecutionContext,
+    /// Keystrokes to write to the PTY while the command runs.
+    input: Option<Receiver<Vec<u8>>>,
+    /// Kills the command when a message is received.
+    cancel: Option<Receiver<()>>,
 }
 
 impl ShellSession {
     /// Creates a new `ShellSession` with the given context.
     #[must_use]
     pub const fn new(context: ExecutionContext) -> Self {
-        Self { context }
+        Self {
+            context,
+            input: None,
+            cancel: None,
+        }
+    }
+
+    /// Forwards the keystrokes received on `input` to the running command, so
+    /// it can answer prompts. Without a PTY, the command gets no input.
+    #[must_use]
+    pub fn with_input(mut self, input: Option<Receiver<Vec<u8>>>) -> Self {
+        self.input = input;
+        self
+    }
+
+    /// Kills the running command when a message is received on `cancel`; the
+    /// run then ends with `StepStatus::Cancelled`.
+    #[must_use]
+    pub fn with_cancel(mut self, cancel: Option<Receiver<()>>) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Why the running command must be killed: cancelled by the user, or
+    /// timed out. Returns the status and the message to print.
+    fn stop_reason(&self, started: Instant) -> Option<(StepStatus, String)> {
+        if self.cancel.as_ref().is_some_and(|c| c.try_recv().is_ok()) {
+            tracing::info!("Process cancelled, killing it");
+            return Some((
+                StepStatus::Cancelled,
+                "\nCancelled. Process killed.\n".to_string(),
+            ));
+        }
+        let timeout = self.context.timeout?;
+        (started.elapsed() >= timeout).then(|| {
+            tracing::warn!(?timeout, "Process timed out, killing it");
+            (
+                StepStatus::TimedOut,
+                format!(
+                    "\nTimed out after {}s. Process killed.\n",
+                    timeout.as_secs()
+                ),
+            )
+        })
     }
 
     /// Executing via PTY and streaming output to a sender.
@@ -37,24 +248,14 @@ impl ShellSession {
         cmd_content: &str,
         language: Option<&str>,
         tx: &Sender<String>,
-    ) -> StepStatus {
-        let pty_system = native_pty_system();
-        let pty_pair = match pty_system.openpty(PtySize {
-            rows: 24,
-            cols: 80,
-            pixel_width: 0,
-            pixel_height: 0,
-        }) {
-            Ok(pair) => pair,
-            Err(e) => {
-                let _ = tx.send(format!("Error opening PTY: {e}\n"));
-                return StepStatus::Failed;
-            }
+    ) -> SessionOutcome {
+        // Prepare using Strategy. The user's shell and profile only exist on the host.
+        let handler = if self.context.sandbox_enabled {
+            get_shell_aware_handler(language, Shell::System, false)
+        } else {
+            get_shell_aware_handler(language, self.context.shell, self.context.source_profile)
         };
 
-        // Prepare using Strategy
-        let handler = get_language_handler(language);
-
         // Use a local temp directory to ensure compatibility with shells (WSL, Bash)
         // that might have trouble with cross-drive paths or absolute Windows paths.
         let temp_dir = self.context.current_dir.join(".compass_temp");
@@ -66,7 +267,7 @@ impl ShellSession {
             Ok(path) => path,
             Err(e) => {
                 let _ = tx.send(format!("Failed to prepare code: {e}\n"));
-                return StepStatus::Failed;
+                return SessionOutcome::failed();
             }
         };
 
@@ -78,9 +279,14 @@ impl ShellSession {
         let run_cmd_parts = run_cmd; // Alias for clarity
 
         // --- Docker Sandbox Logic ---
-        let cmd = if self.context.sandbox_enabled {
-            let mut docker_cmd = CommandBuilder::new("docker");
-            docker_cmd.args(["run", "--rm", "-it"]);
+        // Named, so that it can be killed when the step is stopped
+        let container = self
+            .context
+            .sandbox_enabled
+            .then(|| format!("compass-{}", Uuid::new_v4().simple()));
+        let cmd = if let Some(container) = &container {
+            let mut docker_cmd = CommandSpec::new("docker");
+            docker_cmd.args(["run", "--rm", "--name", container, "-it"]);
 
             // 1. Mount Current Working Directory
             // We mount the project root to /workspace so relative paths work as expected.
@@ -137,11 +343,11 @@ impl ShellSession {
             docker_cmd
         } else {
             // --- Standard Host Execution ---
-            let mut cmd = CommandBuilder::new(&run_cmd_parts[0]);
+            let mut cmd = CommandSpec::new(&run_cmd_parts[0]);
             for arg in &run_cmd_parts[1..] {
                 cmd.arg(arg);
             }
-            cmd.cwd(&self.context.current_dir);
+            cmd.cwd = Some(self.context.current_dir.clone());
             for (key, val) in self
                 .context
                 .env_vars
@@ -153,14 +359,42 @@ impl ShellSession {
             cmd
         };
 
+        tracing::debug!(
+            sandbox = self.context.sandbox_enabled,
+            command = ?run_cmd_parts,
+            "Spawning process"
+        );
+
+        // Pipes keep stdout and stderr apart, which a PTY merges
+        let pty_pair = if self.context.separate_streams {
+            None
+        } else {
+            native_pty_system()
+                .openpty(PtySize {
+                    rows: 24,
+                    cols: 80,
+                    pixel_width: 0,
+                    pixel_height: 0,
+                })
+                // No PTY available (e.g. restricted containers): run with pipes instead
+                .inspect_err(|e| tracing::warn!("Failed to open PTY, falling back to pipes: {e}"))
+                .ok()
+        };
+        let Some(pty_pair) = pty_pair else {
+            let outcome = self.run_piped(&cmd, container.as_deref(), tx);
+            let _ = std::fs::remove_file(&prepared_path);
+            return outcome;
+        };
+
         // Spawn child
-        let mut child = match pty_pair.slave.spawn_command(cmd) {
+        let mut child = match pty_pair.slave.spawn_command(cmd.to_pty()) {
             Ok(child) => child,
             Err(e) => {
+                tracing::error!("Failed to spawn process: {e}");
                 let _ = tx.send(format!("Error spawning process: {e}\n"));
                 // Try to cleanup
                 let _ = std::fs::remove_file(&prepared_path);
-                return StepStatus::Failed;
+                return SessionOutcome::failed();
             }
         };
 
@@ -172,26 +406,71 @@ impl ShellSession {
             Ok(r) => r,
             Err(e) => {
                 let _ = tx.send(format!("Error getting reader: {e}\n"));
-                return StepStatus::Failed;
+                return SessionOutcome::failed();
             }
         };
 
         let tx_output = tx.clone();
         let read_thread = std::thread::spawn(move || {
             let mut buffer = [0u8; 4096]; // Larger buffer
+            let mut decoder = Utf8Decoder::default();
             while let Ok(n) = reader.read(&mut buffer) {
                 if n == 0 {
                     break;
                 }
-                if let Ok(text) = std::str::from_utf8(&buffer[..n]) {
+                let text = decoder.push(&buffer[..n]);
+                if !text.is_empty() {
                     // Send to channel
-                    let _ = tx_output.send(text.to_string());
+                    let _ = tx_output.send(text);
                 }
             }
+            let rest = decoder.finish();
+            if !rest.is_empty() {
+                let _ = tx_output.send(rest);
+            }
         });
 
-        // Wait for child to finish
-        let status = child.wait();
+        let mut writer = match self.input {
+            Some(_) => pty_pair
+                .master
+                .take_writer()
+                .inspect_err(|e| tracing::warn!("Cannot write to the PTY: {e}"))
+                .ok(),
+            None => None,
+        };
+
+        // Wait for child to finish, killing it if it is cancelled or exceeds the timeout
+        let started = Instant::now();
+        // Status of a command killed before it exited
+        let mut stopped = None;
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break Ok(status),
+                Ok(None) => {}
+                Err(e) => break Err(e),
+            }
+
+            // Forward the keystrokes typed since the last check
+            if let (Some(input), Some(w)) = (&self.input, writer.as_mut()) {
+                while let Ok(bytes) = input.try_recv() {
+                    if w.write_all(&bytes).and_then(|()| w.flush()).is_err() {
+                        break;
+                    }
+                }
+            }
+
+            if let Some((status, message)) = self.stop_reason(started) {
+                if let Some(container) = &container {
+                    kill_container(container);
+                }
+                let _ = child.kill();
+                stopped = Some(status);
+                let _ = tx.send(message);
+                break child.wait();
+            }
+
+            std::thread::sleep(Duration::from_millis(20));
+        };
 
         // Cleanup temporary file
         let _ = std::fs::remove_file(&prepared_path);
@@ -202,17 +481,245 @@ impl ShellSession {
         }
 
         // Explicitly drop master after child finishes to signal EOF to reader thread
+        drop(writer);
         drop(pty_pair.master);
 
         // Join reader thread to ensure all output is forwarded
         let _ = read_thread.join();
 
-        status.map_or(StepStatus::Failed, |s| {
-            if s.success() {
-                StepStatus::Success
-            } else {
-                StepStatus::Failed
+        tracing::debug!(?status, "Process exited");
+
+        status.map_or(SessionOutcome::failed(), |s| SessionOutcome {
+            status: match stopped {
+                Some(status) => status,
+                None if s.success() => StepStatus::Success,
+                None => StepStatus::Failed,
+            },
+            exit_code: Some(s.exit_code()),
+        })
+    }
+
+    /// Runs the command with pipes instead of a PTY, tagging the lines read
+    /// from stderr with `STDERR_TAG`. The command runs in the sandbox
+    /// `container`, if any.
+    fn run_piped(
+        &self,
+        spec: &CommandSpec,
+        container: Option<&str>,
+        tx: &Sender<String>,
+    ) -> SessionOutcome {
+        let mut child = match spec.to_piped().spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                tracing::error!("Failed to spawn process: {e}");
+                let _ = tx.send(format!("Error spawning process: {e}\n"));
+                return SessionOutcome::failed();
+            }
+        };
+
+        // Forward whole lines so a tag never lands in the middle of a line
+        let forward = |stream: Box<dyn Read + Send>, tag: &'static str| {
+            let tx = tx.clone();
+            std::thread::spawn(move || {
+                let mut reader = BufReader::new(stream);
+                let mut line = Vec::new();
+                while matches!(reader.read_until(b'\n', &mut line), Ok(n) if n > 0) {
+                    let _ = tx.send(format!("{tag}{}", String::from_utf8_lossy(&line)));
+                    line.clear();
+                }
+            })
+        };
+        let readers = [
+            child.stdout.take().map(|s| forward(Box::new(s), "")),
+            child
+                .stderr
+                .take()
+                .map(|s| forward(Box::new(s), STDERR_TAG)),
+        ];
+
+        let started = Instant::now();
+        // Status of a command killed before it exited
+        let mut stopped = None;
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break Ok(status),
+                Ok(None) => {}
+                Err(e) => break Err(e),
+            }
+
+            if let Some((status, message)) = self.stop_reason(started) {
+                if let Some(container) = container {
+                    kill_container(container);
+                }
+                kill_group(&mut child);
+                stopped = Some(status);
+                let _ = tx.send(message);
+                break child.wait();
+            }
+
+            std::thread::sleep(Duration::from_millis(20));
+        };
+
+        // A killed command may have left a process holding its output, which
+        // the readers would wait for: they are left to finish on their own
+        if stopped.is_none() {
+            for reader in readers.into_iter().flatten() {
+                let _ = reader.join();
             }
+        }
+
+        tracing::debug!(?status, "Process exited");
+
+        status.map_or(SessionOutcome::failed(), |s| SessionOutcome {
+            status: match stopped {
+                Some(status) => status,
+                None if s.success() => StepStatus::Success,
+                None => StepStatus::Failed,
+            },
+            exit_code: s.code().and_then(|code| u32::try_from(code).ok()),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utf8_decoder_split_characters() {
+        let text = "日本語 ─┼─ ok";
+        let bytes = text.as_bytes();
+        let mut decoder = Utf8Decoder::default();
+        // Split in the middle of "本" (3 bytes) and of "┼"
+        let mut decoded = decoder.push(&bytes[..4]);
+        decoded.push_str(&decoder.push(&bytes[4..14]));
+        decoded.push_str(&decoder.push(&bytes[14..]));
+        decoded.push_str(&decoder.finish());
+        assert_eq!(decoded, text);
+
+        let mut decoder = Utf8Decoder::default();
+        assert_eq!(decoder.push(b"a\xffb\xe6"), "a\u{FFFD}b");
+        assert_eq!(decoder.finish(), "\u{FFFD}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_piped_tags_stderr() {
+        let session = ShellSession::new(ExecutionContext::new());
+        let mut spec = CommandSpec::new("sh");
+        spec.args(["-c", "echo out; echo err >&2; exit 3"]);
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        let outcome = session.run_piped(&spec, None, &tx);
+        let output: Vec<String> = rx.try_iter().collect();
+
+        assert_eq!(outcome.status, StepStatus::Failed);
+        assert_eq!(outcome.exit_code, Some(3));
+        assert!(output.contains(&"out\n".to_string()));
+        assert!(output.contains(&format!("{STDERR_TAG}err\n")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_piped_timeout_kills_children() {
+        let mut context = ExecutionContext::new();
+        context.timeout = Some(Duration::from_millis(300));
+        let session = ShellSession::new(context);
+        let mut spec = CommandSpec::new("sh");
+        spec.args(["-c", "sleep 30 | cat; echo done"]);
+        let (tx, _rx) = std::sync::mpsc::channel();
+
+        let started = Instant::now();
+        let outcome = session.run_piped(&spec, None, &tx);
+
+        assert_eq!(outcome.status, StepStatus::TimedOut);
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_forwards_input() {
+        let dir = std::env::temp_dir().join("compass_session_input");
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut context = ExecutionContext::new();
+        context.current_dir = dir.clone();
+        let (input_tx, input_rx) = std::sync::mpsc::channel();
+        input_tx.send(b"yes\r".to_vec()).unwrap();
+        let session = ShellSession::new(context).with_input(Some(input_rx));
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        let outcome = session.run("read answer; echo \"got:$answer\"", Some("bash"), &tx);
+        drop(tx);
+        let output: String = rx.iter().collect();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(outcome.status, StepStatus::Success);
+        assert!(output.contains("got:yes"), "{output}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_cancelled() {
+        let dir = std::env::temp_dir().join("compass_session_cancel");
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut context = ExecutionContext::new();
+        context.current_dir = dir.clone();
+        let (cancel_tx, cancel_rx) = std::sync::mpsc::channel();
+        let session = ShellSession::new(context).with_cancel(Some(cancel_rx));
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        let started = Instant::now();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(300));
+            let _ = cancel_tx.send(());
+        });
+        let outcome = session.run("echo started; sleep 30; echo done", Some("bash"), &tx);
+        drop(tx);
+        let output: String = rx.iter().collect();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(outcome.status, StepStatus::Cancelled);
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert!(output.contains("Cancelled"), "{output}");
+        assert!(!output.contains("done"), "{output}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_timed_out() {
+        let dir = std::env::temp_dir().join("compass_session_timeout");
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut context = ExecutionContext::new();
+        context.current_dir = dir.clone();
+        context.timeout = Some(Duration::from_millis(300));
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        let outcome = ShellSession::new(context).run("sleep 30", Some("bash"), &tx);
+        drop(tx);
+        let output: String = rx.iter().collect();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(outcome.status, StepStatus::TimedOut);
+        assert!(output.contains("Timed out"), "{output}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_separate_streams() {
+        let dir = std::env::temp_dir().join("compass_session_streams");
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut context = ExecutionContext::new();
+        context.current_dir = dir.clone();
+        context.separate_streams = true;
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        let outcome = ShellSession::new(context).run("echo out; echo err >&2", Some("bash"), &tx);
+        drop(tx);
+        let output: Vec<String> = rx.iter().collect();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(outcome.status, StepStatus::Success);
+        assert!(output.contains(&"out\n".to_string()), "{output:?}");
+        assert!(output.contains(&format!("{STDERR_TAG}err\n")), "{output:?}");
+    }
+}
@@ -0,0 +1,127 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Steps that run at the same time, from `compass:group parallel`
+//! annotations.
+
+use crate::core::models::{Step, StepStatus};
+
+/// Indexes of the steps in the parallel group of `steps[index]`, in document
+/// order, or just `index` if the step is in no group.
+pub fn members(steps: &[Step], index: usize) -> Vec<usize> {
+    match steps[index].group {
+        Some(group) => (0..steps.len())
+            .filter(|&i| steps[i].group == Some(group))
+            .collect(),
+        None => vec![index],
+    }
+}
+
+/// Status of a group as a whole: running while one of its steps runs, then
+/// the first failure or cancellation, then a success once every step
+/// succeeded or was skipped.
+pub fn status(statuses: &[StepStatus]) -> StepStatus {
+    if statuses.contains(&StepStatus::Running) {
+        return StepStatus::Running;
+    }
+    if let Some(&failure) = statuses
+        .iter()
+        .find(|s| s.is_failure() || **s == StepStatus::Cancelled)
+    {
+        return failure;
+    }
+    if statuses
+        .iter()
+        .all(|s| matches!(s, StepStatus::Success | StepStatus::Skipped))
+    {
+        StepStatus::Success
+    } else {
+        StepStatus::Pending
+    }
+}
+
+/// One-line summary of the group of `steps[index]`: its other steps and how
+/// many of its steps are in each status. `None` if the step is in no group.
+pub fn describe(steps: &[Step], index: usize) -> Option<String> {
+    steps[index].group?;
+    let members = members(steps, index);
+    let others: Vec<&str> = members
+        .iter()
+        .filter(|&&i| i != index)
+        .map(|&i| steps[i].title.trim())
+        .collect();
+    let mut counts: Vec<(StepStatus, usize)> = Vec::new();
+    for &i in &members {
+        match counts.iter_mut().find(|(s, _)| *s == steps[i].status) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((steps[i].status, 1)),
+        }
+    }
+    let counts: Vec<String> = counts
+        .iter()
+        .map(|(status, count)| format!("{count} {}", status_word(*status)))
+        .collect();
+    Some(format!(
+        "Runs in parallel with: {} ({})",
+        others.join(", "),
+        counts.join(", ")
+    ))
+}
+
+/// How a status reads after a count ("2 running").
+const fn status_word(status: StepStatus) -> &'static str {
+    match status {
+        StepStatus::Pending => "pending",
+        StepStatus::Running => "running",
+        StepStatus::Success => "succeeded",
+        StepStatus::Failed => "failed",
+        StepStatus::Skipped => "skipped",
+        StepStatus::Cancelled => "cancelled",
+        StepStatus::TimedOut => "timed out",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_readme;
+
+    const RUNBOOK: &str = "# Build\n```bash\nmake\n```\n<!-- compass:group parallel -->\n# Database\n```bash\n./db\n```\n# Cache\n```bash\n./cache\n```\n<!-- compass:endgroup -->\n# Test\n```bash\nmake test\n```\n";
+
+    #[test]
+    fn test_parallel_groups() {
+        let (mut steps, _) = parse_readme(RUNBOOK);
+        let groups: Vec<_> = steps.iter().map(|s| s.group).collect();
+        assert_eq!(groups, vec![None, Some(0), Some(0), None]);
+        assert_eq!(members(&steps, 2), vec![1, 2]);
+        assert_eq!(members(&steps, 3), vec![3]);
+
+        steps[1].status = StepStatus::Running;
+        steps[2].status = StepStatus::Failed;
+        assert_eq!(
+            status(&[steps[1].status, steps[2].status]),
+            StepStatus::Running
+        );
+        steps[1].status = StepStatus::Success;
+        assert_eq!(
+            status(&[steps[1].status, steps[2].status]),
+            StepStatus::Failed
+        );
+        assert_eq!(
+            describe(&steps, 1).as_deref(),
+            Some("Runs in parallel with: Cache (1 succeeded, 1 failed)")
+        );
+        assert_eq!(describe(&steps, 0), None);
+    }
+}
@@ -0,0 +1,279 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Commands with a large blast radius, reviewed before they run:
+//! `terraform apply` (and OpenTofu's `tofu apply`) shows its plan first, and
+//! `kubectl` commands that change a cluster name the cluster they target.
+
+use super::tokenizer::simple_commands;
+use crate::core::export::runbook::quote;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+/// Options of `terraform apply` followed by a separate value (`-var x=1`).
+const TERRAFORM_VALUE_FLAGS: &[&str] = &[
+    "-var",
+    "-var-file",
+    "-target",
+    "-replace",
+    "-parallelism",
+    "-lock-timeout",
+    "-state",
+    "-state-out",
+    "-backup",
+];
+
+/// Options of `terraform apply` that `terraform plan` rejects or must not get.
+const APPLY_ONLY_FLAGS: &[&str] = &["-auto-approve", "-input", "-state-out", "-backup"];
+
+/// `kubectl` commands that change the cluster.
+const KUBECTL_CHANGES: &[&str] = &[
+    "apply",
+    "create",
+    "delete",
+    "replace",
+    "patch",
+    "edit",
+    "scale",
+    "autoscale",
+    "rollout",
+    "set",
+    "label",
+    "annotate",
+    "taint",
+    "drain",
+    "cordon",
+    "uncordon",
+    "expose",
+    "run",
+];
+
+/// `kubectl` options followed by a separate value (`-n kube-system`).
+const KUBECTL_VALUE_FLAGS: &[&str] = &[
+    "-n",
+    "--namespace",
+    "--context",
+    "--kubeconfig",
+    "--cluster",
+    "--user",
+    "-f",
+    "--filename",
+    "-l",
+    "--selector",
+    "-o",
+    "--output",
+];
+
+/// The `terraform plan` to run before a snippet that runs `terraform apply`
+/// without a saved plan, with the same options and in the same directory
+/// (`cd` commands before it are kept). `None` if it applies nothing or
+/// applies a plan file, which was reviewed when it was made.
+pub fn plan_before_apply(script: &str) -> Option<String> {
+    let mut prefix = Vec::new();
+    for words in simple_commands(script) {
+        match words[0].as_str() {
+            "cd" | "pushd" => prefix.push(join(&words)),
+            "terraform" | "tofu" => {
+                if let Some(plan) = plan_command(&words) {
+                    prefix.push(plan);
+                    return Some(prefix.join(" && "));
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// `terraform plan` for the words of a `terraform apply` command.
+fn plan_command(words: &[String]) -> Option<String> {
+    // Global options (`-chdir=infra`) come before the subcommand
+    let subcommand = words.iter().skip(1).position(|w| !w.starts_with('-'))? + 1;
+    if words[subcommand] != "apply" {
+        return None;
+    }
+    let mut plan: Vec<String> = words[..subcommand].to_vec();
+    plan.extend(["plan", "-input=false", "-no-color"].map(String::from));
+    let mut args = words[subcommand + 1..].iter();
+    while let Some(arg) = args.next() {
+        let name = arg.split('=').next().unwrap_or(arg);
+        let takes_value = !arg.contains('=') && TERRAFORM_VALUE_FLAGS.contains(&name);
+        if !arg.starts_with('-') {
+            // A saved plan file
+            return None;
+        }
+        let value = if takes_value { args.next() } else { None };
+        if !APPLY_ONLY_FLAGS.contains(&name) {
+            plan.push(arg.clone());
+            plan.extend(value.cloned());
+        }
+    }
+    Some(join(&plan))
+}
+
+/// Joins words into a command line, quoting those the shell would split.
+fn join(words: &[String]) -> String {
+    words
+        .iter()
+        .map(|word| {
+            let plain = !word.is_empty()
+                && word
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-_./=:,@%+".contains(c));
+            if plain { word.clone() } else { quote(word) }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A `kubectl` command that changes a cluster.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KubectlChange {
+    /// The subcommand, e.g. `apply` or `delete`.
+    pub verb: String,
+    /// The context given with `--context`, if any.
+    pub context: Option<String>,
+    /// The namespace given with `-n` or `--namespace`, if any.
+    pub namespace: Option<String>,
+    /// The kubeconfig given with `--kubeconfig`, if any.
+    pub kubeconfig: Option<String>,
+}
+
+/// The first `kubectl` command of a snippet that changes a cluster. Read-only
+/// commands (`get`, `describe`, `logs`, `rollout status`) are left out.
+pub fn kubectl_change(script: &str) -> Option<KubectlChange> {
+    simple_commands(script).into_iter().find_map(|words| {
+        if words[0] != "kubectl" {
+            return None;
+        }
+        let mut change = KubectlChange {
+            verb: String::new(),
+            context: None,
+            namespace: None,
+            kubeconfig: None,
+        };
+        let mut positional = Vec::new();
+        let mut args = words[1..].iter();
+        while let Some(arg) = args.next() {
+            if !arg.starts_with('-') {
+                positional.push(arg.as_str());
+                continue;
+            }
+            let (name, value) = match arg.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None if KUBECTL_VALUE_FLAGS.contains(&arg.as_str()) => {
+                    (arg.as_str(), args.next().cloned())
+                }
+                None => (arg.as_str(), None),
+            };
+            match name {
+                "--context" => change.context = value,
+                "-n" | "--namespace" => change.namespace = value,
+                "--kubeconfig" => change.kubeconfig = value,
+                _ => {}
+            }
+        }
+        let verb = *positional.first()?;
+        let read_only =
+            verb == "rollout" && matches!(positional.get(1), Some(&("status" | "history")));
+        if !KUBECTL_CHANGES.contains(&verb) || read_only {
+            return None;
+        }
+        change.verb = verb.to_string();
+        Some(change)
+    })
+}
+
+impl KubectlChange {
+    /// Describes the cluster the command targets, asking `kubectl` for the
+    /// current context and namespace when the command does not name them.
+    /// `env` and `dir` are those the step runs with.
+    pub fn describe(&self, env: &HashMap<String, String>, dir: &Path) -> String {
+        let config = |args: &[&str]| {
+            let mut command = Command::new("kubectl");
+            command.arg("config").args(args).envs(env).current_dir(dir);
+            if let Some(kubeconfig) = &self.kubeconfig {
+                command.arg("--kubeconfig").arg(kubeconfig);
+            }
+            if let Some(context) = &self.context {
+                command.arg("--context").arg(context);
+            }
+            command
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        };
+        let context = self
+            .context
+            .clone()
+            .or_else(|| config(&["current-context"]))
+            .filter(|c| !c.is_empty())
+            .unwrap_or_else(|| "unknown".to_string());
+        let namespace = self
+            .namespace
+            .clone()
+            .or_else(|| config(&["view", "--minify", "-o", "jsonpath={..namespace}"]))
+            .filter(|n| !n.is_empty())
+            .unwrap_or_else(|| "default".to_string());
+        format!(
+            "This step runs `kubectl {}` against the context '{context}', namespace '{namespace}'.",
+            self.verb
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_before_apply() {
+        assert_eq!(
+            plan_before_apply(
+                "cd infra\nterraform init\nterraform apply -auto-approve -var \"name=my app\" -target=module.db"
+            )
+            .as_deref(),
+            Some(
+                "cd infra && terraform plan -input=false -no-color -var 'name=my app' -target=module.db"
+            )
+        );
+        assert_eq!(
+            plan_before_apply("tofu -chdir=prod apply -input=false -destroy").as_deref(),
+            Some("tofu -chdir=prod plan -input=false -no-color -destroy")
+        );
+        assert!(plan_before_apply("terraform apply tfplan").is_none());
+        assert!(plan_before_apply("terraform plan -out tfplan").is_none());
+    }
+
+    #[test]
+    fn test_kubectl_change() {
+        let change =
+            kubectl_change("kubectl get pods\nkubectl --context=prod -n web apply -f deploy.yaml")
+                .unwrap();
+        assert_eq!(change.verb, "apply");
+        assert_eq!(change.context.as_deref(), Some("prod"));
+        assert_eq!(change.namespace.as_deref(), Some("web"));
+
+        assert!(kubectl_change("kubectl rollout status deploy/web").is_none());
+        assert!(kubectl_change("kubectl logs -f web").is_none());
+        assert_eq!(
+            kubectl_change("kubectl rollout restart deploy/web")
+                .unwrap()
+                .verb,
+            "rollout"
+        );
+    }
+}
@@ -43,6 +43,11 @@ pub trait LanguageDefinition {
     /// * `prepared_path` - The path returned by `prepare`.
     fn get_run_command(&self, prepared_path: &Path) -> Vec<String>;
 
+    /// Returns the arguments that make the required command print its version.
+    fn get_version_args(&self) -> &[&'static str] {
+        &["--version"]
+    }
+
     /// Returns a map of environment variables to set for the execution.
     fn get_env_vars(&self) -> HashMap<String, String> {
         HashMap::new()
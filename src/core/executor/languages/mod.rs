@@ -13,23 +13,108 @@
 // limitations under the License.
 
 pub mod definition;
+pub mod probe;
 pub mod strategies;
 
+use crate::core::plugins::Registry;
 use definition::LanguageDefinition;
+use std::sync::{Arc, LazyLock};
+use strategies::shell::{Shell, ShellHandler};
+
+/// Builds the handler of a language; the flag asks shells to load the
+/// user's profile.
+pub type HandlerFactory = Box<dyn Fn(bool) -> Box<dyn LanguageDefinition> + Send + Sync>;
+
+/// A language code blocks can be written in.
+pub struct Language {
+    /// Tags of the code blocks in this language (`python`, `py`).
+    pub ids: &'static [&'static str],
+    pub handler: HandlerFactory,
+}
+
+impl Language {
+    pub fn new(
+        ids: &'static [&'static str],
+        handler: impl Fn(bool) -> Box<dyn LanguageDefinition> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            ids,
+            handler: Box::new(handler),
+        }
+    }
+}
+
+static LANGUAGES: LazyLock<Registry<Language>> = LazyLock::new(|| {
+    let shell = |ids, lang: &'static str| {
+        Arc::new(Language::new(ids, move |profile| {
+            Box::new(ShellHandler::new(lang).with_profile(profile))
+        }))
+    };
+    Registry::new(vec![
+        Arc::new(Language::new(&["python", "py"], |_| {
+            Box::new(strategies::python::PythonHandler)
+        })),
+        Arc::new(Language::new(&["javascript", "js", "node"], |_| {
+            Box::new(strategies::javascript::JsHandler)
+        })),
+        Arc::new(Language::new(&["csharp", "cs", "c#"], |_| {
+            Box::new(strategies::csharp::CSharpHandler)
+        })),
+        Arc::new(Language::new(&["typescript", "ts"], |_| {
+            Box::new(strategies::typescript::TsHandler)
+        })),
+        Arc::new(Language::new(&["go", "golang"], |_| {
+            Box::new(strategies::go::GoHandler)
+        })),
+        Arc::new(Language::new(&["rust", "rs"], |_| {
+            Box::new(strategies::rust::RustHandler)
+        })),
+        Arc::new(Language::new(&["php"], |_| {
+            Box::new(strategies::php::PhpHandler)
+        })),
+        Arc::new(Language::new(&["ruby", "rb"], |_| {
+            Box::new(strategies::ruby::RubyHandler)
+        })),
+        shell(&["bash", "sh", "zsh"], "bash"),
+        shell(&["fish"], "fish"),
+        Arc::new(Language::new(&["cmd", "batch"], |_| {
+            Box::new(ShellHandler::new("cmd"))
+        })),
+        shell(&["powershell", "pwsh"], "powershell"),
+    ])
+});
+
+/// Adds a language, or replaces the handler of the ids it lists.
+pub fn register_language(language: Language) {
+    LANGUAGES.register(Arc::new(language));
+}
+
+fn language(lang_id: &str) -> Option<Arc<Language>> {
+    LANGUAGES
+        .entries()
+        .into_iter()
+        .find(|language| language.ids.contains(&lang_id))
+}
 
 pub fn get_language_handler(lang_id: Option<&str>) -> Box<dyn LanguageDefinition> {
-    match lang_id {
-        Some("python" | "py") => Box::new(strategies::python::PythonHandler),
-        Some("javascript" | "js" | "node") => Box::new(strategies::javascript::JsHandler),
-        Some("csharp" | "cs" | "c#") => Box::new(strategies::csharp::CSharpHandler),
-        Some("typescript" | "ts") => Box::new(strategies::typescript::TsHandler),
-        Some("go" | "golang") => Box::new(strategies::go::GoHandler),
-        Some("rust" | "rs") => Box::new(strategies::rust::RustHandler),
-        Some("php") => Box::new(strategies::php::PhpHandler),
-        Some("ruby" | "rb") => Box::new(strategies::ruby::RubyHandler),
-        Some("bash" | "sh" | "zsh") => Box::new(strategies::shell::ShellHandler::new("bash")),
-        Some("cmd" | "batch") => Box::new(strategies::shell::ShellHandler::new("cmd")),
-        Some("powershell" | "pwsh") => Box::new(strategies::shell::ShellHandler::new("powershell")),
-        _ => Box::new(strategies::shell::ShellHandler::new("default")),
+    get_shell_aware_handler(lang_id, Shell::System, false)
+}
+
+/// Whether `lang_id` has a handler of its own (see [`get_shell_aware_handler`]),
+/// rather than running with the default shell.
+pub fn is_supported(lang_id: &str) -> bool {
+    language(lang_id).is_some()
+}
+
+/// Like [`get_language_handler`], but runs untagged blocks with the user's
+/// preferred `shell`, optionally loading their profile in shell blocks.
+pub fn get_shell_aware_handler(
+    lang_id: Option<&str>,
+    shell: Shell,
+    source_profile: bool,
+) -> Box<dyn LanguageDefinition> {
+    match lang_id.and_then(language) {
+        Some(language) => (language.handler)(source_profile),
+        None => Box::new(ShellHandler::preferred(shell).with_profile(source_profile)),
     }
 }
@@ -0,0 +1,147 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::core::executor::languages::get_language_handler;
+use regex::Regex;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::{Mutex, OnceLock};
+
+/// The interpreter a language handler resolves to on this machine.
+#[derive(Debug, Clone)]
+pub struct InterpreterInfo {
+    /// The command name (e.g. "python3").
+    pub command: String,
+    /// The resolved executable path.
+    pub path: PathBuf,
+    /// The version reported by the interpreter, if it could be parsed.
+    pub version: Option<String>,
+}
+
+/// The state of an interpreter probe.
+#[derive(Debug, Clone)]
+pub enum InterpreterProbe {
+    /// The probe is still running in the background.
+    Pending(String),
+    /// The command is not installed.
+    Missing(String),
+    /// The interpreter was found.
+    Found(InterpreterInfo),
+}
+
+impl fmt::Display for InterpreterProbe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pending(command) => write!(f, "{command} (probing...)"),
+            Self::Missing(command) => write!(f, "{command} not found"),
+            Self::Found(info) => {
+                write!(f, "{}", info.command)?;
+                if let Some(version) = &info.version {
+                    write!(f, " {version}")?;
+                }
+                write!(f, " via {}", info.path.display())
+            }
+        }
+    }
+}
+
+static PROBES: OnceLock<Mutex<HashMap<String, InterpreterProbe>>> = OnceLock::new();
+
+fn probes() -> &'static Mutex<HashMap<String, InterpreterProbe>> {
+    PROBES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Returns the interpreter used for `language`.
+///
+/// Results are cached for the whole process. The first call for a command
+/// starts the probe in a background thread and returns `Pending`, so the UI
+/// never blocks on a slow `--version`.
+pub fn probe_interpreter(language: Option<&str>) -> InterpreterProbe {
+    let handler = get_language_handler(language);
+    let command = handler.get_required_command().to_string();
+
+    let Ok(mut cache) = probes().lock() else {
+        return InterpreterProbe::Pending(command);
+    };
+    if let Some(probe) = cache.get(&command) {
+        return probe.clone();
+    }
+    cache.insert(command.clone(), InterpreterProbe::Pending(command.clone()));
+    drop(cache);
+
+    let version_args: Vec<String> = handler
+        .get_version_args()
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    let probed_command = command.clone();
+    std::thread::spawn(move || {
+        let probe = run_probe(&probed_command, &version_args);
+        if let Ok(mut cache) = probes().lock() {
+            cache.insert(probed_command, probe);
+        }
+    });
+
+    InterpreterProbe::Pending(command)
+}
+
+fn run_probe(command: &str, version_args: &[String]) -> InterpreterProbe {
+    let Some(path) = crate::core::executor::binaries::find(command) else {
+        return InterpreterProbe::Missing(command.to_string());
+    };
+
+    let version = Command::new(&path)
+        .args(version_args)
+        .stdin(Stdio::null())
+        .output()
+        .ok()
+        .and_then(|output| {
+            // Some interpreters (e.g. older Python) print their version on stderr
+            let text = format!(
+                "{}\n{}",
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            );
+            parse_version(&text)
+        });
+
+    InterpreterProbe::Found(InterpreterInfo {
+        command: command.to_string(),
+        path,
+        version,
+    })
+}
+
+/// Extracts the first version number (e.g. "3.12.1") from a `--version` output.
+fn parse_version(text: &str) -> Option<String> {
+    let re = Regex::new(r"\d+\.\d+(\.\d+)?").unwrap();
+    re.find(text).map(|m| m.as_str().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("Python 3.12.1").as_deref(), Some("3.12.1"));
+        assert_eq!(
+            parse_version("go version go1.22 linux/amd64").as_deref(),
+            Some("1.22")
+        );
+        assert_eq!(parse_version("no version here"), None);
+    }
+}
@@ -24,6 +24,10 @@ impl LanguageDefinition for GoHandler {
         "go"
     }
 
+    fn get_version_args(&self) -> &[&'static str] {
+        &["version"]
+    }
+
     fn prepare(&self, code: &str, temp_dir: &Path) -> Result<PathBuf> {
         let filename = format!("main_{}.go", Uuid::new_v4());
         let file_path = temp_dir.join(filename);
@@ -14,22 +14,58 @@
 
 use crate::core::executor::languages::definition::LanguageDefinition;
 use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
+/// The shell running untagged code blocks, as chosen by the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Shell {
+    /// `sh` on Unix, PowerShell on Windows.
+    #[default]
+    System,
+    Bash,
+    Zsh,
+    Fish,
+    Pwsh,
+}
+
 pub struct ShellHandler {
     lang: String,
+    /// Start the shell as a login shell so the user's profile (and its PATH) is loaded.
+    source_profile: bool,
 }
 
 impl ShellHandler {
     pub fn new(lang: &str) -> Self {
         Self {
             lang: lang.to_string(),
+            source_profile: false,
         }
     }
 
+    /// Creates the handler for the user's preferred shell.
+    pub fn preferred(shell: Shell) -> Self {
+        Self::new(match shell {
+            Shell::System => "default",
+            Shell::Bash => "bash",
+            Shell::Zsh => "zsh",
+            Shell::Fish => "fish",
+            Shell::Pwsh => "pwsh",
+        })
+    }
+
+    /// Loads the user's profile before running the script.
+    pub fn with_profile(mut self, source_profile: bool) -> Self {
+        self.source_profile = source_profile;
+        self
+    }
+
     fn is_powershell(&self) -> bool {
-        self.lang == "powershell" || (self.lang == "default" && cfg!(target_os = "windows"))
+        self.lang == "powershell"
+            || self.lang == "pwsh"
+            || (self.lang == "default" && cfg!(target_os = "windows"))
     }
 
     fn is_cmd(&self) -> bool {
@@ -39,13 +75,19 @@ impl ShellHandler {
 
 impl LanguageDefinition for ShellHandler {
     fn get_required_command(&self) -> &str {
-        if self.is_powershell() {
+        if self.lang == "pwsh" {
+            "pwsh"
+        } else if self.is_powershell() {
             "powershell"
         } else if self.is_cmd() {
             "cmd"
-        } else if self.lang == "bash" || self.lang == "zsh" {
-            // explicit bash/zsh
-            if self.lang == "zsh" { "zsh" } else { "bash" }
+        } else if matches!(self.lang.as_str(), "bash" | "zsh" | "fish") {
+            // explicit bash/zsh/fish
+            match self.lang.as_str() {
+                "zsh" => "zsh",
+                "fish" => "fish",
+                _ => "bash",
+            }
         } else if self.lang == "sh" {
             "sh"
         } else if self.lang == "default" {
@@ -57,16 +99,22 @@ impl LanguageDefinition for ShellHandler {
         }
     }
 
-    fn prepare(&self, code: &str, temp_dir: &Path) -> Result<PathBuf> {
-        let extension = if self.is_powershell() {
-            "ps1"
+    fn get_version_args(&self) -> &[&'static str] {
+        if self.is_powershell() {
+            &[
+                "-NoProfile",
+                "-Command",
+                "$PSVersionTable.PSVersion.ToString()",
+            ]
         } else if self.is_cmd() {
-            "bat"
+            &["/C", "ver"]
         } else {
-            "sh"
-        };
+            &["--version"]
+        }
+    }
 
-        let filename = format!("script_{}.{}", Uuid::new_v4(), extension);
+    fn prepare(&self, code: &str, temp_dir: &Path) -> Result<PathBuf> {
+        let filename = format!("script_{}.{}", Uuid::new_v4(), self.get_extension());
         let file_path = temp_dir.join(filename);
 
         std::fs::write(&file_path, code)
@@ -80,13 +128,17 @@ impl LanguageDefinition for ShellHandler {
 
         if self.is_powershell() {
             // Use -ExecutionPolicy Bypass to ensure the script runs despite local restrictions
-            vec![
-                cmd,
+            let mut parts = vec![cmd];
+            if !self.source_profile {
+                parts.push("-NoProfile".to_string());
+            }
+            parts.extend([
                 "-ExecutionPolicy".to_string(),
                 "Bypass".to_string(),
                 "-File".to_string(),
                 prepared_path.to_string_lossy().to_string(),
-            ]
+            ]);
+            parts
         } else if self.is_cmd() {
             vec![
                 cmd,
@@ -102,7 +154,16 @@ impl LanguageDefinition for ShellHandler {
             } else {
                 path_str
             };
-            vec![cmd, path_argument]
+            let mut parts = vec![cmd];
+            if self.source_profile {
+                // Login shells read ~/.profile, ~/.bash_profile, ~/.zprofile, ...
+                parts.push("-l".to_string());
+            } else if self.lang == "fish" {
+                // fish reads config.fish even when not a login shell
+                parts.push("--no-config".to_string());
+            }
+            parts.push(path_argument);
+            parts
         }
     }
 
@@ -124,6 +185,8 @@ impl LanguageDefinition for ShellHandler {
             "ps1"
         } else if self.is_cmd() {
             "bat"
+        } else if self.lang == "fish" {
+            "fish"
         } else {
             "sh"
         }
@@ -12,11 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod binaries;
 pub mod checker;
+pub mod cloud;
 pub mod conditions;
 pub mod engine;
+pub mod groups;
+pub mod infra;
 pub mod languages;
+pub mod prerequisites;
+pub mod restart;
+pub mod runner;
 pub mod security;
+pub mod tokenizer;
+pub mod transform;
 
 pub use checker::check_dependencies;
-pub use engine::{ExecutionManager, Executor};
+pub use engine::{BackgroundRun, ExecutionManager, Executor};
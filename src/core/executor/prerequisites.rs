@@ -0,0 +1,102 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Steps that must succeed before another, from `compass:requires`
+//! annotations.
+
+use crate::core::models::{Step, StepStatus};
+use anyhow::{Result, bail};
+
+/// Index of the first step with this title (ignoring case).
+pub fn find(steps: &[Step], title: &str) -> Option<usize> {
+    steps
+        .iter()
+        .position(|s| s.title.trim().eq_ignore_ascii_case(title.trim()))
+}
+
+/// Indexes of the steps that `steps[index]` requires, in the order they are
+/// declared. Titles matching no step are left out.
+pub fn prerequisites(steps: &[Step], index: usize) -> Vec<usize> {
+    let mut found = Vec::new();
+    for title in &steps[index].requires {
+        if let Some(i) = find(steps, title)
+            && !found.contains(&i)
+        {
+            found.push(i);
+        }
+    }
+    found
+}
+
+/// Prerequisites of `steps[index]` that have not succeeded yet. Steps without
+/// code have nothing to run and never block.
+pub fn unmet(steps: &[Step], index: usize) -> Vec<usize> {
+    prerequisites(steps, index)
+        .into_iter()
+        .filter(|&i| steps[i].is_executable() && steps[i].status != StepStatus::Success)
+        .collect()
+}
+
+/// Orders the selected steps so that each one comes after the selected steps
+/// it requires, keeping the document order otherwise. Prerequisites outside
+/// the selection are not added.
+///
+/// # Errors
+///
+/// Returns an error if selected steps require each other.
+pub fn order(steps: &[Step], selected: &[usize]) -> Result<Vec<usize>> {
+    let mut remaining = selected.to_vec();
+    let mut ordered = Vec::with_capacity(remaining.len());
+    while !remaining.is_empty() {
+        let ready = remaining.iter().position(|&i| {
+            prerequisites(steps, i)
+                .iter()
+                .all(|p| *p != i && !remaining.contains(p))
+        });
+        let Some(position) = ready else {
+            let numbers: Vec<String> = remaining.iter().map(|i| (i + 1).to_string()).collect();
+            bail!(
+                "Steps {} cannot be ordered: their compass:requires form a cycle.",
+                numbers.join(", ")
+            );
+        };
+        ordered.push(remaining.remove(position));
+    }
+    Ok(ordered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_readme;
+
+    const RUNBOOK: &str = "# Deploy\n<!-- compass:requires \"Build\" \"install dependencies\" -->\n```bash\n./deploy\n```\n# Build\n<!-- compass:requires Install Dependencies -->\n<!-- compass:requires_env TOKEN -->\n```bash\nmake\n```\n# Install Dependencies\n```bash\nnpm ci\n```\n";
+
+    #[test]
+    fn test_order_by_prerequisites() {
+        let (mut steps, _) = parse_readme(RUNBOOK);
+        assert_eq!(steps[0].requires, vec!["Build", "install dependencies"]);
+        assert_eq!(steps[1].requires, vec!["Install Dependencies"]);
+        assert_eq!(prerequisites(&steps, 0), vec![1, 2]);
+
+        assert_eq!(order(&steps, &[0, 1, 2]).unwrap(), vec![2, 1, 0]);
+        assert_eq!(order(&steps, &[0, 1]).unwrap(), vec![1, 0]);
+
+        steps[2].status = StepStatus::Success;
+        assert_eq!(unmet(&steps, 0), vec![1]);
+
+        steps[2].requires = vec!["Deploy".to_string()];
+        assert!(order(&steps, &[0, 1, 2]).is_err());
+    }
+}
@@ -0,0 +1,225 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Steps after which the machine must be rebooted, or the user must log in
+//! again, before the rest of the runbook works: a step that prints "System
+//! restart required", or adds the user to a group (`usermod -aG docker`).
+
+use super::prerequisites;
+use crate::core::models::Step;
+use serde::Serialize;
+
+/// Phrases of outputs asking for a reboot, in lowercase.
+const REBOOT_PHRASES: &[&str] = &[
+    "reboot required",
+    "restart required",
+    "reboot is required",
+    "restart is required",
+    "reboot is needed",
+    "restart is needed",
+    "/var/run/reboot-required",
+    "please reboot",
+    "reboot your system",
+    "reboot the system",
+    "reboot your computer",
+    "reboot your machine",
+    "restart your system",
+    "restart your computer",
+    "restart your machine",
+];
+
+/// Phrases of outputs asking to log in again, in lowercase.
+const RELOGIN_PHRASES: &[&str] = &[
+    "log out and log back in",
+    "log out and back in",
+    "logout and login",
+    "log out and in again",
+    "sign out and sign back in",
+    "re-login",
+    "relogin",
+    "log in again",
+];
+
+/// What a step asks for before the next ones can run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RestartKind {
+    /// Reboot the machine.
+    Reboot,
+    /// Log out and back in, e.g. so that a new group membership applies.
+    Relogin,
+}
+
+impl RestartKind {
+    /// What the user has to do, for messages.
+    #[must_use]
+    pub fn action(self) -> &'static str {
+        match self {
+            Self::Reboot => "Reboot required",
+            Self::Relogin => "Log out and back in",
+        }
+    }
+}
+
+/// A reboot or new login needed after a step, and what revealed it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestartNotice {
+    pub kind: RestartKind,
+    /// The output line or the command asking for it.
+    pub reason: String,
+}
+
+impl std::fmt::Display for RestartNotice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.kind.action(), self.reason)
+    }
+}
+
+/// Whether a step that ran with this `output` needs a reboot or a new login.
+/// A reboot asked for by the output wins over a new login.
+#[must_use]
+pub fn detect(step: &Step, output: &str) -> Option<RestartNotice> {
+    let mut relogin = None;
+    for line in output.lines() {
+        let lower = line.to_lowercase();
+        if REBOOT_PHRASES.iter().any(|p| lower.contains(p)) {
+            return Some(notice(RestartKind::Reboot, line));
+        }
+        if relogin.is_none() && RELOGIN_PHRASES.iter().any(|p| lower.contains(p)) {
+            relogin = Some(notice(RestartKind::Relogin, line));
+        }
+    }
+    relogin.or_else(|| {
+        step.code_blocks
+            .iter()
+            .filter(|b| b.write_to.is_none())
+            .flat_map(|b| b.content.lines())
+            .find_map(|line| {
+                let why = login_change(line)?;
+                Some(RestartNotice {
+                    kind: RestartKind::Relogin,
+                    reason: format!("`{}` {why}", shorten(line)),
+                })
+            })
+    })
+}
+
+fn notice(kind: RestartKind, line: &str) -> RestartNotice {
+    RestartNotice {
+        kind,
+        reason: shorten(line),
+    }
+}
+
+/// A line trimmed to a length that fits a banner.
+fn shorten(line: &str) -> String {
+    let line = line.trim();
+    match line.char_indices().nth(100) {
+        Some((end, _)) => format!("{}…", &line[..end]),
+        None => line.to_string(),
+    }
+}
+
+/// Why a command only applies to new login sessions, if it does: it changes
+/// the groups or the login shell of a user.
+fn login_change(line: &str) -> Option<&'static str> {
+    let words: Vec<&str> = line
+        .split(|c: char| c.is_whitespace() || c == ';' || c == '&' || c == '|')
+        .filter(|w| !w.is_empty())
+        .collect();
+    let command = words.iter().position(|w| {
+        matches!(
+            w.rsplit('/').next(),
+            Some("usermod" | "gpasswd" | "adduser" | "chsh")
+        )
+    })?;
+    let name = words[command].rsplit('/').next().unwrap_or_default();
+    let args = &words[command + 1..];
+    let adds_group = match name {
+        "usermod" => args.iter().any(|a| {
+            *a == "--groups" || (a.starts_with('-') && !a.starts_with("--") && a.contains('G'))
+        }),
+        "gpasswd" => args.iter().any(|a| matches!(*a, "-a" | "--add")),
+        // `adduser alice docker` adds an existing user to a group
+        "adduser" => args.iter().filter(|a| !a.starts_with('-')).count() == 2,
+        _ => return Some("changes the login shell, which applies at the next login"),
+    };
+    adds_group.then_some("changes group membership, which applies at the next login")
+}
+
+/// Whether `steps[index]` must wait for the restart asked for by
+/// `steps[source]`: it comes later in the runbook or requires it, directly
+/// or through other steps.
+#[must_use]
+pub fn blocks(steps: &[Step], source: usize, index: usize) -> bool {
+    if index == source || !steps.get(index).is_some_and(Step::is_executable) {
+        return false;
+    }
+    if index > source {
+        return true;
+    }
+    let mut seen = vec![index];
+    let mut pending = vec![index];
+    while let Some(i) = pending.pop() {
+        for p in prerequisites::prerequisites(steps, i) {
+            if p == source {
+                return true;
+            }
+            if !seen.contains(&p) {
+                seen.push(p);
+                pending.push(p);
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_readme;
+
+    #[test]
+    fn test_detect_restart() {
+        let (steps, _) = parse_readme(
+            "# Docker\n```bash\nsudo usermod -aG docker $USER\n```\n# Upgrade\n```bash\nsudo apt-get upgrade -y\n```\n# Use\n<!-- compass:requires Docker -->\n```bash\ndocker ps\n```\n# Build\n```bash\nmake\n```\n",
+        );
+        let relogin = detect(&steps[0], "").unwrap();
+        assert_eq!(relogin.kind, RestartKind::Relogin);
+        assert!(
+            relogin
+                .reason
+                .starts_with("`sudo usermod -aG docker $USER`")
+        );
+
+        let reboot = detect(&steps[1], "Done.\n*** System restart required ***\n").unwrap();
+        assert_eq!(reboot.kind, RestartKind::Reboot);
+        assert_eq!(reboot.reason, "*** System restart required ***");
+        assert!(detect(&steps[1], "0 upgraded.\n").is_none());
+        assert!(detect(&steps[3], "").is_none());
+
+        assert!(blocks(&steps, 0, 3));
+        assert!(!blocks(&steps, 0, 0));
+        assert!(blocks(&steps, 2, 3));
+        assert!(!blocks(&steps, 3, 1));
+        // Step 3 comes first but requires step 1
+        let (mut steps, _) = parse_readme(
+            "# Use\n<!-- compass:requires Docker -->\n```bash\ndocker ps\n```\n# Other\n```bash\nls\n```\n# Docker\n```bash\nsudo usermod -aG docker $USER\n```\n",
+        );
+        assert!(blocks(&steps, 2, 0));
+        assert!(!blocks(&steps, 2, 1));
+        steps[0].requires.clear();
+        assert!(!blocks(&steps, 2, 0));
+    }
+}
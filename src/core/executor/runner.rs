@@ -0,0 +1,844 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Unattended execution of a runbook (`compass run`).
+
+use crate::core::executor::Executor;
+use crate::core::executor::conditions::evaluator::{ConditionEvaluator, StandardEvaluator};
+use crate::core::executor::engine::CommandBuilder;
+use crate::core::executor::engine::context::ContextSnapshot;
+use crate::core::executor::security::redact::Redactor;
+use crate::core::executor::{groups, prerequisites, restart};
+use crate::core::manifest::PlaceholderSpec;
+use crate::core::models::{ContextMode, Step, StepScope, StepStatus};
+use crate::core::secrets::SecretCache;
+use anyhow::{Result, bail};
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Write;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Instant;
+
+/// Which steps to run and how to report them.
+pub struct RunOptions {
+    /// First step to run (1-based, inclusive).
+    pub from: Option<usize>,
+    /// Last step to run (1-based, inclusive).
+    pub to: Option<usize>,
+    /// Steps not to run (1-based).
+    pub skip: Vec<usize>,
+    /// Print one JSON event per line instead of text.
+    pub json: bool,
+    /// Run the `rollback` command of the step that failed.
+    pub rollback: bool,
+    /// Placeholder values, looked up before the environment variables.
+    pub placeholders: HashMap<String, String>,
+    /// Defaults and formats of the placeholders, from the frontmatter.
+    pub specs: BTreeMap<String, PlaceholderSpec>,
+    /// Print the command of each step instead of running it.
+    pub dry_run: bool,
+    /// Carry on after a failed step (`compass test`), skipping the steps
+    /// that require it.
+    pub keep_going: bool,
+    /// Secrets read from the secret managers of the placeholders, when
+    /// allowed with `--fetch-secrets`.
+    pub secrets: Option<SecretCache>,
+}
+
+/// Indexes of the steps selected by `--from`, `--to` and `--skip`.
+///
+/// # Errors
+///
+/// Returns an error if a bound is outside the runbook or the range is empty.
+pub fn select_steps(steps: &[Step], options: &RunOptions) -> Result<Vec<usize>> {
+    let count = steps.len();
+    if count == 0 {
+        bail!("The runbook has no steps.");
+    }
+    let from = options.from.unwrap_or(1);
+    let to = options.to.unwrap_or(count);
+    for (flag, step) in [("--from", from), ("--to", to)] {
+        if step == 0 || step > count {
+            bail!("{flag} {step} is out of range: the runbook has {count} steps.");
+        }
+    }
+    if from > to {
+        bail!("--from {from} comes after --to {to}.");
+    }
+    Ok((from - 1..to)
+        .filter(|i| !options.skip.contains(&(i + 1)))
+        .collect())
+}
+
+/// Executable steps of the `--from`/`--to` range left out with `--skip`.
+fn excluded_steps(steps: &[Step], options: &RunOptions) -> Vec<usize> {
+    let from = options.from.unwrap_or(1);
+    let to = options.to.unwrap_or(steps.len());
+    (from.saturating_sub(1)..to)
+        .filter(|&i| options.skip.contains(&(i + 1)) && steps[i].is_executable())
+        .collect()
+}
+
+/// Runs the selected steps in order, after the steps they require, streaming
+/// their output, and stops at the first failure unless `keep_going` is set.
+/// Placeholders are read from environment variables of the same name.
+///
+/// A step with `compass:expect` assertions succeeds when they hold, whatever
+/// the exit code of its command.
+///
+/// The selected steps of a parallel group run at the same time, when the
+/// first of them is reached; their output is printed line by line behind
+/// their title.
+///
+/// With `dry_run`, prints the command of each step instead of running it.
+///
+/// Returns whether every step succeeded or was skipped.
+///
+/// # Errors
+///
+/// Returns an error if the step selection is invalid or its steps require
+/// each other.
+pub fn run(steps: &[Step], executor: &mut Executor, options: &RunOptions) -> Result<bool> {
+    let selected = prerequisites::order(steps, &select_steps(steps, options)?)?;
+    let baseline = executor.context.snapshot();
+    let started = Instant::now();
+    let (mut succeeded, mut failed, mut skipped) = (0, 0, 0);
+    let mut previewed = 0;
+    let mut skipped_steps = HashSet::new();
+    let mut failed_steps = HashSet::new();
+    // Steps already run (or skipped) with the first step of their group
+    let mut grouped = HashSet::new();
+
+    for i in excluded_steps(steps, options) {
+        skipped += 1;
+        report_skipped(&steps[i], i + 1, "excluded with --skip", options);
+    }
+
+    for (position, &i) in selected.iter().enumerate() {
+        let step = &steps[i];
+        if !step.is_executable() || grouped.contains(&i) {
+            continue;
+        }
+        let number = i + 1;
+
+        if let Some(reason) = skip_reason(steps, i, &skipped_steps, &failed_steps) {
+            skipped += 1;
+            skipped_steps.insert(i);
+            report_skipped(step, number, &reason, options);
+            continue;
+        }
+
+        if options.dry_run {
+            previewed += 1;
+            preview_step(step, number, executor, &baseline, options);
+            continue;
+        }
+
+        // The other selected steps of its parallel group run with it
+        let mut batch = vec![i];
+        if step.group.is_some() {
+            for m in groups::members(steps, i) {
+                if m == i || !selected.contains(&m) || !steps[m].is_executable() {
+                    continue;
+                }
+                grouped.insert(m);
+                if let Some(reason) = skip_reason(steps, m, &skipped_steps, &failed_steps) {
+                    skipped += 1;
+                    skipped_steps.insert(m);
+                    report_skipped(&steps[m], m + 1, &reason, options);
+                } else {
+                    batch.push(m);
+                }
+            }
+        }
+
+        for &m in &batch {
+            let (number, title) = (m + 1, steps[m].title.trim());
+            report(
+                options,
+                json!({ "event": "step_started", "step": number, "title": title }),
+                || {
+                    println!("▶️  Step {number}: {title}");
+                },
+            );
+        }
+        let outcomes = if batch.len() == 1 {
+            vec![run_step(step, number, executor, &baseline, options)]
+        } else {
+            run_group(steps, &batch, executor, &baseline, options)
+        };
+
+        let mut statuses = Vec::with_capacity(batch.len());
+        let mut restart_needed = None;
+        for (&m, outcome) in batch.iter().zip(outcomes) {
+            let notice = restart::detect(&steps[m], &outcome.output);
+            let captured = steps[m].captured(&outcome.output);
+            let status = finish_step(&steps[m], m + 1, outcome, options);
+            statuses.push(status);
+            if status == StepStatus::Success {
+                succeeded += 1;
+                capture(&captured, m + 1, executor, options);
+                restart_needed = restart_needed.or(notice.map(|n| (m, n)));
+                continue;
+            }
+            failed += 1;
+            if options.rollback
+                && let Some(command) = &steps[m].rollback
+            {
+                roll_back(&steps[m], command, m + 1, executor, options);
+            }
+            failed_steps.insert(m);
+        }
+        let status = groups::status(&statuses);
+        if batch.len() > 1 {
+            let numbers: Vec<usize> = batch.iter().map(|m| m + 1).collect();
+            let passed = statuses
+                .iter()
+                .filter(|&&s| s == StepStatus::Success)
+                .count();
+            report(
+                options,
+                json!({ "event": "group_finished", "steps": numbers, "status": status }),
+                || {
+                    println!(
+                        "∥  Parallel group: {passed} of {} steps succeeded",
+                        batch.len()
+                    );
+                },
+            );
+        }
+        if status != StepStatus::Success && !options.keep_going {
+            break;
+        }
+        // Steps that need the reboot or new login wait for the user to do it
+        if let Some((m, notice)) = restart_needed
+            && let Some(next) = selected[position + 1..]
+                .iter()
+                .copied()
+                .filter(|&j| !grouped.contains(&j) && restart::blocks(steps, m, j))
+                .min()
+        {
+            report(
+                options,
+                json!({ "event": "restart_required", "step": m + 1, "kind": notice.kind, "reason": notice.reason }),
+                || {
+                    println!("\n🔁 {notice} (after step {})", m + 1);
+                    println!(
+                        "   The next steps were not run: do it, then resume with `compass run --from {}`.",
+                        next + 1
+                    );
+                },
+            );
+            break;
+        }
+    }
+
+    if options.dry_run {
+        report(
+            options,
+            json!({ "event": "dry_run_finished", "steps": previewed, "skipped": skipped }),
+            || {
+                println!(
+                    "\n🔍 Dry run: {previewed} steps shown, {skipped} skipped. Nothing was executed."
+                );
+            },
+        );
+        return Ok(true);
+    }
+
+    let passed = failed == 0;
+    let duration_ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+    report(
+        options,
+        json!({ "event": "run_finished", "passed": passed, "succeeded": succeeded, "failed": failed, "skipped": skipped, "duration_ms": duration_ms }),
+        || {
+            let verdict = if passed { "✅ PASS" } else { "❌ FAIL" };
+            println!(
+                "\n{verdict}: {succeeded} succeeded, {failed} failed, {skipped} skipped in {:.1}s",
+                duration_ms as f64 / 1000.0
+            );
+            if options.keep_going {
+                let mut failed_steps: Vec<_> = failed_steps.iter().collect();
+                failed_steps.sort();
+                for &i in failed_steps {
+                    println!("   ❌ Step {}: {}", i + 1, steps[i].title.trim());
+                }
+            }
+        },
+    );
+    Ok(passed)
+}
+
+/// What a run of a step produced.
+struct Outcome {
+    status: StepStatus,
+    output: String,
+    duration_ms: u64,
+    exit_code: Option<u32>,
+}
+
+/// Applies the `compass:expect` assertions of a step that ran and reports it
+/// finished. Returns its final status.
+fn finish_step(step: &Step, number: usize, outcome: Outcome, options: &RunOptions) -> StepStatus {
+    let Outcome {
+        mut status,
+        output,
+        duration_ms,
+        exit_code,
+    } = outcome;
+    if let Some(expect) = &step.expect {
+        let failures = expect.check(status, exit_code, &output);
+        for message in &failures {
+            report(
+                options,
+                json!({ "event": "expectation_failed", "step": number, "message": message }),
+                || {
+                    println!("   ✗ {message}");
+                },
+            );
+        }
+        status = if failures.is_empty() {
+            StepStatus::Success
+        } else {
+            StepStatus::Failed
+        };
+    }
+
+    let title = step.title.trim();
+    report(
+        options,
+        json!({ "event": "step_finished", "step": number, "title": title, "status": status, "duration_ms": duration_ms, "exit_code": exit_code }),
+        || {
+            let icon = match status {
+                StepStatus::Success => "✅",
+                StepStatus::TimedOut => "⏱️ ",
+                _ => "❌",
+            };
+            let code = exit_code
+                .filter(|&code| code != 0)
+                .map(|code| format!(", exit code {code}"))
+                .unwrap_or_default();
+            println!(
+                "{icon} Step {number}: {title} ({:.1}s{code})",
+                duration_ms as f64 / 1000.0
+            );
+        },
+    );
+    status
+}
+
+/// Sets the variables a step captured from its output, for the next steps.
+fn capture(
+    captured: &[(String, String)],
+    number: usize,
+    executor: &mut Executor,
+    options: &RunOptions,
+) {
+    if captured.is_empty() {
+        return;
+    }
+    let names: Vec<&str> = captured.iter().map(|(name, _)| name.as_str()).collect();
+    report(
+        options,
+        json!({ "event": "variables_captured", "step": number, "names": names }),
+        || {
+            println!("   📥 Captured: {}", names.join(", "));
+        },
+    );
+    executor.context.env_vars.extend(captured.iter().cloned());
+}
+
+/// Reports a step that is not run, and why.
+fn report_skipped(step: &Step, number: usize, reason: &str, options: &RunOptions) {
+    let title = step.title.trim();
+    report(
+        options,
+        json!({ "event": "step_skipped", "step": number, "title": title, "reason": reason }),
+        || {
+            println!("⏭️  Step {number}: {title} ({reason})");
+        },
+    );
+}
+
+/// Why a step is not run, if its condition is not met or a step it requires
+/// was skipped or failed.
+fn skip_reason(
+    steps: &[Step],
+    index: usize,
+    skipped: &HashSet<usize>,
+    failed: &HashSet<usize>,
+) -> Option<String> {
+    if let Some(condition) = steps[index]
+        .condition
+        .as_ref()
+        .filter(|c| !StandardEvaluator::new().evaluate(c))
+    {
+        return Some(format!("condition not met: {condition}"));
+    }
+    prerequisites::prerequisites(steps, index)
+        .into_iter()
+        .find_map(|i| {
+            let outcome = if skipped.contains(&i) {
+                "was skipped"
+            } else if failed.contains(&i) {
+                "failed"
+            } else {
+                return None;
+            };
+            Some(format!(
+                "requires '{}', which {outcome}",
+                steps[i].title.trim()
+            ))
+        })
+}
+
+/// Runs the `rollback` command of a failed step, in its working directory and
+/// environment.
+fn roll_back(
+    step: &Step,
+    command: &str,
+    number: usize,
+    executor: &mut Executor,
+    options: &RunOptions,
+) {
+    report(
+        options,
+        json!({ "event": "rollback_started", "step": number, "command": command }),
+        || {
+            println!("↩️  Rolling back step {number}: {command}");
+        },
+    );
+    executor.context.timeout = step.timeout();
+    executor.scope = StepScope {
+        verify: None,
+        ..step.scope.clone()
+    };
+    let secrets = executor.context.secrets.clone();
+    let (status, _) = with_printer(number, options.json, secrets, None, |tx| {
+        executor.execute_streamed(command, None, false, tx)
+    });
+    report(
+        options,
+        json!({ "event": "rollback_finished", "step": number, "status": status }),
+        || {
+            let verdict = if status == StepStatus::Success {
+                "✅ Rolled back"
+            } else {
+                "❌ Rollback failed"
+            };
+            println!("{verdict}: step {number}");
+        },
+    );
+}
+
+/// Runs `run` with a sender whose messages are printed as they arrive, the
+/// values of `secrets` masked. With a `prefix`, text output is printed line by
+/// line, each line behind it. Returns the status and the printed output.
+fn with_printer(
+    number: usize,
+    json: bool,
+    secrets: Vec<String>,
+    prefix: Option<String>,
+    run: impl FnOnce(&mpsc::Sender<String>) -> StepStatus,
+) -> (StepStatus, String) {
+    let (tx, rx) = mpsc::channel::<String>();
+    let printer = thread::spawn(move || {
+        let mut redactor = Redactor::new(secrets);
+        let mut lines = prefix.filter(|_| !json).map(LinePrefixer::new);
+        let mut output = String::new();
+        let mut print = |text: &str, last: bool| {
+            match lines.as_mut() {
+                Some(lines) => print_output(number, json, &lines.push(text, last)),
+                None => print_output(number, json, text),
+            }
+            output.push_str(text);
+        };
+        for text in rx {
+            print(&redactor.push(&text), false);
+        }
+        print(&redactor.finish(), true);
+        output
+    });
+    let status = run(&tx);
+    drop(tx);
+    (status, printer.join().unwrap_or_default())
+}
+
+/// Puts a prefix in front of each line of streamed text, holding back the
+/// last line until it is complete.
+struct LinePrefixer {
+    prefix: String,
+    partial: String,
+}
+
+impl LinePrefixer {
+    fn new(prefix: String) -> Self {
+        Self {
+            prefix,
+            partial: String::new(),
+        }
+    }
+
+    /// The complete lines of the text received so far, prefixed, and with
+    /// `last` the incomplete one too.
+    fn push(&mut self, text: &str, last: bool) -> String {
+        self.partial.push_str(text);
+        let mut lines = String::new();
+        while let Some(end) = self.partial.find('\n') {
+            lines.push_str(&self.prefix);
+            lines.extend(self.partial.drain(..=end));
+        }
+        if last && !self.partial.is_empty() {
+            lines.push_str(&self.prefix);
+            lines.push_str(&std::mem::take(&mut self.partial));
+            lines.push('\n');
+        }
+        lines
+    }
+}
+
+/// Prints output of a step's command.
+fn print_output(number: usize, json: bool, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    if json {
+        println!(
+            "{}",
+            json!({ "event": "output", "step": number, "text": text })
+        );
+    } else {
+        let mut stdout = std::io::stdout();
+        let _ = stdout.write_all(text.as_bytes());
+        let _ = stdout.flush();
+    }
+}
+
+/// Runs one step, printing its output as it arrives.
+fn run_step(
+    step: &Step,
+    number: usize,
+    executor: &mut Executor,
+    baseline: &ContextSnapshot,
+    options: &RunOptions,
+) -> Outcome {
+    let started = Instant::now();
+    let resolved = resolve_variables(step, executor, options);
+    let secrets = executor.context.secrets.clone();
+    let (status, output) = with_printer(number, options.json, secrets, None, |tx| {
+        run_step_with(step, executor, baseline, resolved, tx)
+    });
+    Outcome {
+        status,
+        output,
+        duration_ms: u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX),
+        exit_code: executor.context.last_exit_code,
+    }
+}
+
+/// Runs the steps of a parallel group at the same time, each from a copy of
+/// the context: their changes to the directory and environment are dropped.
+/// Their output is printed line by line, behind their title.
+fn run_group(
+    steps: &[Step],
+    batch: &[usize],
+    executor: &mut Executor,
+    baseline: &ContextSnapshot,
+    options: &RunOptions,
+) -> Vec<Outcome> {
+    let width = batch
+        .iter()
+        .map(|&i| steps[i].title.trim().chars().count())
+        .max()
+        .unwrap_or(0);
+    // Secret values are collected before the copies are made
+    let resolved: Vec<Resolved> = batch
+        .iter()
+        .map(|&i| resolve_variables(&steps[i], executor, options))
+        .collect();
+    let context = &executor.context;
+    thread::scope(|scope| {
+        let runs: Vec<_> = batch
+            .iter()
+            .zip(resolved)
+            .map(|(&i, resolved)| {
+                let mut executor = Executor {
+                    context: context.clone(),
+                    input: None,
+                    cancel: None,
+                    scope: StepScope::default(),
+                    policy_confirmed: false,
+                };
+                let prefix = format!("{:width$} | ", steps[i].title.trim());
+                scope.spawn(move || {
+                    let started = Instant::now();
+                    let secrets = executor.context.secrets.clone();
+                    let (status, output) =
+                        with_printer(i + 1, options.json, secrets, Some(prefix), |tx| {
+                            run_step_with(&steps[i], &mut executor, baseline, resolved, tx)
+                        });
+                    Outcome {
+                        status,
+                        output,
+                        duration_ms: u64::try_from(started.elapsed().as_millis())
+                            .unwrap_or(u64::MAX),
+                        exit_code: executor.context.last_exit_code,
+                    }
+                })
+            })
+            .collect();
+        runs.into_iter()
+            .map(|run| {
+                run.join().unwrap_or_else(|_| Outcome {
+                    status: StepStatus::Failed,
+                    output: String::new(),
+                    duration_ms: 0,
+                    exit_code: None,
+                })
+            })
+            .collect()
+    })
+}
+
+/// Prints the command a step would run, with its placeholders filled and the
+/// lines Compass handles itself annotated, without running it.
+fn preview_step(
+    step: &Step,
+    number: usize,
+    executor: &mut Executor,
+    baseline: &ContextSnapshot,
+    options: &RunOptions,
+) {
+    let Resolved {
+        variables,
+        missing,
+        invalid,
+    } = resolve_variables(step, executor, options);
+
+    // Isolated steps start from the initial context and leave it unchanged
+    let shared = (step.context_mode == ContextMode::Isolated).then(|| {
+        let shared = executor.context.snapshot();
+        executor.context.restore(baseline.clone());
+        shared
+    });
+    let command = CommandBuilder::dry_run(step, &variables, &mut executor.context);
+    if let Some(shared) = shared {
+        executor.context.restore(shared);
+    }
+    let command = Redactor::new(executor.context.secrets.clone()).redact(&command);
+
+    let title = step.title.trim();
+    report(
+        options,
+        json!({ "event": "step_preview", "step": number, "title": title, "command": command, "missing": missing, "invalid": invalid }),
+        || {
+            println!("🔍 Step {number}: {title}");
+            print!("{command}");
+            if !missing.is_empty() {
+                println!("⚠️  Not set, the step would fail: {}", missing.join(", "));
+            }
+            for problem in &invalid {
+                println!("⚠️  Invalid, the step would fail: {problem}");
+            }
+        },
+    );
+}
+
+/// The placeholder values of a step and what prevents it from running.
+struct Resolved {
+    variables: HashMap<String, String>,
+    /// Placeholders and required environment variables that have no value.
+    missing: Vec<String>,
+    /// Values not matching the format declared in the frontmatter.
+    invalid: Vec<String>,
+}
+
+/// Placeholder values of a step, from `placeholders`, then from the
+/// environment of the session (with the variables captured by earlier steps)
+/// and of the process, then from the secret manager or the default declared in the
+/// frontmatter. Values of secret placeholders are added to the secrets masked
+/// in the output.
+fn resolve_variables(step: &Step, executor: &mut Executor, options: &RunOptions) -> Resolved {
+    let mut resolved = Resolved {
+        variables: HashMap::new(),
+        missing: executor.context.missing_env(&step.required_env),
+        invalid: Vec::new(),
+    };
+    for name in CommandBuilder::get_required_placeholders(step) {
+        let spec = options.specs.get(&name);
+        let given = options
+            .placeholders
+            .get(&name)
+            .or_else(|| executor.context.env_vars.get(&name))
+            .cloned()
+            .or_else(|| std::env::var(&name).ok());
+        let source = spec.and_then(|s| s.source.as_deref());
+        let value = match (given, source, &options.secrets) {
+            (None, Some(source), Some(cache)) => match cache.resolve(source) {
+                Ok(value) => Some(value),
+                Err(e) => {
+                    resolved.invalid.push(format!("{name}: {e:#}"));
+                    continue;
+                }
+            },
+            (given, ..) => given,
+        }
+        .or_else(|| spec.and_then(|s| s.default.clone()));
+        let Some(value) = value else {
+            resolved.missing.push(name);
+            continue;
+        };
+        if step.secrets.contains(&name) {
+            executor.context.add_secret(&value);
+        }
+        if let Some(Err(problem)) = spec.map(|s| s.validate(&value)) {
+            resolved.invalid.push(format!("{name}: {problem}"));
+        }
+        if let Err(problem) = step.check_choice(&name, &value) {
+            resolved.invalid.push(format!("{name}: {problem}"));
+        }
+        resolved.variables.insert(name, value);
+    }
+    resolved
+}
+
+/// Runs one step with its resolved placeholder values (see
+/// `resolve_variables`), sending its output to `tx`.
+fn run_step_with(
+    step: &Step,
+    executor: &mut Executor,
+    baseline: &ContextSnapshot,
+    resolved: Resolved,
+    tx: &mpsc::Sender<String>,
+) -> StepStatus {
+    executor.context.last_exit_code = None;
+
+    if !resolved.missing.is_empty() {
+        let _ = tx.send(format!(
+            "Missing environment variables: {} (set them before running).\n",
+            resolved.missing.join(", ")
+        ));
+        return StepStatus::Failed;
+    }
+    if !resolved.invalid.is_empty() {
+        for problem in &resolved.invalid {
+            let _ = tx.send(format!("Invalid value for {problem}.\n"));
+        }
+        return StepStatus::Failed;
+    }
+    let variables = resolved.variables;
+
+    // Isolated steps start from the initial context and leave it unchanged
+    let shared = (step.context_mode == ContextMode::Isolated).then(|| {
+        let shared = executor.context.snapshot();
+        executor.context.restore(baseline.clone());
+        shared
+    });
+
+    let content = CommandBuilder::build_command(step, &variables);
+    let language = step
+        .code_blocks
+        .iter()
+        .find(|b| b.write_to.is_none())
+        .and_then(|b| b.language.as_deref());
+    executor.context.timeout = step.timeout();
+    executor.scope = step.scope.clone();
+    let mut status = StepStatus::Success;
+    for edit in CommandBuilder::build_writes(step, &variables) {
+        status = executor.write_file(&edit, tx);
+        if status != StepStatus::Success {
+            break;
+        }
+    }
+    if status == StepStatus::Success && !content.trim().is_empty() {
+        status = executor.execute_streamed(&content, language, false, tx);
+    }
+    if let Some(shared) = shared {
+        executor.context.restore(shared);
+    }
+    status
+}
+
+/// Prints an event as a JSON line, or runs `text` to print it for humans.
+fn report(options: &RunOptions, event: serde_json::Value, text: impl FnOnce()) {
+    if options.json {
+        println!("{event}");
+    } else {
+        text();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(from: Option<usize>, to: Option<usize>, skip: Vec<usize>) -> RunOptions {
+        RunOptions {
+            from,
+            to,
+            skip,
+            json: false,
+            rollback: false,
+            placeholders: HashMap::new(),
+            specs: BTreeMap::new(),
+            dry_run: false,
+            keep_going: false,
+            secrets: None,
+        }
+    }
+
+    #[test]
+    fn test_select_steps() {
+        let steps = vec![Step::default(); 5];
+        assert_eq!(
+            select_steps(&steps, &options(None, None, vec![])).unwrap(),
+            vec![0, 1, 2, 3, 4]
+        );
+        assert_eq!(
+            select_steps(&steps, &options(Some(2), Some(4), vec![3])).unwrap(),
+            vec![1, 3]
+        );
+        assert!(select_steps(&steps, &options(Some(4), Some(2), vec![])).is_err());
+        assert!(select_steps(&steps, &options(None, Some(6), vec![])).is_err());
+        assert!(select_steps(&steps, &options(Some(0), None, vec![])).is_err());
+    }
+
+    #[test]
+    fn test_excluded_steps() {
+        let (steps, _) = crate::core::parser::parse_readme(
+            "# Intro\n# A\n```bash\na\n```\n# B\n```bash\nb\n```\n# C\n```bash\nc\n```\n",
+        );
+        assert_eq!(
+            excluded_steps(&steps, &options(None, None, vec![1, 3])),
+            vec![2]
+        );
+        assert_eq!(
+            excluded_steps(&steps, &options(Some(2), Some(3), vec![3, 4])),
+            vec![2]
+        );
+    }
+
+    #[test]
+    fn test_line_prefixer() {
+        let mut lines = LinePrefixer::new("db | ".to_string());
+        assert_eq!(lines.push("starting", false), "");
+        assert_eq!(
+            lines.push("...\nready\nlisten", false),
+            "db | starting...\ndb | ready\n"
+        );
+        assert_eq!(lines.push("ing", true), "db | listening\n");
+        assert_eq!(lines.push("", true), "");
+    }
+}
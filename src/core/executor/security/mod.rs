@@ -12,5 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod policy;
+pub mod redact;
 pub mod safety;
 pub mod validator;
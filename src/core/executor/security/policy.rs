@@ -0,0 +1,170 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Organization policies, evaluated with Open Policy Agent before each
+//! execution. Policies are Rego files in the `policies` directory of the
+//! configuration; the `data.compass.decision` rule sees the command about to
+//! run and answers `allow`, `confirm` or `deny`.
+
+use crate::core::executor::cloud;
+use crate::core::infrastructure::config::ConfigManager;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use uuid::Uuid;
+
+/// Rule answering for each command.
+const QUERY: &str = "data.compass.decision";
+
+/// What the policies know about a command (their `input`).
+#[derive(Debug, Serialize)]
+pub struct PolicyInput<'a> {
+    pub command: &'a str,
+    pub language: Option<&'a str>,
+    pub cwd: &'a Path,
+    /// Whether the runbook was fetched from a URL.
+    pub remote: bool,
+    pub sandbox: bool,
+}
+
+/// What the policies decided for a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decision {
+    Allow,
+    /// Runs once the user confirmed, for this reason.
+    Confirm(String),
+    Deny(String),
+}
+
+impl Decision {
+    /// Whether the command may run, `confirmed` when the user gave the
+    /// confirmation the policies may ask for.
+    #[must_use]
+    pub const fn permits(&self, confirmed: bool) -> bool {
+        match self {
+            Self::Allow => true,
+            Self::Confirm(_) => confirmed,
+            Self::Deny(_) => false,
+        }
+    }
+}
+
+/// The directory of the policies, if any are installed.
+pub fn policy_dir() -> Option<PathBuf> {
+    let dir = ConfigManager::get_config_dir().ok()?.join("policies");
+    fs::read_dir(&dir)
+        .ok()?
+        .flatten()
+        .any(|entry| entry.path().extension().is_some_and(|ext| ext == "rego"))
+        .then_some(dir)
+}
+
+/// Evaluates the policies for a command. Everything is allowed when there
+/// are none; policies that cannot be evaluated (no `opa`, a syntax error)
+/// deny, so a broken setup does not lift them.
+pub fn evaluate(input: &PolicyInput) -> Decision {
+    let Some(dir) = policy_dir() else {
+        return Decision::Allow;
+    };
+    let decision = query(&dir, input)
+        .unwrap_or_else(|e| Decision::Deny(format!("the policies could not be evaluated ({e})")));
+    tracing::info!(?decision, "Policies evaluated");
+    decision
+}
+
+fn query(dir: &Path, input: &PolicyInput) -> Result<Decision, String> {
+    let input_path = std::env::temp_dir().join(format!("compass_policy_{}.json", Uuid::new_v4()));
+    let json = serde_json::to_vec(input).map_err(|e| e.to_string())?;
+    fs::write(&input_path, json).map_err(|e| e.to_string())?;
+
+    let mut command = Command::new("opa");
+    command
+        .args(["eval", "--format", "json", "--data"])
+        .arg(dir)
+        .arg("--input")
+        .arg(&input_path)
+        .arg(QUERY);
+    let output = cloud::run(command, "opa");
+    let _ = fs::remove_file(&input_path);
+
+    let output: Value =
+        serde_json::from_str(&output?).map_err(|e| format!("unexpected opa output: {e}"))?;
+    decision(&output)
+}
+
+/// Reads the decision in the output of `opa eval --format json`: a string,
+/// or an object with an `action` and a `reason`. An undefined rule allows.
+fn decision(output: &Value) -> Result<Decision, String> {
+    let Some(value) = output.pointer("/result/0/expressions/0/value") else {
+        return Ok(Decision::Allow);
+    };
+    let (action, reason) = match value {
+        Value::String(action) => (action.as_str(), None),
+        Value::Object(fields) => (
+            fields
+                .get("action")
+                .and_then(Value::as_str)
+                .unwrap_or_default(),
+            fields.get("reason").and_then(Value::as_str),
+        ),
+        _ => ("", None),
+    };
+    let reason = || reason.unwrap_or("no reason given").to_string();
+    match action {
+        "allow" => Ok(Decision::Allow),
+        "confirm" => Ok(Decision::Confirm(reason())),
+        "deny" => Ok(Decision::Deny(reason())),
+        _ => Err(format!(
+            "{QUERY} must be \"allow\", \"confirm\" or \"deny\", not {value}"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn answer(value: Value) -> Value {
+        json!({ "result": [{ "expressions": [{ "value": value, "text": QUERY }] }] })
+    }
+
+    #[test]
+    fn test_policy_decision() {
+        assert_eq!(decision(&json!({})), Ok(Decision::Allow));
+        assert_eq!(decision(&answer(json!("allow"))), Ok(Decision::Allow));
+        assert_eq!(
+            decision(&answer(
+                json!({ "action": "deny", "reason": "no curl | sh" })
+            )),
+            Ok(Decision::Deny("no curl | sh".to_string()))
+        );
+        assert_eq!(
+            decision(&answer(json!("confirm"))),
+            Ok(Decision::Confirm("no reason given".to_string()))
+        );
+        assert!(decision(&answer(json!(true))).is_err());
+    }
+
+    #[test]
+    fn test_confirm_needs_confirmation() {
+        let confirm = Decision::Confirm("remote runbook".to_string());
+        assert!(!confirm.permits(false));
+        assert!(confirm.permits(true));
+        assert!(Decision::Allow.permits(false));
+        assert!(!Decision::Deny("no curl | sh".to_string()).permits(true));
+    }
+}
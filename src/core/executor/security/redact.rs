@@ -0,0 +1,109 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// Shown in place of a secret value.
+pub const MASK: &str = "••••••";
+
+/// Replaces secret values with [`MASK`] in text, including in a stream where
+/// a value may be split across chunks.
+#[derive(Debug, Clone, Default)]
+pub struct Redactor {
+    /// Values to mask, longest first so a value containing another is masked whole.
+    secrets: Vec<String>,
+    /// End of the last chunk that may be the start of a secret, held back
+    /// until the next chunk tells.
+    pending: String,
+}
+
+impl Redactor {
+    pub fn new(secrets: impl IntoIterator<Item = String>) -> Self {
+        let mut secrets: Vec<String> = secrets.into_iter().filter(|s| !s.is_empty()).collect();
+        secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+        secrets.dedup();
+        Self {
+            secrets,
+            pending: String::new(),
+        }
+    }
+
+    /// Whether there is nothing to mask.
+    pub fn is_empty(&self) -> bool {
+        self.secrets.is_empty()
+    }
+
+    /// Masks the secret values of `text`.
+    pub fn redact(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for secret in &self.secrets {
+            if text.contains(secret.as_str()) {
+                text = text.replace(secret.as_str(), MASK);
+            }
+        }
+        text
+    }
+
+    /// Masks the secret values of a chunk of a stream. Text that could be the
+    /// start of a secret is held back and returned with the next chunk, or
+    /// by [`Redactor::finish`].
+    pub fn push(&mut self, chunk: &str) -> String {
+        if self.is_empty() {
+            return chunk.to_string();
+        }
+        let pending = std::mem::take(&mut self.pending);
+        let mut text = self.redact(&(pending + chunk));
+        let held = self
+            .secrets
+            .iter()
+            .filter_map(|secret| {
+                (1..secret.len())
+                    .rev()
+                    .find(|&n| secret.is_char_boundary(n) && text.ends_with(&secret[..n]))
+            })
+            .max()
+            .unwrap_or(0);
+        self.pending = text.split_off(text.len() - held);
+        text
+    }
+
+    /// Returns the text held back at the end of a stream.
+    pub fn finish(&mut self) -> String {
+        std::mem::take(&mut self.pending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact() {
+        let redactor = Redactor::new(["hunter2".to_string(), String::new()]);
+        assert_eq!(
+            redactor.redact("password=hunter2 ok"),
+            format!("password={MASK} ok")
+        );
+        assert_eq!(redactor.redact("nothing here"), "nothing here");
+    }
+
+    #[test]
+    fn test_redact_split_stream() {
+        let mut redactor = Redactor::new(["s3cr3t".to_string()]);
+        let mut output = redactor.push("token: s3");
+        assert_eq!(output, "token: ");
+        output += &redactor.push("cr3t\nsec");
+        output += &redactor.push("ond line\n");
+        output += &redactor.finish();
+        assert_eq!(output, format!("token: {MASK}\nsecond line\n"));
+    }
+}
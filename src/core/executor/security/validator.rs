@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use which::which;
+use crate::core::executor::binaries;
 
 /// Validates that the required binaries for a command are present in the system's PATH.
 pub struct DependencyValidator;
@@ -20,7 +20,7 @@ pub struct DependencyValidator;
 impl DependencyValidator {
     /// Validates that a specific binary is available in the PATH.
     pub fn validate_binary(binary_name: &str) -> Result<(), String> {
-        if which(binary_name).is_err() {
+        if !binaries::exists(binary_name) {
             return Err(format!(
                 "Missing dependency: '{}' is not installed or not in PATH.",
                 binary_name
@@ -51,11 +51,12 @@ impl DependencyValidator {
             return Ok(());
         }
 
-        match which(binary_name) {
-            Ok(_) => Ok(()),
-            Err(_) => Err(format!(
+        if binaries::exists(binary_name) {
+            Ok(())
+        } else {
+            Err(format!(
                 "Requirement not met: '{binary_name}' is not installed."
-            )),
+            ))
         }
     }
 }
@@ -0,0 +1,486 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Shell Tokenizer
+//!
+//! A small, forgiving lexer for POSIX shell snippets. It is not a full shell
+//! grammar: it only knows enough (quotes, operators, redirections, heredocs,
+//! command substitutions, loops, aliases and functions) to tell which words
+//! are in command position, which is what dependency checking needs.
+
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    /// A word after quote removal.
+    Word(String),
+    /// A control operator (`;`, `&&`, `|`, `(`, newline, ...).
+    Op(String),
+    /// A redirection operator (`>`, `2>&`, `<<`, ...). The next word is its target.
+    Redirect,
+}
+
+struct Lexer {
+    chars: Vec<char>,
+    pos: usize,
+    tokens: Vec<Token>,
+    /// Commands found inside `$(...)` and backticks.
+    nested: Vec<String>,
+    /// Heredoc delimiters waiting for the end of the current line
+    /// (delimiter, whether leading tabs are stripped).
+    heredocs: Vec<(String, bool)>,
+}
+
+impl Lexer {
+    fn new(script: &str) -> Self {
+        Self {
+            chars: script.chars().collect(),
+            pos: 0,
+            tokens: Vec::new(),
+            nested: Vec::new(),
+            heredocs: Vec::new(),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn lex(mut self) -> (Vec<Token>, Vec<String>) {
+        while let Some(c) = self.peek() {
+            match c {
+                ' ' | '\t' | '\r' => self.pos += 1,
+                '\\' if self.peek_at(1) == Some('\n') => self.pos += 2,
+                '\n' => {
+                    self.pos += 1;
+                    self.tokens.push(Token::Op("\n".to_string()));
+                    self.skip_heredoc_bodies();
+                }
+                '#' => {
+                    while self.peek().is_some_and(|c| c != '\n') {
+                        self.pos += 1;
+                    }
+                }
+                '&' if self.peek_at(1) == Some('>') => self.lex_redirect(),
+                ';' | '&' | '|' => {
+                    let mut op = c.to_string();
+                    self.pos += 1;
+                    if let Some(next) = self.peek()
+                        && (next == c || (c == '|' && next == '&'))
+                    {
+                        op.push(next);
+                        self.pos += 1;
+                    }
+                    self.tokens.push(Token::Op(op));
+                }
+                '(' if self.peek_at(1) == Some('(') => {
+                    // Arithmetic command: (( i++ ))
+                    self.pos += 2;
+                    self.skip_balanced(2);
+                }
+                '(' | ')' => {
+                    self.pos += 1;
+                    self.tokens.push(Token::Op(c.to_string()));
+                }
+                '<' | '>' => self.lex_redirect(),
+                _ => {
+                    let word = self.read_word();
+                    let is_fd = !word.is_empty() && word.chars().all(|c| c.is_ascii_digit());
+                    if is_fd && matches!(self.peek(), Some('<' | '>')) {
+                        // File descriptor of a redirection (2>&1)
+                        continue;
+                    }
+                    self.tokens.push(Token::Word(word));
+                }
+            }
+        }
+        (self.tokens, self.nested)
+    }
+
+    fn lex_redirect(&mut self) {
+        let mut op = String::new();
+        while let Some(c) = self.peek().filter(|c| matches!(c, '<' | '>' | '&' | '|')) {
+            op.push(c);
+            self.pos += 1;
+        }
+        let strip_tabs = op == "<<" && self.peek() == Some('-');
+        if strip_tabs {
+            self.pos += 1;
+        }
+
+        if op == "<<" {
+            while matches!(self.peek(), Some(' ' | '\t')) {
+                self.pos += 1;
+            }
+            let delimiter = self.read_word();
+            self.heredocs.push((delimiter, strip_tabs));
+        } else {
+            self.tokens.push(Token::Redirect);
+        }
+    }
+
+    /// Skips the bodies of heredocs opened on the line that just ended.
+    fn skip_heredoc_bodies(&mut self) {
+        for (delimiter, strip_tabs) in std::mem::take(&mut self.heredocs) {
+            while self.pos < self.chars.len() {
+                let start = self.pos;
+                while self.peek().is_some_and(|c| c != '\n') {
+                    self.pos += 1;
+                }
+                let line: String = self.chars[start..self.pos].iter().collect();
+                self.pos += 1; // newline
+                let line = line.trim_end_matches('\r');
+                let line = if strip_tabs {
+                    line.trim_start_matches('\t')
+                } else {
+                    line
+                };
+                if line == delimiter {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Skips characters until `depth` unmatched closing parentheses were consumed.
+    /// Returns the skipped text (without the final closing parentheses).
+    fn skip_balanced(&mut self, mut depth: usize) -> String {
+        let start = self.pos;
+        let mut quote = None;
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match (quote, c) {
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, '\'' | '"') => quote = Some(c),
+                (None, '(') => depth += 1,
+                (None, ')') => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        let end = self.pos.saturating_sub(1).max(start);
+        self.chars[start..end].iter().collect()
+    }
+
+    /// Handles a `$` at the current position inside a word.
+    fn read_dollar(&mut self, word: &mut String) {
+        self.pos += 1;
+        match (self.peek(), self.peek_at(1)) {
+            (Some('('), Some('(')) => {
+                self.pos += 2;
+                self.skip_balanced(2);
+                word.push_str("$((...))");
+            }
+            (Some('('), _) => {
+                self.pos += 1;
+                let inner = self.skip_balanced(1);
+                self.nested.extend(extract_commands(&inner));
+                word.push_str("$(...)");
+            }
+            (Some('{'), _) => {
+                while let Some(c) = self.peek() {
+                    self.pos += 1;
+                    if c == '}' {
+                        break;
+                    }
+                }
+                word.push_str("${...}");
+            }
+            _ => word.push('$'),
+        }
+    }
+
+    fn read_backticks(&mut self, word: &mut String) {
+        self.pos += 1;
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c != '`') {
+            self.pos += 1;
+        }
+        let inner: String = self.chars[start..self.pos].iter().collect();
+        self.pos += 1;
+        self.nested.extend(extract_commands(&inner));
+        word.push_str("$(...)");
+    }
+
+    fn read_word(&mut self) -> String {
+        let mut word = String::new();
+        while let Some(c) = self.peek() {
+            match c {
+                ' ' | '\t' | '\r' | '\n' | ';' | '&' | '|' | '(' | ')' | '<' | '>' => break,
+                '\\' => {
+                    if self.peek_at(1) == Some('\n') {
+                        break;
+                    }
+                    if let Some(next) = self.peek_at(1) {
+                        word.push(next);
+                    }
+                    self.pos += 2;
+                }
+                '\'' => {
+                    self.pos += 1;
+                    while let Some(c) = self.peek() {
+                        self.pos += 1;
+                        if c == '\'' {
+                            break;
+                        }
+                        word.push(c);
+                    }
+                }
+                '"' => {
+                    self.pos += 1;
+                    while let Some(c) = self.peek() {
+                        match c {
+                            '"' => {
+                                self.pos += 1;
+                                break;
+                            }
+                            '\\' => {
+                                if let Some(next) = self.peek_at(1) {
+                                    word.push(next);
+                                }
+                                self.pos += 2;
+                            }
+                            '$' => self.read_dollar(&mut word),
+                            '`' => self.read_backticks(&mut word),
+                            _ => {
+                                word.push(c);
+                                self.pos += 1;
+                            }
+                        }
+                    }
+                }
+                '$' => self.read_dollar(&mut word),
+                '`' => self.read_backticks(&mut word),
+                _ => {
+                    word.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+        word
+    }
+}
+
+/// Returns true for `NAME=value` words.
+fn is_assignment(word: &str) -> bool {
+    word.split_once('=').is_some_and(|(name, _)| {
+        !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
+/// Extracts the words in command position of a shell snippet, in order of
+/// appearance and without duplicates.
+///
+/// Commands defined in the snippet itself (aliases and functions) are left out.
+pub fn extract_commands(script: &str) -> Vec<String> {
+    let (tokens, nested) = Lexer::new(script).lex();
+    let mut commands = Vec::new();
+    let mut defined = HashSet::new();
+    let mut expect_command = true;
+    // Keywords that end a header whose words are not commands (for x in ...; do)
+    let mut skip_until: Option<&[&str]> = None;
+
+    let mut i = 0;
+    while i < tokens.len() {
+        let word = match &tokens[i] {
+            Token::Op(_) => {
+                expect_command = true;
+                i += 1;
+                continue;
+            }
+            Token::Redirect => {
+                // Skip the redirection target
+                i += if matches!(tokens.get(i + 1), Some(Token::Word(_))) {
+                    2
+                } else {
+                    1
+                };
+                continue;
+            }
+            Token::Word(word) => word.as_str(),
+        };
+        i += 1;
+
+        if let Some(ends) = skip_until {
+            if ends.contains(&word) {
+                skip_until = None;
+                expect_command = word == "do";
+            }
+            continue;
+        }
+        if !expect_command {
+            continue;
+        }
+
+        match word {
+            "if" | "then" | "else" | "elif" | "fi" | "do" | "done" | "while" | "until" | "esac"
+            | "!" | "{" | "}" | "time" | "exec" | "command" | "builtin" => {}
+            "for" | "select" => skip_until = Some(&["in", "do"]),
+            "case" => skip_until = Some(&["in"]),
+            "function" => {
+                if let Some(Token::Word(name)) = tokens.get(i) {
+                    defined.insert(name.clone());
+                    i += 1;
+                }
+            }
+            "sudo" => {
+                while matches!(tokens.get(i), Some(Token::Word(w)) if w.starts_with('-')) {
+                    i += 1;
+                }
+            }
+            "alias" => {
+                while let Some(Token::Word(definition)) = tokens.get(i) {
+                    if let Some((name, value)) = definition.split_once('=') {
+                        defined.insert(name.to_string());
+                        commands.extend(extract_commands(value));
+                    }
+                    i += 1;
+                }
+                expect_command = false;
+            }
+            w if is_assignment(w) => {}
+            w => {
+                let is_function_definition = tokens.get(i) == Some(&Token::Op("(".to_string()))
+                    && tokens.get(i + 1) == Some(&Token::Op(")".to_string()));
+                if is_function_definition {
+                    defined.insert(w.to_string());
+                    i += 2;
+                } else {
+                    commands.push(w.to_string());
+                    expect_command = false;
+                }
+            }
+        }
+    }
+
+    commands.extend(nested);
+
+    let mut seen = HashSet::new();
+    commands.retain(|c| !c.is_empty() && !defined.contains(c) && seen.insert(c.clone()));
+    commands
+}
+
+/// Splits a shell snippet into its simple commands: the words of each one,
+/// after quote removal, without redirections, leading `NAME=value`
+/// assignments, `sudo` and its options, or shell keywords.
+///
+/// Unlike [`extract_commands`], commands inside `$(...)` are left out.
+pub fn simple_commands(script: &str) -> Vec<Vec<String>> {
+    let (tokens, _) = Lexer::new(script).lex();
+    let mut commands = Vec::new();
+    let mut words: Vec<String> = Vec::new();
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Op(_) => {
+                if !words.is_empty() {
+                    commands.push(std::mem::take(&mut words));
+                }
+            }
+            Token::Redirect => {
+                tokens.next_if(|t| matches!(t, Token::Word(_)));
+            }
+            Token::Word(word) => {
+                let leading = words.is_empty()
+                    && (is_assignment(&word)
+                        || matches!(
+                            word.as_str(),
+                            "if" | "then"
+                                | "else"
+                                | "elif"
+                                | "fi"
+                                | "do"
+                                | "done"
+                                | "while"
+                                | "until"
+                                | "esac"
+                                | "!"
+                                | "{"
+                                | "}"
+                                | "time"
+                                | "exec"
+                                | "command"
+                        ));
+                if leading {
+                    continue;
+                }
+                if words.is_empty() && word == "sudo" {
+                    while tokens
+                        .next_if(|t| matches!(t, Token::Word(w) if w.starts_with('-')))
+                        .is_some()
+                    {}
+                    continue;
+                }
+                words.push(word);
+            }
+        }
+    }
+    if !words.is_empty() {
+        commands.push(words);
+    }
+    commands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chains_and_redirections() {
+        let cmds =
+            extract_commands("FOO=1 cargo build 2>&1 | tee out.log && sudo -E apt install -y git");
+        assert_eq!(cmds, ["cargo", "tee", "apt"]);
+    }
+
+    #[test]
+    fn test_substitutions_and_loops() {
+        let script = "for f in $(ls *.md); do\n  wc -l \"$f\"\ndone\necho \"`whoami`\"";
+        assert_eq!(extract_commands(script), ["wc", "echo", "ls", "whoami"]);
+    }
+
+    #[test]
+    fn test_heredoc_and_continuation() {
+        let script = "cat <<EOF > config.toml\nname = \"x\"\nrun this\nEOF\ndocker run \\\n  --rm \\\n  -it ubuntu";
+        assert_eq!(extract_commands(script), ["cat", "docker"]);
+    }
+
+    #[test]
+    fn test_simple_commands() {
+        let script = "cd infra && FOO=1 sudo -E terraform apply -var \"name=my app\" > out.log\nif kubectl get ns; then echo ok; fi";
+        assert_eq!(
+            simple_commands(script),
+            [
+                vec!["cd", "infra"],
+                vec!["terraform", "apply", "-var", "name=my app"],
+                vec!["kubectl", "get", "ns"],
+                vec!["echo", "ok"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_aliases_and_functions() {
+        let script = "alias ll='ls -la'\nll\nsetup() { mkdir -p out; }\nsetup";
+        assert_eq!(extract_commands(script), ["ls", "mkdir"]);
+    }
+}
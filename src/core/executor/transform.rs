@@ -0,0 +1,54 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Command transformers: plugins rewriting a code block right before it runs
+//! (adding flags, wrapping it in an organization's tooling). The rewritten
+//! command goes through the usual dependency and safety checks.
+
+use crate::core::plugins::Registry;
+use std::sync::{Arc, LazyLock};
+
+/// Rewrites commands before they run.
+pub trait CommandTransformer: Send + Sync {
+    /// Name of the transformer, for logs.
+    fn name(&self) -> &str;
+    /// The command to run instead of `command`, or `None` to keep it.
+    fn transform(&self, command: &str, language: Option<&str>) -> Option<String>;
+}
+
+static TRANSFORMERS: LazyLock<Registry<dyn CommandTransformer>> =
+    LazyLock::new(|| Registry::new(Vec::new()));
+
+/// Adds a transformer, applied after those registered earlier.
+pub fn register_command_transformer(transformer: Arc<dyn CommandTransformer>) {
+    TRANSFORMERS.register(transformer);
+}
+
+/// Applies the transformers to a command, in the order they were registered.
+pub fn transform(command: &str, language: Option<&str>) -> String {
+    TRANSFORMERS
+        .entries()
+        .iter()
+        .rev()
+        .fold(
+            command.to_string(),
+            |command, transformer| match transformer.transform(&command, language) {
+                Some(rewritten) => {
+                    tracing::info!(transformer = transformer.name(), "Command rewritten");
+                    rewritten
+                }
+                None => command,
+            },
+        )
+}
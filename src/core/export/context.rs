@@ -0,0 +1,150 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Snapshots of the execution context: the working directory, the environment
+//! and the placeholder values of a session. Saved from the TUI, they let
+//! someone else (or a later session) pick up a half-finished runbook with
+//! `compass run --context` or `compass tui --context`.
+
+use crate::core::executor::engine::context::ExecutionContext;
+use anyhow::{Context, Result};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The context of a session, as saved to a file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextExport {
+    /// Version of Compass that saved the context.
+    pub compass_version: String,
+    /// When the context was saved (RFC 3339).
+    pub exported_at: String,
+    /// The runbook being followed, for information.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runbook: Option<String>,
+    /// The working directory of the steps.
+    pub current_dir: PathBuf,
+    /// Environment variables set during the session (`export`, prompts).
+    #[serde(default)]
+    pub env_vars: BTreeMap<String, String>,
+    /// Values given to placeholders.
+    #[serde(default)]
+    pub placeholders: BTreeMap<String, String>,
+}
+
+impl ContextExport {
+    /// Captures the context of a session following `runbook`.
+    #[must_use]
+    pub fn capture(
+        context: &ExecutionContext,
+        placeholders: &HashMap<String, String>,
+        runbook: &Path,
+        version: &str,
+    ) -> Self {
+        Self {
+            compass_version: version.to_string(),
+            exported_at: Local::now().to_rfc3339(),
+            runbook: Some(runbook.display().to_string()),
+            current_dir: context.current_dir.clone(),
+            env_vars: context.env_vars.clone().into_iter().collect(),
+            placeholders: placeholders.clone().into_iter().collect(),
+        }
+    }
+
+    /// Default file name for a context saved now, in `base_dir`.
+    #[must_use]
+    pub fn default_path(base_dir: &Path) -> PathBuf {
+        let timestamp = Local::now().format("%Y%m%d_%H%M%S");
+        base_dir.join(format!("compass-context_{timestamp}.json"))
+    }
+
+    /// Writes the context as JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self).context("Failed to serialize context")?;
+        fs::write(path, content)
+            .with_context(|| format!("Failed to write context: {}", path.display()))
+    }
+
+    /// Reads a context saved by [`ContextExport::save`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a saved context.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read context: {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Not a Compass context file: {}", path.display()))
+    }
+
+    /// Sets the environment of `context`, and its working directory if it
+    /// exists on this machine. Returns a warning otherwise.
+    pub fn apply(&self, context: &mut ExecutionContext) -> Option<String> {
+        context.env_vars.extend(self.env_vars.clone());
+        if self.current_dir.is_dir() {
+            context.current_dir.clone_from(&self.current_dir);
+            None
+        } else {
+            Some(format!(
+                "The saved working directory {} does not exist here; staying in {}.",
+                self.current_dir.display(),
+                context.current_dir.display()
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_load_apply() -> Result<()> {
+        let dir = std::env::temp_dir().join("compass_test_context");
+        fs::create_dir_all(&dir)?;
+        let mut context = ExecutionContext::new();
+        context.current_dir.clone_from(&dir);
+        context
+            .env_vars
+            .insert("STAGE".to_string(), "prod".to_string());
+        let placeholders = HashMap::from([("HOST".to_string(), "db1".to_string())]);
+
+        let path = dir.join("ctx.json");
+        ContextExport::capture(&context, &placeholders, Path::new("README.md"), "1.0.0")
+            .save(&path)?;
+        let saved = ContextExport::load(&path)?;
+        assert_eq!(saved.placeholders["HOST"], "db1");
+
+        let mut other = ExecutionContext::new();
+        assert_eq!(saved.apply(&mut other), None);
+        assert_eq!(other.current_dir, dir);
+        assert_eq!(other.env_vars["STAGE"], "prod");
+
+        let moved = ContextExport {
+            current_dir: dir.join("missing"),
+            ..saved
+        };
+        assert!(moved.apply(&mut other).is_some());
+        assert_eq!(other.current_dir, dir);
+
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
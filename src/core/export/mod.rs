@@ -18,19 +18,29 @@
 //! into various formats (JSON and Markdown). This is essential for debugging,
 //! sharing session results, and onboarding support.
 //!
+//! The `runbook` submodule exports the runbook itself to standalone formats
+//! such as shell scripts, and `context` saves the execution context so that a
+//! session can be continued elsewhere.
+//!
 //! ## Extensibility
 //!
-//! New export formats can be added by creating a new module in `formats/`
-//! and calling it from the `Exporter` struct.
+//! New session formats can be added by creating a new module in `formats/`
+//! and calling it from the `Exporter` struct. Runbook formats are
+//! [`runbook::RunbookExporter`]s, registered with
+//! [`runbook::register_exporter`] (see [`crate::core::plugins`]).
 
+pub mod context;
 pub mod formats;
 pub mod models;
+pub mod runbook;
 
+use crate::core::executor::security::redact::{MASK, Redactor};
 use crate::core::models::{Step, StepStatus};
 use anyhow::Result;
 use chrono::{Local, Utc};
 use models::{
-    EnvironmentInfo, ExportReport, ExportedCodeBlock, ExportedStep, ReportMetadata, ReportSummary,
+    EnvironmentInfo, ExportReport, ExportedAttempt, ExportedCodeBlock, ExportedStep,
+    ReportMetadata, ReportSummary,
 };
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -47,7 +57,8 @@ impl Exporter {
     /// * `readme_path` - The path to the README file.
     /// * `current_dir` - The current working directory.
     /// * `env_vars` - Environment variables set during the session.
-    /// * `placeholders` - Placeholder values provided by the user.
+    /// * `placeholders` - Placeholder values provided by the user. Those of
+    ///   secret placeholders are masked, here and in the outputs.
     /// * `version` - The Compass version string.
     ///
     /// # Returns
@@ -62,6 +73,14 @@ impl Exporter {
         placeholders: &HashMap<String, String>,
         version: &str,
     ) -> ExportReport {
+        let secrets: Vec<&String> = steps.iter().flat_map(|s| &s.secrets).collect();
+        let redactor = Redactor::new(
+            secrets
+                .iter()
+                .filter_map(|name| placeholders.get(*name))
+                .cloned(),
+        );
+
         // Convert steps to exportable format
         let exported_steps: Vec<ExportedStep> = steps
             .iter()
@@ -71,6 +90,10 @@ impl Exporter {
                 title: step.title.clone(),
                 description: step.description.clone(),
                 status: Self::status_to_string(step.status),
+                started_at: step.execution.as_ref().map(|e| e.started_at.clone()),
+                finished_at: step.execution.as_ref().map(|e| e.finished_at.clone()),
+                duration_ms: step.execution.as_ref().map(|e| e.duration_ms),
+                exit_code: step.execution.as_ref().and_then(|e| e.exit_code),
                 code_blocks: step
                     .code_blocks
                     .iter()
@@ -79,7 +102,23 @@ impl Exporter {
                         content: b.content.clone(),
                     })
                     .collect(),
-                output: step.output.clone(),
+                output: redactor.redact(&step.output),
+                stdout: redactor.redact(&step.stdout),
+                stderr: redactor.redact(&step.stderr),
+                session_only: step.session_only,
+                attempts: step
+                    .history
+                    .iter()
+                    .enumerate()
+                    .map(|(n, run)| ExportedAttempt {
+                        number: n + 1,
+                        status: Self::status_to_string(run.status),
+                        duration_ms: run.duration_ms,
+                        exit_code: run.exit_code,
+                        output: redactor.redact(&run.output),
+                    })
+                    .collect(),
+                env_changes: step.context_diff.clone(),
             })
             .collect();
 
@@ -92,7 +131,7 @@ impl Exporter {
             .count();
         let failed_steps = executable_steps
             .iter()
-            .filter(|s| s.status == StepStatus::Failed)
+            .filter(|s| s.status.is_failure())
             .count();
         let running_steps = executable_steps
             .iter()
@@ -103,6 +142,12 @@ impl Exporter {
             .filter(|s| s.status == StepStatus::Pending)
             .count();
 
+        let total_duration_ms = steps
+            .iter()
+            .flat_map(|s| &s.history)
+            .filter_map(|run| run.duration_ms)
+            .sum();
+
         #[allow(clippy::cast_precision_loss)]
         let completion_percentage = if total_steps > 0 {
             (completed_steps as f32 / total_steps as f32) * 100.0
@@ -116,6 +161,7 @@ impl Exporter {
                 generated_at: Utc::now().to_rfc3339(),
                 generated_at_local: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
                 readme_path: readme_path.to_string_lossy().to_string(),
+                observed_session: None,
             },
             summary: ReportSummary {
                 total_steps,
@@ -124,12 +170,26 @@ impl Exporter {
                 pending_steps,
                 running_steps,
                 completion_percentage,
+                total_duration_ms,
             },
             steps: exported_steps,
             environment: EnvironmentInfo {
                 current_dir: current_dir.to_string_lossy().to_string(),
-                env_vars: env_vars.clone(),
-                placeholders: placeholders.clone(),
+                env_vars: env_vars
+                    .iter()
+                    .map(|(name, value)| (name.clone(), redactor.redact(value)))
+                    .collect(),
+                placeholders: placeholders
+                    .iter()
+                    .map(|(name, value)| {
+                        let value = if secrets.contains(&name) {
+                            MASK.to_string()
+                        } else {
+                            value.clone()
+                        };
+                        (name.clone(), value)
+                    })
+                    .collect(),
             },
         }
     }
@@ -142,6 +202,8 @@ impl Exporter {
             StepStatus::Success => "✅ Success".to_string(),
             StepStatus::Failed => "❌ Failed".to_string(),
             StepStatus::Skipped => "🚫 Skipped".to_string(),
+            StepStatus::Cancelled => "⛔ Cancelled".to_string(),
+            StepStatus::TimedOut => "⏱️ Timed out".to_string(),
         }
     }
 
@@ -222,7 +284,9 @@ impl Exporter {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::models::CodeBlock;
+    use crate::core::models::{
+        CodeBlock, ContextDiff, ExecutionRecord, MAX_STEP_HISTORY, StepRun, VarChange,
+    };
     use std::fs;
 
     fn create_test_steps() -> Vec<Step> {
@@ -234,10 +298,34 @@ mod tests {
                     language: Some("bash".to_string()),
                     content: "npm install".to_string(),
                     placeholders: vec![],
+                    write_to: None,
+                    indented: false,
                 }],
                 status: StepStatus::Success,
                 output: "added 1234 packages".to_string(),
                 condition: None,
+                execution: Some(ExecutionRecord {
+                    started_at: "2026-01-01T10:00:00+00:00".to_string(),
+                    finished_at: "2026-01-01T10:00:01.500+00:00".to_string(),
+                    duration_ms: 1500,
+                    exit_code: Some(0),
+                }),
+                history: vec![StepRun {
+                    status: StepStatus::Success,
+                    duration_ms: Some(1500),
+                    exit_code: Some(0),
+                    output: "added 1234 packages".to_string(),
+                }],
+                context_diff: Some(ContextDiff {
+                    previous_dir: Some("/project".to_string()),
+                    current_dir: Some("/project/web".to_string()),
+                    variables: vec![VarChange {
+                        name: "NODE_ENV".to_string(),
+                        before: None,
+                        after: Some("production".to_string()),
+                    }],
+                }),
+                ..Default::default()
             },
             Step {
                 title: "Configure Environment".to_string(),
@@ -246,10 +334,13 @@ mod tests {
                     language: Some("bash".to_string()),
                     content: "export API_KEY=<API_KEY>".to_string(),
                     placeholders: vec!["API_KEY".to_string()],
+                    write_to: None,
+                    indented: false,
                 }],
                 status: StepStatus::Pending,
                 output: String::new(),
                 condition: None,
+                ..Default::default()
             },
         ]
     }
@@ -270,6 +361,64 @@ mod tests {
         assert_eq!(report.summary.completed_steps, 1);
         assert_eq!(report.summary.pending_steps, 1);
         assert!((report.summary.completion_percentage - 50.0).abs() < f32::EPSILON);
+        assert_eq!(report.summary.total_duration_ms, 1500);
+        assert_eq!(report.steps[0].duration_ms, Some(1500));
+        assert_eq!(report.steps[0].exit_code, Some(0));
+        assert_eq!(report.steps[1].started_at, None);
+        assert_eq!(report.steps[1].exit_code, None);
+    }
+
+    #[test]
+    fn test_bounded_history_exported_as_attempts() {
+        let mut steps = create_test_steps();
+        let step = &mut steps[1];
+        step.history.clear();
+        for n in 0..MAX_STEP_HISTORY + 2 {
+            step.status = StepStatus::Failed;
+            step.output = format!("run {n}");
+            step.record_run(Some(n as u64), Some(1));
+        }
+        assert_eq!(step.history.len(), MAX_STEP_HISTORY);
+
+        let report = Exporter::generate_report(
+            &steps,
+            Path::new("README.md"),
+            Path::new("/project"),
+            &HashMap::new(),
+            &HashMap::new(),
+            "1.0.0",
+        );
+        let attempts = &report.steps[1].attempts;
+        assert_eq!(attempts.len(), MAX_STEP_HISTORY);
+        assert_eq!(attempts[0].number, 1);
+        assert_eq!(attempts[0].output, "run 2");
+        assert_eq!(attempts[0].duration_ms, Some(2));
+        assert!(attempts[0].status.contains("Failed"));
+        assert_eq!(
+            attempts[MAX_STEP_HISTORY - 1].output,
+            format!("run {}", MAX_STEP_HISTORY + 1)
+        );
+    }
+
+    #[test]
+    fn test_generate_report_masks_secrets() {
+        let mut steps = create_test_steps();
+        steps[1].secrets = vec!["API_KEY".to_string()];
+        steps[1].output = "using key sk-live-42".to_string();
+        let placeholders = HashMap::from([("API_KEY".to_string(), "sk-live-42".to_string())]);
+        let env_vars = HashMap::from([("API_KEY".to_string(), "sk-live-42".to_string())]);
+        let report = Exporter::generate_report(
+            &steps,
+            Path::new("README.md"),
+            Path::new("/project"),
+            &env_vars,
+            &placeholders,
+            "1.0.0",
+        );
+
+        assert_eq!(report.steps[1].output, format!("using key {MASK}"));
+        assert_eq!(report.environment.placeholders["API_KEY"], MASK);
+        assert_eq!(report.environment.env_vars["API_KEY"], MASK);
     }
 
     #[test]
@@ -298,6 +447,8 @@ mod tests {
         assert!(content.contains("Completed | 1"));
         assert!(content.contains("Install Dependencies"));
         assert!(content.contains("npm install"));
+        assert!(content.contains("`cd /project` → `/project/web`"));
+        assert!(content.contains("- set `NODE_ENV=production`"));
 
         // Cleanup
         let _ = fs::remove_file(output_path);
@@ -305,6 +456,32 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_export_observer_copy() -> Result<()> {
+        let mut env_vars = HashMap::new();
+        env_vars.insert("GUEST_ONLY".to_string(), "1".to_string());
+        let report = Exporter::generate_report(
+            &create_test_steps(),
+            Path::new("REMOTE_SESSION"),
+            Path::new("/guest"),
+            &env_vars,
+            &HashMap::new(),
+            "1.0.0",
+        )
+        .into_observer_copy("wss://10.0.0.1:3030");
+
+        let output_path = std::env::temp_dir().join("test_observer_report.md");
+        Exporter::export_markdown(&report, &output_path)?;
+        let content = fs::read_to_string(&output_path)?;
+        let _ = fs::remove_file(output_path);
+
+        assert!(content.contains("Observer copy"));
+        assert!(content.contains("wss://10.0.0.1:3030"));
+        assert!(!content.contains("GUEST_ONLY"));
+        assert!(!content.contains("/guest"));
+        Ok(())
+    }
+
     #[test]
     fn test_status_to_string() {
         assert!(Exporter::status_to_string(StepStatus::Success).contains("Success"));
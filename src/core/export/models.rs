@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::core::models::ContextDiff;
 use serde::Serialize;
 use std::collections::HashMap;
 
@@ -26,10 +27,48 @@ pub struct ExportedStep {
     pub description: String,
     /// The status of the step as a string.
     pub status: String,
+    /// When the last run started (RFC 3339), if the step ran.
+    pub started_at: Option<String>,
+    /// When the last run ended (RFC 3339), if the step ran.
+    pub finished_at: Option<String>,
+    /// How long the last run took in milliseconds, if the step ran.
+    pub duration_ms: Option<u64>,
+    /// Exit code of the last command of the last run, if it exited normally.
+    pub exit_code: Option<u32>,
     /// The code blocks in this step.
     pub code_blocks: Vec<ExportedCodeBlock>,
     /// The captured output from execution.
     pub output: String,
+    /// What the last execution wrote to stdout, when run with separate streams.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub stdout: String,
+    /// What the last execution wrote to stderr, when run with separate streams.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub stderr: String,
+    /// Whether the step was added or copied during the session, and is not
+    /// in the runbook.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub session_only: bool,
+    /// All runs of this step in the session, oldest first.
+    pub attempts: Vec<ExportedAttempt>,
+    /// What the last run changed in the working directory and environment.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env_changes: Option<ContextDiff>,
+}
+
+/// Represents a past run of a step in the exported report.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportedAttempt {
+    /// The attempt number (1-indexed).
+    pub number: usize,
+    /// The final status of the run as a string.
+    pub status: String,
+    /// How long the run took in milliseconds, if known.
+    pub duration_ms: Option<u64>,
+    /// Exit code of the last command of the run, if it exited normally.
+    pub exit_code: Option<u32>,
+    /// The output captured during the run.
+    pub output: String,
 }
 
 /// Represents a code block in the exported report.
@@ -54,6 +93,20 @@ pub struct ExportReport {
     pub environment: EnvironmentInfo,
 }
 
+impl ExportReport {
+    /// Marks the report as a guest's observer copy of a live session.
+    ///
+    /// The working directory and environment variables are the guest's own,
+    /// not the host's, so they are left out.
+    #[must_use]
+    pub fn into_observer_copy(mut self, session: &str) -> Self {
+        self.metadata.observed_session = Some(session.to_string());
+        self.environment.current_dir = String::new();
+        self.environment.env_vars.clear();
+        self
+    }
+}
+
 /// Metadata about the report itself.
 #[derive(Debug, Clone, Serialize)]
 pub struct ReportMetadata {
@@ -65,6 +118,10 @@ pub struct ReportMetadata {
     pub generated_at_local: String,
     /// The path to the README being processed.
     pub readme_path: String,
+    /// The live session this report was replicated from, when exported by a
+    /// collaboration guest rather than the host that ran the steps.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub observed_session: Option<String>,
 }
 
 /// Summary statistics of the session.
@@ -82,6 +139,8 @@ pub struct ReportSummary {
     pub running_steps: usize,
     /// Completion percentage.
     pub completion_percentage: f32,
+    /// Time spent running steps, every run included, in milliseconds.
+    pub total_duration_ms: u64,
 }
 
 /// Environment information captured at export time.
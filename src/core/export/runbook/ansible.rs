@@ -0,0 +1,187 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{replace_placeholders, step_script, variable_name};
+use crate::core::executor::conditions::evaluator::arch_names;
+use crate::core::manifest::RunbookManifest;
+use crate::core::models::{CodeBlock, Condition, Step};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// A play running the runbook on the local machine.
+#[derive(Serialize)]
+struct Play {
+    name: String,
+    hosts: &'static str,
+    connection: &'static str,
+    gather_facts: bool,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    vars: BTreeMap<String, String>,
+    tasks: Vec<Task>,
+}
+
+/// A step run through the shell module.
+#[derive(Serialize)]
+struct Task {
+    name: String,
+    #[serde(rename = "ansible.builtin.shell")]
+    shell: String,
+    args: TaskArgs,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    when: Option<String>,
+}
+
+#[derive(Serialize)]
+struct TaskArgs {
+    executable: &'static str,
+}
+
+/// Ansible playbook running on localhost.
+pub struct AnsibleExporter;
+
+impl super::RunbookExporter for AnsibleExporter {
+    fn name(&self) -> &'static str {
+        "ansible"
+    }
+
+    fn render(
+        &self,
+        steps: &[Step],
+        _manifest: Option<&RunbookManifest>,
+        source: &str,
+    ) -> Result<String> {
+        render(steps, source)
+    }
+}
+
+/// Renders the executable steps of a runbook as an Ansible playbook.
+///
+/// Each step becomes a `shell` task, conditions become `when:` clauses and
+/// placeholders become play variables (set them with `-e NAME=value`).
+///
+/// # Errors
+///
+/// Returns an error if the playbook cannot be serialized.
+pub fn render(steps: &[Step], source: &str) -> Result<String> {
+    let mut vars = BTreeMap::new();
+    let mut tasks = Vec::new();
+
+    for (n, step) in steps.iter().filter(|s| s.is_executable()).enumerate() {
+        for name in step.code_blocks.iter().flat_map(|b| &b.placeholders) {
+            vars.entry(variable_name(name)).or_default();
+        }
+        tasks.push(Task {
+            name: format!("{}. {}", n + 1, step.title.trim()),
+            shell: step_script(step, template),
+            args: TaskArgs {
+                executable: "/bin/bash",
+            },
+            when: step.condition.as_ref().map(when),
+        });
+    }
+
+    let play = Play {
+        name: format!("Compass runbook {source}"),
+        hosts: "localhost",
+        connection: "local",
+        gather_facts: steps
+            .iter()
+            .flat_map(|s| s.condition.iter().flat_map(Condition::predicates))
+            .any(|c| matches!(c, Condition::Os(_) | Condition::Arch(_))),
+        vars,
+        tasks,
+    };
+    let yaml = serde_yaml::to_string(&[play]).context("Failed to serialize playbook")?;
+    Ok(format!("# Generated by Compass from {source}.\n{yaml}"))
+}
+
+/// Escapes Jinja syntax in a block and turns its placeholders into variables.
+fn template(block: &CodeBlock) -> String {
+    // Placeholders go through a marker so their braces are not escaped
+    const MARKER: &str = "\u{0}COMPASS\u{0}";
+    replace_placeholders(block, |name| {
+        format!("{MARKER}{}{MARKER}", variable_name(name))
+    })
+    .replace("{{", "{{ '{{' }}")
+    .replace("{%", "{{ '{%' }}")
+    .replace("{#", "{{ '{#' }}")
+    .split(MARKER)
+    .enumerate()
+    .map(|(i, part)| {
+        if i % 2 == 1 {
+            format!("{{{{ {part} }}}}")
+        } else {
+            part.to_string()
+        }
+    })
+    .collect()
+}
+
+/// Builds the `when:` expression of a condition.
+fn when(condition: &Condition) -> String {
+    match condition {
+        Condition::Os(os) => match os.to_ascii_lowercase().as_str() {
+            "macos" => "ansible_system == 'Darwin'".to_string(),
+            "windows" => "ansible_os_family == 'Windows'".to_string(),
+            other => format!("ansible_system | lower == '{other}'"),
+        },
+        Condition::Arch(arch) => {
+            let names: Vec<String> = arch_names(arch)
+                .into_iter()
+                .map(|name| format!("'{name}'"))
+                .collect();
+            format!("ansible_architecture in [{}]", names.join(", "))
+        }
+        Condition::EnvVarExists(var) => format!("lookup('env', '{var}') | length > 0"),
+        Condition::FileExists(path) => format!("'{path}' is exists"),
+        Condition::CommandExists(command) => {
+            format!("lookup('pipe', 'command -v {command} || true') | length > 0")
+        }
+        Condition::Not(inner) => format!("not ({})", when(inner)),
+        Condition::All(conditions) => grouped(conditions, " and "),
+        Condition::Any(conditions) => grouped(conditions, " or "),
+    }
+}
+
+/// The `when:` expressions of `conditions` joined by `operator`, those
+/// combining others in parentheses.
+fn grouped(conditions: &[Condition], operator: &str) -> String {
+    conditions
+        .iter()
+        .map(|c| match c {
+            Condition::All(_) | Condition::Any(_) => format!("({})", when(c)),
+            _ => when(c),
+        })
+        .collect::<Vec<_>>()
+        .join(operator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_readme;
+
+    #[test]
+    fn test_render_playbook() {
+        let content = "<!-- compass:if os=\"linux\" -->\n# Run\n```bash\ndocker ps --format '{{.Names}}' <NAME>\n```\n";
+        let (steps, _) = parse_readme(content);
+        let playbook = render(&steps, "README.md").unwrap();
+
+        assert!(playbook.contains("name: 1. Run"));
+        assert!(playbook.contains("NAME: ''"));
+        assert!(playbook.contains("when: ansible_system | lower == 'linux'"));
+        assert!(playbook.contains("'{{ '{{' }}.Names}}' {{ NAME }}"));
+    }
+}
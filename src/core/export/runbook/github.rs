@@ -0,0 +1,253 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::shell::guard;
+use super::{quote, replace_placeholders, step_script, variable_name};
+use crate::core::executor::conditions::evaluator::arch_names;
+use crate::core::manifest::RunbookManifest;
+use crate::core::models::{Condition, Step};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Matrix axes declared in the runbook frontmatter.
+pub type Matrix = BTreeMap<String, Vec<serde_yaml::Value>>;
+
+#[derive(Serialize)]
+struct Workflow {
+    name: String,
+    on: Triggers,
+    jobs: BTreeMap<&'static str, Job>,
+}
+
+#[derive(Serialize)]
+struct Triggers {
+    push: BTreeMap<String, String>,
+    workflow_dispatch: Dispatch,
+}
+
+#[derive(Serialize)]
+struct Dispatch {
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    inputs: BTreeMap<String, Input>,
+}
+
+#[derive(Serialize)]
+struct Input {
+    description: String,
+    required: bool,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    kind: Option<&'static str>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    options: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct Job {
+    #[serde(rename = "runs-on")]
+    runs_on: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    strategy: Option<Strategy>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    env: BTreeMap<String, String>,
+    steps: Vec<WorkflowStep>,
+}
+
+#[derive(Serialize)]
+struct Strategy {
+    matrix: Matrix,
+}
+
+#[derive(Serialize)]
+struct WorkflowStep {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uses: Option<&'static str>,
+    #[serde(rename = "if", skip_serializing_if = "Option::is_none")]
+    condition: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shell: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    run: Option<String>,
+}
+
+/// GitHub Actions workflow, with the frontmatter `matrix` as job matrix.
+pub struct GithubExporter;
+
+impl super::RunbookExporter for GithubExporter {
+    fn name(&self) -> &'static str {
+        "github"
+    }
+
+    fn render(
+        &self,
+        steps: &[Step],
+        manifest: Option<&RunbookManifest>,
+        source: &str,
+    ) -> Result<String> {
+        let matrix = manifest.map(|m| m.matrix.clone()).unwrap_or_default();
+        render(steps, &matrix, source)
+    }
+}
+
+/// Renders the executable steps of a runbook as a GitHub Actions workflow.
+///
+/// Placeholders become `workflow_dispatch` inputs (falling back to repository
+/// variables on push, and offering their values for choice placeholders),
+/// conditions become `if:` expressions and the frontmatter
+/// `matrix` becomes the job matrix (an `os` axis selects the runner).
+///
+/// # Errors
+///
+/// Returns an error if the workflow cannot be serialized.
+pub fn render(steps: &[Step], matrix: &Matrix, source: &str) -> Result<String> {
+    let mut inputs = BTreeMap::new();
+    let mut env = BTreeMap::new();
+    let mut workflow_steps = vec![WorkflowStep {
+        name: "Checkout".to_string(),
+        uses: Some("actions/checkout@v4"),
+        condition: None,
+        shell: None,
+        run: None,
+    }];
+
+    for (n, step) in steps.iter().filter(|s| s.is_executable()).enumerate() {
+        for name in step.code_blocks.iter().flat_map(|b| &b.placeholders) {
+            let var = variable_name(name);
+            env.entry(var.clone())
+                .or_insert_with(|| format!("${{{{ inputs.{var} || vars.{var} }}}}"));
+            let options = step.choices.get(name).cloned().unwrap_or_default();
+            inputs.entry(var).or_insert_with(|| Input {
+                description: format!("Value of <{name}>"),
+                required: false,
+                kind: (!options.is_empty()).then_some("choice"),
+                options,
+            });
+        }
+        let mut run = step_script(step, |block| {
+            replace_placeholders(block, |name| {
+                format!("${{{{ env.{} }}}}", variable_name(name))
+            })
+        });
+        let mut condition = step.condition.as_ref().map(expression);
+        // Expressions cannot look for a command: the script tests the condition
+        if let Some(tested) = step.condition.as_ref().filter(|c| {
+            c.predicates()
+                .iter()
+                .any(|p| matches!(p, Condition::CommandExists(_)))
+        }) {
+            condition = None;
+            run = format!(
+                "if {}; then\n{run}else\n  echo {}\nfi\n",
+                guard(tested),
+                quote(&format!("Skipped: requires {tested}"))
+            );
+        }
+        workflow_steps.push(WorkflowStep {
+            name: format!("{}. {}", n + 1, step.title.trim()),
+            uses: None,
+            condition,
+            shell: Some("bash"),
+            run: Some(run),
+        });
+    }
+
+    let runs_on = if matrix.contains_key("os") {
+        "${{ matrix.os }}".to_string()
+    } else {
+        "ubuntu-latest".to_string()
+    };
+    let job = Job {
+        runs_on,
+        strategy: (!matrix.is_empty()).then(|| Strategy {
+            matrix: matrix.clone(),
+        }),
+        env,
+        steps: workflow_steps,
+    };
+    let workflow = Workflow {
+        name: format!("Compass runbook {source}"),
+        on: Triggers {
+            push: BTreeMap::new(),
+            workflow_dispatch: Dispatch { inputs },
+        },
+        jobs: BTreeMap::from([("runbook", job)]),
+    };
+    let yaml = serde_yaml::to_string(&workflow).context("Failed to serialize workflow")?;
+    Ok(format!("# Generated by Compass from {source}.\n{yaml}"))
+}
+
+/// Builds the `if:` expression of a condition.
+fn expression(condition: &Condition) -> String {
+    match condition {
+        Condition::Os(os) => {
+            let runner_os = match os.to_ascii_lowercase().as_str() {
+                "macos" => "macOS".to_string(),
+                "windows" => "Windows".to_string(),
+                "linux" => "Linux".to_string(),
+                other => other.to_string(),
+            };
+            format!("runner.os == '{runner_os}'")
+        }
+        Condition::Arch(arch) => {
+            let runner_arch = match arch_names(arch)[0] {
+                "aarch64" => "ARM64".to_string(),
+                "x86_64" => "X64".to_string(),
+                "x86" => "X86".to_string(),
+                other => other.to_uppercase(),
+            };
+            format!("runner.arch == '{runner_arch}'")
+        }
+        Condition::EnvVarExists(var) => format!("env.{var} != ''"),
+        Condition::FileExists(path) => format!("hashFiles('{path}') != ''"),
+        // Tested by the script of the step instead (see `render`)
+        Condition::CommandExists(_) => "true".to_string(),
+        Condition::Not(inner) => format!("!({})", expression(inner)),
+        Condition::All(conditions) => grouped(conditions, " && "),
+        Condition::Any(conditions) => grouped(conditions, " || "),
+    }
+}
+
+/// The expressions of `conditions` joined by `operator`, those combining
+/// others in parentheses.
+fn grouped(conditions: &[Condition], operator: &str) -> String {
+    conditions
+        .iter()
+        .map(|c| match c {
+            Condition::All(_) | Condition::Any(_) => format!("({})", expression(c)),
+            _ => expression(c),
+        })
+        .collect::<Vec<_>>()
+        .join(operator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_readme;
+
+    #[test]
+    fn test_render_workflow() {
+        let content = "---\nmatrix:\n  os: [ubuntu-latest, macos-latest]\n---\n<!-- compass:if os=\"macos\" -->\n# Brew\n```bash\nbrew install <PKG>\n```\n";
+        let (steps, hooks) = parse_readme(content);
+        let matrix = hooks.map(|h| h.matrix).unwrap_or_default();
+        let workflow = render(&steps, &matrix, "README.md").unwrap();
+
+        assert!(workflow.contains("runs-on: ${{ matrix.os }}"));
+        assert!(workflow.contains("- macos-latest"));
+        assert!(workflow.contains("if: runner.os == 'macOS'"));
+        assert!(workflow.contains("PKG: ${{ inputs.PKG || vars.PKG }}"));
+        assert!(workflow.contains("brew install ${{ env.PKG }}"));
+    }
+}
@@ -0,0 +1,213 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Runbook Export
+//!
+//! Converts the runbook itself (not a session) into formats that run on
+//! machines without Compass.
+
+pub mod ansible;
+pub mod github;
+pub mod shell;
+
+use crate::core::executor::languages::get_language_handler;
+use crate::core::manifest::RunbookManifest;
+use crate::core::models::{CodeBlock, Step};
+use crate::core::parser::{PLACEHOLDERS, placeholder_name};
+use crate::core::plugins::Registry;
+use anyhow::Result;
+use std::fmt::Write;
+use std::sync::{Arc, LazyLock};
+
+/// A format `export-script` can write the runbook in.
+pub trait RunbookExporter: Send + Sync {
+    /// Value of `--format` selecting it.
+    fn name(&self) -> &'static str;
+    /// Whether the output is a script to make executable.
+    fn executable(&self) -> bool {
+        false
+    }
+    /// Renders the steps of the runbook read from `source` (a file name).
+    fn render(
+        &self,
+        steps: &[Step],
+        manifest: Option<&RunbookManifest>,
+        source: &str,
+    ) -> Result<String>;
+}
+
+static EXPORTERS: LazyLock<Registry<dyn RunbookExporter>> = LazyLock::new(|| {
+    let builtins: Vec<Arc<dyn RunbookExporter>> = vec![
+        Arc::new(shell::ShellExporter),
+        Arc::new(ansible::AnsibleExporter),
+        Arc::new(github::GithubExporter),
+    ];
+    Registry::new(builtins)
+});
+
+/// Adds an exporter, or replaces the one with the same name.
+pub fn register_exporter(exporter: Arc<dyn RunbookExporter>) {
+    EXPORTERS.register(exporter);
+}
+
+/// The names of the formats, sorted.
+pub fn exporter_names() -> Vec<&'static str> {
+    let mut names: Vec<&str> = EXPORTERS.entries().iter().map(|e| e.name()).collect();
+    names.sort_unstable();
+    names.dedup();
+    names
+}
+
+/// The exporter of a format.
+pub fn exporter(name: &str) -> Option<Arc<dyn RunbookExporter>> {
+    EXPORTERS
+        .entries()
+        .into_iter()
+        .find(|exporter| exporter.name() == name)
+}
+
+/// Languages whose blocks are run by passing the code to their interpreter.
+pub const INTERPRETED: &[&str] = &[
+    "python",
+    "py",
+    "javascript",
+    "js",
+    "node",
+    "typescript",
+    "ts",
+    "ruby",
+    "rb",
+    "php",
+];
+
+/// Whether blocks in this language can be pasted into a bash script as-is.
+pub fn is_shell(language: &str) -> bool {
+    matches!(language, "bash" | "sh" | "zsh" | "shell" | "console")
+}
+
+/// Turns a placeholder name into a valid shell variable name.
+pub fn variable_name(placeholder: &str) -> String {
+    let name: String = placeholder
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{name}")
+    } else {
+        name
+    }
+}
+
+/// Picks a heredoc delimiter that does not appear in the content.
+pub fn heredoc_delimiter(content: &str) -> String {
+    let mut delimiter = "COMPASS_EOF".to_string();
+    while content.lines().any(|l| l.trim() == delimiter) {
+        delimiter.push('_');
+    }
+    delimiter
+}
+
+/// Quotes a string for the shell.
+pub fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+/// Bash lines writing a `compass:write` block to its file, through `writer`
+/// (e.g. `cat`) so placeholders can be filled on the way.
+pub fn write_block(path: &str, writer: &str, content: &str) -> String {
+    // Keep `~/` expandable
+    let file = match path.strip_prefix("~/") {
+        Some(rest) => format!("\"$HOME\"/{}", quote(rest)),
+        None => quote(path),
+    };
+    let delimiter = heredoc_delimiter(content);
+    format!(
+        "mkdir -p \"$(dirname {file})\"\n{writer} > {file} <<'{delimiter}'\n{}\n{delimiter}\n",
+        content.trim_end_matches('\n')
+    )
+}
+
+/// Wraps the script of a step defined by a `compass` block in a subshell
+/// entering its `cwd` and exporting its `env`, followed by its `verify`
+/// command.
+pub fn scoped(step: &Step, mut script: String) -> String {
+    let scope = &step.scope;
+    if let Some(verify) = &scope.verify {
+        script.push_str(verify.trim_end());
+        script.push('\n');
+    }
+    if scope.cwd.is_none() && scope.env.is_empty() {
+        return script;
+    }
+    let mut setup = String::new();
+    if let Some(cwd) = &scope.cwd {
+        let _ = writeln!(setup, "cd {}", quote(cwd));
+    }
+    for (key, value) in &scope.env {
+        let _ = writeln!(setup, "export {key}={}", quote(value));
+    }
+    format!("(\n{setup}{script})\n")
+}
+
+/// Builds a bash snippet running every block of a step.
+///
+/// `content` returns the code of a block as it should appear in the output
+/// (e.g. with placeholders turned into template expressions). Blocks in
+/// interpreted languages are piped to their interpreter, `compass:write` blocks
+/// become heredocs, and blocks that cannot run outside Compass print a notice
+/// instead. The `cwd`, `env` and `verify` of a `compass` block are kept.
+pub fn step_script(step: &Step, content: impl Fn(&CodeBlock) -> String) -> String {
+    let mut script = String::new();
+    for block in &step.code_blocks {
+        let language = block.language.as_deref().unwrap_or("bash");
+        let code = content(block);
+        if let Some(path) = &block.write_to {
+            script.push_str(&write_block(path, "cat", &code));
+        } else if is_shell(language) {
+            script.push_str(code.trim_end());
+            script.push('\n');
+        } else if INTERPRETED.contains(&language) {
+            let handler = get_language_handler(Some(language));
+            let delimiter = heredoc_delimiter(&code);
+            let _ = writeln!(
+                script,
+                "{} <<'{delimiter}'\n{}\n{delimiter}",
+                handler.get_required_command(),
+                code.trim_end()
+            );
+        } else {
+            let _ = writeln!(
+                script,
+                "echo 'Skipping a {language} block: run this step with Compass.' >&2"
+            );
+        }
+    }
+    scoped(step, script)
+}
+
+/// Replaces the `<NAME>` and `{{NAME}}` placeholders of a block, in any of
+/// their forms (`<SECRET:NAME>`, `<NAME:a|b>`).
+pub fn replace_placeholders(block: &CodeBlock, value: impl Fn(&str) -> String) -> String {
+    let re = &*PLACEHOLDERS;
+    re.replace_all(
+        &block.content,
+        |caps: &regex::Captures| match placeholder_name(caps) {
+            Some(name) if block.placeholders.iter().any(|p| p == name.as_str()) => {
+                value(name.as_str())
+            }
+            _ => caps[0].to_string(),
+        },
+    )
+    .into_owned()
+}
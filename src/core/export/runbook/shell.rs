@@ -0,0 +1,311 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{
+    INTERPRETED, heredoc_delimiter, is_shell, quote, replace_placeholders, scoped, variable_name,
+    write_block,
+};
+use crate::core::executor::conditions::evaluator::arch_names;
+use crate::core::executor::languages::get_language_handler;
+use crate::core::manifest::RunbookManifest;
+use crate::core::models::{CodeBlock, Condition, ContextMode, Step};
+use std::fmt::Write;
+
+/// Bash function that replaces placeholders on stdin with variable values.
+/// Arguments come in pairs: placeholder name, variable name.
+const FILL_FUNCTION: &str = r#"compass_fill() {
+  local text
+  text="$(cat)"
+  while [ "$#" -gt 1 ]; do
+    text="${text//<$1>/${!2}}"
+    text="${text//\{\{$1\}\}/${!2}}"
+    shift 2
+  done
+  printf '%s\n' "$text"
+}
+"#;
+
+/// Standalone bash script, the default format of `export-script`.
+pub struct ShellExporter;
+
+impl super::RunbookExporter for ShellExporter {
+    fn name(&self) -> &'static str {
+        "shell"
+    }
+
+    fn executable(&self) -> bool {
+        true
+    }
+
+    fn render(
+        &self,
+        steps: &[Step],
+        _manifest: Option<&RunbookManifest>,
+        source: &str,
+    ) -> anyhow::Result<String> {
+        Ok(render(steps, source))
+    }
+}
+
+/// Renders the executable steps of a runbook as a standalone bash script.
+///
+/// Placeholders are prompted with `read -p` (unless already set in the
+/// environment, secrets without echo), conditions become `if` guards and isolated steps run in a
+/// subshell started from the initial directory.
+pub fn render(steps: &[Step], source: &str) -> String {
+    let executable: Vec<&Step> = steps.iter().filter(|s| s.is_executable()).collect();
+    let total = executable.len();
+
+    let mut body = String::new();
+    let mut prompted: Vec<String> = Vec::new();
+    let mut needs_fill = false;
+    let mut needs_tmp = false;
+    let mut needs_start_dir = false;
+
+    for (n, step) in executable.iter().enumerate() {
+        let title = step.title.trim();
+        let _ = writeln!(body, "\n# --- Step {}: {title} ---", n + 1);
+        let _ = writeln!(
+            body,
+            "echo {}",
+            quote(&format!("==> [{}/{total}] {title}", n + 1))
+        );
+
+        // Prompts stay out of the subshells below so later steps see the values
+        let mut prompts = String::new();
+        let mut lines = String::new();
+        for name in step.code_blocks.iter().flat_map(|b| &b.placeholders) {
+            if !prompted.contains(name) {
+                let var = variable_name(name);
+                // Secrets are read without echo
+                let (flags, newline) = if step.secrets.contains(name) {
+                    ("-r -s", "; echo")
+                } else {
+                    ("-r", "")
+                };
+                let _ = writeln!(
+                    prompts,
+                    "if [ -z \"${{{var}:-}}\" ]; then read {flags} -p {} {var}{newline}; fi",
+                    quote(&format!("{name}: "))
+                );
+                prompted.push(name.clone());
+            }
+        }
+        for (b, block) in step.code_blocks.iter().enumerate() {
+            let language = block.language.as_deref().unwrap_or("bash");
+            if let Some(path) = &block.write_to {
+                needs_fill |= !block.placeholders.is_empty();
+                let writer = if block.placeholders.is_empty() {
+                    "cat".to_string()
+                } else {
+                    let fill: String = block
+                        .placeholders
+                        .iter()
+                        .map(|p| format!(" {} {}", quote(p), variable_name(p)))
+                        .collect();
+                    format!("compass_fill{fill}")
+                };
+                lines.push_str(&write_block(path, &writer, &canonical(block)));
+            } else if is_shell(language) {
+                lines.push_str(&replace_placeholders(block, |name| {
+                    format!("${{{}}}", variable_name(name))
+                }));
+                if !block.content.ends_with('\n') {
+                    lines.push('\n');
+                }
+            } else if INTERPRETED.contains(&language) {
+                needs_tmp = true;
+                needs_fill |= !block.placeholders.is_empty();
+                let handler = get_language_handler(Some(language));
+                let file = format!(
+                    "\"$COMPASS_TMP/step{}_{}.{}\"",
+                    n + 1,
+                    b + 1,
+                    handler.get_extension()
+                );
+                let content = canonical(block);
+                let delimiter = heredoc_delimiter(&content);
+                let fill = block
+                    .placeholders
+                    .iter()
+                    .map(|p| format!(" {} {}", quote(p), variable_name(p)))
+                    .collect::<String>();
+                if fill.is_empty() {
+                    let _ = writeln!(lines, "cat > {file} <<'{delimiter}'");
+                } else {
+                    let _ = writeln!(lines, "compass_fill{fill} > {file} <<'{delimiter}'");
+                }
+                lines.push_str(&content);
+                if !content.ends_with('\n') {
+                    lines.push('\n');
+                }
+                let _ = writeln!(lines, "{delimiter}");
+                let _ = writeln!(lines, "{} {file}", handler.get_required_command());
+            } else {
+                let _ = writeln!(
+                    lines,
+                    "echo {} >&2",
+                    quote(&format!(
+                        "Skipping a {language} block: run this step with Compass."
+                    ))
+                );
+            }
+        }
+
+        lines = scoped(step, lines);
+        if step.context_mode == ContextMode::Isolated {
+            needs_start_dir = true;
+            lines = format!("(\ncd \"$COMPASS_START_DIR\"\n{lines})\n");
+        }
+        lines.insert_str(0, &prompts);
+        match &step.condition {
+            Some(condition) => {
+                let _ = write!(
+                    body,
+                    "if {}; then\n{lines}else\n  echo {}\nfi\n",
+                    guard(condition),
+                    quote(&format!("Skipped: requires {condition}"))
+                );
+            }
+            None => body.push_str(&lines),
+        }
+    }
+
+    let mut script = format!(
+        "#!/usr/bin/env bash\n# Generated by Compass from {source}.\n# Each step runs in order; the script stops at the first failure.\nset -e\n"
+    );
+    if needs_fill {
+        script.push('\n');
+        script.push_str(FILL_FUNCTION);
+    }
+    if needs_start_dir {
+        script.push_str("\nCOMPASS_START_DIR=\"$PWD\"\n");
+    }
+    if needs_tmp {
+        script.push_str("\nCOMPASS_TMP=\"$(mktemp -d)\"\ntrap 'rm -rf \"$COMPASS_TMP\"' EXIT\n");
+    }
+    script.push_str(&body);
+    script
+}
+
+/// The content of a block with its placeholders written `<NAME>`, the form
+/// `compass_fill` replaces.
+fn canonical(block: &CodeBlock) -> String {
+    replace_placeholders(block, |name| format!("<{name}>"))
+}
+
+/// Builds the shell test of a condition.
+pub fn guard(condition: &Condition) -> String {
+    match condition {
+        Condition::Os(os) => match os.to_ascii_lowercase().as_str() {
+            "macos" => "[ \"$(uname -s)\" = Darwin ]".to_string(),
+            "windows" => {
+                "case \"$(uname -s)\" in MINGW*|MSYS*|CYGWIN*) true ;; *) false ;; esac".to_string()
+            }
+            other => format!(
+                "[ \"$(uname -s | tr '[:upper:]' '[:lower:]')\" = {} ]",
+                quote(other)
+            ),
+        },
+        Condition::Arch(arch) => {
+            let names: Vec<String> = arch_names(arch).into_iter().map(quote).collect();
+            format!(
+                "case \"$(uname -m)\" in {}) true ;; *) false ;; esac",
+                names.join("|")
+            )
+        }
+        Condition::EnvVarExists(var) => format!("[ -n \"${{{}+x}}\" ]", variable_name(var)),
+        Condition::FileExists(path) => format!("[ -e {} ]", quote(path)),
+        Condition::CommandExists(command) => {
+            format!("command -v {} >/dev/null 2>&1", quote(command))
+        }
+        Condition::Not(inner) => format!("! {{ {}; }}", guard(inner)),
+        // `&&` and `||` have the same precedence in the shell
+        Condition::All(conditions) => grouped(conditions, " && "),
+        Condition::Any(conditions) => grouped(conditions, " || "),
+    }
+}
+
+/// The guards of `conditions` joined by `operator`, those combining others
+/// in braces.
+fn grouped(conditions: &[Condition], operator: &str) -> String {
+    conditions
+        .iter()
+        .map(|c| match c {
+            Condition::All(_) | Condition::Any(_) => format!("{{ {}; }}", guard(c)),
+            _ => guard(c),
+        })
+        .collect::<Vec<_>>()
+        .join(operator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_readme;
+
+    #[test]
+    fn test_render_script() {
+        let content = "# Setup\n```bash\necho <NAME>\n```\n<!-- compass:if os=\"macos\" -->\n# Mac\n```python\nprint('{{NAME}}')\n```\n";
+        let (steps, _) = parse_readme(content);
+        let script = render(&steps, "README.md");
+
+        assert!(script.contains("read -r -p 'NAME: ' NAME"));
+        assert!(script.contains("echo ${NAME}"));
+        assert!(script.contains("echo '==> [2/2] Mac'"));
+        assert!(script.contains("if [ \"$(uname -s)\" = Darwin ]; then"));
+        assert!(script.contains("compass_fill 'NAME' NAME > \"$COMPASS_TMP/step2_1.py\""));
+        assert_eq!(script.matches("read -r -p").count(), 1);
+    }
+
+    #[test]
+    fn test_render_secret_placeholder() {
+        let content = "# Login\n```bash\nlogin --token <SECRET:TOKEN>\n```\n";
+        let (steps, _) = parse_readme(content);
+        let script = render(&steps, "README.md");
+
+        assert!(script.contains("read -r -s -p 'TOKEN: ' TOKEN; echo"));
+        assert!(script.contains("login --token ${TOKEN}"));
+    }
+
+    #[test]
+    fn test_render_step_definition() {
+        let content = "# Build\n```compass\ncommand: make <TARGET>\ncwd: app\nenv:\n  CI: true\nverify: test -f out\n```\n";
+        let (steps, _) = parse_readme(content);
+        let script = render(&steps, "README.md");
+
+        assert!(script.contains(
+            "read -r -p 'TARGET: ' TARGET; fi\n(\ncd 'app'\nexport CI='true'\nmake ${TARGET}\ntest -f out\n)\n"
+        ));
+    }
+
+    #[test]
+    fn test_render_write_block() {
+        let content = "# Config
+<!-- compass:write path=\"config/.env\" -->
+```ini
+PORT=<PORT>
+```
+```bash
+cat config/.env
+```
+";
+        let (steps, _) = parse_readme(content);
+        let script = render(&steps, "README.md");
+
+        assert!(script.contains(
+            "mkdir -p \"$(dirname 'config/.env')\"\ncompass_fill 'PORT' PORT > 'config/.env' <<'COMPASS_EOF'\nPORT=<PORT>\nCOMPASS_EOF\ncat config/.env\n"
+        ));
+    }
+}
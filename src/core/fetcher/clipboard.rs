@@ -0,0 +1,103 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::process::Command;
+
+use anyhow::{Result, bail};
+
+/// Location recorded for runbooks read from the clipboard.
+pub const CLIPBOARD_SOURCE: &str = "clipboard://";
+
+/// Commands able to print the clipboard, tried in order.
+#[cfg(target_os = "macos")]
+const READERS: &[(&str, &[&str])] = &[("pbpaste", &[])];
+#[cfg(windows)]
+const READERS: &[(&str, &[&str])] = &[(
+    "powershell",
+    &["-NoProfile", "-Command", "Get-Clipboard -Raw"],
+)];
+#[cfg(not(any(target_os = "macos", windows)))]
+const READERS: &[(&str, &[&str])] = &[
+    ("wl-paste", &["--no-newline"]),
+    ("xclip", &["-selection", "clipboard", "-o"]),
+    ("xsel", &["--clipboard", "--output"]),
+];
+
+/// Reads the text of the system clipboard.
+///
+/// There is no clipboard API shared by every platform, so this shells out to
+/// the usual tools (`pbpaste`, `Get-Clipboard`, `wl-paste`, `xclip`, `xsel`).
+///
+/// # Errors
+///
+/// Returns an error if no clipboard tool works or the clipboard is empty.
+pub fn read_clipboard() -> Result<String> {
+    read_with(READERS)
+}
+
+/// Reads the clipboard with the first of `readers` that works.
+fn read_with(readers: &[(&str, &[&str])]) -> Result<String> {
+    let mut failures = Vec::new();
+    for (program, args) in readers {
+        match Command::new(program).args(*args).output() {
+            Ok(output) if output.status.success() => {
+                let text = String::from_utf8_lossy(&output.stdout).replace("\r\n", "\n");
+                if text.trim().is_empty() {
+                    bail!("The clipboard is empty.");
+                }
+                tracing::info!(program, bytes = text.len(), "Read runbook from clipboard");
+                return Ok(text);
+            }
+            Ok(output) => failures.push(format!(
+                "{program}: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )),
+            Err(e) => failures.push(format!("{program}: {e}")),
+        }
+    }
+    let tools: Vec<&str> = readers.iter().map(|(program, _)| *program).collect();
+    bail!(
+        "Could not read the clipboard (tried {}):\n  {}",
+        tools.join(", "),
+        failures.join("\n  ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_with_first_working_tool() {
+        let text = read_with(&[
+            ("compass-no-such-clipboard", &[]),
+            ("false", &[]),
+            ("printf", &["# Setup\r\nmake\r\n"]),
+        ])
+        .unwrap();
+        assert_eq!(text, "# Setup\nmake\n");
+
+        let error = read_with(&[("printf", &["  \n"])]).unwrap_err();
+        assert_eq!(error.to_string(), "The clipboard is empty.");
+
+        let error = read_with(&[("compass-no-such-clipboard", &[]), ("false", &[])]).unwrap_err();
+        assert!(
+            error.to_string().starts_with(
+                "Could not read the clipboard (tried compass-no-such-clipboard, false)"
+            ),
+            "{error}"
+        );
+    }
+}
@@ -0,0 +1,288 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `compass:include` annotations: large runbooks split across files.
+//!
+//! Each `<!-- compass:include ./docs/setup-db.md -->` line is replaced with
+//! the runbook it names, before parsing, so its steps join the step list.
+//! The included content is wrapped in `compass:origin` and
+//! `compass:endorigin` annotations, which the parser records on the steps.
+
+use super::clipboard::CLIPBOARD_SOURCE;
+use super::{STDIN_SOURCE, download};
+use crate::core::documents;
+use anyhow::{Context, Result, bail};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use url::Url;
+
+/// Deepest chain of includes, against runaway nesting.
+const MAX_DEPTH: usize = 8;
+
+static INCLUDE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^<!--\s*compass:include\s+(?:"([^"]+)"|(\S+))\s*-->$"#).unwrap()
+});
+
+/// What expanding the includes of a runbook has read so far.
+struct Expansion {
+    /// Runbooks being expanded, from the top one, to detect cycles.
+    chain: Vec<String>,
+    /// Bytes left to download, shared by all the included runbooks.
+    remaining: u64,
+    /// Whether an included runbook was downloaded.
+    remote: bool,
+}
+
+/// Replaces the `compass:include` annotations of `content`, read from
+/// `location`, with the runbooks they name, recursively. Paths are relative to
+/// the including file, or to its URL for a remote runbook, which can only
+/// include other remote files. Annotations inside code blocks are left alone.
+///
+/// Returns the expanded runbook and whether it included a remote one. The
+/// downloads, a remote `location` included, share the `max_bytes` limit.
+///
+/// # Errors
+///
+/// Returns an error if an included runbook cannot be read or converted, if
+/// includes form a cycle or nest too deeply, or if they download more than
+/// `max_bytes`.
+pub fn expand_includes(content: &str, location: &str, max_bytes: u64) -> Result<(String, bool)> {
+    if !content.contains("compass:include") {
+        return Ok((content.to_string(), false));
+    }
+    let downloaded = if is_url(location) {
+        content.len() as u64
+    } else {
+        0
+    };
+    // Included files are compared by their canonical path, so is this one
+    let top = if is_url(location) {
+        location.to_string()
+    } else {
+        Path::new(location)
+            .canonicalize()
+            .map_or_else(|_| location.to_string(), |p| p.display().to_string())
+    };
+    let mut expansion = Expansion {
+        chain: vec![top],
+        remaining: max_bytes.saturating_sub(downloaded),
+        remote: false,
+    };
+    let content = expand(content, location, max_bytes, &mut expansion)?;
+    Ok((content, expansion.remote))
+}
+
+fn expand(
+    content: &str,
+    location: &str,
+    max_bytes: u64,
+    expansion: &mut Expansion,
+) -> Result<String> {
+    let mut output = String::with_capacity(content.len());
+    // Opening fence of the code block the line is in
+    let mut fence: Option<&str> = None;
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            output.push_str(line);
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence = Some(&trimmed[..3]);
+            output.push_str(line);
+            continue;
+        }
+        let Some(caps) = INCLUDE.captures(trimmed) else {
+            output.push_str(line);
+            continue;
+        };
+        let target = caps
+            .get(1)
+            .or_else(|| caps.get(2))
+            .map_or("", |m| m.as_str());
+        let resolved = resolve(location, target)?;
+        if expansion.chain.contains(&resolved) {
+            bail!(
+                "compass:include cycle: {} -> {resolved}",
+                expansion.chain.join(" -> ")
+            );
+        }
+        if expansion.chain.len() > MAX_DEPTH {
+            bail!("compass:include nests more than {MAX_DEPTH} runbooks deep at {resolved}");
+        }
+
+        let included = read(&resolved, max_bytes, expansion)
+            .with_context(|| format!("Failed to include {target} (from {location})"))?;
+        let included = documents::to_markdown(&resolved, included)?;
+        expansion.chain.push(resolved.clone());
+        let body = expand(
+            strip_frontmatter(&included),
+            &resolved,
+            max_bytes,
+            expansion,
+        )?;
+        expansion.chain.pop();
+        tracing::info!(location = %resolved, "Included runbook");
+        output.push_str(&format!(
+            "\n<!-- compass:origin {resolved} -->\n\n{}\n\n<!-- compass:endorigin -->\n\n",
+            body.trim_end()
+        ));
+    }
+    Ok(output)
+}
+
+/// The file or URL `target` names, relative to the runbook at `location`.
+fn resolve(location: &str, target: &str) -> Result<String> {
+    let remote = |url: Url| {
+        if matches!(url.scheme(), "http" | "https") {
+            Ok(url.to_string())
+        } else {
+            bail!("compass:include only reads http(s) URLs and local files: {url}")
+        }
+    };
+    if is_url(target) {
+        return remote(Url::parse(target).context("Invalid URL in compass:include")?);
+    }
+    if is_url(location) {
+        // A remote runbook never reads local files
+        let base = Url::parse(location).context("Invalid URL of the runbook")?;
+        return remote(
+            base.join(target)
+                .context("Invalid path in compass:include")?,
+        );
+    }
+    if location == CLIPBOARD_SOURCE {
+        bail!("Runbooks read from the clipboard cannot include files: {target}");
+    }
+    let base = if location == STDIN_SOURCE {
+        PathBuf::from(".")
+    } else {
+        Path::new(location)
+            .parent()
+            .map_or_else(|| PathBuf::from("."), Path::to_path_buf)
+    };
+    let path = base.join(target);
+    let path = path
+        .canonicalize()
+        .with_context(|| format!("Included runbook not found: {}", path.display()))?;
+    Ok(path.display().to_string())
+}
+
+fn read(location: &str, max_bytes: u64, expansion: &mut Expansion) -> Result<String> {
+    if !is_url(location) {
+        return Ok(std::fs::read_to_string(location)?);
+    }
+    let too_large = || {
+        anyhow::anyhow!(
+            "The included runbooks are larger than the download limit of {} MiB",
+            max_bytes / (1024 * 1024)
+        )
+    };
+    if expansion.remaining == 0 {
+        return Err(too_large());
+    }
+    // A runbook cut at the limit would lose its last steps: fail instead
+    let body = download(location, expansion.remaining)?;
+    if body.len() as u64 > expansion.remaining {
+        return Err(too_large());
+    }
+    expansion.remaining -= body.len() as u64;
+    expansion.remote = true;
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+fn is_url(location: &str) -> bool {
+    location.starts_with("http://") || location.starts_with("https://")
+}
+
+/// The content after the frontmatter, if any: only the including runbook has
+/// one.
+fn strip_frontmatter(content: &str) -> &str {
+    content
+        .strip_prefix("---")
+        .and_then(|rest| rest.find("\n---").map(|end| &rest[end + 4..]))
+        .unwrap_or(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_readme;
+
+    #[test]
+    fn test_expand_includes() {
+        let dir = std::env::temp_dir().join(format!("compass-include-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("docs")).unwrap();
+        std::fs::write(
+            dir.join("docs/db.md"),
+            "---\ncontext: isolated\n---\n# Database\n```bash\ncreatedb app\n```\n<!-- compass:include ../seed.md -->\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("seed.md"), "# Seed\n```bash\n./seed\n```\n").unwrap();
+        let main = dir.join("README.md");
+        let content = "# Setup\n```bash\nmake\n```\n<!-- compass:include ./docs/db.md -->\n```markdown\n<!-- compass:include ./missing.md -->\n```\n# Run\n```bash\nmake run\n```\n";
+
+        let (expanded, remote) =
+            expand_includes(content, &main.display().to_string(), 1024).unwrap();
+        assert!(!remote);
+        let (steps, _) = parse_readme(&expanded);
+        let titles: Vec<_> = steps.iter().map(|s| s.title.as_str()).collect();
+        assert_eq!(titles, ["Setup", "Database", "Seed", "Run"]);
+        let db = dir.join("docs/db.md").canonicalize().unwrap();
+        assert_eq!(steps[0].origin, None);
+        assert_eq!(steps[1].origin.as_deref(), Some(db.to_str().unwrap()));
+        assert!(steps[2].origin.as_deref().unwrap().ends_with("seed.md"));
+        assert_eq!(steps[3].origin, None);
+
+        std::fs::write(dir.join("seed.md"), "<!-- compass:include docs/db.md -->\n").unwrap();
+        let error = expand_includes(content, &main.display().to_string(), 1024).unwrap_err();
+        assert!(error.to_string().contains("cycle"), "{error:#}");
+
+        // A runbook including itself is caught before it is read again
+        let own = "# Top\n<!-- compass:include ./README.md -->\n";
+        std::fs::write(&main, own).unwrap();
+        let location = dir.join(".").join("README.md").display().to_string();
+        let error = expand_includes(own, &location, 1024).unwrap_err();
+        let path = main.canonicalize().unwrap().display().to_string();
+        assert_eq!(
+            error.to_string(),
+            format!("compass:include cycle: {path} -> {path}")
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_includes_share_download_limit() {
+        let content = "# Setup\n<!-- compass:include ./db.md -->\n";
+        let error = expand_includes(content, "https://example.com/setup.md", 16).unwrap_err();
+        assert!(format!("{error:#}").contains("download limit"), "{error:#}");
+    }
+
+    #[test]
+    fn test_resolve_remote() {
+        assert_eq!(
+            resolve("https://example.com/runbooks/setup.md", "../db/init.md").unwrap(),
+            "https://example.com/db/init.md"
+        );
+        assert_eq!(
+            resolve("https://example.com/setup.md", "/etc/passwd").unwrap(),
+            "https://example.com/etc/passwd"
+        );
+        assert!(resolve("https://example.com/setup.md", "file:///etc/passwd").is_err());
+    }
+}
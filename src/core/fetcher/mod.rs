@@ -12,22 +12,84 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod clipboard;
+pub mod includes;
 pub mod rewriters;
 
+use std::io::{IsTerminal, Read};
+
 use anyhow::{Context, Result, bail};
+use pulldown_cmark::{Event, Parser, Tag};
 use reqwest::header::USER_AGENT;
 use url::Url;
 
 use self::rewriters::normalize_git_forge_url;
 
-/// Fetches remote content from a URL.
+/// File argument meaning "read the runbook from stdin".
+pub const STDIN_SOURCE: &str = "-";
+
+/// Reads a runbook piped into stdin (`cat notes.md | compass tui -`).
+///
+/// # Errors
+///
+/// Returns an error if stdin is a terminal, cannot be read or is empty.
+pub fn read_stdin() -> Result<String> {
+    let stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        bail!("Nothing piped into stdin. Usage: cat runbook.md | compass tui -");
+    }
+    read_piped(stdin)
+}
+
+/// Reads a whole runbook piped into Compass.
+fn read_piped(mut input: impl Read) -> Result<String> {
+    let mut content = String::new();
+    input
+        .read_to_string(&mut content)
+        .context("Failed to read the runbook from stdin")?;
+    if content.trim().is_empty() {
+        bail!("The runbook read from stdin is empty.");
+    }
+    Ok(content)
+}
+
+/// Opens the controlling terminal for prompts once stdin carried the runbook.
+///
+/// # Errors
+///
+/// Returns an error if the process has no controlling terminal.
+pub fn open_terminal() -> Result<std::fs::File> {
+    let path = if cfg!(windows) { "CONIN$" } else { "/dev/tty" };
+    std::fs::File::open(path).with_context(|| format!("Failed to open the terminal ({path})"))
+}
+
+/// Fetches remote content from a URL, reading at most `max_bytes`.
 /// Handles automatic conversion of GitHub/GitLab blob URLs to raw URLs.
-pub fn fetch_remote_content(input_url: &str) -> Result<String> {
+///
+/// A longer document is cut before its last section, which may be
+/// incomplete, and ends with a section saying so.
+pub fn fetch_remote_content(input_url: &str, max_bytes: u64) -> Result<String> {
+    let body = download(input_url, max_bytes)?;
+    if body.len() as u64 <= max_bytes {
+        return Ok(String::from_utf8_lossy(&body).into_owned());
+    }
+
+    eprintln!(
+        "Warning: {input_url} is larger than {} MiB, only its beginning is loaded.",
+        max_bytes / (1024 * 1024)
+    );
+    Ok(truncate_document(&body, max_bytes, input_url))
+}
+
+/// Downloads a URL like [`fetch_remote_content`], reading at most one byte
+/// past `max_bytes`, which tells whether the document goes on.
+pub(crate) fn download(input_url: &str, max_bytes: u64) -> Result<Vec<u8>> {
     let url = Url::parse(input_url).context("Invalid URL format")?;
 
     // Normalize URL for raw content if hosted on known forges (Moved to submodule)
     let target_url = normalize_git_forge_url(&url);
     let url_str = target_url.as_str();
+    tracing::info!(url = url_str, "Fetching remote content");
 
     // Use version from Cargo.toml
     let current_version = env!("CARGO_PKG_VERSION");
@@ -56,19 +118,88 @@ pub fn fetch_remote_content(input_url: &str) -> Result<String> {
         }
     }
 
-    let content = response
-        .text()
-        .with_context(|| "Failed to read response body as text")?;
+    if let Some(length) = response.content_length()
+        && length > max_bytes
+    {
+        tracing::warn!(
+            url = url_str,
+            length,
+            max_bytes,
+            "Document exceeds the download limit"
+        );
+    }
 
-    Ok(content)
+    // Read one byte past the limit to tell whether the document goes on
+    let mut body = Vec::new();
+    response
+        .take(max_bytes.saturating_add(1))
+        .read_to_end(&mut body)
+        .with_context(|| "Failed to read response body")?;
+    Ok(body)
+}
+
+/// Keeps the first `max_bytes` of a document, without its last section (cut
+/// in the middle, a code block could run half a command), and appends a
+/// section explaining that the rest is missing.
+fn truncate_document(body: &[u8], max_bytes: u64, source: &str) -> String {
+    let limit = usize::try_from(max_bytes)
+        .unwrap_or(usize::MAX)
+        .min(body.len());
+    let body = &body[..limit];
+    // Stop at the last complete line, which also avoids splitting a character
+    let end = body.iter().rposition(|b| *b == b'\n').map_or(0, |i| i + 1);
+    let content = String::from_utf8_lossy(&body[..end]);
+
+    let last_heading = Parser::new(&content)
+        .into_offset_iter()
+        .filter(|(event, _)| matches!(event, Event::Start(Tag::Heading { .. })))
+        .map(|(_, range)| range.start)
+        .last();
+    let kept = match last_heading {
+        Some(start) if start > 0 => &content[..start],
+        _ => &content,
+    };
+
+    format!(
+        "{}\n\n# ⚠️ Document Truncated\n\nCompass only loaded the first {} MiB of {source}, the sections after this one are missing. Raise the download limit with `compass setup` to load the whole runbook.\n",
+        kept.trim_end(),
+        max_bytes / (1024 * 1024)
+    )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_read_piped() {
+        let runbook = "# Setup\n```bash\nmake\n```\n";
+        assert_eq!(read_piped(runbook.as_bytes()).unwrap(), runbook);
+        let error = read_piped(" \n".as_bytes()).unwrap_err();
+        assert_eq!(error.to_string(), "The runbook read from stdin is empty.");
+        assert!(read_piped(&[0xff, 0xfe][..]).is_err());
+    }
+
     #[test]
     fn test_fetch_invalid_url() {
-        assert!(fetch_remote_content("not-a-url").is_err());
+        assert!(fetch_remote_content("not-a-url", 1024).is_err());
+    }
+
+    #[test]
+    fn test_truncate_document() {
+        let body = "# One\n```bash\necho one\n```\n# Two\n```bash\nrm -rf /tmp/build/cache\n```\n";
+        let cut = body.find("cache").unwrap() as u64;
+        let content = truncate_document(body.as_bytes(), cut, "https://example.com/README.md");
+
+        let (steps, _) = crate::core::parser::parse_readme(&content);
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].code_blocks[0].content, "echo one\n");
+        assert!(steps[1].title.contains("Truncated"));
+        assert!(!steps[1].is_executable());
+        assert!(
+            steps[1]
+                .description
+                .contains("https://example.com/README.md")
+        );
     }
 }
@@ -15,13 +15,15 @@
 pub mod github;
 pub mod gitlab;
 
+use crate::core::plugins::Registry;
+use std::sync::{Arc, LazyLock};
 use url::Url;
 
 use self::github::GitHubRewriter;
 use self::gitlab::GitLabRewriter;
 
 /// Trait to define a URL rewriter strategy for specific hosts.
-pub trait UrlRewriter {
+pub trait UrlRewriter: Send + Sync {
     /// Determines if this rewriter supports the given URL.
     fn can_handle(&self, url: &Url) -> bool;
 
@@ -30,14 +32,20 @@ pub trait UrlRewriter {
     fn rewrite(&self, url: &Url) -> Option<Url>;
 }
 
+static REWRITERS: LazyLock<Registry<dyn UrlRewriter>> = LazyLock::new(|| {
+    let builtins: Vec<Arc<dyn UrlRewriter>> =
+        vec![Arc::new(GitHubRewriter), Arc::new(GitLabRewriter)];
+    Registry::new(builtins)
+});
+
+/// Adds a rewriter, tried before the built-in ones.
+pub fn register_rewriter(rewriter: Arc<dyn UrlRewriter>) {
+    REWRITERS.register(rewriter);
+}
+
 /// Main entry point to normalize URLs using registered rewriters.
 pub fn normalize_git_forge_url(url: &Url) -> Url {
-    // List of available rewriters
-    // In a larger system, this could be dynamic or plugin-based.
-    let rewriters: Vec<Box<dyn UrlRewriter>> =
-        vec![Box::new(GitHubRewriter), Box::new(GitLabRewriter)];
-
-    for rewriter in rewriters {
+    for rewriter in REWRITERS.entries() {
         if rewriter.can_handle(url)
             && let Some(rewritten) = rewriter.rewrite(url)
         {
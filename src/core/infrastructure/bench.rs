@@ -0,0 +1,263 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::core::executor::security::redact::Redactor;
+use crate::core::parser::parse_readme;
+use crate::ui::utils::append_output;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::hint::black_box;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Size of the chunks read from a PTY, in bytes.
+const CHUNK_SIZE: usize = 4096;
+
+/// Secret masked in the output by the redacted benchmark.
+pub const SAMPLE_SECRET: &str = "sk-live-4f9a2c";
+
+/// Sizes of the generated workloads of `compass bench`.
+pub struct BenchOptions {
+    /// Steps of the generated runbook.
+    pub steps: usize,
+    /// Lines of the generated command output.
+    pub lines: usize,
+    /// Runs of each benchmark; the median is reported.
+    pub iterations: u32,
+}
+
+/// The timing of one benchmark.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Measurement {
+    pub name: String,
+    pub median_ms: f64,
+    pub min_ms: f64,
+    /// Work done per second, in `unit`.
+    pub throughput: f64,
+    pub unit: String,
+}
+
+/// Generates a runbook of `steps` steps mixing the constructs the parser
+/// handles: frontmatter, annotations, placeholders, inline commands and
+/// several languages.
+pub fn sample_document(steps: usize) -> String {
+    let mut document = String::from("---\nenv:\n  RUST_LOG: info\nsecrets: [API_TOKEN]\n---\n");
+    document.push_str("# Service Setup\n\nEverything needed to run the services locally.\n\n");
+    for i in 1..=steps {
+        document.push_str(&format!(
+            "## Step {i}: Configure service {i}\n\n\
+             Set up `service-{i}` with the **default** settings, then run `npm ci` \
+             in its directory. See [the guide](https://example.com/{i}) for details.\n\n"
+        ));
+        if i % 3 == 0 {
+            document.push_str(&format!("<!-- compass:env PORT={} -->\n", 8000 + i));
+        }
+        if i % 5 == 0 {
+            document.push_str(&format!(
+                "```python\nimport os\nprint(os.environ.get(\"PORT\", \"{i}\"))\n```\n\n"
+            ));
+        } else {
+            document.push_str(&format!(
+                "```bash\ncd /srv/service-{i}\nexport SERVICE_ID={i}\n\
+                 curl -H \"Authorization: <SECRET:API_TOKEN>\" http://<HOST>:{{{{PORT}}}}/health\n```\n\n"
+            ));
+        }
+    }
+    document
+}
+
+/// Generates `lines` lines of colored command output, as a PTY delivers it:
+/// in fixed-size chunks cut anywhere, with `\r\n` line endings.
+pub fn sample_output(lines: usize) -> Vec<String> {
+    let mut output = String::new();
+    for i in 0..lines {
+        output.push_str(&format!(
+            "\x1b[32m   Compiling\x1b[0m crate-{i} v0.{}.0 (token {SAMPLE_SECRET})\r\n",
+            i % 10
+        ));
+    }
+    let mut chunks = Vec::new();
+    let mut rest = output.as_str();
+    while !rest.is_empty() {
+        let mut end = CHUNK_SIZE.min(rest.len());
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        chunks.push(rest[..end].to_string());
+        rest = &rest[end..];
+    }
+    chunks
+}
+
+/// Appends output chunks to a step's output the way a running step does,
+/// masking `secrets` first.
+pub fn append_chunks(chunks: &[String], secrets: &[String]) -> String {
+    let mut redactor = Redactor::new(secrets.iter().cloned());
+    let mut buffer = String::new();
+    for chunk in chunks {
+        append_output(&mut buffer, &redactor.push(chunk));
+    }
+    append_output(&mut buffer, &redactor.finish());
+    buffer
+}
+
+/// Runs the benchmarks: parsing a large runbook, and appending command output
+/// with and without secrets to mask.
+pub fn run(options: &BenchOptions) -> Vec<Measurement> {
+    let document = sample_document(options.steps);
+    let chunks = sample_output(options.lines);
+    let output_mb = chunks.iter().map(String::len).sum::<usize>() as f64 / 1_000_000.0;
+    let secrets = [SAMPLE_SECRET.to_string()];
+
+    vec![
+        measure(
+            "parse",
+            options.iterations,
+            options.steps as f64,
+            "steps/s",
+            || {
+                black_box(parse_readme(black_box(&document)));
+            },
+        ),
+        measure(
+            "output_append",
+            options.iterations,
+            output_mb,
+            "MB/s",
+            || {
+                black_box(append_chunks(black_box(&chunks), &[]));
+            },
+        ),
+        measure(
+            "output_append_redacted",
+            options.iterations,
+            output_mb,
+            "MB/s",
+            || {
+                black_box(append_chunks(black_box(&chunks), &secrets));
+            },
+        ),
+    ]
+}
+
+/// Times `iterations` runs of `work`, which processes `amount` of `unit`.
+fn measure(
+    name: &str,
+    iterations: u32,
+    amount: f64,
+    unit: &str,
+    mut work: impl FnMut(),
+) -> Measurement {
+    let mut timings: Vec<Duration> = (0..iterations.max(1))
+        .map(|_| {
+            let started = Instant::now();
+            work();
+            started.elapsed()
+        })
+        .collect();
+    timings.sort();
+    let median = timings[timings.len() / 2];
+    Measurement {
+        name: name.to_string(),
+        median_ms: median.as_secs_f64() * 1000.0,
+        min_ms: timings[0].as_secs_f64() * 1000.0,
+        throughput: amount / median.as_secs_f64().max(f64::EPSILON),
+        unit: unit.to_string(),
+    }
+}
+
+/// Reads the results of an earlier `compass bench --json`.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or is not such a result.
+pub fn load_baseline(path: &Path) -> Result<Vec<Measurement>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&content).with_context(|| {
+        format!(
+            "{} is not the output of `compass bench --json`",
+            path.display()
+        )
+    })
+}
+
+/// Describes the benchmarks whose median is more than `tolerance` percent
+/// slower than in `baseline`.
+pub fn regressions(
+    results: &[Measurement],
+    baseline: &[Measurement],
+    tolerance: u32,
+) -> Vec<String> {
+    results
+        .iter()
+        .filter_map(|result| {
+            let before = baseline.iter().find(|b| b.name == result.name)?;
+            let limit = before.median_ms * (1.0 + f64::from(tolerance) / 100.0);
+            (result.median_ms > limit).then(|| {
+                format!(
+                    "{}: {:.2} ms, was {:.2} ms (+{:.0}%)",
+                    result.name,
+                    result.median_ms,
+                    before.median_ms,
+                    (result.median_ms / before.median_ms - 1.0) * 100.0
+                )
+            })
+        })
+        .collect()
+}
+
+/// Prints the results as a table.
+pub fn print(results: &[Measurement]) {
+    for result in results {
+        println!(
+            "⏱️  {:<24} median {:>9.2} ms   min {:>9.2} ms   {:>12.1} {}",
+            result.name, result.median_ms, result.min_ms, result.throughput, result.unit
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_workloads() {
+        let (steps, _) = parse_readme(&sample_document(20));
+        assert_eq!(steps.iter().filter(|s| s.is_executable()).count(), 20);
+
+        let chunks = sample_output(500);
+        assert!(chunks.len() > 1);
+        let output = append_chunks(&chunks, &[SAMPLE_SECRET.to_string()]);
+        assert_eq!(output.lines().count(), 500);
+        assert!(!output.contains(SAMPLE_SECRET));
+        assert!(!output.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_regressions() {
+        let measurement = |median_ms| Measurement {
+            name: "parse".to_string(),
+            median_ms,
+            min_ms: median_ms,
+            throughput: 0.0,
+            unit: "steps/s".to_string(),
+        };
+        assert!(regressions(&[measurement(11.0)], &[measurement(10.0)], 20).is_empty());
+        assert_eq!(
+            regressions(&[measurement(13.0)], &[measurement(10.0)], 20).len(),
+            1
+        );
+    }
+}
@@ -17,12 +17,16 @@
 //! This module provides persistent configuration management for Compass.
 //! It saves user-provided placeholder values per README file, so users don't
 //! have to re-enter the same values every time they run the same README.
+//! Global preferences (theme, status markers, safety policy, sandbox, registry, output, shell) live in
+//! `settings.json`, written by the first-run wizard.
 //!
 //! Configuration files are stored in the user's config directory:
 //! - Linux: `~/.config/compass/`
 //! - macOS: `~/Library/Application Support/compass/`
 //! - Windows: `%APPDATA%\compass\`
 
+use crate::core::executor::engine::context::ExecutionContext;
+use crate::core::executor::languages::strategies::shell::Shell;
 use anyhow::{Context, Result};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
@@ -39,6 +43,154 @@ const APP_QUALIFIER: &str = "";
 /// The organization name.
 const APP_ORGANIZATION: &str = "eraflo";
 
+/// The file holding the global settings, inside the config directory.
+const SETTINGS_FILE: &str = "settings.json";
+
+/// The color theme of the TUI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+    HighContrast,
+}
+
+/// How the TUI marks the status of steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StatusMarkers {
+    /// Colored emoji (✅, ❌, ⏳).
+    #[default]
+    Emoji,
+    /// Bracketed text (`[OK]`, `[!!]`, `[..]`), readable without colors.
+    Text,
+}
+
+/// How the TUI reacts to commands matching a dangerous pattern.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SafetyPolicy {
+    /// Ask for confirmation before running them.
+    #[default]
+    Prompt,
+    /// Refuse to run them.
+    Strict,
+    /// Run them without asking.
+    Relaxed,
+}
+
+/// User preferences shared by every runbook.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GlobalConfig {
+    pub theme: Theme,
+    /// Status markers of the step list, for color-blind users.
+    pub status_markers: StatusMarkers,
+    pub safety_policy: SafetyPolicy,
+    /// Run steps in a Docker sandbox without passing `--sandbox`.
+    pub sandbox: bool,
+    /// Docker image used when `--image` is not passed.
+    pub docker_image: String,
+    /// Look up runbooks in the online registry when a file is not found.
+    pub registry_enabled: bool,
+    /// Largest runbook downloaded from a URL, in MiB. Longer ones are cut.
+    pub max_download_mb: u64,
+    /// Use the linear, screen-reader friendly renderer instead of the full-screen TUI.
+    pub linear_output: bool,
+    /// Shell running untagged code blocks.
+    pub shell: Shell,
+    /// Start shells as login shells so profile PATH customizations apply.
+    pub source_profile: bool,
+    /// Offer inline code like `npm ci` as runnable commands in steps without code blocks.
+    pub inline_commands: bool,
+    /// Run commands with pipes instead of a terminal to capture stdout and stderr separately.
+    pub separate_streams: bool,
+    /// Free disk space, in MiB, below which running steps get a warning (0 never warns).
+    pub min_free_disk_mb: u64,
+}
+
+impl Default for GlobalConfig {
+    fn default() -> Self {
+        Self {
+            theme: Theme::default(),
+            status_markers: StatusMarkers::default(),
+            safety_policy: SafetyPolicy::default(),
+            sandbox: false,
+            docker_image: "ubuntu:latest".to_string(),
+            registry_enabled: true,
+            max_download_mb: 10,
+            linear_output: false,
+            shell: Shell::default(),
+            source_profile: false,
+            inline_commands: false,
+            separate_streams: false,
+            min_free_disk_mb: 1024,
+        }
+    }
+}
+
+impl GlobalConfig {
+    /// The largest runbook downloaded from a URL, in bytes.
+    pub const fn max_download_bytes(&self) -> u64 {
+        self.max_download_mb.saturating_mul(1024 * 1024)
+    }
+
+    /// Applies the shell and terminal preferences to an execution context.
+    pub fn apply_shell(&self, context: &mut ExecutionContext) {
+        context.shell = self.shell;
+        context.source_profile = self.source_profile;
+        context.separate_streams = self.separate_streams;
+    }
+
+    /// Path of the settings file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration directory cannot be determined.
+    pub fn path() -> Result<PathBuf> {
+        Ok(ConfigManager::get_config_dir()?.join(SETTINGS_FILE))
+    }
+
+    /// Whether the settings were saved before (i.e. this is not the first run).
+    pub fn exists() -> bool {
+        Self::path().is_ok_and(|p| p.exists())
+    }
+
+    /// Loads the settings, falling back to defaults when none were saved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings file exists but cannot be parsed.
+    pub fn load() -> Result<Self> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read settings: {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse settings: {}", path.display()))
+    }
+
+    /// Saves the settings to disk.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings cannot be written.
+    pub fn save(&self) -> Result<PathBuf> {
+        let path = Self::path()?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create config directory: {}", dir.display()))?;
+        }
+        let content = serde_json::to_string_pretty(self).context("Failed to serialize settings")?;
+        fs::write(&path, content)
+            .with_context(|| format!("Failed to write settings: {}", path.display()))?;
+        Ok(path)
+    }
+}
+
 /// Represents the persistent configuration for a specific README file.
 ///
 /// Each README file gets its own configuration file, identified by a hash
@@ -51,6 +203,9 @@ pub struct ReadmeConfig {
     pub placeholders: HashMap<String, String>,
     /// Last modified timestamp (ISO 8601 format).
     pub last_modified: Option<String>,
+    /// Measured step durations in milliseconds, keyed by step title.
+    #[serde(default)]
+    pub durations: HashMap<String, u64>,
 }
 
 /// Manages persistent configuration for Compass.
@@ -103,7 +258,7 @@ impl ConfigManager {
     ///
     /// Uses the `directories` crate to find the appropriate config location
     /// for the current operating system.
-    fn get_config_dir() -> Result<PathBuf> {
+    pub fn get_config_dir() -> Result<PathBuf> {
         let proj_dirs = ProjectDirs::from(APP_QUALIFIER, APP_ORGANIZATION, APP_NAME)
             .context("Could not determine project directories for configuration")?;
 
@@ -114,12 +269,15 @@ impl ConfigManager {
     ///
     /// Uses a simple hash of the canonical path to create a unique identifier.
     fn readme_config_filename(readme_path: &Path) -> String {
-        // Use a simple hash based on the path string
-        let path_str = readme_path.to_string_lossy();
-        let hash: u64 = path_str.bytes().fold(0u64, |acc, b| {
+        format!("readme_{:016x}.json", Self::path_hash(readme_path))
+    }
+
+    /// Simple hash of a README path, naming the files stored for it.
+    #[must_use]
+    pub fn path_hash(readme_path: &Path) -> u64 {
+        readme_path.to_string_lossy().bytes().fold(0u64, |acc, b| {
             acc.wrapping_mul(31).wrapping_add(u64::from(b))
-        });
-        format!("readme_{hash:016x}.json")
+        })
     }
 
     /// Loads the configuration for a specific README file.
@@ -142,6 +300,7 @@ impl ConfigManager {
         let config_file_path = self.config_dir.join(&config_filename);
 
         self.config_file_path = Some(config_file_path.clone());
+        tracing::debug!(path = %config_file_path.display(), "Loading README configuration");
 
         if config_file_path.exists() {
             let content = fs::read_to_string(&config_file_path).with_context(|| {
@@ -160,6 +319,7 @@ impl ConfigManager {
                 readme_path: canonical_path.to_string_lossy().to_string(),
                 placeholders: HashMap::new(),
                 last_modified: None,
+                durations: HashMap::new(),
             };
         }
 
@@ -229,11 +389,21 @@ impl ConfigManager {
     /// # Arguments
     ///
     /// * `placeholders` - A map of placeholder names to values.
-    pub fn update_placeholders(&mut self, placeholders: &HashMap<String, String>) {
+    /// * `secrets` - Names of secret placeholders, never stored (and removed
+    ///   if stored before they were declared secret).
+    pub fn update_placeholders(
+        &mut self,
+        placeholders: &HashMap<String, String>,
+        secrets: &[String],
+    ) {
         for (key, value) in placeholders {
-            self.current_config
-                .placeholders
-                .insert(key.clone(), value.clone());
+            if secrets.contains(key) {
+                self.current_config.placeholders.remove(key);
+            } else {
+                self.current_config
+                    .placeholders
+                    .insert(key.clone(), value.clone());
+            }
         }
     }
 
@@ -247,6 +417,24 @@ impl ConfigManager {
         &self.current_config.placeholders
     }
 
+    /// Records a measured duration for a step.
+    ///
+    /// The stored value is averaged with the previous measurement so a
+    /// single slow run does not throw off the estimate.
+    pub fn record_duration(&mut self, title: &str, duration_ms: u64) {
+        self.current_config
+            .durations
+            .entry(title.to_string())
+            .and_modify(|d| *d = (*d + duration_ms) / 2)
+            .or_insert(duration_ms);
+    }
+
+    /// Gets the measured duration of a step, in milliseconds.
+    #[must_use]
+    pub fn get_duration(&self, title: &str) -> Option<u64> {
+        self.current_config.durations.get(title).copied()
+    }
+
     /// Gets the configuration directory path.
     ///
     /// # Returns
@@ -283,4 +471,42 @@ mod tests {
 
         assert_eq!(filename1, filename2);
     }
+
+    #[test]
+    fn test_global_config_defaults_missing_settings() {
+        let config: GlobalConfig =
+            serde_json::from_str(r#"{ "theme": "high-contrast", "safety_policy": "strict" }"#)
+                .unwrap();
+
+        assert_eq!(config.theme, Theme::HighContrast);
+        assert_eq!(config.safety_policy, SafetyPolicy::Strict);
+        assert_eq!(config.docker_image, "ubuntu:latest");
+        assert!(config.registry_enabled);
+        assert!(!config.sandbox);
+        assert!(serde_json::from_str::<GlobalConfig>(r#"{ "theme": "solarized" }"#).is_err());
+    }
+
+    #[test]
+    fn test_secret_placeholders_never_saved() {
+        let mut manager = ConfigManager {
+            config_dir: PathBuf::new(),
+            current_config: ReadmeConfig::default(),
+            config_file_path: None,
+        };
+        // A value saved before the placeholder was declared secret
+        manager
+            .current_config
+            .placeholders
+            .insert("TOKEN".to_string(), "old".to_string());
+
+        let values = HashMap::from([
+            ("HOST".to_string(), "db.local".to_string()),
+            ("TOKEN".to_string(), "s3cr3t".to_string()),
+        ]);
+        manager.update_placeholders(&values, &["TOKEN".to_string()]);
+
+        let saved = &manager.current_config.placeholders;
+        assert_eq!(saved.get("HOST").map(String::as_str), Some("db.local"));
+        assert!(!saved.contains_key("TOKEN"));
+    }
 }
@@ -0,0 +1,136 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Crash Reporting
+//!
+//! Keeps a short trail of recent user actions and writes a crash report to the
+//! config directory when Compass panics, so users can attach it to an issue.
+
+use crate::core::infrastructure::config::ConfigManager;
+use anyhow::{Context, Result};
+use chrono::Local;
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+/// Maximum number of actions kept in the trail.
+const MAX_ACTIONS: usize = 20;
+
+/// Where users are pointed to report a crash.
+const ISSUE_URL: &str = "https://github.com/eraflo/Compass/issues/new";
+
+static RECENT_ACTIONS: OnceLock<Mutex<VecDeque<String>>> = OnceLock::new();
+
+fn recent_actions() -> &'static Mutex<VecDeque<String>> {
+    RECENT_ACTIONS.get_or_init(|| Mutex::new(VecDeque::with_capacity(MAX_ACTIONS)))
+}
+
+/// Records a user action, dropping the oldest one once the trail is full.
+pub fn record_action(action: impl Into<String>) {
+    if let Ok(mut actions) = recent_actions().lock() {
+        push_action(&mut actions, action.into());
+    }
+}
+
+/// Adds an action to a trail, keeping at most [`MAX_ACTIONS`].
+fn push_action(actions: &mut VecDeque<String>, action: String) {
+    if actions.len() == MAX_ACTIONS {
+        actions.pop_front();
+    }
+    actions.push_back(action);
+}
+
+/// Returns the recorded actions, oldest first.
+fn snapshot_actions() -> Vec<String> {
+    recent_actions()
+        .lock()
+        .map(|actions| actions.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Builds the text of a crash report, with the recorded `actions`.
+fn build_report(message: &str, backtrace: &str, actions: &[String]) -> String {
+    let mut report = String::new();
+    let _ = writeln!(report, "Compass crash report");
+    let _ = writeln!(report, "Version: {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(
+        report,
+        "Platform: {} ({})",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    let _ = writeln!(report, "Time: {}", Local::now().format("%Y-%m-%d %H:%M:%S"));
+    let _ = writeln!(report, "\nPanic: {message}");
+
+    let _ = writeln!(report, "\nLast actions:");
+    if actions.is_empty() {
+        let _ = writeln!(report, "  (none)");
+    }
+    for action in actions {
+        let _ = writeln!(report, "  - {action}");
+    }
+
+    let _ = writeln!(report, "\nBacktrace:\n{backtrace}");
+    report
+}
+
+/// Writes a crash report to `<config_dir>/crashes/` and returns its path.
+///
+/// # Errors
+///
+/// Returns an error if the config directory cannot be determined or written.
+pub fn write_report(message: &str, backtrace: &str) -> Result<PathBuf> {
+    let crash_dir = ConfigManager::get_config_dir()?.join("crashes");
+    fs::create_dir_all(&crash_dir)
+        .with_context(|| format!("Failed to create directory: {}", crash_dir.display()))?;
+
+    let timestamp = Local::now().format("%Y%m%d_%H%M%S");
+    let path = crash_dir.join(format!("crash_{timestamp}.log"));
+    fs::write(&path, build_report(message, backtrace, &snapshot_actions()))
+        .with_context(|| format!("Failed to write crash report to: {}", path.display()))?;
+
+    Ok(path)
+}
+
+/// Returns the URL where a new issue can be opened for a crash.
+pub fn issue_url(message: &str) -> String {
+    let mut url = url::Url::parse(ISSUE_URL).expect("static issue URL is valid");
+    url.query_pairs_mut()
+        .append_pair("title", &format!("Crash: {message}"));
+    url.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_trail_is_bounded() {
+        // A trail of its own: the global one is shared with other tests
+        let mut actions = VecDeque::new();
+        for i in 0..MAX_ACTIONS + 5 {
+            push_action(&mut actions, format!("action {i}"));
+        }
+        let actions: Vec<String> = actions.into();
+        assert_eq!(actions.len(), MAX_ACTIONS);
+        assert_eq!(actions.first().map(String::as_str), Some("action 5"));
+        assert_eq!(actions.last().map(String::as_str), Some("action 24"));
+
+        let report = build_report("boom", "<bt>", &actions);
+        assert!(report.contains("Panic: boom"));
+        assert!(report.contains("  - action 24"));
+    }
+}
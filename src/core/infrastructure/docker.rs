@@ -17,17 +17,21 @@ use anyhow::Context;
 use anyhow::{Result, bail};
 use std::process::Command;
 
-/// Checks if Docker is available and running.
-/// If installed but not running, attempts to start Docker Desktop (Windows).
-pub fn ensure_docker_available() -> Result<()> {
-    // 1. Check if Docker is responsive (daemon running)
-    let status = Command::new("docker")
+/// Checks if the Docker daemon responds, without trying to start it.
+pub fn is_running() -> bool {
+    Command::new("docker")
         .arg("info")
         .stdout(std::process::Stdio::null())
         .stderr(std::process::Stdio::null())
-        .status();
+        .status()
+        .is_ok_and(|s| s.success())
+}
 
-    if status.is_ok_and(|s| s.success()) {
+/// Checks if Docker is available and running.
+/// If installed but not running, attempts to start Docker Desktop (Windows).
+pub fn ensure_docker_available() -> Result<()> {
+    // 1. Check if Docker is responsive (daemon running)
+    if is_running() {
         return Ok(());
     }
 
@@ -91,13 +95,7 @@ pub fn ensure_docker_available() -> Result<()> {
                 let _ = std::io::stdout().flush();
 
                 std::thread::sleep(std::time::Duration::from_secs(2));
-                let check = Command::new("docker")
-                    .arg("info")
-                    .stdout(std::process::Stdio::null())
-                    .stderr(std::process::Stdio::null())
-                    .status();
-
-                if check.is_ok_and(|s| s.success()) {
+                if is_running() {
                     println!("\n✅ Docker started successfully!");
                     return Ok(());
                 }
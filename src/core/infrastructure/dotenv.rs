@@ -0,0 +1,138 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Placeholder and environment values read from a `.env` file
+//! (`--env-file`), so a runbook can run without prompts.
+//!
+//! The usual dotenv syntax is understood: `KEY=value` lines, an optional
+//! `export ` prefix, `#` comments, single quotes kept literally and double
+//! quotes with `\n`, `\"` and `\\` escapes, which may span several lines.
+
+use anyhow::{Context, Result, bail};
+use std::path::Path;
+
+/// Reads the `KEY=value` pairs of a dotenv file, in file order.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or a line is not an
+/// assignment.
+pub fn load(path: &Path) -> Result<Vec<(String, String)>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read env file {}", path.display()))?;
+    parse(&content).with_context(|| format!("Invalid env file {}", path.display()))
+}
+
+/// Parses dotenv content into `KEY=value` pairs, in order.
+///
+/// # Errors
+///
+/// Returns an error naming the line that is not an assignment, or where a
+/// quoted value is not closed.
+pub fn parse(content: &str) -> Result<Vec<(String, String)>> {
+    let mut pairs = Vec::new();
+    let mut lines = content.lines().enumerate();
+    while let Some((n, line)) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, value)) = line.split_once('=') else {
+            bail!("line {}: expected KEY=value", n + 1);
+        };
+        let key = key.trim();
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            bail!("line {}: invalid name '{key}'", n + 1);
+        }
+
+        let value = value.trim_start();
+        let value = if let Some(rest) = value.strip_prefix('\'') {
+            match rest.split_once('\'') {
+                Some((literal, _)) => literal.to_string(),
+                None => bail!("line {}: unterminated single quote", n + 1),
+            }
+        } else if let Some(rest) = value.strip_prefix('"') {
+            // Double-quoted values go on until the closing quote
+            let mut quoted = rest.to_string();
+            loop {
+                if let Some(value) = unescape_quoted(&quoted) {
+                    break value;
+                }
+                let Some((_, next)) = lines.next() else {
+                    bail!("line {}: unterminated double quote", n + 1);
+                };
+                quoted.push('\n');
+                quoted.push_str(next);
+            }
+        } else {
+            // Unquoted values end at a comment
+            let end = value.find(" #").unwrap_or(value.len());
+            value[..end].trim_end().to_string()
+        };
+        pairs.push((key.to_string(), value));
+    }
+    Ok(pairs)
+}
+
+/// Decodes a double-quoted value up to its closing quote, or `None` if the
+/// quote is not closed yet.
+fn unescape_quoted(text: &str) -> Option<String> {
+    let mut value = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return Some(value),
+            '\\' => match chars.next()? {
+                'n' => value.push('\n'),
+                't' => value.push('\t'),
+                other => value.push(other),
+            },
+            other => value.push(other),
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let content = "# Deployment\nexport HOST=example.com\nPORT = 8080 # default\nEMPTY=\nLITERAL='a \\n #b'\nQUOTED=\"say \\\"hi\\\"\\nthere\"\nKEY=\"-----BEGIN-----\nabc\n-----END-----\"\n";
+        let pairs = parse(content).unwrap();
+        assert_eq!(
+            pairs,
+            vec![
+                ("HOST".to_string(), "example.com".to_string()),
+                ("PORT".to_string(), "8080".to_string()),
+                ("EMPTY".to_string(), String::new()),
+                ("LITERAL".to_string(), "a \\n #b".to_string()),
+                ("QUOTED".to_string(), "say \"hi\"\nthere".to_string()),
+                (
+                    "KEY".to_string(),
+                    "-----BEGIN-----\nabc\n-----END-----".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("just text\n").is_err());
+        assert!(parse("BAD NAME=1\n").is_err());
+        assert!(parse("OPEN=\"never closed\n").is_err());
+    }
+}
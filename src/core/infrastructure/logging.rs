@@ -0,0 +1,190 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Logging Module
+//!
+//! Sets up `tracing` for Compass. Events are appended to a log file in the
+//! config directory and the most recent ones are kept in memory so the TUI
+//! debug overlay can display them.
+
+use crate::core::infrastructure::config::ConfigManager;
+use anyhow::{Context, Result};
+use chrono::Local;
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context as LayerContext, Layer, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+
+/// Maximum number of events kept in memory for the debug overlay.
+const MAX_RECENT_EVENTS: usize = 200;
+
+/// Size past which the log file is moved aside at startup (5 MiB).
+const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+
+static RECENT_EVENTS: OnceLock<Mutex<VecDeque<String>>> = OnceLock::new();
+
+fn recent_buffer() -> &'static Mutex<VecDeque<String>> {
+    RECENT_EVENTS.get_or_init(|| Mutex::new(VecDeque::with_capacity(MAX_RECENT_EVENTS)))
+}
+
+/// Returns the most recent log events, oldest first.
+pub fn recent_events() -> Vec<String> {
+    recent_buffer()
+        .lock()
+        .map(|events| events.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Initializes the global subscriber and returns the path of the log file.
+///
+/// # Errors
+///
+/// Returns an error if the log file cannot be opened or a subscriber is
+/// already installed.
+pub fn init(level: LevelFilter) -> Result<PathBuf> {
+    let log_dir = ConfigManager::get_config_dir()?.join("logs");
+    fs::create_dir_all(&log_dir)
+        .with_context(|| format!("Failed to create directory: {}", log_dir.display()))?;
+
+    let log_path = log_dir.join("compass.log");
+    if let Err(e) = rotate(&log_path, MAX_LOG_BYTES) {
+        eprintln!("Could not rotate the log file: {e}");
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)
+        .with_context(|| format!("Failed to open log file: {}", log_path.display()))?;
+
+    tracing_subscriber::registry()
+        .with(level)
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(Mutex::new(file)),
+        )
+        .with(RecentEventsLayer)
+        .try_init()
+        .context("Failed to initialize logging")?;
+
+    Ok(log_path)
+}
+
+/// Moves the log file to `<name>.1` once it is larger than `max_bytes`,
+/// replacing the previous one, so the logs take at most twice that.
+fn rotate(path: &Path, max_bytes: u64) -> std::io::Result<()> {
+    match fs::metadata(path) {
+        Ok(metadata) if metadata.len() > max_bytes => {
+            let mut old = path.as_os_str().to_owned();
+            old.push(".1");
+            fs::rename(path, old)
+        }
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// A layer that keeps a formatted copy of the latest events in memory.
+struct RecentEventsLayer;
+
+impl<S: Subscriber> Layer<S> for RecentEventsLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: LayerContext<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+
+        let metadata = event.metadata();
+        let line = format!(
+            "{} {:<5} {}: {}",
+            Local::now().format("%H:%M:%S"),
+            metadata.level(),
+            metadata.target(),
+            visitor.0
+        );
+
+        if let Ok(mut events) = recent_buffer().lock() {
+            if events.len() == MAX_RECENT_EVENTS {
+                events.pop_front();
+            }
+            events.push_back(line);
+        }
+    }
+}
+
+/// Collects the message and extra fields of an event into a single line.
+#[derive(Default)]
+struct FieldVisitor(String);
+
+impl Visit for FieldVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.0, "{value:?}");
+        } else {
+            let _ = write!(self.0, " {}={value:?}", field.name());
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.0.push_str(value);
+        } else {
+            let _ = write!(self.0, " {}={value}", field.name());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotate_large_log() {
+        let dir = std::env::temp_dir().join(format!("compass_log_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("compass.log");
+
+        rotate(&path, 10).unwrap();
+        fs::write(&path, "short").unwrap();
+        rotate(&path, 10).unwrap();
+        assert!(path.exists());
+
+        fs::write(&path, "longer than ten bytes").unwrap();
+        rotate(&path, 10).unwrap();
+        let old = fs::read_to_string(dir.join("compass.log.1"));
+        let _ = fs::remove_dir_all(&dir);
+        assert!(!path.exists());
+        assert_eq!(old.unwrap(), "longer than ten bytes");
+    }
+
+    #[test]
+    fn test_recent_events_layer() {
+        let subscriber = tracing_subscriber::registry().with(RecentEventsLayer);
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!(step = 2, path = "app.env", "Failed to write file");
+        });
+
+        let events = recent_events();
+        let line = events.last().unwrap();
+        assert!(
+            line.ends_with(" WARN  compass_cli::core::infrastructure::logging::tests: Failed to write file step=2 path=app.env"),
+            "{line}"
+        );
+    }
+}
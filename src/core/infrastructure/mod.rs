@@ -1,2 +1,12 @@
+pub mod bench;
 pub mod config;
+pub mod crash;
 pub mod docker;
+pub mod dotenv;
+pub mod logging;
+pub mod monitor;
+pub mod recent;
+pub mod session;
+pub mod snippets;
+pub mod trust;
+pub mod wizard;
@@ -0,0 +1,118 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Sampling of the host's CPU, memory and disk space while steps run, in a
+//! background thread.
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+use sysinfo::{Disk, Disks, System};
+
+/// Time between two samples.
+const INTERVAL: Duration = Duration::from_secs(1);
+
+/// Usage of the host at one point in time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResourceSample {
+    /// CPU usage of all cores together, in percent.
+    pub cpu: f32,
+    /// Memory in use, in bytes.
+    pub memory_used: u64,
+    /// Memory of the host, in bytes.
+    pub memory_total: u64,
+    /// Free space on the disk holding the working directory, in bytes, if
+    /// that disk was found.
+    pub disk_free: Option<u64>,
+}
+
+/// Samples the host every second until it is dropped.
+pub struct ResourceMonitor {
+    rx: Receiver<ResourceSample>,
+    latest: Option<ResourceSample>,
+}
+
+impl ResourceMonitor {
+    /// Starts sampling, reading the free space of the disk holding `dir`.
+    pub fn start(dir: PathBuf) -> Self {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let mut system = System::new();
+            let mut disks = Disks::new_with_refreshed_list();
+            // CPU usage is measured between two refreshes
+            system.refresh_cpu_usage();
+            loop {
+                thread::sleep(INTERVAL);
+                system.refresh_cpu_usage();
+                system.refresh_memory();
+                disks.refresh(false);
+                let sample = ResourceSample {
+                    cpu: system.global_cpu_usage(),
+                    memory_used: system.used_memory(),
+                    memory_total: system.total_memory(),
+                    disk_free: free_space(&disks, &dir),
+                };
+                // The monitor was dropped
+                if tx.send(sample).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { rx, latest: None }
+    }
+
+    /// The most recent sample, `None` until the first one is taken.
+    pub fn latest(&mut self) -> Option<ResourceSample> {
+        while let Ok(sample) = self.rx.try_recv() {
+            self.latest = Some(sample);
+        }
+        self.latest
+    }
+}
+
+/// Free space of the disk mounted closest to `dir`.
+fn free_space(disks: &Disks, dir: &Path) -> Option<u64> {
+    disks
+        .list()
+        .iter()
+        .filter(|disk| dir.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(Disk::available_space)
+}
+
+/// Formats a number of bytes as `512M` or `3.2G`.
+pub fn format_bytes(bytes: u64) -> String {
+    const MIB: u64 = 1024 * 1024;
+    const GIB: u64 = 1024 * MIB;
+    if bytes < GIB {
+        format!("{}M", bytes / MIB)
+    } else {
+        format!("{:.1}G", bytes as f64 / GIB as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(300 * 1024 * 1024), "300M");
+        assert_eq!(
+            format_bytes(3 * 1024 * 1024 * 1024 + 200 * 1024 * 1024),
+            "3.2G"
+        );
+    }
+}
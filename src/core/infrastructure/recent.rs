@@ -0,0 +1,138 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Recently opened runbooks, stored in `recent.json` in the config directory.
+
+use crate::core::infrastructure::config::ConfigManager;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// The file holding the recent runbooks, inside the config directory.
+const RECENT_FILE: &str = "recent.json";
+
+/// Maximum number of runbooks remembered.
+const MAX_RECENT: usize = 20;
+
+/// A runbook opened in the TUI.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentRunbook {
+    /// Local path or URL of the runbook.
+    pub location: String,
+    /// Title of the first step, if any.
+    pub title: Option<String>,
+    /// Index of the step selected when the runbook was closed.
+    pub last_step: usize,
+    /// When the runbook was last closed (RFC 3339).
+    pub opened_at: String,
+}
+
+impl std::fmt::Display for RecentRunbook {
+    /// Formats the entry as `title — location (step n, date)`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(title) = &self.title {
+            write!(f, "{title} — ")?;
+        }
+        let date = self
+            .opened_at
+            .get(..16)
+            .unwrap_or(&self.opened_at)
+            .replace('T', " ");
+        write!(f, "{} (step {}, {date})", self.location, self.last_step + 1)
+    }
+}
+
+fn path() -> Result<PathBuf> {
+    Ok(ConfigManager::get_config_dir()?.join(RECENT_FILE))
+}
+
+/// Loads the recent runbooks, most recent first.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be read or parsed.
+pub fn load() -> Result<Vec<RecentRunbook>> {
+    let path = path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read recent runbooks: {}", path.display()))?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse recent runbooks: {}", path.display()))
+}
+
+/// Returns the step reached the last time the runbook was opened.
+pub fn last_step(location: &str) -> Option<usize> {
+    load()
+        .ok()?
+        .into_iter()
+        .find(|r| r.location == location)
+        .map(|r| r.last_step)
+}
+
+/// Moves the runbook to the top of the list, remembering the step reached.
+///
+/// # Errors
+///
+/// Returns an error if the list cannot be saved.
+pub fn record(location: &str, title: Option<String>, last_step: usize) -> Result<()> {
+    let mut recent = load().unwrap_or_default();
+    recent.retain(|r| r.location != location);
+    recent.insert(
+        0,
+        RecentRunbook {
+            location: location.to_string(),
+            title,
+            last_step,
+            opened_at: chrono::Local::now().to_rfc3339(),
+        },
+    );
+    recent.truncate(MAX_RECENT);
+
+    let path = path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let content =
+        serde_json::to_string_pretty(&recent).context("Failed to serialize recent runbooks")?;
+    fs::write(&path, content)
+        .with_context(|| format!("Failed to write recent runbooks: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_recent_runbook() {
+        let mut runbook = RecentRunbook {
+            location: "/srv/app/README.md".to_string(),
+            title: Some("Deploy".to_string()),
+            last_step: 2,
+            opened_at: "2026-03-14T09:26:53.589+01:00".to_string(),
+        };
+        assert_eq!(
+            runbook.to_string(),
+            "Deploy — /srv/app/README.md (step 3, 2026-03-14 09:26)"
+        );
+
+        runbook.title = None;
+        assert_eq!(
+            runbook.to_string(),
+            "/srv/app/README.md (step 3, 2026-03-14 09:26)"
+        );
+    }
+}
@@ -0,0 +1,228 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Sessions saved while a runbook is followed in the TUI, so that
+//! `compass tui --resume` picks up where a closed or crashed terminal left
+//! off. One file per runbook, next to its configuration in the config
+//! directory.
+
+use crate::core::executor::engine::context::ExecutionContext;
+use crate::core::export::context::ContextExport;
+use crate::core::infrastructure::config::ConfigManager;
+use crate::core::models::{ExecutionRecord, Step, StepRun, StepStatus, is_secret_placeholder};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The state of a step when the session was saved.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedStep {
+    /// Title of the step, to detect a runbook changed since.
+    pub title: String,
+    /// Status of the last run.
+    pub status: StepStatus,
+    /// Output of the last run.
+    pub output: String,
+    /// Standard output of the last run, when the streams are separated.
+    #[serde(default)]
+    pub stdout: String,
+    /// Standard error of the last run, when the streams are separated.
+    #[serde(default)]
+    pub stderr: String,
+    /// When the last run started and ended.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execution: Option<ExecutionRecord>,
+    /// Earlier runs of the step.
+    #[serde(default)]
+    pub history: Vec<StepRun>,
+}
+
+/// A session of the TUI, as saved to disk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSession {
+    /// Working directory, environment and placeholder values (secrets left out).
+    pub context: ContextExport,
+    /// Position of the selected step in the runbook.
+    pub selected: usize,
+    /// The steps of the runbook, without those added during the session.
+    pub steps: Vec<SavedStep>,
+}
+
+impl SavedSession {
+    /// Captures the session: `placeholders` should already be stripped of
+    /// secrets, environment variables holding one are dropped here.
+    #[must_use]
+    pub fn capture(
+        steps: &[Step],
+        selected: usize,
+        context: &ExecutionContext,
+        placeholders: &HashMap<String, String>,
+        runbook: &Path,
+        version: &str,
+    ) -> Self {
+        let mut export = ContextExport::capture(context, placeholders, runbook, version);
+        export.env_vars.retain(|name, value| {
+            !is_secret_placeholder(name) && !context.secrets.iter().any(|s| s == value)
+        });
+        let selected = steps[..selected.min(steps.len())]
+            .iter()
+            .filter(|s| !s.session_only)
+            .count();
+        let steps = steps
+            .iter()
+            .filter(|s| !s.session_only)
+            .map(|s| SavedStep {
+                title: s.title.clone(),
+                status: s.status,
+                output: s.output.clone(),
+                stdout: s.stdout.clone(),
+                stderr: s.stderr.clone(),
+                execution: s.execution.clone(),
+                history: s.history.clone(),
+            })
+            .collect();
+        Self {
+            context: export,
+            selected,
+            steps,
+        }
+    }
+
+    /// Writes the session as JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_string_pretty(self).context("Failed to serialize session")?;
+        fs::write(path, content)
+            .with_context(|| format!("Failed to write session: {}", path.display()))
+    }
+
+    /// Reads a session saved by [`SavedSession::save`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read session: {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse session: {}", path.display()))
+    }
+
+    /// Restores the statuses and outputs of `steps` and the context of the
+    /// session. Steps whose title changed since are left pending; a step
+    /// still running when the session was saved is marked cancelled.
+    /// Returns the warnings to show.
+    pub fn apply(&self, steps: &mut [Step], context: &mut ExecutionContext) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let mut changed = 0;
+        let runbook = steps.iter_mut().filter(|s| !s.session_only);
+        for (step, saved) in runbook.zip(&self.steps) {
+            if step.title != saved.title {
+                changed += 1;
+                continue;
+            }
+            step.status = saved.status;
+            step.output.clone_from(&saved.output);
+            step.stdout.clone_from(&saved.stdout);
+            step.stderr.clone_from(&saved.stderr);
+            step.execution.clone_from(&saved.execution);
+            step.history.clone_from(&saved.history);
+            if step.status == StepStatus::Running {
+                step.status = StepStatus::Cancelled;
+                step.output.push_str(
+                    "\n\n---\n⛔ Interrupted: Compass closed while the step was running.",
+                );
+            }
+        }
+        if changed > 0 {
+            warnings.push(format!(
+                "The runbook changed since the session was saved: {changed} step(s) start over."
+            ));
+        }
+        warnings.extend(self.context.apply(context));
+        warnings
+    }
+}
+
+/// Path of the session file of the runbook at `location` (a path or a URL).
+///
+/// # Errors
+///
+/// Returns an error if the config directory cannot be determined.
+pub fn path(location: &Path) -> Result<PathBuf> {
+    let location = location
+        .canonicalize()
+        .unwrap_or_else(|_| location.to_path_buf());
+    let hash = ConfigManager::path_hash(&location);
+    Ok(ConfigManager::get_config_dir()?.join(format!("session_{hash:016x}.json")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(title: &str) -> Step {
+        Step {
+            title: title.to_string(),
+            ..Step::default()
+        }
+    }
+
+    #[test]
+    fn test_capture_apply() {
+        let mut steps = vec![step("Build"), step("Deploy")];
+        steps[0].status = StepStatus::Success;
+        steps[0].output = "built".to_string();
+        steps[1].status = StepStatus::Running;
+        let mut context = ExecutionContext::new();
+        context.env_vars.extend([
+            ("STAGE".to_string(), "prod".to_string()),
+            ("API_TOKEN".to_string(), "t0k3n".to_string()),
+            ("PASS".to_string(), "hunter2".to_string()),
+        ]);
+        context.add_secret("hunter2");
+
+        let session = SavedSession::capture(
+            &steps,
+            1,
+            &context,
+            &HashMap::new(),
+            Path::new("README.md"),
+            "1.0.0",
+        );
+        assert_eq!(session.selected, 1);
+        assert_eq!(session.context.env_vars.len(), 1);
+
+        let mut fresh = vec![step("Build"), step("Deploy")];
+        let mut other = ExecutionContext::new();
+        assert!(session.apply(&mut fresh, &mut other).is_empty());
+        assert_eq!(fresh[0].status, StepStatus::Success);
+        assert_eq!(fresh[0].output, "built");
+        assert_eq!(fresh[1].status, StepStatus::Cancelled);
+        assert_eq!(other.env_vars["STAGE"], "prod");
+
+        let mut edited = vec![step("Compile"), step("Deploy")];
+        let warnings = session.apply(&mut edited, &mut ExecutionContext::new());
+        assert_eq!(edited[0].status, StepStatus::Pending);
+        assert_eq!(warnings.len(), 1);
+    }
+}
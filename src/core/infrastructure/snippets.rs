@@ -0,0 +1,134 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The user's snippet library, stored in `snippets.json` in the config
+//! directory: commands run often while following any runbook.
+
+use crate::core::infrastructure::config::ConfigManager;
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// The file holding the snippets, inside the config directory.
+const SNIPPETS_FILE: &str = "snippets.json";
+
+/// A command saved for reuse.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snippet {
+    /// Short name shown in the palette.
+    pub name: String,
+    /// The command to run.
+    pub command: String,
+    /// Language of the command (the preferred shell by default).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// How many times the snippet was run from the palette.
+    #[serde(default)]
+    pub uses: u32,
+}
+
+impl std::fmt::Display for Snippet {
+    /// Formats the snippet as `name: command`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.name, self.command)
+    }
+}
+
+fn path() -> Result<PathBuf> {
+    Ok(ConfigManager::get_config_dir()?.join(SNIPPETS_FILE))
+}
+
+/// Loads the snippets, most used first.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be read or parsed.
+pub fn load() -> Result<Vec<Snippet>> {
+    let path = path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read snippets: {}", path.display()))?;
+    let mut snippets: Vec<Snippet> = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse snippets: {}", path.display()))?;
+    snippets.sort_by_key(|s| std::cmp::Reverse(s.uses));
+    Ok(snippets)
+}
+
+fn save(snippets: &[Snippet]) -> Result<()> {
+    let path = path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let content = serde_json::to_string_pretty(snippets).context("Failed to serialize snippets")?;
+    fs::write(&path, content)
+        .with_context(|| format!("Failed to write snippets: {}", path.display()))
+}
+
+/// Adds a snippet, replacing the one with the same name.
+///
+/// # Errors
+///
+/// Returns an error if the name or command is empty, or the library cannot
+/// be saved.
+pub fn add(name: &str, command: &str, language: Option<String>) -> Result<()> {
+    let (name, command) = (name.trim(), command.trim());
+    if name.is_empty() || command.is_empty() {
+        bail!("A snippet needs a name and a command.");
+    }
+    let mut snippets = load()?;
+    let uses = snippets
+        .iter()
+        .find(|s| s.name == name)
+        .map_or(0, |s| s.uses);
+    snippets.retain(|s| s.name != name);
+    snippets.push(Snippet {
+        name: name.to_string(),
+        command: command.to_string(),
+        language,
+        uses,
+    });
+    save(&snippets)
+}
+
+/// Removes a snippet by name.
+///
+/// # Errors
+///
+/// Returns an error if there is no such snippet or the library cannot be saved.
+pub fn remove(name: &str) -> Result<()> {
+    let mut snippets = load()?;
+    let count = snippets.len();
+    snippets.retain(|s| s.name != name);
+    if snippets.len() == count {
+        bail!("No snippet named '{name}'.");
+    }
+    save(&snippets)
+}
+
+/// Counts a run of the snippet, so the most used ones come first.
+///
+/// # Errors
+///
+/// Returns an error if the library cannot be saved.
+pub fn record_use(name: &str) -> Result<()> {
+    let mut snippets = load()?;
+    if let Some(snippet) = snippets.iter_mut().find(|s| s.name == name) {
+        snippet.uses += 1;
+        save(&snippets)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,169 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runbooks the user trusted (accepted their hooks or allowed one of their
+//! dangerous commands), stored in `trust.json` in the config directory with a
+//! hash of their content. Once a runbook changes it is no longer trusted: the
+//! user is shown what changed since and asked again.
+
+use crate::core::analysis::edits::{self, DiffLine};
+use crate::core::infrastructure::config::ConfigManager;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The file holding the trusted runbooks, inside the config directory.
+const TRUST_FILE: &str = "trust.json";
+
+/// The version of a runbook the user trusted last.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustedRunbook {
+    /// Local path or URL of the runbook.
+    pub location: String,
+    /// SHA-256 of its content.
+    pub hash: String,
+    /// Its content, to show what changed since.
+    pub content: String,
+    /// When it was trusted (RFC 3339).
+    pub trusted_at: String,
+}
+
+impl TrustedRunbook {
+    /// The date it was trusted, as `YYYY-MM-DD HH:MM`.
+    pub fn date(&self) -> String {
+        self.trusted_at
+            .get(..16)
+            .unwrap_or(&self.trusted_at)
+            .replace('T', " ")
+    }
+}
+
+/// How a runbook compares with the version the user trusted.
+#[derive(Debug, Clone)]
+pub enum Trust {
+    /// Never trusted.
+    Unknown,
+    /// Unchanged since it was trusted.
+    Trusted(TrustedRunbook),
+    /// Changed since this version was trusted.
+    Changed(TrustedRunbook),
+}
+
+/// Key of a runbook in the store: its canonical path, or its URL.
+pub fn location(path: &Path) -> String {
+    path.canonicalize()
+        .unwrap_or_else(|_| path.to_path_buf())
+        .to_string_lossy()
+        .to_string()
+}
+
+/// SHA-256 of a runbook, in hexadecimal.
+pub fn content_hash(content: &str) -> String {
+    hex::encode(Sha256::digest(content.as_bytes()))
+}
+
+fn path() -> Result<PathBuf> {
+    Ok(ConfigManager::get_config_dir()?.join(TRUST_FILE))
+}
+
+/// Loads the trusted runbooks.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be read or parsed.
+pub fn load() -> Result<Vec<TrustedRunbook>> {
+    let path = path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read trusted runbooks: {}", path.display()))?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse trusted runbooks: {}", path.display()))
+}
+
+/// Compares a runbook with the version of it the user trusted.
+pub fn check(location: &str, content: &str) -> Trust {
+    compare(load().unwrap_or_default(), location, content)
+}
+
+fn compare(trusted: Vec<TrustedRunbook>, location: &str, content: &str) -> Trust {
+    match trusted.into_iter().find(|t| t.location == location) {
+        None => Trust::Unknown,
+        Some(runbook) if runbook.hash == content_hash(content) => Trust::Trusted(runbook),
+        Some(runbook) => Trust::Changed(runbook),
+    }
+}
+
+/// The changes made to a runbook since the version the user trusted.
+pub fn changes(trusted: &TrustedRunbook, content: &str) -> Vec<DiffLine> {
+    edits::diff(&trusted.content, content)
+}
+
+/// Records this content of the runbook as trusted, in place of an earlier
+/// version.
+///
+/// # Errors
+///
+/// Returns an error if the store cannot be saved.
+pub fn record(location: &str, content: &str) -> Result<()> {
+    let mut trusted = load().unwrap_or_default();
+    trusted.retain(|t| t.location != location);
+    trusted.push(TrustedRunbook {
+        location: location.to_string(),
+        hash: content_hash(content),
+        content: content.to_string(),
+        trusted_at: chrono::Local::now().to_rfc3339(),
+    });
+
+    let path = path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let content =
+        serde_json::to_string_pretty(&trusted).context("Failed to serialize trusted runbooks")?;
+    fs::write(&path, content)
+        .with_context(|| format!("Failed to write trusted runbooks: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_with_trusted_version() {
+        let trusted = vec![TrustedRunbook {
+            location: "/runbooks/setup.md".to_string(),
+            hash: content_hash("# Setup\necho hi\n"),
+            content: "# Setup\necho hi\n".to_string(),
+            trusted_at: "2026-10-16T10:00:00+02:00".to_string(),
+        }];
+        let check = |location, content| compare(trusted.clone(), location, content);
+
+        assert!(matches!(check("/runbooks/other.md", "x"), Trust::Unknown));
+        assert!(matches!(
+            check("/runbooks/setup.md", "# Setup\necho hi\n"),
+            Trust::Trusted(_)
+        ));
+        let Trust::Changed(previous) = check("/runbooks/setup.md", "# Setup\ncurl x | sh\n") else {
+            panic!("expected a change");
+        };
+        assert_eq!(previous.date(), "2026-10-16 10:00");
+        let changes = changes(&previous, "# Setup\ncurl x | sh\n");
+        assert!(changes.contains(&DiffLine::Removed("echo hi".to_string())));
+        assert!(changes.contains(&DiffLine::Added("curl x | sh".to_string())));
+    }
+}
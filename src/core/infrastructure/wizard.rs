@@ -0,0 +1,189 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! First-run setup wizard, asking for the global settings on the terminal.
+
+use crate::core::executor::languages::strategies::shell::Shell;
+use crate::core::infrastructure::config::{GlobalConfig, SafetyPolicy, StatusMarkers, Theme};
+use anyhow::Result;
+use std::io::{self, BufRead, Write};
+
+/// Asks for each setting, keeping the current value when the answer is empty,
+/// then saves the result.
+///
+/// # Errors
+///
+/// Returns an error if stdin cannot be read or the settings cannot be saved.
+pub fn run(current: &GlobalConfig) -> Result<GlobalConfig> {
+    println!(
+        "🧭 Welcome to Compass! Let's set a few defaults (press Enter to keep the suggested value).\n"
+    );
+
+    let mut settings = current.clone();
+    settings.theme = ask(
+        "Theme [dark/light/high-contrast]",
+        current.theme,
+        |answer| match answer {
+            "dark" => Some(Theme::Dark),
+            "light" => Some(Theme::Light),
+            "high-contrast" | "contrast" => Some(Theme::HighContrast),
+            _ => None,
+        },
+        |theme| {
+            match theme {
+                Theme::Dark => "dark",
+                Theme::Light => "light",
+                Theme::HighContrast => "high-contrast",
+            }
+            .to_string()
+        },
+    )?;
+    settings.status_markers = ask(
+        "Status markers: emoji, or text like [OK] [!!] that does not rely on colors [emoji/text]",
+        current.status_markers,
+        |answer| match answer {
+            "emoji" => Some(StatusMarkers::Emoji),
+            "text" => Some(StatusMarkers::Text),
+            _ => None,
+        },
+        |markers| format!("{markers:?}").to_lowercase(),
+    )?;
+    settings.safety_policy = ask(
+        "Dangerous commands: prompt to confirm, strict to block, relaxed to run [prompt/strict/relaxed]",
+        current.safety_policy,
+        |answer| match answer {
+            "prompt" => Some(SafetyPolicy::Prompt),
+            "strict" => Some(SafetyPolicy::Strict),
+            "relaxed" => Some(SafetyPolicy::Relaxed),
+            _ => None,
+        },
+        |policy| format!("{policy:?}").to_lowercase(),
+    )?;
+    settings.sandbox = ask(
+        "Run steps in a Docker sandbox by default? [y/n]",
+        current.sandbox,
+        yes_no,
+        bool_label,
+    )?;
+    if settings.sandbox {
+        settings.docker_image = ask(
+            "Docker image",
+            current.docker_image.clone(),
+            |answer| Some(answer.to_string()),
+            Clone::clone,
+        )?;
+    }
+    settings.registry_enabled = ask(
+        "Look up runbooks in the online registry when a file is not found? [y/n]",
+        current.registry_enabled,
+        yes_no,
+        bool_label,
+    )?;
+    settings.max_download_mb = ask(
+        "Largest runbook to download from a URL, in MiB (longer ones are cut)",
+        current.max_download_mb,
+        |answer| answer.parse().ok().filter(|mb| *mb > 0),
+        u64::to_string,
+    )?;
+    settings.linear_output = ask(
+        "Use linear output for screen readers instead of the full-screen interface? [y/n]",
+        current.linear_output,
+        yes_no,
+        bool_label,
+    )?;
+
+    settings.shell = ask(
+        "Shell for untagged code blocks [system/bash/zsh/fish/pwsh]",
+        current.shell,
+        |answer| match answer {
+            "system" | "default" => Some(Shell::System),
+            "bash" => Some(Shell::Bash),
+            "zsh" => Some(Shell::Zsh),
+            "fish" => Some(Shell::Fish),
+            "pwsh" | "powershell" => Some(Shell::Pwsh),
+            _ => None,
+        },
+        |shell| format!("{shell:?}").to_lowercase(),
+    )?;
+    settings.source_profile = ask(
+        "Load your shell profile (e.g. for PATH customizations) before each step? [y/n]",
+        current.source_profile,
+        yes_no,
+        bool_label,
+    )?;
+    settings.inline_commands = ask(
+        "Offer inline code like `npm ci` as commands in steps without code blocks? [y/n]",
+        current.inline_commands,
+        yes_no,
+        bool_label,
+    )?;
+    settings.separate_streams = ask(
+        "Capture stdout and stderr separately? Commands then run without a terminal and cannot be typed into [y/n]",
+        current.separate_streams,
+        yes_no,
+        bool_label,
+    )?;
+    settings.min_free_disk_mb = ask(
+        "Warn when free disk space drops below this while steps run, in MiB (0 to never warn)",
+        current.min_free_disk_mb,
+        |answer| answer.parse().ok(),
+        u64::to_string,
+    )?;
+
+    let path = settings.save()?;
+    println!(
+        "\n✅ Settings saved to {}. Run `compass setup` to change them.\n",
+        path.display()
+    );
+    Ok(settings)
+}
+
+/// Asks a question until the answer is empty (keeping `default`) or valid.
+fn ask<T>(
+    question: &str,
+    default: T,
+    parse: impl Fn(&str) -> Option<T>,
+    label: impl Fn(&T) -> String,
+) -> Result<T> {
+    let stdin = io::stdin();
+    loop {
+        print!("{question} ({}): ", label(&default));
+        io::stdout().flush()?;
+
+        let mut answer = String::new();
+        if stdin.lock().read_line(&mut answer)? == 0 {
+            return Ok(default);
+        }
+        let answer = answer.trim().to_lowercase();
+        if answer.is_empty() {
+            return Ok(default);
+        }
+        match parse(&answer) {
+            Some(value) => return Ok(value),
+            None => println!("  Unrecognized answer '{answer}'."),
+        }
+    }
+}
+
+fn yes_no(answer: &str) -> Option<bool> {
+    match answer {
+        "y" | "yes" => Some(true),
+        "n" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+fn bool_label(value: &bool) -> String {
+    if *value { "y" } else { "n" }.to_string()
+}
@@ -0,0 +1,191 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::core::executor::binaries;
+use crate::core::export::runbook::github::Matrix;
+use crate::core::models::ContextMode;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The frontmatter of a runbook: what it is and needs, event hooks, options
+/// of the steps and declarations of its placeholders.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RunbookManifest {
+    /// Name of the runbook, shown in the title bar.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// What the runbook is for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Binaries the steps need, checked when the runbook is loaded.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub requires: Vec<String>,
+    /// Oldest version of Compass the runbook works with (`2.1` or `2.1.0`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_version: Option<String>,
+    /// Docker image of sandbox mode, unless `--image` picks another one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    pub pre_run: Option<String>,
+    pub post_run: Option<String>,
+    pub on_failure: Option<String>,
+    pub on_success: Option<String>,
+    /// Default context mode of the steps (`shared` or `isolated`).
+    #[serde(default)]
+    pub context: ContextMode,
+    /// Header depth of the steps: deeper headers are folded into their step.
+    #[serde(default)]
+    pub step_level: Option<u8>,
+    /// Environment variables set for the commands of every step; those of a
+    /// step take precedence.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Placeholders holding secrets, masked when typed and never saved.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secrets: Vec<String>,
+    /// Default value, help text and format of placeholders, by name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub placeholders: BTreeMap<String, PlaceholderSpec>,
+    /// Matrix axes used when exporting to GitHub Actions.
+    #[serde(default, skip_serializing_if = "Matrix::is_empty")]
+    pub matrix: Matrix,
+}
+
+impl RunbookManifest {
+    pub fn has_any(&self) -> bool {
+        self.pre_run.is_some()
+            || self.post_run.is_some()
+            || self.on_failure.is_some()
+            || self.on_success.is_some()
+    }
+
+    /// Problems running the runbook with this version of Compass: a newer
+    /// version required, or required binaries missing.
+    pub fn warnings(&self, version: &str) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if let Some(min_version) = &self.min_version
+            && version_parts(version) < version_parts(min_version)
+        {
+            warnings.push(format!(
+                "This runbook requires Compass {min_version} or later (this is {version}); some steps may not work."
+            ));
+        }
+        let missing: Vec<&str> = self
+            .requires
+            .iter()
+            .filter(|tool| !binaries::exists(tool))
+            .map(String::as_str)
+            .collect();
+        if !missing.is_empty() {
+            warnings.push(format!(
+                "This runbook requires tools that are not installed: {}.",
+                missing.join(", ")
+            ));
+        }
+        warnings
+    }
+}
+
+/// Numbers of a version, to compare them: `v2.1` gives `[2, 1, 0]`. Suffixes
+/// such as `-beta` are ignored.
+fn version_parts(version: &str) -> [u64; 3] {
+    let mut parts = [0; 3];
+    let numbers = version.trim().trim_start_matches('v').split('.');
+    for (part, number) in parts.iter_mut().zip(numbers) {
+        let digits = number
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(number.len());
+        *part = number[..digits].parse().unwrap_or(0);
+    }
+    parts
+}
+
+/// What a runbook declares about one of its placeholders.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct PlaceholderSpec {
+    /// Value used when none is given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    /// Help text shown when the value is asked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Regular expression the whole value must match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// The only values accepted, picked from a list.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub choices: Vec<String>,
+    /// Secret manager the value is read from instead of being typed
+    /// (`vault:secret/data/app#token`, see [`crate::core::secrets`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+impl PlaceholderSpec {
+    /// Checks `value` against the pattern, describing the problem if it does
+    /// not match. A pattern that is not a valid regular expression accepts
+    /// everything.
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        let Some(pattern) = &self.pattern else {
+            return Ok(());
+        };
+        match Regex::new(&format!("^(?:{pattern})$")) {
+            Ok(re) if !re.is_match(value) => Err(format!("The value must match {pattern}")),
+            Ok(_) => Ok(()),
+            Err(e) => {
+                tracing::warn!(%pattern, error = %e, "Invalid placeholder pattern");
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholder_specs() {
+        let manifest: RunbookManifest = serde_yaml::from_str(
+            "placeholders:\n  PORT:\n    default: \"8080\"\n    description: Port of the server\n    pattern: \"[0-9]+\"\n",
+        )
+        .unwrap();
+        let spec = &manifest.placeholders["PORT"];
+        assert_eq!(spec.default.as_deref(), Some("8080"));
+        assert_eq!(spec.description.as_deref(), Some("Port of the server"));
+        assert!(spec.validate("443").is_ok());
+        assert!(spec.validate("80a").is_err());
+        assert!(PlaceholderSpec::default().validate("anything").is_ok());
+    }
+
+    #[test]
+    fn test_runbook_requirements() {
+        let manifest: RunbookManifest = serde_yaml::from_str(
+            "title: Deploy\nauthor: Ops\nmin_version: \"2.1\"\nrequires: [sh, surely-not-installed-tool]\nimage: alpine:3\n",
+        )
+        .unwrap();
+        assert_eq!(manifest.title.as_deref(), Some("Deploy"));
+        assert_eq!(manifest.image.as_deref(), Some("alpine:3"));
+
+        let warnings = manifest.warnings("2.0.7");
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("Compass 2.1 or later"));
+        assert!(warnings[1].ends_with(": surely-not-installed-tool."));
+        assert_eq!(manifest.warnings("2.1.0-beta").len(), 1);
+        assert_eq!(version_parts("v10.2"), [10, 2, 0]);
+    }
+}
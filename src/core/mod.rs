@@ -17,17 +17,27 @@
 //! This module contains the core functionality of Compass:
 //!
 //! - `config` - Persistent configuration management
+//! - `documents` - AsciiDoc and reStructuredText conversion to Markdown
 //! - `executor` - Command execution engine with PTY support
 //! - `export` - Session export to JSON and Markdown
+//! - `manifest` - Runbook frontmatter (hooks, options, placeholder declarations)
 //! - `models` - Data models (`Step`, `CodeBlock`, `StepStatus`)
+//! - `outline` - Heading hierarchy of the steps
 //! - `parser` - Markdown README parser
+//! - `plugins` - Registries of rewriters, languages, recovery rules, exporters and secret managers
+//! - `secrets` - Placeholder values read from secret managers
 
 pub mod analysis;
 pub mod collab;
+pub mod documents;
 pub mod ecosystem;
 pub mod executor;
 pub mod export;
 pub mod fetcher;
 pub mod infrastructure;
+pub mod manifest;
 pub mod models;
+pub mod outline;
 pub mod parser;
+pub mod plugins;
+pub mod secrets;
@@ -12,10 +12,35 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use chrono::{DateTime, Local};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Maximum number of past runs kept per step.
+pub const MAX_STEP_HISTORY: usize = 10;
+
+/// Name fragments marking a placeholder as holding a secret.
+const SECRET_MARKERS: &[&str] = &[
+    "SECRET",
+    "TOKEN",
+    "PASSWORD",
+    "PASSWD",
+    "PASSPHRASE",
+    "API_KEY",
+    "PRIVATE_KEY",
+    "CREDENTIAL",
+];
+
+/// Checks if a placeholder name looks like it holds a secret (e.g. `API_TOKEN`).
+pub fn is_secret_placeholder(name: &str) -> bool {
+    let name = name.to_uppercase().replace('-', "_");
+    SECRET_MARKERS.iter().any(|marker| name.contains(marker))
+}
 
 /// The status of a step's execution.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
 pub enum StepStatus {
     #[default]
     Pending,
@@ -23,6 +48,29 @@ pub enum StepStatus {
     Success,
     Failed,
     Skipped,
+    /// Killed by the user while running.
+    Cancelled,
+    /// Killed after running longer than its timeout.
+    TimedOut,
+}
+
+impl StepStatus {
+    /// Whether the step ran and did not succeed (cancelled steps excluded).
+    pub const fn is_failure(self) -> bool {
+        matches!(self, Self::Failed | Self::TimedOut)
+    }
+}
+
+/// How a step shares the working directory and environment with other steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContextMode {
+    /// `cd` and `export` carry over to the following steps.
+    #[default]
+    Shared,
+    /// The step starts from the initial directory and environment, and its
+    /// changes are discarded when it finishes.
+    Isolated,
 }
 
 /// A condition that must be met for a step to be applicable.
@@ -30,10 +78,263 @@ pub enum StepStatus {
 pub enum Condition {
     /// The step applies only to a specific OS (linux, macos, windows).
     Os(String),
+    /// The step applies only to a specific CPU architecture (x86_64, aarch64).
+    Arch(String),
     /// The step applies only if a specific environment variable exists.
     EnvVarExists(String),
     /// The step applies only if a specific file exists.
     FileExists(String),
+    /// The step applies only if a specific command is on the `PATH`.
+    CommandExists(String),
+    /// The step applies only if the condition is not met (`!`, `compass:else`).
+    Not(Box<Condition>),
+    /// The step applies only if all the conditions are met (`&&`).
+    All(Vec<Condition>),
+    /// The step applies if one of the conditions is met (`||`).
+    Any(Vec<Condition>),
+}
+
+impl Condition {
+    /// The condition of a `compass:else` branch: none of `branches` is met.
+    pub fn otherwise(branches: &[Condition]) -> Option<Self> {
+        let mut negations: Vec<Self> = branches
+            .iter()
+            .map(|c| Self::Not(Box::new(c.clone())))
+            .collect();
+        match negations.len() {
+            0 => None,
+            1 => negations.pop(),
+            _ => Some(Self::All(negations)),
+        }
+    }
+
+    /// The predicates the condition is made of, without the operators.
+    pub fn predicates(&self) -> Vec<&Self> {
+        match self {
+            Self::Not(inner) => inner.predicates(),
+            Self::All(conditions) | Self::Any(conditions) => {
+                conditions.iter().flat_map(Self::predicates).collect()
+            }
+            predicate => vec![predicate],
+        }
+    }
+}
+
+impl fmt::Display for Condition {
+    /// Formats the condition the way it is written in a `compass:if` annotation.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Os(os) => write!(f, "os={os}"),
+            Self::Arch(arch) => write!(f, "arch={arch}"),
+            Self::EnvVarExists(var) => write!(f, "env_var_exists={var}"),
+            Self::FileExists(path) => write!(f, "file_exists={path}"),
+            Self::CommandExists(command) => write!(f, "command_exists={command}"),
+            Self::Not(inner) if matches!(**inner, Self::All(_) | Self::Any(_)) => {
+                write!(f, "!({inner})")
+            }
+            Self::Not(inner) => write!(f, "!{inner}"),
+            Self::All(conditions) => {
+                let parts: Vec<String> = conditions
+                    .iter()
+                    .map(|c| match c {
+                        Self::Any(_) => format!("({c})"),
+                        _ => c.to_string(),
+                    })
+                    .collect();
+                write!(f, "{}", parts.join(" && "))
+            }
+            Self::Any(conditions) => {
+                let parts: Vec<String> = conditions.iter().map(ToString::to_string).collect();
+                write!(f, "{}", parts.join(" || "))
+            }
+        }
+    }
+}
+
+/// Settings of a step defined by a `compass` block, applied to its command
+/// only.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StepScope {
+    /// Directory the command runs in, relative to the current one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    /// Environment variables set for the command.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Command run once the step succeeded; the step fails if it fails.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verify: Option<String>,
+}
+
+impl StepScope {
+    /// Whether the scope changes nothing.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// What a run of a step must produce, from `compass:expect` annotations.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Expectation {
+    /// Exit code of the last command (success by default).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<u32>,
+    /// Texts the output must contain.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contains: Vec<String>,
+    /// Texts the output must not contain.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub not_contains: Vec<String>,
+}
+
+impl Expectation {
+    /// Checks a run that ended with `status` and `exit_code`, describing each
+    /// assertion that does not hold.
+    pub fn check(&self, status: StepStatus, exit_code: Option<u32>, output: &str) -> Vec<String> {
+        let mut failures = Vec::new();
+        match self.exit_code {
+            Some(expected) if exit_code != Some(expected) => {
+                let actual = exit_code.map_or_else(|| "none".to_string(), |c| c.to_string());
+                failures.push(format!("expected exit code {expected}, got {actual}"));
+            }
+            None if status != StepStatus::Success => {
+                failures.push(format!("expected success, got {status:?}"));
+            }
+            _ => {}
+        }
+        for text in &self.contains {
+            if !output.contains(text.as_str()) {
+                failures.push(format!("expected the output to contain \"{text}\""));
+            }
+        }
+        for text in &self.not_contains {
+            if output.contains(text.as_str()) {
+                failures.push(format!("expected the output not to contain \"{text}\""));
+            }
+        }
+        failures
+    }
+}
+
+/// Variables a step sets from its output, from a `compass:capture` annotation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Capture {
+    /// Variables set from the groups of the pattern, in order.
+    pub names: Vec<String>,
+    /// Regular expression searched in the output.
+    pub pattern: String,
+}
+
+impl Capture {
+    /// Values of the variables in the first match of the pattern in `output`:
+    /// one per group, or the whole match for a pattern without groups. Empty
+    /// when nothing matches or the pattern is invalid.
+    pub fn apply(&self, output: &str) -> Vec<(String, String)> {
+        let Some(caps) = Regex::new(&self.pattern)
+            .ok()
+            .and_then(|re| re.captures(output))
+        else {
+            return Vec::new();
+        };
+        let groups: Vec<_> = if caps.len() > 1 {
+            caps.iter().skip(1).collect()
+        } else {
+            vec![caps.get(0)]
+        };
+        self.names
+            .iter()
+            .zip(groups)
+            .filter_map(|(name, group)| Some((name.clone(), group?.as_str().to_string())))
+            .collect()
+    }
+}
+
+/// When the last run of a step started and ended.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutionRecord {
+    /// When the run started (RFC 3339).
+    pub started_at: String,
+    /// When the run ended (RFC 3339).
+    pub finished_at: String,
+    /// How long the run took.
+    pub duration_ms: u64,
+    /// Exit code of the last command run, if it exited normally.
+    #[serde(default)]
+    pub exit_code: Option<u32>,
+}
+
+impl ExecutionRecord {
+    /// Records a run between two instants, ended with `exit_code`.
+    #[must_use]
+    pub fn new(
+        started: DateTime<Local>,
+        finished: DateTime<Local>,
+        exit_code: Option<u32>,
+    ) -> Self {
+        Self {
+            started_at: started.to_rfc3339(),
+            finished_at: finished.to_rfc3339(),
+            duration_ms: u64::try_from((finished - started).num_milliseconds()).unwrap_or(0),
+            exit_code,
+        }
+    }
+}
+
+/// How a run of a step changed the working directory and environment
+/// variables carried over to the next steps. Values of variables that look
+/// secret are masked.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextDiff {
+    /// Working directory before the step, if the step changed it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_dir: Option<String>,
+    /// Working directory after the step, if the step changed it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_dir: Option<String>,
+    /// Variables set, changed or unset, by name.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variables: Vec<VarChange>,
+}
+
+impl ContextDiff {
+    /// Whether the step changed nothing.
+    pub fn is_empty(&self) -> bool {
+        self.current_dir.is_none() && self.variables.is_empty()
+    }
+}
+
+/// An environment variable changed by a step.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VarChange {
+    pub name: String,
+    /// Value before the step (`None` if it was not set).
+    pub before: Option<String>,
+    /// Value after the step (`None` if it was unset).
+    pub after: Option<String>,
+}
+
+impl fmt::Display for VarChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.before, &self.after) {
+            (None, Some(after)) => write!(f, "+ {}={after}", self.name),
+            (Some(before), Some(after)) => write!(f, "~ {}={after} (was {before})", self.name),
+            (_, None) => write!(f, "- {}", self.name),
+        }
+    }
+}
+
+/// A finished execution of a step, kept in the step's history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepRun {
+    /// The final status of the run.
+    pub status: StepStatus,
+    /// How long the run took, if known.
+    pub duration_ms: Option<u64>,
+    /// Exit code of the last command run, if it exited normally.
+    #[serde(default)]
+    pub exit_code: Option<u32>,
+    /// The output captured during the run.
+    pub output: String,
 }
 
 /// A block of code extracted from a Markdown file.
@@ -45,6 +346,14 @@ pub struct CodeBlock {
     pub content: String,
     /// Placeholders found in this block (e.g., "`VARIABLE_NAME`").
     pub placeholders: Vec<String>,
+    /// File written with the block content instead of running it, from a
+    /// `compass:write` annotation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub write_to: Option<String>,
+    /// Whether the block is indented rather than fenced. Such blocks are run
+    /// as shell but may be sample output, so they need a confirmation.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub indented: bool,
 }
 
 /// A parsing step representing a section of the README.
@@ -52,6 +361,11 @@ pub struct CodeBlock {
 pub struct Step {
     /// The title of the step (extracted from a header).
     pub title: String,
+    /// The depth of the header (1 for `#`, 2 for `##`, ...), 0 for the
+    /// introduction before the first header. A step is nested under the
+    /// previous step of a lower level (see `outline`).
+    #[serde(default)]
+    pub level: u8,
     /// The accumulated text description between headers.
     pub description: String,
     /// A list of code blocks found within this section.
@@ -60,8 +374,85 @@ pub struct Step {
     pub status: StepStatus,
     /// The captured output (stdout and stderr) from the last execution.
     pub output: String,
+    /// What the last execution wrote to stdout, when run with separate streams.
+    #[serde(default)]
+    pub stdout: String,
+    /// What the last execution wrote to stderr, when run with separate streams.
+    #[serde(default)]
+    pub stderr: String,
     /// An optional condition for this step (e.g., OS-specific).
     pub condition: Option<Condition>,
+    /// Whether the step is one branch of a `compass:if` with `compass:elif`
+    /// or `compass:else` branches: only the branch that applies is shown.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub alternative: bool,
+    /// Free-form tags from `compass:tag` annotations (e.g. `ci-safe`).
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Whether the step runs in the shared context or a fresh one.
+    #[serde(default)]
+    pub context_mode: ContextMode,
+    /// Expected duration in seconds, from a `compass:eta` annotation.
+    #[serde(default)]
+    pub eta_secs: Option<u64>,
+    /// Maximum run time in seconds, from a `compass:timeout` annotation.
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// Environment variables the step needs, from a `compass:requires_env` annotation.
+    #[serde(default)]
+    pub required_env: Vec<String>,
+    /// Placeholders holding secrets, written `<SECRET:NAME>` or listed under
+    /// `secrets` in the frontmatter. Their values are masked and never saved.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secrets: Vec<String>,
+    /// Values allowed for choice placeholders, written `<ENV:dev|prod>` or
+    /// given as `choices` of a frontmatter placeholder.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub choices: BTreeMap<String, Vec<String>>,
+    /// Titles of the steps that must succeed first, from a `compass:requires`
+    /// annotation.
+    #[serde(default)]
+    pub requires: Vec<String>,
+    /// Files the step modifies, from a `compass:edits` annotation.
+    #[serde(default)]
+    pub edited_files: Vec<String>,
+    /// Inline code spans of the description that read like commands (`npm ci`).
+    #[serde(default)]
+    pub inline_commands: Vec<String>,
+    /// Working directory, environment and verification of the command, from
+    /// a `compass` block.
+    #[serde(default, skip_serializing_if = "StepScope::is_empty")]
+    pub scope: StepScope,
+    /// Command undoing the step, from a `compass` block.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollback: Option<String>,
+    /// Start, end and duration of the last finished run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execution: Option<ExecutionRecord>,
+    /// Added or copied during the session rather than read from the runbook.
+    #[serde(default)]
+    pub session_only: bool,
+    /// Past runs of this step in the current session, oldest first.
+    #[serde(default)]
+    pub history: Vec<StepRun>,
+    /// What the last run changed in the working directory and environment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_diff: Option<ContextDiff>,
+    /// Assertions on the result of a run, from `compass:expect` annotations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expect: Option<Expectation>,
+    /// Variables read from the output of a successful run, from
+    /// `compass:capture` annotations.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub captures: Vec<Capture>,
+    /// Parallel group of the step, from the `compass:group parallel`
+    /// annotation around it: the steps of a group run at the same time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<usize>,
+    /// File or URL the step was included from with `compass:include`, `None`
+    /// for the steps of the runbook itself.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
 }
 
 impl Step {
@@ -69,4 +460,76 @@ impl Step {
     pub const fn is_executable(&self) -> bool {
         !self.code_blocks.is_empty()
     }
+
+    /// Maximum run time of the step's commands, after which they are killed.
+    pub fn timeout(&self) -> Option<std::time::Duration> {
+        self.timeout_secs.map(std::time::Duration::from_secs)
+    }
+
+    /// Values of the variables the step captures from `output`.
+    pub fn captured(&self, output: &str) -> Vec<(String, String)> {
+        self.captures
+            .iter()
+            .flat_map(|capture| capture.apply(output))
+            .collect()
+    }
+
+    /// Checks if the step captures a variable from its output.
+    pub fn captures_variable(&self, name: &str) -> bool {
+        self.captures
+            .iter()
+            .any(|capture| capture.names.iter().any(|n| n == name))
+    }
+
+    /// Checks if a placeholder of the step holds a secret, declared as such
+    /// or named like one (`API_TOKEN`).
+    pub fn is_secret(&self, name: &str) -> bool {
+        self.secrets.iter().any(|s| s == name) || is_secret_placeholder(name)
+    }
+
+    /// Checks that a value is allowed for a choice placeholder of the step.
+    pub fn check_choice(&self, name: &str, value: &str) -> Result<(), String> {
+        match self.choices.get(name) {
+            Some(choices) if !choices.iter().any(|c| c == value) => {
+                Err(format!("Expected one of {}", choices.join(", ")))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks if the step carries the given tag.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+
+    /// Records the current status and output as a finished run, dropping the
+    /// oldest run once `MAX_STEP_HISTORY` is reached.
+    pub fn record_run(&mut self, duration_ms: Option<u64>, exit_code: Option<u32>) {
+        if self.history.len() == MAX_STEP_HISTORY {
+            self.history.remove(0);
+        }
+        self.history.push(StepRun {
+            status: self.status,
+            duration_ms,
+            exit_code,
+            output: self.output.clone(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_execution_record_duration() {
+        let started = Local::now();
+        let finished = started + chrono::Duration::milliseconds(1500);
+        let record = ExecutionRecord::new(started, finished, Some(0));
+        assert_eq!(record.duration_ms, 1500);
+        assert_eq!(record.started_at, started.to_rfc3339());
+
+        // A clock set back never yields a negative duration
+        assert_eq!(ExecutionRecord::new(finished, started, None).duration_ms, 0);
+    }
 }
@@ -0,0 +1,130 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Heading hierarchy of the steps: the children of a step are the steps under
+//! its header, down to the next header of the same or a higher level (`##`
+//! sections nest under the `#` before them). Steps stay in a flat list in
+//! document order; the tree is read from their `level`.
+//!
+//! The introduction (level 0) is at the top level and has no children.
+
+use crate::core::executor::conditions::evaluator::{ConditionEvaluator, StandardEvaluator};
+use crate::core::models::Step;
+use std::collections::HashSet;
+
+/// Nesting depth of each step: 0 for the top level, 1 for its children, ...
+pub fn depths(steps: &[Step]) -> Vec<usize> {
+    // Levels of the headers the current step is under
+    let mut open: Vec<u8> = Vec::new();
+    steps
+        .iter()
+        .map(|step| {
+            if step.level == 0 {
+                open.clear();
+                return 0;
+            }
+            while open.last().is_some_and(|&level| level >= step.level) {
+                open.pop();
+            }
+            let depth = open.len();
+            open.push(step.level);
+            depth
+        })
+        .collect()
+}
+
+/// Index of the step `steps[index]` is nested under, if any.
+pub fn parent(steps: &[Step], index: usize) -> Option<usize> {
+    let level = steps[index].level;
+    if level == 0 {
+        return None;
+    }
+    steps[..index]
+        .iter()
+        .rposition(|s| s.level > 0 && s.level < level)
+}
+
+/// Index just after the last step nested under `steps[index]`: its
+/// descendants are `index + 1..subtree_end(steps, index)`.
+pub fn subtree_end(steps: &[Step], index: usize) -> usize {
+    let level = steps[index].level;
+    if level == 0 {
+        return index + 1;
+    }
+    steps[index + 1..]
+        .iter()
+        .position(|s| s.level <= level)
+        .map_or(steps.len(), |n| index + 1 + n)
+}
+
+/// Whether other steps are nested under `steps[index]`.
+pub fn has_children(steps: &[Step], index: usize) -> bool {
+    subtree_end(steps, index) > index + 1
+}
+
+/// Indexes of the steps shown when the steps in `collapsed` are folded: the
+/// steps nested under a collapsed one are left out, and so are the
+/// `compass:elif` / `compass:else` alternatives that do not apply here.
+pub fn visible(steps: &[Step], collapsed: &HashSet<usize>) -> Vec<usize> {
+    let evaluator = StandardEvaluator::new();
+    let mut shown = Vec::with_capacity(steps.len());
+    let mut i = 0;
+    while i < steps.len() {
+        let step = &steps[i];
+        if step.alternative
+            && step
+                .condition
+                .as_ref()
+                .is_some_and(|c| !evaluator.evaluate(c))
+        {
+            i += 1;
+            continue;
+        }
+        shown.push(i);
+        i = if collapsed.contains(&i) {
+            subtree_end(steps, i)
+        } else {
+            i + 1
+        };
+    }
+    shown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_readme;
+
+    const RUNBOOK: &str =
+        "Intro\n# Setup\n## Install\n### Linux\n### macOS\n## Configure\n# Deploy\n## Staging\n";
+
+    #[test]
+    fn test_outline() {
+        let (steps, _) = parse_readme(RUNBOOK);
+        assert_eq!(depths(&steps), vec![0, 0, 1, 2, 2, 1, 0, 1]);
+        assert_eq!(parent(&steps, 4), Some(2));
+        assert_eq!(parent(&steps, 5), Some(1));
+        assert_eq!(parent(&steps, 1), None);
+        assert_eq!(subtree_end(&steps, 1), 6);
+        assert_eq!(subtree_end(&steps, 2), 5);
+        assert_eq!(subtree_end(&steps, 0), 1);
+        assert!(has_children(&steps, 6));
+        assert!(!has_children(&steps, 7));
+
+        let collapsed = HashSet::from([2, 6]);
+        assert_eq!(visible(&steps, &collapsed), vec![0, 1, 2, 5, 6]);
+        let collapsed = HashSet::from([1, 2]);
+        assert_eq!(visible(&steps, &collapsed), vec![0, 1, 6, 7]);
+    }
+}
@@ -12,24 +12,134 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::core::ecosystem::hooks::HookConfig;
-use crate::core::models::{CodeBlock, Condition, Step};
-use pulldown_cmark::{Event, Parser, Tag};
+use crate::core::executor::conditions::expression;
+use crate::core::manifest::RunbookManifest;
+use crate::core::models::{
+    Capture, CodeBlock, Condition, ContextMode, Expectation, Step, StepScope,
+};
+use anyhow::{Result, bail};
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
 use regex::Regex;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+
+/// Title of the step holding the content found before the first header.
+pub const INTRODUCTION_TITLE: &str = "Introduction";
+
+/// Language of the code blocks defining a step explicitly, in YAML.
+pub const DEFINITION_LANGUAGE: &str = "compass";
+
+/// A step written as a `compass` block, overriding what the parser would
+/// guess from the section.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct StepDefinition {
+    name: Option<String>,
+    command: String,
+    language: Option<String>,
+    cwd: Option<String>,
+    #[serde(default, deserialize_with = "scalar_map")]
+    env: BTreeMap<String, String>,
+    verify: Option<String>,
+    rollback: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    requires: Vec<String>,
+}
+
+/// Reads a map of YAML scalars as strings, so `PORT: 8080` needs no quotes.
+fn scalar_map<'de, D>(deserializer: D) -> Result<BTreeMap<String, String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let map = BTreeMap::<String, serde_yaml::Value>::deserialize(deserializer)?;
+    map.into_iter()
+        .map(|(key, value)| {
+            let value = match value {
+                serde_yaml::Value::String(s) => s,
+                serde_yaml::Value::Number(n) => n.to_string(),
+                serde_yaml::Value::Bool(b) => b.to_string(),
+                _ => {
+                    return Err(serde::de::Error::custom(format!(
+                        "the value of {key} must be a string, a number or a boolean"
+                    )));
+                }
+            };
+            Ok((key, value))
+        })
+        .collect()
+}
 
 /// Parses a Markdown string into a sequence of steps and optional hook configuration.
-pub fn parse_readme(content: &str) -> (Vec<Step>, Option<HookConfig>) {
+pub fn parse_readme(content: &str) -> (Vec<Step>, Option<RunbookManifest>) {
+    parse_readme_at(content, None)
+}
+
+/// Parses a Markdown string, making steps of the headers of depth `step_level`
+/// (overriding the `step_level` of the frontmatter).
+///
+/// Deeper headers are folded into the step above them, and shallower ones
+/// (titles, groups) are only kept when they hold text or code.
+pub fn parse_readme_at(
+    content: &str,
+    step_level: Option<u8>,
+) -> (Vec<Step>, Option<RunbookManifest>) {
+    // Annotations, compiled once
+    static IF: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"<!--\s*compass:if\s+(.*?)\s*-->"#).unwrap());
+    static ELIF: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"<!--\s*compass:elif\s+(.*?)\s*-->"#).unwrap());
+    static ELSE: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"<!--\s*compass:else\s*-->"#).unwrap());
+    static ENDIF: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"<!--\s*compass:endif\s*-->"#).unwrap());
+    static GROUP: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"<!--\s*compass:group\s+parallel\s*-->"#).unwrap());
+    static ENDGROUP: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"<!--\s*compass:endgroup\s*-->"#).unwrap());
+    static ORIGIN: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"<!--\s*compass:origin\s+(\S+)\s*-->"#).unwrap());
+    static ENDORIGIN: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"<!--\s*compass:endorigin\s*-->"#).unwrap());
+    static CONTEXT: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r#"<!--\s*compass:context\s+(shared|isolated)\s*-->"#).unwrap()
+    });
+    static TAGS: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"<!--\s*compass:tags?\s+([^>]*?)\s*-->"#).unwrap());
+    static ETA: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"<!--\s*compass:eta\s+(\S+)\s*-->"#).unwrap());
+    static TIMEOUT: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"<!--\s*compass:timeout\s+(\S+)\s*-->"#).unwrap());
+    static EDITS: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"<!--\s*compass:edits\s+([^>]*?)\s*-->"#).unwrap());
+    static REQUIRES_ENV: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"<!--\s*compass:requires_env\s+([^>]*?)\s*-->"#).unwrap());
+    static STEP_ENV: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"<!--\s*compass:env\s+([^>]*?)\s*-->"#).unwrap());
+    static REQUIRES: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"<!--\s*compass:requires\s+([^>]*?)\s*-->"#).unwrap());
+    static WRITE: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"<!--\s*compass:write\s+path="([^"]+)"\s*-->"#).unwrap());
+    static EXPECT: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"<!--\s*compass:expect\s+([^>]*?)\s*-->"#).unwrap());
+    static CAPTURE: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r#"<!--\s*compass:capture\s+(\w+(?:[\s,]+\w+)*)\s+from=(?:"(.*)"|'(.*)')\s*-->"#)
+            .unwrap()
+    });
+
     let mut current_content = content;
-    let mut hook_config = None;
+    let mut manifest = None;
 
     // Frontmatter parsing
     if let Some(rest) = content.strip_prefix("---")
         && let Some(end_idx) = rest.find("\n---")
     {
         let frontmatter_str = &rest[..end_idx];
-        match serde_yaml::from_str::<HookConfig>(frontmatter_str) {
+        match serde_yaml::from_str::<RunbookManifest>(frontmatter_str) {
             Ok(config) => {
-                hook_config = Some(config);
+                manifest = Some(config);
                 // Skip the closing delimiter "\n---" (4 chars)
                 if rest.len() > end_idx + 4 {
                     current_content = &rest[end_idx + 4..];
@@ -47,73 +157,400 @@ pub fn parse_readme(content: &str) -> (Vec<Step>, Option<HookConfig>) {
         }
     }
 
-    let parser = Parser::new(current_content);
+    let parser = Parser::new_ext(current_content, Options::ENABLE_TABLES);
     let mut steps = Vec::new();
     let mut current_step: Option<Step> = None;
     let mut in_heading = false;
     let mut in_code_block = false;
+    let mut new_code_block = false;
     let mut current_code_lang = None;
+    let mut current_code_indented = false;
+    // File the next code block is written to (`compass:write`)
+    let mut pending_write: Option<String> = None;
     let mut active_condition: Option<Condition> = None;
-
-    let re_if = Regex::new(r#"<!--\s*compass:if\s+(\w+)="([^"]+)"\s*-->"#).unwrap();
-    let re_endif = Regex::new(r#"<!--\s*compass:endif\s*-->"#).unwrap();
+    // Conditions of the branches of the current `compass:if` so far, and
+    // those of the branches of chains with an `elif` or `else`
+    let mut branches: Vec<Condition> = Vec::new();
+    let mut alternatives: Vec<Condition> = Vec::new();
+    // Parallel group of the next steps, and number of groups so far
+    let mut active_group: Option<usize> = None;
+    let mut groups = 0;
+    // Runbooks the next steps were included from, innermost last
+    let mut origins: Vec<String> = Vec::new();
+    let default_context = manifest.as_ref().map(|c| c.context).unwrap_or_default();
+    let step_level = step_level.or_else(|| manifest.as_ref().and_then(|c| c.step_level));
+    // Whether the current header is folded into the step instead of starting one
+    let mut folded_heading = false;
+    // Raw HTML (or JSX) block being read
+    let mut html_block: Option<String> = None;
+    // YAML of the `compass` block being read
+    let mut definition: Option<String> = None;
+    // Whether the current step comes from a `compass` block: its other code
+    // blocks are only shown, as part of the description
+    let mut defined_step = false;
+    let mut shown_block = false;
+    // Title of the section, for the steps of its `compass` blocks without a name
+    let mut section_title = String::new();
+    // Descriptions keep their inline formatting, lists and tables as Markdown:
+    // destinations of the links being read, and lists being read with the
+    // number of their next item (ordered lists) and the width of the marker
+    // of their current item, which nested lists are indented by
+    let mut links: Vec<String> = Vec::new();
+    let mut lists: Vec<(Option<u64>, usize)> = Vec::new();
+    let mut table_columns = 0;
 
     for event in parser {
+        // Whether the event belongs to the description of the current step
+        let describing = (!in_heading || folded_heading) && !in_code_block && definition.is_none();
         match event {
+            Event::Start(Tag::Strong) | Event::End(TagEnd::Strong) if describing => {
+                description(&mut current_step, &active_condition, default_context).push_str("**");
+            }
+            Event::Start(Tag::Emphasis) | Event::End(TagEnd::Emphasis) if describing => {
+                description(&mut current_step, &active_condition, default_context).push('*');
+            }
+            Event::Start(Tag::Link { dest_url, .. }) => {
+                if describing {
+                    description(&mut current_step, &active_condition, default_context).push('[');
+                }
+                links.push(dest_url.to_string());
+            }
+            Event::End(TagEnd::Link) => {
+                let url = links.pop().unwrap_or_default();
+                if describing {
+                    description(&mut current_step, &active_condition, default_context)
+                        .push_str(&format!("]({url})"));
+                }
+            }
+            Event::Start(Tag::List(start)) => lists.push((start, 0)),
+            Event::End(TagEnd::List(_)) => {
+                lists.pop();
+                // A blank line ends the list, or the next text would continue its last item
+                if lists.is_empty() && describing {
+                    let text = description(&mut current_step, &active_condition, default_context);
+                    while !text.is_empty() && !text.ends_with("\n\n") {
+                        text.push('\n');
+                    }
+                }
+            }
+            Event::Start(Tag::Item) if describing => {
+                let indent: usize = lists.iter().rev().skip(1).map(|(_, width)| width).sum();
+                let marker = match lists.last_mut() {
+                    Some((Some(number), width)) => {
+                        let marker = format!("{number}. ");
+                        *number += 1;
+                        *width = marker.len();
+                        marker
+                    }
+                    Some((None, width)) => {
+                        *width = 2;
+                        "- ".to_string()
+                    }
+                    None => String::new(),
+                };
+                let text = description(&mut current_step, &active_condition, default_context);
+                if !text.is_empty() && !text.ends_with('\n') {
+                    text.push('\n');
+                }
+                text.push_str(&" ".repeat(indent));
+                text.push_str(&marker);
+            }
+            Event::End(TagEnd::Item) if describing => {
+                let text = description(&mut current_step, &active_condition, default_context);
+                if !text.ends_with('\n') {
+                    text.push('\n');
+                }
+            }
+            Event::Start(Tag::Table(alignments)) if describing => {
+                table_columns = alignments.len();
+                let text = description(&mut current_step, &active_condition, default_context);
+                if !text.is_empty() && !text.ends_with('\n') {
+                    text.push('\n');
+                }
+            }
+            Event::Start(Tag::TableHead | Tag::TableRow) if describing => {
+                description(&mut current_step, &active_condition, default_context).push('|');
+            }
+            Event::Start(Tag::TableCell) if describing => {
+                description(&mut current_step, &active_condition, default_context).push(' ');
+            }
+            Event::End(TagEnd::TableCell) if describing => {
+                description(&mut current_step, &active_condition, default_context).push_str(" |");
+            }
+            Event::End(TagEnd::TableHead) if describing => {
+                let separator = "---|".repeat(table_columns);
+                description(&mut current_step, &active_condition, default_context)
+                    .push_str(&format!("\n|{separator}\n"));
+            }
+            Event::End(TagEnd::TableRow) if describing => {
+                description(&mut current_step, &active_condition, default_context).push('\n');
+            }
+            Event::End(TagEnd::Table) if describing => {
+                description(&mut current_step, &active_condition, default_context).push('\n');
+            }
             Event::Html(cow_str) => {
+                if let Some(block) = html_block.as_mut() {
+                    block.push_str(&cow_str);
+                }
                 let text = cow_str.trim();
 
-                if let Some(caps) = re_if.captures(text) {
-                    let key = caps.get(1).map_or("", |m| m.as_str());
-                    let val = caps.get(2).map_or("", |m| m.as_str());
-
-                    active_condition = match key {
-                        "os" => Some(Condition::Os(val.to_string())),
-                        "env_var_exists" => Some(Condition::EnvVarExists(val.to_string())),
-                        "file_exists" => Some(Condition::FileExists(val.to_string())),
-                        _ => None, // Unknown condition type
+                if let Some(caps) = IF.captures(text) {
+                    // A condition that does not parse applies everywhere
+                    active_condition = expression::parse(&caps[1]).ok();
+                    branches = active_condition.iter().cloned().collect();
+                } else if let Some(caps) = ELIF.captures(text) {
+                    // Only when none of the branches before applies
+                    let own = expression::parse(&caps[1]).ok();
+                    let branch = match (Condition::otherwise(&branches), own.clone()) {
+                        (Some(Condition::All(mut all)), Some(own)) => {
+                            all.push(own);
+                            Some(Condition::All(all))
+                        }
+                        (Some(before), Some(own)) => Some(Condition::All(vec![before, own])),
+                        (before, own) => own.or(before),
                     };
-                } else if re_endif.is_match(text) {
+                    alternatives.extend(active_condition.take());
+                    alternatives.extend(branch.clone());
+                    active_condition = branch;
+                    branches.extend(own);
+                } else if ELSE.is_match(text) {
+                    alternatives.extend(active_condition.take());
+                    active_condition = Condition::otherwise(&branches);
+                    alternatives.extend(active_condition.clone());
+                    branches.clear();
+                } else if ENDIF.is_match(text) {
                     active_condition = None;
+                    branches.clear();
+                } else if GROUP.is_match(text) {
+                    active_group = Some(groups);
+                    groups += 1;
+                } else if ENDGROUP.is_match(text) {
+                    active_group = None;
+                } else if let Some(caps) = ORIGIN.captures(text) {
+                    origins.push(caps[1].to_string());
+                } else if ENDORIGIN.is_match(text) {
+                    origins.pop();
+                } else if let Some(caps) = CONTEXT.captures(text)
+                    && let Some(step) = current_step.as_mut()
+                {
+                    step.context_mode = if &caps[1] == "isolated" {
+                        ContextMode::Isolated
+                    } else {
+                        ContextMode::Shared
+                    };
+                } else if let Some(caps) = TAGS.captures(text)
+                    && let Some(step) = current_step.as_mut()
+                {
+                    step.tags.extend(
+                        caps[1]
+                            .split([',', ' '])
+                            .filter(|t| !t.is_empty())
+                            .map(ToString::to_string),
+                    );
+                } else if let Some(caps) = ETA.captures(text)
+                    && let Some(step) = current_step.as_mut()
+                {
+                    step.eta_secs = parse_duration(&caps[1]);
+                } else if let Some(caps) = TIMEOUT.captures(text)
+                    && let Some(step) = current_step.as_mut()
+                {
+                    step.timeout_secs = parse_duration(&caps[1]).filter(|&secs| secs > 0);
+                } else if let Some(caps) = REQUIRES_ENV.captures(text)
+                    && let Some(step) = current_step.as_mut()
+                {
+                    step.required_env.extend(
+                        caps[1]
+                            .split([',', ' '])
+                            .filter(|v| !v.is_empty())
+                            .map(ToString::to_string),
+                    );
+                } else if let Some(caps) = REQUIRES.captures(text)
+                    && let Some(step) = current_step.as_mut()
+                {
+                    step.requires.extend(required_titles(&caps[1]));
+                } else if let Some(caps) = STEP_ENV.captures(text)
+                    && let Some(step) = current_step.as_mut()
+                {
+                    step.scope.env.extend(env_assignments(&caps[1]));
+                } else if let Some(caps) = EXPECT.captures(text)
+                    && let Some(step) = current_step.as_mut()
+                {
+                    let expect = step.expect.get_or_insert_with(Expectation::default);
+                    for (key, value) in env_assignments(&caps[1]) {
+                        match key.as_str() {
+                            "exit_code" => expect.exit_code = value.parse().ok(),
+                            "contains" => expect.contains.push(value),
+                            "not_contains" => expect.not_contains.push(value),
+                            _ => {}
+                        }
+                    }
+                } else if let Some(caps) = CAPTURE.captures(text)
+                    && let Some(step) = current_step.as_mut()
+                {
+                    let pattern = caps
+                        .get(2)
+                        .or_else(|| caps.get(3))
+                        .map_or("", |m| m.as_str());
+                    step.captures.push(Capture {
+                        names: caps[1]
+                            .split([',', ' '])
+                            .filter(|n| !n.is_empty())
+                            .map(ToString::to_string)
+                            .collect(),
+                        pattern: pattern.to_string(),
+                    });
+                } else if let Some(caps) = WRITE.captures(text) {
+                    pending_write = Some(caps[1].to_string());
+                } else if let Some(caps) = EDITS.captures(text)
+                    && let Some(step) = current_step.as_mut()
+                {
+                    step.edited_files.extend(
+                        caps[1]
+                            .split([',', ' '])
+                            .filter(|p| !p.is_empty())
+                            .map(ToString::to_string),
+                    );
                 }
             }
-            Event::Start(Tag::Heading { .. }) => {
+            Event::Start(Tag::HtmlBlock) => html_block = Some(String::new()),
+            Event::End(pulldown_cmark::TagEnd::HtmlBlock) => {
+                // Keep the text and code of the block, without its tags
+                for content in flatten_html(&html_block.take().unwrap_or_default()) {
+                    let step = current_step
+                        .get_or_insert_with(|| introduction(&active_condition, default_context));
+                    match content {
+                        HtmlContent::Text(text) => {
+                            if !step.description.is_empty() && !step.description.ends_with('\n') {
+                                step.description.push('\n');
+                            }
+                            step.description.push_str(&text);
+                            step.description.push('\n');
+                        }
+                        HtmlContent::Code { language, content } => {
+                            step.code_blocks.push(CodeBlock {
+                                language,
+                                placeholders: extract_placeholders(&content),
+                                content,
+                                write_to: pending_write.take(),
+                                indented: false,
+                            });
+                        }
+                    }
+                }
+            }
+            Event::Start(Tag::Heading { level, .. }) => {
+                let level = level as u8;
+                in_heading = true;
+                if let Some(step) = current_step.as_mut().filter(|s| s.level > 0)
+                    && step_level.is_some_and(|max| level > max)
+                {
+                    folded_heading = true;
+                    if !step.description.is_empty() && !step.description.ends_with('\n') {
+                        step.description.push('\n');
+                    }
+                    continue;
+                }
                 // If we were already in a step, push it to the list
-                if let Some(step) = current_step.take() {
+                if let Some(step) = current_step.take()
+                    && !is_empty_group(&step, step_level)
+                {
                     steps.push(step);
                 }
+                defined_step = false;
                 current_step = Some(Step {
+                    level,
                     condition: active_condition.clone(),
+                    context_mode: default_context,
+                    group: active_group,
+                    origin: origins.last().cloned(),
                     ..Default::default()
                 });
-                in_heading = true;
             }
             Event::End(pulldown_cmark::TagEnd::Heading(_)) => {
+                if folded_heading && let Some(step) = current_step.as_mut() {
+                    step.description.push('\n');
+                }
                 in_heading = false;
+                folded_heading = false;
             }
             Event::Start(Tag::CodeBlock(kind)) => {
-                // Currently only support fenced code blocks
                 in_code_block = true;
-                if let pulldown_cmark::CodeBlockKind::Fenced(lang) = kind {
-                    // Detect if language is defined
-                    if !lang.is_empty() {
-                        current_code_lang = Some(lang.to_string());
+                new_code_block = true;
+                match kind {
+                    pulldown_cmark::CodeBlockKind::Fenced(lang) => {
+                        // Detect if language is defined
+                        if !lang.is_empty() {
+                            current_code_lang = Some(lang.to_string());
+                        }
                     }
+                    // Older READMEs indent commands by 4 spaces: run them as shell
+                    pulldown_cmark::CodeBlockKind::Indented => current_code_indented = true,
+                }
+                if current_code_lang.as_deref() == Some(DEFINITION_LANGUAGE) {
+                    definition = Some(String::new());
+                } else if defined_step && let Some(step) = current_step.as_mut() {
+                    shown_block = true;
+                    step.description.push_str(&format!(
+                        "```{}\n",
+                        current_code_lang.as_deref().unwrap_or_default()
+                    ));
                 }
             }
             Event::End(pulldown_cmark::TagEnd::CodeBlock) => {
+                if let Some(source) = definition.take() {
+                    // Each `compass` block of a section after the first starts a step
+                    let step = match current_step.take() {
+                        Some(previous) if defined_step => {
+                            let step = Step {
+                                title: section_title.clone(),
+                                level: previous.level,
+                                condition: active_condition.clone(),
+                                context_mode: default_context,
+                                group: previous.group,
+                                origin: previous.origin.clone(),
+                                ..Default::default()
+                            };
+                            steps.push(previous);
+                            step
+                        }
+                        step => {
+                            let step = step.unwrap_or_else(|| {
+                                introduction(&active_condition, default_context)
+                            });
+                            section_title.clone_from(&step.title);
+                            step
+                        }
+                    };
+                    current_step = Some(define_step(step, &source));
+                    defined_step = true;
+                } else if shown_block && let Some(step) = current_step.as_mut() {
+                    step.description.push_str("```\n");
+                    shown_block = false;
+                }
                 in_code_block = false;
                 current_code_lang = None;
+                current_code_indented = false;
+            }
+            Event::Text(text) if definition.is_some() || shown_block => {
+                if let Some(source) = definition.as_mut() {
+                    source.push_str(&text);
+                } else if let Some(step) = current_step.as_mut() {
+                    step.description.push_str(&text);
+                }
             }
             Event::Text(text) => {
+                // Content before the first header goes to an implicit step
+                if current_step.is_none() && (in_code_block || !text.trim().is_empty()) {
+                    current_step = Some(introduction(&active_condition, default_context));
+                }
                 // Currently, get content from text
                 if let Some(ref mut step) = current_step {
-                    if in_heading {
+                    if in_heading && !folded_heading {
                         step.title.push_str(&text);
                     } else if in_code_block {
-                        // If we are in a code block, add the text to the last code block
-                        // Else, create a new code block
-                        if let Some(last_block) = step.code_blocks.last_mut() {
+                        // The first text of a code block starts a new block,
+                        // the following ones are added to it
+                        if !new_code_block && let Some(last_block) = step.code_blocks.last_mut() {
                             last_block.content.push_str(&text);
                             // Re-extract placeholders if content grows
                             last_block.placeholders = extract_placeholders(&last_block.content);
@@ -123,13 +560,31 @@ pub fn parse_readme(content: &str) -> (Vec<Step>, Option<HookConfig>) {
                                 language: current_code_lang.clone(),
                                 content: text.to_string(),
                                 placeholders,
+                                write_to: pending_write.take(),
+                                indented: current_code_indented,
                             });
+                            new_code_block = false;
                         }
                     } else {
                         step.description.push_str(&text);
                     }
                 }
             }
+            Event::Code(code) => {
+                if current_step.is_none() {
+                    current_step = Some(introduction(&active_condition, default_context));
+                }
+                if let Some(ref mut step) = current_step {
+                    if in_heading && !folded_heading {
+                        step.title.push_str(&code);
+                    } else {
+                        step.description.push_str(&format!("`{code}`"));
+                        if let Some(command) = inline_command(&code) {
+                            step.inline_commands.push(command);
+                        }
+                    }
+                }
+            }
             Event::SoftBreak | Event::HardBreak | Event::End(pulldown_cmark::TagEnd::Paragraph) => {
                 // Add a new line to the description
                 if let Some(step) = current_step
@@ -144,21 +599,376 @@ pub fn parse_readme(content: &str) -> (Vec<Step>, Option<HookConfig>) {
     }
 
     // Push the last step if it exists
-    if let Some(step) = current_step {
+    if let Some(step) = current_step
+        && !is_empty_group(&step, step_level)
+    {
         steps.push(step);
     }
 
-    (steps, hook_config)
+    for step in &mut steps {
+        step.alternative = step
+            .condition
+            .as_ref()
+            .is_some_and(|c| alternatives.contains(c));
+    }
+
+    // The environment of the runbook, under the one of each step
+    if let Some(config) = &manifest {
+        for step in steps.iter_mut().filter(|s| s.is_executable()) {
+            for (key, value) in &config.env {
+                step.scope
+                    .env
+                    .entry(key.clone())
+                    .or_insert_with(|| value.clone());
+            }
+        }
+    }
+
+    // Secret placeholders, marked inline, declared in the frontmatter or read
+    // from a secret manager
+    let declared: Vec<String> = manifest
+        .as_ref()
+        .map(|c| {
+            let sourced = c
+                .placeholders
+                .iter()
+                .filter(|(_, spec)| spec.source.is_some())
+                .map(|(name, _)| name);
+            c.secrets.iter().chain(sourced).cloned().collect()
+        })
+        .unwrap_or_default();
+    for step in steps.iter_mut() {
+        let mut secrets = Vec::new();
+        for block in &step.code_blocks {
+            for name in extract_secret_placeholders(&block.content)
+                .into_iter()
+                .chain(declared.iter().cloned())
+            {
+                if block.placeholders.contains(&name) && !secrets.contains(&name) {
+                    secrets.push(name);
+                }
+            }
+        }
+        step.secrets = secrets;
+    }
+
+    // Choice placeholders, listed inline or in the frontmatter
+    for step in steps.iter_mut() {
+        let mut choices = BTreeMap::new();
+        for block in &step.code_blocks {
+            choices.extend(extract_choices(&block.content));
+            if let Some(config) = &manifest {
+                for name in &block.placeholders {
+                    if let Some(spec) = config.placeholders.get(name)
+                        && !spec.choices.is_empty()
+                    {
+                        choices
+                            .entry(name.clone())
+                            .or_insert_with(|| spec.choices.clone());
+                    }
+                }
+            }
+        }
+        step.choices = choices;
+    }
+
+    (steps, manifest)
+}
+
+/// Whether a step is a header above the step level (a title or a group) with
+/// nothing to show or run.
+fn is_empty_group(step: &Step, step_level: Option<u8>) -> bool {
+    step_level.is_some_and(|level| step.level > 0 && step.level < level)
+        && step.description.trim().is_empty()
+        && step.code_blocks.is_empty()
+}
+
+/// Creates the step holding the content found before the first header.
+fn introduction(condition: &Option<Condition>, context_mode: ContextMode) -> Step {
+    Step {
+        title: INTRODUCTION_TITLE.to_string(),
+        condition: condition.clone(),
+        context_mode,
+        ..Default::default()
+    }
+}
+
+/// Makes a step of a `compass` block. The code blocks found before it in the
+/// section are moved to the description; an invalid block is reported there.
+fn define_step(mut step: Step, source: &str) -> Step {
+    let definition = match serde_yaml::from_str::<StepDefinition>(source) {
+        Ok(definition) => definition,
+        Err(e) => {
+            tracing::warn!(step = %step.title, "Invalid compass block: {e}");
+            step.description
+                .push_str(&format!("⚠️ Invalid compass block: {e}\n"));
+            return step;
+        }
+    };
+
+    if let Some(name) = definition.name {
+        step.title = name;
+    }
+    for block in std::mem::take(&mut step.code_blocks) {
+        step.description.push_str(&format!(
+            "```{}\n{}```\n",
+            block.language.unwrap_or_default(),
+            block.content
+        ));
+    }
+    let mut command = definition.command;
+    if !command.ends_with('\n') {
+        command.push('\n');
+    }
+    step.code_blocks.push(CodeBlock {
+        language: definition.language,
+        placeholders: extract_placeholders(&command),
+        content: command,
+        write_to: None,
+        indented: false,
+    });
+    step.tags.extend(definition.tags);
+    step.requires.extend(definition.requires);
+    let mut env = std::mem::take(&mut step.scope.env);
+    env.extend(definition.env);
+    step.scope = StepScope {
+        cwd: definition.cwd,
+        env,
+        verify: definition.verify,
+    };
+    step.rollback = definition.rollback;
+    step
+}
+
+/// Step titles of a `compass:requires` annotation: quoted titles, or the
+/// whole text when there are no quotes.
+fn required_titles(text: &str) -> Vec<String> {
+    static QUOTED: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#""([^"]+)""#).unwrap());
+    if text.contains('"') {
+        QUOTED
+            .captures_iter(text)
+            .map(|caps| caps[1].trim().to_string())
+            .collect()
+    } else {
+        vec![text.trim().to_string()]
+    }
+}
+
+/// Variables of a `compass:env` annotation (or the assertions of a
+/// `compass:expect` one): `KEY=value` pairs separated by spaces, with values
+/// quoted when they hold spaces.
+fn env_assignments(text: &str) -> Vec<(String, String)> {
+    static ASSIGNMENT: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"(\w+)=(?:"([^"]*)"|'([^']*)'|(\S*))"#).unwrap());
+    ASSIGNMENT
+        .captures_iter(text)
+        .map(|caps| {
+            let value = caps.get(2).or_else(|| caps.get(3)).or_else(|| caps.get(4));
+            (
+                caps[1].to_string(),
+                value.map_or_else(String::new, |v| v.as_str().to_string()),
+            )
+        })
+        .collect()
+}
+
+/// Text or code found in a raw HTML (or MDX/JSX) block.
+#[derive(Debug, PartialEq)]
+enum HtmlContent {
+    Text(String),
+    Code {
+        language: Option<String>,
+        content: String,
+    },
+}
+
+/// Flattens an HTML block: `<pre>` elements become code blocks (with the
+/// language of a `language-*` class), the text of the other elements is kept
+/// line by line, and tags and comments are dropped.
+fn flatten_html(html: &str) -> Vec<HtmlContent> {
+    static PRE: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"(?is)<pre\b([^>]*)>(.*?)</pre\s*>").unwrap());
+    static LANGUAGE: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"class="[^"]*\b(?:language|lang)-([\w+#-]+)"#).unwrap());
+
+    let mut contents = Vec::new();
+    let mut rest = 0;
+    for caps in PRE.captures_iter(html) {
+        let whole = caps.get(0).unwrap();
+        contents.extend(html_text(&html[rest..whole.start()]).map(HtmlContent::Text));
+        rest = whole.end();
+
+        let code = html_to_text(&caps[2]);
+        let code = code.trim_matches('\n');
+        if !code.trim().is_empty() {
+            contents.push(HtmlContent::Code {
+                // On the `<pre>` or on its `<code>`
+                language: LANGUAGE.captures(whole.as_str()).map(|l| l[1].to_string()),
+                content: format!("{code}\n"),
+            });
+        }
+    }
+    contents.extend(html_text(&html[rest..]).map(HtmlContent::Text));
+    contents
+}
+
+/// The visible text of an HTML fragment, one trimmed line per line of text.
+fn html_text(html: &str) -> Option<String> {
+    static BREAK: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"(?i)<br\s*/?>|</(?:p|div|li|tr|h[1-6]|summary|blockquote)\s*>").unwrap()
+    });
+    let text = html_to_text(&BREAK.replace_all(html, "\n"));
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect();
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// Removes the comments and tags (HTML or JSX components) of a fragment and
+/// decodes the common entities.
+fn html_to_text(html: &str) -> String {
+    static COMMENT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<!--.*?-->").unwrap());
+    static TAG: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"</?[A-Za-z][\w.:-]*(?:\s[^<>]*)?/?>").unwrap());
+
+    let text = COMMENT.replace_all(html, "");
+    TAG.replace_all(&text, "")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+/// The command of an inline code span: the one after a `$ ` prompt
+/// (`$ make`), or the whole span if it reads like a command.
+fn inline_command(code: &str) -> Option<String> {
+    let code = code.trim();
+    match code.strip_prefix("$ ") {
+        Some(command) => Some(command.trim().to_string()).filter(|c| !c.is_empty()),
+        None => looks_like_command(code).then(|| code.to_string()),
+    }
+}
+
+/// The description of the current step, starting the implicit first step
+/// when there is none yet.
+fn description<'a>(
+    step: &'a mut Option<Step>,
+    condition: &Option<Condition>,
+    context: ContextMode,
+) -> &'a mut String {
+    &mut step
+        .get_or_insert_with(|| introduction(condition, context))
+        .description
+}
+
+/// Whether an inline code span reads like a command (`npm ci`, `./install.sh`)
+/// rather than a file name, a value or a single word.
+fn looks_like_command(code: &str) -> bool {
+    static COMMAND: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"^(?:(?:sudo )?[a-z][a-z0-9._-]*(?: +\S+)+|\./\S+(?: +\S+)*)$").unwrap()
+    });
+    COMMAND.is_match(code.trim())
+}
+
+/// Keeps only the sections with the given titles and their subsections, in
+/// document order. Case and leading or trailing emoji are ignored.
+///
+/// # Errors
+///
+/// Returns an error naming the available sections if a title matches nothing.
+pub fn select_sections(steps: Vec<Step>, titles: &[String]) -> Result<Vec<Step>> {
+    let mut keep = vec![false; steps.len()];
+    for title in titles {
+        let wanted = section_key(title);
+        let mut found = false;
+        for (i, step) in steps.iter().enumerate() {
+            if section_key(&step.title) != wanted {
+                continue;
+            }
+            found = true;
+            keep[i] = true;
+            if step.level == 0 {
+                continue;
+            }
+            for (j, child) in steps.iter().enumerate().skip(i + 1) {
+                if child.level <= step.level {
+                    break;
+                }
+                keep[j] = true;
+            }
+        }
+        if !found {
+            // The title and the main sections (`#` and `##` in most READMEs)
+            let top = steps.iter().map(|s| s.level).min().unwrap_or(1);
+            let available: Vec<&str> = steps
+                .iter()
+                .filter(|s| s.level <= top + 1)
+                .map(|s| s.title.trim())
+                .collect();
+            bail!(
+                "Section '{}' not found. Available sections: {}",
+                title.trim(),
+                available.join(", ")
+            );
+        }
+    }
+    Ok(steps
+        .into_iter()
+        .zip(keep)
+        .filter_map(|(step, kept)| kept.then_some(step))
+        .collect())
+}
+
+/// Normalizes a header for matching: `🚀 Getting Started` -> `getting started`.
+fn section_key(title: &str) -> String {
+    title
+        .trim_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase()
+}
+
+/// Parses a duration like `90`, `45s`, `5m` or `1h30m` into seconds.
+pub fn parse_duration(text: &str) -> Option<u64> {
+    if let Ok(secs) = text.parse() {
+        return Some(secs);
+    }
+    static DURATION: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^(?:(\d+)h)?(?:(\d+)m)?(?:(\d+)s)?$").unwrap());
+    let caps = DURATION.captures(text).filter(|_| !text.is_empty())?;
+    let part = |i: usize| {
+        caps.get(i)
+            .map_or(Some(0), |m| m.as_str().parse::<u64>().ok())
+    };
+    Some(part(1)? * 3600 + part(2)? * 60 + part(3)?)
+}
+
+/// Matches placeholders like <VAR> or {{VAR}}, capturing the name in group 1
+/// or 3 (see [`placeholder_name`]). A `SECRET:` prefix (`<SECRET:VAR>`) marks
+/// a placeholder holding a secret, and values after the name (`<ENV:dev|prod>`,
+/// in group 2 or 4) the only ones it accepts.
+///
+/// We restrict placeholders to alphanumeric chars to avoid matching
+/// HTML tags, PHP tags (<?php ... ?>), or generics (<T>).
+pub const PLACEHOLDER_PATTERN: &str = r"\{{2}(?:SECRET:)?([a-zA-Z0-9_-]+)(?::([^{}|\s]+(?:\|[^{}|\s]+)+))?\}{2}|<(?:SECRET:)?([a-zA-Z0-9_-]+)(?::([^<>|\s]+(?:\|[^<>|\s]+)+))?>";
+
+/// [`PLACEHOLDER_PATTERN`], compiled once: it runs on every code block.
+pub static PLACEHOLDERS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(PLACEHOLDER_PATTERN).unwrap());
+
+/// The name of a placeholder matched by [`PLACEHOLDER_PATTERN`].
+pub fn placeholder_name<'h>(caps: &regex::Captures<'h>) -> Option<regex::Match<'h>> {
+    caps.get(1).or_else(|| caps.get(3))
 }
 
 /// Extracts placeholders like <VAR> or {{VAR}} from a string.
-fn extract_placeholders(text: &str) -> Vec<String> {
-    // We restrict placeholders to alphanumeric chars to avoid matching
-    // HTML tags, PHP tags (<?php ... ?>), or generics (<T>).
-    let re = regex::Regex::new(r"\{{2}([a-zA-Z0-9_-]+)\}{2}|<([a-zA-Z0-9_-]+)>").unwrap();
+pub fn extract_placeholders(text: &str) -> Vec<String> {
+    let re = &*PLACEHOLDERS;
     let mut placeholders = Vec::new();
     for cap in re.captures_iter(text) {
-        if let Some(m) = cap.get(1).or_else(|| cap.get(2)) {
+        if let Some(m) = placeholder_name(&cap) {
             let name = m.as_str().trim().to_string();
             if !placeholders.contains(&name) {
                 placeholders.push(name);
@@ -168,9 +978,34 @@ fn extract_placeholders(text: &str) -> Vec<String> {
     placeholders
 }
 
+/// Extracts the placeholders marked as secrets, like <SECRET:VAR>.
+fn extract_secret_placeholders(text: &str) -> Vec<String> {
+    let re = &*PLACEHOLDERS;
+    re.captures_iter(text)
+        .filter(|caps| caps[0].contains("SECRET:"))
+        .filter_map(|caps| placeholder_name(&caps))
+        .map(|m| m.as_str().to_string())
+        .collect()
+}
+
+/// Extracts the values allowed by choice placeholders, like <ENV:dev|prod>.
+fn extract_choices(text: &str) -> Vec<(String, Vec<String>)> {
+    let re = &*PLACEHOLDERS;
+    re.captures_iter(text)
+        .filter_map(|caps| {
+            let values = caps.get(2).or_else(|| caps.get(4))?;
+            Some((
+                placeholder_name(&caps)?.as_str().to_string(),
+                values.as_str().split('|').map(str::to_string).collect(),
+            ))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::models::StepStatus;
 
     #[test]
     fn test_parse_simple() {
@@ -189,6 +1024,21 @@ mod tests {
         assert_eq!(steps[1].description.trim(), "Description 2");
     }
 
+    #[test]
+    fn test_parse_introduction() {
+        let content = "Install this first:\n```bash\nmake deps\n```\n# Build\n```bash\nmake\n```";
+        let (steps, _) = parse_readme(content);
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].title, INTRODUCTION_TITLE);
+        assert_eq!(steps[0].level, 0);
+        assert_eq!(steps[0].description.trim(), "Install this first:");
+        assert_eq!(steps[0].code_blocks[0].content.trim(), "make deps");
+        assert_eq!(steps[1].title, "Build");
+
+        let (steps, _) = parse_readme("\n\n# Build\n");
+        assert_eq!(steps.len(), 1);
+    }
+
     #[test]
     fn test_extract_placeholders() {
         let text = "echo <USER_NAME> and {{API_KEY}}";
@@ -198,10 +1048,450 @@ mod tests {
         assert_eq!(placeholders[1], "API_KEY");
     }
 
+    #[test]
+    fn test_parse_secret_placeholders() {
+        let content = "---\nsecrets: [DB_PASSWORD]\n---\n# Login\n```bash\ncurl -H \"Authorization: <SECRET:API_KEY>\" <HOST>\npsql -W {{DB_PASSWORD}}\n```\n";
+        let (steps, _) = parse_readme(content);
+        let block = &steps[0].code_blocks[0];
+        assert_eq!(block.placeholders, vec!["API_KEY", "HOST", "DB_PASSWORD"]);
+        assert_eq!(steps[0].secrets, vec!["API_KEY", "DB_PASSWORD"]);
+        assert!(!steps[0].is_secret("HOST"));
+    }
+
+    #[test]
+    fn test_parse_alternatives() {
+        let content = "<!-- compass:if os=\"windows\" -->\n# Winget\n<!-- compass:elif os=\"macos\" -->\n# Brew\n<!-- compass:else -->\n# Apt\n<!-- compass:endif -->\n<!-- compass:if env_var_exists=\"CI\" -->\n# Report\n<!-- compass:endif -->\n# Done\n";
+        let (steps, _) = parse_readme(content);
+        let windows = Condition::Os("windows".to_string());
+        let macos = Condition::Os("macos".to_string());
+        let not = |c: &Condition| Condition::Not(Box::new(c.clone()));
+        assert_eq!(steps[0].condition, Some(windows.clone()));
+        assert_eq!(
+            steps[1].condition,
+            Some(Condition::All(vec![not(&windows), macos.clone()]))
+        );
+        assert_eq!(
+            steps[2].condition,
+            Some(Condition::All(vec![not(&windows), not(&macos)]))
+        );
+        assert_eq!(
+            steps[2].condition.as_ref().unwrap().to_string(),
+            "!os=windows && !os=macos"
+        );
+        assert!(steps[..3].iter().all(|s| s.alternative));
+        assert!(steps[3].condition.is_some() && !steps[3].alternative);
+        assert!(steps[4].condition.is_none());
+    }
+
+    #[test]
+    fn test_parse_choice_placeholders() {
+        let content = "---\nplaceholders:\n  REGION:\n    choices: [eu, us]\n---\n# Deploy\n```bash\ndeploy --env <ENV:dev|staging|prod> --region {{REGION}}\necho <ENV>\n```\n";
+        let (steps, _) = parse_readme(content);
+        assert_eq!(steps[0].code_blocks[0].placeholders, vec!["ENV", "REGION"]);
+        assert_eq!(steps[0].choices["ENV"], vec!["dev", "staging", "prod"]);
+        assert_eq!(steps[0].choices["REGION"], vec!["eu", "us"]);
+        assert!(steps[0].check_choice("ENV", "prod").is_ok());
+        assert!(steps[0].check_choice("ENV", "qa").is_err());
+    }
+
     #[test]
     fn test_parse_with_placeholders() {
         let content = "# Test\n```bash\necho <HELLO>\n```";
         let (steps, _) = parse_readme(content);
         assert_eq!(steps[0].code_blocks[0].placeholders[0], "HELLO");
     }
+
+    #[test]
+    fn test_parse_context_mode() {
+        let content =
+            "---\ncontext: isolated\n---\n# One\n# Two\n<!-- compass:context shared -->\n";
+        let (steps, _) = parse_readme(content);
+        assert_eq!(steps[0].context_mode, ContextMode::Isolated);
+        assert_eq!(steps[1].context_mode, ContextMode::Shared);
+    }
+
+    #[test]
+    fn test_parse_env() {
+        let content = "---\nenv:\n  RUST_LOG: info\n  REGION: eu\n---\n# One\n<!-- compass:env RUST_LOG=debug GREETING=\"hello world\" -->\n```bash\ncargo test\n```\n# Two\n```bash\ncargo build\n```\n# Three\n";
+        let (steps, _) = parse_readme(content);
+        assert_eq!(steps[0].scope.env["RUST_LOG"], "debug");
+        assert_eq!(steps[0].scope.env["GREETING"], "hello world");
+        assert_eq!(steps[0].scope.env["REGION"], "eu");
+        assert_eq!(steps[1].scope.env["RUST_LOG"], "info");
+        assert!(steps[2].scope.env.is_empty());
+    }
+
+    #[test]
+    fn test_parse_tags() {
+        let content = "# One\n<!-- compass:tag ci-safe, slow -->\n# Two\n";
+        let (steps, _) = parse_readme(content);
+        assert_eq!(steps[0].tags, vec!["ci-safe", "slow"]);
+        assert!(steps[1].tags.is_empty());
+    }
+
+    #[test]
+    fn test_parse_required_env() {
+        let content = "# One\n<!-- compass:requires_env AWS_PROFILE,DATABASE_URL -->\n# Two\n";
+        let (steps, _) = parse_readme(content);
+        assert_eq!(steps[0].required_env, vec!["AWS_PROFILE", "DATABASE_URL"]);
+        assert!(steps[1].required_env.is_empty());
+    }
+
+    #[test]
+    fn test_parse_captures() {
+        let content = "# Serve\n```bash\n./serve\n```\n<!-- compass:capture PORT from=\"listening on port (\\d+)\" -->\n<!-- compass:capture HOST, ADDR from='at (\\S+):(\\d+)' -->\n# Call\n```bash\ncurl localhost:<PORT>\n```\n";
+        let (steps, _) = parse_readme(content);
+        assert_eq!(steps[0].captures.len(), 2);
+        assert_eq!(steps[0].captures[1].names, vec!["HOST", "ADDR"]);
+        assert!(steps[0].captures_variable("PORT"));
+        assert!(steps[1].captures.is_empty());
+
+        let output = "Server listening on port 8080\nReachable at 10.0.0.2:8080\n";
+        assert_eq!(
+            steps[0].captured(output),
+            vec![
+                ("PORT".to_string(), "8080".to_string()),
+                ("HOST".to_string(), "10.0.0.2".to_string()),
+                ("ADDR".to_string(), "8080".to_string()),
+            ]
+        );
+        assert!(steps[0].captured("starting...").is_empty());
+    }
+
+    #[test]
+    fn test_parse_expectations() {
+        let content = "# Serve\n```bash\n./serve\n```\n<!-- compass:expect exit_code=0 contains=\"Server started\" -->\n<!-- compass:expect not_contains=ERROR -->\n# Two\n";
+        let (steps, _) = parse_readme(content);
+        let expect = steps[0].expect.as_ref().unwrap();
+        assert_eq!(expect.exit_code, Some(0));
+        assert_eq!(expect.contains, vec!["Server started"]);
+        assert_eq!(expect.not_contains, vec!["ERROR"]);
+        assert!(steps[1].expect.is_none());
+
+        assert!(
+            expect
+                .check(StepStatus::Success, Some(0), "Server started on :80")
+                .is_empty()
+        );
+        assert_eq!(expect.check(StepStatus::Failed, Some(1), "ERROR").len(), 3);
+    }
+
+    #[test]
+    fn test_parse_write_blocks() {
+        let content = "# One\n```bash\necho a\n```\n<!-- compass:write path=\".env\" -->\n```ini\nKEY=<VALUE>\n```\n";
+        let (steps, _) = parse_readme(content);
+        let blocks = &steps[0].code_blocks;
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].write_to, None);
+        assert_eq!(blocks[1].write_to.as_deref(), Some(".env"));
+        assert_eq!(blocks[1].placeholders, vec!["VALUE"]);
+    }
+
+    #[test]
+    fn test_select_sections() {
+        let content = "# App\n## Build\n### Linux\n## 🚀 Deploy\n### Staging\n#### Checks\n### Production\n## FAQ\n";
+        let (steps, _) = parse_readme(content);
+        assert_eq!(steps[2].level, 3);
+
+        let titles = |steps: Vec<Step>| steps.into_iter().map(|s| s.title).collect::<Vec<_>>();
+        let error = select_sections(steps.clone(), &["deployment".into()]).unwrap_err();
+        assert!(error.to_string().contains("App, Build, 🚀 Deploy, FAQ"));
+
+        let selected = select_sections(steps.clone(), &["FAQ".into(), "deploy".into()]).unwrap();
+        assert_eq!(
+            titles(selected),
+            vec!["🚀 Deploy", "Staging", "Checks", "Production", "FAQ"]
+        );
+        let selected = select_sections(steps, &["Staging".into()]).unwrap();
+        assert_eq!(titles(selected), vec!["Staging", "Checks"]);
+    }
+
+    #[test]
+    fn test_parse_indented_blocks() {
+        let content =
+            "# Install\nRun:\n\n    ./configure\n    make\n\n```bash\nmake install\n```\n";
+        let (steps, _) = parse_readme(content);
+        let blocks = &steps[0].code_blocks;
+        assert_eq!(blocks.len(), 2);
+        assert!(blocks[0].indented);
+        assert_eq!(blocks[0].language, None);
+        assert_eq!(blocks[0].content, "./configure\nmake\n");
+        assert!(!blocks[1].indented);
+    }
+
+    #[test]
+    fn test_parse_inline_commands() {
+        let content = "# Install `deps`\nRun `npm ci` then `./scripts/setup.sh --dev`. Edit `config.yaml` or set `debug` to `true`, then `$ make`.";
+        let (steps, _) = parse_readme(content);
+        assert_eq!(steps[0].title, "Install deps");
+        assert_eq!(
+            steps[0].inline_commands,
+            vec!["npm ci", "./scripts/setup.sh --dev", "make"]
+        );
+        assert!(steps[0].description.starts_with("Run `npm ci` then"));
+        assert!(looks_like_command("sudo apt install jq"));
+        assert!(!looks_like_command("Cargo.toml"));
+        assert!(!looks_like_command("--release"));
+        assert_eq!(inline_command("$ "), None);
+    }
+
+    #[test]
+    fn test_inline_command_after_prompt() {
+        // A prompt makes a command of any span, without the prompt
+        assert_eq!(inline_command("$ ls"), Some("ls".to_string()));
+        assert_eq!(
+            inline_command("  $ docker compose up -d "),
+            Some("docker compose up -d".to_string())
+        );
+        assert_eq!(inline_command("ls"), None);
+        assert_eq!(inline_command("$HOME"), None);
+
+        let (steps, _) = parse_readme(
+            "# Check
+Run `$ cargo test` and `$ ls`, not `$PATH`.",
+        );
+        assert_eq!(steps[0].inline_commands, vec!["cargo test", "ls"]);
+        assert!(steps[0].description.contains("`$ cargo test`"));
+    }
+
+    #[test]
+    fn test_parse_formatted_description() {
+        let content = "# Setup\nRead the **guide** and *then* the [docs](https://x.dev).\n\n- a\n- b:\n  1. c\n  2. d\n\n| Tool | Version |\n|---|---|\n| jq | 1.7 |\n\nDone.\n";
+        let (steps, _) = parse_readme(content);
+        assert_eq!(
+            steps[0].description,
+            "Read the **guide** and *then* the [docs](https://x.dev).\n- a\n- b:\n  1. c\n  2. d\n\n| Tool | Version |\n|---|---|\n| jq | 1.7 |\n\nDone.\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_step_level() {
+        let content = "# App\n## Setup\n### Install\n```bash\nmake\n```\n#### On macOS\nUse brew.\n```bash\nbrew install jq\n```\n### Run\n";
+        let titles = |steps: &[Step]| steps.iter().map(|s| s.title.clone()).collect::<Vec<_>>();
+
+        let (steps, _) = parse_readme(content);
+        assert_eq!(steps.len(), 5);
+
+        let (steps, _) = parse_readme_at(content, Some(3));
+        assert_eq!(titles(&steps), vec!["Install", "Run"]);
+        assert_eq!(steps[0].code_blocks.len(), 2);
+        assert_eq!(steps[0].description, "On macOS\nUse brew.\n");
+
+        let front = format!("---\nstep_level: 2\n---\n{content}");
+        let (steps, _) = parse_readme(&front);
+        assert_eq!(titles(&steps), vec!["Setup"]);
+        let (steps, _) = parse_readme_at(&front, Some(3));
+        assert_eq!(steps.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_html_blocks() {
+        let content = r#"# Install
+<div class="note">
+  <p>Requires <b>Docker</b> &amp; Git.</p>
+</div>
+
+<Tabs>
+<TabItem value="linux">
+
+```bash
+apt install docker
+```
+
+</TabItem>
+</Tabs>
+
+<pre><code class="language-bash">docker run --rm &lt;IMAGE&gt;
+</code></pre>
+"#;
+        let (steps, _) = parse_readme(content);
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].description, "Requires Docker & Git.\n");
+        let blocks = &steps[0].code_blocks;
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].content, "apt install docker\n");
+        assert_eq!(blocks[1].language.as_deref(), Some("bash"));
+        assert_eq!(blocks[1].content, "docker run --rm <IMAGE>\n");
+        assert_eq!(blocks[1].placeholders, vec!["IMAGE"]);
+    }
+
+    #[test]
+    fn test_parse_step_definitions() {
+        let content = r#"# Setup
+Install the tools.
+```bash
+guessed command
+```
+```compass
+name: Install dependencies
+command: npm ci
+cwd: frontend
+env:
+  NODE_ENV: production
+  PORT: 8080
+verify: test -d node_modules
+rollback: rm -rf node_modules
+tags: [ci-safe]
+```
+```bash
+example output
+```
+```compass
+command: npm run build
+```
+# Broken
+```compass
+command: [not, a, string]
+```
+"#;
+        let (steps, _) = parse_readme(content);
+        let titles: Vec<_> = steps.iter().map(|s| s.title.as_str()).collect();
+        assert_eq!(titles, vec!["Install dependencies", "Setup", "Broken"]);
+
+        let step = &steps[0];
+        assert_eq!(step.code_blocks.len(), 1);
+        assert_eq!(step.code_blocks[0].content, "npm ci\n");
+        assert_eq!(
+            step.description,
+            "Install the tools.\n```bash\nguessed command\n```\n```bash\nexample output\n```\n"
+        );
+        assert_eq!(step.scope.cwd.as_deref(), Some("frontend"));
+        assert_eq!(step.scope.env["PORT"], "8080");
+        assert_eq!(step.scope.verify.as_deref(), Some("test -d node_modules"));
+        assert_eq!(step.rollback.as_deref(), Some("rm -rf node_modules"));
+        assert_eq!(step.tags, vec!["ci-safe"]);
+
+        assert_eq!(steps[1].code_blocks[0].content, "npm run build\n");
+        assert!(steps[1].scope.is_empty());
+        assert!(!steps[2].is_executable());
+        assert!(steps[2].description.contains("Invalid compass block"));
+    }
+
+    #[test]
+    fn test_parse_eta() {
+        let content =
+            "# One\n<!-- compass:eta 1h30m -->\n# Two\n<!-- compass:eta 45 -->\n# Three\n";
+        let (steps, _) = parse_readme(content);
+        assert_eq!(steps[0].eta_secs, Some(5400));
+        assert_eq!(steps[1].eta_secs, Some(45));
+        assert_eq!(steps[2].eta_secs, None);
+
+        let (steps, _) = parse_readme("# Build\n<!-- compass:timeout 2m -->\n# Test\n");
+        assert_eq!(steps[0].timeout_secs, Some(120));
+        assert_eq!(steps[1].timeout_secs, None);
+        assert_eq!(parse_duration("5m"), Some(300));
+        assert_eq!(parse_duration("soon"), None);
+    }
+}
+
+/// Property tests feeding the parser malformed Markdown: runbooks are fetched
+/// from URLs and shared, so a document must never make it panic.
+#[cfg(test)]
+mod properties {
+    use super::*;
+    use proptest::prelude::*;
+    use proptest::sample::select;
+
+    /// Pieces of Markdown the parser handles specially, to be shuffled into
+    /// broken documents.
+    const FRAGMENTS: &[&str] = &[
+        "# Title",
+        "## Step",
+        "####### Too deep",
+        "```bash",
+        "```",
+        "~~~",
+        "```compass",
+        "    indented code",
+        "echo <NAME> {{OTHER}}",
+        "deploy <ENV:dev|prod> <SECRET:TOKEN> {{ENV:a|}}",
+        "<!-- compass:if os=\"linux\" -->",
+        "<!-- compass:if env=\"\" -->",
+        "<!-- compass:elif os=\"windows\" -->",
+        "<!-- compass:else -->",
+        "<!-- compass:endif -->",
+        "<!-- compass:context isolated -->",
+        "<!-- compass:env A=1 B -->",
+        "<!-- compass:requires \"Step\" \"",
+        "<!-- compass:requires_env -->",
+        "<!-- compass:write path=\"",
+        "<!-- compass:edits -->",
+        "<!-- compass:timeout 99999999999999999999h -->",
+        "<!-- compass:eta 1h1h1h -->",
+        "<!-- compass:tags , ,",
+        "<!-- compass:",
+        "<!--",
+        "-->",
+        "<div><code>make</code>",
+        "</div>",
+        "<pre>",
+        "title: x\nrun: [",
+        "`inline` and `echo <X>`",
+        "---",
+        "\r\n",
+        "é",
+        "\u{0}",
+    ];
+
+    /// Lines of a frontmatter, valid or not.
+    const FRONTMATTER: &[&str] = &[
+        "context: isolated",
+        "context: 3",
+        "step_level: 0",
+        "step_level: 255",
+        "step_level: -1",
+        "env:\n  PORT: 8080\n  LIST: [1]",
+        "secrets: [A, B]",
+        "secrets: A",
+        "placeholders:\n  PORT:\n    pattern: \"[\"",
+        "placeholders:\n  ENV:\n    choices: []",
+        "placeholders: ~",
+        "matrix:\n  os: [linux]",
+        "pre_run: |",
+        "  echo",
+        "&anchor",
+        "*alias",
+        "{",
+        ":",
+    ];
+
+    fn document() -> impl Strategy<Value = String> {
+        let piece = prop_oneof![
+            3 => select(FRAGMENTS).prop_map(str::to_string),
+            1 => any::<String>(),
+        ];
+        proptest::collection::vec(piece, 0..40).prop_map(|pieces| pieces.join("\n"))
+    }
+
+    fn frontmatter() -> impl Strategy<Value = String> {
+        proptest::collection::vec(select(FRONTMATTER), 0..8)
+            .prop_map(|lines| format!("---\n{}\n---\n", lines.join("\n")))
+    }
+
+    proptest! {
+        // Deeper searches are left to the `fuzz/` targets
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn parse_any_text(content in any::<String>()) {
+            parse_readme(&content);
+        }
+
+        #[test]
+        fn parse_broken_markdown(content in document(), level in proptest::option::of(any::<u8>())) {
+            let (steps, _) = parse_readme_at(&content, level);
+            for step in &steps {
+                for block in &step.code_blocks {
+                    for name in &block.placeholders {
+                        prop_assert!(block.content.contains(name.as_str()));
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn parse_broken_frontmatter(header in frontmatter(), body in document()) {
+            parse_readme(&format!("{header}{body}"));
+        }
+    }
 }
@@ -0,0 +1,93 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Plugin API
+//!
+//! Compass looks its providers up in registries rather than in hardcoded
+//! lists, so new ones can be added without touching the code using them:
+//!
+//! | Provider | Trait | Registered with |
+//! |----------|-------|-----------------|
+//! | URL rewriter (`compass clone`, remote runbooks) | [`UrlRewriter`] | [`register_rewriter`] |
+//! | Language of code blocks | [`LanguageDefinition`] | [`register_language`] |
+//! | Recovery suggestion after a failure | [`RecoveryRule`] | [`register_recovery_rule`] |
+//! | `export-script` format | [`RunbookExporter`] | [`register_exporter`] |
+//! | Secret manager of placeholder sources | [`SecretProvider`] | [`register_secret_provider`] |
+//! | Command rewriting before execution | [`CommandTransformer`] | [`register_command_transformer`] |
+//!
+//! Each registry starts with the built-in providers. A provider registered
+//! later is consulted before the earlier ones, so it can replace a built-in
+//! (same language id, exporter name or secret scheme).
+//!
+//! Register providers at startup, before any runbook is loaded: from `main`,
+//! or from a module compiled in behind a Cargo feature
+//! (`#[cfg(feature = "...")]`). With the `wasm-plugins` feature, WASM
+//! modules found in the configuration directory register through the same
+//! functions (see [`wasm`]).
+
+pub use crate::core::analysis::recovery::{RecoveryRule, register_recovery_rule};
+pub use crate::core::executor::languages::definition::LanguageDefinition;
+pub use crate::core::executor::languages::{Language, register_language};
+pub use crate::core::executor::transform::{CommandTransformer, register_command_transformer};
+pub use crate::core::export::runbook::{RunbookExporter, register_exporter};
+pub use crate::core::fetcher::rewriters::{UrlRewriter, register_rewriter};
+pub use crate::core::secrets::{SecretProvider, register_secret_provider};
+
+#[cfg(feature = "wasm-plugins")]
+pub mod wasm;
+
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// Providers of one kind, most recently registered first.
+pub struct Registry<T: ?Sized> {
+    entries: RwLock<Vec<Arc<T>>>,
+}
+
+impl<T: ?Sized> Registry<T> {
+    /// A registry holding the built-in providers, in the order they are tried.
+    pub fn new(builtins: Vec<Arc<T>>) -> Self {
+        Self {
+            entries: RwLock::new(builtins),
+        }
+    }
+
+    /// Adds a provider, tried before all the others.
+    pub fn register(&self, entry: Arc<T>) {
+        self.entries
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(0, entry);
+    }
+
+    /// The providers, in the order they are tried.
+    pub fn entries(&self) -> Vec<Arc<T>> {
+        self.entries
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registered_entries_come_first() {
+        let registry: Registry<str> = Registry::new(vec![Arc::from("a"), Arc::from("b")]);
+        registry.register(Arc::from("c"));
+        let names: Vec<String> = registry.entries().iter().map(|e| e.to_string()).collect();
+        assert_eq!(names, ["c", "a", "b"]);
+    }
+}
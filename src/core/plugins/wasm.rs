@@ -0,0 +1,271 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # WASM Plugins
+//!
+//! Organization-specific hooks compiled to WebAssembly, loaded from the
+//! `plugins` directory of the configuration (`~/.config/compass/plugins` on
+//! Linux). Each `.wasm` (or `.wat`) file there is a plugin named after it.
+//!
+//! Plugins are sandboxed: Compass gives them no host functions, so they have
+//! no filesystem, network, environment or clock, and a module importing
+//! anything is rejected. Each call runs in a fresh instance with a fuel
+//! budget and a memory cap, so a plugin can neither hang a run nor keep state
+//! between calls.
+//!
+//! ## ABI
+//!
+//! A plugin exports its `memory`, an allocator and at least one hook:
+//!
+//! - `alloc(len: i32) -> i32`: returns a buffer of `len` bytes, where Compass
+//!   writes the input of a hook.
+//! - `transform_command(ptr: i32, len: i32) -> i64`: receives a command
+//!   before it runs and returns the one to run instead, or `0` to keep it.
+//! - `analyze_output(ptr: i32, len: i32) -> i64`: receives the errors of a
+//!   failed step and returns a recovery suggestion, or `0` when it has none.
+//!   A second line, if any, is a command fixing the problem.
+//!
+//! Strings are UTF-8. A returned string is packed as `(ptr << 32) | len`.
+
+use super::{
+    CommandTransformer, RecoveryRule, register_command_transformer, register_recovery_rule,
+};
+use crate::core::analysis::recovery::RecoveryRecommendation;
+use anyhow::{Context, Result, bail};
+use std::path::Path;
+use std::sync::Arc;
+use wasmtime::{Config, Engine, Instance, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+/// Instructions a hook may run: plenty for text processing, not for a loop
+/// that never ends.
+const FUEL: u64 = 100_000_000;
+/// Memory an instance may grow to.
+const MEMORY_LIMIT: usize = 64 * 1024 * 1024;
+
+const TRANSFORM_HOOK: &str = "transform_command";
+const ANALYZE_HOOK: &str = "analyze_output";
+
+/// A compiled plugin.
+pub struct WasmPlugin {
+    name: String,
+    module: Module,
+}
+
+impl WasmPlugin {
+    /// Compiles the plugin at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the module is invalid, imports anything or does
+    /// not export `memory` and `alloc`.
+    pub fn load(engine: &Engine, path: &Path) -> Result<Self> {
+        let module = Module::from_file(engine, path)?;
+        if let Some(import) = module.imports().next() {
+            bail!(
+                "it imports '{}::{}', but plugins get no host functions",
+                import.module(),
+                import.name()
+            );
+        }
+        for export in ["memory", "alloc"] {
+            if module.get_export(export).is_none() {
+                bail!("it does not export '{export}'");
+            }
+        }
+        let name = path
+            .file_stem()
+            .map_or_else(String::new, |s| s.to_string_lossy().to_string());
+        Ok(Self { name, module })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the plugin implements a hook.
+    pub fn has_hook(&self, hook: &str) -> bool {
+        self.module.get_export(hook).is_some()
+    }
+
+    /// Calls a hook with `input` in a fresh instance, returning its result.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the plugin traps, runs out of fuel or memory, or
+    /// returns something else than a UTF-8 string of its memory.
+    pub fn call(&self, hook: &str, input: &str) -> Result<Option<String>> {
+        let limits = StoreLimitsBuilder::new().memory_size(MEMORY_LIMIT).build();
+        let mut store: Store<StoreLimits> = Store::new(self.module.engine(), limits);
+        store.limiter(|limits| limits);
+        store.set_fuel(FUEL)?;
+
+        let instance = Instance::new(&mut store, &self.module, &[])?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .context("'memory' is not a memory")?;
+        let alloc = instance.get_typed_func::<i32, i32>(&mut store, "alloc")?;
+        let hook = instance.get_typed_func::<(i32, i32), i64>(&mut store, hook)?;
+
+        let len = i32::try_from(input.len()).context("input too large")?;
+        let ptr = alloc.call(&mut store, len)?;
+        memory.write(&mut store, ptr as u32 as usize, input.as_bytes())?;
+        let packed = hook.call(&mut store, (ptr, len))?;
+        if packed == 0 {
+            return Ok(None);
+        }
+
+        let start = (packed as u64 >> 32) as usize;
+        let end = start + (packed as u32 as usize);
+        let bytes = memory
+            .data(&store)
+            .get(start..end)
+            .context("it returned a string outside of its memory")?;
+        let text = String::from_utf8(bytes.to_vec()).context("it returned invalid UTF-8")?;
+        Ok(Some(text))
+    }
+}
+
+/// The `transform_command` hook of a plugin.
+struct Transformer(Arc<WasmPlugin>);
+
+impl CommandTransformer for Transformer {
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+
+    fn transform(&self, command: &str, _language: Option<&str>) -> Option<String> {
+        // A broken plugin must not block the runbook: the command runs as written
+        self.0
+            .call(TRANSFORM_HOOK, command)
+            .inspect_err(|e| {
+                tracing::warn!(plugin = self.0.name(), "{TRANSFORM_HOOK} failed: {e:#}")
+            })
+            .ok()
+            .flatten()
+    }
+}
+
+/// The `analyze_output` hook of a plugin.
+struct Analyzer(Arc<WasmPlugin>);
+
+impl RecoveryRule for Analyzer {
+    fn analyze(&self, stderr: &str) -> Option<RecoveryRecommendation> {
+        let answer = self
+            .0
+            .call(ANALYZE_HOOK, stderr)
+            .inspect_err(|e| tracing::warn!(plugin = self.0.name(), "{ANALYZE_HOOK} failed: {e:#}"))
+            .ok()
+            .flatten()?;
+        let mut lines = answer.lines().map(str::trim).filter(|l| !l.is_empty());
+        Some(RecoveryRecommendation {
+            message: lines.next()?.to_string(),
+            fix_command: lines.next().map(str::to_string),
+        })
+    }
+}
+
+/// Loads the plugins of `dir` and registers their hooks. Returns a warning
+/// for each file that is not a valid plugin.
+pub fn register_plugins(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == "wasm" || ext == "wat")
+        })
+        .collect();
+    paths.sort();
+    if paths.is_empty() {
+        return Vec::new();
+    }
+
+    let mut config = Config::new();
+    config.consume_fuel(true);
+    let engine = match Engine::new(&config) {
+        Ok(engine) => engine,
+        Err(e) => return vec![format!("WASM plugins disabled: {e:#}")],
+    };
+
+    let mut warnings = Vec::new();
+    for path in paths {
+        let plugin = match WasmPlugin::load(&engine, &path) {
+            Ok(plugin) => Arc::new(plugin),
+            Err(e) => {
+                warnings.push(format!("Plugin '{}' not loaded: {e:#}", path.display()));
+                continue;
+            }
+        };
+        if !plugin.has_hook(TRANSFORM_HOOK) && !plugin.has_hook(ANALYZE_HOOK) {
+            warnings.push(format!(
+                "Plugin '{}' not loaded: it exports no hook ({TRANSFORM_HOOK} or {ANALYZE_HOOK})",
+                path.display()
+            ));
+            continue;
+        }
+        tracing::info!(plugin = plugin.name(), "WASM plugin loaded");
+        if plugin.has_hook(TRANSFORM_HOOK) {
+            register_command_transformer(Arc::new(Transformer(plugin.clone())));
+        }
+        if plugin.has_hook(ANALYZE_HOOK) {
+            register_recovery_rule(Arc::new(Analyzer(plugin)));
+        }
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLUGIN: &str = r#"(module
+        (memory (export "memory") 1)
+        (data (i32.const 16) "echo plugin")
+        (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+        (func (export "transform_command") (param i32 i32) (result i64)
+            (i64.or (i64.shl (i64.const 16) (i64.const 32)) (i64.const 11)))
+        (func (export "analyze_output") (param i32 i32) (result i64)
+            (loop $forever (br $forever))
+            (i64.const 0)))"#;
+
+    fn load(name: &str, wat: &str) -> Result<WasmPlugin> {
+        let path = std::env::temp_dir().join(format!("compass_test_{name}.wat"));
+        std::fs::write(&path, wat).unwrap();
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let plugin = WasmPlugin::load(&Engine::new(&config).unwrap(), &path);
+        let _ = std::fs::remove_file(path);
+        plugin
+    }
+
+    #[test]
+    fn test_wasm_plugin_hooks() {
+        let plugin = load("plugin", PLUGIN).unwrap();
+        assert_eq!(plugin.name(), "compass_test_plugin");
+        assert_eq!(
+            plugin.call(TRANSFORM_HOOK, "echo hi").unwrap().as_deref(),
+            Some("echo plugin")
+        );
+        // An endless loop runs out of fuel instead of hanging
+        assert!(plugin.call(ANALYZE_HOOK, "error").is_err());
+
+        let importing =
+            r#"(module (import "wasi" "fd_write" (func)) (memory (export "memory") 1))"#;
+        let e = load("importing", importing).err().unwrap();
+        assert!(e.to_string().contains("no host functions"));
+    }
+}
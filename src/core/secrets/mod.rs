@@ -0,0 +1,178 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Placeholder values read from secret managers instead of being typed. A
+//! placeholder declares its source in the frontmatter
+//! (`source: vault:secret/data/app#token`); the value is fetched once the user
+//! agreed, kept in memory for the session and never saved.
+
+pub mod providers;
+
+use crate::core::plugins::Registry;
+use anyhow::{Result, anyhow, bail};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, LazyLock, Mutex};
+
+/// A secret manager placeholder values can be read from.
+pub trait SecretProvider: Send + Sync {
+    /// Prefix of the sources it reads (`vault` in `vault:secret/data/app`).
+    fn scheme(&self) -> &'static str;
+    /// Name of the secret manager, for messages.
+    fn name(&self) -> &'static str;
+    /// Reads the secret at `path`, or its field `key` when it holds several.
+    fn fetch(&self, path: &str, key: Option<&str>) -> Result<String>;
+}
+
+static PROVIDERS: LazyLock<Registry<dyn SecretProvider>> = LazyLock::new(|| {
+    let builtins: Vec<Arc<dyn SecretProvider>> = vec![
+        Arc::new(providers::Vault),
+        Arc::new(providers::AwsSecretsManager),
+        Arc::new(providers::GcpSecretManager),
+        Arc::new(providers::AzureKeyVault),
+    ];
+    Registry::new(builtins)
+});
+
+/// Adds a secret manager, or replaces the one reading the same scheme.
+pub fn register_secret_provider(provider: Arc<dyn SecretProvider>) {
+    PROVIDERS.register(provider);
+}
+
+/// The secret managers Compass can read from.
+pub fn providers() -> Vec<Arc<dyn SecretProvider>> {
+    PROVIDERS.entries()
+}
+
+/// The secret manager of a scheme.
+pub fn provider(scheme: &str) -> Option<Arc<dyn SecretProvider>> {
+    providers().into_iter().find(|p| p.scheme() == scheme)
+}
+
+/// Where a placeholder value is read from: `scheme:path#key`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretSource {
+    pub scheme: String,
+    pub path: String,
+    /// Field of a secret holding several values.
+    pub key: Option<String>,
+}
+
+impl SecretSource {
+    /// Parses `scheme:path#key`, checking that a provider reads the scheme.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source is malformed or its scheme unknown.
+    pub fn parse(source: &str) -> Result<Self> {
+        let (scheme, rest) = source
+            .split_once(':')
+            .filter(|(scheme, rest)| !scheme.is_empty() && !rest.is_empty())
+            .ok_or_else(|| {
+                anyhow!("'{source}' is not a secret source: expected scheme:path#key")
+            })?;
+        if provider(scheme).is_none() {
+            let schemes: Vec<&str> = providers().iter().map(|p| p.scheme()).collect();
+            bail!(
+                "Unknown secret manager '{scheme}' (expected {})",
+                schemes.join(", ")
+            );
+        }
+        let (path, key) = match rest.rsplit_once('#') {
+            Some((path, key)) if !key.is_empty() => (path, Some(key.to_string())),
+            _ => (rest, None),
+        };
+        Ok(Self {
+            scheme: scheme.to_string(),
+            path: path.to_string(),
+            key,
+        })
+    }
+}
+
+impl fmt::Display for SecretSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.scheme, self.path)?;
+        if let Some(key) = &self.key {
+            write!(f, "#{key}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Secrets read for placeholders, by name, or why they could not be read.
+pub type FetchedSecrets = Vec<(String, Result<String, String>)>;
+
+/// Secrets read during the session, by source, so that each one is fetched
+/// once. Shared with the threads that fetch them.
+#[derive(Default)]
+pub struct SecretCache {
+    values: Mutex<HashMap<String, String>>,
+}
+
+impl SecretCache {
+    /// The value of `source` if it was already read.
+    pub fn get(&self, source: &str) -> Option<String> {
+        self.values.lock().ok()?.get(source).cloned()
+    }
+
+    /// Reads the secret of `source`, from the cache or its secret manager.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source is invalid, or the secret manager
+    /// cannot be reached or has no such secret.
+    pub fn resolve(&self, source: &str) -> Result<String> {
+        if let Some(value) = self.get(source) {
+            return Ok(value);
+        }
+        let parsed = SecretSource::parse(source)?;
+        let provider = provider(&parsed.scheme).expect("parse checks the scheme");
+        let value = provider
+            .fetch(&parsed.path, parsed.key.as_deref())
+            .map_err(|e| anyhow!("{}: {e}", provider.name()))?;
+        let value = value.trim_end_matches(['\r', '\n']).to_string();
+        if value.is_empty() {
+            bail!("{}: the secret {parsed} is empty", provider.name());
+        }
+        if let Ok(mut values) = self.values.lock() {
+            values.insert(source.to_string(), value.clone());
+        }
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sources() {
+        let source = SecretSource::parse("vault:secret/data/app#token").unwrap();
+        assert_eq!(source.scheme, "vault");
+        assert_eq!(source.path, "secret/data/app");
+        assert_eq!(source.key.as_deref(), Some("token"));
+        assert_eq!(source.to_string(), "vault:secret/data/app#token");
+
+        let source = SecretSource::parse("aws-sm:prod/db").unwrap();
+        assert_eq!(source.key, None);
+        assert!(SecretSource::parse("keychain:x").is_err());
+        assert!(SecretSource::parse("vault:").is_err());
+        assert!(SecretSource::parse("token").is_err());
+
+        let cache = SecretCache::default();
+        assert!(cache.resolve("nope:x").is_err());
+        assert_eq!(cache.get("nope:x"), None);
+    }
+}
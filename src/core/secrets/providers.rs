@@ -0,0 +1,197 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The secret managers, read through their CLIs with the credentials of the
+//! user: `vault`, `aws`, `gcloud` and `az`.
+
+use super::SecretProvider;
+use crate::core::executor::cloud;
+use anyhow::{Result, anyhow, bail};
+use serde_json::Value;
+use std::process::Command;
+
+/// HashiCorp Vault: `vault:secret/data/app#token`. KV v2 paths include
+/// `data/`, as for `vault read`.
+pub struct Vault;
+
+impl SecretProvider for Vault {
+    fn scheme(&self) -> &'static str {
+        "vault"
+    }
+
+    fn name(&self) -> &'static str {
+        "Vault"
+    }
+
+    fn fetch(&self, path: &str, key: Option<&str>) -> Result<String> {
+        let output = cli("vault", &["read", "-format=json", path])?;
+        let json: Value = serde_json::from_str(&output)?;
+        pick(vault_fields(&json), key)
+    }
+}
+
+/// The fields of the answer of `vault read`: KV v2 nests them under
+/// `data.data`.
+fn vault_fields(json: &Value) -> &Value {
+    let data = &json["data"];
+    data.get("data").filter(|d| d.is_object()).unwrap_or(data)
+}
+
+/// AWS Secrets Manager: `aws-sm:prod/db#password`.
+pub struct AwsSecretsManager;
+
+impl SecretProvider for AwsSecretsManager {
+    fn scheme(&self) -> &'static str {
+        "aws-sm"
+    }
+
+    fn name(&self) -> &'static str {
+        "AWS Secrets Manager"
+    }
+
+    fn fetch(&self, path: &str, key: Option<&str>) -> Result<String> {
+        let args = [
+            "secretsmanager",
+            "get-secret-value",
+            "--secret-id",
+            path,
+            "--query",
+            "SecretString",
+            "--output",
+            "text",
+        ];
+        field(&cli("aws", &args)?, key)
+    }
+}
+
+/// Google Cloud Secret Manager: `gcp-sm:db-password`, latest version.
+pub struct GcpSecretManager;
+
+impl SecretProvider for GcpSecretManager {
+    fn scheme(&self) -> &'static str {
+        "gcp-sm"
+    }
+
+    fn name(&self) -> &'static str {
+        "Google Cloud Secret Manager"
+    }
+
+    fn fetch(&self, path: &str, key: Option<&str>) -> Result<String> {
+        let secret = format!("--secret={path}");
+        field(
+            &cli(
+                "gcloud",
+                &["secrets", "versions", "access", "latest", &secret],
+            )?,
+            key,
+        )
+    }
+}
+
+/// Azure Key Vault: `az-kv:my-vault/db-password`.
+pub struct AzureKeyVault;
+
+impl SecretProvider for AzureKeyVault {
+    fn scheme(&self) -> &'static str {
+        "az-kv"
+    }
+
+    fn name(&self) -> &'static str {
+        "Azure Key Vault"
+    }
+
+    fn fetch(&self, path: &str, key: Option<&str>) -> Result<String> {
+        let Some((vault, name)) = path.split_once('/') else {
+            bail!("expected az-kv:<vault>/<secret>, got '{path}'");
+        };
+        let args = [
+            "keyvault",
+            "secret",
+            "show",
+            "--vault-name",
+            vault,
+            "--name",
+            name,
+            "--query",
+            "value",
+            "--output",
+            "tsv",
+        ];
+        field(&cli("az", &args)?, key)
+    }
+}
+
+/// Runs a secret manager CLI, with the timeout of the cloud probes.
+fn cli(program: &str, args: &[&str]) -> Result<String> {
+    let mut command = Command::new(program);
+    command.args(args);
+    cloud::run(command, program).map_err(|e| anyhow!(e))
+}
+
+/// The secret itself, or its field `key` when it holds a JSON object.
+fn field(secret: &str, key: Option<&str>) -> Result<String> {
+    match key {
+        None => Ok(secret.to_string()),
+        Some(_) => pick(&serde_json::from_str(secret.trim())?, key),
+    }
+}
+
+/// Field `key` of the fields of a secret, or its only field.
+fn pick(fields: &Value, key: Option<&str>) -> Result<String> {
+    let Some(fields) = fields.as_object() else {
+        bail!("the secret holds no fields");
+    };
+    let value = match key {
+        Some(key) => fields
+            .get(key)
+            .ok_or_else(|| anyhow!("the secret has no field '{key}'"))?,
+        None if fields.len() == 1 => fields.values().next().expect("one field"),
+        None => {
+            let names: Vec<&str> = fields.keys().map(String::as_str).collect();
+            bail!(
+                "the secret holds several fields ({}): name one with #field",
+                names.join(", ")
+            );
+        }
+    };
+    Ok(match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_pick_fields() {
+        let kv2 = json!({"data": {"data": {"token": "s3cr3t", "port": 5432}}});
+        let fields = vault_fields(&kv2);
+        assert_eq!(pick(fields, Some("token")).unwrap(), "s3cr3t");
+        assert_eq!(pick(fields, Some("port")).unwrap(), "5432");
+        assert!(pick(fields, None).is_err());
+        assert!(pick(fields, Some("user")).is_err());
+        let kv1 = json!({"data": {"password": "pw"}, "lease_duration": 60});
+        assert_eq!(pick(vault_fields(&kv1), None).unwrap(), "pw");
+
+        assert_eq!(field("plain\n", None).unwrap(), "plain\n");
+        assert_eq!(
+            field(r#"{"user":"app","password":"pw"}"#, Some("password")).unwrap(),
+            "pw"
+        );
+        assert!(field("plain", Some("password")).is_err());
+    }
+}
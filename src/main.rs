@@ -12,12 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-mod core;
-mod ui;
+use compass_cli::core;
+use compass_cli::ui;
 
 use anyhow::Context;
 use clap::{Parser, Subcommand};
+use core::analysis::edits::DiffLine;
+use core::infrastructure::config::GlobalConfig;
+use core::infrastructure::trust::{self, Trust};
+use core::manifest::RunbookManifest;
 use std::fs;
+use std::io::IsTerminal;
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -31,28 +36,188 @@ struct Cli {
     #[arg(short, long, global = true)]
     sandbox: bool,
 
-    /// Docker image to use in sandbox mode
-    #[arg(long, global = true, default_value = "ubuntu:latest")]
-    image: String,
+    /// Docker image to use in sandbox mode (default: the configured image, ubuntu:latest)
+    #[arg(long, global = true)]
+    image: Option<String>,
 
     /// Run in Headless mode (JSON-RPC over Stdio)
     #[arg(long, global = true)]
     headless: bool,
+
+    /// Print changes as plain lines instead of redrawing the screen (for screen readers)
+    #[arg(long, global = true)]
+    linear: bool,
+
+    /// Header depth of the steps (e.g. 3 when `###` headers are the steps); deeper headers are folded into them
+    #[arg(long, global = true, value_parser = clap::value_parser!(u8).range(1..=6))]
+    step_level: Option<u8>,
+
+    /// Minimum level of internal events written to the log file (off, error, warn, info, debug, trace)
+    #[arg(long, global = true, default_value = "info")]
+    log_level: tracing::level_filters::LevelFilter,
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Parse and display a summary of the README
-    Parse { file: String },
-    /// Launch the interactive TUI
-    Tui {
+    Parse {
         file: String,
+        /// Print the parsed steps and hooks as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Launch the interactive TUI (without a file, pick a recent runbook; `-` reads stdin)
+    Tui {
+        file: Option<String>,
         /// Share this session with others (Host mode)
         #[arg(long)]
         share: bool,
+        /// Start in focus mode (only the current step, no step list)
+        #[arg(long)]
+        focus: bool,
+        /// Send filled placeholder values to guests (secret-looking ones are withheld)
+        #[arg(long, requires = "share")]
+        share_placeholders: bool,
+        /// Read the runbook from the clipboard (treated as an untrusted source, like a URL)
+        #[arg(long, conflicts_with = "file")]
+        from_clipboard: bool,
+        /// Only load this section and its subsections (repeat to pick several)
+        #[arg(long = "section", value_name = "TITLE")]
+        sections: Vec<String>,
+        /// Start from a context saved with `e` (working directory, environment, placeholders)
+        #[arg(long, value_name = "FILE")]
+        context: Option<PathBuf>,
+        /// Read placeholder values and environment variables from a dotenv file instead of asking for them
+        #[arg(long, value_name = "FILE")]
+        env_file: Option<PathBuf>,
+        /// Restore the step statuses, outputs and context saved when the runbook was last closed
+        #[arg(long, conflicts_with = "from_clipboard")]
+        resume: bool,
+        /// Open the runbook as a viewer: no step or hook runs, and nothing is exported to the working directory
+        #[arg(long)]
+        read_only: bool,
     },
-    /// Check if system dependencies are met
-    Check { file: String },
+    /// Check if system dependencies are met (exits with 1 if any are missing)
+    Check {
+        file: String,
+        /// Print the result as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Verify a runbook in CI: lint, check dependencies and run `ci-safe` steps (exits with 1 on failure)
+    Verify {
+        file: String,
+        /// Execute the steps tagged `ci-safe` (placeholders are read from environment variables)
+        #[arg(long)]
+        run: bool,
+        /// Fail on lint warnings too
+        #[arg(long)]
+        strict: bool,
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check a runbook without running it: frontmatter, annotations, placeholders, hooks (exits with 1 on errors)
+    Validate {
+        file: String,
+        /// Fail on warnings too
+        #[arg(long)]
+        strict: bool,
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Run the steps of a runbook without the TUI, stopping at the first failure (exits with 1)
+    Run {
+        file: String,
+        /// First step to run (1-based)
+        #[arg(long, value_name = "STEP")]
+        from: Option<usize>,
+        /// Last step to run (1-based)
+        #[arg(long, value_name = "STEP")]
+        to: Option<usize>,
+        /// Steps not to run (1-based, comma-separated or repeated)
+        #[arg(long, value_name = "STEP", value_delimiter = ',')]
+        skip: Vec<usize>,
+        /// Print one JSON event per line
+        #[arg(long)]
+        json: bool,
+        /// Run the rollback command of the step that failed
+        #[arg(long)]
+        rollback: bool,
+        /// Start from a context saved in the TUI (working directory, environment, placeholders)
+        #[arg(long, value_name = "FILE")]
+        context: Option<PathBuf>,
+        /// Print the command of each step, placeholders filled, without running anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Read placeholder values and environment variables from a dotenv file
+        #[arg(long, value_name = "FILE")]
+        env_file: Option<PathBuf>,
+        /// Read placeholders that have no value from the secret manager declared as their source
+        #[arg(long)]
+        fetch_secrets: bool,
+    },
+    /// Run every step and check its `compass:expect` assertions, then print a pass/fail summary (exits with 1 on failure)
+    Test {
+        file: String,
+        /// Print one JSON event per line
+        #[arg(long)]
+        json: bool,
+        /// Stop at the first failed step
+        #[arg(long)]
+        fail_fast: bool,
+        /// Start from a context saved in the TUI (working directory, environment, placeholders)
+        #[arg(long, value_name = "FILE")]
+        context: Option<PathBuf>,
+        /// Read placeholder values and environment variables from a dotenv file
+        #[arg(long, value_name = "FILE")]
+        env_file: Option<PathBuf>,
+        /// Read placeholders that have no value from the secret manager declared as their source
+        #[arg(long)]
+        fetch_secrets: bool,
+    },
+    /// Measure parse time and output throughput, to catch performance regressions
+    #[command(hide = true)]
+    Bench {
+        /// Steps of the generated runbook
+        #[arg(long, default_value_t = 2000)]
+        steps: usize,
+        /// Lines of generated command output
+        #[arg(long, default_value_t = 50_000)]
+        lines: usize,
+        /// Runs of each benchmark (the median is reported)
+        #[arg(long, default_value_t = 10)]
+        iterations: u32,
+        /// Print the results as JSON
+        #[arg(long)]
+        json: bool,
+        /// Compare with the JSON results of an earlier run (exits with 1 on regressions)
+        #[arg(long, value_name = "FILE")]
+        baseline: Option<PathBuf>,
+        /// Slowdown tolerated against the baseline, in percent
+        #[arg(long, default_value_t = 20)]
+        tolerance: u32,
+    },
+    /// Open an example runbook shipped with Compass (default: the tour)
+    Demo {
+        /// Name of the demo
+        name: Option<String>,
+        /// List the demos
+        #[arg(long, conflicts_with = "name")]
+        list: bool,
+    },
+    /// Learn the keys of the TUI in a guided tour of five minutes
+    Tutorial,
+    /// List recently opened runbooks
+    Recent,
+    /// Manage the snippet library offered by the TUI snippet palette
+    Snippet {
+        #[command(subcommand)]
+        action: SnippetAction,
+    },
+    /// Configure the global settings (theme, safety policy, sandbox, registry)
+    Setup,
     /// Join a shared session (Guest mode)
     Join {
         /// The secure connection URL (wss://.../?pin=...)
@@ -76,12 +241,322 @@ enum Commands {
         /// Destination filename (optional)
         destination: Option<String>,
     },
+    /// Export the runbook as a standalone bash script or an automation format
+    ExportScript {
+        file: String,
+        /// Write the script to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Output format: shell (bash script), ansible (playbook), github (Actions workflow)
+        /// or one added by a plugin
+        #[arg(long, default_value = "shell")]
+        format: String,
+    },
+    /// Generate a Brewfile, apt install line, winget import file or Nix shell for the dependencies
+    Bundle {
+        file: String,
+        /// Package manager (default: the one of this platform)
+        #[arg(long, value_enum)]
+        format: Option<BundleFormat>,
+        /// Include the dependencies that are already installed
+        #[arg(long)]
+        all: bool,
+        /// Write the bundle to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Convert a shell script or recent shell history into a runbook skeleton
+    Import {
+        /// Shell script to convert
+        #[arg(
+            required_unless_present = "from_history",
+            conflicts_with = "from_history"
+        )]
+        script: Option<PathBuf>,
+        /// Convert the last N commands of your shell history instead
+        #[arg(long, value_name = "N")]
+        from_history: Option<usize>,
+        /// Write the runbook to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+/// Actions of the `snippet` command.
+#[derive(Subcommand)]
+enum SnippetAction {
+    /// Save a command (replaces the snippet with the same name)
+    Add {
+        name: String,
+        command: String,
+        /// Language of the command (default: your preferred shell)
+        #[arg(long)]
+        language: Option<String>,
+    },
+    /// List the saved snippets, most used first
+    List,
+    /// Delete a snippet
+    Remove { name: String },
+}
+
+/// Package managers supported by `bundle`.
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum BundleFormat {
+    /// Homebrew Brewfile (`brew bundle`)
+    Brew,
+    /// `apt-get install` command line
+    Apt,
+    /// winget import file (`winget import`)
+    Winget,
+    /// `flake.nix` with a development shell (`nix develop`)
+    Nix,
+    /// `shell.nix` (`nix-shell`)
+    ShellNix,
+}
+
+/// Prepares an executor for an unattended run of the runbook at `path`, from
+/// a saved context and an env file if given, and returns it with the
+/// placeholder values they hold.
+fn headless_executor(
+    path: &std::path::Path,
+    sandbox: bool,
+    image: &str,
+    settings: &GlobalConfig,
+    context: Option<&std::path::Path>,
+    env_file: Option<&std::path::Path>,
+) -> anyhow::Result<(
+    core::executor::Executor,
+    std::collections::HashMap<String, String>,
+)> {
+    let mut executor = core::executor::Executor::new();
+    executor.context.current_dir = if path.is_file() {
+        path.parent().unwrap_or(path).to_path_buf()
+    } else {
+        std::env::current_dir()?
+    };
+    executor.context.sandbox_enabled = sandbox;
+    executor.context.docker_image = image.to_string();
+    settings.apply_shell(&mut executor.context);
+    let mut placeholders = match context {
+        Some(context) => load_context(context, &mut executor.context)?,
+        None => std::collections::HashMap::new(),
+    };
+    if let Some(env_file) = env_file {
+        let vars = core::infrastructure::dotenv::load(env_file)?;
+        executor.context.import_env(&vars);
+        placeholders.extend(vars);
+    }
+    Ok((executor, placeholders))
+}
+
+/// Applies a saved context to `context` and returns its placeholder values.
+fn load_context(
+    path: &std::path::Path,
+    context: &mut core::executor::engine::context::ExecutionContext,
+) -> anyhow::Result<std::collections::HashMap<String, String>> {
+    let saved = core::export::context::ContextExport::load(path)?;
+    if let Some(warning) = saved.apply(context) {
+        eprintln!("⚠️  {warning}");
+    }
+    eprintln!(
+        "Context loaded from {} (saved {}).",
+        path.display(),
+        saved.exported_at
+    );
+    Ok(saved.placeholders.into_iter().collect())
 }
 
-async fn load_readme(file: &str) -> anyhow::Result<(String, PathBuf, bool)> {
-    if file.starts_with("http://") || file.starts_with("https://") {
+/// Asks which recent runbook to open.
+fn pick_recent() -> anyhow::Result<String> {
+    use std::io::Write;
+
+    let recent = core::infrastructure::recent::load()?;
+    if recent.is_empty() {
+        anyhow::bail!("No recent runbooks. Usage: compass tui <FILE>");
+    }
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!("No file given. Usage: compass tui <FILE>");
+    }
+
+    println!("🕘 Recent runbooks:");
+    for (i, entry) in recent.iter().enumerate() {
+        println!("  {}. {entry}", i + 1);
+    }
+    loop {
+        print!("Open which runbook? (1): ");
+        std::io::stdout().flush()?;
+        let mut answer = String::new();
+        if std::io::stdin().read_line(&mut answer)? == 0 {
+            anyhow::bail!("No runbook selected.");
+        }
+        let answer = answer.trim();
+        let index = if answer.is_empty() {
+            Some(0)
+        } else {
+            answer.parse::<usize>().ok().and_then(|n| n.checked_sub(1))
+        };
+        if let Some(entry) = index.and_then(|i| recent.get(i)) {
+            return Ok(entry.location.clone());
+        }
+        println!("  Enter a number between 1 and {}.", recent.len());
+    }
+}
+
+/// Prints what the frontmatter of a runbook says about it.
+fn print_manifest(manifest: &RunbookManifest) {
+    let fields = [
+        ("📖 Title", manifest.title.clone()),
+        ("   Description", manifest.description.clone()),
+        ("   Author", manifest.author.clone()),
+        (
+            "🧰 Requires",
+            (!manifest.requires.is_empty()).then(|| manifest.requires.join(", ")),
+        ),
+        (
+            "   Compass",
+            manifest
+                .min_version
+                .as_ref()
+                .map(|v| format!("{v} or later")),
+        ),
+        ("📦 Image", manifest.image.clone()),
+    ];
+    for (label, value) in fields {
+        if let Some(value) = value {
+            println!("{label}: {value}");
+        }
+    }
+    if manifest.has_any() {
+        println!("🪝 Hooks detected: Yes");
+    }
+}
+
+/// Warns about what the frontmatter of a runbook requires and is missing:
+/// a newer Compass or some binaries.
+fn warn_requirements(manifest: Option<&RunbookManifest>) {
+    for warning in manifest
+        .map(|m| m.warnings(ui::app::VERSION))
+        .unwrap_or_default()
+    {
+        eprintln!("⚠️  {warning}");
+    }
+}
+
+/// Docker image of sandbox mode: `--image`, then the one the runbook asks
+/// for, then the configured one.
+fn runbook_image(cli: &Cli, manifest: Option<&RunbookManifest>, image: String) -> String {
+    manifest
+        .and_then(|m| m.image.clone())
+        .filter(|_| cli.image.is_none())
+        .unwrap_or(image)
+}
+
+/// Opens a runbook shipped with Compass in the TUI, from a scratch directory,
+/// with the guided tour of the tutorial or without.
+fn open_demo(
+    demo: &core::ecosystem::demo::Demo,
+    sandbox: bool,
+    image: String,
+    linear: bool,
+    tutorial: bool,
+) -> anyhow::Result<()> {
+    if sandbox {
+        core::infrastructure::docker::ensure_docker_available()?;
+    }
+    let path = core::ecosystem::demo::install(demo)?;
+    // Steps run from the working directory, the scratch one of the demo
+    if let Some(dir) = path.parent() {
+        std::env::set_current_dir(dir)?;
+    }
+    let (steps, manifest) = core::parser::parse_readme(demo.content);
+    println!("Opening the {} demo from {}...", demo.name, path.display());
+
+    ui::run_tui(
+        steps,
+        path,
+        ui::TuiOptions {
+            sandbox,
+            image,
+            manifest,
+            linear,
+            tutorial,
+            ..Default::default()
+        },
+    )
+}
+
+/// Maximum number of changed lines shown when a trusted runbook changed.
+const MAX_CHANGES_SHOWN: usize = 40;
+
+/// Prints what changed in a runbook since the user trusted it.
+fn print_changes(changes: &[DiffLine]) {
+    // Every group of changes starts with a hunk, the first one needs no separator
+    let shown = changes.iter().skip(1);
+    for line in shown.clone().take(MAX_CHANGES_SHOWN) {
+        match line {
+            DiffLine::Added(text) => println!("  + {text}"),
+            DiffLine::Removed(text) => println!("  - {text}"),
+            DiffLine::Context(text) => println!("    {text}"),
+            DiffLine::File(text) | DiffLine::Note(text) => println!("  {text}"),
+            DiffLine::Hunk => println!("  ..."),
+        }
+    }
+    let hidden = shown.count().saturating_sub(MAX_CHANGES_SHOWN);
+    if hidden > 0 {
+        println!("  ... and {hidden} more lines");
+    }
+}
+
+/// Reads an answer typed in the terminal, even when stdin carried the runbook.
+fn read_answer() -> anyhow::Result<String> {
+    use std::io::BufRead;
+
+    let mut input = String::new();
+    if std::io::stdin().is_terminal() {
+        std::io::stdin().read_line(&mut input)?;
+    } else {
+        std::io::BufReader::new(core::fetcher::open_terminal()?).read_line(&mut input)?;
+    }
+    Ok(input)
+}
+
+/// Reads a runbook, converting MDX, AsciiDoc, reStructuredText and notebooks to
+/// Markdown, and splices in the runbooks it includes.
+async fn load_readme(
+    file: &str,
+    settings: &GlobalConfig,
+) -> anyhow::Result<(String, PathBuf, bool)> {
+    let (content, path, is_remote) = read_source(file, settings).await?;
+    let location = path.to_string_lossy().to_string();
+    let content = core::documents::to_markdown(&location, content)?;
+    let max_bytes = settings.max_download_bytes();
+    let (content, includes_remote) = tokio::task::spawn_blocking(move || {
+        core::fetcher::includes::expand_includes(&content, &location, max_bytes)
+    })
+    .await??;
+    Ok((content, path, is_remote || includes_remote))
+}
+
+/// Downloads a runbook on a blocking thread: the blocking HTTP client cannot
+/// be dropped on the async runtime.
+async fn fetch(url: &str, settings: &GlobalConfig) -> anyhow::Result<String> {
+    let url = url.to_string();
+    let max_bytes = settings.max_download_bytes();
+    tokio::task::spawn_blocking(move || core::fetcher::fetch_remote_content(&url, max_bytes))
+        .await?
+}
+
+async fn read_source(
+    file: &str,
+    settings: &GlobalConfig,
+) -> anyhow::Result<(String, PathBuf, bool)> {
+    if file == core::fetcher::STDIN_SOURCE {
+        eprintln!("Reading runbook from stdin...");
+        let content = core::fetcher::read_stdin()?;
+        Ok((content, PathBuf::from(file), false))
+    } else if file.starts_with("http://") || file.starts_with("https://") {
         eprintln!("Downloading remote README from {}...", file);
-        let content = core::fetcher::fetch_remote_content(file)?;
+        let content = fetch(file, settings).await?;
         Ok((content, PathBuf::from(file), true))
     } else {
         let path = PathBuf::from(file);
@@ -91,6 +566,8 @@ async fn load_readme(file: &str) -> anyhow::Result<(String, PathBuf, bool)> {
             let content = fs::read_to_string(&canonical_path)
                 .with_context(|| format!("Failed to read file: {file}"))?;
             Ok((content, canonical_path, false))
+        } else if !settings.registry_enabled {
+            anyhow::bail!("File '{}' not found (registry lookup is disabled).", file);
         } else {
             // Try matching registry
             eprintln!(
@@ -99,11 +576,11 @@ async fn load_readme(file: &str) -> anyhow::Result<(String, PathBuf, bool)> {
             );
             match core::ecosystem::hub::resolve_runbook(file).await {
                 Ok(Some(runbook)) => {
-                    println!(
+                    eprintln!(
                         "Found '{}' in registry. Downloading from: {}",
                         runbook.name, runbook.url
                     );
-                    let content = core::fetcher::fetch_remote_content(&runbook.url)?;
+                    let content = fetch(&runbook.url, settings).await?;
                     Ok((content, PathBuf::from(runbook.url), true))
                 }
                 _ => {
@@ -122,13 +599,51 @@ async fn main() -> anyhow::Result<()> {
 
     let cli = Cli::parse();
 
+    if let Err(e) = core::infrastructure::logging::init(cli.log_level) {
+        eprintln!("Warning: logging disabled: {e}");
+    }
+
+    let mut settings = GlobalConfig::load().unwrap_or_else(|e| {
+        eprintln!("Warning: using default settings: {e}");
+        GlobalConfig::default()
+    });
+    #[cfg(feature = "wasm-plugins")]
+    if let Ok(dir) = core::infrastructure::config::ConfigManager::get_config_dir() {
+        for warning in core::plugins::wasm::register_plugins(&dir.join("plugins")) {
+            eprintln!("Warning: {warning}");
+        }
+    }
+    // First run: ask for the global settings before opening the TUI
+    if matches!(
+        cli.command,
+        Commands::Tui { .. } | Commands::Demo { list: false, .. } | Commands::Tutorial
+    ) && !cli.headless
+        && !GlobalConfig::exists()
+        && std::io::stdin().is_terminal()
+        && std::io::stdout().is_terminal()
+    {
+        settings = core::infrastructure::wizard::run(&settings)?;
+    }
+    let sandbox = cli.sandbox || settings.sandbox;
+    let linear = cli.linear || settings.linear_output;
+    let image = cli
+        .image
+        .clone()
+        .unwrap_or_else(|| settings.docker_image.clone());
+
     match &cli.command {
-        Commands::Parse { file } => {
-            let (content, _, _) = load_readme(file).await?;
-            let (steps, hooks) = core::parser::parse_readme(&content);
+        Commands::Parse { file, json } => {
+            let (content, _, _) = load_readme(file, &settings).await?;
+            let (steps, hooks) = core::parser::parse_readme_at(&content, cli.step_level);
+
+            if *json {
+                let output = serde_json::json!({ "steps": steps, "hooks": hooks });
+                println!("{}", serde_json::to_string_pretty(&output)?);
+                return Ok(());
+            }
 
-            if hooks.is_some() {
-                println!("🪝 Hooks detected: Yes");
+            if let Some(manifest) = &hooks {
+                print_manifest(manifest);
             }
             println!("Detected {} steps:", steps.len());
             for (i, step) in steps.iter().enumerate() {
@@ -140,23 +655,133 @@ async fn main() -> anyhow::Result<()> {
                 );
             }
         }
-        Commands::Tui { file, share } => {
+        Commands::Tui {
+            file,
+            share,
+            focus,
+            share_placeholders,
+            from_clipboard,
+            sections,
+            context,
+            env_file,
+            resume,
+            read_only,
+        } => {
+            // IDE integrations can start empty and push content with `load_runbook`
+            if cli.headless && file.is_none() && !from_clipboard {
+                eprintln!("Running in HEADLESS mode (JSON-RPC) without a runbook...");
+                let cwd = std::env::current_dir()?;
+                core::ecosystem::rpc::start_headless_server(
+                    Vec::new(),
+                    cwd,
+                    core::ecosystem::rpc::HeadlessOptions {
+                        sandbox,
+                        image,
+                        manifest: None,
+                        step_level: cli.step_level,
+                        remote: false,
+                    },
+                    &settings,
+                )
+                .await?;
+                return Ok(());
+            }
+            if cli.headless && file.as_deref() == Some(core::fetcher::STDIN_SOURCE) {
+                anyhow::bail!(
+                    "Headless mode reads JSON-RPC from stdin; pass the runbook as a file."
+                );
+            }
             // Check for sandbox availability if enabled
-            if cli.sandbox {
-                if let Err(e) = core::infrastructure::docker::ensure_docker_available() {
-                    eprintln!("Error: {}", e);
-                    std::process::exit(1);
-                }
-                println!("📦 Sandbox mode enabled (Image: {})", cli.image);
+            if sandbox && let Err(e) = core::infrastructure::docker::ensure_docker_available() {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            if cli.headless && context.is_some() {
+                anyhow::bail!(
+                    "--context is not supported in headless mode: pass `cwd` and `env` to execute_step instead."
+                );
+            }
+            if cli.headless && env_file.is_some() {
+                anyhow::bail!(
+                    "--env-file is not supported in headless mode: pass `env` and `placeholders` to execute_step instead."
+                );
+            }
+            if cli.headless && *resume {
+                anyhow::bail!("--resume is not supported in headless mode.");
+            }
+            if cli.headless && *read_only {
+                anyhow::bail!("--read-only is not supported in headless mode.");
             }
+            let saved_context = context
+                .as_deref()
+                .map(core::export::context::ContextExport::load)
+                .transpose()?;
+            let env_file = env_file
+                .as_deref()
+                .map(core::infrastructure::dotenv::load)
+                .transpose()?
+                .unwrap_or_default();
 
-            let (content, path, is_remote) = load_readme(file).await?;
-            let (steps, hooks) = core::parser::parse_readme(&content);
+            let (content, path, is_remote) = if *from_clipboard {
+                eprintln!("Reading runbook from the clipboard...");
+                let content = core::fetcher::clipboard::read_clipboard()?;
+                let path = PathBuf::from(core::fetcher::clipboard::CLIPBOARD_SOURCE);
+                (content, path, true)
+            } else {
+                let file = match file {
+                    Some(file) => file.clone(),
+                    None => pick_recent()?,
+                };
+                load_readme(&file, &settings).await?
+            };
+            let (mut steps, hooks) = core::parser::parse_readme_at(&content, cli.step_level);
+            if !sections.is_empty() {
+                steps = core::parser::select_sections(steps, sections)?;
+            }
 
             if steps.is_empty() {
                 println!("No sections (headers) found in the Markdown file.");
                 return Ok(());
             }
+            warn_requirements(hooks.as_ref());
+            let image = runbook_image(&cli, hooks.as_ref(), image);
+            if sandbox {
+                println!("📦 Sandbox mode enabled (Image: {})", image);
+            }
+
+            // Trust is remembered per runbook, except for pasted or piped ones.
+            // Nothing runs in read-only mode, so there is nothing to trust.
+            let source = path.to_string_lossy();
+            let trust_location = (!cli.headless
+                && !*read_only
+                && source != core::fetcher::clipboard::CLIPBOARD_SOURCE
+                && source != core::fetcher::STDIN_SOURCE)
+                .then(|| trust::location(&path));
+            let trust = match &trust_location {
+                Some(location) => trust::check(location, &content),
+                None => Trust::Unknown,
+            };
+            // The user's answer, when the runbook is trusted or they were asked
+            let mut trusted = matches!(trust, Trust::Trusted(_)).then_some(true);
+            let has_hooks = hooks.as_ref().is_some_and(|h| h.has_any());
+
+            if let Trust::Changed(previous) = &trust {
+                println!(
+                    "\n⚠️  This runbook changed since you trusted it on {}:",
+                    previous.date()
+                );
+                print_changes(&trust::changes(previous, &content));
+                if let Some(cmd) = hooks.as_ref().and_then(|h| h.pre_run.as_ref()) {
+                    println!("If trusted, it executes this command IMMEDIATELY:");
+                    println!("  Command: {}", cmd);
+                }
+                println!("Do you trust this version? [y/N]");
+                let answer = read_answer()?.trim().eq_ignore_ascii_case("y");
+                if !answer && has_hooks {
+                    println!("❌ Hooks disabled for this session.");
+                }
+                trusted = Some(answer);
+            }
 
             // Trigger Pre-run hook (environment setup)
             let mut hooks_trusted = false;
@@ -164,7 +789,23 @@ async fn main() -> anyhow::Result<()> {
             if let Some(h) = hooks.as_ref()
                 && h.has_any()
             {
-                if !cli.headless {
+                if *read_only {
+                    println!("👁 Read-only mode: the hooks of this runbook are not run.");
+                } else if trusted.is_some() {
+                    if let Trust::Trusted(previous) = &trust {
+                        println!(
+                            "✅ Hooks trusted on {} (runbook unchanged).",
+                            previous.date()
+                        );
+                    }
+                    if trusted == Some(true) {
+                        hooks_trusted = true;
+                        core::ecosystem::hooks::trigger_hook(
+                            &h.pre_run,
+                            &std::collections::HashMap::new(),
+                        );
+                    }
+                } else if !cli.headless {
                     println!("\n⚠️  SECURITY WARNING ⚠️");
                     println!(
                         "This runbook contains automation hooks (pre_run, post_run, on_failure, etc.)."
@@ -176,9 +817,9 @@ async fn main() -> anyhow::Result<()> {
                     println!("Compass cannot verify if these commands are safe.");
                     println!("Do you trust this runbook? [y/N]");
 
-                    let mut input = String::new();
-                    std::io::stdin().read_line(&mut input)?;
-                    if input.trim().eq_ignore_ascii_case("y") {
+                    let input = read_answer()?;
+                    trusted = Some(input.trim().eq_ignore_ascii_case("y"));
+                    if trusted == Some(true) {
                         hooks_trusted = true;
                         // Trigger pre_run immediately if trusted
                         core::ecosystem::hooks::trigger_hook(
@@ -199,14 +840,34 @@ async fn main() -> anyhow::Result<()> {
                 }
             }
 
+            // Remember the trusted version, or let a confirmed dangerous command record it
+            let mut untrusted_content = None;
+            if let Some(location) = &trust_location {
+                match trusted {
+                    Some(true) if !matches!(trust, Trust::Trusted(_)) => {
+                        if let Err(e) = trust::record(location, &content) {
+                            eprintln!("Could not record the runbook as trusted: {e:#}");
+                        }
+                    }
+                    None => untrusted_content = Some(content.clone()),
+                    _ => {}
+                }
+            }
+
             // Headless Mode Check
             if cli.headless {
                 eprintln!("Running in HEADLESS mode (JSON-RPC)...");
                 core::ecosystem::rpc::start_headless_server(
                     steps,
                     path,
-                    cli.sandbox,
-                    cli.image.clone(),
+                    core::ecosystem::rpc::HeadlessOptions {
+                        sandbox,
+                        image: image.clone(),
+                        manifest: hooks.clone(),
+                        step_level: cli.step_level,
+                        remote: is_remote,
+                    },
+                    &settings,
                 )
                 .await?;
                 return Ok(());
@@ -229,8 +890,7 @@ async fn main() -> anyhow::Result<()> {
                 println!("    (Share this link securely. It acts as both key and certificate.)\n");
 
                 println!("Press ENTER to launch the Host Interface...");
-                let mut input = String::new();
-                std::io::stdin().read_line(&mut input)?;
+                read_answer()?;
 
                 let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
 
@@ -243,12 +903,15 @@ async fn main() -> anyhow::Result<()> {
                     }
                 });
 
-                collab_session = Some(core::collab::session::CollabSession::new(
-                    true, // is_host
-                    Some(secure_link),
-                    Some(tx), // App writes to this
-                    None,     // Host doesn't read from guest yet
-                ));
+                collab_session = Some(
+                    core::collab::session::CollabSession::new(
+                        true, // is_host
+                        Some(secure_link),
+                        Some(tx), // App writes to this
+                        None,     // Host doesn't read from guest yet
+                    )
+                    .with_placeholder_sync(*share_placeholders),
+                );
             } else {
                 println!("Launching UI for {} steps...", steps.len());
             }
@@ -256,18 +919,39 @@ async fn main() -> anyhow::Result<()> {
             ui::run_tui(
                 steps,
                 path,
-                is_remote,
-                cli.sandbox,
-                cli.image,
-                collab_session,
-                hooks,
-                hooks_trusted,
+                ui::TuiOptions {
+                    is_remote,
+                    sandbox,
+                    image,
+                    collab_session,
+                    manifest: hooks,
+                    hooks_trusted,
+                    untrusted_content,
+                    read_only: *read_only,
+                    focus: *focus,
+                    linear,
+                    context: saved_context,
+                    env_file,
+                    resume: *resume,
+                    tutorial: false,
+                },
             )?;
         }
-        Commands::Check { file } => {
-            let (content, _, _) = load_readme(file).await?;
-            let (steps, _) = core::parser::parse_readme(&content);
-            let result = core::executor::check_dependencies(&steps);
+        Commands::Check { file, json } => {
+            let (content, _, _) = load_readme(file, &settings).await?;
+            let (steps, manifest) = core::parser::parse_readme_at(&content, cli.step_level);
+            let mut result = core::executor::check_dependencies(&steps);
+            if let Some(manifest) = &manifest {
+                result.add(&manifest.requires);
+            }
+
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&result)?);
+                if !result.missing.is_empty() {
+                    std::process::exit(1);
+                }
+                return Ok(());
+            }
 
             if !result.present.is_empty() {
                 println!("\n✅ Present:");
@@ -281,10 +965,156 @@ async fn main() -> anyhow::Result<()> {
             } else {
                 println!("\n❌ Missing:");
                 for cmd in &result.missing {
-                    println!("   - {cmd}");
+                    println!(
+                        "   - {cmd}  ({})",
+                        core::executor::checker::install_hint(cmd)
+                    );
                 }
                 println!("\nSome dependencies are missing. Please install them before proceeding.");
             }
+
+            println!(
+                "\nSummary: {} present, {} missing.",
+                result.present.len(),
+                result.missing.len()
+            );
+            if !result.missing.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        Commands::Verify {
+            file,
+            run,
+            strict,
+            json,
+        } => {
+            if sandbox {
+                core::infrastructure::docker::ensure_docker_available()?;
+            }
+            let (content, path, _) = load_readme(file, &settings).await?;
+            let (steps, manifest) = core::parser::parse_readme_at(&content, cli.step_level);
+            let image = runbook_image(&cli, manifest.as_ref(), image.clone());
+            let base_dir = if path.is_file() {
+                path.parent().unwrap_or(&path).to_path_buf()
+            } else {
+                std::env::current_dir()?
+            };
+            let options = core::analysis::verify::VerifyOptions {
+                run: *run,
+                sandbox,
+                image: image.clone(),
+                strict: *strict,
+            };
+            let report = core::analysis::verify::verify(&steps, &base_dir, &options)?;
+
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                report.print(file);
+            }
+            if !report.passed {
+                std::process::exit(1);
+            }
+        }
+        Commands::Validate { file, strict, json } => {
+            let (content, _, _) = load_readme(file, &settings).await?;
+            let (steps, _) = core::parser::parse_readme_at(&content, cli.step_level);
+            let report = core::analysis::validate::validate(&content, &steps, *strict);
+
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                report.print(file);
+            }
+            if !report.passed {
+                std::process::exit(1);
+            }
+        }
+        Commands::Run {
+            file,
+            from,
+            to,
+            skip,
+            json,
+            rollback,
+            context,
+            dry_run,
+            env_file,
+            fetch_secrets,
+        } => {
+            if sandbox && !dry_run {
+                core::infrastructure::docker::ensure_docker_available()?;
+            }
+            let (content, path, is_remote) = load_readme(file, &settings).await?;
+            let (steps, manifest) = core::parser::parse_readme_at(&content, cli.step_level);
+            warn_requirements(manifest.as_ref());
+            let image = runbook_image(&cli, manifest.as_ref(), image.clone());
+            let (mut executor, placeholders) = headless_executor(
+                &path,
+                sandbox,
+                &image,
+                &settings,
+                context.as_deref(),
+                env_file.as_deref(),
+            )?;
+            executor.context.remote = is_remote;
+
+            let options = core::executor::runner::RunOptions {
+                from: *from,
+                to: *to,
+                skip: skip.clone(),
+                json: *json,
+                rollback: *rollback,
+                placeholders,
+                specs: manifest.map(|m| m.placeholders).unwrap_or_default(),
+                dry_run: *dry_run,
+                keep_going: false,
+                secrets: fetch_secrets.then(Default::default),
+            };
+            if !core::executor::runner::run(&steps, &mut executor, &options)? {
+                std::process::exit(1);
+            }
+        }
+        Commands::Test {
+            file,
+            json,
+            fail_fast,
+            context,
+            env_file,
+            fetch_secrets,
+        } => {
+            if sandbox {
+                core::infrastructure::docker::ensure_docker_available()?;
+            }
+            let (content, path, is_remote) = load_readme(file, &settings).await?;
+            let (steps, manifest) = core::parser::parse_readme_at(&content, cli.step_level);
+            warn_requirements(manifest.as_ref());
+            let image = runbook_image(&cli, manifest.as_ref(), image.clone());
+            let (mut executor, placeholders) = headless_executor(
+                &path,
+                sandbox,
+                &image,
+                &settings,
+                context.as_deref(),
+                env_file.as_deref(),
+            )?;
+            executor.context.remote = is_remote;
+
+            let options = core::executor::runner::RunOptions {
+                from: None,
+                to: None,
+                skip: Vec::new(),
+                json: *json,
+                rollback: false,
+                placeholders,
+                specs: manifest.map(|m| m.placeholders).unwrap_or_default(),
+                dry_run: false,
+                keep_going: !fail_fast,
+                secrets: fetch_secrets.then(Default::default),
+            };
+            if !core::executor::runner::run(&steps, &mut executor, &options)? {
+                std::process::exit(1);
+            }
         }
         Commands::Join { url } => {
             // Fix URL format if needed
@@ -335,18 +1165,114 @@ async fn main() -> anyhow::Result<()> {
             ));
 
             println!("Joining session with {} steps...", steps.len());
+            // Hooks are not trusted nor present locally in guest mode
             ui::run_tui(
                 steps,
                 path,
+                ui::TuiOptions {
+                    is_remote: true,
+                    collab_session,
+                    linear,
+                    ..Default::default()
+                },
+            )?;
+        }
+        Commands::Demo { name, list } => {
+            use core::ecosystem::demo;
+
+            if *list {
+                println!("🧭 Demo runbooks:");
+                for demo in demo::DEMOS {
+                    println!("  {:<8} {}", demo.name, demo.summary);
+                }
+                println!("\nOpen one with `compass demo <NAME>`.");
+                return Ok(());
+            }
+            let demo = demo::find(name.as_deref().unwrap_or(demo::DEMOS[0].name))?;
+            open_demo(demo, sandbox, image, linear, false)?;
+        }
+        Commands::Tutorial => {
+            open_demo(
+                &core::ecosystem::demo::TUTORIAL,
+                sandbox,
+                image,
+                linear,
                 true,
-                false,
-                "ubuntu:latest".to_string(),
-                collab_session,
-                None,
-                false, // Hooks not trusted/present in guest mode locally
             )?;
         }
+        Commands::Bench {
+            steps,
+            lines,
+            iterations,
+            json,
+            baseline,
+            tolerance,
+        } => {
+            use core::infrastructure::bench;
+            let options = bench::BenchOptions {
+                steps: *steps,
+                lines: *lines,
+                iterations: *iterations,
+            };
+            let results = bench::run(&options);
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&results)?);
+            } else {
+                bench::print(&results);
+            }
+            if let Some(path) = baseline {
+                let regressions =
+                    bench::regressions(&results, &bench::load_baseline(path)?, *tolerance);
+                if !regressions.is_empty() {
+                    for regression in &regressions {
+                        eprintln!("❌ Slower than the baseline: {regression}");
+                    }
+                    std::process::exit(1);
+                }
+                if !*json {
+                    println!("✅ No regression against {}", path.display());
+                }
+            }
+        }
+        Commands::Recent => {
+            let recent = core::infrastructure::recent::load()?;
+            if recent.is_empty() {
+                println!("No recent runbooks.");
+            }
+            for (i, entry) in recent.iter().enumerate() {
+                println!("{}. {entry}", i + 1);
+            }
+        }
+        Commands::Snippet { action } => match action {
+            SnippetAction::Add {
+                name,
+                command,
+                language,
+            } => {
+                core::infrastructure::snippets::add(name, command, language.clone())?;
+                println!("✅ Saved snippet '{}'.", name.trim());
+            }
+            SnippetAction::List => {
+                let snippets = core::infrastructure::snippets::load()?;
+                if snippets.is_empty() {
+                    println!("No snippets. Add one with `compass snippet add <NAME> <COMMAND>`.");
+                }
+                for snippet in &snippets {
+                    println!("{snippet}");
+                }
+            }
+            SnippetAction::Remove { name } => {
+                core::infrastructure::snippets::remove(name)?;
+                println!("🗑️  Removed snippet '{name}'.");
+            }
+        },
+        Commands::Setup => {
+            core::infrastructure::wizard::run(&settings)?;
+        }
         Commands::Search { query } => {
+            if !settings.registry_enabled {
+                anyhow::bail!("The registry is disabled. Run `compass setup` to enable it.");
+            }
             println!("🔍 Searching Compass Hub for '{}'...", query);
             let results = core::ecosystem::hub::search_remote(query).await?;
             if results.is_empty() {
@@ -376,6 +1302,9 @@ async fn main() -> anyhow::Result<()> {
                 println!("Downloading from URL...");
                 (name.clone(), "runbook.md".to_string())
             } else {
+                if !settings.registry_enabled {
+                    anyhow::bail!("The registry is disabled. Run `compass setup` to enable it.");
+                }
                 println!("Searching registry for '{}'...", name);
                 if let Some(runbook) = core::ecosystem::hub::resolve_runbook(name).await? {
                     (runbook.url, format!("{}.md", runbook.name))
@@ -385,11 +1314,101 @@ async fn main() -> anyhow::Result<()> {
             };
 
             println!("Fetching content from {}...", url);
-            let content = core::fetcher::fetch_remote_content(&url)?;
+            let content = fetch(&url, &settings).await?;
             let filename = destination.as_deref().unwrap_or(&default_name);
             std::fs::write(filename, content)?;
             println!("✅ Successfully cloned into '{}'", filename);
         }
+        Commands::ExportScript {
+            file,
+            output,
+            format,
+        } => {
+            let exporter = core::export::runbook::exporter(format).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unknown format '{format}' (expected {})",
+                    core::export::runbook::exporter_names().join(", ")
+                )
+            })?;
+            let (content, path, _) = load_readme(file, &settings).await?;
+            let (steps, hooks) = core::parser::parse_readme_at(&content, cli.step_level);
+            let source = path
+                .file_name()
+                .map_or_else(|| file.clone(), |n| n.to_string_lossy().to_string());
+            let script = exporter.render(&steps, hooks.as_ref(), &source)?;
+
+            if let Some(output) = output {
+                fs::write(output, script)?;
+                #[cfg(unix)]
+                if exporter.executable() {
+                    use std::os::unix::fs::PermissionsExt;
+                    fs::set_permissions(output, fs::Permissions::from_mode(0o755))?;
+                }
+                eprintln!("✅ Script written to '{}'", output.display());
+            } else {
+                print!("{script}");
+            }
+        }
+        Commands::Bundle {
+            file,
+            format,
+            all,
+            output,
+        } => {
+            use core::ecosystem::bundle::PackageManager;
+
+            let (content, path, _) = load_readme(file, &settings).await?;
+            let (steps, _) = core::parser::parse_readme_at(&content, cli.step_level);
+            let source = path
+                .file_name()
+                .map_or_else(|| file.clone(), |n| n.to_string_lossy().to_string());
+            let manager = match format {
+                Some(BundleFormat::Brew) => PackageManager::Brew,
+                Some(BundleFormat::Apt) => PackageManager::Apt,
+                Some(BundleFormat::Winget) => PackageManager::Winget,
+                Some(BundleFormat::Nix) => PackageManager::NixFlake,
+                Some(BundleFormat::ShellNix) => PackageManager::NixShell,
+                None if cfg!(target_os = "macos") => PackageManager::Brew,
+                None if cfg!(target_os = "windows") => PackageManager::Winget,
+                None => PackageManager::Apt,
+            };
+            let bundle = core::ecosystem::bundle::render(&steps, manager, *all, &source);
+
+            if !bundle.skipped.is_empty() {
+                eprintln!(
+                    "⚠️  No package found for: {} (install them manually)",
+                    bundle.skipped.join(", ")
+                );
+            }
+            if let Some(output) = output {
+                fs::write(output, bundle.content)?;
+                eprintln!("✅ Bundle written to '{}'", output.display());
+            } else {
+                print!("{}", bundle.content);
+            }
+        }
+        Commands::Import {
+            script,
+            from_history,
+            output,
+        } => {
+            let runbook = match (script, from_history) {
+                (Some(script), _) => {
+                    let content = fs::read_to_string(script)
+                        .with_context(|| format!("Failed to read script: {}", script.display()))?;
+                    core::ecosystem::importer::from_script(&content, script)
+                }
+                (None, Some(count)) => core::ecosystem::importer::from_history(*count)?,
+                (None, None) => unreachable!("clap requires a script or --from-history"),
+            };
+
+            if let Some(output) = output {
+                fs::write(output, runbook)?;
+                eprintln!("✅ Runbook skeleton written to '{}'", output.display());
+            } else {
+                print!("{runbook}");
+            }
+        }
     }
 
     Ok(())
@@ -12,17 +12,32 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::core::analysis::edits::{DiffLine, FileBackup};
 use crate::core::analysis::recovery::RecoveryRecommendation;
 use crate::core::collab::session::CollabSession;
-use crate::core::ecosystem::hooks::HookConfig;
 use crate::core::executor::ExecutionManager;
-use crate::core::infrastructure::config::ConfigManager;
-use crate::core::models::{Condition, Step};
-use crate::ui::state::Mode;
+use crate::core::executor::checker::CheckResult;
+use crate::core::executor::cloud::{CloudCall, CloudCli};
+use crate::core::executor::restart::RestartNotice;
+use crate::core::infrastructure::config::{ConfigManager, GlobalConfig};
+use crate::core::infrastructure::docker;
+use crate::core::infrastructure::monitor::{ResourceMonitor, ResourceSample, format_bytes};
+use crate::core::infrastructure::session::SavedSession;
+use crate::core::infrastructure::snippets::Snippet;
+use crate::core::manifest::{PlaceholderSpec, RunbookManifest};
+use crate::core::models::{Step, StepStatus};
+use crate::core::outline;
+use crate::core::secrets::{FetchedSecrets, SecretCache};
 use crate::ui::state::modal::ModalState;
+use crate::ui::state::palette::PaletteState;
+use crate::ui::state::{Checkpoint, ContextChange, Mode};
+use crate::ui::widgets::step_list::StepListCache;
 
 use ratatui::widgets::ListState;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver};
 
 /// The current version of Compass (synchronized with Cargo.toml).
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -48,6 +63,10 @@ pub struct App {
     pub safety_pattern: Option<String>,
     /// The current recovery recommendation (if any).
     pub recovery_suggestion: Option<RecoveryRecommendation>,
+    /// Global user preferences.
+    pub settings: GlobalConfig,
+    /// The latest dependency check of the runbook (if any).
+    pub dependency_report: Option<CheckResult>,
     /// Scroll offset for the details panel.
     pub details_scroll: u16,
     /// Total height of the details content (wrapped).
@@ -66,10 +85,103 @@ pub struct App {
     pub is_remote: bool,
     /// Active collaboration session (if any).
     pub collab: Option<CollabSession>,
-    /// Active hooks configuration.
-    pub hooks: Option<HookConfig>,
+    /// Frontmatter of the runbook: hooks and placeholder declarations.
+    pub manifest: Option<RunbookManifest>,
     /// Whether the user has explicitly trusted the execution of hooks.
     pub hooks_trusted: bool,
+    /// Opened as a viewer (`--read-only`): no step, hook or fix runs, and
+    /// nothing is exported to the working directory.
+    pub read_only: bool,
+    /// Content of the runbook to record as trusted once the user allows one of
+    /// its dangerous commands; `None` when this version is trusted already.
+    pub untrusted_content: Option<String>,
+    /// Indices of steps the user force-enabled despite an unmet condition.
+    pub forced_steps: HashSet<usize>,
+    /// Steps run on the other side of the session's sandbox setting: in Docker
+    /// when the session runs on the host, on the host otherwise.
+    pub sandbox_flipped: HashSet<usize>,
+    /// Pending check that Docker runs, started when a step was moved into the sandbox.
+    pub docker_check: Option<Receiver<bool>>,
+    /// Sampling of the host's resources, while steps run.
+    pub monitor: Option<ResourceMonitor>,
+    /// Latest sample of the host's resources, shown in the status bar.
+    pub resources: Option<ResourceSample>,
+    /// Whether the running steps were warned that the disk is almost full.
+    pub low_disk_warned: bool,
+    /// Step whose output asked for a reboot or a new login, blocking the steps
+    /// after it until the user confirms it is done.
+    pub restart_notice: Option<(usize, RestartNotice)>,
+    /// Steps the auto-pilot will run next, in order.
+    pub autopilot: VecDeque<usize>,
+    /// Step the auto-pilot started last and waits for.
+    pub autopilot_current: Option<usize>,
+    /// Steps of a parallel group still to start, in order.
+    pub group_queue: VecDeque<usize>,
+    /// Step the user chose to run although its prerequisites have not succeeded.
+    pub prerequisites_override: Option<usize>,
+    /// Step whose `terraform plan` the user reviewed, to apply on its next run.
+    pub plan_approved: Option<usize>,
+    /// Step whose file edits the user reviewed, to write on its next run.
+    pub diff_approved: Option<usize>,
+    /// Step whose Kubernetes context the user confirmed for its next run.
+    pub cluster_confirmed: Option<usize>,
+    /// Step whose cloud account the user confirmed for its next run.
+    pub cloud_confirmed: Option<usize>,
+    /// Step whose policy confirmation the user gave for its next run.
+    pub policy_confirmed: Option<usize>,
+    /// Probe of the cloud account of the step about to run, until it answers.
+    pub cloud_probe: Option<(CloudCli, Receiver<Result<String, String>>)>,
+    /// Secrets read from secret managers during the session, by source.
+    pub secret_cache: Arc<SecretCache>,
+    /// Placeholders of the step about to run to read from a secret manager
+    /// (name, source), waiting for the user to agree.
+    pub secret_requests: Vec<(String, String)>,
+    /// Values of `secret_requests` being read, by placeholder.
+    pub secret_fetch: Option<Receiver<FetchedSecrets>>,
+    /// Placeholders whose secret could not be read, typed by hand instead.
+    pub secrets_unread: HashSet<String>,
+    /// The past run of the selected step being viewed (`None` for the latest output).
+    pub attempt_view: Option<usize>,
+    /// Context mutations made by steps, oldest first, used to revert them.
+    pub context_history: Vec<ContextChange>,
+    /// Session state saved before each run, oldest first, used to rewind it.
+    pub checkpoints: Vec<Checkpoint>,
+    /// Whether only the current step is shown, without the list and status bar.
+    pub focus_mode: bool,
+    /// Steps whose nested steps are hidden in the list.
+    pub collapsed: HashSet<usize>,
+    /// Whether the details panel lists the environment changes of the step.
+    pub show_env_changes: bool,
+    /// Rows of the step list built in earlier frames.
+    pub step_list_cache: StepListCache,
+    /// Diff of the file changes awaiting confirmation.
+    pub edit_preview: Vec<DiffLine>,
+    /// Scroll position in the diff and dry run previews.
+    pub diff_scroll: u16,
+    /// Whether the next execution only previews the command (dry run).
+    pub dry_run: bool,
+    /// The command of the selected step, as a dry run shows it.
+    pub dry_run_preview: Option<String>,
+    /// Files as they were before the last run of each step, to roll edits back.
+    pub file_backups: HashMap<usize, Vec<FileBackup>>,
+    /// Steps whose `rollback` command is running.
+    pub rolling_back: HashSet<usize>,
+    /// Steps running the `terraform plan` of their `terraform apply`, with
+    /// their status before it.
+    pub planning: HashMap<usize, StepStatus>,
+    /// Number of times steps were added, moved or rewound in the session, for
+    /// views that remember something per step index.
+    pub steps_edited: usize,
+    /// Inline command of the selected step chosen to run instead of its code blocks.
+    pub inline_choice: Option<usize>,
+    /// The user's snippet library, loaded when the snippet palette opens.
+    pub snippets: Vec<Snippet>,
+    /// Query and highlighted entry of the open palette.
+    pub palette: PaletteState,
+    /// File the session is saved to after each run and on exit, to resume it.
+    pub session_file: Option<PathBuf>,
+    /// Current lesson of the guided tour, when opened with `compass tutorial`.
+    pub tutorial: Option<usize>,
 }
 
 impl App {
@@ -85,41 +197,28 @@ impl App {
     ///
     /// A new `App` instance ready for rendering.
     #[must_use]
-    pub fn new(mut steps: Vec<Step>, readme_path: PathBuf, is_remote: bool) -> Self {
+    pub fn new(steps: Vec<Step>, readme_path: PathBuf, is_remote: bool) -> Self {
         let mut list_state = ListState::default();
-        if !steps.is_empty() {
-            list_state.select(Some(0));
-        }
-
-        // Filter steps based on conditions
-        steps.retain(|step| {
-            if let Some(cond) = &step.condition {
-                match cond {
-                    Condition::Os(os) => std::env::consts::OS == os,
-                    Condition::EnvVarExists(v) => std::env::var(v).is_ok(),
-                    Condition::FileExists(p) => std::path::Path::new(p).exists(),
-                }
-            } else {
-                true
-            }
-        });
-
-        if steps.is_empty() {
-            list_state.select(None);
-        }
+        list_state.select(outline::visible(&steps, &HashSet::new()).first().copied());
 
         // Initialize configuration manager
         let config_manager = ConfigManager::new().ok();
+        let settings = GlobalConfig::load().unwrap_or_default();
+        let mut execution_manager = ExecutionManager::new();
+        settings.apply_shell(&mut execution_manager.executor.context);
+        execution_manager.executor.context.remote = is_remote;
 
         Self {
             steps,
             list_state,
             should_quit: false,
-            execution_manager: ExecutionManager::new(),
+            execution_manager,
             mode: Mode::Normal,
             modal: ModalState::new(),
             safety_pattern: None,
             recovery_suggestion: None,
+            settings,
+            dependency_report: None,
             details_scroll: 0,
             content_height: 0,
             viewport_height: 0,
@@ -129,18 +228,71 @@ impl App {
             help_scroll: 0,
             is_remote,
             collab: None,
-            hooks: None,
+            manifest: None,
             hooks_trusted: false,
+            read_only: false,
+            untrusted_content: None,
+            forced_steps: HashSet::new(),
+            sandbox_flipped: HashSet::new(),
+            docker_check: None,
+            monitor: None,
+            resources: None,
+            low_disk_warned: false,
+            restart_notice: None,
+            autopilot: VecDeque::new(),
+            autopilot_current: None,
+            group_queue: VecDeque::new(),
+            prerequisites_override: None,
+            plan_approved: None,
+            diff_approved: None,
+            cluster_confirmed: None,
+            cloud_confirmed: None,
+            policy_confirmed: None,
+            cloud_probe: None,
+            secret_cache: Arc::default(),
+            secret_requests: Vec::new(),
+            secret_fetch: None,
+            secrets_unread: HashSet::new(),
+            attempt_view: None,
+            context_history: Vec::new(),
+            checkpoints: Vec::new(),
+            focus_mode: false,
+            collapsed: HashSet::new(),
+            show_env_changes: false,
+            step_list_cache: StepListCache::default(),
+            edit_preview: Vec::new(),
+            diff_scroll: 0,
+            dry_run: false,
+            dry_run_preview: None,
+            file_backups: HashMap::new(),
+            rolling_back: HashSet::new(),
+            planning: HashMap::new(),
+            steps_edited: 0,
+            inline_choice: None,
+            snippets: Vec::new(),
+            palette: PaletteState::default(),
+            session_file: None,
+            tutorial: None,
         }
     }
 
-    /// Sets the hooks configuration.
-    pub fn with_hooks(mut self, hooks: Option<HookConfig>, trusted: bool) -> Self {
-        self.hooks = hooks;
+    /// Sets the runbook manifest and whether its hooks are trusted.
+    pub fn with_manifest(mut self, manifest: Option<RunbookManifest>, trusted: bool) -> Self {
+        self.manifest = manifest;
         self.hooks_trusted = trusted;
         self
     }
 
+    /// Title of the runbook from its frontmatter, with its author.
+    pub fn runbook_title(&self) -> Option<String> {
+        let manifest = self.manifest.as_ref()?;
+        let title = manifest.title.as_deref()?;
+        Some(match &manifest.author {
+            Some(author) => format!("{title} — {author}"),
+            None => title.to_string(),
+        })
+    }
+
     /// Set sandbox mode
     pub fn with_sandbox(mut self, enabled: bool, image: String) -> Self {
         self.execution_manager.executor.context.sandbox_enabled = enabled;
@@ -168,8 +320,16 @@ impl App {
     ///
     /// This persists the user's input so it can be restored on next launch.
     pub fn save_config(&mut self) {
+        let secrets = self.secret_placeholders();
+        let typed: HashMap<String, String> = self
+            .modal
+            .variable_store
+            .iter()
+            .filter(|(name, _)| !self.modal.imported.contains(*name))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
         if let Some(ref mut config) = self.config_manager {
-            config.update_placeholders(&self.modal.variable_store);
+            config.update_placeholders(&typed, &secrets);
             let _ = config.save(); // Ignore errors silently for now
         }
     }
@@ -179,18 +339,24 @@ impl App {
         if self.mode != Mode::Normal {
             return;
         }
-        let i = match self.list_state.selected() {
-            Some(i) => {
-                if i >= self.steps.len() - 1 {
-                    0
-                } else {
-                    i + 1
-                }
-            }
-            None => 0,
+        // Steps nested under a collapsed one are passed over
+        let visible = outline::visible(&self.steps, &self.collapsed);
+        let Some(&first) = visible.first() else {
+            return;
         };
+        let i = self
+            .list_state
+            .selected()
+            .and_then(|i| visible.iter().copied().find(|&v| v > i))
+            .unwrap_or(first);
+        self.select(i);
+    }
+
+    /// Selects a step, showing its latest output from the top.
+    fn select(&mut self, i: usize) {
         self.list_state.select(Some(i));
         self.details_scroll = 0;
+        self.attempt_view = None;
 
         // Emit collab event if host
         if let Some(session) = &self.collab
@@ -201,38 +367,513 @@ impl App {
         }
     }
 
+    /// Whether steps can be added or moved: not while one runs, since its
+    /// index is in flight, nor in a live session, whose guests follow the
+    /// host's indexes.
+    #[must_use]
+    pub fn can_edit_steps(&self) -> bool {
+        self.mode == Mode::Normal
+            && self.collab.is_none()
+            && !self.steps.iter().any(|s| s.status == StepStatus::Running)
+    }
+
+    /// Indices of the snippets matching the palette query, best match first.
+    #[must_use]
+    pub fn snippet_matches(&self) -> Vec<usize> {
+        let texts: Vec<String> = self.snippets.iter().map(ToString::to_string).collect();
+        self.palette.matches(texts.iter().map(String::as_str))
+    }
+
+    /// Inserts a session-only step after the selected one (after the steps
+    /// hidden under it if it is collapsed) and selects it.
+    pub fn insert_step(&mut self, mut step: Step) {
+        if !self.can_edit_steps() {
+            return;
+        }
+        let at = self.list_state.selected().map_or(self.steps.len(), |i| {
+            if self.collapsed.contains(&i) {
+                outline::subtree_end(&self.steps, i)
+            } else {
+                i + 1
+            }
+        });
+        step.session_only = true;
+        self.steps.insert(at, step);
+        self.remap_steps(|i| if i >= at { i + 1 } else { i });
+        self.list_state.select(Some(at));
+    }
+
+    /// Copies the selected step after it, as a pending session-only step.
+    pub fn duplicate_selected(&mut self) {
+        let Some(step) = self.list_state.selected().and_then(|i| self.steps.get(i)) else {
+            return;
+        };
+        let copy = Step {
+            title: format!("{} (copy)", step.title.trim()),
+            status: StepStatus::Pending,
+            output: String::new(),
+            stdout: String::new(),
+            stderr: String::new(),
+            history: Vec::new(),
+            ..step.clone()
+        };
+        self.insert_step(copy);
+    }
+
+    /// Swaps the selected step with the previous one (`down` false) or the
+    /// next one, keeping it selected.
+    pub fn move_selected(&mut self, down: bool) {
+        if !self.can_edit_steps() {
+            return;
+        }
+        let Some(i) = self.list_state.selected() else {
+            return;
+        };
+        let Some(j) = (if down {
+            i.checked_add(1)
+        } else {
+            i.checked_sub(1)
+        })
+        .filter(|&j| j < self.steps.len()) else {
+            return;
+        };
+        self.steps.swap(i, j);
+        self.remap_steps(|k| {
+            if k == i {
+                j
+            } else if k == j {
+                i
+            } else {
+                k
+            }
+        });
+        self.list_state.select(Some(j));
+    }
+
+    /// Renumbers the state kept per step after steps were inserted or moved;
+    /// `new_index` gives the new index of each old one.
+    fn remap_steps(&mut self, new_index: impl Fn(usize) -> usize) {
+        self.forced_steps = self.forced_steps.drain().map(&new_index).collect();
+        self.sandbox_flipped = self.sandbox_flipped.drain().map(&new_index).collect();
+        self.collapsed = self.collapsed.drain().map(&new_index).collect();
+        for i in &mut self.autopilot {
+            *i = new_index(*i);
+        }
+        self.autopilot_current = self.autopilot_current.map(&new_index);
+        if let Some((i, _)) = &mut self.restart_notice {
+            *i = new_index(*i);
+        }
+        for i in &mut self.group_queue {
+            *i = new_index(*i);
+        }
+        self.file_backups = self
+            .file_backups
+            .drain()
+            .map(|(i, backups)| (new_index(i), backups))
+            .collect();
+        for change in &mut self.context_history {
+            change.step = new_index(change.step);
+        }
+        for checkpoint in &mut self.checkpoints {
+            checkpoint.step = new_index(checkpoint.step);
+            let mut statuses = vec![StepStatus::Pending; self.steps.len()];
+            for (i, status) in checkpoint.statuses.drain(..).enumerate() {
+                statuses[new_index(i)] = status;
+            }
+            checkpoint.statuses = statuses;
+        }
+        self.attempt_view = None;
+        self.inline_choice = None;
+        self.prerequisites_override = None;
+        self.plan_approved = None;
+        self.diff_approved = None;
+        self.cluster_confirmed = None;
+        self.cloud_confirmed = None;
+        self.policy_confirmed = None;
+        self.details_scroll = 0;
+        self.steps_edited += 1;
+    }
+
+    /// Whether the auto-pilot is running steps.
+    #[must_use]
+    pub fn autopilot_active(&self) -> bool {
+        self.autopilot_current.is_some() || !self.autopilot.is_empty()
+    }
+
     /// Does the app have sandbox enabled?
     pub fn is_sandbox(&self) -> bool {
         self.execution_manager.executor.context.sandbox_enabled
     }
 
+    /// Whether the step runs in the Docker sandbox.
+    #[must_use]
+    pub fn is_sandboxed(&self, index: usize) -> bool {
+        self.is_sandbox() != self.sandbox_flipped.contains(&index)
+    }
+
+    /// Moves the selected step into the sandbox or out of it for its next runs.
+    /// Moving it in checks in the background that Docker runs.
+    pub fn toggle_sandbox_selected(&mut self) {
+        if self.mode != Mode::Normal {
+            return;
+        }
+        let Some(i) = self
+            .list_state
+            .selected()
+            .filter(|&i| self.steps.get(i).is_some_and(Step::is_executable))
+        else {
+            return;
+        };
+        if !self.sandbox_flipped.remove(&i) {
+            self.sandbox_flipped.insert(i);
+        }
+        if self.is_sandboxed(i) && self.docker_check.is_none() {
+            let (tx, rx) = mpsc::channel();
+            std::thread::spawn(move || {
+                let _ = tx.send(docker::is_running());
+            });
+            self.docker_check = Some(rx);
+        }
+    }
+
+    /// Applies the result of the Docker check once it is known: without
+    /// Docker, the steps moved into the sandbox go back to the host.
+    pub fn poll_docker_check(&mut self) {
+        let Some(rx) = &self.docker_check else {
+            return;
+        };
+        let running = match rx.try_recv() {
+            Ok(running) => running,
+            Err(mpsc::TryRecvError::Empty) => return,
+            Err(mpsc::TryRecvError::Disconnected) => false,
+        };
+        self.docker_check = None;
+        if running {
+            return;
+        }
+        for i in std::mem::take(&mut self.sandbox_flipped) {
+            if let Some(step) = self.steps.get_mut(i) {
+                step.output.push_str(
+                    "\n\n---\n📦 Docker is not running: this step stays on this machine.",
+                );
+            }
+        }
+    }
+
+    /// Probes the cloud account the selected step would use, in the
+    /// background, and asks for confirmation once it is known.
+    pub fn start_cloud_probe(&mut self, call: CloudCall) {
+        let context = &self.execution_manager.executor.context;
+        let (env, dir) = (context.env_vars.clone(), context.current_dir.clone());
+        let cli = call.cli;
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(call.probe(&env, &dir));
+        });
+        self.cloud_probe = Some((cli, rx));
+        self.safety_pattern = Some(format!(
+            "Checking the {} account this step would use...",
+            cli.provider()
+        ));
+        self.mode = Mode::CloudCheck;
+    }
+
+    /// Shows the account found by the cloud probe once it answered.
+    pub fn poll_cloud_probe(&mut self) {
+        let Some((cli, rx)) = &self.cloud_probe else {
+            return;
+        };
+        let message = match rx.try_recv() {
+            Ok(Ok(account)) => format!("This step runs against {account}."),
+            Ok(Err(reason)) => format!(
+                "⚠️ {reason}.\nThe step will likely fail: check the {} credentials first.",
+                cli.provider()
+            ),
+            Err(mpsc::TryRecvError::Empty) => return,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                format!("The {} account could not be checked.", cli.provider())
+            }
+        };
+        self.cloud_probe = None;
+        if self.mode == Mode::CloudCheck {
+            self.safety_pattern = Some(message);
+            self.mode = Mode::CloudAlert;
+        }
+    }
+
+    /// Samples the host while steps run, and warns the running steps once
+    /// the free disk space drops below the threshold of the settings.
+    pub fn poll_resources(&mut self) {
+        let running: Vec<usize> = (0..self.steps.len())
+            .filter(|&i| self.steps[i].status == StepStatus::Running)
+            .collect();
+        if running.is_empty() {
+            self.monitor = None;
+            self.resources = None;
+            self.low_disk_warned = false;
+            return;
+        }
+        let monitor = self.monitor.get_or_insert_with(|| {
+            ResourceMonitor::start(self.execution_manager.executor.context.current_dir.clone())
+        });
+        self.resources = monitor.latest();
+
+        let threshold = self.settings.min_free_disk_mb.saturating_mul(1024 * 1024);
+        let Some(free) = self
+            .resources
+            .and_then(|sample| sample.disk_free)
+            .filter(|&free| free < threshold && !self.low_disk_warned)
+        else {
+            return;
+        };
+        self.low_disk_warned = true;
+        tracing::warn!(free, "low disk space during a run");
+        for i in running {
+            let output = &mut self.steps[i].output;
+            if !output.is_empty() && !output.ends_with('\n') {
+                output.push('\n');
+            }
+            output.push_str(&format!(
+                "⚠️ Low disk space: {} left on the disk of the working directory. Commands may fail for lack of space.\n",
+                format_bytes(free)
+            ));
+        }
+    }
+
     /// Selects the previous step in the list.
     pub fn previous(&mut self) {
         if self.mode != Mode::Normal {
             return;
         }
+        let visible = outline::visible(&self.steps, &self.collapsed);
+        let Some(&last) = visible.last() else {
+            return;
+        };
         let i = match self.list_state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.steps.len() - 1
-                } else {
-                    i - 1
-                }
+            Some(i) => visible.iter().copied().rfind(|&v| v < i).unwrap_or(last),
+            None => visible[0],
+        };
+        self.select(i);
+    }
+
+    /// What the runbook's frontmatter declares about a placeholder.
+    pub fn placeholder_spec(&self, name: &str) -> Option<&PlaceholderSpec> {
+        self.manifest.as_ref()?.placeholders.get(name)
+    }
+
+    /// Value the input modal starts with for `name`: the last one given, or
+    /// the default declared by the runbook.
+    pub fn placeholder_prefill(&self, name: &str) -> String {
+        self.modal
+            .variable_store
+            .get(name)
+            .or_else(|| self.placeholder_spec(name)?.default.as_ref())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Uses values read from an env file for the placeholders and environment
+    /// variables of the same name, so the steps run without asking for them.
+    pub fn import_env(&mut self, vars: Vec<(String, String)>) {
+        let secrets = self.secret_placeholders();
+        let context = &mut self.execution_manager.executor.context;
+        context.import_env(&vars);
+        for (name, value) in vars {
+            if secrets.contains(&name) {
+                context.add_secret(&value);
             }
-            None => 0,
+            self.modal.variable_store.insert(name.clone(), value);
+            self.modal.imported.insert(name);
+        }
+        self.execution_manager.reset_baseline();
+    }
+
+    /// Saves the step statuses and outputs, the context and the placeholder
+    /// values (secrets and imported values left out) to the session file.
+    pub fn save_session(&self) {
+        let Some(path) = &self.session_file else {
+            return;
         };
-        self.list_state.select(Some(i));
-        self.details_scroll = 0;
+        let secrets = self.secret_placeholders();
+        let placeholders = self
+            .modal
+            .variable_store
+            .iter()
+            .filter(|(name, _)| !secrets.contains(*name) && !self.modal.imported.contains(*name))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        let mut session = SavedSession::capture(
+            &self.steps,
+            self.list_state.selected().unwrap_or(0),
+            &self.execution_manager.executor.context,
+            &placeholders,
+            &self.readme_path,
+            VERSION,
+        );
+        session
+            .context
+            .env_vars
+            .retain(|name, _| !self.modal.imported.contains(name));
+        if let Err(e) = session.save(path) {
+            tracing::warn!("Could not save the session: {e:#}");
+        }
+    }
+
+    /// Restores a saved session: step statuses and outputs, context,
+    /// placeholder values and selected step. Returns the warnings to show.
+    pub fn resume_session(&mut self, session: SavedSession) -> Vec<String> {
+        let context = &mut self.execution_manager.executor.context;
+        let warnings = session.apply(&mut self.steps, context);
+        self.modal
+            .variable_store
+            .extend(session.context.placeholders);
+        self.execution_manager.reset_baseline();
+        self.list_state.select(Some(
+            session.selected.min(self.steps.len().saturating_sub(1)),
+        ));
+        warnings
+    }
+
+    /// Names of the placeholders declared as secrets in the runbook.
+    pub fn secret_placeholders(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.steps.iter().flat_map(|s| s.secrets.clone()).collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Sends the filled placeholder values to guests when the host opted in,
+    /// withholding the values of secret placeholders.
+    pub fn share_placeholders(&self) {
+        use crate::core::collab::events::CompassEvent;
+        use crate::core::models::is_secret_placeholder;
 
-        // Emit collab event if host
         if let Some(session) = &self.collab
             && session.is_host
+            && session.share_placeholders
             && let Some(tx) = &session.tx
+            && !self.modal.variable_store.is_empty()
         {
-            let _ = tx.send(crate::core::collab::events::CompassEvent::StepChanged(i));
+            let secrets = self.secret_placeholders();
+            let values = self
+                .modal
+                .variable_store
+                .iter()
+                .map(|(name, value)| {
+                    let secret = is_secret_placeholder(name) || secrets.contains(name);
+                    let value = (!secret).then(|| value.clone());
+                    (name.clone(), value)
+                })
+                .collect();
+            let _ = tx.send(CompassEvent::Placeholders { values });
         }
     }
 
+    /// Toggles focus mode, which hides the step list and status bar.
+    pub const fn toggle_focus_mode(&mut self) {
+        self.focus_mode = !self.focus_mode;
+    }
+
+    /// Collapses the selected step, hiding the steps nested under it, or
+    /// moves to its parent if it has no children or is already collapsed.
+    pub fn collapse_selected(&mut self) {
+        let Some(i) = self.list_state.selected().filter(|&i| i < self.steps.len()) else {
+            return;
+        };
+        if outline::has_children(&self.steps, i) && self.collapsed.insert(i) {
+            return;
+        }
+        if let Some(parent) = outline::parent(&self.steps, i) {
+            self.select(parent);
+        }
+    }
+
+    /// Expands the selected step, showing the steps nested under it, or moves
+    /// to its first child if it is already expanded.
+    pub fn expand_selected(&mut self) {
+        let Some(i) = self.list_state.selected().filter(|&i| i < self.steps.len()) else {
+            return;
+        };
+        if !self.collapsed.remove(&i) && outline::has_children(&self.steps, i) {
+            self.select(i + 1);
+        }
+    }
+
+    /// Expands the steps the selected step is nested under, so that the list
+    /// shows it (e.g. after the auto-pilot moved to it).
+    pub fn reveal_selected(&mut self) {
+        let Some(mut i) = self.list_state.selected().filter(|&i| i < self.steps.len()) else {
+            return;
+        };
+        while let Some(parent) = outline::parent(&self.steps, i) {
+            self.collapsed.remove(&parent);
+            i = parent;
+        }
+    }
+
+    /// Expands or collapses the environment changes in the details panel.
+    pub const fn toggle_env_changes(&mut self) {
+        self.show_env_changes = !self.show_env_changes;
+    }
+
+    /// Inline commands offered for a step: only for steps without code blocks,
+    /// and when enabled in the settings.
+    #[must_use]
+    pub fn inline_commands(&self, index: usize) -> &[String] {
+        match self.steps.get(index) {
+            Some(step) if self.settings.inline_commands && !step.is_executable() => {
+                &step.inline_commands
+            }
+            _ => &[],
+        }
+    }
+
+    /// Toggles force-enabling of the selected step when its condition is not met.
+    pub fn toggle_force_selected(&mut self) {
+        use crate::core::executor::conditions::evaluator::{ConditionEvaluator, StandardEvaluator};
+
+        if self.mode != Mode::Normal {
+            return;
+        }
+        let Some(i) = self.list_state.selected() else {
+            return;
+        };
+        let condition_unmet = self
+            .steps
+            .get(i)
+            .and_then(|s| s.condition.as_ref())
+            .is_some_and(|c| !StandardEvaluator::new().evaluate(c));
+
+        if condition_unmet && !self.forced_steps.remove(&i) {
+            self.forced_steps.insert(i);
+        }
+    }
+
+    /// Shows the previous (older) run of the selected step.
+    pub fn previous_attempt(&mut self) {
+        let Some(step) = self.list_state.selected().and_then(|i| self.steps.get(i)) else {
+            return;
+        };
+        if step.history.is_empty() {
+            return;
+        }
+        let last = step.history.len() - 1;
+        self.attempt_view = Some(self.attempt_view.map_or(last, |n| n.saturating_sub(1)));
+        self.details_scroll = 0;
+    }
+
+    /// Shows the next (newer) run of the selected step, back to the latest output.
+    pub fn next_attempt(&mut self) {
+        let Some(step) = self.list_state.selected().and_then(|i| self.steps.get(i)) else {
+            return;
+        };
+        self.attempt_view = self
+            .attempt_view
+            .map(|n| n + 1)
+            .filter(|&n| n < step.history.len());
+        self.details_scroll = 0;
+    }
+
     /// Scrolls the details panel up.
     pub const fn scroll_details_up(&mut self) {
         self.details_scroll = self.details_scroll.saturating_sub(5);
@@ -267,13 +908,23 @@ impl App {
         self.modal.input_buffer.clear();
         self.modal.required_placeholders.clear();
         self.safety_pattern = None;
+        self.cloud_probe = None;
+        self.secret_requests.clear();
+        self.secret_fetch = None;
+        self.prerequisites_override = None;
+        self.plan_approved = None;
+        self.diff_approved = None;
+        self.cluster_confirmed = None;
+        self.cloud_confirmed = None;
+        self.policy_confirmed = None;
         self.export_message = None;
+        self.dry_run = false;
+        self.dry_run_preview = None;
     }
 
     /// Gets the count of completed steps.
     #[must_use]
     pub fn completed_count(&self) -> usize {
-        use crate::core::models::StepStatus;
         self.steps
             .iter()
             .filter(|s| s.is_executable() && s.status == StepStatus::Success)
@@ -283,10 +934,9 @@ impl App {
     /// Gets the count of failed steps.
     #[must_use]
     pub fn failed_count(&self) -> usize {
-        use crate::core::models::StepStatus;
         self.steps
             .iter()
-            .filter(|s| s.is_executable() && s.status == StepStatus::Failed)
+            .filter(|s| s.is_executable() && s.status.is_failure())
             .count()
     }
 
@@ -295,4 +945,122 @@ impl App {
     pub fn total_executable_steps(&self) -> usize {
         self.steps.iter().filter(|s| s.is_executable()).count()
     }
+
+    /// Estimated duration of a step in seconds.
+    ///
+    /// Durations measured in previous sessions take precedence over the
+    /// author's `compass:eta` annotation.
+    #[must_use]
+    pub fn estimated_secs(&self, index: usize) -> Option<u64> {
+        let step = self.steps.get(index)?;
+        self.config_manager
+            .as_ref()
+            .and_then(|c| c.get_duration(&step.title))
+            .map(|ms| ms.div_ceil(1000))
+            .or(step.eta_secs)
+    }
+
+    /// Estimated time left for the steps that have not succeeded or been
+    /// skipped yet, or `None` when no remaining step has an estimate.
+    #[must_use]
+    pub fn remaining_secs(&self) -> Option<u64> {
+        self.steps
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| {
+                s.is_executable() && !matches!(s.status, StepStatus::Success | StepStatus::Skipped)
+            })
+            .filter_map(|(i, _)| self.estimated_secs(i))
+            .reduce(|a, b| a + b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_readme;
+
+    #[test]
+    fn test_move_scratch_step_keeps_indices() {
+        let (steps, _) = parse_readme(
+            "# One\n```bash\necho 1\n```\n# Two\n```bash\necho 2\n```\n# Three\n```bash\necho 3\n```\n",
+        );
+        let mut app = App::new(steps, PathBuf::from("README.md"), false);
+        app.forced_steps.insert(2);
+        app.list_state.select(Some(0));
+
+        app.insert_step(Step {
+            title: "Scratch".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(app.list_state.selected(), Some(1));
+        assert!(app.steps[1].session_only);
+        assert_eq!(app.forced_steps, HashSet::from([3]));
+
+        app.move_selected(false);
+        assert_eq!(app.list_state.selected(), Some(0));
+        assert_eq!(app.steps[0].title, "Scratch");
+
+        // Moving past a step carries the state kept for it along
+        for _ in 0..3 {
+            app.move_selected(true);
+        }
+        let titles: Vec<&str> = app.steps.iter().map(|s| s.title.trim()).collect();
+        assert_eq!(titles, vec!["One", "Two", "Three", "Scratch"]);
+        assert_eq!(app.list_state.selected(), Some(3));
+        assert_eq!(app.forced_steps, HashSet::from([2]));
+
+        // The last step cannot go further down
+        app.move_selected(true);
+        assert_eq!(app.list_state.selected(), Some(3));
+    }
+
+    #[test]
+    fn test_toggle_sandbox_for_one_step() {
+        let (steps, _) = parse_readme("# One\n```bash\necho 1\n```\n# Two\n```bash\necho 2\n```\n");
+        let mut app = App::new(steps, PathBuf::from("README.md"), false);
+        // Stands in for the Docker check, answering that Docker is not running.
+        let (tx, rx) = mpsc::channel();
+        app.docker_check = Some(rx);
+
+        app.list_state.select(Some(1));
+        app.toggle_sandbox_selected();
+        assert!(!app.is_sandboxed(0));
+        assert!(app.is_sandboxed(1));
+
+        tx.send(false).unwrap();
+        app.poll_docker_check();
+        assert!(!app.is_sandboxed(1));
+        assert!(app.steps[1].output.contains("Docker is not running"));
+    }
+
+    #[test]
+    fn test_share_placeholders_withholds_secrets() {
+        use crate::core::collab::events::CompassEvent;
+        use crate::core::collab::session::CollabSession;
+
+        let (steps, _) = parse_readme("# Login\n```bash\nlogin <HOST> <SECRET:KEY>\n```\n");
+        let mut app = App::new(steps, PathBuf::from("README.md"), false);
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        app.collab =
+            Some(CollabSession::new(true, None, Some(tx), None).with_placeholder_sync(true));
+        for (name, value) in [
+            ("HOST", "db.local"),
+            ("API_TOKEN", "t0k3n"),
+            ("KEY", "s3cr3t"),
+        ] {
+            app.modal
+                .variable_store
+                .insert(name.to_string(), value.to_string());
+        }
+
+        app.share_placeholders();
+
+        let Ok(CompassEvent::Placeholders { values }) = rx.try_recv() else {
+            panic!("no placeholders sent");
+        };
+        assert_eq!(values["HOST"].as_deref(), Some("db.local"));
+        assert_eq!(values["API_TOKEN"], None);
+        assert_eq!(values["KEY"], None);
+    }
 }
@@ -12,10 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use super::execution::perform_execution;
+use super::execution::{broadcast_status, perform_execution};
+use crate::core::executor::BackgroundRun;
 use crate::core::export::Exporter;
+use crate::core::export::context::ContextExport;
+use crate::core::infrastructure::{snippets, trust};
+use crate::core::models::{CodeBlock, Step, StepStatus};
+use crate::core::parser::extract_placeholders;
 use crate::ui::app::{App, VERSION};
-use crate::ui::state::Mode;
+use crate::ui::state::{Checkpoint, Mode};
 
 /// Handles submission of a placeholder value from the input modal.
 pub fn submit_input(app: &mut App) {
@@ -26,7 +31,28 @@ pub fn submit_input(app: &mut App) {
     let var_name = app.modal.required_placeholders[app.modal.current_placeholder_idx].clone();
     let value = app.modal.input_buffer.clone();
 
-    app.modal.variable_store.insert(var_name, value);
+    // Values not matching the runbook's format are asked again
+    if app.modal.is_placeholder()
+        && let Some(Err(problem)) = app.placeholder_spec(&var_name).map(|s| s.validate(&value))
+    {
+        app.modal.error = Some(problem);
+        return;
+    }
+    if app.modal.is_secret() {
+        app.execution_manager.executor.context.add_secret(&value);
+    }
+
+    // Environment variables go to the context only, so they are not saved with placeholders
+    if app.modal.is_env() {
+        app.execution_manager
+            .executor
+            .context
+            .env_vars
+            .insert(var_name.clone(), value.clone());
+    }
+    if app.modal.is_placeholder() {
+        app.modal.variable_store.insert(var_name, value);
+    }
 
     app.modal.current_placeholder_idx += 1;
     app.modal.input_buffer.clear();
@@ -34,30 +60,239 @@ pub fn submit_input(app: &mut App) {
     if app.modal.current_placeholder_idx < app.modal.required_placeholders.len() {
         // Pre-fill next variable
         let next_var = &app.modal.required_placeholders[app.modal.current_placeholder_idx];
-        app.modal.input_buffer = app
-            .modal
-            .variable_store
-            .get(next_var)
-            .cloned()
-            .unwrap_or_default();
+        app.modal.input_buffer = app.placeholder_prefill(next_var);
+        app.modal.fit_choice();
     } else {
-        // All filled, save config and execute
+        // All filled, save config, share with guests and execute
         app.save_config();
+        app.share_placeholders();
         app.mode = Mode::Normal;
         perform_execution(app, false);
     }
 }
 
+/// Shows the command the selected step would run, placeholders filled, without
+/// running it. Placeholders are asked first, as for a run.
+pub fn preview_command(app: &mut App) {
+    if app.mode != Mode::Normal
+        || !app
+            .list_state
+            .selected()
+            .and_then(|i| app.steps.get(i))
+            .is_some_and(Step::is_executable)
+    {
+        return;
+    }
+    app.dry_run = true;
+    app.inline_choice = None;
+    perform_execution(app, false);
+}
+
 /// Confirms execution of a dangerous command.
 pub fn confirm_safety(app: &mut App) {
-    if app.mode != Mode::SafetyAlert && app.mode != Mode::DependencyAlert {
+    if !matches!(app.mode, Mode::SafetyAlert | Mode::DependencyAlert) {
         return;
     }
+    if app.mode == Mode::SafetyAlert
+        && let Some(content) = app.untrusted_content.take()
+    {
+        let location = trust::location(&app.readme_path);
+        if let Err(e) = trust::record(&location, &content) {
+            tracing::warn!("Could not record the runbook as trusted: {e:#}");
+        }
+    }
     app.mode = Mode::Normal;
     app.safety_pattern = None;
+    app.edit_preview.clear();
     perform_execution(app, true);
 }
 
+/// Writes the file edits the user reviewed: runs the selected step.
+///
+/// The preview comes after the safety checks, so they are not repeated.
+pub fn confirm_diff(app: &mut App) {
+    if app.mode != Mode::DiffPreview {
+        return;
+    }
+    app.mode = Mode::Normal;
+    app.edit_preview.clear();
+    app.diff_approved = app.list_state.selected();
+    perform_execution(app, true);
+}
+
+/// Runs the selected step although its prerequisites have not succeeded.
+pub fn confirm_prerequisites(app: &mut App) {
+    if app.mode != Mode::PrerequisiteAlert {
+        return;
+    }
+    app.mode = Mode::Normal;
+    app.safety_pattern = None;
+    app.prerequisites_override = app.list_state.selected();
+    perform_execution(app, false);
+}
+
+/// Runs the selected step once the user confirmed the reboot or new login a
+/// step asked for is done. The steps after it are no longer blocked.
+pub fn confirm_restart(app: &mut App) {
+    if app.mode != Mode::RestartAlert {
+        return;
+    }
+    app.mode = Mode::Normal;
+    app.safety_pattern = None;
+    app.restart_notice = None;
+    perform_execution(app, false);
+}
+
+/// Applies the reviewed `terraform plan`: runs the selected step.
+pub fn confirm_plan(app: &mut App) {
+    if app.mode != Mode::PlanReview {
+        return;
+    }
+    app.mode = Mode::Normal;
+    app.plan_approved = app.list_state.selected();
+    perform_execution(app, false);
+}
+
+/// Runs the selected step against the Kubernetes context the user confirmed.
+pub fn confirm_cluster(app: &mut App) {
+    if app.mode != Mode::ClusterAlert {
+        return;
+    }
+    app.mode = Mode::Normal;
+    app.safety_pattern = None;
+    app.cluster_confirmed = app.list_state.selected();
+    perform_execution(app, false);
+}
+
+/// Reads the placeholders of the selected step from their secret managers,
+/// as the user agreed, then runs it.
+pub fn confirm_secrets(app: &mut App) {
+    if app.mode != Mode::SecretConsent || app.secret_fetch.is_some() {
+        return;
+    }
+    super::execution::start_secret_fetch(app);
+}
+
+/// Runs the selected step with the cloud account shown, or without waiting
+/// for the probe to answer.
+pub fn confirm_cloud(app: &mut App) {
+    if !matches!(app.mode, Mode::CloudCheck | Mode::CloudAlert) {
+        return;
+    }
+    app.mode = Mode::Normal;
+    app.safety_pattern = None;
+    app.cloud_probe = None;
+    app.cloud_confirmed = app.list_state.selected();
+    perform_execution(app, false);
+}
+
+/// Runs the step whose confirmation an organization policy requires.
+pub fn confirm_policy(app: &mut App) {
+    if app.mode != Mode::PolicyAlert {
+        return;
+    }
+    app.mode = Mode::Normal;
+    app.safety_pattern = None;
+    app.policy_confirmed = app.list_state.selected();
+    perform_execution(app, false);
+}
+
+/// Asks for the command of a scratch step to add after the selected one.
+pub fn start_scratch_step(app: &mut App) {
+    if !app.can_edit_steps() {
+        return;
+    }
+    app.modal.input_buffer.clear();
+    app.mode = Mode::ScratchInput;
+}
+
+/// Adds the typed command as a session-only step after the selected one.
+pub fn add_scratch_step(app: &mut App) {
+    if app.mode != Mode::ScratchInput {
+        return;
+    }
+    let command = std::mem::take(&mut app.modal.input_buffer);
+    app.mode = Mode::Normal;
+    let command = command.trim();
+    if command.is_empty() {
+        return;
+    }
+    let level = app
+        .list_state
+        .selected()
+        .and_then(|i| app.steps.get(i))
+        .map_or(1, |s| s.level.max(1));
+    app.insert_step(Step {
+        title: format!("Scratch: {command}"),
+        level,
+        code_blocks: vec![CodeBlock {
+            language: None,
+            placeholders: extract_placeholders(command),
+            content: format!("{command}\n"),
+            write_to: None,
+            indented: false,
+        }],
+        ..Default::default()
+    });
+}
+
+/// Opens the snippet palette with the library as it is on disk.
+pub fn open_snippet_palette(app: &mut App) {
+    if !app.can_edit_steps() {
+        return;
+    }
+    app.snippets = match snippets::load() {
+        Ok(snippets) => snippets,
+        Err(e) => {
+            app.export_message = Some((false, format!("{e:#}")));
+            app.mode = Mode::ExportNotification;
+            return;
+        }
+    };
+    app.palette.reset();
+    app.mode = Mode::SnippetPalette;
+}
+
+/// Runs the highlighted snippet as a session-only step after the selected one.
+pub fn run_snippet(app: &mut App) {
+    if app.mode != Mode::SnippetPalette {
+        return;
+    }
+    app.mode = Mode::Normal;
+    let Some(snippet) = app
+        .snippet_matches()
+        .get(app.palette.selected)
+        .and_then(|&i| app.snippets.get(i))
+        .cloned()
+    else {
+        return;
+    };
+    let level = app
+        .list_state
+        .selected()
+        .and_then(|i| app.steps.get(i))
+        .map_or(1, |s| s.level.max(1));
+    app.insert_step(Step {
+        title: format!("Snippet: {}", snippet.name),
+        level,
+        code_blocks: vec![CodeBlock {
+            language: snippet.language.clone(),
+            placeholders: extract_placeholders(&snippet.command),
+            content: format!("{}\n", snippet.command),
+            write_to: None,
+            indented: false,
+        }],
+        ..Default::default()
+    });
+    if let Err(e) = snippets::record_use(&snippet.name) {
+        tracing::warn!(
+            "Failed to record the use of snippet '{}': {e:#}",
+            snippet.name
+        );
+    }
+    perform_execution(app, false);
+}
+
 /// Handles interaction with the recovery alert modal.
 #[allow(clippy::collapsible_if)]
 pub fn confirm_recovery(app: &mut App) {
@@ -70,11 +305,18 @@ pub fn confirm_recovery(app: &mut App) {
         if let Some(cmd) = &rec.fix_command {
             // Find current step index
             if let Some(i) = app.list_state.selected() {
+                let mode = app.steps.get(i).map(|s| s.context_mode).unwrap_or_default();
+                let sandbox = app.is_sandboxed(i);
                 app.execution_manager.execute_background(
                     i,
-                    cmd.clone(),
-                    Some("bash".to_string()),
-                    true,
+                    BackgroundRun {
+                        content: cmd.clone(),
+                        language: Some("bash".to_string()),
+                        bypass_safety: true,
+                        mode,
+                        sandbox,
+                        ..Default::default()
+                    },
                 );
                 // We don't perform full execution, just run the fix
             }
@@ -88,7 +330,7 @@ pub fn confirm_recovery(app: &mut App) {
 
 /// Exports the current session to JSON and Markdown files.
 pub fn export_report(app: &mut App) {
-    if app.mode != Mode::Normal {
+    if app.mode != Mode::Normal || refuse_export(app) {
         return;
     }
 
@@ -101,6 +343,15 @@ pub fn export_report(app: &mut App) {
         &app.modal.variable_store,
         VERSION,
     );
+    // Guests export what they observed, not their own environment. The join
+    // URL is recorded without its `?pin=`, which grants access to the session.
+    let report = match &app.collab {
+        Some(session) if !session.is_host => {
+            let url = session.id.as_deref().unwrap_or("unknown");
+            report.into_observer_copy(url.split('?').next().unwrap_or(url))
+        }
+        _ => report,
+    };
 
     // Get the base directory (current working directory)
     let base_dir = &app.execution_manager.executor.context.current_dir;
@@ -118,3 +369,371 @@ pub fn export_report(app: &mut App) {
         }
     }
 }
+
+/// Saves the working directory, environment and placeholder values, to
+/// continue the runbook later or elsewhere with `--context`.
+pub fn export_context(app: &mut App) {
+    if app.mode != Mode::Normal || refuse_export(app) {
+        return;
+    }
+    let context = &app.execution_manager.executor.context;
+    // Secret placeholders are not written to the file
+    let secrets = app.secret_placeholders();
+    let placeholders = app
+        .modal
+        .variable_store
+        .iter()
+        .filter(|(name, _)| !secrets.contains(name))
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect();
+    let export = ContextExport::capture(context, &placeholders, &app.readme_path, VERSION);
+    let path = ContextExport::default_path(&context.current_dir);
+    app.export_message = Some(match export.save(&path) {
+        Ok(()) => (true, path.display().to_string()),
+        Err(e) => (false, format!("{e:#}")),
+    });
+    app.mode = Mode::ExportNotification;
+}
+
+/// Tells that nothing is exported to the working directory in read-only mode.
+/// Returns whether the export is refused.
+fn refuse_export(app: &mut App) -> bool {
+    if app.read_only {
+        app.export_message = Some((
+            false,
+            "Read-only mode: nothing is written to the working directory.".to_string(),
+        ));
+        app.mode = Mode::ExportNotification;
+    }
+    app.read_only
+}
+
+/// Reverts the working directory and environment to their state before the
+/// last run of the selected step, dropping every context change made since.
+pub fn revert_context(app: &mut App) {
+    if app.mode != Mode::Normal {
+        return;
+    }
+    // A running step would overwrite the restored context when it finishes.
+    if app.steps.iter().any(|s| s.status == StepStatus::Running) {
+        return;
+    }
+    let Some(i) = app.list_state.selected() else {
+        return;
+    };
+    let Some(pos) = app.context_history.iter().rposition(|c| c.step == i) else {
+        if let Some(step) = app.steps.get_mut(i) {
+            step.output
+                .push_str("\n\n---\n↩ This step did not change the context.");
+        }
+        return;
+    };
+
+    let undone = app.context_history.len() - pos;
+    let change = app
+        .context_history
+        .drain(pos..)
+        .next()
+        .expect("pos is in range");
+    let dir = change.before.current_dir.display().to_string();
+    app.execution_manager
+        .executor
+        .context
+        .restore(change.before);
+    tracing::info!(step = i, undone, "reverted execution context");
+
+    if let Some(step) = app.steps.get_mut(i) {
+        step.output.push_str(&format!(
+            "\n\n---\n↩ Context reverted to before this step ({undone} change(s) undone). Working directory: {dir}"
+        ));
+    }
+}
+
+/// Rewinds the session to just before the selected step last ran: statuses,
+/// outputs, working directory, environment and placeholder values. Edited
+/// files are left as they are.
+pub fn rewind_session(app: &mut App) {
+    if app.mode != Mode::Normal {
+        return;
+    }
+    // A running step would finish into the restored state.
+    if app.steps.iter().any(|s| s.status == StepStatus::Running) {
+        return;
+    }
+    let Some(i) = app.list_state.selected() else {
+        return;
+    };
+    let Some(pos) = app.checkpoints.iter().rposition(|c| c.step == i) else {
+        if let Some(step) = app.steps.get_mut(i) {
+            step.output
+                .push_str("\n\n---\n⏪ This step has not run in this session.");
+        }
+        return;
+    };
+
+    let undone: Vec<Checkpoint> = app.checkpoints.drain(pos..).collect();
+    let mut runs = vec![0; app.steps.len()];
+    for checkpoint in &undone {
+        if let Some(count) = runs.get_mut(checkpoint.step) {
+            *count += 1;
+        }
+    }
+    let checkpoint = undone.into_iter().next().expect("pos is in range");
+    for (j, step) in app.steps.iter_mut().enumerate() {
+        if let Some(&status) = checkpoint.statuses.get(j) {
+            step.status = status;
+        }
+        if runs[j] > 0 {
+            // The history keeps the undone runs, viewable with [ and ]
+            step.output = step
+                .history
+                .len()
+                .checked_sub(runs[j] + 1)
+                .map(|k| step.history[k].output.clone())
+                .unwrap_or_default();
+            step.stdout.clear();
+            step.stderr.clear();
+            step.context_diff = None;
+            step.execution = None;
+        }
+        broadcast_status(app.collab.as_ref(), j, step.status);
+    }
+
+    let dir = checkpoint.context.current_dir.display().to_string();
+    app.execution_manager
+        .executor
+        .context
+        .restore(checkpoint.context);
+    app.context_history.truncate(checkpoint.context_changes);
+    app.modal.variable_store = checkpoint.placeholders;
+    app.autopilot.clear();
+    app.autopilot_current = None;
+    app.attempt_view = None;
+    app.details_scroll = 0;
+    app.steps_edited += 1;
+    let undone = runs.iter().sum::<usize>();
+    tracing::info!(step = i, undone, "rewound session");
+
+    if let Some(step) = app.steps.get_mut(i) {
+        step.output.push_str(&format!(
+            "\n\n---\n⏪ Session rewound to before this step ({undone} run(s) undone). Working directory: {dir}\nEdited files were left as they are: press U on a step to restore them."
+        ));
+    }
+}
+
+/// Checks the dependencies of the whole runbook and shows the summary.
+pub fn check_dependencies(app: &mut App) {
+    if app.mode != Mode::Normal {
+        return;
+    }
+    let mut report = crate::core::executor::check_dependencies(&app.steps);
+    if let Some(manifest) = &app.manifest {
+        report.add(&manifest.requires);
+    }
+    app.dependency_report = Some(report);
+    app.mode = Mode::DependencyCheck;
+}
+
+/// Restores the files edited by the last run of the selected step, then runs
+/// its `rollback` command if it has one and ran.
+pub fn rollback_edits(app: &mut App) {
+    if app.mode != Mode::Normal {
+        return;
+    }
+    let Some(i) = app.list_state.selected() else {
+        return;
+    };
+    if app
+        .steps
+        .get(i)
+        .is_none_or(|s| s.status == StepStatus::Running)
+    {
+        return;
+    }
+    // Guests cannot run commands
+    let rollback = app.steps[i].rollback.is_some()
+        && !app.steps[i].history.is_empty()
+        && app.collab.as_ref().is_none_or(|s| s.is_host);
+    let message = match app.file_backups.remove(&i) {
+        None if rollback => String::new(),
+        None => "↩ This step did not edit any file.".to_string(),
+        Some(backups) => match crate::core::analysis::edits::restore(&backups) {
+            Ok(count) => {
+                tracing::info!(step = i, count, "rolled back file edits");
+                format!("↩ Restored {count} file(s) to their state before the step.")
+            }
+            Err(e) => {
+                tracing::warn!("Failed to roll back file edits: {e:#}");
+                format!("❌ Rollback failed: {e:#}")
+            }
+        },
+    };
+    if !message.is_empty() {
+        app.steps[i].output.push_str("\n\n---\n");
+        app.steps[i].output.push_str(&message);
+    }
+    if rollback {
+        super::execution::start_rollback(app, i);
+    }
+}
+
+/// Kills the command of the selected step, if it is running.
+pub fn cancel_running(app: &mut App) {
+    if app.mode != Mode::Normal {
+        return;
+    }
+    if let Some(session) = &app.collab
+        && !session.is_host
+    {
+        return; // Guests cannot stop the host's commands
+    }
+    let Some(i) = app.list_state.selected() else {
+        return;
+    };
+    if app
+        .steps
+        .get(i)
+        .is_some_and(|s| s.status == StepStatus::Running)
+        && app.execution_manager.cancel(i)
+    {
+        tracing::info!(step = i, "cancelling step");
+    }
+}
+
+/// Starts forwarding keystrokes to the selected step, if it is running.
+pub fn start_interactive(app: &mut App) {
+    if app.mode != Mode::Normal {
+        return;
+    }
+    if let Some(session) = &app.collab
+        && !session.is_host
+    {
+        return; // Guests cannot type into the host's commands
+    }
+    if app.execution_manager.executor.context.separate_streams {
+        return; // Commands run with pipes get no input
+    }
+    if app
+        .list_state
+        .selected()
+        .and_then(|i| app.steps.get(i))
+        .is_some_and(|s| s.status == StepStatus::Running)
+    {
+        app.mode = Mode::Interactive;
+    }
+}
+
+/// Sends a key to the running step, leaving interactive mode once it finished.
+pub fn forward_key(app: &mut App, key: crossterm::event::KeyEvent) {
+    let Some(i) = app.list_state.selected() else {
+        app.mode = Mode::Normal;
+        return;
+    };
+    if let Some(bytes) = crate::ui::utils::key_to_bytes(key)
+        && !app.execution_manager.send_input(i, bytes)
+    {
+        app.mode = Mode::Normal;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_readme;
+    use crate::ui::state::ContextChange;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_revert_context_undoes_later_changes() {
+        let (steps, _) =
+            parse_readme("# Enter\n```bash\ncd app\n```\n# Build\n```bash\ncd build\n```\n");
+        let mut app = App::new(steps, PathBuf::from("README.md"), false);
+        let context = &mut app.execution_manager.executor.context;
+        context.current_dir = PathBuf::from("/work");
+        let before_enter = context.snapshot();
+        context.current_dir = PathBuf::from("/work/app");
+        let before_build = context.snapshot();
+        context.current_dir = PathBuf::from("/work/app/build");
+        context
+            .env_vars
+            .insert("TARGET".to_string(), "release".to_string());
+        app.context_history = vec![
+            ContextChange {
+                step: 0,
+                before: before_enter,
+            },
+            ContextChange {
+                step: 1,
+                before: before_build,
+            },
+        ];
+
+        revert_context(&mut app);
+
+        let context = &app.execution_manager.executor.context;
+        assert_eq!(context.current_dir, PathBuf::from("/work"));
+        assert!(!context.env_vars.contains_key("TARGET"));
+        assert!(app.context_history.is_empty());
+        assert!(app.steps[0].output.contains("2 change(s) undone"));
+    }
+
+    #[test]
+    fn test_rewind_restores_statuses_and_context() {
+        let (steps, _) =
+            parse_readme("# Enter\n```bash\ncd app\n```\n# Build\n```bash\nmake <ENV>\n```\n");
+        let mut app = App::new(steps, PathBuf::from("README.md"), false);
+        let context = &mut app.execution_manager.executor.context;
+        context.current_dir = PathBuf::from("/work");
+        let before_enter = context.snapshot();
+        context.current_dir = PathBuf::from("/work/app");
+        context
+            .env_vars
+            .insert("TARGET".to_string(), "release".to_string());
+        let before_build = context.snapshot();
+        app.checkpoints = vec![
+            Checkpoint {
+                step: 0,
+                statuses: vec![StepStatus::Pending, StepStatus::Pending],
+                context: before_enter,
+                placeholders: HashMap::new(),
+                context_changes: 0,
+            },
+            Checkpoint {
+                step: 1,
+                statuses: vec![StepStatus::Success, StepStatus::Pending],
+                context: before_build,
+                placeholders: HashMap::new(),
+                context_changes: 0,
+            },
+        ];
+        app.steps[0].status = StepStatus::Success;
+        app.steps[1].status = StepStatus::Failed;
+        app.modal
+            .variable_store
+            .insert("ENV".to_string(), "prod".to_string());
+
+        // Back to before the second step: the first one stays done
+        app.list_state.select(Some(1));
+        rewind_session(&mut app);
+        assert_eq!(app.steps[0].status, StepStatus::Success);
+        assert_eq!(app.steps[1].status, StepStatus::Pending);
+        let context = &app.execution_manager.executor.context;
+        assert_eq!(context.current_dir, PathBuf::from("/work/app"));
+        assert_eq!(
+            context.env_vars.get("TARGET").map(String::as_str),
+            Some("release")
+        );
+        assert!(app.modal.variable_store.is_empty());
+
+        // Back to the start
+        app.list_state.select(Some(0));
+        rewind_session(&mut app);
+        assert_eq!(app.steps[0].status, StepStatus::Pending);
+        let context = &app.execution_manager.executor.context;
+        assert_eq!(context.current_dir, PathBuf::from("/work"));
+        assert!(!context.env_vars.contains_key("TARGET"));
+        assert!(app.checkpoints.is_empty());
+        assert!(app.steps[0].output.contains("1 run(s) undone"));
+    }
+}
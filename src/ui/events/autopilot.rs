@@ -0,0 +1,171 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Auto-pilot: runs the steps that have not succeeded yet one after the
+//! other, stopping at the first one that does not succeed.
+//!
+//! Each step goes through the usual execution flow, so placeholder prompts
+//! and confirmations still show up; the auto-pilot waits for them. The steps
+//! of a parallel group start together, and the auto-pilot waits for all of
+//! them.
+
+use super::execution::perform_execution;
+use crate::core::executor::{groups, prerequisites};
+use crate::core::models::StepStatus;
+use crate::ui::app::App;
+use crate::ui::state::Mode;
+
+/// Starts the auto-pilot, or stops it if it is on. Stopping lets the running
+/// step finish.
+pub fn toggle(app: &mut App) {
+    if app.mode != Mode::Normal {
+        return;
+    }
+    if app.autopilot_active() {
+        stop(app, None);
+        return;
+    }
+    if let Some(session) = &app.collab
+        && !session.is_host
+    {
+        return; // Guest cannot execute
+    }
+    if app.read_only {
+        return;
+    }
+    let remaining: Vec<usize> = app
+        .steps
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| {
+            s.is_executable()
+                && !matches!(
+                    s.status,
+                    StepStatus::Success | StepStatus::Skipped | StepStatus::Running
+                )
+        })
+        .map(|(i, _)| i)
+        .collect();
+    // A cycle in the prerequisites is reported when its steps run
+    app.autopilot = prerequisites::order(&app.steps, &remaining)
+        .unwrap_or(remaining)
+        .into();
+    app.autopilot_current = None;
+    advance(app);
+}
+
+/// Starts the next step once the current one succeeded or was skipped, and
+/// stops at the first one that did not. Called on every tick.
+pub fn advance(app: &mut App) {
+    if !app.autopilot_active() || app.mode != Mode::Normal {
+        // Off, or waiting for the user to answer a prompt
+        return;
+    }
+    if let Some(i) = app.autopilot_current {
+        // A step of a parallel group waits for the whole group
+        let members = if i < app.steps.len() {
+            groups::members(&app.steps, i)
+        } else {
+            Vec::new()
+        };
+        let statuses: Vec<StepStatus> = members.iter().map(|&m| app.steps[m].status).collect();
+        let at = members
+            .iter()
+            .copied()
+            .find(|&m| {
+                !matches!(
+                    app.steps[m].status,
+                    StepStatus::Success | StepStatus::Skipped
+                )
+            })
+            .unwrap_or(i);
+        match (!statuses.is_empty()).then(|| groups::status(&statuses)) {
+            Some(StepStatus::Running) => return,
+            Some(StepStatus::Success | StepStatus::Skipped) | None => {}
+            Some(StepStatus::Pending) => {
+                stop(
+                    app,
+                    Some((at, "⏸ Auto-pilot stopped: this step did not run.")),
+                );
+                return;
+            }
+            Some(_) => {
+                stop(app, Some((at, "⏸ Auto-pilot stopped at this step.")));
+                return;
+            }
+        }
+    }
+
+    // Steps may have been run by hand in the meantime
+    while let Some(i) = app.autopilot.pop_front() {
+        if let Some(status) = app
+            .steps
+            .get(i)
+            .map(|s| s.status)
+            .filter(|&s| s != StepStatus::Success)
+        {
+            app.autopilot_current = Some(i);
+            app.list_state.select(Some(i));
+            app.inline_choice = None;
+            app.attempt_view = None;
+            perform_execution(app, false);
+            if status != StepStatus::Running {
+                super::group::start(app, i);
+            }
+            return;
+        }
+    }
+    app.autopilot_current = None;
+}
+
+/// Turns the auto-pilot off, noting why in the output of a step.
+fn stop(app: &mut App, reason: Option<(usize, &str)>) {
+    app.autopilot.clear();
+    app.autopilot_current = None;
+    if let Some((i, message)) = reason
+        && let Some(step) = app.steps.get_mut(i)
+    {
+        step.output.push_str("\n\n---\n");
+        step.output.push_str(message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_readme;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_stops_at_first_failed_step() {
+        let (steps, _) = parse_readme(
+            "# One\n```bash\nfalse\n```\n# Two\n```bash\necho 2\n```\n# Three\n```bash\necho 3\n```\n",
+        );
+        let mut app = App::new(steps, PathBuf::from("README.md"), false);
+        // The first step was started, the others are queued
+        app.autopilot = [1, 2].into();
+        app.autopilot_current = Some(0);
+        app.steps[0].status = StepStatus::Running;
+
+        advance(&mut app);
+        assert!(app.autopilot_active());
+        assert_eq!(app.autopilot_current, Some(0));
+
+        app.steps[0].status = StepStatus::Failed;
+        advance(&mut app);
+        assert!(!app.autopilot_active());
+        assert!(app.steps[0].output.contains("Auto-pilot stopped"));
+        assert_eq!(app.steps[1].status, StepStatus::Pending);
+    }
+}
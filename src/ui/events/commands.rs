@@ -0,0 +1,225 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Actions listed by the command palette (`Ctrl+P`), so that every feature
+//! can be found without knowing its key.
+
+use super::execution::execute_selected;
+use super::{actions, autopilot};
+use crate::ui::app::App;
+use crate::ui::state::Mode;
+use crate::ui::state::palette::PaletteState;
+
+/// An action of the command palette.
+pub struct Command {
+    /// What the action does, matched against the query.
+    pub name: &'static str,
+    /// The key running the action outside the palette.
+    pub key: &'static str,
+    /// Runs the action, in normal mode.
+    pub run: fn(&mut App),
+}
+
+/// Every action of the TUI, in the order shown when the query is empty.
+pub const COMMANDS: &[Command] = &[
+    Command {
+        name: "Run the selected step",
+        key: "Enter",
+        run: execute_selected,
+    },
+    Command {
+        name: "Dry run: show the command of the selected step",
+        key: "v",
+        run: actions::preview_command,
+    },
+    Command {
+        name: "Auto-pilot: run all remaining steps",
+        key: "A",
+        run: autopilot::toggle,
+    },
+    Command {
+        name: "Run a snippet from your library",
+        key: "S",
+        run: actions::open_snippet_palette,
+    },
+    Command {
+        name: "Add a scratch step",
+        key: "a",
+        run: actions::start_scratch_step,
+    },
+    Command {
+        name: "Duplicate the selected step",
+        key: "D",
+        run: App::duplicate_selected,
+    },
+    Command {
+        name: "Move the selected step up",
+        key: "<",
+        run: |app| app.move_selected(false),
+    },
+    Command {
+        name: "Move the selected step down",
+        key: ">",
+        run: |app| app.move_selected(true),
+    },
+    Command {
+        name: "Export the session report",
+        key: "s",
+        run: actions::export_report,
+    },
+    Command {
+        name: "Save the context to continue elsewhere",
+        key: "e",
+        run: actions::export_context,
+    },
+    Command {
+        name: "Check the runbook's dependencies",
+        key: "c",
+        run: actions::check_dependencies,
+    },
+    Command {
+        name: "Force-enable the selected step despite its condition",
+        key: "f",
+        run: App::toggle_force_selected,
+    },
+    Command {
+        name: "Toggle the Docker sandbox for the selected step",
+        key: "b",
+        run: App::toggle_sandbox_selected,
+    },
+    Command {
+        name: "Type into the running step",
+        key: "i",
+        run: actions::start_interactive,
+    },
+    Command {
+        name: "Cancel the running step",
+        key: "x",
+        run: actions::cancel_running,
+    },
+    Command {
+        name: "Revert the working directory and environment to before the step",
+        key: "u",
+        run: actions::revert_context,
+    },
+    Command {
+        name: "Roll back the selected step",
+        key: "U",
+        run: actions::rollback_edits,
+    },
+    Command {
+        name: "Rewind the session to before the step",
+        key: "R",
+        run: actions::rewind_session,
+    },
+    Command {
+        name: "Show the previous run of the step",
+        key: "[",
+        run: App::previous_attempt,
+    },
+    Command {
+        name: "Show the next run of the step",
+        key: "]",
+        run: App::next_attempt,
+    },
+    Command {
+        name: "Show or hide the environment changes of the step",
+        key: "E",
+        run: App::toggle_env_changes,
+    },
+    Command {
+        name: "Collapse the selected step (hide its sub-steps)",
+        key: "h",
+        run: App::collapse_selected,
+    },
+    Command {
+        name: "Expand the selected step (show its sub-steps)",
+        key: "l",
+        run: App::expand_selected,
+    },
+    Command {
+        name: "Toggle focus mode",
+        key: "z",
+        run: App::toggle_focus_mode,
+    },
+    Command {
+        name: "Show the keyboard shortcuts",
+        key: "?",
+        run: |app| app.mode = Mode::HelpModal,
+    },
+    Command {
+        name: "Show the debug log",
+        key: "F12",
+        run: |app| app.mode = Mode::DebugOverlay,
+    },
+    Command {
+        name: "Quit Compass",
+        key: "q",
+        run: |app| app.should_quit = true,
+    },
+];
+
+/// The commands matching the palette query, best match first.
+pub fn matching(palette: &PaletteState) -> Vec<&'static Command> {
+    palette
+        .matches(COMMANDS.iter().map(|c| c.name))
+        .into_iter()
+        .map(|i| &COMMANDS[i])
+        .collect()
+}
+
+/// Opens the command palette.
+pub fn open(app: &mut App) {
+    if app.mode != Mode::Normal {
+        return;
+    }
+    app.palette.reset();
+    app.mode = Mode::CommandPalette;
+}
+
+/// Runs the highlighted command.
+pub fn run(app: &mut App) {
+    if app.mode != Mode::CommandPalette {
+        return;
+    }
+    app.mode = Mode::Normal;
+    if let Some(command) = matching(&app.palette).get(app.palette.selected) {
+        (command.run)(app);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_readme;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_palette_runs_best_match() {
+        let (steps, _) = parse_readme("# Build\n```bash\nmake\n```\n");
+        let mut app = App::new(steps, PathBuf::from("README.md"), false);
+
+        open(&mut app);
+        assert_eq!(app.mode, Mode::CommandPalette);
+        for c in "duplicate".chars() {
+            app.palette.push(c);
+        }
+        assert_eq!(matching(&app.palette)[0].key, "D");
+
+        run(&mut app);
+        assert_eq!(app.mode, Mode::Normal);
+        assert_eq!(app.steps.len(), 2);
+        assert_eq!(app.steps[1].title, "Build (copy)");
+    }
+}
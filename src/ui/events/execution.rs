@@ -12,23 +12,47 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::core::analysis::edits;
+use crate::core::collab::session::CollabSession;
+use crate::core::executor::BackgroundRun;
 use crate::core::executor::engine::CommandBuilder;
+use crate::core::executor::engine::builtin::BuiltinHandler;
+use crate::core::executor::engine::session::STDERR_TAG;
 use crate::core::executor::languages::get_language_handler;
+use crate::core::executor::security::policy::{self, Decision, PolicyInput};
+use crate::core::executor::security::redact::Redactor;
 use crate::core::executor::security::safety::SafetyShield;
 use crate::core::executor::security::validator::DependencyValidator;
-use crate::core::models::StepStatus;
+use crate::core::executor::{cloud, infra, prerequisites, restart};
+use crate::core::infrastructure::config::SafetyPolicy;
+use crate::core::models::{StepScope, StepStatus};
 use crate::ui::app::App;
-use crate::ui::state::{ExecutionMessage, Mode};
+use crate::ui::state::{Checkpoint, ContextChange, ExecutionMessage, Mode};
+use crate::ui::widgets::details::description_lines;
+use std::sync::{Arc, mpsc};
 
 /// Polls for messages from the execution thread and updates the UI state.
 pub fn update(app: &mut App) {
+    app.poll_docker_check();
+    app.poll_cloud_probe();
+    poll_secret_fetch(app);
     let messages = app.execution_manager.poll_messages();
+    // Save the session once a run ends, so that it survives a crash
+    let finished = messages
+        .iter()
+        .any(|m| matches!(m, ExecutionMessage::Finished(..)));
 
     for message in messages {
         match message {
             ExecutionMessage::OutputPartial(i, partial) => {
                 if let Some(step) = app.steps.get_mut(i) {
                     crate::ui::utils::append_output(&mut step.output, &partial);
+                    if app.execution_manager.executor.context.separate_streams {
+                        match partial.strip_prefix(STDERR_TAG) {
+                            Some(text) => step.stderr.push_str(text),
+                            None => step.stdout.push_str(&partial),
+                        }
+                    }
 
                     if let Some(session) = &app.collab
                         && session.is_host
@@ -42,37 +66,39 @@ pub fn update(app: &mut App) {
                     }
                 }
             }
-            ExecutionMessage::Finished(i, status, new_dir, new_env) => {
+            ExecutionMessage::Finished(i, status, new_dir, new_env, record) => {
+                if app.mode == Mode::Interactive && app.list_state.selected() == Some(i) {
+                    app.mode = Mode::Normal;
+                }
+                if app.rolling_back.remove(&i) {
+                    finish_rollback(app, i, status);
+                    continue;
+                }
+                if let Some(before) = app.planning.remove(&i) {
+                    finish_plan(app, i, status, before);
+                    continue;
+                }
                 let mut recommendation = None;
+                let mut restart_notice = None;
+                let mut captured = Vec::new();
 
                 let scroll_target = if let Some(step) = app.steps.get_mut(i) {
+                    // Assertions decide the status of a step that has some
+                    let failures = step.expect.as_ref().map_or_else(Vec::new, |expect| {
+                        expect.check(status, record.exit_code, &step.output)
+                    });
+                    let status = match &step.expect {
+                        Some(_) if failures.is_empty() => StepStatus::Success,
+                        Some(_) => StepStatus::Failed,
+                        None => status,
+                    };
                     step.status = status;
+                    broadcast_status(app.collab.as_ref(), i, status);
 
-                    // Broadcast status change if host
-                    if let Some(session) = &app.collab
-                        && session.is_host
-                    {
-                        let status_str = match status {
-                            StepStatus::Running => "Running",
-                            StepStatus::Success => "Success",
-                            StepStatus::Failed => "Failed",
-                            StepStatus::Skipped => "Skipped",
-                            StepStatus::Pending => "Pending",
-                        }
-                        .to_string();
-                        if let Some(tx) = &session.tx {
-                            let _ =
-                                tx.send(crate::core::collab::events::CompassEvent::StatusChanged {
-                                    index: i,
-                                    status: status_str,
-                                });
-                        }
-                    }
-
-                    if status == StepStatus::Failed {
+                    if status.is_failure() {
                         // Trigger on_failure hook
                         if app.hooks_trusted
-                            && let Some(config) = &app.hooks
+                            && let Some(config) = &app.manifest
                         {
                             crate::core::ecosystem::hooks::trigger_hook(
                                 &config.on_failure,
@@ -82,9 +108,11 @@ pub fn update(app: &mut App) {
                         recommendation =
                             crate::core::analysis::recovery::analyze_error(&step.output);
                     } else if status == StepStatus::Success {
+                        restart_notice = restart::detect(step, &step.output);
+                        captured = step.captured(&step.output);
                         // Trigger on_success hook
                         if app.hooks_trusted
-                            && let Some(config) = &app.hooks
+                            && let Some(config) = &app.manifest
                         {
                             crate::core::ecosystem::hooks::trigger_hook(
                                 &config.on_success,
@@ -96,12 +124,41 @@ pub fn update(app: &mut App) {
                     let finish_status = match status {
                         StepStatus::Success => "✅ Execution finished successfully.",
                         StepStatus::Failed => "❌ Execution failed.",
+                        StepStatus::Cancelled => "⛔ Execution cancelled.",
+                        StepStatus::TimedOut => "⏱️ Execution timed out.",
                         _ => "",
                     };
                     step.output.push_str("\n\n---\n");
+                    for failure in &failures {
+                        step.output.push_str(&format!("✗ {failure}\n"));
+                    }
                     step.output.push_str(finish_status);
+                    if !captured.is_empty() {
+                        let names: Vec<&str> =
+                            captured.iter().map(|(name, _)| name.as_str()).collect();
+                        step.output
+                            .push_str(&format!("\n📥 Captured: {}", names.join(", ")));
+                    }
+                    if let Some(notice) = &restart_notice {
+                        step.output.push_str(&format!(
+                            "\n🔁 {notice}\nThe next steps are blocked until you confirm it is done."
+                        ));
+                    }
+
+                    let duration_ms = record.duration_ms;
+                    step.record_run(Some(duration_ms), record.exit_code);
+                    step.execution = Some(record);
+                    // The step may have installed binaries reported missing before
+                    crate::core::executor::binaries::forget_missing();
 
-                    let description_height = step.description.lines().count() + 2;
+                    if status == StepStatus::Success
+                        && let Some(config) = app.config_manager.as_mut()
+                    {
+                        config.record_duration(&step.title, duration_ms);
+                        let _ = config.save();
+                    }
+
+                    let description_height = description_lines(&step.description).len() + 1;
                     let code_blocks_height: usize = step
                         .code_blocks
                         .iter()
@@ -115,17 +172,235 @@ pub fn update(app: &mut App) {
                     0
                 };
 
+                if let Some(notice) = restart_notice {
+                    tracing::info!(step = i, reason = %notice.reason, "restart required");
+                    app.restart_notice = Some((i, notice));
+                }
                 if let Some(rec) = recommendation {
                     app.recovery_suggestion = Some(rec);
                     app.mode = crate::ui::state::Mode::RecoveryAlert;
                 }
 
                 app.details_scroll = scroll_target;
-                app.execution_manager.executor.context.current_dir = new_dir;
-                app.execution_manager.executor.context.env_vars = new_env;
+                // Steps of a parallel group leave the shared context unchanged,
+                // except for the variables they capture
+                let context = &mut app.execution_manager.executor.context;
+                if app.steps.get(i).is_some_and(|s| s.group.is_some()) {
+                    context.env_vars.extend(captured);
+                    continue;
+                }
+                let before = context.snapshot();
+                context.current_dir = new_dir;
+                context.env_vars = new_env;
+                context.env_vars.extend(captured);
+                let diff = before.diff(&context.snapshot(), &context.secrets);
+                if !diff.is_empty() {
+                    app.context_history.push(ContextChange { step: i, before });
+                }
+                if let Some(step) = app.steps.get_mut(i) {
+                    step.context_diff = (!diff.is_empty()).then_some(diff);
+                }
+            }
+        }
+    }
+    if finished {
+        app.save_session();
+    }
+    app.poll_resources();
+    super::group::advance(app);
+    super::autopilot::advance(app);
+    super::tutorial::advance(app);
+}
+
+/// Sends the new status of a step to the guests, if hosting a session.
+pub fn broadcast_status(collab: Option<&CollabSession>, index: usize, status: StepStatus) {
+    if let Some(session) = collab
+        && session.is_host
+        && let Some(tx) = &session.tx
+    {
+        let status = match status {
+            StepStatus::Running => "Running",
+            StepStatus::Success => "Success",
+            StepStatus::Failed => "Failed",
+            StepStatus::Skipped => "Skipped",
+            StepStatus::Cancelled => "Cancelled",
+            StepStatus::TimedOut => "TimedOut",
+            StepStatus::Pending => "Pending",
+        }
+        .to_string();
+        let _ = tx.send(crate::core::collab::events::CompassEvent::StatusChanged { index, status });
+    }
+}
+
+/// Ends the run of a step's `rollback` command: the step is pending again
+/// once undone. Hooks, history and durations are left untouched.
+fn finish_rollback(app: &mut App, index: usize, status: StepStatus) {
+    let Some(step) = app.steps.get_mut(index) else {
+        return;
+    };
+    let (status, message) = if status == StepStatus::Success {
+        (StepStatus::Pending, "↩ Rolled back.")
+    } else {
+        (StepStatus::Failed, "❌ Rollback failed.")
+    };
+    step.status = status;
+    step.output.push_str("\n\n---\n");
+    step.output.push_str(message);
+    broadcast_status(app.collab.as_ref(), index, status);
+}
+
+/// Starts the `rollback` command of a step, after it ran.
+pub fn start_rollback(app: &mut App, index: usize) {
+    if app.read_only {
+        return;
+    }
+    let Some(step) = app.steps.get_mut(index) else {
+        return;
+    };
+    let Some(command) = step.rollback.clone() else {
+        return;
+    };
+    step.status = StepStatus::Running;
+    step.output
+        .push_str(&format!("\n\n---\n↩ Rolling back: {command}\n"));
+    let mode = step.context_mode;
+    let timeout = step.timeout();
+    app.execution_manager.executor.scope = StepScope {
+        verify: None,
+        ..step.scope.clone()
+    };
+    app.rolling_back.insert(index);
+    broadcast_status(app.collab.as_ref(), index, StepStatus::Running);
+    let sandbox = app.is_sandboxed(index);
+    app.execution_manager.execute_background(
+        index,
+        BackgroundRun {
+            content: command,
+            mode,
+            timeout,
+            sandbox,
+            ..Default::default()
+        },
+    );
+}
+
+/// Reads the secrets the user agreed to fetch in the background (see
+/// `confirm_secrets`).
+pub fn start_secret_fetch(app: &mut App) {
+    let requests = std::mem::take(&mut app.secret_requests);
+    let cache = Arc::clone(&app.secret_cache);
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let results = requests
+            .into_iter()
+            .map(|(name, source)| {
+                let value = cache.resolve(&source).map_err(|e| format!("{e:#}"));
+                (name, value)
+            })
+            .collect();
+        let _ = tx.send(results);
+    });
+    app.secret_fetch = Some(rx);
+}
+
+/// Fills the placeholders with the secrets once they are read, and runs the
+/// step. Those that could not be read are asked for instead.
+fn poll_secret_fetch(app: &mut App) {
+    let Some(rx) = &app.secret_fetch else {
+        return;
+    };
+    let results = match rx.try_recv() {
+        Ok(results) => results,
+        Err(mpsc::TryRecvError::Empty) => return,
+        Err(mpsc::TryRecvError::Disconnected) => Vec::new(),
+    };
+    app.secret_fetch = None;
+    if app.mode != Mode::SecretConsent {
+        return;
+    }
+    let mut errors = Vec::new();
+    for (name, value) in results {
+        match value {
+            Ok(value) => {
+                app.execution_manager.executor.context.add_secret(&value);
+                app.modal.variable_store.insert(name, value);
+            }
+            Err(e) => {
+                errors.push(format!("{name}: {e}"));
+                app.secrets_unread.insert(name);
             }
         }
     }
+    app.mode = Mode::Normal;
+    app.safety_pattern = None;
+    perform_execution(app, false);
+    if !errors.is_empty() && app.mode == Mode::InputModal {
+        app.modal.error = Some(format!(
+            "Could not read {}: type the value instead",
+            errors.join("; ")
+        ));
+    }
+}
+
+/// Runs the `terraform plan` of a step that applies one, so the user reviews
+/// it before anything is applied.
+fn start_plan(app: &mut App, index: usize, plan: String) {
+    let Some(step) = app.steps.get_mut(index) else {
+        return;
+    };
+    app.planning.insert(index, step.status);
+    step.status = StepStatus::Running;
+    step.output = format!("📋 Planning before applying: {plan}\n\n");
+    step.stdout.clear();
+    step.stderr.clear();
+    let mode = step.context_mode;
+    let timeout = step.timeout();
+    app.execution_manager.executor.scope = StepScope {
+        verify: None,
+        ..step.scope.clone()
+    };
+    app.attempt_view = None;
+    // The apply asks for the placeholders again once the plan is approved
+    app.modal.required_placeholders.clear();
+    broadcast_status(app.collab.as_ref(), index, StepStatus::Running);
+    let sandbox = app.is_sandboxed(index);
+    app.execution_manager.execute_background(
+        index,
+        BackgroundRun {
+            content: plan,
+            mode,
+            timeout,
+            sandbox,
+            ..Default::default()
+        },
+    );
+}
+
+/// Ends the `terraform plan` run of a step: the plan is shown for approval,
+/// and the step goes back to its status before it. Nothing is applied if the
+/// plan fails.
+fn finish_plan(app: &mut App, index: usize, status: StepStatus, before: StepStatus) {
+    let Some(step) = app.steps.get_mut(index) else {
+        return;
+    };
+    let planned = status == StepStatus::Success;
+    let (status, message) = match status {
+        StepStatus::Success => (before, "📋 Review the plan: Enter applies it, Esc cancels."),
+        StepStatus::Cancelled => (before, "⛔ Plan cancelled: nothing was applied."),
+        _ => (
+            StepStatus::Failed,
+            "❌ The plan failed: nothing was applied.",
+        ),
+    };
+    step.status = status;
+    step.output.push_str("\n\n---\n");
+    step.output.push_str(message);
+    broadcast_status(app.collab.as_ref(), index, status);
+    if planned && app.mode == Mode::Normal {
+        app.list_state.select(Some(index));
+        app.diff_scroll = 0;
+        app.mode = Mode::PlanReview;
+    }
 }
 
 /// Executes the currently selected step (Non-blocking).
@@ -135,12 +410,48 @@ pub fn execute_selected(app: &mut App) {
     {
         return; // Guest cannot execute
     }
+    if app.read_only && !app.dry_run {
+        return;
+    }
+    app.inline_choice = None;
+    let Some(i) = app.list_state.selected() else {
+        return;
+    };
+    let idle = app
+        .steps
+        .get(i)
+        .is_some_and(|s| s.status != StepStatus::Running);
+    let preview = app.dry_run;
+    perform_execution(app, false);
+    if idle && !preview {
+        super::group::start(app, i);
+    }
+}
+
+/// Runs the `number`-th inline command of the selected step (1-based).
+pub fn execute_inline(app: &mut App, number: usize) {
+    if let Some(session) = &app.collab
+        && !session.is_host
+    {
+        return; // Guest cannot execute
+    }
+    if app.read_only {
+        return;
+    }
+    let Some(i) = app.list_state.selected() else {
+        return;
+    };
+    if number == 0 || number > app.inline_commands(i).len() {
+        return;
+    }
+    app.inline_choice = Some(number - 1);
     perform_execution(app, false);
 }
 
 /// Internal helper to handle execution flow with safety checks.
 pub fn perform_execution(app: &mut App, bypass_safety: bool) {
-    if app.mode != Mode::Normal {
+    // Read-only mode only previews commands
+    if app.mode != Mode::Normal || (app.read_only && !app.dry_run) {
         return;
     }
 
@@ -148,13 +459,16 @@ pub fn perform_execution(app: &mut App, bypass_safety: bool) {
         // Check if already running
         if let Some(step) = app.steps.get(i) {
             #[allow(clippy::collapsible_if)]
-            if step.status == StepStatus::Running {
+            if step.status == StepStatus::Running && !app.dry_run {
                 return;
             }
         }
 
-        // Check conditions
-        let should_skip = if let Some(step) = app.steps.get(i) {
+        // Check conditions (unless the user force-enabled the step or only previews it)
+        let should_skip = if let Some(step) = app.steps.get(i)
+            && !app.forced_steps.contains(&i)
+            && !app.dry_run
+        {
             if let Some(condition) = &step.condition {
                 use crate::core::executor::conditions::evaluator::{
                     ConditionEvaluator, StandardEvaluator,
@@ -176,50 +490,233 @@ pub fn perform_execution(app: &mut App, bypass_safety: bool) {
             return;
         }
 
-        // Check if we need to prompt for placeholders.
-        let step_placeholders = CommandBuilder::get_required_placeholders(&app.steps[i]);
-
-        if !step_placeholders.is_empty() && app.modal.required_placeholders.is_empty() {
-            app.modal.reset(step_placeholders);
-
-            // Pre-fill with previous value if exists (from config or previous input)
-            if !app.modal.required_placeholders.is_empty() {
-                let first_var = &app.modal.required_placeholders[0];
-                app.modal.input_buffer = app
-                    .modal
-                    .variable_store
-                    .get(first_var)
-                    .cloned()
-                    .unwrap_or_default();
+        // Steps it requires must have succeeded, unless the user runs it anyway
+        if app.prerequisites_override != Some(i) && !app.dry_run {
+            let unmet = prerequisites::unmet(&app.steps, i);
+            if !unmet.is_empty() {
+                let titles: Vec<String> = unmet
+                    .iter()
+                    .map(|&p| format!("{} (step {})", app.steps[p].title.trim(), p + 1))
+                    .collect();
+                app.safety_pattern = Some(format!(
+                    "This step requires steps that have not succeeded yet: {}.",
+                    titles.join(", ")
+                ));
+                app.mode = Mode::PrerequisiteAlert;
+                return;
+            }
+        }
+
+        // A reboot or new login asked for by an earlier step comes first
+        if let Some((source, notice)) = &app.restart_notice
+            && !app.dry_run
+            && restart::blocks(&app.steps, *source, i)
+        {
+            app.safety_pattern = Some(format!(
+                "{notice}\n\nAsked for by step {} ({}), before the steps after it.",
+                source + 1,
+                app.steps[*source].title.trim()
+            ));
+            app.mode = Mode::RestartAlert;
+            return;
+        }
+
+        // Check if we need to prompt for placeholders or missing environment variables.
+        let mut step_placeholders = CommandBuilder::get_required_placeholders(&app.steps[i]);
+        // Values captured from the output of earlier steps are not asked for
+        let context = &app.execution_manager.executor.context;
+        let captured: Vec<(String, String)> = step_placeholders
+            .iter()
+            .filter(|name| app.steps.iter().any(|s| s.captures_variable(name)))
+            .filter_map(|name| Some((name.clone(), context.env_vars.get(name)?.clone())))
+            .collect();
+        step_placeholders.retain(|name| !captured.iter().any(|(n, _)| n == name));
+        app.modal.variable_store.extend(captured);
+        // Values read from an env file are only asked for when the runbook rejects them
+        step_placeholders.retain(|name| {
+            let Some(value) = app
+                .modal
+                .variable_store
+                .get(name)
+                .filter(|_| app.modal.imported.contains(name))
+            else {
+                return true;
+            };
+            app.placeholder_spec(name)
+                .is_some_and(|spec| spec.validate(value).is_err())
+                || app.steps[i].check_choice(name, value).is_err()
+        });
+        // Placeholders read from a secret manager are not asked for
+        if app.modal.required_placeholders.is_empty() {
+            let mut requests = Vec::new();
+            let mut typed = Vec::new();
+            for name in step_placeholders {
+                let source = app.placeholder_spec(&name).and_then(|s| s.source.clone());
+                match source.filter(|_| !app.secrets_unread.contains(&name)) {
+                    Some(source) => match app.secret_cache.get(&source) {
+                        Some(value) => {
+                            app.execution_manager.executor.context.add_secret(&value);
+                            app.modal.variable_store.insert(name, value);
+                        }
+                        None => requests.push((name, source)),
+                    },
+                    None => typed.push(name),
+                }
+            }
+            step_placeholders = typed;
+            if !requests.is_empty() {
+                let sources: Vec<String> = requests
+                    .iter()
+                    .map(|(name, source)| format!("  {name} ← {source}"))
+                    .collect();
+                app.safety_pattern = Some(format!(
+                    "Step {} reads secrets from secret managers:\n\n{}\n\nThey are kept in memory for this session only, never saved.",
+                    i + 1,
+                    sources.join("\n")
+                ));
+                app.secret_requests = requests;
+                app.mode = Mode::SecretConsent;
+                return;
+            }
+        }
+        let missing_env = app
+            .execution_manager
+            .executor
+            .context
+            .missing_env(&app.steps[i].required_env);
+
+        if (!step_placeholders.is_empty() || !missing_env.is_empty())
+            && app.modal.required_placeholders.is_empty()
+        {
+            app.modal
+                .reset(step_placeholders, missing_env, &app.steps[i]);
+
+            // Pre-fill with the previous value (from config or previous input) or the default
+            if let Some(first_var) = app.modal.required_placeholders.first() {
+                app.modal.input_buffer = app.placeholder_prefill(first_var);
+                app.modal.fit_choice();
             }
 
             app.mode = Mode::InputModal;
             return;
         }
 
-        let content = CommandBuilder::build_command(&app.steps[i], &app.modal.variable_store);
-        app.modal.required_placeholders.clear();
+        let inline = app
+            .inline_choice
+            .and_then(|n| app.inline_commands(i).get(n))
+            .cloned();
+        let content = match &inline {
+            Some(command) => format!("{command}\n"),
+            None => CommandBuilder::build_command(&app.steps[i], &app.modal.variable_store),
+        };
+        let writes = CommandBuilder::build_writes(&app.steps[i], &app.modal.variable_store);
+
+        // Dry run: show what would run, as the execution context would see it
+        if app.dry_run {
+            let mut context = app.execution_manager.executor.context.clone();
+            let preview = match inline {
+                Some(_) => BuiltinHandler::annotate(content.trim_end(), &mut context),
+                None => {
+                    CommandBuilder::dry_run(&app.steps[i], &app.modal.variable_store, &mut context)
+                }
+            };
+            app.modal.required_placeholders.clear();
+            app.dry_run = false;
+            app.dry_run_preview = Some(Redactor::new(context.secrets).redact(&preview));
+            app.diff_scroll = 0;
+            app.mode = Mode::DryRunPreview;
+            return;
+        }
 
-        if content.trim().is_empty() {
+        if content.trim().is_empty() && writes.is_empty() {
+            app.modal.required_placeholders.clear();
+            app.prerequisites_override = None;
+            app.plan_approved = None;
+            app.diff_approved = None;
+            app.cluster_confirmed = None;
+            app.cloud_confirmed = None;
+            app.policy_confirmed = None;
             return;
         }
 
         let language = app.steps[i]
             .code_blocks
-            .first()
+            .iter()
+            .find(|cb| cb.write_to.is_none())
             .and_then(|cb| cb.language.as_deref())
             .map(ToString::to_string);
 
+        // Organization policies, for the command and its verification
+        if app.policy_confirmed != Some(i) {
+            let verify = app.steps[i].scope.verify.as_deref();
+            let decision = std::iter::once((content.as_str(), language.as_deref()))
+                .chain(verify.map(|verify| (verify, None)))
+                .map(|(command, language)| {
+                    policy::evaluate(&PolicyInput {
+                        command,
+                        language,
+                        cwd: &app.execution_manager.executor.context.current_dir,
+                        remote: app.is_remote,
+                        sandbox: app.is_sandboxed(i),
+                    })
+                })
+                .find(|decision| *decision != Decision::Allow)
+                .unwrap_or(Decision::Allow);
+            match decision {
+                Decision::Allow => {}
+                Decision::Confirm(reason) => {
+                    app.safety_pattern = Some(reason);
+                    app.mode = Mode::PolicyAlert;
+                    return;
+                }
+                Decision::Deny(reason) => {
+                    app.steps[i].output = format!("🛑 Denied by policy: {reason}.\n");
+                    app.modal.required_placeholders.clear();
+                    app.prerequisites_override = None;
+                    app.plan_approved = None;
+                    app.diff_approved = None;
+                    app.cluster_confirmed = None;
+                    app.cloud_confirmed = None;
+                    return;
+                }
+            }
+        }
+
+        // Commands with a large blast radius: review the plan, the cluster and
+        // the cloud account
+        if is_shell_language(language.as_deref()) && !bypass_safety {
+            if app.plan_approved != Some(i)
+                && let Some(plan) = infra::plan_before_apply(&content)
+            {
+                start_plan(app, i, plan);
+                return;
+            }
+            if app.cluster_confirmed != Some(i)
+                && let Some(change) = infra::kubectl_change(&content)
+            {
+                let context = &app.execution_manager.executor.context;
+                app.safety_pattern = Some(change.describe(&context.env_vars, &context.current_dir));
+                app.mode = Mode::ClusterAlert;
+                return;
+            }
+            if app.cloud_confirmed != Some(i)
+                && let Some(call) = cloud::find(&content)
+            {
+                app.start_cloud_probe(call);
+                return;
+            }
+        }
+
+        let mut file_edits = writes.clone();
+        if is_shell_language(language.as_deref()) {
+            file_edits.extend(edits::detect(&content));
+        }
+        let edited_files = &app.steps[i].edited_files;
+
         // Safety Checks
         if !bypass_safety {
             // 1. Dependency Check
-            let is_shell = language.is_none()
-                || matches!(
-                    language.as_deref(),
-                    Some("bash" | "sh" | "shell" | "zsh" | "fish" | "cmd" | "powershell")
-                );
-
-            if is_shell {
+            if is_shell_language(language.as_deref()) {
                 if let Err(e) = DependencyValidator::validate(&content) {
                     app.safety_pattern = Some(e);
                     app.mode = Mode::DependencyAlert;
@@ -241,28 +738,177 @@ pub fn perform_execution(app: &mut App, bypass_safety: bool) {
             let patterns = handler.get_dangerous_patterns();
             let check_result = SafetyShield::check(&content, patterns);
 
-            if app.is_remote {
+            // Indented blocks may be sample output rather than commands
+            let indented = app.steps[i].code_blocks.iter().any(|cb| cb.indented);
+            if app.is_remote || indented {
+                let reason = if app.is_remote {
+                    "Remote Source (Strict Mode)"
+                } else {
+                    "Indented code block (may not be a command)"
+                };
                 app.safety_pattern = Some(
                     check_result
                         .map(ToString::to_string)
-                        .unwrap_or_else(|| "Remote Source (Strict Mode)".to_string()),
+                        .unwrap_or_else(|| reason.to_string()),
                 );
                 app.mode = Mode::SafetyAlert;
                 return;
             }
 
-            #[allow(clippy::collapsible_if)]
             if let Some(pattern) = check_result {
-                app.safety_pattern = Some(pattern.to_string());
-                app.mode = Mode::SafetyAlert;
-                return;
+                match app.settings.safety_policy {
+                    SafetyPolicy::Prompt => {
+                        app.safety_pattern = Some(pattern.to_string());
+                        app.mode = Mode::SafetyAlert;
+                        return;
+                    }
+                    SafetyPolicy::Strict => {
+                        app.steps[i].output = format!(
+                            "🛑 Blocked by the strict safety policy: '{pattern}'.\nRun `compass setup` to change it.\n"
+                        );
+                        app.modal.required_placeholders.clear();
+                        app.prerequisites_override = None;
+                        app.plan_approved = None;
+                        app.diff_approved = None;
+                        app.cluster_confirmed = None;
+                        app.cloud_confirmed = None;
+                        app.policy_confirmed = None;
+                        return;
+                    }
+                    SafetyPolicy::Relaxed => {}
+                }
             }
         }
 
-        // Execute background
+        // File edits: show what will change before writing, also when a
+        // safety alert was confirmed
+        if app.diff_approved != Some(i) && (!file_edits.is_empty() || !edited_files.is_empty()) {
+            app.edit_preview = edits::preview(
+                &file_edits,
+                edited_files,
+                &app.execution_manager.executor.context.current_dir,
+            );
+            app.diff_scroll = 0;
+            app.mode = Mode::DiffPreview;
+            return;
+        }
+
+        // Back up the edited files so the step can be rolled back
+        if !file_edits.is_empty() || !edited_files.is_empty() {
+            let paths = file_edits
+                .iter()
+                .map(|e| e.path.as_str())
+                .chain(edited_files.iter().map(String::as_str));
+            let backups = edits::backup(paths, &app.execution_manager.executor.context.current_dir);
+            app.file_backups.insert(i, backups);
+        }
+
+        // The user confirmed what the policies ask for: the executor runs it too
+        app.execution_manager.executor.policy_confirmed = app.policy_confirmed == Some(i);
+
+        // Execute background. The values were asked for this run only: the next
+        // one prompts again (confirmation alerts above keep them until then).
+        app.modal.required_placeholders.clear();
+        app.prerequisites_override = None;
+        app.plan_approved = None;
+        app.diff_approved = None;
+        app.cluster_confirmed = None;
+        app.cloud_confirmed = None;
+        app.policy_confirmed = None;
+        app.checkpoints.push(Checkpoint {
+            step: i,
+            statuses: app.steps.iter().map(|s| s.status).collect(),
+            context: app.execution_manager.executor.context.snapshot(),
+            placeholders: app.modal.variable_store.clone(),
+            context_changes: app.context_history.len(),
+        });
         app.steps[i].status = StepStatus::Running;
         app.steps[i].output = String::new();
-        app.execution_manager
-            .execute_background(i, content, language, bypass_safety);
+        app.steps[i].stdout.clear();
+        app.steps[i].stderr.clear();
+        app.steps[i].context_diff = None;
+        app.attempt_view = None;
+        let mode = app.steps[i].context_mode;
+        let timeout = app.steps[i].timeout();
+        app.execution_manager.executor.scope = app.steps[i].scope.clone();
+        let sandbox = app.is_sandboxed(i);
+        app.execution_manager.execute_background(
+            i,
+            BackgroundRun {
+                content,
+                writes,
+                language,
+                bypass_safety,
+                mode,
+                timeout,
+                sandbox,
+            },
+        );
+    }
+}
+
+/// Whether code in this language runs in a shell.
+fn is_shell_language(language: Option<&str>) -> bool {
+    language.is_none()
+        || matches!(
+            language,
+            Some("bash" | "sh" | "shell" | "zsh" | "fish" | "cmd" | "powershell")
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_readme;
+    use crate::ui::events::handlers;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_confirmed_safety_alert_still_previews_edits() {
+        let content = "# Configure\n<!-- compass:write path=\"app.env\" -->\n```ini\nKEY=1\n```\n";
+        let (steps, _) = parse_readme(content);
+        let mut app = App::new(steps, PathBuf::from("https://example.com/r.md"), true);
+
+        execute_selected(&mut app);
+        assert_eq!(app.mode, Mode::SafetyAlert);
+
+        handlers::confirm_safety(&mut app);
+        assert_eq!(app.mode, Mode::DiffPreview);
+        assert!(!app.edit_preview.is_empty());
+    }
+
+    #[test]
+    fn test_unmet_condition_skips_unless_forced() {
+        let content = "<!-- compass:if os=\"plan9\" -->\n# Install\n```bash\necho installed\n```\n<!-- compass:endif -->\n";
+        let (steps, _) = parse_readme(content);
+        let mut app = App::new(steps, PathBuf::from("README.md"), false);
+
+        execute_selected(&mut app);
+        assert_eq!(app.steps[0].status, StepStatus::Skipped);
+
+        app.toggle_force_selected();
+        assert!(app.forced_steps.contains(&0));
+        app.toggle_force_selected();
+        assert!(app.forced_steps.is_empty());
+    }
+
+    #[test]
+    fn test_read_only_refuses_execution() {
+        let (steps, _) = parse_readme("# Build\n```bash\necho built\n```\n");
+        let mut app = App::new(steps, PathBuf::from("README.md"), false);
+        app.read_only = true;
+
+        execute_selected(&mut app);
+        execute_inline(&mut app, 1);
+        perform_execution(&mut app, true);
+        start_rollback(&mut app, 0);
+        assert_eq!(app.steps[0].status, StepStatus::Pending);
+        assert!(app.checkpoints.is_empty());
+        assert_eq!(app.mode, Mode::Normal);
+
+        // Commands can still be previewed
+        handlers::preview_command(&mut app);
+        assert_eq!(app.mode, Mode::DryRunPreview);
+        assert_eq!(app.steps[0].status, StepStatus::Pending);
     }
 }
@@ -0,0 +1,58 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parallel groups: running a step of a `compass:group parallel` starts the
+//! other steps of its group too, without waiting for it to finish.
+//!
+//! Each step goes through the usual execution flow; the next one starts once
+//! its placeholder prompts and confirmations are answered.
+
+use super::execution::perform_execution;
+use crate::core::executor::groups;
+use crate::core::models::StepStatus;
+use crate::ui::app::App;
+use crate::ui::state::Mode;
+
+/// Queues the other steps of the group of `steps[index]` that are not
+/// running, then starts them.
+pub fn start(app: &mut App, index: usize) {
+    if app.steps.get(index).is_none_or(|s| s.group.is_none()) {
+        return;
+    }
+    for i in groups::members(&app.steps, index) {
+        if i != index && !app.group_queue.contains(&i) {
+            app.group_queue.push_back(i);
+        }
+    }
+    advance(app);
+}
+
+/// Starts the queued steps one after the other, waiting while a prompt is
+/// open. Called on every tick.
+pub fn advance(app: &mut App) {
+    while app.mode == Mode::Normal
+        && let Some(i) = app.group_queue.pop_front()
+    {
+        if app
+            .steps
+            .get(i)
+            .is_some_and(|s| s.is_executable() && s.status != StepStatus::Running)
+        {
+            app.list_state.select(Some(i));
+            app.inline_choice = None;
+            app.attempt_view = None;
+            perform_execution(app, false);
+        }
+    }
+}
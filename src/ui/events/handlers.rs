@@ -20,5 +20,11 @@
 //! - `execution`: Core execution logic
 //! - `actions`: User interaction actions
 
-pub use super::actions::{confirm_recovery, confirm_safety, export_report, submit_input};
-pub use super::execution::{execute_selected, update};
+pub use super::actions::{
+    add_scratch_step, cancel_running, check_dependencies, confirm_cloud, confirm_cluster,
+    confirm_diff, confirm_plan, confirm_policy, confirm_prerequisites, confirm_recovery,
+    confirm_restart, confirm_safety, confirm_secrets, export_context, export_report, forward_key,
+    open_snippet_palette, preview_command, revert_context, rewind_session, rollback_edits,
+    run_snippet, start_interactive, start_scratch_step, submit_input,
+};
+pub use super::execution::{execute_inline, execute_selected, update};
@@ -13,9 +13,22 @@
 // limitations under the License.
 
 use crate::ui::app::App;
-use crate::ui::events::handlers;
+use crate::ui::events::{autopilot, commands, handlers};
 use crate::ui::state::Mode;
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Describes a key for the crash report's trail of recent actions.
+///
+/// Characters typed into a prompt or forwarded to a running step may be a
+/// secret or a password, so only the mode they were typed in is kept.
+fn described_key(mode: &Mode, code: KeyCode) -> String {
+    match (mode, code) {
+        (Mode::InputModal | Mode::ScratchInput | Mode::Interactive, KeyCode::Char(_)) => {
+            format!("Typing in {:?}", mode)
+        }
+        _ => format!("{:?} in {:?}", code, mode),
+    }
+}
 
 /// Handles key events and dispatches actions to the App.
 ///
@@ -27,8 +40,13 @@ use crossterm::event::{KeyCode, KeyEvent};
 /// * `app` - The application state.
 /// * `key` - The key event to handle.
 pub fn handle_input(app: &mut App, key: KeyEvent) {
+    crate::core::infrastructure::crash::record_action(described_key(&app.mode, key.code));
+
     match app.mode {
         Mode::Normal => match key.code {
+            KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                commands::open(app);
+            }
             KeyCode::Char('q') => app.should_quit = true,
             KeyCode::Down | KeyCode::Char('j') => app.next(),
             KeyCode::Up | KeyCode::Char('k') => app.previous(),
@@ -43,8 +61,47 @@ pub fn handle_input(app: &mut App, key: KeyEvent) {
             KeyCode::Char('s') => {
                 handlers::export_report(app);
             }
+            KeyCode::Char('e') => handlers::export_context(app),
+            KeyCode::Char('f') => app.toggle_force_selected(),
+            KeyCode::Char('b') => app.toggle_sandbox_selected(),
+            KeyCode::Char('[') => app.previous_attempt(),
+            KeyCode::Char(']') => app.next_attempt(),
+            KeyCode::Char('u') => handlers::revert_context(app),
+            KeyCode::Char('U') => handlers::rollback_edits(app),
+            KeyCode::Char('R') => handlers::rewind_session(app),
+            KeyCode::Char('v') => handlers::preview_command(app),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                handlers::cancel_running(app);
+            }
+            KeyCode::Char('x') => handlers::cancel_running(app),
+            KeyCode::Char('c') => handlers::check_dependencies(app),
+            KeyCode::Char('z') => app.toggle_focus_mode(),
+            KeyCode::Char('E') => app.toggle_env_changes(),
+            KeyCode::Char('i') => handlers::start_interactive(app),
+            KeyCode::Char('a') => handlers::start_scratch_step(app),
+            KeyCode::Char('A') => autopilot::toggle(app),
+            KeyCode::Char('S') => handlers::open_snippet_palette(app),
+            KeyCode::Char('D') => app.duplicate_selected(),
+            KeyCode::Char('<') => app.move_selected(false),
+            KeyCode::Char('>') => app.move_selected(true),
+            KeyCode::Char(c @ '1'..='9') => {
+                handlers::execute_inline(app, c as usize - '0' as usize);
+            }
+            KeyCode::Right | KeyCode::Char(' ' | 'n') if app.focus_mode => app.next(),
+            KeyCode::Left | KeyCode::Backspace | KeyCode::Char('p') if app.focus_mode => {
+                app.previous();
+            }
+            KeyCode::Left | KeyCode::Char('h') => app.collapse_selected(),
+            KeyCode::Right | KeyCode::Char('l') => app.expand_selected(),
+            KeyCode::F(12) => {
+                app.mode = Mode::DebugOverlay;
+            }
             _ => {}
         },
+        Mode::Interactive => match key.code {
+            KeyCode::Esc => app.mode = Mode::Normal,
+            _ => handlers::forward_key(app, key),
+        },
         Mode::InputModal => match key.code {
             KeyCode::Enter => {
                 handlers::submit_input(app);
@@ -52,14 +109,51 @@ pub fn handle_input(app: &mut App, key: KeyEvent) {
             KeyCode::Esc => {
                 app.cancel_modal();
             }
+            // Choice placeholders are picked from their list
+            KeyCode::Down | KeyCode::Tab => app.modal.cycle_choice(true),
+            KeyCode::Up | KeyCode::BackTab => app.modal.cycle_choice(false),
+            _ if app.modal.current_choices().is_some() => {}
             KeyCode::Char(c) => {
                 app.modal.input_buffer.push(c);
+                app.modal.error = None;
+            }
+            KeyCode::Backspace => {
+                app.modal.input_buffer.pop();
+                app.modal.error = None;
             }
+            _ => {}
+        },
+        Mode::ScratchInput => match key.code {
+            KeyCode::Enter => handlers::add_scratch_step(app),
+            KeyCode::Esc => app.cancel_modal(),
+            KeyCode::Char(c) => app.modal.input_buffer.push(c),
             KeyCode::Backspace => {
                 app.modal.input_buffer.pop();
             }
             _ => {}
         },
+        Mode::SnippetPalette => match key.code {
+            KeyCode::Enter => handlers::run_snippet(app),
+            KeyCode::Esc => app.cancel_modal(),
+            KeyCode::Down => app.palette.next(app.snippet_matches().len()),
+            KeyCode::Up => app.palette.previous(app.snippet_matches().len()),
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app.palette.push(c);
+            }
+            KeyCode::Backspace => app.palette.pop(),
+            _ => {}
+        },
+        Mode::CommandPalette => match key.code {
+            KeyCode::Enter => commands::run(app),
+            KeyCode::Esc => app.cancel_modal(),
+            KeyCode::Down => app.palette.next(commands::matching(&app.palette).len()),
+            KeyCode::Up => app.palette.previous(commands::matching(&app.palette).len()),
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app.palette.push(c);
+            }
+            KeyCode::Backspace => app.palette.pop(),
+            _ => {}
+        },
         Mode::SafetyAlert | Mode::DependencyAlert => match key.code {
             KeyCode::Enter => {
                 handlers::confirm_safety(app);
@@ -69,6 +163,71 @@ pub fn handle_input(app: &mut App, key: KeyEvent) {
             }
             _ => {}
         },
+        Mode::PrerequisiteAlert => match key.code {
+            KeyCode::Enter => handlers::confirm_prerequisites(app),
+            KeyCode::Esc => app.cancel_modal(),
+            _ => {}
+        },
+        Mode::RestartAlert => match key.code {
+            KeyCode::Enter => handlers::confirm_restart(app),
+            KeyCode::Esc => app.cancel_modal(),
+            _ => {}
+        },
+        Mode::ClusterAlert => match key.code {
+            KeyCode::Enter => handlers::confirm_cluster(app),
+            KeyCode::Esc => app.cancel_modal(),
+            _ => {}
+        },
+        Mode::SecretConsent => match key.code {
+            KeyCode::Enter => handlers::confirm_secrets(app),
+            KeyCode::Esc => app.cancel_modal(),
+            _ => {}
+        },
+        Mode::CloudCheck | Mode::CloudAlert => match key.code {
+            KeyCode::Enter => handlers::confirm_cloud(app),
+            KeyCode::Esc => app.cancel_modal(),
+            _ => {}
+        },
+        Mode::PolicyAlert => match key.code {
+            KeyCode::Enter => handlers::confirm_policy(app),
+            KeyCode::Esc => app.cancel_modal(),
+            _ => {}
+        },
+        Mode::PlanReview => match key.code {
+            KeyCode::Enter => handlers::confirm_plan(app),
+            KeyCode::Esc => app.cancel_modal(),
+            KeyCode::Down | KeyCode::Char('j') => {
+                app.diff_scroll = app.diff_scroll.saturating_add(1);
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                app.diff_scroll = app.diff_scroll.saturating_sub(1);
+            }
+            _ => {}
+        },
+        Mode::DiffPreview => match key.code {
+            KeyCode::Enter => handlers::confirm_diff(app),
+            KeyCode::Esc => {
+                app.edit_preview.clear();
+                app.cancel_modal();
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                app.diff_scroll = app.diff_scroll.saturating_add(1);
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                app.diff_scroll = app.diff_scroll.saturating_sub(1);
+            }
+            _ => {}
+        },
+        Mode::DryRunPreview => match key.code {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q' | 'v') => app.cancel_modal(),
+            KeyCode::Down | KeyCode::Char('j') => {
+                app.diff_scroll = app.diff_scroll.saturating_add(1);
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                app.diff_scroll = app.diff_scroll.saturating_sub(1);
+            }
+            _ => {}
+        },
         Mode::HelpModal => match key.code {
             KeyCode::Esc | KeyCode::Char('?' | 'q') => {
                 app.mode = Mode::Normal;
@@ -82,6 +241,19 @@ pub fn handle_input(app: &mut App, key: KeyEvent) {
             // Any key dismisses the notification
             app.cancel_modal();
         }
+        Mode::DebugOverlay => {
+            if matches!(key.code, KeyCode::Esc | KeyCode::F(12) | KeyCode::Char('q')) {
+                app.mode = Mode::Normal;
+            }
+        }
+        Mode::DependencyCheck => {
+            if matches!(
+                key.code,
+                KeyCode::Esc | KeyCode::Enter | KeyCode::Char('c' | 'q')
+            ) {
+                app.mode = Mode::Normal;
+            }
+        }
         Mode::RecoveryAlert => match key.code {
             KeyCode::Enter => {
                 handlers::confirm_recovery(app);
@@ -93,3 +265,52 @@ pub fn handle_input(app: &mut App, key: KeyEvent) {
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_readme;
+    use std::path::PathBuf;
+
+    fn press(app: &mut App, code: KeyCode) {
+        handle_input(app, KeyEvent::new(code, KeyModifiers::NONE));
+    }
+
+    #[test]
+    fn test_focus_mode_pages_through_steps() {
+        let (steps, _) = parse_readme("# One\n```bash\na\n```\n# Two\n```bash\nb\n```\n");
+        let mut app = App::new(steps, PathBuf::from("README.md"), false);
+
+        press(&mut app, KeyCode::Char('z'));
+        assert!(app.focus_mode);
+        press(&mut app, KeyCode::Char(' '));
+        assert_eq!(app.list_state.selected(), Some(1));
+        press(&mut app, KeyCode::Left);
+        assert_eq!(app.list_state.selected(), Some(0));
+
+        press(&mut app, KeyCode::Char('z'));
+        assert!(!app.focus_mode);
+        press(&mut app, KeyCode::Char(' '));
+        assert_eq!(app.list_state.selected(), Some(0));
+    }
+
+    #[test]
+    fn test_typed_characters_stay_out_of_crash_trail() {
+        assert_eq!(
+            described_key(&Mode::Normal, KeyCode::Char('x')),
+            "Char('x') in Normal"
+        );
+        assert_eq!(
+            described_key(&Mode::InputModal, KeyCode::Char('s')),
+            "Typing in InputModal"
+        );
+        assert_eq!(
+            described_key(&Mode::Interactive, KeyCode::Char('p')),
+            "Typing in Interactive"
+        );
+        assert_eq!(
+            described_key(&Mode::Interactive, KeyCode::Enter),
+            "Enter in Interactive"
+        );
+    }
+}
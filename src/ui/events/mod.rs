@@ -13,6 +13,10 @@
 // limitations under the License.
 
 pub mod actions;
+pub mod autopilot;
+pub mod commands;
 pub mod execution;
+pub mod group;
 pub mod handlers;
 pub mod input;
+pub mod tutorial;
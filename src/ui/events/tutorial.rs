@@ -0,0 +1,134 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Guided tour of `compass tutorial`: a lesson at a time, the TUI shows which
+//! key to press next and moves on once the user did what the lesson asks.
+//!
+//! Lessons check the state of the app rather than the keys pressed, so any
+//! way of doing it (arrows or `j`, a palette command) counts.
+
+use crate::core::models::StepStatus;
+use crate::ui::app::App;
+use crate::ui::state::Mode;
+
+/// A lesson of the tutorial.
+pub struct Lesson {
+    /// The key(s) to press, highlighted in the hint.
+    pub keys: &'static str,
+    /// What pressing them does.
+    pub hint: &'static str,
+    /// Whether the user did what the lesson asks.
+    done: fn(&App) -> bool,
+}
+
+/// Whether the step at `index` of the tutorial runbook succeeded.
+fn succeeded(app: &App, index: usize) -> bool {
+    app.steps
+        .get(index)
+        .is_some_and(|s| s.status == StepStatus::Success)
+}
+
+/// The lessons, in order. They expect the steps of the tutorial runbook.
+pub const LESSONS: &[Lesson] = &[
+    Lesson {
+        keys: "↓ or j",
+        hint: "Select the next step.",
+        done: |app| app.list_state.selected().is_some_and(|i| i >= 1),
+    },
+    Lesson {
+        keys: "Enter",
+        hint: "Run the selected step and see its output.",
+        done: |app| succeeded(app, 1),
+    },
+    Lesson {
+        keys: "↓ then Enter",
+        hint: "Run the next step. Type a value for its placeholder in the popup, then press Enter.",
+        done: |app| succeeded(app, 2),
+    },
+    Lesson {
+        keys: "?",
+        hint: "Open the list of every key.",
+        done: |app| app.mode == Mode::HelpModal,
+    },
+    Lesson {
+        keys: "Esc",
+        hint: "Close the list of keys.",
+        done: |app| app.mode == Mode::Normal,
+    },
+    Lesson {
+        keys: "e",
+        hint: "Save the context of the session (directory, environment, placeholders) to a file.",
+        done: |app| app.mode == Mode::ExportNotification,
+    },
+    Lesson {
+        keys: "any key",
+        hint: "Close the notification.",
+        done: |app| app.mode == Mode::Normal,
+    },
+    Lesson {
+        keys: "q",
+        hint: "That's it! Quit, or keep exploring: `compass demo` has more to try.",
+        done: |_| false,
+    },
+];
+
+/// The current lesson, with its position, if the tutorial is on.
+pub fn current(app: &App) -> Option<(usize, &'static Lesson)> {
+    let index = app.tutorial?;
+    LESSONS.get(index).map(|lesson| (index, lesson))
+}
+
+/// Moves on to the next lesson once the current one is done. Called on
+/// every tick.
+pub fn advance(app: &mut App) {
+    while let Some((index, lesson)) = current(app)
+        && (lesson.done)(app)
+    {
+        app.tutorial = Some(index + 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_readme;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_lessons_follow_the_app() {
+        let (steps, _) = parse_readme(
+            "# Welcome\n# Hello\n```bash\necho hi\n```\n# Greet\n```bash\necho <NAME>\n```\n",
+        );
+        let mut app = App::new(steps, PathBuf::from("README.md"), false);
+        app.tutorial = Some(0);
+
+        app.list_state.select(Some(1));
+        advance(&mut app);
+        assert_eq!(current(&app).map(|(i, _)| i), Some(1));
+
+        app.steps[1].status = StepStatus::Success;
+        app.steps[2].status = StepStatus::Success;
+        advance(&mut app);
+        assert_eq!(
+            current(&app).map(|(i, lesson)| (i, lesson.keys)),
+            Some((3, "?"))
+        );
+
+        app.mode = Mode::HelpModal;
+        advance(&mut app);
+        app.mode = Mode::Normal;
+        advance(&mut app);
+        assert_eq!(app.tutorial, Some(5));
+    }
+}
@@ -0,0 +1,791 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Linear Renderer
+//!
+//! An alternative to the full-screen view for terminal screen readers.
+//! Instead of redrawing the screen, it appends labeled lines to the terminal
+//! whenever something changes: the selected step, step statuses, new output
+//! and prompts. Key handling is the same as in the full-screen TUI.
+
+use crate::core::analysis::edits::DiffLine;
+use crate::core::executor::checker::install_hint;
+use crate::core::executor::conditions::evaluator::{ConditionEvaluator, StandardEvaluator};
+use crate::core::executor::groups;
+use crate::core::executor::restart::{self, RestartKind};
+use crate::core::infrastructure::logging::recent_events;
+use crate::core::models::{ContextDiff, Step, StepStatus};
+use crate::core::outline;
+use crate::ui::app::App;
+use crate::ui::events::commands;
+use crate::ui::events::tutorial::{self, LESSONS};
+use crate::ui::state::Mode;
+use crate::ui::utils::format_elapsed;
+use crate::ui::widgets::details::description_lines;
+use crate::ui::widgets::popups::help::SHORTCUTS;
+use std::collections::HashSet;
+use std::io::{self, Write};
+
+/// Number of log events printed for the debug overlay.
+const DEBUG_EVENTS: usize = 20;
+
+/// Remembers what was already announced, so that only changes get printed.
+#[derive(Default)]
+pub struct LinearView {
+    /// The step last described.
+    selected: Option<usize>,
+    /// The mode last announced (`None` before the first render).
+    mode: Option<Mode>,
+    /// The placeholder last prompted for.
+    placeholder: Option<usize>,
+    /// Number of characters of the input buffer already echoed.
+    echoed: usize,
+    /// The rejection of an input value last announced.
+    input_error: Option<String>,
+    /// The palette entry (or placeholder choice) last announced as
+    /// highlighted.
+    highlighted: Option<String>,
+    /// Last announced status of each step.
+    statuses: Vec<StepStatus>,
+    /// Number of bytes of each step's output already printed.
+    printed: Vec<usize>,
+    /// The step whose output was printed last, until something else is said.
+    output_step: Option<usize>,
+    /// Steps last announced as running on the other side of the sandbox setting.
+    sandbox_flipped: HashSet<usize>,
+    /// Whether the auto-pilot was last announced as on.
+    autopilot: bool,
+    /// Steps last announced as collapsed.
+    collapsed: HashSet<usize>,
+    /// The tutorial lesson last announced.
+    tutorial: Option<usize>,
+    /// Number of step additions and moves already taken into account.
+    steps_edited: usize,
+    /// Text waiting to be written by `flush`.
+    buffer: String,
+    /// Whether the cursor is at the start of a line.
+    at_line_start: bool,
+}
+
+impl LinearView {
+    /// Creates a view for the current state of the app, without announcing
+    /// the output steps already have.
+    pub fn new(app: &App) -> Self {
+        let mut view = Self {
+            at_line_start: true,
+            ..Default::default()
+        };
+        view.sync_steps(app);
+        view
+    }
+
+    /// Prints everything that changed since the last call.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the terminal cannot be written to.
+    pub fn render(&mut self, app: &App) -> io::Result<()> {
+        if self.mode.is_none() {
+            if let Some(title) = app.runbook_title() {
+                self.say(&title);
+            }
+            if app.read_only {
+                self.say("Read-only mode: steps are shown but not run.");
+            }
+            self.say(&format!(
+                "Compass, linear mode. {} steps. Up and down arrows move between steps, Enter runs a step, question mark lists the keys, q quits.",
+                app.steps.len()
+            ));
+        }
+        if self.statuses.len() != app.steps.len() || self.steps_edited != app.steps_edited {
+            // The steps were replaced (e.g. a collaboration snapshot), added, moved or rewound
+            self.sync_steps(app);
+            self.steps_edited = app.steps_edited;
+            self.selected = None;
+            self.sandbox_flipped.clone_from(&app.sandbox_flipped);
+            self.collapsed.clone_from(&app.collapsed);
+        }
+        self.announce_sandbox(app);
+        self.announce_autopilot(app);
+        self.announce_collapsed(app);
+
+        self.announce_output(app);
+        self.announce_statuses(app);
+        if app.mode == Mode::Normal && self.selected != app.list_state.selected() {
+            self.selected = app.list_state.selected();
+            if let Some(i) = self.selected {
+                self.describe_step(app, i);
+            }
+        }
+        if self.mode.as_ref() != Some(&app.mode) {
+            self.announce_mode(app);
+            self.mode = Some(app.mode.clone());
+        }
+        if app.mode == Mode::InputModal {
+            self.echo_input(app);
+        } else if app.mode == Mode::ScratchInput {
+            self.echo_buffer(&app.modal.input_buffer);
+        } else if app.mode == Mode::SnippetPalette && !app.snippets.is_empty() {
+            let entries = app
+                .snippet_matches()
+                .into_iter()
+                .map(|i| app.snippets[i].to_string())
+                .collect();
+            self.echo_palette(app, entries);
+        } else if app.mode == Mode::CommandPalette {
+            let entries = commands::matching(&app.palette)
+                .into_iter()
+                .map(|c| format!("{}, key {}", c.name, c.key))
+                .collect();
+            self.echo_palette(app, entries);
+        }
+        self.announce_tutorial(app);
+
+        self.flush()
+    }
+
+    /// Records the current statuses and output lengths as already announced.
+    fn sync_steps(&mut self, app: &App) {
+        self.statuses = app.steps.iter().map(|s| s.status).collect();
+        self.printed = app.steps.iter().map(|s| s.output.len()).collect();
+    }
+
+    /// Prints the output produced since the last render.
+    fn announce_output(&mut self, app: &App) {
+        for (i, step) in app.steps.iter().enumerate() {
+            let printed = self.printed[i];
+            let printed = if printed > step.output.len() || !step.output.is_char_boundary(printed) {
+                0
+            } else {
+                printed
+            };
+            if printed < step.output.len() {
+                // Steps running at the same time take turns
+                if printed == 0 || self.output_step != Some(i) {
+                    self.say(&format!("Output of step {}:", i + 1));
+                }
+                self.raw(&step.output[printed..]);
+                self.output_step = Some(i);
+            }
+            self.printed[i] = step.output.len();
+        }
+    }
+
+    /// Announces the steps whose status changed.
+    fn announce_statuses(&mut self, app: &App) {
+        for (i, step) in app.steps.iter().enumerate() {
+            if self.statuses[i] != step.status {
+                self.statuses[i] = step.status;
+                let execution = match step.status {
+                    StepStatus::Pending | StepStatus::Running | StepStatus::Skipped => None,
+                    _ => step.execution.as_ref(),
+                };
+                let mut duration = execution
+                    .map(|e| format!(" in {}", format_elapsed(e.duration_ms)))
+                    .unwrap_or_default();
+                if let Some(code) = execution.and_then(|e| e.exit_code).filter(|&c| c != 0) {
+                    duration.push_str(&format!(" with exit code {code}"));
+                }
+                self.say(&format!(
+                    "Step {} {}{duration}: {}",
+                    i + 1,
+                    status_label(step.status),
+                    step.title.trim()
+                ));
+                if let Some(diff) = &step.context_diff
+                    && step.status != StepStatus::Running
+                {
+                    self.describe_env_changes(diff);
+                }
+                if step.status == StepStatus::Running
+                    && app.execution_manager.executor.context.separate_streams
+                {
+                    self.say("Press x to cancel it.");
+                } else if step.status == StepStatus::Running {
+                    self.say("Press i to type into it, for example to answer a prompt, or x to cancel it.");
+                }
+            }
+        }
+    }
+
+    /// Reads out what a step changed in the working directory and environment.
+    fn describe_env_changes(&mut self, diff: &ContextDiff) {
+        if let Some(dir) = &diff.current_dir {
+            self.say(&format!("Changed the directory to {dir}."));
+        }
+        for change in &diff.variables {
+            let text = match (&change.before, &change.after) {
+                (None, Some(after)) => format!("Set {} to {after}.", change.name),
+                (Some(before), Some(after)) => {
+                    format!("Changed {} to {after}, was {before}.", change.name)
+                }
+                (_, None) => format!("Unset {}.", change.name),
+            };
+            self.say(&text);
+        }
+    }
+
+    /// Describes a step: title, status, condition, description and code.
+    fn describe_step(&mut self, app: &App, index: usize) {
+        let Some(step) = app.steps.get(index) else {
+            return;
+        };
+        self.say("");
+        self.say(&format!(
+            "Step {} of {}: {}. Status: {}.",
+            index + 1,
+            app.steps.len(),
+            step.title.trim(),
+            status_label(step.status)
+        ));
+        if step.session_only {
+            self.say("Added during this session, not in the runbook.");
+        }
+        if let Some(origin) = &step.origin {
+            self.say(&format!("Included from {origin}."));
+        }
+        if let Some(parent) = outline::parent(&app.steps, index) {
+            self.say(&format!(
+                "Under step {}: {}.",
+                parent + 1,
+                app.steps[parent].title.trim()
+            ));
+        }
+        let nested = outline::subtree_end(&app.steps, index) - index - 1;
+        if nested > 0 {
+            self.say(&if app.collapsed.contains(&index) {
+                format!("Collapsed, {nested} sub-steps hidden. Press the right arrow to expand it.")
+            } else {
+                format!("Has {nested} sub-steps. Press the left arrow to collapse it.")
+            });
+        }
+        if let Some(group) = groups::describe(&app.steps, index) {
+            self.say(&format!("{group}."));
+        }
+        if let Some((source, notice)) = &app.restart_notice
+            && restart::blocks(&app.steps, *source, index)
+        {
+            let what = match notice.kind {
+                RestartKind::Reboot => "reboot the machine",
+                RestartKind::Relogin => "log out and back in",
+            };
+            self.say(&format!(
+                "Blocked: step {} asked to {what} first. Running it asks you to confirm it is done.",
+                source + 1
+            ));
+        }
+        if app.sandbox_flipped.contains(&index) {
+            self.say(if app.is_sandboxed(index) {
+                "Runs in the Docker sandbox. Press b to run it on this machine."
+            } else {
+                "Runs on this machine, outside the sandbox. Press b to sandbox it again."
+            });
+        }
+        if let Some(condition) = &step.condition {
+            let met = if StandardEvaluator::new().evaluate(condition) {
+                "met"
+            } else if app.forced_steps.contains(&index) {
+                "not met, force-enabled"
+            } else {
+                "not met"
+            };
+            self.say(&format!("Condition: {condition} ({met})."));
+        }
+        for line in description_lines(&step.description) {
+            let line: String = line.spans.iter().map(|s| s.content.as_ref()).collect();
+            // Table rules have nothing to read
+            if line.chars().any(char::is_alphanumeric) {
+                self.say(line.trim());
+            }
+        }
+        self.describe_code(step);
+        for (n, command) in app.inline_commands(index).iter().take(9).enumerate() {
+            self.say(&format!(
+                "Command {} in the text: {command}. Press {} to run it.",
+                n + 1,
+                n + 1
+            ));
+        }
+        if !step.output.is_empty() {
+            self.say("Output of the last run:");
+            self.raw(&step.output);
+        }
+    }
+
+    /// Announces the steps moved into or out of the sandbox.
+    fn announce_sandbox(&mut self, app: &App) {
+        if self.sandbox_flipped == app.sandbox_flipped {
+            return;
+        }
+        let mut changed: Vec<usize> = self
+            .sandbox_flipped
+            .symmetric_difference(&app.sandbox_flipped)
+            .copied()
+            .collect();
+        changed.sort_unstable();
+        for i in changed {
+            let place = if app.is_sandboxed(i) {
+                "in the Docker sandbox"
+            } else {
+                "on this machine"
+            };
+            self.say(&format!("Step {} now runs {place}.", i + 1));
+        }
+        self.sandbox_flipped.clone_from(&app.sandbox_flipped);
+    }
+
+    /// Announces the auto-pilot being switched on or off.
+    fn announce_autopilot(&mut self, app: &App) {
+        if self.autopilot == app.autopilot_active() {
+            return;
+        }
+        self.autopilot = app.autopilot_active();
+        if self.autopilot {
+            let left = app.autopilot.len() + usize::from(app.autopilot_current.is_some());
+            self.say(&format!(
+                "Auto-pilot on: {left} steps to run. Press capital A to stop."
+            ));
+        } else {
+            self.say("Auto-pilot off.");
+        }
+    }
+
+    /// Announces the steps collapsed or expanded since the last render.
+    fn announce_collapsed(&mut self, app: &App) {
+        if self.collapsed == app.collapsed {
+            return;
+        }
+        let mut changed: Vec<usize> = self
+            .collapsed
+            .symmetric_difference(&app.collapsed)
+            .copied()
+            .filter(|&i| i < app.steps.len())
+            .collect();
+        changed.sort_unstable();
+        for i in changed {
+            let nested = outline::subtree_end(&app.steps, i) - i - 1;
+            self.say(&if app.collapsed.contains(&i) {
+                format!(
+                    "Step {} collapsed: its {nested} sub-steps are skipped. Press the right arrow to expand it.",
+                    i + 1
+                )
+            } else {
+                format!("Step {} expanded: {nested} sub-steps.", i + 1)
+            });
+        }
+        self.collapsed.clone_from(&app.collapsed);
+    }
+
+    /// Announces the next lesson of the tutorial.
+    fn announce_tutorial(&mut self, app: &App) {
+        if let Some((index, lesson)) = tutorial::current(app)
+            && self.tutorial != Some(index)
+        {
+            self.tutorial = Some(index);
+            self.say(&format!(
+                "Tutorial, {} of {}: press {}. {}",
+                index + 1,
+                LESSONS.len(),
+                lesson.keys,
+                lesson.hint
+            ));
+        }
+    }
+
+    /// Reads out the code blocks of a step.
+    fn describe_code(&mut self, step: &Step) {
+        let count = step.code_blocks.len();
+        for (i, block) in step.code_blocks.iter().enumerate() {
+            let language = match block.language.as_deref() {
+                Some(language) => language,
+                None if block.indented => "indented shell",
+                None => "no language",
+            };
+            if count == 1 {
+                self.say(&format!("Code, {language}:"));
+            } else {
+                self.say(&format!("Code block {} of {count}, {language}:", i + 1));
+            }
+            self.raw(&block.content);
+            self.say("End of code.");
+        }
+        if !step.requires.is_empty() {
+            self.say(&format!("Requires: {}.", step.requires.join(", ")));
+        }
+        if let Some(cwd) = &step.scope.cwd {
+            self.say(&format!("Runs in {cwd}."));
+        }
+        for (key, value) in &step.scope.env {
+            self.say(&format!("Sets {key} to {value}."));
+        }
+        if let Some(verify) = &step.scope.verify {
+            self.say(&format!("Verified by: {verify}"));
+        }
+        if let Some(rollback) = &step.rollback {
+            self.say(&format!(
+                "Rolled back by: {rollback}. Press capital U to run it."
+            ));
+        }
+    }
+
+    /// Announces a popup or prompt when the mode changes.
+    fn announce_mode(&mut self, app: &App) {
+        match app.mode {
+            Mode::Normal => {
+                if self.mode.is_some() {
+                    self.say("Back to the steps.");
+                }
+            }
+            Mode::InputModal => self.placeholder = None,
+            Mode::ScratchInput => {
+                self.echoed = 0;
+                self.say(
+                    "Type the command of the new step, then press Enter to add it after this one, or Escape to cancel.",
+                );
+            }
+            Mode::SnippetPalette => {
+                self.echoed = 0;
+                self.highlighted = None;
+                if app.snippets.is_empty() {
+                    self.say(
+                        "No snippets yet: add one with compass snippet add. Press Escape to go back.",
+                    );
+                } else {
+                    self.say(&format!(
+                        "Snippet palette, {} snippets. Type to filter, up and down arrows to choose, Enter to run the snippet as a new step, Escape to cancel.",
+                        app.snippets.len()
+                    ));
+                }
+            }
+            Mode::CommandPalette => {
+                self.echoed = 0;
+                self.highlighted = None;
+                self.say(
+                    "Command palette. Type to search the actions, up and down arrows to choose, Enter to run the action, Escape to cancel.",
+                );
+            }
+            Mode::SafetyAlert => {
+                let pattern = app.safety_pattern.as_deref().unwrap_or_default();
+                self.say(&format!(
+                    "Warning: this step contains a dangerous command ({pattern}). Press Enter to run it anyway or Escape to cancel."
+                ));
+            }
+            Mode::DependencyAlert => {
+                let message = app.safety_pattern.as_deref().unwrap_or_default();
+                self.say(&format!(
+                    "{message} Press Enter to run anyway or Escape to cancel."
+                ));
+            }
+            Mode::PrerequisiteAlert => {
+                let message = app.safety_pattern.as_deref().unwrap_or_default();
+                self.say(&format!(
+                    "{message} Press Enter to run it anyway or Escape to cancel."
+                ));
+            }
+            Mode::ClusterAlert => {
+                let message = app.safety_pattern.as_deref().unwrap_or_default();
+                self.say(&format!(
+                    "{message} Press Enter to run it or Escape to cancel."
+                ));
+            }
+            Mode::SecretConsent => {
+                let message = app.safety_pattern.as_deref().unwrap_or_default();
+                self.say(&format!(
+                    "{message} Press Enter to read them or Escape to cancel."
+                ));
+            }
+            Mode::CloudCheck => {
+                let message = app.safety_pattern.as_deref().unwrap_or_default();
+                self.say(&format!(
+                    "{message} Press Enter to run it without waiting or Escape to cancel."
+                ));
+            }
+            Mode::CloudAlert => {
+                let message = app.safety_pattern.as_deref().unwrap_or_default();
+                self.say(&format!(
+                    "{message} Press Enter to run it or Escape to cancel."
+                ));
+            }
+            Mode::PolicyAlert => {
+                let reason = app.safety_pattern.as_deref().unwrap_or_default();
+                self.say(&format!(
+                    "An organization policy asks for a confirmation: {reason}. Press Enter to run it or Escape to cancel."
+                ));
+            }
+            Mode::PlanReview => {
+                self.say(
+                    "Review the plan printed above. Press Enter to apply it or Escape to cancel.",
+                );
+            }
+            Mode::RestartAlert => {
+                let message = app.safety_pattern.as_deref().unwrap_or_default();
+                self.say(&format!(
+                    "Restart required. {message} Press Enter once it is done, or Escape to cancel."
+                ));
+            }
+            Mode::HelpModal => {
+                self.say("Keyboard shortcuts.");
+                for &(section, shortcuts) in SHORTCUTS {
+                    self.say(&format!("{section}:"));
+                    for &(key, description) in shortcuts {
+                        self.say(&format!("  {key}: {description}"));
+                    }
+                }
+                self.say("Press Escape to close the help.");
+            }
+            Mode::ExportNotification => {
+                if let Some((success, message)) = &app.export_message {
+                    let label = if *success { "Saved" } else { "Save failed" };
+                    self.say(&format!("{label}: {message}"));
+                }
+                self.say("Press any key to continue.");
+            }
+            Mode::RecoveryAlert => {
+                if let Some(rec) = &app.recovery_suggestion {
+                    self.say(&format!("Suggested fix: {}", rec.message));
+                    if let Some(command) = &rec.fix_command {
+                        self.say(&format!("Command: {command}"));
+                    }
+                }
+                self.say("Press Enter to apply it or Escape to dismiss.");
+            }
+            Mode::DebugOverlay => {
+                let events = recent_events();
+                self.say("Recent log events:");
+                let skip = events.len().saturating_sub(DEBUG_EVENTS);
+                for event in events.into_iter().skip(skip) {
+                    self.say(&event);
+                }
+                self.say("Press Escape to close the log.");
+            }
+            Mode::DiffPreview => {
+                self.say("This step edits files:");
+                for line in &app.edit_preview {
+                    let text = match line {
+                        DiffLine::File(path) => format!("File {path}:"),
+                        DiffLine::Hunk => continue,
+                        DiffLine::Added(text) => format!("added: {text}"),
+                        DiffLine::Removed(text) => format!("removed: {text}"),
+                        DiffLine::Context(text) => format!("unchanged: {text}"),
+                        DiffLine::Note(note) => note.clone(),
+                    };
+                    self.say(&text);
+                }
+                self.say("Press Enter to apply the changes or Escape to cancel.");
+            }
+            Mode::DryRunPreview => {
+                if let Some(preview) = &app.dry_run_preview {
+                    self.say("Dry run, nothing was executed. The step would run:");
+                    self.raw(preview);
+                    self.say("End of dry run. Press Escape to close.");
+                }
+            }
+            Mode::Interactive => {
+                self.say(
+                    "Interactive: your keys are sent to the running step. Press Escape to stop.",
+                );
+            }
+            Mode::DependencyCheck => {
+                if let Some(report) = &app.dependency_report {
+                    self.say(&format!(
+                        "Dependency check: {} present, {} missing.",
+                        report.present.len(),
+                        report.missing.len()
+                    ));
+                    for cmd in &report.missing {
+                        self.say(&format!("Missing: {cmd}. {}", install_hint(cmd)));
+                    }
+                    if !report.present.is_empty() {
+                        self.say(&format!("Present: {}.", report.present.join(", ")));
+                    }
+                }
+                self.say("Press Escape to close the check.");
+            }
+        }
+    }
+
+    /// Prompts for the current placeholder and echoes what the user types.
+    fn echo_input(&mut self, app: &App) {
+        let modal = &app.modal;
+        if self.placeholder != Some(modal.current_placeholder_idx) {
+            self.placeholder = Some(modal.current_placeholder_idx);
+            self.echoed = 0;
+            if let Some(name) = modal
+                .required_placeholders
+                .get(modal.current_placeholder_idx)
+            {
+                let kind = if modal.is_secret() {
+                    "the secret "
+                } else if modal.is_env() && !modal.is_placeholder() {
+                    "the environment variable "
+                } else {
+                    ""
+                };
+                let description = app
+                    .placeholder_spec(name)
+                    .and_then(|s| s.description.as_deref())
+                    .map(|d| format!(" ({d})"))
+                    .unwrap_or_default();
+                if let Some(choices) = modal.current_choices() {
+                    self.say(&format!(
+                        "Choose a value for {kind}{name}{description} with the up and down arrows, then press Enter: {}. Escape cancels.",
+                        choices.join(", ")
+                    ));
+                } else {
+                    self.say(&format!(
+                        "Enter a value for {kind}{name}{description}, then press Enter. Escape cancels."
+                    ));
+                }
+                self.highlighted = None;
+            }
+        }
+        if modal.error != self.input_error {
+            self.input_error.clone_from(&modal.error);
+            if let Some(error) = &modal.error {
+                self.say(&format!("{error}. Fix the value, then press Enter."));
+                self.echoed = 0;
+            }
+        }
+
+        if let Some(choices) = modal.current_choices() {
+            if self.highlighted.as_ref() != Some(&modal.input_buffer) {
+                let position = choices.iter().position(|c| *c == modal.input_buffer);
+                self.say(&format!(
+                    "{}, {} of {}.",
+                    modal.input_buffer,
+                    position.map_or(0, |p| p + 1),
+                    choices.len()
+                ));
+                self.highlighted = Some(modal.input_buffer.clone());
+            }
+        } else if modal.is_secret() {
+            self.echo_buffer(&"*".repeat(modal.input_buffer.chars().count()));
+        } else {
+            self.echo_buffer(&modal.input_buffer);
+        }
+    }
+
+    /// Echoes what was typed in the input buffer since the last render.
+    /// Announces the highlighted palette entry when it changes, then echoes
+    /// the query typed so far.
+    fn echo_palette(&mut self, app: &App, entries: Vec<String>) {
+        let count = entries.len();
+        let highlighted = entries.into_iter().nth(app.palette.selected);
+        if highlighted != self.highlighted {
+            match &highlighted {
+                Some(entry) => self.say(&format!(
+                    "{entry}, {} of {count}.",
+                    app.palette.selected + 1
+                )),
+                None => self.say("Nothing matches."),
+            }
+            self.highlighted = highlighted;
+            self.raw("Filter: ");
+            self.echoed = 0;
+        }
+        self.echo_buffer(&app.palette.query);
+    }
+
+    fn echo_buffer(&mut self, buffer: &str) {
+        let typed = buffer.chars().count();
+        if typed > self.echoed {
+            let new: String = buffer.chars().skip(self.echoed).collect();
+            self.raw(&new);
+        } else {
+            for _ in typed..self.echoed {
+                self.raw("\u{8} \u{8}");
+            }
+        }
+        self.echoed = typed;
+    }
+
+    /// Queues a full line, starting a new one if needed.
+    fn say(&mut self, text: &str) {
+        self.output_step = None;
+        if !self.at_line_start {
+            self.buffer.push('\n');
+        }
+        self.buffer.push_str(text);
+        self.buffer.push('\n');
+        self.at_line_start = true;
+    }
+
+    /// Queues text as is.
+    fn raw(&mut self, text: &str) {
+        if let Some(last) = text.chars().last() {
+            self.buffer.push_str(text);
+            self.at_line_start = last == '\n';
+        }
+    }
+
+    /// Writes the queued text, translating newlines for raw mode.
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let mut stdout = io::stdout();
+        stdout.write_all(self.buffer.replace('\n', "\r\n").as_bytes())?;
+        self.buffer.clear();
+        stdout.flush()
+    }
+}
+
+/// A spoken label for a step status.
+const fn status_label(status: StepStatus) -> &'static str {
+    match status {
+        StepStatus::Pending => "pending",
+        StepStatus::Running => "running",
+        StepStatus::Success => "succeeded",
+        StepStatus::Failed => "failed",
+        StepStatus::Skipped => "skipped",
+        StepStatus::Cancelled => "cancelled",
+        StepStatus::TimedOut => "timed out",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_readme;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_announces_only_changes() {
+        let (steps, _) = parse_readme("# Build\n```bash\nmake\n```\n");
+        let mut app = App::new(steps, PathBuf::from("README.md"), false);
+        let mut view = LinearView::new(&app);
+
+        app.steps[0].status = StepStatus::Running;
+        app.steps[0].output = "compiling\n".to_string();
+        view.announce_output(&app);
+        view.announce_statuses(&app);
+        assert!(view.buffer.starts_with("Output of step 1:\ncompiling\n"));
+        assert!(view.buffer.contains("Step 1 running: Build\n"));
+
+        view.buffer.clear();
+        app.steps[0].output.push_str("done\n");
+        app.steps[0].status = StepStatus::Failed;
+        view.announce_output(&app);
+        view.announce_statuses(&app);
+        assert_eq!(
+            view.buffer,
+            "Output of step 1:\ndone\nStep 1 failed: Build\n"
+        );
+
+        view.buffer.clear();
+        view.announce_output(&app);
+        view.announce_statuses(&app);
+        assert!(view.buffer.is_empty());
+    }
+}
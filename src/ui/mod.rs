@@ -14,15 +14,25 @@
 
 pub mod app;
 pub mod events;
+pub mod linear;
 pub mod state;
+pub mod theme;
 pub mod utils;
 pub mod view;
 pub mod widgets;
 
+use crate::core::collab::session::CollabSession;
+use crate::core::export::context::ContextExport;
+use crate::core::fetcher::STDIN_SOURCE;
+use crate::core::fetcher::clipboard::CLIPBOARD_SOURCE;
+use crate::core::infrastructure::recent;
+use crate::core::infrastructure::session::{self, SavedSession};
+use crate::core::manifest::RunbookManifest;
 use crate::core::models::Step;
 use crate::ui::app::App;
 use anyhow::Result;
 use crossterm::{
+    cursor::Show,
     event::{self, Event, KeyEventKind},
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
@@ -32,42 +42,217 @@ use ratatui::backend::CrosstermBackend;
 use std::io;
 use std::path::PathBuf;
 
+/// Installs a panic hook that restores the terminal before reporting the crash.
+///
+/// Without it, a panic inside the main loop leaves the user's shell in raw mode
+/// on the alternate screen.
+fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = disable_raw_mode();
+        let _ = execute!(io::stdout(), LeaveAlternateScreen, Show);
+
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(ToString::to_string)
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        let message = match info.location() {
+            Some(location) => format!("{message} at {location}"),
+            None => message,
+        };
+        let backtrace = std::backtrace::Backtrace::force_capture().to_string();
+
+        default_hook(info);
+
+        eprintln!("\n💥 Compass crashed unexpectedly.");
+        match crate::core::infrastructure::crash::write_report(&message, &backtrace) {
+            Ok(path) => eprintln!("   Crash report saved to: {}", path.display()),
+            Err(e) => eprintln!("   Could not save crash report: {e}"),
+        }
+        eprintln!(
+            "   Please open an issue and attach the report:\n   {}",
+            crate::core::infrastructure::crash::issue_url(&message)
+        );
+    }));
+}
+
+/// How the TUI opens a runbook.
+pub struct TuiOptions {
+    /// Whether the runbook was downloaded, which puts it in strict mode.
+    pub is_remote: bool,
+    /// Run the steps in a Docker container of `image`.
+    pub sandbox: bool,
+    pub image: String,
+    /// Collaboration session joined or hosted.
+    pub collab_session: Option<CollabSession>,
+    /// Frontmatter of the runbook.
+    pub manifest: Option<RunbookManifest>,
+    /// Whether the hooks of the manifest may run.
+    pub hooks_trusted: bool,
+    /// Content to record as trusted once the user allows one of its dangerous
+    /// commands.
+    pub untrusted_content: Option<String>,
+    /// Open the runbook as a viewer (`--read-only`).
+    pub read_only: bool,
+    /// Show only the current step.
+    pub focus: bool,
+    /// Write to the normal screen, line by line, for screen readers.
+    pub linear: bool,
+    /// Context exported by an earlier run to start from.
+    pub context: Option<ContextExport>,
+    /// Variables read from an env file.
+    pub env_file: Vec<(String, String)>,
+    /// Resume the saved session of the runbook.
+    pub resume: bool,
+    /// Start the guided tour (`compass tutorial`).
+    pub tutorial: bool,
+}
+
+impl Default for TuiOptions {
+    fn default() -> Self {
+        Self {
+            is_remote: false,
+            sandbox: false,
+            image: "ubuntu:latest".to_string(),
+            collab_session: None,
+            manifest: None,
+            hooks_trusted: false,
+            untrusted_content: None,
+            read_only: false,
+            focus: false,
+            linear: false,
+            context: None,
+            env_file: Vec::new(),
+            resume: false,
+            tutorial: false,
+        }
+    }
+}
+
 /// Starts the TUI application.
-#[allow(clippy::too_many_arguments)]
-pub fn run_tui(
-    steps: Vec<Step>,
-    readme_path: PathBuf,
-    is_remote: bool,
-    sandbox: bool,
-    image: String,
-    collab_session: Option<crate::core::collab::session::CollabSession>,
-    hooks: Option<crate::core::ecosystem::hooks::HookConfig>,
-    hooks_trusted: bool,
-) -> Result<()> {
-    // Setup terminal
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?; // Enter alternate screen
-    let backend = CrosstermBackend::new(stdout); // Create backend
-    let mut terminal = Terminal::new(backend)?; // Create terminal
+pub fn run_tui(steps: Vec<Step>, readme_path: PathBuf, options: TuiOptions) -> Result<()> {
+    let TuiOptions {
+        is_remote,
+        sandbox,
+        image,
+        collab_session,
+        manifest,
+        hooks_trusted,
+        untrusted_content,
+        read_only,
+        focus,
+        linear,
+        context,
+        env_file,
+        resume,
+        tutorial,
+    } = options;
+    install_panic_hook();
 
-    // Create app and run main loop
+    // Create the app before the terminal, so that warnings can still be printed
     let mut app = App::new(steps, readme_path, is_remote)
         .with_sandbox(sandbox, image)
-        .with_hooks(hooks, hooks_trusted);
+        .with_manifest(manifest, hooks_trusted);
+
+    app.untrusted_content = untrusted_content;
+    app.read_only = read_only;
+    app.focus_mode = focus;
+    app.tutorial = tutorial.then_some(0);
+    crate::core::executor::checker::warm_cache(&app.steps);
 
     if let Some(session) = collab_session {
         app.collab = Some(session);
     }
 
-    // Load persisted configuration (placeholders)
+    // Guests follow the host instead, and pasted or piped runbooks cannot be reopened
+    let location = app.readme_path.to_string_lossy().to_string();
+    let tracked = app.collab.as_ref().is_none_or(|s| s.is_host)
+        && location != CLIPBOARD_SOURCE
+        && location != STDIN_SOURCE;
+    if tracked {
+        app.session_file = session::path(&app.readme_path).ok();
+    }
+
+    // Load persisted configuration (placeholders), then the session and the
+    // context to resume from
     app.load_config();
+    let mut resumed = false;
+    if resume {
+        match app.session_file.as_deref().filter(|p| p.exists()) {
+            Some(path) => {
+                for warning in app.resume_session(SavedSession::load(path)?) {
+                    eprintln!("⚠️  {warning}");
+                }
+                resumed = true;
+            }
+            None => eprintln!("⚠️  No saved session for {location}; starting from scratch."),
+        }
+    }
+    if let Some(context) = context {
+        if let Some(warning) = context.apply(&mut app.execution_manager.executor.context) {
+            eprintln!("⚠️  {warning}");
+        }
+        app.modal.variable_store.extend(context.placeholders);
+        app.execution_manager.reset_baseline();
+    }
+    app.import_env(env_file);
+
+    // Otherwise start at the step reached last time
+    if tracked
+        && !resumed
+        && !tutorial
+        && let Some(step) = recent::last_step(&location)
+    {
+        app.list_state
+            .select(Some(step.min(app.steps.len().saturating_sub(1))));
+    }
+
+    // Setup terminal (the linear renderer keeps writing to the normal screen)
+    enable_raw_mode()?;
+    let mut terminal = if linear {
+        None
+    } else {
+        let mut stdout = io::stdout();
+        execute!(stdout, EnterAlternateScreen)?; // Enter alternate screen
+        let backend = CrosstermBackend::new(stdout); // Create backend
+        Some(Terminal::new(backend)?) // Create terminal
+    };
+
+    let res = match terminal.as_mut() {
+        Some(terminal) => run_loop(&mut app, |app| {
+            terminal.draw(|f| view::draw(f, app))?;
+            Ok(())
+        }),
+        None => {
+            let mut view = linear::LinearView::new(&app);
+            run_loop(&mut app, |app| Ok(view.render(app)?))
+        }
+    };
 
-    let res = run_loop(&mut terminal, &mut app);
+    if tracked {
+        app.save_session();
+        let title = app
+            .steps
+            .iter()
+            .find(|s| s.level > 0)
+            .or(app.steps.first())
+            .map(|s| s.title.clone());
+        // Position in the runbook, without the steps added during the session
+        let selected = app.list_state.selected().unwrap_or(0);
+        let step = app.steps[..selected.min(app.steps.len())]
+            .iter()
+            .filter(|s| !s.session_only)
+            .count();
+        if let Err(e) = recent::record(&location, title, step) {
+            tracing::warn!("Could not record recent runbook: {e}");
+        }
+    }
 
     // Trigger Post-run hook
     if app.hooks_trusted
-        && let Some(config) = &app.hooks
+        && let Some(config) = &app.manifest
     {
         crate::core::ecosystem::hooks::trigger_hook(
             &config.post_run,
@@ -77,14 +262,19 @@ pub fn run_tui(
 
     // Restore terminal
     disable_raw_mode()?; // Disable raw mode
-    execute!(terminal.backend_mut(), LeaveAlternateScreen,)?; // Leave alternate screen
-    terminal.show_cursor()?; // Show cursor
+    if let Some(mut terminal) = terminal {
+        execute!(terminal.backend_mut(), LeaveAlternateScreen,)?; // Leave alternate screen
+        terminal.show_cursor()?; // Show cursor
+    }
 
     res
 }
 
 /// Runs the main loop of the TUI application.
-fn run_loop(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut App) -> Result<()> {
+///
+/// `render` is called once per iteration to draw the full-screen view or
+/// print the changes in linear mode.
+fn run_loop(app: &mut App, mut render: impl FnMut(&mut App) -> Result<()>) -> Result<()> {
     // Send initial snapshot if host
     if let Some(session) = &app.collab
         && session.is_host
@@ -96,9 +286,10 @@ fn run_loop(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut App
         };
         let _ = tx.send(event);
     }
+    app.share_placeholders();
 
     loop {
-        terminal.draw(|f| view::draw(f, app))?;
+        render(app)?;
 
         // Handle incoming collab events
         let mut events_to_process = Vec::new();
@@ -122,6 +313,8 @@ fn run_loop(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut App
                             "Success" => crate::core::models::StepStatus::Success,
                             "Failed" => crate::core::models::StepStatus::Failed,
                             "Skipped" => crate::core::models::StepStatus::Skipped,
+                            "Cancelled" => crate::core::models::StepStatus::Cancelled,
+                            "TimedOut" => crate::core::models::StepStatus::TimedOut,
                             _ => crate::core::models::StepStatus::Pending,
                         };
                     }
@@ -139,6 +332,15 @@ fn run_loop(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut App
                     app.steps = steps;
                     app.list_state.select(Some(current_step));
                 }
+                crate::core::collab::events::CompassEvent::Placeholders { values } => {
+                    // Keep the host's values so previews match; secrets stay out
+                    for (name, value) in values {
+                        match value {
+                            Some(value) => app.modal.variable_store.insert(name, value),
+                            None => app.modal.variable_store.remove(&name),
+                        };
+                    }
+                }
                 crate::core::collab::events::CompassEvent::ConnectionLost(msg) => {
                     return Err(anyhow::anyhow!("Session disconnected: {}", msg));
                 }
@@ -13,8 +13,10 @@
 // limitations under the License.
 
 pub mod modal;
+pub mod palette;
 
-use crate::core::models::StepStatus;
+use crate::core::executor::engine::context::ContextSnapshot;
+use crate::core::models::{ExecutionRecord, StepStatus};
 use std::collections::HashMap;
 use std::path::PathBuf;
 
@@ -22,8 +24,38 @@ use std::path::PathBuf;
 pub enum ExecutionMessage {
     /// Partial output from a PTY.
     OutputPartial(usize, String),
-    /// Execution finished with status and final context.
-    Finished(usize, StepStatus, PathBuf, HashMap<String, String>),
+    /// Execution finished with status, final context and timing.
+    Finished(
+        usize,
+        StepStatus,
+        PathBuf,
+        HashMap<String, String>,
+        ExecutionRecord,
+    ),
+}
+
+/// A context mutation made by a step, with the state it replaced.
+#[derive(Debug, Clone)]
+pub struct ContextChange {
+    /// Index of the step that changed the context.
+    pub step: usize,
+    /// Working directory and environment before the step ran.
+    pub before: ContextSnapshot,
+}
+
+/// The session state just before a step was run, used to rewind to it.
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    /// Index of the step about to run.
+    pub step: usize,
+    /// Status of every step at that time.
+    pub statuses: Vec<StepStatus>,
+    /// Working directory and environment at that time.
+    pub context: ContextSnapshot,
+    /// Placeholder values known at that time.
+    pub placeholders: HashMap<String, String>,
+    /// Length of the context history at that time.
+    pub context_changes: usize,
 }
 
 /// The various states the application UI can be in.
@@ -33,14 +65,48 @@ pub enum Mode {
     Normal,
     /// Waiting for user input to fill placeholders.
     InputModal,
+    /// Waiting for the command of a scratch step to add.
+    ScratchInput,
+    /// Choosing a snippet of the user's library to run as a new step.
+    SnippetPalette,
+    /// Choosing an action to run from the list of all actions.
+    CommandPalette,
     /// Waiting for confirmation of a dangerous command.
     SafetyAlert,
     /// Waiting for confirmation of a missing dependency.
     DependencyAlert,
+    /// Waiting for confirmation of a step whose prerequisites have not succeeded.
+    PrerequisiteAlert,
+    /// Waiting for confirmation that the reboot or new login a step asked for
+    /// is done, before running a step after it.
+    RestartAlert,
+    /// Displaying the `terraform plan` of a step, waiting for approval to apply it.
+    PlanReview,
+    /// Waiting for confirmation of the Kubernetes context a step changes.
+    ClusterAlert,
+    /// Waiting for consent to read placeholders from secret managers, then
+    /// for their values.
+    SecretConsent,
+    /// Waiting for the probe of the cloud account a step would use.
+    CloudCheck,
+    /// Waiting for confirmation of the cloud account a step would use.
+    CloudAlert,
+    /// Waiting for the confirmation an organization policy requires.
+    PolicyAlert,
     /// Displaying the help panel with all keyboard shortcuts.
     HelpModal,
     /// Displaying export success/failure message.
     ExportNotification,
     /// Displaying a suggested fix for a failed command.
     RecoveryAlert,
+    /// Displaying recent internal log events.
+    DebugOverlay,
+    /// Displaying the dependency check of the runbook.
+    DependencyCheck,
+    /// Waiting for confirmation of the file changes a step makes.
+    DiffPreview,
+    /// Displaying the command a step would run, without running it.
+    DryRunPreview,
+    /// Forwarding keystrokes to the running step (e.g. to answer a prompt).
+    Interactive,
 }
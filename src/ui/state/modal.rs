@@ -12,7 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashMap;
+use crate::core::models::Step;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 /// Manages state for the placeholder input modal.
 #[derive(Debug, Default)]
@@ -21,10 +22,24 @@ pub struct ModalState {
     pub input_buffer: String,
     /// Store for variable values (KEY -> VALUE).
     pub variable_store: HashMap<String, String>,
+    /// Placeholders read from `--env-file`: never asked for, never saved.
+    pub imported: HashSet<String>,
     /// List of placeholders required for the current step.
     pub required_placeholders: Vec<String>,
     /// Index of the currently active placeholder being filled.
     pub current_placeholder_idx: usize,
+    /// Missing environment variables required by the current step, asked
+    /// after the placeholders (a name can be both).
+    pub env_vars: Vec<String>,
+    /// How many entries of `required_placeholders` are actual placeholders.
+    pub placeholder_count: usize,
+    /// Placeholders of the current step holding secrets: masked while typed,
+    /// never saved.
+    pub secrets: Vec<String>,
+    /// Values allowed for the choice placeholders of the current step.
+    pub choices: BTreeMap<String, Vec<String>>,
+    /// Why the submitted value was rejected, until the user types again.
+    pub error: Option<String>,
 }
 
 impl ModalState {
@@ -32,10 +47,82 @@ impl ModalState {
         Self::default()
     }
 
-    /// Resets the modal state for a new interaction.
-    pub fn reset(&mut self, required: Vec<String>) {
+    /// Resets the modal state for a new interaction with `step`, asking for
+    /// the placeholders first and then for the environment variables.
+    pub fn reset(&mut self, placeholders: Vec<String>, env_vars: Vec<String>, step: &Step) {
         self.input_buffer.clear();
+        self.placeholder_count = placeholders.len();
+        let mut required = placeholders;
+        for name in &env_vars {
+            if !required.contains(name) {
+                required.push(name.clone());
+            }
+        }
         self.required_placeholders = required;
+        self.env_vars = env_vars;
+        self.secrets.clone_from(&step.secrets);
+        self.choices.clone_from(&step.choices);
+        self.error = None;
         self.current_placeholder_idx = 0;
     }
+
+    /// Whether the value being typed is exported as an environment variable.
+    pub fn is_env(&self) -> bool {
+        self.required_placeholders
+            .get(self.current_placeholder_idx)
+            .is_some_and(|name| self.env_vars.contains(name))
+    }
+
+    /// Whether the value being typed is a secret, to be masked.
+    pub fn is_secret(&self) -> bool {
+        self.required_placeholders
+            .get(self.current_placeholder_idx)
+            .is_some_and(|name| self.secrets.contains(name))
+    }
+
+    /// The values to pick from, if the current placeholder is a choice.
+    pub fn current_choices(&self) -> Option<&[String]> {
+        if !self.is_placeholder() {
+            return None;
+        }
+        let name = self
+            .required_placeholders
+            .get(self.current_placeholder_idx)?;
+        self.choices.get(name).map(Vec::as_slice)
+    }
+
+    /// Keeps the pre-filled value of a choice placeholder if it is allowed,
+    /// and selects the first choice otherwise.
+    pub fn fit_choice(&mut self) {
+        if let Some(choices) = self.current_choices()
+            && !choices.contains(&self.input_buffer)
+        {
+            self.input_buffer = choices[0].clone();
+        }
+    }
+
+    /// Selects the next (or previous) value of a choice placeholder, wrapping
+    /// around.
+    pub fn cycle_choice(&mut self, forward: bool) {
+        let Some(choices) = self.current_choices() else {
+            return;
+        };
+        let count = choices.len();
+        let current = choices
+            .iter()
+            .position(|c| *c == self.input_buffer)
+            .unwrap_or(0);
+        let next = if forward {
+            (current + 1) % count
+        } else {
+            (current + count - 1) % count
+        };
+        self.input_buffer = choices[next].clone();
+    }
+
+    /// Whether the value being typed is a placeholder value (as opposed to
+    /// only an environment variable).
+    pub const fn is_placeholder(&self) -> bool {
+        self.current_placeholder_idx < self.placeholder_count
+    }
 }
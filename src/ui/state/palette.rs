@@ -0,0 +1,108 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// State of a palette: a list of entries filtered by a typed query.
+#[derive(Debug, Default)]
+pub struct PaletteState {
+    /// The filter being typed.
+    pub query: String,
+    /// Position of the highlighted entry among the matches.
+    pub selected: usize,
+}
+
+impl PaletteState {
+    /// Clears the query and highlights the first entry.
+    pub fn reset(&mut self) {
+        self.query.clear();
+        self.selected = 0;
+    }
+
+    /// Adds a character to the query.
+    pub fn push(&mut self, c: char) {
+        self.query.push(c);
+        self.selected = 0;
+    }
+
+    /// Removes the last character of the query.
+    pub fn pop(&mut self) {
+        self.query.pop();
+        self.selected = 0;
+    }
+
+    /// Highlights the next match, wrapping around.
+    pub const fn next(&mut self, count: usize) {
+        if count > 0 {
+            self.selected = (self.selected + 1) % count;
+        }
+    }
+
+    /// Highlights the previous match, wrapping around.
+    pub const fn previous(&mut self, count: usize) {
+        if count > 0 {
+            self.selected = (self.selected + count - 1) % count;
+        }
+    }
+
+    /// Indices of the entries matching the query, best match first. Entries
+    /// that match equally well keep their order.
+    pub fn matches<'a>(&self, texts: impl IntoIterator<Item = &'a str>) -> Vec<usize> {
+        let mut scored: Vec<(usize, usize)> = texts
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, text)| fuzzy_score(&self.query, text).map(|score| (score, i)))
+            .collect();
+        scored.sort_by_key(|&(score, _)| score);
+        scored.into_iter().map(|(_, i)| i).collect()
+    }
+}
+
+/// Matches `query` against `text` as a case-insensitive subsequence.
+///
+/// Returns `None` when a character of the query is missing, otherwise a score
+/// where lower is better: the number of characters skipped before and
+/// between the matched ones.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<usize> {
+    let mut chars = text.chars().flat_map(char::to_lowercase);
+    let mut score = 0;
+    for wanted in query.chars().flat_map(char::to_lowercase) {
+        if wanted.is_whitespace() {
+            continue;
+        }
+        score += chars.by_ref().position(|c| c == wanted)?;
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("", "docker ps"), Some(0));
+        assert_eq!(fuzzy_score("dps", "docker ps"), Some(6));
+        assert_eq!(fuzzy_score("Docker", "docker ps"), Some(0));
+        assert_eq!(fuzzy_score("pods", "docker ps"), None);
+    }
+
+    #[test]
+    fn test_matches_best_first() {
+        let palette = PaletteState {
+            query: "ps".to_string(),
+            selected: 0,
+        };
+        let texts = ["kubectl get pods", "docker ps", "ls"];
+        assert_eq!(palette.matches(texts), vec![1, 0]);
+    }
+}
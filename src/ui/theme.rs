@@ -0,0 +1,109 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::core::infrastructure::config::{StatusMarkers, Theme};
+use crate::core::models::StepStatus;
+use ratatui::style::{Color, Modifier, Style};
+
+/// Styles that depend on the configured theme.
+pub struct Palette {
+    /// Style of the selected step. Bold on top of the color, so that it
+    /// stands out without telling colors apart.
+    pub highlight: Style,
+    /// Style of panel borders.
+    pub border: Style,
+    /// How the status of steps is marked.
+    pub markers: StatusMarkers,
+}
+
+/// Returns the palette of a theme.
+pub fn palette(theme: Theme, markers: StatusMarkers) -> Palette {
+    let (highlight, border) = match theme {
+        Theme::Dark => (Style::default().bg(Color::Blue), Style::default()),
+        Theme::Light => (
+            Style::default().bg(Color::LightBlue).fg(Color::Black),
+            Style::default().fg(Color::DarkGray),
+        ),
+        Theme::HighContrast => (
+            Style::default().add_modifier(Modifier::REVERSED),
+            Style::default()
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD),
+        ),
+    };
+    Palette {
+        highlight: highlight.add_modifier(Modifier::BOLD),
+        border,
+        markers,
+    }
+}
+
+impl Palette {
+    /// Marker of a step status, followed by a space. Steps without code have
+    /// a blank marker of the same width.
+    #[must_use]
+    pub const fn status_marker(&self, status: StepStatus, executable: bool) -> &'static str {
+        match self.markers {
+            StatusMarkers::Emoji => match status {
+                StepStatus::Running => "⏳ ",
+                StepStatus::Success => "✅ ",
+                StepStatus::Failed => "❌ ",
+                StepStatus::Skipped => "🚫 ",
+                StepStatus::Cancelled => "⛔ ",
+                StepStatus::TimedOut => "⏱️ ",
+                StepStatus::Pending if executable => "⚡ ",
+                StepStatus::Pending => "   ",
+            },
+            StatusMarkers::Text => match status {
+                StepStatus::Running => "[..] ",
+                StepStatus::Success => "[OK] ",
+                StepStatus::Failed => "[!!] ",
+                StepStatus::Skipped => "[--] ",
+                StepStatus::Cancelled => "[XX] ",
+                StepStatus::TimedOut => "[TO] ",
+                StepStatus::Pending if executable => "[  ] ",
+                StepStatus::Pending => "     ",
+            },
+        }
+    }
+
+    /// Marker of a step with nested steps, followed by a space.
+    #[must_use]
+    pub const fn fold_marker(&self, collapsed: bool) -> &'static str {
+        match (self.markers, collapsed) {
+            (StatusMarkers::Emoji, false) => "▾ ",
+            (StatusMarkers::Emoji, true) => "▸ ",
+            (StatusMarkers::Text, false) => "- ",
+            (StatusMarkers::Text, true) => "+ ",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_markers_keep_one_width() {
+        let palette = palette(Theme::Dark, StatusMarkers::Text);
+        assert!(palette.highlight.add_modifier.contains(Modifier::BOLD));
+        assert_eq!(palette.status_marker(StepStatus::Success, true), "[OK] ");
+        assert_eq!(palette.status_marker(StepStatus::Failed, true), "[!!] ");
+        assert_eq!(
+            palette.status_marker(StepStatus::Pending, false).len(),
+            palette.status_marker(StepStatus::Pending, true).len()
+        );
+        assert_eq!(palette.fold_marker(true), "+ ");
+    }
+}
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use regex::Regex;
 
@@ -36,6 +37,37 @@ pub fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         .split(popup_layout[1])[1]
 }
 
+/// Encodes a key as the bytes a terminal sends for it, to forward it to a PTY.
+pub fn key_to_bytes(key: KeyEvent) -> Option<Vec<u8>> {
+    let bytes = match key.code {
+        KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            // Ctrl+A..Ctrl+Z are 0x01..0x1A (Ctrl+C interrupts the command)
+            c.is_ascii_alphabetic()
+                .then(|| vec![c.to_ascii_lowercase() as u8 & 0x1f])?
+        }
+        KeyCode::Char(c) => c.to_string().into_bytes(),
+        KeyCode::Enter => b"\r".to_vec(),
+        KeyCode::Backspace => vec![0x7f],
+        KeyCode::Tab => b"\t".to_vec(),
+        KeyCode::Up => b"\x1b[A".to_vec(),
+        KeyCode::Down => b"\x1b[B".to_vec(),
+        KeyCode::Right => b"\x1b[C".to_vec(),
+        KeyCode::Left => b"\x1b[D".to_vec(),
+        _ => return None,
+    };
+    Some(bytes)
+}
+
+/// Formats a run time as `0.4s`, `12.3s`, `2m05s` or `1h02m`.
+pub fn format_elapsed(ms: u64) -> String {
+    let secs = ms / 1000;
+    match secs {
+        0..60 => format!("{:.1}s", ms as f64 / 1000.0),
+        60..3600 => format!("{}m{:02}s", secs / 60, secs % 60),
+        _ => format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60),
+    }
+}
+
 /// Appends output to the buffer, handling ANSI sequences and line endings.
 pub fn append_output(buffer: &mut String, new_data: &str) {
     let cleaned_ansi = clean_ansi(new_data);
@@ -66,3 +98,16 @@ pub fn clean_ansi(s: &str) -> String {
 
     re.replace_all(s, "").to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(420), "0.4s");
+        assert_eq!(format_elapsed(12_345), "12.3s");
+        assert_eq!(format_elapsed(125_000), "2m05s");
+        assert_eq!(format_elapsed(3_725_000), "1h02m");
+    }
+}
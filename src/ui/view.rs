@@ -12,8 +12,16 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::core::executor::groups;
+use crate::core::executor::languages::probe::probe_interpreter;
+use crate::core::executor::restart::RestartNotice;
+use crate::core::fetcher::clipboard::CLIPBOARD_SOURCE;
+use crate::core::infrastructure::monitor::format_bytes;
+use crate::core::models::StepStatus;
 use crate::ui::app::{App, VERSION};
+use crate::ui::events::{commands, tutorial};
 use crate::ui::state::Mode;
+use crate::ui::theme;
 use crate::ui::widgets::{details, popups, step_list};
 use ratatui::{
     Frame,
@@ -23,11 +31,21 @@ use ratatui::{
     widgets::Paragraph,
 };
 
+/// Formats a duration in seconds as `45s`, `5m` or `1h20m`.
+fn format_secs(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3600 => format!("{}m", secs.div_ceil(60)),
+        _ => format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60),
+    }
+}
+
 /// Renders the status bar at the bottom of the screen.
 ///
 /// The status bar displays:
 /// - Compass version
 /// - Progress summary (completed/total steps)
+/// - Estimated time for the selected step and the remaining ones
 /// - Quick help hint
 fn render_status_bar(frame: &mut Frame, area: Rect, app: &App) {
     let completed = app.completed_count();
@@ -46,8 +64,13 @@ fn render_status_bar(frame: &mut Frame, area: Rect, app: &App) {
     ];
 
     if app.is_remote {
+        let label = if app.readme_path.as_os_str() == CLIPBOARD_SOURCE {
+            " 📋 Clipboard "
+        } else {
+            " 🌐 Remote "
+        };
         spans.push(Span::styled(
-            " 🌐 Remote ",
+            label,
             Style::default()
                 .fg(Color::Magenta)
                 .add_modifier(Modifier::BOLD),
@@ -71,6 +94,44 @@ fn render_status_bar(frame: &mut Frame, area: Rect, app: &App) {
         spans.push(Span::styled("│", Style::default().fg(Color::DarkGray)));
     }
 
+    if app.autopilot_active() {
+        let left = app.autopilot.len() + usize::from(app.autopilot_current.is_some());
+        spans.push(Span::styled(
+            format!(" 🤖 AUTO-PILOT ({left} left, A to stop) "),
+            Style::default()
+                .fg(Color::LightGreen)
+                .add_modifier(Modifier::BOLD),
+        ));
+        spans.push(Span::styled("│", Style::default().fg(Color::DarkGray)));
+    }
+
+    if let Some(sample) = app.resources {
+        let memory = sample.memory_used * 100 / sample.memory_total.max(1);
+        let mut text = format!(" 🖥 CPU {:.0}% RAM {memory}% ", sample.cpu);
+        if let Some(free) = sample.disk_free {
+            text.push_str(&format!("Disk {} free ", format_bytes(free)));
+        }
+        spans.push(Span::styled(
+            text,
+            Style::default().fg(if app.low_disk_warned {
+                Color::Red
+            } else {
+                Color::Gray
+            }),
+        ));
+        spans.push(Span::styled("│", Style::default().fg(Color::DarkGray)));
+    }
+
+    if app.read_only {
+        spans.push(Span::styled(
+            " 👁 READ-ONLY ",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ));
+        spans.push(Span::styled("│", Style::default().fg(Color::DarkGray)));
+    }
+
     if app.is_sandbox() {
         spans.push(Span::styled(
             " 📦 SANDBOXED ",
@@ -81,17 +142,57 @@ fn render_status_bar(frame: &mut Frame, area: Rect, app: &App) {
         spans.push(Span::styled("│", Style::default().fg(Color::DarkGray)));
     }
 
+    let palette = theme::palette(app.settings.theme, app.settings.status_markers);
     spans.extend(vec![
         Span::styled(
-            format!(" ✅ {completed}/{total} "),
+            format!(
+                " {}{completed}/{total} ",
+                palette.status_marker(StepStatus::Success, true)
+            ),
             Style::default().fg(Color::Green),
         ),
         if failed > 0 {
-            Span::styled(format!("❌ {failed} "), Style::default().fg(Color::Red))
+            Span::styled(
+                format!(
+                    "{}{failed} ",
+                    palette.status_marker(StepStatus::Failed, true)
+                ),
+                Style::default().fg(Color::Red),
+            )
         } else {
             Span::raw("")
         },
         Span::styled("│", Style::default().fg(Color::DarkGray)),
+    ]);
+
+    let step_eta = app
+        .list_state
+        .selected()
+        .and_then(|i| app.estimated_secs(i));
+    if step_eta.is_some() || app.remaining_secs().is_some() {
+        let mut eta = String::from(" ⏱ ");
+        if let Some(secs) = step_eta {
+            eta.push_str(&format!("step ~{} ", format_secs(secs)));
+        }
+        if let Some(secs) = app.remaining_secs() {
+            eta.push_str(&format!("· ~{} left ", format_secs(secs)));
+        }
+        spans.push(Span::styled(eta, Style::default().fg(Color::LightBlue)));
+        spans.push(Span::styled("│", Style::default().fg(Color::DarkGray)));
+    }
+
+    if app.mode == Mode::Interactive {
+        spans.push(Span::styled(
+            " ⌨ INTERACTIVE: keys go to the step, Esc to stop ",
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ));
+        spans.push(Span::styled("│", Style::default().fg(Color::DarkGray)));
+    }
+
+    spans.extend(vec![
         Span::styled(" ? Help ", Style::default().fg(Color::Yellow)),
         Span::styled("│ s Save │ q Quit ", Style::default().fg(Color::DarkGray)),
     ]);
@@ -103,6 +204,60 @@ fn render_status_bar(frame: &mut Frame, area: Rect, app: &App) {
     frame.render_widget(status_bar, area);
 }
 
+/// Renders the focus mode header: the position and title of the current step.
+fn render_focus_header(frame: &mut Frame, area: Rect, app: &App) {
+    let index = app.list_state.selected().unwrap_or(0);
+    let title = app.steps.get(index).map_or("", |s| s.title.as_str());
+    let header = Line::from(vec![
+        Span::styled(
+            format!(" {}/{} ", index + 1, app.steps.len()),
+            Style::default().fg(Color::DarkGray),
+        ),
+        Span::styled(
+            title.to_string(),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        ),
+    ]);
+    frame.render_widget(Paragraph::new(header), area);
+}
+
+/// Renders the focus mode footer with the navigation keys.
+fn render_focus_footer(frame: &mut Frame, area: Rect, interactive: bool) {
+    let footer = if interactive {
+        Line::from(Span::styled(
+            " ⌨ INTERACTIVE: keys go to the step, Esc to stop ",
+            Style::default().fg(Color::Yellow),
+        ))
+    } else {
+        Line::from(Span::styled(
+            " ← / → previous/next │ Enter run │ z leave focus mode ",
+            Style::default().fg(Color::DarkGray),
+        ))
+    };
+    frame.render_widget(Paragraph::new(footer), area);
+}
+
+/// Renders the banner of a reboot or new login asked for by a step, shown
+/// until the user confirms it is done.
+fn render_restart_banner(frame: &mut Frame, area: Rect, step: usize, notice: &RestartNotice) {
+    let banner = Line::from(Span::styled(
+        format!(
+            " 🔁 {notice} (step {}). The next steps are blocked until you confirm it is done. ",
+            step + 1
+        ),
+        Style::default()
+            .fg(Color::White)
+            .bg(Color::Red)
+            .add_modifier(Modifier::BOLD),
+    ));
+    frame.render_widget(
+        Paragraph::new(banner).style(Style::default().bg(Color::Red)),
+        area,
+    );
+}
+
 /// Renders the UI.
 ///
 /// This function is responsible for drawing all UI components:
@@ -110,37 +265,110 @@ fn render_status_bar(frame: &mut Frame, area: Rect, app: &App) {
 /// - Details panel (right panel)
 /// - Status bar (bottom)
 /// - Modal popups (overlays)
+/// - Restart banner (top), while a step waits for a reboot or new login
+///
+/// In focus mode, the step list and status bar are replaced by a one-line
+/// header and footer around the details panel.
 pub fn draw(frame: &mut Frame, app: &mut App) {
-    // Main layout: content area + status bar
-    let main_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Min(0), Constraint::Length(1)])
-        .split(frame.area());
+    let area = match &app.restart_notice {
+        Some((step, notice)) => {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(1), Constraint::Min(0)])
+                .split(frame.area());
+            render_restart_banner(frame, chunks[0], *step, notice);
+            chunks[1]
+        }
+        None => frame.area(),
+    };
+    let details_area = if app.focus_mode {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ])
+            .split(area);
+        render_focus_header(frame, chunks[0], app);
+        render_focus_footer(frame, chunks[2], app.mode == Mode::Interactive);
+        chunks[1]
+    } else {
+        // Main layout: content area + status bar
+        let main_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)])
+            .split(area);
 
-    let content_area = main_chunks[0];
-    let status_area = main_chunks[1];
+        // Content layout: step list + details
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(33), Constraint::Percentage(67)])
+            .split(main_chunks[0]);
 
-    // Content layout: step list + details
-    let chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(33), Constraint::Percentage(67)])
-        .split(content_area);
+        // Render the step list
+        app.reveal_selected();
+        step_list::render_step_list(
+            frame,
+            chunks[0],
+            &step_list::StepListView {
+                title: app.runbook_title().as_deref(),
+                steps: &app.steps,
+                collapsed: &app.collapsed,
+                restart: app.restart_notice.as_ref().map(|(step, _)| *step),
+                forced: &app.forced_steps,
+                sandbox: app.is_sandbox(),
+                sandbox_flipped: &app.sandbox_flipped,
+                elapsed: &app.execution_manager.elapsed(),
+                palette: &theme::palette(app.settings.theme, app.settings.status_markers),
+            },
+            &mut app.list_state,
+            &mut app.step_list_cache,
+        );
 
-    // Render the step list
-    step_list::render_step_list(frame, chunks[0], &app.steps, &mut app.list_state);
+        // Render the status bar
+        render_status_bar(frame, main_chunks[1], app);
+        chunks[1]
+    };
 
     // Render the details
     let selected_index = app.list_state.selected().unwrap_or(0);
+    let selected_step = app.steps.get(selected_index);
+
+    // Resolve the interpreter on the host (the sandbox image has its own)
+    let interpreter = selected_step
+        .filter(|step| step.is_executable())
+        .map(|step| {
+            if app.is_sandboxed(selected_index) {
+                let image = &app.execution_manager.executor.context.docker_image;
+                return format!("📦 sandbox ({image}), b to run on this machine");
+            }
+            let language = step
+                .code_blocks
+                .first()
+                .and_then(|block| block.language.as_deref());
+            probe_interpreter(language).to_string()
+        });
+
     app.content_height = details::render_details(
         frame,
-        chunks[1],
-        app.steps.get(selected_index),
-        app.details_scroll,
+        details_area,
+        &details::DetailsView {
+            step: selected_step,
+            forced: app.forced_steps.contains(&selected_index),
+            interpreter: interpreter.as_deref(),
+            attempt: app.attempt_view,
+            values: &app.modal.variable_store,
+            inline_commands: app.inline_commands(selected_index),
+            group: selected_step
+                .and_then(|_| groups::describe(&app.steps, selected_index))
+                .as_deref(),
+            separate_streams: app.execution_manager.executor.context.separate_streams,
+            show_env_changes: app.show_env_changes,
+            scroll: app.details_scroll,
+        },
     );
-    app.viewport_height = chunks[1].height.saturating_sub(2);
-
-    // Render the status bar
-    render_status_bar(frame, status_area, app);
+    app.viewport_height = details_area.height.saturating_sub(2);
 
     // Render modals if active
     match app.mode {
@@ -151,9 +379,53 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
                 .required_placeholders
                 .get(app.modal.current_placeholder_idx)
             {
-                popups::input::render(frame, frame.area(), var_name, &app.modal.input_buffer);
+                let description = app
+                    .placeholder_spec(var_name)
+                    .and_then(|s| s.description.as_deref());
+                popups::input::render(frame, frame.area(), &app.modal, var_name, description);
             }
         }
+        Mode::ScratchInput => {
+            popups::scratch::render(frame, frame.area(), &app.modal.input_buffer);
+        }
+        Mode::SnippetPalette => {
+            let entries: Vec<(String, String)> = app
+                .snippet_matches()
+                .into_iter()
+                .map(|i| {
+                    (
+                        app.snippets[i].name.clone(),
+                        app.snippets[i].command.clone(),
+                    )
+                })
+                .collect();
+            let empty = app
+                .snippets
+                .is_empty()
+                .then_some("No snippets yet: add one with `compass snippet add <NAME> <COMMAND>`.");
+            popups::palette::render(
+                frame,
+                frame.area(),
+                "📎 Snippets",
+                &entries,
+                &app.palette,
+                empty,
+            );
+        }
+        Mode::CommandPalette => {
+            let entries: Vec<(String, String)> = commands::matching(&app.palette)
+                .into_iter()
+                .map(|c| (c.name.to_string(), c.key.to_string()))
+                .collect();
+            popups::palette::render(
+                frame,
+                frame.area(),
+                "🔎 Commands",
+                &entries,
+                &app.palette,
+                None,
+            );
+        }
         Mode::SafetyAlert => {
             if let Some(ref pattern) = app.safety_pattern {
                 popups::safety::render(frame, frame.area(), pattern);
@@ -164,6 +436,47 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
                 popups::dependency::render(frame, frame.area(), message);
             }
         }
+        Mode::PrerequisiteAlert => {
+            if let Some(ref message) = app.safety_pattern {
+                popups::prerequisite::render(frame, frame.area(), message);
+            }
+        }
+        Mode::RestartAlert => {
+            if let Some(ref message) = app.safety_pattern {
+                popups::restart::render(frame, frame.area(), message);
+            }
+        }
+        Mode::ClusterAlert => {
+            if let Some(ref message) = app.safety_pattern {
+                popups::cluster::render(frame, frame.area(), message);
+            }
+        }
+        Mode::SecretConsent => {
+            if let Some(ref message) = app.safety_pattern {
+                popups::secrets::render(frame, frame.area(), message, app.secret_fetch.is_some());
+            }
+        }
+        Mode::CloudCheck | Mode::CloudAlert => {
+            if let Some(ref message) = app.safety_pattern {
+                popups::cloud::render(frame, frame.area(), message);
+            }
+        }
+        Mode::PolicyAlert => {
+            if let Some(ref reason) = app.safety_pattern {
+                popups::policy::render(frame, frame.area(), reason);
+            }
+        }
+        Mode::PlanReview => {
+            if let Some(step) = selected_step {
+                popups::plan::render(
+                    frame,
+                    frame.area(),
+                    step.title.trim(),
+                    &step.output,
+                    app.diff_scroll,
+                );
+            }
+        }
         Mode::HelpModal => {
             popups::help::render(frame, frame.area(), app.help_scroll);
         }
@@ -177,6 +490,35 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
                 popups::recovery::render(frame, frame.area(), rec);
             }
         }
-        Mode::Normal => {}
+        Mode::DebugOverlay => {
+            popups::debug::render(frame, frame.area());
+        }
+        Mode::DependencyCheck => {
+            if let Some(ref report) = app.dependency_report {
+                popups::check::render(frame, frame.area(), report);
+            }
+        }
+        Mode::DiffPreview => {
+            popups::diff::render(frame, frame.area(), &app.edit_preview, app.diff_scroll);
+        }
+        Mode::DryRunPreview => {
+            if let Some(ref preview) = app.dry_run_preview
+                && let Some(step) = app.list_state.selected().and_then(|i| app.steps.get(i))
+            {
+                popups::dry_run::render(
+                    frame,
+                    frame.area(),
+                    step.title.trim(),
+                    preview,
+                    app.diff_scroll,
+                );
+            }
+        }
+        Mode::Normal | Mode::Interactive => {}
+    }
+
+    // The tutorial hint stays visible over the popups it talks about
+    if let Some((index, lesson)) = tutorial::current(app) {
+        popups::tutorial::render(frame, details_area, index, lesson);
     }
 }
@@ -12,8 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::core::models::Step;
+use crate::core::executor::conditions::evaluator::{ConditionEvaluator, StandardEvaluator};
+use crate::core::executor::engine::builder::{CommandBuilder, Segment};
+use crate::core::executor::engine::session::STDERR_TAG;
+use crate::core::executor::security::redact::MASK;
+use crate::core::models::{Step, StepStatus};
+use crate::ui::utils::format_elapsed;
 use ansi_to_tui::IntoText;
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
 use ratatui::{
     Frame,
     layout::Rect,
@@ -21,6 +27,7 @@ use ratatui::{
     text::{Line, Span},
     widgets::{Block, Borders, Paragraph, Wrap},
 };
+use std::collections::HashMap;
 use std::sync::OnceLock;
 use syntect::easy::HighlightLines;
 use syntect::highlighting::ThemeSet;
@@ -37,39 +44,330 @@ fn get_theme_set() -> &'static ThemeSet {
     THEME_SET.get_or_init(ThemeSet::load_defaults)
 }
 
+/// Renders command output with its ANSI colors, in `style` when given.
+fn push_output(lines: &mut Vec<Line<'_>>, output: &str, style: Option<Style>) {
+    if output.is_empty() {
+        return;
+    }
+    match output.as_bytes().into_text() {
+        Ok(text) => lines.extend(text.lines.into_iter().map(|line| match style {
+            Some(style) => line.style(style),
+            None => line,
+        })),
+        Err(_) => {
+            // Fallback to plain text if parsing fails
+            for line in output.lines() {
+                lines.push(Line::from(Span::styled(
+                    line.to_string(),
+                    style.unwrap_or_else(|| Style::default().fg(Color::Gray)),
+                )));
+            }
+        }
+    }
+}
+
+/// Builds the preview of a code line with its placeholder values substituted,
+/// masking those of the step's secrets.
+fn preview_spans<'a>(step: &Step, segments: Vec<Segment<'a>>) -> Vec<Span<'a>> {
+    let value_style = Style::default()
+        .fg(Color::LightGreen)
+        .add_modifier(Modifier::BOLD);
+    segments
+        .into_iter()
+        .map(|segment| match segment {
+            Segment::Text(text) => Span::raw(text),
+            Segment::Placeholder {
+                name,
+                value: Some(_),
+                ..
+            } if step.is_secret(name) => Span::styled(MASK, value_style),
+            Segment::Placeholder {
+                value: Some(value), ..
+            } => Span::styled(value, value_style),
+            Segment::Placeholder { token, .. } => Span::styled(
+                token,
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::ITALIC),
+            ),
+        })
+        .collect()
+}
+
+/// Renders a step description, kept as Markdown by the parser: emphasis,
+/// links (followed by their address), list items and tables are styled.
+pub fn description_lines(description: &str) -> Vec<Line<'static>> {
+    let text = Style::default().fg(Color::White);
+    let mut lines = Vec::new();
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    let mut style = text;
+    let mut styles = Vec::new();
+    let mut links = Vec::new();
+    let mut lists: Vec<Option<u64>> = Vec::new();
+    let mut table: Option<Vec<Vec<String>>> = None;
+    let mut in_code = false;
+
+    let flush = |lines: &mut Vec<Line<'static>>, spans: &mut Vec<Span<'static>>| {
+        if !spans.is_empty() {
+            lines.push(Line::from(std::mem::take(spans)));
+        }
+    };
+
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH;
+    for event in Parser::new_ext(description, options) {
+        match event {
+            Event::Start(tag @ (Tag::Strong | Tag::Emphasis | Tag::Strikethrough)) => {
+                styles.push(style);
+                style = style.add_modifier(match tag {
+                    Tag::Strong => Modifier::BOLD,
+                    Tag::Emphasis => Modifier::ITALIC,
+                    _ => Modifier::CROSSED_OUT,
+                });
+            }
+            Event::Start(Tag::Link { dest_url, .. }) => {
+                styles.push(style);
+                style = style
+                    .fg(Color::LightBlue)
+                    .add_modifier(Modifier::UNDERLINED);
+                links.push(dest_url.to_string());
+            }
+            Event::End(TagEnd::Strong | TagEnd::Emphasis | TagEnd::Strikethrough) => {
+                style = styles.pop().unwrap_or(text);
+            }
+            Event::End(TagEnd::Link) => {
+                style = styles.pop().unwrap_or(text);
+                let url = links.pop().unwrap_or_default();
+                // Show where the link goes, unless its text already says it
+                if !url.is_empty() && spans.last().is_none_or(|s| s.content != url) {
+                    spans.push(Span::styled(
+                        format!(" ({url})"),
+                        Style::default().fg(Color::DarkGray),
+                    ));
+                }
+            }
+            Event::Start(Tag::List(start)) => {
+                flush(&mut lines, &mut spans);
+                lists.push(start);
+            }
+            Event::End(TagEnd::List(_)) => {
+                flush(&mut lines, &mut spans);
+                lists.pop();
+            }
+            Event::Start(Tag::Item) => {
+                flush(&mut lines, &mut spans);
+                let marker = match lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ => "• ".to_string(),
+                };
+                spans.push(Span::raw("  ".repeat(lists.len().saturating_sub(1))));
+                spans.push(Span::styled(marker, Style::default().fg(Color::Yellow)));
+            }
+            Event::Start(Tag::Table(_)) => {
+                flush(&mut lines, &mut spans);
+                table = Some(Vec::new());
+            }
+            Event::Start(Tag::TableHead | Tag::TableRow) => {
+                if let Some(rows) = table.as_mut() {
+                    rows.push(Vec::new());
+                }
+            }
+            Event::Start(Tag::TableCell) => {
+                if let Some(row) = table.as_mut().and_then(|rows| rows.last_mut()) {
+                    row.push(String::new());
+                }
+            }
+            Event::End(TagEnd::Table) => {
+                lines.extend(table_lines(&table.take().unwrap_or_default()));
+            }
+            Event::Start(Tag::CodeBlock(_)) => {
+                flush(&mut lines, &mut spans);
+                in_code = true;
+            }
+            Event::End(TagEnd::CodeBlock) => in_code = false,
+            Event::Text(content) | Event::Code(content) | Event::Html(content)
+                if table.is_some() =>
+            {
+                if let Some(cell) = table
+                    .as_mut()
+                    .and_then(|rows| rows.last_mut())
+                    .and_then(|row| row.last_mut())
+                {
+                    cell.push_str(&content);
+                }
+            }
+            Event::Text(content) if in_code => {
+                for line in content.lines() {
+                    lines.push(Line::from(Span::styled(
+                        format!("  {line}"),
+                        Style::default().fg(Color::Gray),
+                    )));
+                }
+            }
+            Event::Text(content) | Event::Html(content) | Event::InlineHtml(content) => {
+                spans.push(Span::styled(content.to_string(), style));
+            }
+            Event::Code(code) => {
+                spans.push(Span::styled(code.to_string(), style.fg(Color::Cyan)));
+            }
+            Event::SoftBreak
+            | Event::HardBreak
+            | Event::End(TagEnd::Paragraph | TagEnd::Item | TagEnd::Heading(_)) => {
+                flush(&mut lines, &mut spans);
+            }
+            _ => {}
+        }
+    }
+    flush(&mut lines, &mut spans);
+    lines
+}
+
+/// Lays a table out in aligned columns, its header in bold.
+fn table_lines(rows: &[Vec<String>]) -> Vec<Line<'static>> {
+    let mut widths: Vec<usize> = Vec::new();
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            let width = cell.trim().chars().count();
+            match widths.get_mut(i) {
+                Some(max) => *max = (*max).max(width),
+                None => widths.push(width),
+            }
+        }
+    }
+    let border = Style::default().fg(Color::DarkGray);
+    let mut lines = Vec::new();
+    for (n, row) in rows.iter().enumerate() {
+        let style = if n == 0 {
+            Style::default()
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        let mut spans = Vec::new();
+        for (i, cell) in row.iter().enumerate() {
+            if i > 0 {
+                spans.push(Span::styled(" │ ", border));
+            }
+            let width = widths.get(i).copied().unwrap_or_default();
+            spans.push(Span::styled(format!("{:width$}", cell.trim()), style));
+        }
+        lines.push(Line::from(spans));
+        if n == 0 {
+            let rule: Vec<String> = widths.iter().map(|w| "─".repeat(*w)).collect();
+            lines.push(Line::from(Span::styled(rule.join("─┼─"), border)));
+        }
+    }
+    lines
+}
+
+/// What the details panel shows.
+pub struct DetailsView<'a> {
+    /// The selected step to display.
+    pub step: Option<&'a Step>,
+    /// Whether the user force-enabled the step despite its condition.
+    pub forced: bool,
+    /// The resolved interpreter and version, shown in the header.
+    pub interpreter: Option<&'a str>,
+    /// The past run to show instead of the latest output, if any.
+    pub attempt: Option<usize>,
+    /// The placeholder values filled so far.
+    pub values: &'a HashMap<String, String>,
+    /// Commands found in the description, runnable with 1-9.
+    pub inline_commands: &'a [String],
+    /// The parallel group of the step and how its steps are doing.
+    pub group: Option<&'a str>,
+    /// Whether commands run with pipes, so they cannot be typed into.
+    pub separate_streams: bool,
+    /// Whether the environment changes of the last run are expanded.
+    pub show_env_changes: bool,
+    /// The current vertical scroll offset.
+    pub scroll: u16,
+}
+
 /// Renders the details panel for the selected step.
 ///
 /// This panel shows:
 /// - Step description
-/// - Code block(s) with simple syntax highlighting
+/// - Code block(s) with simple syntax highlighting, and the placeholder
+///   values that will be substituted (secrets masked)
 /// - Execution output with basic ANSI color support
 ///
-/// # Arguments
-///
-/// * `frame` - The frame to render into.
-/// * `area` - The available area for the widget.
-/// * `step` - The selected step to display.
-/// * `scroll` - The current vertical scroll offset.
-///
 /// # Returns
 ///
 /// The total height of the content (for scrolling logic).
-pub fn render_details(frame: &mut Frame, area: Rect, step: Option<&Step>, scroll: u16) -> u16 {
+pub fn render_details(frame: &mut Frame, area: Rect, view: &DetailsView) -> u16 {
+    let DetailsView {
+        step,
+        forced,
+        interpreter,
+        attempt,
+        values,
+        inline_commands,
+        group,
+        separate_streams,
+        show_env_changes,
+        scroll,
+    } = *view;
     let mut text_lines = Vec::new();
 
     if let Some(step) = step {
+        // --- Condition ---
+        if let Some(condition) = &step.condition {
+            let (text, color) = if StandardEvaluator::new().evaluate(condition) {
+                (format!("Condition: {condition} ✓ (met)"), Color::Green)
+            } else if forced {
+                (
+                    format!("Condition: {condition} ✗ (not met, force-enabled)"),
+                    Color::Yellow,
+                )
+            } else {
+                (
+                    format!(
+                        "Condition: {condition} ✗ (not met, will be skipped; press f to force)"
+                    ),
+                    Color::Red,
+                )
+            };
+            text_lines.push(Line::from(Span::styled(text, Style::default().fg(color))));
+            text_lines.push(Line::from(""));
+        }
+
         // --- Description ---
-        text_lines.push(Line::from(Span::styled(
-            &step.description,
-            Style::default().fg(Color::White),
-        )));
+        text_lines.extend(description_lines(&step.description));
         text_lines.push(Line::from(""));
 
+        // --- Inline Commands ---
+        if !inline_commands.is_empty() {
+            text_lines.push(Line::from(Span::styled(
+                "Commands in the text (press the number to run one):",
+                Style::default().fg(Color::DarkGray),
+            )));
+            for (n, command) in inline_commands.iter().take(9).enumerate() {
+                text_lines.push(Line::from(vec![
+                    Span::styled(
+                        format!("  [{}] ", n + 1),
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(command.as_str(), Style::default().fg(Color::Cyan)),
+                ]));
+            }
+            text_lines.push(Line::from(""));
+        }
+
         // --- Code Blocks ---
         for block in &step.code_blocks {
-            let lang = block.language.as_deref().unwrap_or("text");
+            let lang =
+                block
+                    .language
+                    .as_deref()
+                    .unwrap_or(if block.indented { "shell" } else { "text" });
             // Header
-            text_lines.push(Line::from(vec![
+            let mut header = vec![
                 Span::raw("```"),
                 Span::styled(
                     lang,
@@ -77,7 +375,20 @@ pub fn render_details(frame: &mut Frame, area: Rect, step: Option<&Step>, scroll
                         .fg(Color::Yellow)
                         .add_modifier(Modifier::ITALIC),
                 ),
-            ]));
+            ];
+            if block.indented {
+                header.push(Span::styled(
+                    "  indented, asks before running",
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            if let Some(path) = &block.write_to {
+                header.push(Span::styled(
+                    format!("  → written to {path}"),
+                    Style::default().fg(Color::Cyan),
+                ));
+            }
+            text_lines.push(Line::from(header));
 
             // Prepare highlighter
             let ps = get_syntax_set();
@@ -96,6 +407,17 @@ pub fn render_details(frame: &mut Frame, area: Rect, step: Option<&Step>, scroll
 
             // Content
             for line in block.content.lines() {
+                let segments =
+                    CommandBuilder::split_placeholders(line, &block.placeholders, values);
+                let filled = segments
+                    .iter()
+                    .any(|s| matches!(s, Segment::Placeholder { value: Some(_), .. }));
+                if filled {
+                    // Show exactly what will run, with the substituted values highlighted
+                    text_lines.push(Line::from(preview_spans(step, segments)));
+                    continue;
+                }
+
                 // Syntect expects standard Rust strings, but technically prefers newlines for context.
                 // However, for single-pass highlighting of lines, this works well enough for display.
                 let ranges = h.highlight_line(line, ps).unwrap_or_default();
@@ -117,31 +439,156 @@ pub fn render_details(frame: &mut Frame, area: Rect, step: Option<&Step>, scroll
             text_lines.push(Line::from(""));
         }
 
+        // --- Origin, prerequisites and scope of a `compass` block ---
+        let scope = &step.scope;
+        let settings = [
+            step.session_only
+                .then(|| "Session-only: added during this session, not in the runbook".to_string()),
+            step.origin
+                .as_ref()
+                .map(|origin| format!("Included from: {origin}")),
+            group.map(ToString::to_string),
+            (!step.requires.is_empty()).then(|| format!("Requires: {}", step.requires.join(", "))),
+            scope.cwd.as_ref().map(|cwd| format!("Runs in: {cwd}")),
+            (!scope.env.is_empty()).then(|| {
+                let vars: Vec<_> = scope.env.iter().map(|(k, v)| format!("{k}={v}")).collect();
+                format!("Environment: {}", vars.join(" "))
+            }),
+            scope
+                .verify
+                .as_ref()
+                .map(|cmd| format!("Verified by: {cmd}")),
+            step.rollback
+                .as_ref()
+                .map(|cmd| format!("Rolled back by (U): {cmd}")),
+        ];
+        let shown = settings.iter().any(Option::is_some);
+        for setting in settings.into_iter().flatten() {
+            text_lines.push(Line::from(Span::styled(
+                setting,
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+        if shown {
+            text_lines.push(Line::from(""));
+        }
+
         // --- Output ---
-        let raw_output = &step.output;
+        let run = attempt.and_then(|n| step.history.get(n).map(|run| (n, run)));
+        let raw_output = run.map_or(&step.output, |(_, run)| &run.output);
         let trimmed_output = raw_output.trim();
 
+        let output_header = match run {
+            Some((n, run)) => {
+                let duration = run
+                    .duration_ms
+                    .map(|ms| format!(", {}", format_elapsed(ms)))
+                    .unwrap_or_default();
+                format!(
+                    "--- Run {}/{} ({:?}{duration}) [ ] to browse ---",
+                    n + 1,
+                    step.history.len(),
+                    run.status
+                )
+            }
+            None if step.status == StepStatus::Running && separate_streams => {
+                "--- Output (running, x to cancel) ---".to_string()
+            }
+            None if step.status == StepStatus::Running => {
+                "--- Output (running, i to type into it, x to cancel) ---".to_string()
+            }
+            None => {
+                let duration = step
+                    .execution
+                    .as_ref()
+                    .filter(|_| step.status != StepStatus::Pending)
+                    .map(|e| format!(" in {}", format_elapsed(e.duration_ms)))
+                    .unwrap_or_default();
+                if step.history.len() > 1 {
+                    format!(
+                        "--- Output (latest of {} runs{duration}, [ to browse) ---",
+                        step.history.len()
+                    )
+                } else if duration.is_empty() {
+                    "--- Output ---".to_string()
+                } else {
+                    format!("--- Output (finished{duration}) ---")
+                }
+            }
+        };
+
         if !trimmed_output.is_empty() {
             text_lines.push(Line::from(Span::styled(
-                "--- Output ---",
+                output_header,
                 Style::default()
                     .fg(Color::Magenta)
                     .add_modifier(Modifier::BOLD),
             )));
 
-            // Render ANSI output using ansi-to-tui
-            match trimmed_output.as_bytes().into_text() {
-                Ok(output_text) => {
-                    text_lines.extend(output_text.lines);
+            // Lines read from stderr (runs without a PTY) get their own section
+            let (stderr, stdout): (Vec<&str>, Vec<&str>) = trimmed_output
+                .lines()
+                .partition(|line| line.starts_with(STDERR_TAG));
+            push_output(&mut text_lines, &stdout.join("\n"), None);
+            if !stderr.is_empty() {
+                let style = Style::default().fg(Color::LightRed);
+                text_lines.push(Line::from(Span::styled(
+                    "--- stderr ---",
+                    style.add_modifier(Modifier::BOLD),
+                )));
+                let stderr: Vec<&str> = stderr
+                    .iter()
+                    .map(|line| line.trim_start_matches(STDERR_TAG))
+                    .collect();
+                push_output(&mut text_lines, &stderr.join("\n"), Some(style));
+            }
+        }
+
+        // --- Exit code of the run shown ---
+        let exit_code = match run {
+            Some((_, run)) => run.exit_code,
+            None if matches!(step.status, StepStatus::Pending | StepStatus::Running) => None,
+            None => step.execution.as_ref().and_then(|e| e.exit_code),
+        };
+        if let Some(code) = exit_code {
+            let color = if code == 0 { Color::Green } else { Color::Red };
+            text_lines.push(Line::from(Span::styled(
+                format!("--- Exit code {code} ---"),
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            )));
+        }
+
+        // --- Environment changes of the last run ---
+        if let Some(diff) = step.context_diff.as_ref().filter(|_| run.is_none()) {
+            let count = diff.variables.len() + usize::from(diff.current_dir.is_some());
+            let header = if show_env_changes {
+                "▾ Environment changes (E to collapse)".to_string()
+            } else {
+                format!("▸ Environment changes: {count} (E to expand)")
+            };
+            text_lines.push(Line::from(Span::styled(
+                header,
+                Style::default()
+                    .fg(Color::LightBlue)
+                    .add_modifier(Modifier::BOLD),
+            )));
+            if show_env_changes {
+                if let (Some(before), Some(after)) = (&diff.previous_dir, &diff.current_dir) {
+                    text_lines.push(Line::from(Span::styled(
+                        format!("  cd {before} → {after}"),
+                        Style::default().fg(Color::Cyan),
+                    )));
                 }
-                Err(_) => {
-                    // Fallback to plain text if parsing fails
-                    for line in trimmed_output.lines() {
-                        text_lines.push(Line::from(Span::styled(
-                            line,
-                            Style::default().fg(Color::Gray),
-                        )));
-                    }
+                for change in &diff.variables {
+                    let color = match (&change.before, &change.after) {
+                        (None, Some(_)) => Color::Green,
+                        (Some(_), Some(_)) => Color::Yellow,
+                        (_, None) => Color::Red,
+                    };
+                    text_lines.push(Line::from(Span::styled(
+                        format!("  {change}"),
+                        Style::default().fg(color),
+                    )));
                 }
             }
         }
@@ -173,8 +620,13 @@ pub fn render_details(frame: &mut Frame, area: Rect, step: Option<&Step>, scroll
         total_lines += 2;
     }
 
+    let title = interpreter.map_or_else(
+        || " Details ".to_string(),
+        |interpreter| format!(" Details │ {interpreter} "),
+    );
+
     let details = Paragraph::new(text_lines)
-        .block(Block::default().title(" Details ").borders(Borders::ALL))
+        .block(Block::default().title(title).borders(Borders::ALL))
         .wrap(Wrap { trim: true })
         .scroll((scroll, 0));
 
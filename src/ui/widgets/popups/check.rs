@@ -0,0 +1,86 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::core::executor::checker::{CheckResult, install_hint};
+use crate::ui::utils::centered_rect;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+pub fn render(frame: &mut Frame, area: Rect, report: &CheckResult) {
+    let area = centered_rect(70, 70, area);
+    frame.render_widget(Clear, area);
+
+    let color = if report.missing.is_empty() {
+        Color::Green
+    } else {
+        Color::Yellow
+    };
+    let block = Block::default()
+        .title(Span::styled(
+            " 📦 Dependency Check (Esc to close) ",
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(color));
+
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            format!(
+                "  {} present, {} missing",
+                report.present.len(),
+                report.missing.len()
+            ),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+
+    if report.present.is_empty() && report.missing.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  No external commands detected.",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    for cmd in &report.missing {
+        lines.push(Line::from(vec![
+            Span::styled("  ❌ ", Style::default().fg(Color::Red)),
+            Span::styled(
+                cmd.clone(),
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!("  → {}", install_hint(cmd)),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]));
+    }
+    for cmd in &report.present {
+        lines.push(Line::from(vec![
+            Span::styled("  ✅ ", Style::default().fg(Color::Green)),
+            Span::raw(cmd.clone()),
+        ]));
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+    frame.render_widget(paragraph, area);
+}
@@ -0,0 +1,67 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::core::infrastructure::logging::recent_events;
+use crate::ui::utils::centered_rect;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+pub fn render(frame: &mut Frame, area: Rect) {
+    let area = centered_rect(90, 80, area);
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(Span::styled(
+            " 🐞 Debug Log (F12/Esc to close) ",
+            Style::default()
+                .fg(Color::Magenta)
+                .add_modifier(Modifier::BOLD),
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Magenta));
+
+    // Only keep the events that fit, newest at the bottom.
+    let visible = area.height.saturating_sub(2) as usize;
+    let events = recent_events();
+    let skip = events.len().saturating_sub(visible);
+
+    let lines: Vec<Line> = if events.is_empty() {
+        vec![Line::from(Span::styled(
+            "No events recorded yet.",
+            Style::default().fg(Color::DarkGray),
+        ))]
+    } else {
+        events
+            .into_iter()
+            .skip(skip)
+            .map(|event| {
+                let color = if event.contains(" ERROR ") {
+                    Color::Red
+                } else if event.contains(" WARN ") {
+                    Color::Yellow
+                } else {
+                    Color::Gray
+                };
+                Line::from(Span::styled(event, Style::default().fg(color)))
+            })
+            .collect()
+    };
+
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
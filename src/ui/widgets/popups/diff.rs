@@ -0,0 +1,74 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::core::analysis::edits::DiffLine;
+use crate::ui::utils::centered_rect;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+pub fn render(frame: &mut Frame, area: Rect, preview: &[DiffLine], scroll: u16) {
+    let area = centered_rect(80, 80, area);
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(Span::styled(
+            " 📝 This step edits files (Enter: Apply | Esc: Cancel | ↑/↓: Scroll) ",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+
+    let lines: Vec<Line> = preview
+        .iter()
+        .map(|line| match line {
+            DiffLine::File(path) => Line::from(Span::styled(
+                format!(" {path}"),
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            )),
+            DiffLine::Hunk => {
+                Line::from(Span::styled(" ···", Style::default().fg(Color::DarkGray)))
+            }
+            DiffLine::Added(text) => Line::from(Span::styled(
+                format!(" + {text}"),
+                Style::default().fg(Color::Green),
+            )),
+            DiffLine::Removed(text) => Line::from(Span::styled(
+                format!(" - {text}"),
+                Style::default().fg(Color::Red),
+            )),
+            DiffLine::Context(text) => Line::from(Span::styled(
+                format!("   {text}"),
+                Style::default().fg(Color::Gray),
+            )),
+            DiffLine::Note(note) => Line::from(Span::styled(
+                format!("   {note}"),
+                Style::default()
+                    .fg(Color::DarkGray)
+                    .add_modifier(Modifier::ITALIC),
+            )),
+        })
+        .collect();
+
+    let paragraph = Paragraph::new(lines).block(block).scroll((scroll, 0));
+    frame.render_widget(paragraph, area);
+}
@@ -0,0 +1,71 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::ui::utils::centered_rect;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+/// Marks the lines Compass handles itself in a dry run.
+const HANDLED: &str = "(Handled by Compass)";
+
+pub fn render(frame: &mut Frame, area: Rect, title: &str, preview: &str, scroll: u16) {
+    let area = centered_rect(80, 80, area);
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(Span::styled(
+            format!(" 🔍 Dry run: {title}, nothing was executed (Esc: Close | ↑/↓: Scroll) "),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let lines: Vec<Line> = preview
+        .lines()
+        .map(|line| {
+            if line.ends_with(HANDLED)
+                && let Some((command, note)) = line.rsplit_once("  # ")
+            {
+                Line::from(vec![
+                    Span::raw(format!(" {command}")),
+                    Span::styled(
+                        format!("  # {note}"),
+                        Style::default()
+                            .fg(Color::DarkGray)
+                            .add_modifier(Modifier::ITALIC),
+                    ),
+                ])
+            } else if line.starts_with("# writes ") || line.starts_with("# end of ") {
+                Line::from(Span::styled(
+                    format!(" {line}"),
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ))
+            } else {
+                Line::from(format!(" {line}"))
+            }
+        })
+        .collect();
+
+    let paragraph = Paragraph::new(lines).block(block).scroll((scroll, 0));
+    frame.render_widget(paragraph, area);
+}
@@ -21,6 +21,75 @@ use ratatui::{
     widgets::{Block, Borders, Clear, Paragraph, Wrap},
 };
 
+/// Keyboard shortcuts, grouped by section.
+///
+/// Shared with the linear renderer, which prints them instead of drawing a popup.
+pub const SHORTCUTS: &[(&str, &[(&str, &str)])] = &[
+    (
+        "Navigation",
+        &[
+            ("↑ / k", "Move to previous step"),
+            ("↓ / j", "Move to next step"),
+            ("← / h", "Collapse the step, or go to its parent"),
+            ("→ / l", "Expand the step, or go to its first sub-step"),
+            ("PgUp / K", "Scroll details up"),
+            ("PgDown / J", "Scroll details down"),
+            ("[ / ]", "Browse previous runs of the step"),
+            ("u", "Revert cwd/env to before the step"),
+            ("U", "Roll back the step (edited files, rollback command)"),
+            ("R", "Rewind the session to before the step"),
+            ("c", "Check the runbook's dependencies"),
+            ("E", "Show/hide the environment changes of the step"),
+            ("z", "Toggle focus mode (current step only)"),
+            ("→ / Space", "Next step (focus mode)"),
+            ("← / Bksp", "Previous step (focus mode)"),
+        ],
+    ),
+    (
+        "Execution",
+        &[
+            ("Enter", "Execute the selected step"),
+            ("v", "Dry run: show the command without running it"),
+            (
+                "A",
+                "Auto-pilot: run the remaining steps, stop at a failure",
+            ),
+            ("1-9", "Run an inline command of a step without code"),
+            (
+                "i",
+                "Type into the running step, e.g. to answer a prompt (Esc to stop)",
+            ),
+            ("x / Ctrl+C", "Cancel the running step, killing its command"),
+            ("f", "Force-enable a step whose condition is not met"),
+            ("b", "Run the step in the Docker sandbox / on this machine"),
+            ("a", "Add a scratch step with a command you type"),
+            ("S", "Run a snippet from your library as a new step"),
+            ("D", "Duplicate the selected step"),
+            ("< / >", "Move the selected step up / down"),
+            ("Esc", "Cancel current modal/action"),
+        ],
+    ),
+    (
+        "Export & Save",
+        &[
+            ("s", "Save/export session report"),
+            (
+                "e",
+                "Save the context (cwd, env, placeholders) for --context",
+            ),
+        ],
+    ),
+    (
+        "Application",
+        &[
+            ("?", "Show this help panel"),
+            ("Ctrl+P", "Search every action by name"),
+            ("F12", "Show the debug log overlay"),
+            ("q", "Quit Compass"),
+        ],
+    ),
+];
+
 pub fn render(frame: &mut Frame, area: Rect, scroll: u16) {
     let area = centered_rect(70, 70, area);
     frame.render_widget(Clear, area);
@@ -35,33 +104,9 @@ pub fn render(frame: &mut Frame, area: Rect, scroll: u16) {
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::Cyan));
 
-    let help_items = vec![
-        (
-            "Navigation",
-            vec![
-                ("↑ / k", "Move to previous step"),
-                ("↓ / j", "Move to next step"),
-                ("PgUp / K", "Scroll details up"),
-                ("PgDown / J", "Scroll details down"),
-            ],
-        ),
-        (
-            "Execution",
-            vec![
-                ("Enter", "Execute the selected step"),
-                ("Esc", "Cancel current modal/action"),
-            ],
-        ),
-        ("Export & Save", vec![("s", "Save/export session report")]),
-        (
-            "Application",
-            vec![("?", "Show this help panel"), ("q", "Quit Compass")],
-        ),
-    ];
-
     let mut lines: Vec<Line> = vec![Line::from("")];
 
-    for (section, shortcuts) in help_items {
+    for &(section, shortcuts) in SHORTCUTS {
         // Section header
         lines.push(Line::from(vec![Span::styled(
             format!("  ─── {section} ───"),
@@ -72,7 +117,7 @@ pub fn render(frame: &mut Frame, area: Rect, scroll: u16) {
         lines.push(Line::from(""));
 
         // Shortcuts
-        for (key, description) in shortcuts {
+        for &(key, description) in shortcuts {
             lines.push(Line::from(vec![
                 Span::raw("    "),
                 Span::styled(
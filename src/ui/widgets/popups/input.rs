@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::ui::state::modal::ModalState;
 use crate::ui::utils::centered_rect;
 use ratatui::{
     Frame,
@@ -21,10 +22,28 @@ use ratatui::{
     widgets::{Block, Borders, Clear, Paragraph, Wrap},
 };
 
-pub fn render(frame: &mut Frame, area: Rect, var_name: &str, current_input: &str) {
+/// Renders the prompt for `var_name`, with its description from the runbook
+/// and why the last value was rejected.
+pub fn render(
+    frame: &mut Frame,
+    area: Rect,
+    modal: &ModalState,
+    var_name: &str,
+    description: Option<&str>,
+) {
     let area = centered_rect(60, 30, area);
     frame.render_widget(Clear, area);
 
+    let is_secret = modal.is_secret();
+    let is_env = modal.is_env() && !modal.is_placeholder();
+
+    // Secrets show one dot per typed character
+    let current_input = if is_secret {
+        "•".repeat(modal.input_buffer.chars().count())
+    } else {
+        modal.input_buffer.clone()
+    };
+
     // Dynamic title to ensure visibility of input
     let title = format!(" [ Input: {var_name} ] (Typing: \"{current_input}\") ");
 
@@ -42,10 +61,16 @@ pub fn render(frame: &mut Frame, area: Rect, var_name: &str, current_input: &str
                 .add_modifier(Modifier::BOLD),
         );
 
-    let text = vec![
+    let mut text = vec![
         Line::from(""),
         Line::from(vec![
-            Span::raw("  Please provide a value for: "),
+            Span::raw(if is_secret {
+                "  Please provide the secret (not saved): "
+            } else if is_env {
+                "  Please provide a value for the environment variable: "
+            } else {
+                "  Please provide a value for: "
+            }),
             Span::styled(
                 var_name,
                 Style::default()
@@ -53,8 +78,30 @@ pub fn render(frame: &mut Frame, area: Rect, var_name: &str, current_input: &str
                     .add_modifier(Modifier::BOLD),
             ),
         ]),
-        Line::from(""),
-        Line::from(vec![
+    ];
+    if let Some(description) = description {
+        text.push(Line::from(Span::styled(
+            format!("  {description}"),
+            Style::default().fg(Color::Gray),
+        )));
+    }
+    text.push(Line::from(""));
+    if let Some(choices) = modal.current_choices() {
+        // Choice placeholders list their values, the selected one highlighted
+        for choice in choices {
+            text.push(if *choice == modal.input_buffer {
+                Line::from(Span::styled(
+                    format!("  ▸ {choice}"),
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ))
+            } else {
+                Line::from(format!("    {choice}"))
+            });
+        }
+    } else {
+        text.push(Line::from(vec![
             Span::raw("  > "),
             Span::styled(
                 current_input,
@@ -63,13 +110,25 @@ pub fn render(frame: &mut Frame, area: Rect, var_name: &str, current_input: &str
                     .add_modifier(Modifier::BOLD),
             ),
             Span::styled("█", Style::default().fg(Color::White)),
-        ]),
+        ]));
+    }
+    if let Some(error) = &modal.error {
+        text.push(Line::from(Span::styled(
+            format!("  ✗ {error}"),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        )));
+    }
+    text.extend([
         Line::from(""),
         Line::from(Span::styled(
-            "  (Enter: Confirm | Esc: Cancel)",
+            if modal.current_choices().is_some() {
+                "  (↑/↓: Choose | Enter: Confirm | Esc: Cancel)"
+            } else {
+                "  (Enter: Confirm | Esc: Cancel)"
+            },
             Style::default().fg(Color::DarkGray),
         )),
-    ];
+    ]);
 
     let paragraph = Paragraph::new(text).block(block).wrap(Wrap { trim: false });
 
@@ -12,9 +12,23 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod check;
+pub mod cloud;
+pub mod cluster;
+pub mod debug;
 pub mod dependency;
+pub mod diff;
+pub mod dry_run;
 pub mod help;
 pub mod input;
 pub mod notification;
+pub mod palette;
+pub mod plan;
+pub mod policy;
+pub mod prerequisite;
 pub mod recovery;
+pub mod restart;
 pub mod safety;
+pub mod scratch;
+pub mod secrets;
+pub mod tutorial;
@@ -0,0 +1,109 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::ui::state::palette::PaletteState;
+use crate::ui::utils::centered_rect;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+/// Renders a palette: the query, then the matching entries as a label
+/// followed by a dimmed detail. `empty` is shown when there is nothing to
+/// choose from at all.
+pub fn render(
+    frame: &mut Frame,
+    area: Rect,
+    title: &str,
+    entries: &[(String, String)],
+    palette: &PaletteState,
+    empty: Option<&str>,
+) {
+    let area = centered_rect(70, 60, area);
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(Span::styled(
+            format!(" {title} "),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ))
+        .borders(Borders::ALL)
+        .border_style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        );
+
+    let mut text = vec![
+        Line::from(vec![
+            Span::raw(" > "),
+            Span::styled(
+                palette.query.as_str(),
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled("█", Style::default().fg(Color::White)),
+        ]),
+        Line::from(""),
+    ];
+
+    if let Some(empty) = empty {
+        text.push(Line::from(Span::styled(
+            format!(" {empty}"),
+            Style::default().fg(Color::DarkGray),
+        )));
+    } else if entries.is_empty() {
+        text.push(Line::from(Span::styled(
+            " Nothing matches.",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    // Keep the highlighted entry visible (borders, query and hint take 5 rows)
+    let visible = usize::from(area.height.saturating_sub(5)).max(1);
+    let first = palette.selected.saturating_sub(visible - 1);
+    for (pos, (label, detail)) in entries.iter().enumerate().skip(first).take(visible) {
+        let highlighted = pos == palette.selected;
+        let name_style = if highlighted {
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Cyan)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().add_modifier(Modifier::BOLD)
+        };
+        text.push(Line::from(vec![
+            Span::raw(if highlighted { " ▶ " } else { "   " }),
+            Span::styled(label.as_str(), name_style),
+            Span::styled(format!("  {detail}"), Style::default().fg(Color::DarkGray)),
+        ]));
+    }
+
+    let hint_row = area.height.saturating_sub(3);
+    while text.len() < usize::from(hint_row) {
+        text.push(Line::from(""));
+    }
+    text.push(Line::from(Span::styled(
+        " (Type to filter | ↑/↓: Choose | Enter: Run | Esc: Cancel)",
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    frame.render_widget(Paragraph::new(text).block(block), area);
+}
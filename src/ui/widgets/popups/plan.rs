@@ -0,0 +1,56 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::ui::utils::centered_rect;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+/// Shows the `terraform plan` of a step, with the resources to create,
+/// destroy and update colored like Terraform does.
+pub fn render(frame: &mut Frame, area: Rect, title: &str, plan: &str, scroll: u16) {
+    let area = centered_rect(80, 80, area);
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(Span::styled(
+            format!(" 📋 Plan of {title} (Enter: Apply | Esc: Cancel | ↑/↓: Scroll) "),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+
+    let lines: Vec<Line> = plan
+        .lines()
+        .map(|line| {
+            let color = match line.trim_start().split(' ').next() {
+                Some("+") => Color::Green,
+                Some("-") => Color::Red,
+                Some("~") => Color::Yellow,
+                Some("-/+" | "+/-") => Color::Magenta,
+                _ => return Line::from(format!(" {line}")),
+            };
+            Line::from(Span::styled(format!(" {line}"), Style::default().fg(color)))
+        })
+        .collect();
+
+    let paragraph = Paragraph::new(lines).block(block).scroll((scroll, 0));
+    frame.render_widget(paragraph, area);
+}
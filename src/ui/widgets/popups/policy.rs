@@ -0,0 +1,46 @@
+// Copyright 2026 eraflo
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::ui::utils::centered_rect;
+use ratatui::{
+    Frame,
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+pub fn render(frame: &mut Frame, area: Rect, reason: &str) {
+    let area = centered_rect(60, 40, area);
+    frame.render_widget(Clear, area); // This clears the area under the popup
+
+    let block = Block::default()
+        .title(" ⚖ POLICY ")
+        .borders(Borders::ALL)
+        .border_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        );
+
+    let text = format!(
+        "\nAn organization policy asks for a confirmation before this step runs:\n\n{reason}\n\nPress [Enter] to run it, or [Esc] to cancel."
+    );
+
+    let paragraph = Paragraph::new(text)
+        .block(block)
+        .wrap(Wrap { trim: true })
+        .alignment(Alignment::Center);
+
+    frame.render_widget(paragraph, area);
+}
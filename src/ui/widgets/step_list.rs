@@ -12,39 +12,351 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::core::models::{Step, StepStatus};
+use crate::core::executor::conditions::evaluator::{ConditionEvaluator, StandardEvaluator};
+use crate::core::executor::restart;
+use crate::core::models::{Condition, Step, StepStatus};
+use crate::core::outline;
+use crate::ui::theme::Palette;
+use crate::ui::utils::format_elapsed;
 use ratatui::{
     Frame,
     layout::Rect,
     style::{Color, Style},
+    text::{Line, Span},
     widgets::{Block, Borders, List, ListItem, ListState},
 };
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
 
-pub fn render_step_list(frame: &mut Frame, area: Rect, steps: &[Step], list_state: &mut ListState) {
-    let items: Vec<ListItem> = steps
+/// How long the result of a step condition is reused before it is evaluated again.
+const CONDITION_TTL: Duration = Duration::from_secs(1);
+
+/// Rows built in earlier frames, keyed by a hash of everything they show, so
+/// that only the rows whose step changed are built again.
+#[derive(Default)]
+pub struct StepListCache {
+    rows: HashMap<usize, (u64, ListItem<'static>)>,
+    /// Last result of each condition (by its text) and when it was evaluated.
+    conditions: HashMap<String, (bool, Instant)>,
+}
+
+impl StepListCache {
+    /// Whether a condition is met on this machine, evaluated again once the
+    /// cached result is older than [`CONDITION_TTL`].
+    fn condition_met(&mut self, condition: &Condition, evaluator: &StandardEvaluator) -> bool {
+        let now = Instant::now();
+        let text = condition.to_string();
+        match self.conditions.get(&text) {
+            Some(&(met, at)) if now.duration_since(at) < CONDITION_TTL => met,
+            _ => {
+                let met = evaluator.evaluate(condition);
+                self.conditions.insert(text, (met, now));
+                met
+            }
+        }
+    }
+}
+
+/// Everything a row shows besides the step itself.
+struct RowState<'a> {
+    forced: bool,
+    sandbox: bool,
+    flipped: bool,
+    met: Option<bool>,
+    duration: Option<String>,
+    /// Nesting depth of the step under the headers before it.
+    depth: usize,
+    /// Whether the step has nested steps, and the number hidden if collapsed.
+    fold: Option<Option<usize>>,
+    /// Whether the step waits for a reboot or new login asked for by another.
+    blocked: bool,
+    palette: &'a Palette,
+}
+
+/// What the step list shows.
+pub struct StepListView<'a> {
+    /// Title of the runbook, "Steps" if it has none.
+    pub title: Option<&'a str>,
+    pub steps: &'a [Step],
+    /// Steps whose nested steps are hidden.
+    pub collapsed: &'a HashSet<usize>,
+    /// Step that asked for a reboot or a new login, blocking the steps after it.
+    pub restart: Option<usize>,
+    /// Steps force-enabled despite their condition.
+    pub forced: &'a HashSet<usize>,
+    /// Whether the session runs steps in the sandbox.
+    pub sandbox: bool,
+    /// Steps running on the other side of the session's sandbox setting.
+    pub sandbox_flipped: &'a HashSet<usize>,
+    /// Run time of the running steps.
+    pub elapsed: &'a HashMap<usize, Duration>,
+    pub palette: &'a Palette,
+}
+
+/// Renders the step list as a tree of the headers, without the steps nested
+/// under collapsed ones. Steps running on the other side of the sandbox
+/// setting are marked as such, and so are the steps blocked by a reboot or
+/// new login.
+///
+/// Only the rows that fit in `area` are built, so long runbooks render as fast
+/// as short ones.
+pub fn render_step_list(
+    frame: &mut Frame,
+    area: Rect,
+    view: &StepListView,
+    list_state: &mut ListState,
+    cache: &mut StepListCache,
+) {
+    let StepListView {
+        title,
+        steps,
+        collapsed,
+        restart,
+        forced,
+        sandbox,
+        sandbox_flipped,
+        elapsed,
+        palette,
+    } = *view;
+    let visible = outline::visible(steps, collapsed);
+    let depths = outline::depths(steps);
+    let selected = list_state
+        .selected()
+        .and_then(|selected| visible.iter().position(|&i| i == selected));
+
+    // Scroll just enough to keep the selected step visible
+    let height = usize::from(area.height.saturating_sub(2)).max(1);
+    let mut offset = list_state
+        .offset()
+        .min(visible.len().saturating_sub(height));
+    if let Some(selected) = selected {
+        if selected < offset {
+            offset = selected;
+        } else if selected >= offset + height {
+            offset = selected + 1 - height;
+        }
+    }
+    let end = (offset + height).min(visible.len());
+    cache.rows.retain(|&i, _| i < steps.len());
+
+    let evaluator = StandardEvaluator::new();
+    let items: Vec<ListItem> = visible[offset..end]
         .iter()
-        .map(|step| {
-            let (symbol, style) = match step.status {
-                StepStatus::Running => ("⏳ ", Style::default().fg(Color::Yellow)),
-                StepStatus::Success => ("✅ ", Style::default().fg(Color::Green)),
-                StepStatus::Failed => ("❌ ", Style::default().fg(Color::Red)),
-                StepStatus::Skipped => ("🚫 ", Style::default().fg(Color::DarkGray)),
-                StepStatus::Pending => {
-                    if step.is_executable() {
-                        ("⚡ ", Style::default().fg(Color::Cyan))
-                    } else {
-                        ("   ", Style::default().fg(Color::Gray))
-                    }
-                }
+        .map(|&i| {
+            let step = &steps[i];
+            let nested = outline::subtree_end(steps, i) - i - 1;
+            let fold = (nested > 0).then(|| collapsed.contains(&i).then_some(nested));
+            let met = step
+                .condition
+                .as_ref()
+                .map(|condition| cache.condition_met(condition, &evaluator));
+            // Live run time of running steps, duration of the last run otherwise
+            let millis = match step.status {
+                StepStatus::Running => elapsed
+                    .get(&i)
+                    .map(|d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX)),
+                StepStatus::Pending | StepStatus::Skipped => None,
+                _ => step.execution.as_ref().map(|e| e.duration_ms),
+            };
+            let state = RowState {
+                forced: forced.contains(&i),
+                sandbox,
+                flipped: sandbox_flipped.contains(&i),
+                met,
+                duration: millis.map(format_elapsed),
+                depth: depths[i],
+                fold,
+                blocked: restart.is_some_and(|source| restart::blocks(steps, source, i)),
+                palette,
             };
-            ListItem::new(format!("{symbol}{title}", title = step.title)).style(style)
+            let key = row_key(step, &state);
+            match cache.rows.get(&i) {
+                Some((cached, item)) if *cached == key => item.clone(),
+                _ => {
+                    let item = build_row(step, &state);
+                    cache.rows.insert(i, (key, item.clone()));
+                    item
+                }
+            }
         })
         .collect();
 
     let list = List::new(items)
-        .block(Block::default().title(" Steps ").borders(Borders::ALL))
-        .highlight_style(Style::default().bg(Color::Blue))
+        .block(
+            Block::default()
+                .title(title.map_or_else(|| " Steps ".to_string(), |t| format!(" {t} ")))
+                .borders(Borders::ALL)
+                .border_style(palette.border),
+        )
+        .highlight_style(palette.highlight)
         .highlight_symbol(">> ");
 
-    frame.render_stateful_widget(list, area, list_state);
+    let mut window = ListState::default().with_selected(
+        selected
+            .filter(|&selected| selected < end)
+            .map(|selected| selected - offset),
+    );
+    frame.render_stateful_widget(list, area, &mut window);
+    *list_state.offset_mut() = offset;
+}
+
+/// Hash of everything a row shows.
+fn row_key(step: &Step, state: &RowState) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    step.title.hash(&mut hasher);
+    step.status.hash(&mut hasher);
+    step.is_executable().hash(&mut hasher);
+    step.session_only.hash(&mut hasher);
+    step.group.hash(&mut hasher);
+    step.condition
+        .as_ref()
+        .map(ToString::to_string)
+        .hash(&mut hasher);
+    state.forced.hash(&mut hasher);
+    state.sandbox.hash(&mut hasher);
+    state.flipped.hash(&mut hasher);
+    state.met.hash(&mut hasher);
+    state.duration.hash(&mut hasher);
+    state.depth.hash(&mut hasher);
+    state.fold.hash(&mut hasher);
+    state.blocked.hash(&mut hasher);
+    state.palette.markers.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Builds the row of a step: status marker, title, duration and markers.
+fn build_row(step: &Step, state: &RowState) -> ListItem<'static> {
+    let symbol = state
+        .palette
+        .status_marker(step.status, step.is_executable());
+    let mut style = match step.status {
+        StepStatus::Running => Style::default().fg(Color::Yellow),
+        StepStatus::Success => Style::default().fg(Color::Green),
+        StepStatus::Failed | StepStatus::TimedOut => Style::default().fg(Color::Red),
+        StepStatus::Skipped => Style::default().fg(Color::DarkGray),
+        StepStatus::Cancelled => Style::default().fg(Color::Magenta),
+        StepStatus::Pending if step.is_executable() => Style::default().fg(Color::Cyan),
+        StepStatus::Pending => Style::default().fg(Color::Gray),
+    };
+    let indent = "  ".repeat(state.depth);
+    let fold = state
+        .fold
+        .map_or("", |hidden| state.palette.fold_marker(hidden.is_some()));
+    let mut spans = vec![Span::raw(format!(
+        "{symbol}{indent}{fold}{title}",
+        title = step.title
+    ))];
+    if let Some(Some(hidden)) = state.fold {
+        spans.push(Span::styled(
+            format!(" (+{hidden})"),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    if let Some(duration) = &state.duration {
+        spans.push(Span::styled(
+            format!(" {duration}"),
+            Style::default().fg(Color::LightBlue),
+        ));
+    }
+    if state.flipped {
+        spans.push(Span::styled(
+            if state.sandbox {
+                " [host]"
+            } else {
+                " [sandbox]"
+            },
+            Style::default().fg(Color::LightCyan),
+        ));
+    }
+    if step.session_only {
+        spans.push(Span::styled(
+            " [session]",
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    if step.group.is_some() {
+        spans.push(Span::styled(
+            " [parallel]",
+            Style::default().fg(Color::LightMagenta),
+        ));
+    }
+    if state.blocked {
+        spans.push(Span::styled(" [blocked]", Style::default().fg(Color::Red)));
+    }
+
+    // Live evaluation of the step condition on this machine
+    if let (Some(condition), Some(met)) = (&step.condition, state.met) {
+        let (mark, color) = match (met, state.forced) {
+            (true, _) => ("✓", Color::Green),
+            (false, true) => ("✗ forced", Color::Yellow),
+            (false, false) => {
+                style = Style::default().fg(Color::DarkGray);
+                ("✗", Color::Red)
+            }
+        };
+        spans.push(Span::styled(
+            format!(" [{condition} {mark}]"),
+            Style::default().fg(color),
+        ));
+    }
+
+    ListItem::new(Line::from(spans)).style(style)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::infrastructure::config::{StatusMarkers, Theme};
+    use crate::core::parser::parse_readme;
+    use crate::ui::theme;
+    use ratatui::{Terminal, backend::TestBackend};
+    use std::fmt::Write;
+
+    #[test]
+    fn test_builds_only_visible_rows() {
+        let mut md = String::new();
+        for i in 0..100 {
+            write!(md, "# Step {i}\n```bash\necho {i}\n```\n").unwrap();
+        }
+        let (mut steps, _) = parse_readme(&md);
+        let palette = theme::palette(Theme::Dark, StatusMarkers::Text);
+        let mut terminal = Terminal::new(TestBackend::new(40, 7)).unwrap();
+        let mut list_state = ListState::default().with_selected(Some(50));
+        let mut cache = StepListCache::default();
+        let mut draw = |steps: &[Step], list_state: &mut ListState, cache: &mut StepListCache| {
+            terminal
+                .draw(|frame| {
+                    let view = StepListView {
+                        title: None,
+                        steps,
+                        collapsed: &HashSet::new(),
+                        restart: None,
+                        forced: &HashSet::new(),
+                        sandbox: false,
+                        sandbox_flipped: &HashSet::new(),
+                        elapsed: &HashMap::new(),
+                        palette: &palette,
+                    };
+                    render_step_list(frame, frame.area(), &view, list_state, cache);
+                })
+                .unwrap();
+        };
+
+        // Five rows fit between the borders, ending with the selected step
+        draw(&steps, &mut list_state, &mut cache);
+        assert_eq!(list_state.offset(), 46);
+        let mut built: Vec<usize> = cache.rows.keys().copied().collect();
+        built.sort_unstable();
+        assert_eq!(built, vec![46, 47, 48, 49, 50]);
+
+        // Only the row of the step that changed is built again
+        let keys: HashMap<usize, u64> = cache.rows.iter().map(|(&i, (k, _))| (i, *k)).collect();
+        steps[48].status = StepStatus::Success;
+        draw(&steps, &mut list_state, &mut cache);
+        for (i, (key, _)) in &cache.rows {
+            assert_eq!(*i == 48, keys[i] != *key);
+        }
+    }
 }